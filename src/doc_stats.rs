@@ -0,0 +1,163 @@
+//////////////////////////////////////////////////////////////////////////////////////////////////////////////
+//
+//	Statistics about the resources in pdf documents for finding out what makes spellbooks big
+//
+//////////////////////////////////////////////////////////////////////////////////////////////////////////////
+
+use std::collections::{HashMap, HashSet};
+
+use printpdf::lopdf::{self, Object, ObjectId};
+
+/// The kind of resource a pdf object is.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ResourceKind
+{
+	/// An image (like a page background).
+	Image,
+	/// A font (including the font file embedded in the document).
+	Font,
+	/// The operations that draw the contents of a page.
+	PageContent
+}
+
+/// How much a single resource adds to the size of a pdf document.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ResourceStats
+{
+	/// The id of the pdf object of the resource.
+	pub id: ObjectId,
+	/// What kind of resource it is.
+	pub kind: ResourceKind,
+	/// The estimated number of bytes the resource adds to the document (the size of its data and the data of any
+	/// objects that only it uses, like embedded font files and image masks).
+	pub size: usize
+}
+
+/// Statistics about the resources in a pdf document.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct DocStats
+{
+	/// The number of pages in the document.
+	pub page_count: usize,
+	/// The number of images embedded in the document.
+	pub image_count: usize,
+	/// The number of images that have exactly the same data as another image in the document (ex: a background image
+	/// that got embedded again for every page instead of once for all of them).
+	pub duplicate_image_count: usize,
+	/// The number of fonts in the document.
+	pub font_count: usize,
+	/// The number of operations in the contents of every page combined.
+	pub operation_count: usize,
+	/// The estimated size of each image, font, and page content in the document, from largest to smallest.
+	pub resources: Vec<ResourceStats>
+}
+
+impl DocStats
+{
+	/// The estimated number of bytes that all resources of a certain kind add to the document.
+	pub fn total_size(&self, kind: ResourceKind) -> usize
+	{
+		self.resources.iter().filter(|resource| resource.kind == kind).map(|resource| resource.size).sum()
+	}
+}
+
+/// Gets statistics about the images, fonts, and page contents of a pdf document to help figure out what makes it big.
+///
+/// A saved spellbook can be loaded for this with `lopdf::Document::load()` or `lopdf::Document::load_mem()` on the
+/// bytes from `Spellbook::save_to_bytes()`.
+pub fn doc_stats(doc: &lopdf::Document) -> DocStats
+{
+	let mut stats = DocStats::default();
+	let pages = doc.get_pages();
+	stats.page_count = pages.len();
+	// The hash of each image's data for finding duplicates
+	let mut image_data = HashSet::new();
+	for (id, object) in &doc.objects
+	{
+		if let Ok(stream) = object.as_stream()
+		{
+			if name_of(&stream.dict, b"Subtype") == Some(b"Image")
+			{
+				stats.image_count += 1;
+				if !image_data.insert(&stream.content) { stats.duplicate_image_count += 1; }
+				let size = referenced_size(doc, *id);
+				stats.resources.push(ResourceStats { id: *id, kind: ResourceKind::Image, size: size });
+			}
+		}
+		else if let Ok(dict) = object.as_dict()
+		{
+			// Only count top level fonts (not the fonts that composite fonts are made from)
+			let is_font = name_of(dict, b"Type") == Some(b"Font") &&
+				!matches!(name_of(dict, b"Subtype"), Some(b"CIDFontType0") | Some(b"CIDFontType2"));
+			if is_font
+			{
+				stats.font_count += 1;
+				let size = referenced_size(doc, *id);
+				stats.resources.push(ResourceStats { id: *id, kind: ResourceKind::Font, size: size });
+			}
+		}
+	}
+	// Page content streams can be shared between pages, so only count each one once
+	let mut content_sizes = HashMap::new();
+	for page_id in pages.values()
+	{
+		if let Ok(content) = doc.get_page_content(*page_id)
+		{
+			if let Ok(content) = lopdf::content::Content::decode(&content)
+			{
+				stats.operation_count += content.operations.len();
+			}
+		}
+		for content_id in doc.get_page_contents(*page_id)
+		{
+			if let Ok(stream) = doc.get_object(content_id).and_then(Object::as_stream)
+			{
+				content_sizes.insert(content_id, stream.content.len());
+			}
+		}
+	}
+	stats.resources.extend(content_sizes.into_iter()
+		.map(|(id, size)| ResourceStats { id: id, kind: ResourceKind::PageContent, size: size }));
+	stats.resources.sort_by(|a, b| b.size.cmp(&a.size).then(a.id.cmp(&b.id)));
+	stats
+}
+
+// Gets the name value of a key in a dictionary (`None` if it isn't there or isn't a name)
+fn name_of<'a>(dict: &'a lopdf::Dictionary, key: &[u8]) -> Option<&'a [u8]>
+{
+	dict.get(key).and_then(Object::as_name).ok()
+}
+
+// Adds up the size of the stream data in an object and every object it references (directly or through other
+// objects)
+fn referenced_size(doc: &lopdf::Document, id: ObjectId) -> usize
+{
+	let mut visited = HashSet::new();
+	let mut to_visit = vec![id];
+	let mut size = 0;
+	while let Some(id) = to_visit.pop()
+	{
+		if !visited.insert(id) { continue; }
+		let object = match doc.get_object(id)
+		{
+			Ok(object) => object,
+			Err(_) => continue
+		};
+		if let Ok(stream) = object.as_stream() { size += stream.content.len(); }
+		collect_references(object, &mut to_visit);
+	}
+	size
+}
+
+// Adds the ids of every object an object references to a list (without following the references)
+fn collect_references(object: &Object, references: &mut Vec<ObjectId>)
+{
+	match object
+	{
+		Object::Reference(id) => references.push(*id),
+		Object::Array(array) => for object in array { collect_references(object, references); },
+		Object::Dictionary(dict) => for (_, object) in dict.iter() { collect_references(object, references); },
+		Object::Stream(stream) => for (_, object) in stream.dict.iter() { collect_references(object, references); },
+		_ => ()
+	}
+}
@@ -0,0 +1,283 @@
+//////////////////////////////////////////////////////////////////////////////////////////////////////////////
+//
+//	Exporting spellbooks as epub files for e-readers
+//
+//////////////////////////////////////////////////////////////////////////////////////////////////////////////
+
+use std::fs;
+use std::error::Error;
+use std::io::{Cursor, Write};
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use zip::{CompressionMethod, ZipWriter};
+use zip::write::FileOptions;
+
+use crate::spellbook_options::{BookmarkNamePolicy, UpcastOptions};
+use crate::spellbook_writer::get_bookmark_names;
+use crate::vtt_export::{escape_html, spell_description_html};
+use crate::spells::Spell;
+
+/// The title used for spellbooks that weren't given one.
+const DEFAULT_EPUB_TITLE: &str = "Spellbook";
+/// The name of the title page in the navigation document (same as the title page bookmark in pdf spellbooks).
+const TITLE_PAGE_NAME: &str = "Title Page";
+
+/// The container file that tells e-readers where the package document is.
+const CONTAINER_XML: &str = "<?xml version=\"1.0\" encoding=\"UTF-8\"?>
+<container version=\"1.0\" xmlns=\"urn:oasis:names:tc:opendocument:xmlns:container\">
+	<rootfiles>
+		<rootfile full-path=\"OEBPS/content.opf\" media-type=\"application/oebps-package+xml\"/>
+	</rootfiles>
+</container>
+";
+
+/// The stylesheet for every page of the spellbook.
+const STYLESHEET: &str = "h1 { font-variant: small-caps; margin-bottom: 0; }
+.level-school { font-style: italic; margin-top: 0; }
+.stat { margin: 0; }
+.title-page { text-align: center; margin-top: 40%; }
+table { border-collapse: collapse; width: 100%; }
+th { text-align: left; }
+tbody tr:nth-child(odd) { background-color: #e8e8e8; }
+";
+
+/// Settings for the epub file made by `render_spellbook_epub()`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct EpubOptions
+{
+	/// The title of the spellbook.
+	pub title: String,
+	/// The language of the spellbook as a language tag (ex: "en").
+	pub language: String,
+	/// How the entry for each spell in the navigation document gets named (same as pdf bookmarks).
+	pub bookmark_names: BookmarkNamePolicy,
+	/// The text that goes before the upcast description of each spell.
+	pub upcast: UpcastOptions
+}
+
+impl Default for EpubOptions
+{
+	fn default() -> Self
+	{
+		Self
+		{
+			title: String::from(DEFAULT_EPUB_TITLE),
+			language: String::from("en"),
+			bookmark_names: BookmarkNamePolicy::default(),
+			upcast: UpcastOptions::default()
+		}
+	}
+}
+
+/// Turns a list of spells into an epub file so the spellbook can be read on e-readers.
+///
+/// The spellbook gets a title page, then each spell gets its own chapter with the same html that
+/// `vtt_export::spell_description_html()` makes for its description. The navigation document has the same entries as
+/// the bookmarks of a pdf spellbook (the title page, then each spell).
+///
+/// # Parameters
+///
+/// - `spells` The spells to put in the spellbook (in the order they'll appear).
+/// - `options` Settings for the epub file.
+///
+/// # Output
+///
+/// - `Ok` The bytes of the epub file.
+/// - `Err` Any errors that occurred while zipping the epub file.
+pub fn render_spellbook_epub(spells: &Vec<Spell>, options: &EpubOptions)
+-> Result<Vec<u8>, Box<dyn Error + Send + Sync>>
+{
+	let title = if options.title.is_empty() { DEFAULT_EPUB_TITLE } else { &options.title };
+	let chapter_names = get_bookmark_names(spells, options.bookmark_names);
+	let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+	// The mimetype file has to be first and can't be compressed
+	zip.start_file("mimetype", FileOptions::default().compression_method(CompressionMethod::Stored))?;
+	zip.write_all(b"application/epub+zip")?;
+	let mut files = vec!
+	[
+		(String::from("META-INF/container.xml"), String::from(CONTAINER_XML)),
+		(String::from("OEBPS/content.opf"), package_document(spells.len(), title, &options.language)),
+		(String::from("OEBPS/nav.xhtml"), navigation_document(&chapter_names, title, &options.language)),
+		(String::from("OEBPS/style.css"), String::from(STYLESHEET)),
+		(String::from("OEBPS/title.xhtml"), title_page(title, &options.language))
+	];
+	for (index, spell) in spells.iter().enumerate()
+	{
+		let chapter = spell_chapter(spell, &options.language, &options.upcast);
+		files.push((format!("OEBPS/{}", chapter_file_name(index)), chapter));
+	}
+	for (file_name, contents) in files
+	{
+		zip.start_file(file_name, FileOptions::default().compression_method(CompressionMethod::Deflated))?;
+		zip.write_all(contents.as_bytes())?;
+	}
+	Ok(zip.finish()?.into_inner())
+}
+
+/// Saves a list of spells to an epub file (see `render_spellbook_epub()`).
+///
+/// # Parameters
+///
+/// - `spells` The spells to put in the spellbook (in the order they'll appear).
+/// - `options` Settings for the epub file.
+/// - `file_path` The file to save the spellbook to.
+///
+/// # Output
+///
+/// - `Ok` Returns nothing.
+/// - `Err` Returns any errors that occurred.
+pub fn save_spellbook_epub(spells: &Vec<Spell>, options: &EpubOptions, file_path: &str)
+-> Result<(), Box<dyn Error + Send + Sync>>
+{
+	fs::write(file_path, render_spellbook_epub(spells, options)?)?;
+	Ok(())
+}
+
+/// The name of the file (relative to the package document) that a spell's chapter goes in.
+fn chapter_file_name(index: usize) -> String { format!("spells/spell_{:04}.xhtml", index + 1) }
+
+/// Makes an xhtml page with the given title and body.
+fn xhtml_page(title: &str, language: &str, stylesheet_path: &str, body: &str) -> String
+{
+	format!
+	(
+		"<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<!DOCTYPE html>\n\
+		<html xmlns=\"http://www.w3.org/1999/xhtml\" xmlns:epub=\"http://www.idpf.org/2007/ops\" lang=\"{0}\" \
+		xml:lang=\"{0}\">\n<head>\n<meta charset=\"UTF-8\"/>\n<title>{1}</title>\n\
+		<link rel=\"stylesheet\" type=\"text/css\" href=\"{2}\"/>\n</head>\n<body>\n{3}\n</body>\n</html>\n",
+		escape_html(language),
+		escape_html(title),
+		stylesheet_path,
+		body
+	)
+}
+
+/// Makes the title page of the spellbook.
+fn title_page(title: &str, language: &str) -> String
+{
+	let body = format!
+	(
+		"<section epub:type=\"titlepage\" class=\"title-page\"><h1>{}</h1></section>",
+		escape_html(title)
+	);
+	xhtml_page(title, language, "style.css", &body)
+}
+
+/// Makes the chapter for a single spell.
+fn spell_chapter(spell: &Spell, language: &str, upcast: &UpcastOptions) -> String
+{
+	let mut body = format!
+	(
+		"<section epub:type=\"chapter\">\n<h1>{}</h1>\n<p class=\"level-school\">{}</p>\n",
+		escape_html(&spell.name),
+		escape_html(&spell.get_level_school_text())
+	);
+	let stats =
+	[
+		("Casting Time", spell.get_casting_time_text()),
+		("Range", spell.range.to_string()),
+		("Components", spell.get_component_string()),
+		("Duration", spell.duration.to_string())
+	];
+	for (name, value) in stats
+	{
+		body.push_str(&format!("<p class=\"stat\"><strong>{}:</strong> {}</p>\n", name, escape_html(&value)));
+	}
+	body.push_str(&spell_description_html(spell, upcast));
+	body.push_str("\n</section>");
+	xhtml_page(&spell.name, language, "../style.css", &body)
+}
+
+/// Makes the navigation document with an entry for the title page and each spell.
+fn navigation_document(chapter_names: &Vec<String>, title: &str, language: &str) -> String
+{
+	let mut body = format!
+	(
+		"<nav epub:type=\"toc\" id=\"toc\">\n<h1>{}</h1>\n<ol>\n<li><a href=\"title.xhtml\">{}</a></li>\n",
+		escape_html(title),
+		TITLE_PAGE_NAME
+	);
+	for (index, chapter_name) in chapter_names.iter().enumerate()
+	{
+		let entry = format!("<li><a href=\"{}\">{}</a></li>\n", chapter_file_name(index), escape_html(chapter_name));
+		body.push_str(&entry);
+	}
+	body.push_str("</ol>\n</nav>");
+	xhtml_page(title, language, "style.css", &body)
+}
+
+/// Makes the package document that lists every file in the epub and the order they're read in.
+fn package_document(chapter_count: usize, title: &str, language: &str) -> String
+{
+	// The identifier only needs to be unique to this spellbook, so a hash of the title and the time is good enough
+	let modified = SystemTime::now().duration_since(UNIX_EPOCH).map(|time| time.as_secs()).unwrap_or_default();
+	let mut hasher = DefaultHasher::new();
+	title.hash(&mut hasher);
+	modified.hash(&mut hasher);
+	let mut manifest = String::from
+	(
+		"\t\t<item id=\"nav\" href=\"nav.xhtml\" media-type=\"application/xhtml+xml\" properties=\"nav\"/>\n\
+		\t\t<item id=\"style\" href=\"style.css\" media-type=\"text/css\"/>\n\
+		\t\t<item id=\"title\" href=\"title.xhtml\" media-type=\"application/xhtml+xml\"/>\n"
+	);
+	let mut spine = String::from("\t\t<itemref idref=\"title\"/>\n");
+	for index in 0..chapter_count
+	{
+		manifest.push_str(&format!
+		(
+			"\t\t<item id=\"spell_{}\" href=\"{}\" media-type=\"application/xhtml+xml\"/>\n",
+			index + 1,
+			chapter_file_name(index)
+		));
+		spine.push_str(&format!("\t\t<itemref idref=\"spell_{}\"/>\n", index + 1));
+	}
+	format!
+	(
+		"<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+		<package xmlns=\"http://www.idpf.org/2007/opf\" version=\"3.0\" unique-identifier=\"book-id\">\n\
+		\t<metadata xmlns:dc=\"http://purl.org/dc/elements/1.1/\">\n\
+		\t\t<dc:identifier id=\"book-id\">urn:dnd-spellbook-maker:{:016x}</dc:identifier>\n\
+		\t\t<dc:title>{}</dc:title>\n\
+		\t\t<dc:language>{}</dc:language>\n\
+		\t\t<meta property=\"dcterms:modified\">{}</meta>\n\
+		\t</metadata>\n\
+		\t<manifest>\n{}\t</manifest>\n\
+		\t<spine>\n{}\t</spine>\n\
+		</package>\n",
+		hasher.finish(),
+		escape_html(title),
+		escape_html(language),
+		utc_timestamp(modified),
+		manifest,
+		spine
+	)
+}
+
+/// Turns a unix time into a UTC timestamp in the format epub files use (ex: "2024-11-20T08:30:00Z").
+fn utc_timestamp(unix_seconds: u64) -> String
+{
+	let days = (unix_seconds / 86400) as i64;
+	let seconds_of_day = unix_seconds % 86400;
+	// Converts days since 1970-01-01 into a date in the proleptic Gregorian calendar
+	let days = days + 719468;
+	let era = days.div_euclid(146097);
+	let day_of_era = days.rem_euclid(146097);
+	let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+	let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+	let month_index = (5 * day_of_year + 2) / 153;
+	let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+	let month = if month_index < 10 { month_index + 3 } else { month_index - 9 };
+	let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+	format!
+	(
+		"{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+		year,
+		month,
+		day,
+		seconds_of_day / 3600,
+		seconds_of_day % 3600 / 60,
+		seconds_of_day % 60
+	)
+}
@@ -0,0 +1,128 @@
+//////////////////////////////////////////////////////////////////////////////////////////////////////////////
+//
+//	Fingerprints of everything that goes into a spellbook
+//
+//////////////////////////////////////////////////////////////////////////////////////////////////////////////
+
+use std::fmt;
+use std::fs;
+
+use crate::spellbook_options::{FontPaths, AdditionalOptions};
+use crate::spells::Spell;
+
+// The starting value and prime of 64 bit FNV-1a hashes
+const FNV_OFFSET_BASIS: u64 = 0xcbf29ce484222325;
+const FNV_PRIME: u64 = 0x100000001b3;
+
+/// A 64 bit FNV-1a hash. Used instead of the hashers in `std` because their output isn't guaranteed to stay the same
+/// between versions of Rust or on different platforms, and fingerprints need to match wherever they're made.
+struct FnvHash(u64);
+
+impl FnvHash
+{
+	/// Constructs a hash of nothing.
+	fn new() -> Self { Self(FNV_OFFSET_BASIS) }
+
+	/// Adds some bytes to the hash.
+	fn write(&mut self, bytes: &[u8])
+	{
+		for byte in bytes
+		{
+			self.0 ^= *byte as u64;
+			self.0 = self.0.wrapping_mul(FNV_PRIME);
+		}
+	}
+
+	/// Adds a piece of text to the hash along with its length (so pieces that run into each other differently don't
+	/// hash the same).
+	fn write_text(&mut self, text: &str)
+	{
+		self.write(&(text.len() as u64).to_le_bytes());
+		self.write(text.as_bytes());
+	}
+
+	/// The hash as 16 hexadecimal digits.
+	fn hex(&self) -> String { format!("{:016x}", self.0) }
+}
+
+/// Hashes the contents of a file so that spellbooks made with copies of the same file in different places get the
+/// same fingerprint. Files that can't be read get hashed by their path instead.
+fn file_hash(file_path: &str) -> String
+{
+	let mut hash = FnvHash::new();
+	match fs::read(file_path)
+	{
+		Ok(bytes) => hash.write(&bytes),
+		Err(_) => hash.write_text(file_path)
+	}
+	hash.hex()
+}
+
+/// Returns a fingerprint of a spellbook made from a title, a list of spells, and some options. Every option is hashed
+/// from its debug text except for font files and background images, which are hashed from their contents.
+///
+/// # Parameters
+///
+/// - `title` The title of the spellbook.
+/// - `spells` The spells in the spellbook.
+/// - `font_paths` File paths to the font files that the spellbook uses.
+/// - `background_path` The file path to the background image of the spellbook (`None` if it doesn't have one).
+/// - `additional_options` The optional features of the spellbook.
+/// - `options` Every other option that the spellbook is made with.
+pub(crate) fn spellbook_fingerprint
+(
+	title: &str,
+	spells: &[Spell],
+	font_paths: &FontPaths,
+	background_path: Option<&str>,
+	additional_options: &AdditionalOptions,
+	options: &[&dyn fmt::Debug]
+)
+-> String
+{
+	let mut hash = FnvHash::new();
+	// Spellbooks made by different versions of this crate might not look the same
+	hash.write_text(env!("CARGO_PKG_VERSION"));
+	hash.write_text(title);
+	hash.write(&(spells.len() as u64).to_le_bytes());
+	for spell in spells { hash.write_text(&format!("{:?}", spell)); }
+	// Replace each font path with the hash of its file before hashing the font paths
+	let mut font_paths = font_paths.clone();
+	for path in [&mut font_paths.regular, &mut font_paths.bold, &mut font_paths.italic, &mut font_paths.bold_italic]
+	{
+		*path = file_hash(path);
+	}
+	if let Some(display) = &mut font_paths.display
+	{
+		for path in [&mut display.regular, &mut display.bold, &mut display.italic, &mut display.bold_italic]
+		{
+			*path = file_hash(path);
+		}
+	}
+	hash.write_text(&format!("{:?}", font_paths));
+	hash.write_text(&format!("{:?}", background_path.map(file_hash)));
+	for option in options { hash.write_text(&format!("{:?}", option)); }
+	// Layout caches and image caches only make spellbooks faster to make, and the school colors are in a hash map
+	// that can be in any order, so they get sorted and hashed on their own
+	let mut additional_options = additional_options.clone();
+	additional_options.layout_cache = None;
+	additional_options.image_cache = None;
+	// Replace the paths of the section background images with the hashes of their files too
+	if let Some(section_backgrounds) = &mut additional_options.section_backgrounds
+	{
+		let backgrounds = [&mut section_backgrounds.title_page, &mut section_backgrounds.chapter_pages];
+		for (path, _) in backgrounds.into_iter().flatten()
+		{
+			*path = file_hash(path);
+		}
+	}
+	let school_colors = additional_options.school_colors.take().map(|school_colors|
+	{
+		let mut name_colors: Vec<_> = school_colors.name_colors.into_iter().collect();
+		name_colors.sort();
+		name_colors
+	});
+	hash.write_text(&format!("{:?}", school_colors));
+	hash.write_text(&format!("{:?}", additional_options));
+	hash.hex()
+}
@@ -0,0 +1,354 @@
+//////////////////////////////////////////////////////////////////////////////////////////////////////////////
+//
+//	Instancing variable fonts into static fonts so they can be used like any other font file
+//
+//////////////////////////////////////////////////////////////////////////////////////////////////////////////
+
+use ttf_parser::{Face, GlyphId, OutlineBuilder, Tag};
+
+use crate::utils::VariableFontError;
+
+/// Tables that get copied into instanced fonts as they are. Every other table either has variation data that has
+/// already been applied, outlines and metrics that get rebuilt, or hinting that wouldn't match the rebuilt outlines.
+const COPIED_TABLES: [&[u8; 4]; 8] = [b"GDEF", b"GPOS", b"GSUB", b"OS/2", b"cmap", b"kern", b"name", b"post"];
+
+/// The number that the checksums of every table in a font (plus the checksum adjustment) add up to.
+const CHECKSUM_MAGIC: u32 = 0xB1B0AFBA;
+
+/// A point in a TrueType glyph's contour.
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct ContourPoint
+{
+	x: f32,
+	y: f32,
+	/// Whether the point is on the curve or is the control point of a quadratic curve.
+	on_curve: bool
+}
+
+/// Collects the outline of a glyph as TrueType contours (made of lines and quadratic curves).
+#[derive(Default)]
+struct ContourBuilder
+{
+	contours: Vec<Vec<ContourPoint>>,
+	/// The last point that was added to the outline.
+	current: (f32, f32)
+}
+
+impl ContourBuilder
+{
+	/// Adds a point to the end of the current contour.
+	fn push(&mut self, x: f32, y: f32, on_curve: bool)
+	{
+		if let Some(contour) = self.contours.last_mut()
+		{
+			contour.push(ContourPoint { x: x, y: y, on_curve: on_curve });
+		}
+		if on_curve { self.current = (x, y); }
+	}
+}
+
+impl OutlineBuilder for ContourBuilder
+{
+	fn move_to(&mut self, x: f32, y: f32)
+	{
+		self.contours.push(Vec::new());
+		self.push(x, y, true);
+	}
+
+	fn line_to(&mut self, x: f32, y: f32) { self.push(x, y, true); }
+
+	fn quad_to(&mut self, x1: f32, y1: f32, x: f32, y: f32)
+	{
+		self.push(x1, y1, false);
+		self.push(x, y, true);
+	}
+
+	fn curve_to(&mut self, x1: f32, y1: f32, x2: f32, y2: f32, x: f32, y: f32)
+	{
+		// TrueType fonts can't have cubic curves, so they get split into enough quadratic curves that none of them
+		// are more than a font unit away from the cubic curve
+		let (x0, y0) = self.current;
+		let error = (x - 3.0 * x2 + 3.0 * x1 - x0).hypot(y - 3.0 * y2 + 3.0 * y1 - y0) * 3.0_f32.sqrt() / 36.0;
+		let segments = error.cbrt().ceil().max(1.0) as usize;
+		// The point on the cubic curve at t and the curve's derivative at t
+		let point = |t: f32, p0: f32, p1: f32, p2: f32, p3: f32|
+		{
+			let u = 1.0 - t;
+			u * u * u * p0 + 3.0 * u * u * t * p1 + 3.0 * u * t * t * p2 + t * t * t * p3
+		};
+		let derivative = |t: f32, p0: f32, p1: f32, p2: f32, p3: f32|
+		{
+			let u = 1.0 - t;
+			3.0 * u * u * (p1 - p0) + 6.0 * u * t * (p2 - p1) + 3.0 * t * t * (p3 - p2)
+		};
+		for segment in 0..segments
+		{
+			let start = segment as f32 / segments as f32;
+			let end = (segment + 1) as f32 / segments as f32;
+			let length = end - start;
+			// The cubic curve between start and end, then the quadratic curve closest to its middle
+			let (start_x, start_y) = (point(start, x0, x1, x2, x), point(start, y0, y1, y2, y));
+			let (end_x, end_y) = (point(end, x0, x1, x2, x), point(end, y0, y1, y2, y));
+			let control_1_x = start_x + length / 3.0 * derivative(start, x0, x1, x2, x);
+			let control_1_y = start_y + length / 3.0 * derivative(start, y0, y1, y2, y);
+			let control_2_x = end_x - length / 3.0 * derivative(end, x0, x1, x2, x);
+			let control_2_y = end_y - length / 3.0 * derivative(end, y0, y1, y2, y);
+			let control_x = (3.0 * (control_1_x + control_2_x) - (start_x + end_x)) / 4.0;
+			let control_y = (3.0 * (control_1_y + control_2_y) - (start_y + end_y)) / 4.0;
+			self.push(control_x, control_y, false);
+			self.push(end_x, end_y, true);
+		}
+		// Makes sure the contour ends exactly where the curve was supposed to end
+		self.current = (x, y);
+	}
+
+	fn close(&mut self)
+	{
+		// TrueType contours are always closed, so a last point that's the same as the first one isn't needed
+		if let Some(contour) = self.contours.last_mut()
+		{
+			let same_as_first = |point: &ContourPoint|
+				point.x.round() == contour[0].x.round() && point.y.round() == contour[0].y.round();
+			if contour.len() > 1 && contour.last().is_some_and(|point| point.on_curve && same_as_first(point))
+			{
+				contour.pop();
+			}
+		}
+	}
+}
+
+/// Instances a variable font at some axis values into a static TrueType font.
+///
+/// # Parameters
+///
+/// - `bytes` The variable font file.
+/// - `axes` The axis values to instance the font at (ex: `("wght", 700.0)`). Axes that aren't given use their default
+///   values.
+///
+/// # Output
+///
+/// - `Ok` The bytes of the static font.
+/// - `Err` An error saying why the font couldn't be instanced.
+pub(crate) fn instance_variable_font(bytes: &[u8], axes: &Vec<(String, f32)>) -> Result<Vec<u8>, VariableFontError>
+{
+	let mut face = Face::parse(bytes, 0)
+		.map_err(|error| VariableFontError(format!("Couldn't read the font: {}.", error)))?;
+	if !face.is_variable() { return Err(VariableFontError(String::from("The font isn't a variable font."))); }
+	for (axis, value) in axes
+	{
+		let tag = Tag::from_bytes_lossy(axis.as_bytes());
+		if !face.variation_axes().into_iter().any(|variation_axis| variation_axis.tag == tag)
+		{
+			return Err(VariableFontError(format!("The font doesn't have a \"{}\" axis.", axis)));
+		}
+		face.set_variation(tag, *value);
+	}
+	let raw_table = |tag: &[u8; 4]| face.raw_face().table(Tag::from_bytes(tag));
+	// Fonts with cubic outlines (CFF) draw outer contours counter-clockwise and TrueType fonts draw them clockwise
+	let reverse_contours = raw_table(b"glyf").is_none();
+
+	// Rebuild the outlines and horizontal metrics of every glyph
+	let glyph_count = face.number_of_glyphs();
+	let mut glyf = Vec::new();
+	let mut loca = vec![0_u32];
+	let mut hmtx = Vec::with_capacity(glyph_count as usize * 4);
+	let mut font_bounds: Option<[i16; 4]> = None;
+	let mut advance_width_max = 0_u16;
+	let mut min_left_side_bearing = i16::MAX;
+	let mut min_right_side_bearing = i16::MAX;
+	let mut x_max_extent = i16::MIN;
+	let mut max_points = 0;
+	let mut max_contours = 0;
+	for glyph_index in 0..glyph_count
+	{
+		let glyph_id = GlyphId(glyph_index);
+		let mut builder = ContourBuilder::default();
+		face.outline_glyph(glyph_id, &mut builder);
+		let mut contours: Vec<_> = builder.contours.into_iter().filter(|contour| contour.len() > 1).collect();
+		for contour in contours.iter_mut()
+		{
+			if reverse_contours { contour.reverse(); }
+			// Starts the contour on a point that's on the curve
+			let first_on_curve = contour.iter().position(|point| point.on_curve).unwrap_or(0);
+			contour.rotate_left(first_on_curve);
+		}
+		let advance = face.glyph_hor_advance(glyph_id).unwrap_or(0);
+		advance_width_max = advance_width_max.max(advance);
+		let (glyph_data, bounds) = encode_glyph(&contours);
+		let left_side_bearing = match bounds
+		{
+			Some([x_min, y_min, x_max, y_max]) =>
+			{
+				min_left_side_bearing = min_left_side_bearing.min(x_min);
+				min_right_side_bearing = min_right_side_bearing.min((advance as i32 - x_max as i32) as i16);
+				x_max_extent = x_max_extent.max(x_max);
+				max_points = max_points.max(contours.iter().map(|contour| contour.len()).sum::<usize>());
+				max_contours = max_contours.max(contours.len());
+				font_bounds = Some(match font_bounds
+				{
+					Some([font_x_min, font_y_min, font_x_max, font_y_max]) =>
+						[font_x_min.min(x_min), font_y_min.min(y_min), font_x_max.max(x_max), font_y_max.max(y_max)],
+					None => [x_min, y_min, x_max, y_max]
+				});
+				x_min
+			},
+			None => 0
+		};
+		hmtx.extend(advance.to_be_bytes());
+		hmtx.extend(left_side_bearing.to_be_bytes());
+		glyf.extend(glyph_data);
+		loca.push(glyf.len() as u32);
+	}
+	let font_bounds = font_bounds.unwrap_or_default();
+
+	// Update the header tables to match the new glyphs
+	let missing_table = |name: &str| VariableFontError(format!("The font doesn't have a valid {} table.", name));
+	let mut head = raw_table(b"head").filter(|table| table.len() >= 54).ok_or_else(|| missing_table("head"))?.to_vec();
+	// The checksum adjustment gets calculated at the end
+	head[8..12].copy_from_slice(&[0; 4]);
+	for (index, bound) in font_bounds.iter().enumerate()
+	{
+		head[36 + index * 2..38 + index * 2].copy_from_slice(&bound.to_be_bytes());
+	}
+	// Use 32 bit offsets in the loca table
+	head[50..52].copy_from_slice(&1_i16.to_be_bytes());
+	head[52..54].copy_from_slice(&0_i16.to_be_bytes());
+	let mut hhea = raw_table(b"hhea").filter(|table| table.len() >= 36).ok_or_else(|| missing_table("hhea"))?.to_vec();
+	hhea[10..12].copy_from_slice(&advance_width_max.to_be_bytes());
+	if x_max_extent != i16::MIN
+	{
+		hhea[12..14].copy_from_slice(&min_left_side_bearing.to_be_bytes());
+		hhea[14..16].copy_from_slice(&min_right_side_bearing.to_be_bytes());
+		hhea[16..18].copy_from_slice(&x_max_extent.to_be_bytes());
+	}
+	hhea[34..36].copy_from_slice(&glyph_count.to_be_bytes());
+	// TrueType fonts need version 1.0 of the maxp table (the hinting values are all 0 since there's no hinting)
+	let mut maxp = Vec::with_capacity(32);
+	maxp.extend(0x00010000_u32.to_be_bytes());
+	maxp.extend(glyph_count.to_be_bytes());
+	maxp.extend((max_points.min(u16::MAX as usize) as u16).to_be_bytes());
+	maxp.extend((max_contours.min(u16::MAX as usize) as u16).to_be_bytes());
+	// Composite points and contours, zones, then the rest of the hinting values and composite glyph values
+	maxp.extend([0, 0, 0, 0, 0, 2]);
+	maxp.extend([0; 18]);
+
+	let mut tables: Vec<([u8; 4], Vec<u8>)> = vec!
+	[
+		(*b"glyf", glyf),
+		(*b"head", head),
+		(*b"hhea", hhea),
+		(*b"hmtx", hmtx),
+		(*b"loca", loca.iter().flat_map(|offset| offset.to_be_bytes()).collect()),
+		(*b"maxp", maxp)
+	];
+	for tag in COPIED_TABLES
+	{
+		if let Some(table) = raw_table(tag)
+		{
+			let mut table = table.to_vec();
+			// Make the weight class of the font match its weight axis
+			if tag == b"OS/2" && table.len() >= 6
+			{
+				if let Some((_, weight)) = axes.iter().find(|(axis, _)| axis == "wght")
+				{
+					table[4..6].copy_from_slice(&(weight.round().clamp(1.0, 1000.0) as u16).to_be_bytes());
+				}
+			}
+			tables.push((*tag, table));
+		}
+	}
+	tables.sort_by_key(|(tag, _)| *tag);
+	Ok(write_font(tables))
+}
+
+/// Turns the contours of a glyph into a simple glyph in the glyf table format.
+///
+/// # Output
+///
+/// The glyph's data (empty if it has no contours) and its bounding box (x min, y min, x max, y max).
+fn encode_glyph(contours: &Vec<Vec<ContourPoint>>) -> (Vec<u8>, Option<[i16; 4]>)
+{
+	let points: Vec<_> = contours.iter().flatten()
+		.map(|point| (point.x.round() as i16, point.y.round() as i16, point.on_curve))
+		.collect();
+	if points.is_empty() { return (Vec::new(), None); }
+	let x_min = points.iter().map(|point| point.0).min().unwrap_or(0);
+	let y_min = points.iter().map(|point| point.1).min().unwrap_or(0);
+	let x_max = points.iter().map(|point| point.0).max().unwrap_or(0);
+	let y_max = points.iter().map(|point| point.1).max().unwrap_or(0);
+	let mut data = Vec::new();
+	data.extend((contours.len() as i16).to_be_bytes());
+	for bound in [x_min, y_min, x_max, y_max] { data.extend(bound.to_be_bytes()); }
+	// The index of the last point in each contour
+	let mut end_point = 0;
+	for contour in contours
+	{
+		end_point += contour.len();
+		data.extend(((end_point - 1) as u16).to_be_bytes());
+	}
+	// No hinting instructions
+	data.extend(0_u16.to_be_bytes());
+	// Every coordinate is stored as a 16 bit difference from the last point, so the only flag needed is on-curve
+	data.extend(points.iter().map(|point| point.2 as u8));
+	let mut last = (0_i16, 0_i16);
+	let mut y_data = Vec::with_capacity(points.len() * 2);
+	for (x, y, _) in &points
+	{
+		data.extend(x.wrapping_sub(last.0).to_be_bytes());
+		y_data.extend(y.wrapping_sub(last.1).to_be_bytes());
+		last = (*x, *y);
+	}
+	data.extend(y_data);
+	// Keep every glyph aligned to 4 bytes
+	while data.len() % 4 != 0 { data.push(0); }
+	(data, Some([x_min, y_min, x_max, y_max]))
+}
+
+/// Adds up a table as big-endian 32 bit numbers (padded with zeros at the end).
+fn checksum(data: &[u8]) -> u32
+{
+	data.chunks(4).fold(0_u32, |sum, chunk|
+	{
+		let mut word = [0; 4];
+		word[..chunk.len()].copy_from_slice(chunk);
+		sum.wrapping_add(u32::from_be_bytes(word))
+	})
+}
+
+/// Puts tables together into a TrueType font file.
+///
+/// # Parameters
+///
+/// - `tables` The tag and data of each table sorted by tag. One must be the head table.
+fn write_font(tables: Vec<([u8; 4], Vec<u8>)>) -> Vec<u8>
+{
+	let table_count = tables.len() as u16;
+	let entry_selector = 15 - table_count.leading_zeros() as u16;
+	let search_range = (1_u16 << entry_selector) * 16;
+	let mut font = Vec::new();
+	font.extend(0x00010000_u32.to_be_bytes());
+	font.extend(table_count.to_be_bytes());
+	font.extend(search_range.to_be_bytes());
+	font.extend(entry_selector.to_be_bytes());
+	font.extend((table_count * 16 - search_range).to_be_bytes());
+	// Table records, then the tables themselves (each starting on a multiple of 4 bytes)
+	let mut offset = 12 + tables.len() * 16;
+	let mut head_offset = 0;
+	for (tag, table) in &tables
+	{
+		if tag == b"head" { head_offset = offset; }
+		font.extend(tag);
+		font.extend(checksum(table).to_be_bytes());
+		font.extend((offset as u32).to_be_bytes());
+		font.extend((table.len() as u32).to_be_bytes());
+		offset += table.len().div_ceil(4) * 4;
+	}
+	for (_, table) in &tables
+	{
+		font.extend(table);
+		while font.len() % 4 != 0 { font.push(0); }
+	}
+	let checksum_adjustment = CHECKSUM_MAGIC.wrapping_sub(checksum(&font));
+	font[head_offset + 8..head_offset + 12].copy_from_slice(&checksum_adjustment.to_be_bytes());
+	font
+}
@@ -0,0 +1,188 @@
+//////////////////////////////////////////////////////////////////////////////////////////////////////////////
+//
+//	Checking what font files can write and writing plainer text in place of characters they don't have
+//
+//////////////////////////////////////////////////////////////////////////////////////////////////////////////
+
+use std::borrow::Cow;
+use std::error::Error;
+
+use rusttype::Font;
+
+use crate::spellbook_options::{FontVariant, FontPaths};
+use crate::spellbook_gen_types::
+{
+	FontSizeData,
+	SyntheticStyle,
+	BytesToFontSizeDataConversionError,
+	synthetic_font_sources
+};
+use crate::utils::load_font_bytes;
+
+/// Characters that spellbooks write (like bullet points and dashes) that not every font has, along with the plainer
+/// text that gets written instead when a font doesn't have a glyph for them.
+pub const GLYPH_FALLBACKS: [(char, &str); 11] =
+[
+	('\u{2022}', "-"),
+	('\u{2014}', "--"),
+	('\u{2013}', "-"),
+	('\u{2212}', "-"),
+	('\u{2018}', "'"),
+	('\u{2019}', "'"),
+	('\u{201C}', "\""),
+	('\u{201D}', "\""),
+	('\u{2026}', "..."),
+	('\u{00D7}', "x"),
+	('\u{00BD}', "1/2")
+];
+
+/// The characters in `GLYPH_FALLBACKS` that a font doesn't have glyphs for, along with what gets written instead.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct GlyphFallbacks(pub(crate) Vec<(char, &'static str)>);
+
+impl GlyphFallbacks
+{
+	/// Finds the characters in `GLYPH_FALLBACKS` that a font doesn't have. Characters whose fallback text the font
+	/// doesn't have either are left alone.
+	pub fn for_font(font: &Font) -> Self
+	{
+		let has_glyph = |c: char| font.glyph(c).id().0 != 0;
+		Self(GLYPH_FALLBACKS.iter()
+			.filter(|(c, fallback)| !has_glyph(*c) && fallback.chars().all(has_glyph))
+			.copied()
+			.collect())
+	}
+
+	/// Replaces the characters in some text that the font doesn't have with their fallback text.
+	pub fn apply<'t>(&self, text: &'t str) -> Cow<'t, str>
+	{
+		if !text.chars().any(|c| self.0.iter().any(|(missing, _)| *missing == c)) { return Cow::Borrowed(text); }
+		let mut replaced = String::with_capacity(text.len());
+		for c in text.chars()
+		{
+			match self.0.iter().find(|(missing, _)| *missing == c)
+			{
+				Some((_, fallback)) => replaced.push_str(fallback),
+				None => replaced.push(c)
+			}
+		}
+		Cow::Owned(replaced)
+	}
+}
+
+/// Holds the glyph fallbacks for each font type of a font.
+#[derive(Clone, Debug, PartialEq)]
+pub struct FontGlyphFallbacks
+{
+	pub regular: GlyphFallbacks,
+	pub bold: GlyphFallbacks,
+	pub italic: GlyphFallbacks,
+	pub bold_italic: GlyphFallbacks
+}
+
+impl FontGlyphFallbacks
+{
+	/// Finds the glyph fallbacks for each font type from their size data.
+	pub fn from_size_data(size_data: &FontSizeData) -> Self
+	{
+		Self
+		{
+			regular: GlyphFallbacks::for_font(&size_data.regular),
+			bold: GlyphFallbacks::for_font(&size_data.bold),
+			italic: GlyphFallbacks::for_font(&size_data.italic),
+			bold_italic: GlyphFallbacks::for_font(&size_data.bold_italic)
+		}
+	}
+
+	/// Returns the glyph fallbacks for a specific font variant.
+	pub fn get_for(&self, font_variant: FontVariant) -> &GlyphFallbacks
+	{
+		match font_variant
+		{
+			FontVariant::Regular => &self.regular,
+			FontVariant::Bold => &self.bold,
+			FontVariant::Italic => &self.italic,
+			FontVariant::BoldItalic => &self.bold_italic
+		}
+	}
+}
+
+/// A character that a font variant doesn't have a glyph for.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MissingGlyph
+{
+	/// The font variant that doesn't have the character.
+	pub font_variant: FontVariant,
+	/// The character it doesn't have.
+	pub character: char,
+	/// The text that gets written instead (`None` if the font doesn't have that either, so the character gets left
+	/// out).
+	pub fallback: Option<&'static str>
+}
+
+/// What a set of font files can and can't write.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct FontReport
+{
+	/// The font variants that don't have their own font file along with the font variant they get made from and the
+	/// styles that get faked to make them.
+	pub synthesized_variants: Vec<(FontVariant, FontVariant, SyntheticStyle)>,
+	/// The characters in `GLYPH_FALLBACKS` that each font file doesn't have (listed under the font variant of the
+	/// file).
+	pub missing_glyphs: Vec<MissingGlyph>
+}
+
+impl FontReport
+{
+	/// Whether or not the font files have every font variant and every character in `GLYPH_FALLBACKS`.
+	pub fn is_complete(&self) -> bool { self.synthesized_variants.is_empty() && self.missing_glyphs.is_empty() }
+}
+
+/// Checks which font variants a set of font files has and which of the characters spellbooks write (see
+/// `GLYPH_FALLBACKS`) they don't have, so fonts (like the font of an `AccessibilityPreset`) can be checked before
+/// making a spellbook with them. Spellbooks get made with the same fallbacks either way.
+///
+/// # Parameters
+///
+/// - `font_paths` File paths to all of the font variants (regular, bold, italic, bold-italic). Variants with an
+///   empty path get synthesized from another variant.
+///
+/// # Output
+///
+/// - `Ok` What the font files can and can't write.
+/// - `Err` Returns any errors that occured when reading the font files.
+pub fn font_report(font_paths: &FontPaths) -> Result<FontReport, Box<dyn Error + Send + Sync>>
+{
+	let paths = [&font_paths.regular, &font_paths.bold, &font_paths.italic, &font_paths.bold_italic];
+	let sources = synthetic_font_sources(paths);
+	let font_variants = [FontVariant::Regular, FontVariant::Bold, FontVariant::Italic, FontVariant::BoldItalic];
+	let mut report = FontReport::default();
+	for (font_variant, (source, synthetic_style)) in font_variants.into_iter().zip(sources)
+	{
+		if source != font_variant
+		{
+			report.synthesized_variants.push((font_variant, source, synthetic_style));
+			continue;
+		}
+		let axes = font_paths.variable_axes.as_ref().map(|axes| axes.axes_for(font_variant));
+		let path = paths[font_variant as usize];
+		let font = match Font::try_from_vec(load_font_bytes(path, axes)?)
+		{
+			Some(font) => font,
+			None => return Err(Box::new(BytesToFontSizeDataConversionError(format!
+				("Could not convert font size data from bytes in \"{}\".", path))))
+		};
+		let has_glyph = |c: char| font.glyph(c).id().0 != 0;
+		for (character, fallback) in GLYPH_FALLBACKS
+		{
+			if has_glyph(character) { continue; }
+			report.missing_glyphs.push(MissingGlyph
+			{
+				font_variant: font_variant,
+				character: character,
+				fallback: if fallback.chars().all(has_glyph) { Some(fallback) } else { None }
+			});
+		}
+	}
+	Ok(report)
+}
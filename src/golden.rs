@@ -0,0 +1,289 @@
+//////////////////////////////////////////////////////////////////////////////////////////////////////////////
+//
+//	Golden image testing for catching changes to how spellbooks look
+//
+//////////////////////////////////////////////////////////////////////////////////////////////////////////////
+
+use std::fs;
+use std::error::Error;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use image::RgbaImage;
+
+/// Turns pages of a pdf document into images so they can be compared to reference images.
+pub trait PageRasterizer
+{
+	/// Renders a single page of a pdf document.
+	///
+	/// # Parameters
+	///
+	/// - `pdf_bytes` The bytes of the whole pdf document (ex: from `Spellbook::save_to_bytes()`).
+	/// - `page_index` The index of the page to render (0 for the title page).
+	/// - `dpi` How many pixels to render per inch of the page.
+	///
+	/// # Output
+	///
+	/// - `Ok` The rendered page.
+	/// - `Err` Returns any errors that occurred.
+	fn rasterize_page(&self, pdf_bytes: &[u8], page_index: usize, dpi: f32)
+	-> Result<RgbaImage, Box<dyn Error + Send + Sync>>;
+}
+
+/// Renders pages with the `pdftoppm` program from poppler, which has to be installed separately.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PdftoppmRasterizer
+{
+	/// The name or path of the `pdftoppm` program.
+	pub program: String
+}
+
+impl Default for PdftoppmRasterizer
+{
+	fn default() -> Self { Self { program: String::from("pdftoppm") } }
+}
+
+impl PdftoppmRasterizer
+{
+	/// Whether or not the `pdftoppm` program can be run. Useful for skipping golden image tests on machines that
+	/// don't have it.
+	pub fn is_available(&self) -> bool
+	{
+		Command::new(&self.program).arg("-v").output().is_ok()
+	}
+}
+
+// Used to give each temporary file a different name so rasterizers can be used from multiple tests at once
+static TEMP_FILE_COUNT: AtomicUsize = AtomicUsize::new(0);
+
+impl PageRasterizer for PdftoppmRasterizer
+{
+	fn rasterize_page(&self, pdf_bytes: &[u8], page_index: usize, dpi: f32)
+	-> Result<RgbaImage, Box<dyn Error + Send + Sync>>
+	{
+		// pdftoppm only reads pdf documents from files, so write the document to a temporary file
+		let temp_name = format!
+		(
+			"dnd_spellbook_maker_golden_{}_{}",
+			std::process::id(),
+			TEMP_FILE_COUNT.fetch_add(1, Ordering::Relaxed)
+		);
+		let pdf_path = std::env::temp_dir().join(format!("{}.pdf", temp_name));
+		let image_prefix = std::env::temp_dir().join(&temp_name);
+		let image_path = image_prefix.with_extension("png");
+		fs::write(&pdf_path, pdf_bytes)?;
+		// pdftoppm page numbers start at 1
+		let page_number = (page_index + 1).to_string();
+		let output = Command::new(&self.program)
+			.args(["-png", "-singlefile", "-r", &dpi.to_string(), "-f", &page_number, "-l", &page_number])
+			.arg(&pdf_path)
+			.arg(&image_prefix)
+			.output();
+		let _ = fs::remove_file(&pdf_path);
+		let output = output?;
+		if !output.status.success()
+		{
+			let _ = fs::remove_file(&image_path);
+			return Err(Box::new(GoldenImageError(format!
+			(
+				"{} failed to render page {}: {}",
+				self.program,
+				page_index,
+				String::from_utf8_lossy(&output.stderr).trim()
+			))));
+		}
+		let image = image::open(&image_path);
+		let _ = fs::remove_file(&image_path);
+		Ok(image?.to_rgba8())
+	}
+}
+
+/// How closely rendered pages have to match their reference images.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct GoldenImageOptions
+{
+	/// How many pixels to render per inch of each page.
+	pub dpi: f32,
+	/// How much each color channel of a pixel can be off by before the pixel counts as different
+	/// (to allow for small differences in anti-aliasing between renderers).
+	pub channel_tolerance: u8,
+	/// The fraction of pixels that can be different before a page doesn't match its reference (0.0 to 1.0).
+	pub max_differing_pixel_ratio: f32,
+	/// Overwrites the reference images with the rendered pages instead of comparing them.
+	/// Used for accepting changes to how spellbooks look.
+	pub update_references: bool
+}
+
+impl Default for GoldenImageOptions
+{
+	fn default() -> Self
+	{
+		Self
+		{
+			dpi: 50.0,
+			channel_tolerance: 16,
+			max_differing_pixel_ratio: 0.001,
+			update_references: false
+		}
+	}
+}
+
+/// How different a rendered page is from its reference image.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ImageDifference
+{
+	/// The number of pixels that were different by more than the channel tolerance.
+	pub differing_pixels: usize,
+	/// The total number of pixels in each image.
+	pub total_pixels: usize,
+	/// The largest difference between the same color channel of the same pixel in each image.
+	pub max_channel_difference: u8
+}
+
+impl ImageDifference
+{
+	/// The fraction of pixels that were different (0.0 to 1.0).
+	pub fn differing_pixel_ratio(&self) -> f32
+	{
+		if self.total_pixels == 0 { return 0.0; }
+		self.differing_pixels as f32 / self.total_pixels as f32
+	}
+}
+
+/// Error for when a rendered page doesn't match its reference image.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct GoldenImageError(String);
+
+impl std::fmt::Display for GoldenImageError
+{
+	fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result
+	{
+		write!(f, "{}", self.0)
+	}
+}
+impl Error for GoldenImageError {}
+
+/// Compares two images pixel by pixel.
+///
+/// # Parameters
+///
+/// - `actual` The rendered image.
+/// - `expected` The reference image.
+/// - `channel_tolerance` How much each color channel of a pixel can be off by before the pixel counts as different.
+///
+/// # Output
+///
+/// - `Ok` How different the images are.
+/// - `Err` The images aren't the same size.
+pub fn compare_images(actual: &RgbaImage, expected: &RgbaImage, channel_tolerance: u8)
+-> Result<ImageDifference, GoldenImageError>
+{
+	if actual.dimensions() != expected.dimensions()
+	{
+		return Err(GoldenImageError(format!
+		(
+			"Image is {}x{} but the reference image is {}x{}.",
+			actual.width(),
+			actual.height(),
+			expected.width(),
+			expected.height()
+		)));
+	}
+	let mut differing_pixels = 0;
+	let mut max_channel_difference = 0;
+	for (actual_pixel, expected_pixel) in actual.pixels().zip(expected.pixels())
+	{
+		let pixel_difference = actual_pixel.0.iter().zip(expected_pixel.0.iter())
+			.map(|(a, b)| a.abs_diff(*b))
+			.max()
+			.unwrap_or(0);
+		if pixel_difference > channel_tolerance { differing_pixels += 1; }
+		max_channel_difference = max_channel_difference.max(pixel_difference);
+	}
+	Ok(ImageDifference
+	{
+		differing_pixels: differing_pixels,
+		total_pixels: actual.pixels().len(),
+		max_channel_difference: max_channel_difference
+	})
+}
+
+/// Renders pages of a pdf document and compares them to reference images in a folder.
+///
+/// The reference image for each page is named "{name}_page_{page index}.png". If a reference image doesn't exist
+/// yet (or `update_references` is on), the rendered page gets saved as the reference image instead.
+/// If a page doesn't match its reference image, the rendered page gets saved next to it as
+/// "{name}_page_{page index}_actual.png" so the two can be looked at.
+///
+/// # Parameters
+///
+/// - `pdf_bytes` The bytes of the pdf document (ex: from `Spellbook::save_to_bytes()`).
+/// - `page_indexes` The indexes of the pages to check (0 for the title page).
+/// - `reference_folder` The folder that has the reference images in it.
+/// - `name` The name at the start of each reference image's file name.
+/// - `rasterizer` What to render the pages with.
+/// - `options` How closely the pages have to match their reference images.
+///
+/// # Output
+///
+/// - `Ok` Every page matched its reference image.
+/// - `Err` A page didn't match its reference image or there was a problem rendering or saving it.
+pub fn check_pages_against_references
+(
+	pdf_bytes: &[u8],
+	page_indexes: &[usize],
+	reference_folder: &Path,
+	name: &str,
+	rasterizer: &dyn PageRasterizer,
+	options: &GoldenImageOptions
+)
+-> Result<(), Box<dyn Error + Send + Sync>>
+{
+	fs::create_dir_all(reference_folder)?;
+	let mut mismatches = Vec::new();
+	for page_index in page_indexes
+	{
+		let actual = rasterizer.rasterize_page(pdf_bytes, *page_index, options.dpi)?;
+		let reference_path = reference_image_path(reference_folder, name, *page_index, "");
+		let actual_path = reference_image_path(reference_folder, name, *page_index, "_actual");
+		// Save the page as the reference if there's nothing to compare it to
+		if options.update_references || !reference_path.exists()
+		{
+			actual.save(&reference_path)?;
+			let _ = fs::remove_file(&actual_path);
+			continue;
+		}
+		let expected = image::open(&reference_path)?.to_rgba8();
+		let matches = match compare_images(&actual, &expected, options.channel_tolerance)
+		{
+			Ok(difference) if difference.differing_pixel_ratio() <= options.max_differing_pixel_ratio => true,
+			Ok(difference) =>
+			{
+				mismatches.push(format!
+				(
+					"Page {} is {:.3}% different from {}.",
+					page_index,
+					difference.differing_pixel_ratio() * 100.0,
+					reference_path.display()
+				));
+				false
+			},
+			Err(error) =>
+			{
+				mismatches.push(format!("Page {}: {}", page_index, error));
+				false
+			}
+		};
+		if matches { let _ = fs::remove_file(&actual_path); }
+		else { actual.save(&actual_path)?; }
+	}
+	if mismatches.is_empty() { Ok(()) }
+	else { Err(Box::new(GoldenImageError(mismatches.join("\n")))) }
+}
+
+/// The file path of a reference image (or the rendered page next to it if `suffix` isn't empty).
+fn reference_image_path(reference_folder: &Path, name: &str, page_index: usize, suffix: &str) -> PathBuf
+{
+	reference_folder.join(format!("{}_page_{}{}.png", name, page_index, suffix))
+}
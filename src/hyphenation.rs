@@ -0,0 +1,132 @@
+//////////////////////////////////////////////////////////////////////////////////////////////////////////////
+//
+//	Finding where words can be hyphenated
+//
+//////////////////////////////////////////////////////////////////////////////////////////////////////////////
+
+use std::error::Error;
+
+use crate::spellbook_options::HyphenationOptions;
+use crate::spellbook_gen_types::{REDACTED_START, PAGE_REFERENCE_START};
+
+/// Error for when a hyphenator couldn't be made for a language.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HyphenationError(pub(crate) String);
+// Makes the struct displayable
+impl std::fmt::Display for HyphenationError
+{
+	fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result
+	{
+		write!(f, "{}", self.0)
+	}
+}
+// Makes the struct officially an error
+impl Error for HyphenationError {}
+
+/// Finds the syllable boundaries in words using the Knuth-Liang hyphenation patterns of a language so that words that
+/// are too long to fit on a line get hyphenated between syllables. Needs the `hyphenation` feature.
+#[derive(Clone, Debug)]
+pub struct Hyphenator
+{
+	#[cfg(feature = "hyphenation")]
+	language: hypher::Lang,
+	#[cfg(feature = "hyphenation")]
+	min_prefix: usize,
+	#[cfg(feature = "hyphenation")]
+	min_suffix: usize
+}
+
+impl Hyphenator
+{
+	/// Constructor
+	///
+	/// # Parameters
+	///
+	/// - `options` The language of the hyphenation patterns and how many letters have to go before and after each
+	///   hyphen.
+	///
+	/// # Output
+	///
+	/// - `Ok` A Hyphenator instance.
+	/// - `Err` The language doesn't have hyphenation patterns (or the `hyphenation` feature is missing).
+	#[cfg(feature = "hyphenation")]
+	pub fn new(options: &HyphenationOptions) -> Result<Self, HyphenationError>
+	{
+		let code = options.language.to_lowercase();
+		let language = match code.as_bytes()
+		{
+			[first, second] => hypher::Lang::from_iso([*first, *second]),
+			_ => None
+		};
+		match language
+		{
+			Some(language) => Ok(Self
+			{
+				language: language,
+				min_prefix: options.min_prefix,
+				min_suffix: options.min_suffix
+			}),
+			None => Err(HyphenationError(format!("There are no hyphenation patterns for the language \"{}\".",
+				options.language)))
+		}
+	}
+
+	/// Words can't be hyphenated by syllable without the `hyphenation` feature.
+	#[cfg(not(feature = "hyphenation"))]
+	pub fn new(options: &HyphenationOptions) -> Result<Self, HyphenationError>
+	{
+		Err(HyphenationError(format!
+			("The `hyphenation` feature is needed to hyphenate words in the language \"{}\".", options.language)))
+	}
+
+	/// The byte indexes in a word that it can be split at with a hyphen at the end of the first part, in order.
+	/// Punctuation around the word (like quotes or commas) is ignored, and words that already have hyphens in them
+	/// can also be split after those hyphens. Words that aren't made of letters can't be split anywhere.
+	pub fn break_points(&self, word: &str) -> Vec<usize>
+	{
+		// Words hidden behind shaded bars have to stay in one piece to stay hidden, and so do page references since
+		// their page numbers get filled in later
+		if word.contains(REDACTED_START) || word.contains(PAGE_REFERENCE_START) { return Vec::new(); }
+		// Only look at the letters between any punctuation at the start and end of the word
+		let start = match word.find(char::is_alphabetic)
+		{
+			Some(start) => start,
+			None => return Vec::new()
+		};
+		let end = word.char_indices().rev()
+			.find(|(_, c)| c.is_alphabetic())
+			.map_or(word.len(), |(index, c)| index + c.len_utf8());
+		let core = &word[start..end];
+		if !core.chars().all(|c| c.is_alphabetic() || c == '-' || c == '\'' || c == '’') { return Vec::new(); }
+		let mut break_points = Vec::new();
+		let mut part_start = start;
+		// Each part of a word with hyphens in it gets hyphenated on its own
+		for part in core.split('-')
+		{
+			let mut index = part_start;
+			let syllables = self.syllables(part);
+			for syllable in &syllables[..syllables.len().saturating_sub(1)]
+			{
+				index += syllable.len();
+				break_points.push(index);
+			}
+			part_start += part.len() + 1;
+			// The word can also be split right after its hyphen
+			if part_start < end { break_points.push(part_start); }
+		}
+		break_points.sort_unstable();
+		break_points.dedup();
+		break_points
+	}
+
+	/// Splits a word (without punctuation around it) into syllables.
+	#[cfg(feature = "hyphenation")]
+	fn syllables<'w>(&self, word: &'w str) -> Vec<&'w str>
+	{
+		hypher::hyphenate_bounded(word, self.language, self.min_prefix, self.min_suffix).collect()
+	}
+
+	/// Words can't be split into syllables without the `hyphenation` feature.
+	#[cfg(not(feature = "hyphenation"))]
+	fn syllables<'w>(&self, word: &'w str) -> Vec<&'w str> { vec![word] }
+}
@@ -0,0 +1,91 @@
+//////////////////////////////////////////////////////////////////////////////////////////////////////////////
+//
+//	Images that can be reused between spellbooks
+//
+//////////////////////////////////////////////////////////////////////////////////////////////////////////////
+
+use std::collections::HashMap;
+use std::collections::hash_map::DefaultHasher;
+use std::error::Error;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::sync::{Arc, Mutex};
+
+use image::DynamicImage;
+use printpdf::{ImageTransform, ImageXObject};
+
+use crate::utils::load_image;
+
+/// An image that has been opened along with the image data that gets put into pdf documents for it.
+#[derive(Clone, Debug)]
+pub(crate) struct CachedImage
+{
+	pub(crate) image: Arc<DynamicImage>,
+	pub(crate) pdf_image: Arc<ImageXObject>
+}
+
+/// Remembers background images between spellbooks so that making more than one spellbook with the same backgrounds
+/// (like the DM and player editions of a spellbook, or a spellbook that gets made again after changing a few spells)
+/// doesn't have to open, decode, and convert the same image files again. Each image is stored under its file path and
+/// the transform it was given with (since svg files get rasterized at the transform's dpi).
+///
+/// Clones of an image cache share the same images, so a clone can be put in `AdditionalOptions` for each spellbook
+/// while keeping the original. Changes to image files aren't noticed unless their file paths change too.
+#[derive(Clone, Default)]
+pub struct ImageCache
+{
+	images: Arc<Mutex<HashMap<u64, CachedImage>>>
+}
+
+impl ImageCache
+{
+	/// Constructs an empty image cache.
+	pub fn new() -> Self { Self::default() }
+
+	/// The number of images in the cache.
+	pub fn len(&self) -> usize { self.images.lock().unwrap().len() }
+
+	/// Whether or not there aren't any images in the cache.
+	pub fn is_empty(&self) -> bool { self.len() == 0 }
+
+	/// Forgets every image in the cache.
+	pub fn clear(&self) { self.images.lock().unwrap().clear(); }
+
+	/// Gets the image at a file path with the given transform, opening it and putting it in the cache if it isn't in
+	/// there yet.
+	pub(crate) fn get_or_load(&self, image_path: &str, transform: &ImageTransform)
+	-> Result<CachedImage, Box<dyn Error + Send + Sync>>
+	{
+		let key = image_key(image_path, transform);
+		if let Some(image) = self.images.lock().unwrap().get(&key) { return Ok(image.clone()); }
+		// printpdf uses 300 dpi for images that don't have one
+		let image = load_image(image_path, transform.dpi.unwrap_or(300.0))?;
+		let pdf_image = ImageXObject::from_dynamic_image(&image);
+		let image = CachedImage { image: Arc::new(image), pdf_image: Arc::new(pdf_image) };
+		self.images.lock().unwrap().insert(key, image.clone());
+		Ok(image)
+	}
+}
+
+impl fmt::Debug for ImageCache
+{
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
+	{
+		write!(f, "ImageCache ({} images)", self.len())
+	}
+}
+
+// Caches are only equal if they share the same images
+impl PartialEq for ImageCache
+{
+	fn eq(&self, other: &Self) -> bool { Arc::ptr_eq(&self.images, &other.images) }
+}
+
+/// Returns the key that an image is stored under (from its file path and the debug text of its transform).
+fn image_key(image_path: &str, transform: &ImageTransform) -> u64
+{
+	let mut hasher = DefaultHasher::new();
+	image_path.hash(&mut hasher);
+	format!("{:?}", transform).hash(&mut hasher);
+	hasher.finish()
+}
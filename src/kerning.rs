@@ -0,0 +1,214 @@
+//////////////////////////////////////////////////////////////////////////////////////////////////////////////
+//
+//	Kerning data read from font files so text can be measured and written with kerning
+//
+//////////////////////////////////////////////////////////////////////////////////////////////////////////////
+
+use std::collections::HashMap;
+
+use ttf_parser::{Face, GlyphId, Tag};
+use ttf_parser::gpos::{PairAdjustment, PositioningSubtable};
+use ttf_parser::kern;
+
+/// Kerning adjustments between pairs of glyphs in a font, in the font's design units.
+///
+/// Kerning comes from the pair adjustment lookups of the font's `kern` feature (in its GPOS table), or from its
+/// legacy kern table if it doesn't have any of those.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct KerningTable
+{
+	/// Each lookup's subtables in the order they get checked. Kerning from every lookup adds together.
+	lookups: Vec<Vec<KerningSubtable>>,
+	/// The number of design units in the font's em square.
+	units_per_em: u16
+}
+
+/// A single subtable of kerning pairs.
+#[derive(Clone, Debug, PartialEq)]
+enum KerningSubtable
+{
+	/// Kerning for specific pairs of glyphs (keyed by the glyph ids of the left and right glyphs).
+	Pairs(HashMap<(u16, u16), i16>),
+	/// Kerning between classes of glyphs.
+	Classes(ClassKerning)
+}
+
+/// Kerning between classes of glyphs.
+#[derive(Clone, Debug, PartialEq)]
+struct ClassKerning
+{
+	/// The class of each glyph this subtable covers when it's the left glyph of a pair.
+	first_classes: HashMap<u16, u16>,
+	/// The class of each glyph when it's the right glyph of a pair (glyphs that aren't in here are class 0).
+	second_classes: HashMap<u16, u16>,
+	/// The number of classes for right glyphs.
+	second_class_count: usize,
+	/// The kerning for each pair of classes (indexed by first class * `second_class_count` + second class).
+	values: Vec<i16>
+}
+
+impl KerningSubtable
+{
+	/// Gives the kerning between two glyphs, or `None` if this subtable doesn't cover that pair.
+	fn kerning(&self, left: u16, right: u16) -> Option<i16>
+	{
+		match self
+		{
+			Self::Pairs(pairs) => pairs.get(&(left, right)).copied(),
+			Self::Classes(classes) =>
+			{
+				let first_class = usize::from(*classes.first_classes.get(&left)?);
+				let second_class = usize::from(classes.second_classes.get(&right).copied().unwrap_or(0));
+				Some(classes.values.get(first_class * classes.second_class_count + second_class).copied().unwrap_or(0))
+			}
+		}
+	}
+}
+
+impl KerningTable
+{
+	/// Reads the kerning data from the bytes of a font file.
+	/// Gives an empty table if the font can't be parsed or doesn't have any kerning.
+	pub fn from_font_bytes(font_bytes: &[u8]) -> Self
+	{
+		let face = match Face::parse(font_bytes, 0)
+		{
+			Ok(face) => face,
+			Err(_) => return Self::default()
+		};
+		let mut lookups = gpos_kerning(&face);
+		// Only use the legacy kern table if the font doesn't have any kerning in its GPOS table (same as shapers do)
+		if lookups.is_empty() { lookups = kern_table_kerning(&face); }
+		Self
+		{
+			lookups: lookups,
+			units_per_em: face.units_per_em()
+		}
+	}
+
+	/// Gives the kerning between two glyphs (by glyph id) in the font's design units.
+	/// Negative values move the right glyph closer to the left one.
+	pub fn kerning(&self, left: u16, right: u16) -> i16
+	{
+		let kerning: i32 = self.lookups.iter()
+			.filter_map(|subtables| subtables.iter().find_map(|subtable| subtable.kerning(left, right)))
+			.map(i32::from)
+			.sum();
+		kerning.clamp(i16::MIN.into(), i16::MAX.into()) as i16
+	}
+
+	/// Gives the kerning between two glyphs in thousandths of an em (which is what pdf text operations use).
+	/// Positive values move the right glyph closer to the left one (the opposite of `kerning()`).
+	pub fn pdf_adjustment(&self, left: u16, right: u16) -> i64
+	{
+		let units_per_em = f32::from(self.units_per_em.max(1));
+		(-f32::from(self.kerning(left, right)) * 1000.0 / units_per_em).round() as i64
+	}
+}
+
+/// Reads the pair adjustments from the lookups of the `kern` feature in a font's GPOS table.
+fn gpos_kerning(face: &Face) -> Vec<Vec<KerningSubtable>>
+{
+	let gpos = match face.tables().gpos
+	{
+		Some(gpos) => gpos,
+		None => return Vec::new()
+	};
+	// Get every lookup the kern feature uses in any script and language (in the order they get applied)
+	let kern_tag = Tag::from_bytes(b"kern");
+	let mut lookup_indices: Vec<u16> = gpos.features.into_iter()
+		.filter(|feature| feature.tag == kern_tag)
+		.flat_map(|feature| feature.lookup_indices.into_iter())
+		.collect();
+	lookup_indices.sort_unstable();
+	lookup_indices.dedup();
+	let glyph_count = face.number_of_glyphs();
+	let mut lookups = Vec::new();
+	for lookup_index in lookup_indices
+	{
+		let lookup = match gpos.lookups.get(lookup_index)
+		{
+			Some(lookup) => lookup,
+			None => continue
+		};
+		let mut subtables = Vec::new();
+		for subtable in lookup.subtables.into_iter::<PositioningSubtable>()
+		{
+			match subtable
+			{
+				PositioningSubtable::Pair(PairAdjustment::Format1 { coverage, sets }) =>
+				{
+					let mut pairs = HashMap::new();
+					for left in 0..glyph_count
+					{
+						let set = match coverage.get(GlyphId(left)).and_then(|index| sets.get(index))
+						{
+							Some(set) => set,
+							None => continue
+						};
+						for right in 0..glyph_count
+						{
+							if let Some((record, _)) = set.get(GlyphId(right)) { pairs.insert((left, right), record.x_advance); }
+						}
+					}
+					subtables.push(KerningSubtable::Pairs(pairs));
+				},
+				PositioningSubtable::Pair(PairAdjustment::Format2 { coverage, classes, matrix }) =>
+				{
+					let first_classes: HashMap<u16, u16> = (0..glyph_count)
+						.filter(|glyph| coverage.contains(GlyphId(*glyph)))
+						.map(|glyph| (glyph, classes.0.get(GlyphId(glyph))))
+						.collect();
+					let second_classes: HashMap<u16, u16> = (0..glyph_count)
+						.map(|glyph| (glyph, classes.1.get(GlyphId(glyph))))
+						.filter(|(_, class)| *class != 0)
+						.collect();
+					let first_class_count = first_classes.values().max().map_or(1, |class| usize::from(*class) + 1);
+					let second_class_count = second_classes.values().max().map_or(1, |class| usize::from(*class) + 1);
+					let mut values = Vec::with_capacity(first_class_count * second_class_count);
+					for first_class in 0..first_class_count
+					{
+						for second_class in 0..second_class_count
+						{
+							let value = matrix.get((first_class as u16, second_class as u16))
+								.map_or(0, |(record, _)| record.x_advance);
+							values.push(value);
+						}
+					}
+					subtables.push(KerningSubtable::Classes(ClassKerning
+					{
+						first_classes: first_classes,
+						second_classes: second_classes,
+						second_class_count: second_class_count,
+						values: values
+					}));
+				},
+				_ => ()
+			}
+		}
+		if !subtables.is_empty() { lookups.push(subtables); }
+	}
+	lookups
+}
+
+/// Reads the kerning pairs from a font's legacy kern table.
+/// Only horizontal subtables with lists of pairs are supported (which is what almost every font with a kern table has).
+fn kern_table_kerning(face: &Face) -> Vec<Vec<KerningSubtable>>
+{
+	let kern = match face.tables().kern
+	{
+		Some(kern) => kern,
+		None => return Vec::new()
+	};
+	let mut lookups = Vec::new();
+	for subtable in kern.subtables
+	{
+		if !subtable.horizontal || subtable.variable || subtable.has_cross_stream { continue; }
+		if let kern::Format::Format0(pairs) = subtable.format
+		{
+			let pairs = pairs.pairs.into_iter().map(|pair| ((pair.left().0, pair.right().0), pair.value)).collect();
+			lookups.push(vec![KerningSubtable::Pairs(pairs)]);
+		}
+	}
+	lookups
+}
@@ -0,0 +1,232 @@
+//////////////////////////////////////////////////////////////////////////////////////////////////////////////
+//
+//	Exporting spellbooks as LaTeX documents
+//
+//////////////////////////////////////////////////////////////////////////////////////////////////////////////
+
+use crate::spellbook_options::{FontVariant, UpcastOptions};
+use crate::rich_text::{blocks, description_with_upcast, styled_runs, Block};
+use crate::spells::{self, Spell};
+
+/// The title used for spellbooks that weren't given one.
+const DEFAULT_LATEX_TITLE: &str = "Spellbook";
+
+/// Settings for the LaTeX document made by `render_spellbook_latex()`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct LatexOptions
+{
+	/// The title of the spellbook.
+	pub title: String,
+	/// The LaTeX document class to use (ex: "book").
+	pub document_class: String,
+	/// Options for the document class (ex: "letterpaper,11pt"). Tables use `longtable`, which only works in one
+	/// column mode, so "twocolumn" shouldn't be used if any spells have tables.
+	pub class_options: String,
+	/// Whether or not to put a table of contents after the title page.
+	pub table_of_contents: bool,
+	/// Extra LaTeX that goes at the end of the preamble (ex: "\\usepackage{libertine}" to change the font).
+	pub preamble: String,
+	/// The text that goes before the upcast description of each spell.
+	pub upcast: UpcastOptions
+}
+
+impl Default for LatexOptions
+{
+	fn default() -> Self
+	{
+		Self
+		{
+			title: String::from(DEFAULT_LATEX_TITLE),
+			document_class: String::from("book"),
+			class_options: String::from("letterpaper,11pt"),
+			table_of_contents: true,
+			preamble: String::new(),
+			upcast: UpcastOptions::default()
+		}
+	}
+}
+
+/// Turns a list of spells into a LaTeX document so the typography can be fine-tuned with LaTeX.
+///
+/// Each spell goes in a `spell` environment (which takes the spell's name and its level and school) with its stats in
+/// `\spellstat` commands, so how spells look can be changed by redefining them in `preamble`. Tables in spell
+/// descriptions become `longtable` tables, bullet points become `itemize` lists, and font tags, url tags, and color
+/// tags become `\textbf`, `\textit`, `\href`, and `\textcolor` commands.
+///
+/// # Parameters
+///
+/// - `spells` The spells to put in the document (in the order they'll appear).
+/// - `options` Settings for the document.
+///
+/// # Output
+///
+/// The LaTeX source of the document.
+pub fn render_spellbook_latex(spells: &[Spell], options: &LatexOptions) -> String
+{
+	let title = if options.title.is_empty() { DEFAULT_LATEX_TITLE } else { &options.title };
+	let mut latex = String::new();
+	// Preamble
+	if options.class_options.is_empty() { latex.push_str(&format!("\\documentclass{{{}}}\n", options.document_class)); }
+	else
+	{
+		latex.push_str(&format!("\\documentclass[{}]{{{}}}\n", options.class_options, options.document_class));
+	}
+	latex.push_str("\\usepackage[utf8]{inputenc}\n");
+	latex.push_str("\\usepackage[T1]{fontenc}\n");
+	latex.push_str("\\usepackage{longtable}\n");
+	latex.push_str("\\usepackage[table]{xcolor}\n");
+	latex.push_str("\\usepackage{hyperref}\n");
+	latex.push_str("% Starts a spell with its name as a heading and its level and school under that\n");
+	latex.push_str("\\newenvironment{spell}[2]\n");
+	latex.push_str("{\\section*{#1}\\addcontentsline{toc}{section}{#1}\\noindent\\textit{#2}\\par\\smallskip}\n");
+	latex.push_str("{\\bigskip}\n");
+	latex.push_str("% A single stat of a spell (ex: the casting time)\n");
+	latex.push_str("\\newcommand{\\spellstat}[2]{\\noindent\\textbf{#1:} #2\\par}\n");
+	if !options.preamble.is_empty()
+	{
+		latex.push_str(&options.preamble);
+		latex.push('\n');
+	}
+	latex.push_str(&format!("\\title{{{}}}\n\\date{{}}\n\n", escape_latex(title)));
+	// Document
+	latex.push_str("\\begin{document}\n\n\\maketitle\n");
+	if options.table_of_contents { latex.push_str("\\tableofcontents\n"); }
+	for spell in spells
+	{
+		latex.push('\n');
+		latex.push_str(&spell_latex(spell, &options.upcast));
+	}
+	latex.push_str("\n\\end{document}\n");
+	latex
+}
+
+/// Turns a single spell into a `spell` environment.
+fn spell_latex(spell: &Spell, upcast: &UpcastOptions) -> String
+{
+	let mut latex = format!
+	(
+		"\\begin{{spell}}{{{}}}{{{}}}\n",
+		escape_latex(&spell.name),
+		inline_latex(&spell.get_level_school_text())
+	);
+	let stats =
+	[
+		("Casting Time", spell.get_casting_time_text()),
+		("Range", spell.range.to_string()),
+		("Components", spell.get_component_string()),
+		("Duration", spell.duration.to_string())
+	];
+	for (name, value) in stats { latex.push_str(&format!("\\spellstat{{{}}}{{{}}}\n", name, inline_latex(&value))); }
+	latex.push_str("\\medskip\n");
+	// The first paragraph isn't indented to match the official books
+	let mut first_paragraph = true;
+	for block in blocks(&description_with_upcast(spell, upcast), &spell.tables)
+	{
+		match block
+		{
+			Block::Paragraph(paragraph) =>
+			{
+				if first_paragraph { latex.push_str("\\noindent "); }
+				latex.push_str(&format!("{}\n\n", inline_latex(paragraph)));
+			},
+			Block::Bullets(bullets) =>
+			{
+				latex.push_str("\\begin{itemize}\n");
+				for bullet in bullets { latex.push_str(&format!("\t\\item {}\n", inline_latex(bullet))); }
+				latex.push_str("\\end{itemize}\n\n");
+			},
+			Block::Table(table) => latex.push_str(&table_latex(table))
+		}
+		first_paragraph = false;
+	}
+	latex.push_str("\\end{spell}\n");
+	latex
+}
+
+/// Turns a table into a `longtable` with every other row shaded like spellbook tables.
+fn table_latex(table: &spells::Table) -> String
+{
+	// Jagged tables get as many columns as their widest row
+	let columns = table.cells.iter().map(|row| row.len()).chain([table.column_labels.len()]).max().unwrap_or(0);
+	if columns == 0 { return String::new(); }
+	let mut latex = format!("\\begin{{longtable}}{{{}}}\n", "l".repeat(columns));
+	// Turns a row of cells into a row of the table
+	let row_latex = |cells: &Vec<String>, bold: bool|
+	{
+		let cells: Vec<_> = cells.iter()
+			.map(|cell| if bold { format!("\\textbf{{{}}}", inline_latex(cell)) } else { inline_latex(cell) })
+			.collect();
+		format!("{} \\\\\n", cells.join(" & "))
+	};
+	if !table.title.is_empty()
+	{
+		let title = inline_latex(&table.title);
+		latex.push_str(&format!("\\multicolumn{{{}}}{{l}}{{\\textbf{{{}}}}} \\\\\n", columns, title));
+	}
+	if !table.column_labels.is_empty() { latex.push_str(&row_latex(&table.column_labels, true)); }
+	latex.push_str("\\endhead\n");
+	for (row_index, row) in table.cells.iter().enumerate()
+	{
+		if row_index % 2 == 0 { latex.push_str("\\rowcolor{black!8}\n"); }
+		latex.push_str(&row_latex(row, false));
+	}
+	latex.push_str("\\end{longtable}\n\n");
+	latex
+}
+
+/// Turns a paragraph of text with font tags, url tags, and color tags into LaTeX.
+fn inline_latex(text: &str) -> String
+{
+	let runs: Vec<_> = styled_runs(text).iter().map(|(style, text)|
+	{
+		let mut latex = escape_latex(text);
+		latex = match style.font_variant
+		{
+			FontVariant::Regular => latex,
+			FontVariant::Bold => format!("\\textbf{{{}}}", latex),
+			FontVariant::Italic => format!("\\textit{{{}}}", latex),
+			FontVariant::BoldItalic => format!("\\textbf{{\\textit{{{}}}}}", latex)
+		};
+		if let Some((r, g, b)) = style.color
+		{
+			latex = format!("\\textcolor[HTML]{{{:02X}{:02X}{:02X}}}{{{}}}", r, g, b, latex);
+		}
+		if let Some(url) = &style.link { latex = format!("\\href{{{}}}{{{}}}", escape_url(url), latex); }
+		latex
+	}).collect();
+	runs.join(" ")
+}
+
+/// Replaces characters that have special meanings in LaTeX with commands that write them as text.
+fn escape_latex(text: &str) -> String
+{
+	let mut escaped = String::with_capacity(text.len());
+	for c in text.chars()
+	{
+		match c
+		{
+			'\\' => escaped.push_str("\\textbackslash{}"),
+			'{' | '}' | '#' | '$' | '%' | '&' | '_' =>
+			{
+				escaped.push('\\');
+				escaped.push(c);
+			},
+			'~' => escaped.push_str("\\textasciitilde{}"),
+			'^' => escaped.push_str("\\textasciicircum{}"),
+			_ => escaped.push(c)
+		}
+	}
+	escaped
+}
+
+/// Escapes the characters in a url that `\href` can't have as they are.
+fn escape_url(url: &str) -> String
+{
+	let mut escaped = String::with_capacity(url.len());
+	for c in url.chars()
+	{
+		if matches!(c, '#' | '%' | '\\' | '{' | '}') { escaped.push('\\'); }
+		escaped.push(c);
+	}
+	escaped
+}
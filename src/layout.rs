@@ -0,0 +1,1030 @@
+//////////////////////////////////////////////////////////////////////////////////////////////////////////////
+//
+//	Splitting text into lines that fit inside of a textbox (independent of pdf output)
+//
+//////////////////////////////////////////////////////////////////////////////////////////////////////////////
+
+use std::error::Error;
+use std::fmt;
+
+use rusttype::{Font, Scale};
+
+use crate::spellbook_options::{FontVariant, FontPaths, FontScalars, FONTVARIANT_VARIANTS};
+use crate::spellbook_gen_types::
+{
+	Mm,
+	Pt,
+	FontBytes,
+	FontSizeData,
+	FontKerning,
+	FontLigatures,
+	FontShapers,
+	BytesToFontSizeDataConversionError,
+	SyntheticStyle,
+	calc_text_width,
+	synthetic_bold_extra_width,
+	synthetic_font_sources,
+	text_glyphs,
+	SPACE
+};
+use crate::shaping::needs_shaping;
+use crate::glyph_fallbacks::FontGlyphFallbacks;
+use crate::spells;
+use crate::utils::load_font_bytes;
+use crate::hyphenation::Hyphenator;
+
+pub(crate) const REGULAR_FONT_TAG: &str = "<r>";
+pub(crate) const BOLD_FONT_TAG: &str = "<b>";
+const ITALIC_FONT_TAG: &str = "<i>";
+const BOLD_ITALIC_FONT_TAG: &str = "<bi>";
+const ITALIC_BOLD_FONT_TAG: &str = "<ib>";
+const URL_TAG: &str = "<url>";
+const URL_END_TAG: &str = "</url>";
+// Start of a url tag with the url inside of it (ex: "<url=https://example.com>")
+const URL_TAG_START: &str = "<url=";
+// Start of a color tag with the color inside of it (ex: "<color=#8b0000>")
+const COLOR_TAG_START: &str = "<color=#";
+const COLOR_END_TAG: &str = "</color>";
+const TAG_END: &str = ">";
+
+/// Measures how wide text is so it can be split into lines.
+///
+/// All widths just have to use the same units as the widths of the textboxes text gets split into
+/// (spellbooks use `printpdf::Mm`).
+pub trait TextMeasurer
+{
+	/// The width of some text in a font variant.
+	fn text_width(&self, text: &str, font_variant: FontVariant) -> f32;
+
+	/// The width of the space between two tokens when the first one is in a font variant.
+	fn space_width(&self, font_variant: FontVariant) -> f32;
+}
+
+/// Measures text using the same font files and font scalars as a spellbook so text can be split into the exact same
+/// lines that it would be in a spellbook. Widths are in `printpdf::Mm`.
+#[derive(Clone, Debug)]
+pub struct FontMeasurer
+{
+	fonts: FontSizeData<'static>,
+	font_size: f32,
+	scale: Scale,
+	scalars: FontScalars,
+	/// The font variant each variant is made from and the styles that have to be synthesized for it.
+	font_sources: [(FontVariant, SyntheticStyle); FONTVARIANT_VARIANTS],
+	/// The kerning of each font variant (`None` if text isn't measured with kerning).
+	kerning: Option<FontKerning>,
+	/// The standard ligatures of each font variant (`None` if text isn't measured with ligatures).
+	ligatures: Option<FontLigatures>,
+	/// The text shaper of each font variant (`None` if text isn't shaped).
+	shaping: Option<FontShapers>,
+	/// Plainer text that gets measured in place of characters each font variant doesn't have.
+	glyph_fallbacks: FontGlyphFallbacks
+}
+
+impl FontMeasurer
+{
+	/// Constructor
+	///
+	/// # Parameters
+	///
+	/// - `font_paths` File paths to all of the font variants (regular, bold, italic, bold-italic). Variants with an
+	///   empty path are measured the same way spellbooks synthesize them.
+	/// - `font_size` The size of the text being measured.
+	/// - `font_scalars` Scalar values to make sure text width can be calculated correctly for each font variant.
+	///
+	/// # Output
+	///
+	/// - `Ok` A FontMeasurer instance.
+	/// - `Err` Returns any errors that occured when reading the font files.
+	pub fn new(font_paths: &FontPaths, font_size: f32, font_scalars: FontScalars)
+	-> Result<Self, Box<dyn Error + Send + Sync>>
+	{
+		Self::with_font_features(font_paths, font_size, font_scalars, false, false, false)
+	}
+
+	/// Constructs a FontMeasurer that measures text with the kerning in the font files (the same as spellbooks made
+	/// with `AdditionalOptions::kerning` turned on). Takes the same parameters as `FontMeasurer::new()`.
+	pub fn with_kerning(font_paths: &FontPaths, font_size: f32, font_scalars: FontScalars)
+	-> Result<Self, Box<dyn Error + Send + Sync>>
+	{
+		Self::with_font_features(font_paths, font_size, font_scalars, true, false, false)
+	}
+
+	/// Constructs a FontMeasurer that can measure text with the kerning and standard ligatures in the font files (the
+	/// same as spellbooks made with `AdditionalOptions::kerning` and `AdditionalOptions::ligatures`), and that can
+	/// shape text in scripts that need it (the same as `AdditionalOptions::shaping`).
+	///
+	/// # Parameters
+	///
+	/// - `font_paths` File paths to all of the font variants (regular, bold, italic, bold-italic).
+	/// - `font_size` The size of the text being measured.
+	/// - `font_scalars` Scalar values to make sure text width can be calculated correctly for each font variant.
+	/// - `kerning` Whether or not to measure text with kerning.
+	/// - `ligatures` Whether or not to measure text with standard ligatures (like "fi" and "fl").
+	/// - `shaping` Whether or not to measure text in scripts like Arabic and Devanagari by the glyphs it gets shaped
+	///   into (needs the `shaping` feature).
+	///
+	/// # Output
+	///
+	/// - `Ok` A FontMeasurer instance.
+	/// - `Err` Returns any errors that occured when reading the font files.
+	pub fn with_font_features
+	(
+		font_paths: &FontPaths,
+		font_size: f32,
+		font_scalars: FontScalars,
+		kerning: bool,
+		ligatures: bool,
+		shaping: bool
+	)
+	-> Result<Self, Box<dyn Error + Send + Sync>>
+	{
+		let paths = [&font_paths.regular, &font_paths.bold, &font_paths.italic, &font_paths.bold_italic];
+		// Variants without their own font file are measured with the font file they get synthesized from
+		let font_sources = synthetic_font_sources(paths);
+		// Reads the font file a font variant uses (instancing it if it's a variable font)
+		let read_bytes = |font_variant: FontVariant|
+		{
+			let source = font_sources[font_variant as usize].0;
+			let axes = font_paths.variable_axes.as_ref().map(|axes| axes.axes_for(source));
+			load_font_bytes(paths[source as usize], axes)
+		};
+		let font_bytes = FontBytes
+		{
+			regular: read_bytes(FontVariant::Regular)?,
+			bold: read_bytes(FontVariant::Bold)?,
+			italic: read_bytes(FontVariant::Italic)?,
+			bold_italic: read_bytes(FontVariant::BoldItalic)?
+		};
+		// Turns the bytes of the font file a font variant uses into font size data
+		let to_font = |bytes: &Vec<u8>, font_variant: FontVariant|
+		-> Result<Font<'static>, Box<dyn Error + Send + Sync>>
+		{
+			let path = paths[font_sources[font_variant as usize].0 as usize];
+			match Font::try_from_vec(bytes.clone())
+			{
+				Some(font) => Ok(font),
+				None => Err(Box::new(BytesToFontSizeDataConversionError(format!
+					("Could not convert font size data from bytes in \"{}\".", path))))
+			}
+		};
+		let fonts = FontSizeData
+		{
+			regular: to_font(&font_bytes.regular, FontVariant::Regular)?,
+			bold: to_font(&font_bytes.bold, FontVariant::Bold)?,
+			italic: to_font(&font_bytes.italic, FontVariant::Italic)?,
+			bold_italic: to_font(&font_bytes.bold_italic, FontVariant::BoldItalic)?
+		};
+		Ok(Self
+		{
+			glyph_fallbacks: FontGlyphFallbacks::from_size_data(&fonts),
+			fonts: fonts,
+			font_size: font_size,
+			scale: Scale::uniform(font_size),
+			scalars: font_scalars,
+			font_sources: font_sources,
+			kerning: if kerning { Some(FontKerning::from_font_bytes(&font_bytes)) } else { None },
+			ligatures: if ligatures { Some(FontLigatures::from_font_bytes(&font_bytes)) } else { None },
+			shaping: if shaping { Some(FontShapers::from_font_bytes(&font_bytes)?) } else { None }
+		})
+	}
+}
+
+impl TextMeasurer for FontMeasurer
+{
+	fn text_width(&self, text: &str, font_variant: FontVariant) -> f32
+	{
+		let font = match font_variant
+		{
+			FontVariant::Regular => &self.fonts.regular,
+			FontVariant::Bold => &self.fonts.bold,
+			FontVariant::Italic => &self.fonts.italic,
+			FontVariant::BoldItalic => &self.fonts.bold_italic
+		};
+		// Synthesized variants use the scalar of the font file they're made from
+		let (source, synthetic_style) = self.font_sources[font_variant as usize];
+		let scalar = match source
+		{
+			FontVariant::Regular => self.scalars.regular_scalar(),
+			FontVariant::Bold => self.scalars.bold_scalar(),
+			FontVariant::Italic => self.scalars.italic_scalar(),
+			FontVariant::BoldItalic => self.scalars.bold_italic_scalar()
+		};
+		let text = &*self.glyph_fallbacks.get_for(font_variant).apply(text);
+		let kerning = self.kerning.as_ref().map(|kerning| kerning.get_for(font_variant));
+		let ligatures = self.ligatures.as_ref().map(|ligatures| ligatures.get_for(font_variant));
+		let shaper = self.shaping.as_ref().map(|shaping| shaping.get_for(font_variant));
+		let extra_width = if synthetic_style.bold
+		{
+			let glyph_count = match shaper
+			{
+				Some(shaper) if needs_shaping(text) => shaper.shape(text).len(),
+				_ => text_glyphs(text, font, ligatures).len()
+			};
+			synthetic_bold_extra_width(glyph_count, Pt(self.font_size))
+		}
+		else { Mm(0.0) };
+		(calc_text_width(text, font, &self.scale, scalar, kerning, ligatures, shaper) + extra_width).0
+	}
+
+	fn space_width(&self, font_variant: FontVariant) -> f32 { self.text_width(SPACE, font_variant) }
+}
+
+/// Returns the url inside of a url tag that has the url in it (ex: "https://example.com" from
+/// "<url=https://example.com>"). Returns `None` if the token isn't that kind of url tag.
+pub(crate) fn url_from_tag(token: &str) -> Option<&str>
+{
+	match token.strip_prefix(URL_TAG_START)
+	{
+		Some(rest) => match rest.strip_suffix(TAG_END)
+		{
+			Some(url) if !url.is_empty() => Some(url),
+			_ => None
+		},
+		None => None
+	}
+}
+
+/// Returns the RGB color inside of a color tag (ex: (139, 0, 0) from "<color=#8b0000>"). Returns `None` if the token
+/// isn't a color tag.
+pub(crate) fn color_from_tag(token: &str) -> Option<(u8, u8, u8)>
+{
+	let hex = token.strip_prefix(COLOR_TAG_START)?.strip_suffix(TAG_END)?;
+	if hex.len() != 6 || !hex.chars().all(|c| c.is_ascii_hexdigit()) { return None; }
+	let channel = |index: usize| u8::from_str_radix(&hex[index..index + 2], 16).ok();
+	Some((channel(0)?, channel(2)?, channel(4)?))
+}
+
+/// Splits text into lines of tokens that fit within a max width the same way spell descriptions are split in
+/// spellbooks. Tokens that are too wide to fit on a line get hyphenated.
+///
+/// Font tags (ex: "<b>") change the font variant that the following text gets measured with and url tags
+/// (ex: "<url=https://example.com>") turn the following text into a link. Color tags (ex: "<color=#8b0000>") change
+/// the color of the following text until a "</color>" tag. Escaped tags (ex: "\<b>") have their first backslash
+/// removed and are treated as text.
+///
+/// # Parameters
+///
+/// - `text` The text to split into lines.
+/// - `first_line_width` The max width of the first line (which may already have something at the start of it).
+/// - `textbox_width` The max width of every line after the first.
+/// - `font_variant` The font variant the text starts in.
+/// - `measurer` Measures the width of text.
+///
+/// # Output
+///
+/// Each line of text. Empty text returns no lines.
+pub fn wrap_text
+(
+	text: &str,
+	first_line_width: f32,
+	textbox_width: f32,
+	font_variant: FontVariant,
+	measurer: &dyn TextMeasurer
+)
+-> Vec<TextLine>
+{
+	wrap_text_with_keyword_links(text, first_line_width, textbox_width, font_variant, measurer, &|_| None)
+}
+
+/// A function that gets called with the tokens starting at a word and gives the number of tokens in a keyword that
+/// starts there and where it links to (see `wrap_text_with_keyword_links()`).
+pub type KeywordLink<'a> = dyn Fn(&[&str]) -> Option<(usize, LinkTarget)> + 'a;
+
+/// The same as `wrap_text()` except that runs of words can be turned into links (like how glossary terms in spell
+/// descriptions link to the glossary).
+///
+/// `keyword_link` gets called with the tokens starting at each word outside of a link. If it returns the number of
+/// tokens in a keyword that starts there and where it links to, those tokens get turned into a link.
+pub fn wrap_text_with_keyword_links
+(
+	text: &str,
+	first_line_width: f32,
+	textbox_width: f32,
+	font_variant: FontVariant,
+	measurer: &dyn TextMeasurer,
+	keyword_link: &KeywordLink<'_>
+)
+-> Vec<TextLine>
+{
+	wrap_text_with_features(text, first_line_width, textbox_width, font_variant, measurer, keyword_link, None)
+}
+
+/// The same as `wrap_text()` except that tokens that are too wide to fit on a line get hyphenated between their
+/// syllables when they can be (like words in spellbooks made with `AdditionalOptions::hyphenation`).
+pub fn wrap_text_with_hyphenation
+(
+	text: &str,
+	first_line_width: f32,
+	textbox_width: f32,
+	font_variant: FontVariant,
+	measurer: &dyn TextMeasurer,
+	hyphenator: &Hyphenator
+)
+-> Vec<TextLine>
+{
+	wrap_text_with_features(text, first_line_width, textbox_width, font_variant, measurer, &|_| None, Some(hyphenator))
+}
+
+/// The same as `wrap_text_with_keyword_links()` except that tokens that are too wide to fit on a line get hyphenated
+/// between their syllables when they can be if a hyphenator is given.
+pub(crate) fn wrap_text_with_features
+(
+	text: &str,
+	first_line_width: f32,
+	textbox_width: f32,
+	font_variant: FontVariant,
+	measurer: &dyn TextMeasurer,
+	keyword_link: &KeywordLink<'_>,
+	hyphenator: Option<&Hyphenator>
+)
+-> Vec<TextLine>
+{
+	let mut wrapper = LineWrapper
+	{
+		measurer: measurer,
+		font_variant: font_variant,
+		hyphenator: hyphenator,
+		word_breaks: Vec::new(),
+		word_offset: 0
+	};
+	wrapper.get_textbox_lines(text, first_line_width, textbox_width, keyword_link)
+}
+
+/// Keeps track of the font variant that text is being measured with while it gets split into lines.
+struct LineWrapper<'m>
+{
+	measurer: &'m dyn TextMeasurer,
+	font_variant: FontVariant,
+	// Finds the syllables of tokens that need to be hyphenated (`None` to hyphenate them wherever the line runs out)
+	hyphenator: Option<&'m Hyphenator>,
+	// Where the token being hyphenated can be split between syllables
+	word_breaks: Vec<usize>,
+	// How much of the start of the token being hyphenated has already been split off onto other lines
+	word_offset: usize
+}
+
+impl <'m> LineWrapper<'m>
+{
+	/// Takes a string along with a maximum width for lines to fit into, separates the string into lines of tokens
+	/// that fit within the max width, and returns a vec of those lines.
+	fn get_textbox_lines
+	(
+		&mut self,
+		text: &str,
+		first_line_width: f32,
+		textbox_width: f32,
+		keyword_link: &KeywordLink<'_>
+	)
+	-> Vec<TextLine>
+	{
+		// Get all tokens separated by whitespace
+		// Collects it into a vec so the `is_empty` method can be used without having to clone a new iterator.
+		let mut tokens: Vec<_> = text.split_whitespace().collect();
+		// If there is no text, do nothing
+		if tokens.is_empty() { return Vec::new(); }
+		// Keeps track of the current max textbox width
+		// Uses `first_line_width` for the first line and `textbox_width` for all lines after that
+		let mut current_line_max_width = first_line_width;
+		// Vec containing each line of text to write to the textbox
+		let mut lines: Vec<TextLine> = Vec::with_capacity(1);
+		// Keeps track of the next line of tokens to fill up and add to the vec of lines
+		let mut line = TextLine::with_capacity(tokens.len(), self.font_variant);
+		// Keeps track of whether or not the tokens being processed are inside of a link
+		let mut in_link = false;
+		// Keeps track of whether or not the tokens being processed are inside of a color tag
+		let mut in_color = false;
+		// Index of the last token in the keyword currently being linked (if there is one)
+		let mut keyword_link_end: Option<usize> = None;
+		// Loop through each token to measure how many lines there will be and how long each line is
+		for i in 0..tokens.len()
+		{
+			match tokens[i]
+			{
+				// If it's the end of a link, end the link (if there is one)
+				URL_END_TAG =>
+				{
+					if in_link { line.add_link_end(); }
+					in_link = false;
+				},
+				// If it's the start of a link with the url between it and the end tag, start a link using all of the
+				// text up to the end tag as the url
+				URL_TAG =>
+				{
+					// End the previous link if there is one
+					if in_link { line.add_link_end(); }
+					// Find the end of the link, or use the rest of the text if there isn't an end tag
+					let url_end = match tokens[i + 1..].iter().position(|token| *token == URL_END_TAG)
+					{
+						Some(index) => i + 1 + index,
+						None => tokens.len()
+					};
+					line.add_link_start(LinkTarget::Url(tokens[i + 1..url_end].concat()));
+					in_link = true;
+				},
+				// If it's a url tag with the url inside of it, start a link to that url
+				token if url_from_tag(token).is_some() =>
+				{
+					// End the previous link if there is one
+					if in_link { line.add_link_end(); }
+					line.add_link_start(LinkTarget::Url(String::from(url_from_tag(token).unwrap_or_default())));
+					in_link = true;
+				},
+				// If it's the end of a color tag, go back to the normal color (if the color was changed)
+				COLOR_END_TAG =>
+				{
+					if in_color { line.add_color_end(); }
+					in_color = false;
+				},
+				// If it's a color tag, change the color of the following text
+				// Colors don't nest, so this replaces any color that's already being used
+				token if color_from_tag(token).is_some() =>
+				{
+					line.add_color_start(color_from_tag(token).unwrap_or_default());
+					in_color = true;
+				},
+				// If It's a font tag, add the tag to the line and switch the current font variant so width can be
+				// calculated correctly for the following tokens
+				REGULAR_FONT_TAG =>
+				{
+					line.add_font_tag(FontVariant::Regular,);
+					self.font_variant = FontVariant::Regular;
+				},
+				BOLD_FONT_TAG =>
+				{
+					line.add_font_tag(FontVariant::Bold,);
+					self.font_variant = FontVariant::Bold;
+				},
+				ITALIC_FONT_TAG =>
+				{
+					line.add_font_tag(FontVariant::Italic);
+					self.font_variant = FontVariant::Italic;
+				},
+				BOLD_ITALIC_FONT_TAG | ITALIC_BOLD_FONT_TAG =>
+				{
+					line.add_font_tag(FontVariant::BoldItalic);
+					self.font_variant = FontVariant::BoldItalic;
+				},
+				// If it's not a special token, calculate its width and determine what to do from there
+				_ =>
+				{
+					// If a keyword starts at this token, link it to wherever it goes
+					if !in_link && keyword_link_end.is_none()
+					{
+						if let Some((word_count, target)) = keyword_link(&tokens[i..])
+						{
+							line.add_link_start(target);
+							keyword_link_end = Some(i + word_count - 1);
+						}
+					}
+					// If the token is an escaped font tag or link tag, remove the first backslash at the start
+					tokens[i] = spells::unescape_token(tokens[i], false);
+					// Declare a width variable that will be calculated when the tokens is hyphenated
+					#[allow(unused_assignments)]
+					let mut width = 0.0;
+					// Hyphenate the token if it's too long to fit on a line and compute its width
+					(tokens[i], width) = self.hyphenate_token
+					(
+						tokens[i],
+						&mut current_line_max_width,
+						textbox_width,
+						&mut line,
+						&mut lines
+					);
+					// If the line is currently empty
+					if line.width() == 0.0
+					{
+						// Put the token into the line
+						let text_token = TextToken::with_width(tokens[i], width);
+						line.add_text(text_token, self.measurer);
+					}
+					// If the line is not empty
+					else if line.width() > 0.0
+					{
+						// Calculate the width of the current token with a space in front of it
+						let padded_width = line.get_last_space_width(self.measurer) + width;
+						// If adding this token to the line would make it go outside the textbox,
+						// apply the current line and set it to just the current token
+						if line.width() + padded_width > current_line_max_width
+						{
+							// Make sure the line doesn't have any excess capacity in its vec
+							line.shrink_to_fit();
+							// Add the current line to the vec of lines
+							lines.push(line);
+							// Create a new line with the capacity of the number of remaining tokens
+							line = TextLine::with_capacity(tokens.len() - i, self.font_variant);
+							// Add the token to the start of the new line
+							let text_token = TextToken::with_width(tokens[i], width);
+							line.add_text(text_token, self.measurer);
+							// Set the max width width to the textbox width in case the previous line was the first
+							// line
+							current_line_max_width = textbox_width;
+						}
+						// If this token can fit on the line, add it to the line
+						else
+						{
+							// Add this token to the line
+							let text_token = TextToken::with_width(tokens[i], width);
+							line.add_text(text_token, self.measurer);
+						}
+					}
+					// If the line has a negative width
+					else
+					{
+						panic!("Line width is less than 0.0 in `dnd_spellbook_maker::layout::LineWrapper::get_textbox_lines`");
+					}
+				}
+			}
+			// End the keyword link if this was the last token in the keyword
+			if keyword_link_end == Some(i)
+			{
+				line.add_link_end();
+				keyword_link_end = None;
+			}
+		}
+		// End the link if the text ended without closing it
+		if in_link || keyword_link_end.is_some() { line.add_link_end(); }
+		// Go back to the normal color if the text ended without closing the color tag
+		if in_color { line.add_color_end(); }
+		// Make sure the line doesn't have any excess capacity in its vec
+		line.shrink_to_fit();
+		// Push the remaining text in the last line to the vec of lines
+		lines.push(line);
+		// Return the lines of text
+		lines
+	}
+
+	/// If the given token is too wide to fit on a single line within the given textbox constraints, hyphenate it and
+	/// apply it to the spellbook until the end of it is reached and it can fit in a single line without being
+	/// hyphenated.
+	/// Takes the current max width (which might be shorter on the first line of a textbox) and sets it to the
+	/// textbox width if the token is hyphenated and a line is applied.
+	/// Takes the current line and the vec of lines being processed to modify them if the token is hyphenated.
+	/// Returns the token and its calculated width if it was short enough to fit on a line, otherwise it returns the
+	/// end of the hyphenated token and its width.
+	fn hyphenate_token<'t>
+	(
+		&mut self,
+		mut token: &'t str,
+		current_line_max_width: &mut f32,
+		textbox_width: f32,
+		current_line: &mut TextLine,
+		lines: &mut Vec<TextLine>
+	)
+	-> (&'t str, f32)
+	{
+		// Calculate the width of the token
+		let mut width = self.text_width(token);
+		// Find where the token can be split between syllables if it's going to be hyphenated (and there's a
+		// hyphenator)
+		self.word_breaks = match self.hyphenator
+		{
+			Some(hyphenator) if width > textbox_width.min(*current_line_max_width) => hyphenator.break_points(token),
+			_ => Vec::new()
+		};
+		self.word_offset = 0;
+		// If the line is empty and the token is wider than the current line
+		if current_line.width() == 0.0 && width > *current_line_max_width
+		{
+			// Hyphenate the token using the current line's width
+			(token, width) = self.hyphenate_once
+			(
+				token,
+				width,
+				*current_line_max_width,
+				false,
+				current_line,
+				lines
+			);
+		}
+		// If the token is wider than the textbox width
+		else if width > textbox_width
+		{
+			// Hyphenate the token using the remaining width on the current line
+			let remaining_width =
+			*current_line_max_width - current_line.width() - current_line.get_last_space_width(self.measurer);
+			(token, width) = self.hyphenate_once
+			(
+				token,
+				width,
+				remaining_width,
+				false,
+				current_line,
+				lines
+			);
+		}
+		// If the token fits on the current line and doesn't need to be hyphenated, just return it and its width
+		else { return (token, width); }
+		// Reset the current line width to the width of the textbox since a line just had to have been applied to get
+		// to this point and it is no longer the first line (which is the only line that could have a different
+		// width than the rest)
+		*current_line_max_width = textbox_width;
+		// Hyphenate the token until just the end of it remains and it can fit on a single line
+		while width > textbox_width
+		{
+			let token_length = token.len();
+			// Always split off at least one character since every line from here on is as wide as they get
+			(token, width) = self.hyphenate_once(token, width, textbox_width, true, current_line, lines);
+			// Stop if the token couldn't get any shorter (a single character that's wider than the textbox)
+			if token.len() == token_length { break; }
+		}
+		// Return the end of the token and its width
+		(token, width)
+	}
+
+	/// Hyphenates it a single time, applies and resets the current line, and returns the rest of the hyphenated
+	/// token along with its width if the token is too big to fit on a line. Otherwise it just returns the token the
+	/// way it is along with its width.
+	/// If `force_split` is true, the first character gets split off even if it doesn't fit with a hyphen after it.
+	fn hyphenate_once<'t>
+	(
+		&mut self,
+		mut token: &'t str,
+		mut width: f32,
+		textbox_width: f32,
+		force_split: bool,
+		current_line: &mut TextLine,
+		lines: &mut Vec<TextLine>
+	)
+	-> (&'t str, f32)
+	{
+		// If its small enough to fit on the line, return it the way it is along with its width
+		if width <= textbox_width { return (token, width); }
+		// Hyphenates the string and gets the hyphenated part as a `TextToken` and an index for where the rest of it
+		// starts in the string
+		let (hyphenated_token, index) = self.get_hyphen_str(token, width, textbox_width, force_split);
+		// If the token can't be split at all (a single character), leave it the way it is
+		if index == 0 && force_split { return (token, width); }
+		// If the token could be hyphenated to fit on the line (if the returned index is 0, that means the token was
+		// either too close to the end of the line to be hyphenated or has characters that are too wide to fit in the
+		// textbox)
+		if index > 0
+		{
+			// If the token was hyphenated
+			if index < token.len()
+			{
+				// Add the hyphenated part of the token to the current line
+				current_line.add_text(hyphenated_token, self.measurer);
+				// Make sure there isn't any extra capacity in the line's token vec
+				current_line.shrink_to_fit();
+			}
+			// If the returned index was the length of the token (which means it could fit on the line without being
+			// hyphenated)
+			else
+			{
+				// Return the token the way it is along with its width
+				return (token, width);
+			}
+		}
+		// Apply the line
+		lines.push(current_line.clone());
+		// Empty the line
+		*current_line = TextLine::with_capacity(1, self.font_variant);
+		// Chop off the hyphenated part from the token
+		token = &token[index..];
+		self.word_offset += index;
+		// Recalculate the width of the token
+		width = self.text_width(token);
+		// Return the token and its width
+		(token, width)
+	}
+
+	/// Takes a string that is too wide to fit on a single line in a textbox and finds the cutoff / delimiter index
+	/// so that `&text[0..index] + '-'` fits inside the textbox, along with that hyphenated string itself.
+	/// The index is always at the start of a character. If not even one character fits, the index is 0 unless
+	/// `force_first_char` is true, in which case the first character is used anyway (if there's more than one).
+	fn get_hyphen_str(&self, text: &str, token_width: f32, textbox_width: f32, force_first_char: bool)
+	-> (TextToken, usize)
+	{
+		// If the string can fit in the textbox, return an empty text token and the inputted token's length
+		if token_width <= textbox_width { return (TextToken::empty(), text.len()); }
+		// Every index the text can be split at (the start of each character after the first)
+		// Byte indexes in the middle of a multi-byte character can't be used to slice the text
+		let split_indexes: Vec<usize> = text.char_indices().map(|(index, _)| index).skip(1).collect();
+		// If the text is a single character, it can't be hyphenated
+		if split_indexes.is_empty() { return (TextToken::empty(), 0); }
+		// Split the text at the last syllable boundary that fits (if the token can be split between syllables)
+		let syllable_indexes = self.word_breaks.iter()
+			.filter_map(|index| index.checked_sub(self.word_offset))
+			.filter(|index| *index > 0 && *index < text.len());
+		for index in syllable_indexes.rev()
+		{
+			// Words that already have a hyphen where they get split don't need another one
+			let start = &text[0..index];
+			let hyphenated_string = if start.ends_with('-') { start.to_string() } else { format!("{}-", start) };
+			let hyphen_str_width = self.text_width(&hyphenated_string);
+			if hyphen_str_width <= textbox_width
+			{
+				return (TextToken::with_width(&hyphenated_string, hyphen_str_width), index);
+			}
+		}
+		// Keeps track of the longest hyphenated part of the text that was measured and fits in the textbox
+		let mut hyphenated_token = TextToken::empty();
+		// Lower and upper possible bounds for how many characters go before the hyphen
+		// Everything up to the lower bound is known to fit
+		let mut lower_bound = 0;
+		let mut upper_bound = split_indexes.len();
+		// Binary search for the split index where the text plus a hyphen at the end is as long as possible without
+		// going outside the textbox
+		while lower_bound < upper_bound
+		{
+			// The number of characters being tested (rounded up so the loop always makes progress)
+			let char_count = (lower_bound + upper_bound).div_ceil(2);
+			// Get a string of the start of the text up to the split index with a hyphen at the end
+			let hyphenated_string = format!("{}-", &text[0..split_indexes[char_count - 1]]);
+			// Calculate the width of the hyphenated string
+			let hyphen_str_width = self.text_width(&hyphenated_string);
+			// If it fits in the textbox, it's the longest hyphenated string so far
+			if hyphen_str_width <= textbox_width
+			{
+				lower_bound = char_count;
+				hyphenated_token = TextToken::with_width(&hyphenated_string, hyphen_str_width);
+			}
+			// Otherwise the hyphenated string has to be shorter than this
+			else { upper_bound = char_count - 1; }
+		}
+		// If nothing fit
+		if lower_bound == 0
+		{
+			// Use the first character anyway if that's desired
+			if force_first_char
+			{
+				let hyphenated_string = format!("{}-", &text[0..split_indexes[0]]);
+				let hyphen_str_width = self.text_width(&hyphenated_string);
+				return (TextToken::with_width(&hyphenated_string, hyphen_str_width), split_indexes[0]);
+			}
+			// Otherwise return an empty token
+			return (TextToken::empty(), 0);
+		}
+		// Return the token and index
+		(hyphenated_token, split_indexes[lower_bound - 1])
+	}
+
+	/// Calculates the width of some text in the current font variant.
+	fn text_width(&self, text: &str) -> f32 { self.measurer.text_width(text, self.font_variant) }
+}
+
+/// Holds a single token in a line of text.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Token
+{
+	/// A symbol that changes the font variant that the following text uses.
+	// Ex: Regular: "<r>", Bold: "<b>", Italic: "<i>", Bold-Italic: "<bi>" or "<ib>".
+	FontTag(FontVariant),
+	/// Tokens that are treated like text and are applied to the page.
+	Text(TextToken),
+	/// A symbol that makes the following text a clickable link.
+	// Ex: "<url=https://example.com>" or "<url>" with the url between it and "</url>".
+	// Also used for glossary terms in spell descriptions that link to the glossary.
+	LinkStart(LinkTarget),
+	/// A symbol that ends the link started by the last `LinkStart` token.
+	// Ex: "</url>"
+	LinkEnd,
+	/// A symbol that changes the RGB color of the following text.
+	// Ex: "<color=#8b0000>"
+	ColorStart((u8, u8, u8)),
+	/// A symbol that changes the color of the following text back to the normal color for its type of text.
+	// Ex: "</color>"
+	ColorEnd
+}
+
+impl Token
+{
+	/// Gets a string of this token as it will appear when written.
+	/// Font tags will return an empty string, text tokens will return the string they are holding.
+	pub fn as_spellbook_string(&self) -> &str
+	{
+		static EMPTY_STR: &str = "";
+		match self
+		{
+			Self::FontTag(_) => EMPTY_STR,
+			Self::Text(token) => &token.text(),
+			Self::LinkStart(_) => EMPTY_STR,
+			Self::LinkEnd => EMPTY_STR,
+			Self::ColorStart(_) => EMPTY_STR,
+			Self::ColorEnd => EMPTY_STR
+		}
+	}
+}
+
+impl fmt::Display for Token
+{
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
+	{
+		match self
+		{
+			Self::FontTag(tag) => tag.fmt(f),
+			Self::Text(token) => token.fmt(f),
+			Self::LinkStart(target) => write!(f, "Link ({})", target),
+			Self::LinkEnd => write!(f, "Link End"),
+			Self::ColorStart((r, g, b)) => write!(f, "Color (#{:02x}{:02x}{:02x})", r, g, b),
+			Self::ColorEnd => write!(f, "Color End")
+		}
+	}
+}
+
+/// Holds a single token of text along with the width of the text in the font it will be written with.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TextToken
+{
+	/// The actual text.
+	text: String,
+	/// The width of the token in the units of the `TextMeasurer` it was measured with (`printpdf::Mm` in spellbooks).
+	width: f32
+}
+
+impl TextToken
+{
+	// /// Creates a new text token from a string and font data. Calculates width automatically.
+	// pub fn new(text: &str, font_size_data: &Font, font_scale: &Scale, font_scalar: f32) -> Self
+	// {
+	// 	let width = calc_text_width(text, font_size_data, font_scale, font_scalar);
+	// 	Self
+	// 	{
+	// 		text: String::from(text),
+	// 		width: width
+	// 	}
+	// }
+
+	/// Creates a new text token from a string and a precalculated width of that string. Does not check to make sure
+	/// the given width is correct.
+	pub fn with_width(text: &str, width: f32) -> Self
+	{
+		Self
+		{
+			text: String::from(text),
+			width: width
+		}
+	}
+
+	/// Creates a new empty text token with no text and a width of 0.0.
+	pub fn empty() -> Self
+	{
+		Self
+		{
+			text: String::new(),
+			width: 0.0
+		}
+	}
+
+	// Getters
+
+	/// Returns the text this object is holding.
+	pub fn text(&self) -> &str {&self.text.as_str() }
+	// /// Returns the width of the text his object is holding.
+	// pub fn width(&self) -> f32 { self.width }
+}
+
+impl fmt::Display for TextToken
+{
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
+	{
+		write!(f, "{}", self.text)
+	}
+}
+
+/// Holds a line of tokens that fits in a textbox along with the width of the entire line.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TextLine
+{
+	/// The line of tokens in the order they get written.
+	tokens: Vec<Token>,
+	/// The width of the entire line in the units of the `TextMeasurer` it was measured with.
+	width: f32,
+	/// Holds the current font variant of the line (used for calculating space widths)
+	current_font_variant: FontVariant,
+	/// Holds the font variant of the previous token in the line (used for calculating space widths)
+	previous_font_variant: FontVariant
+}
+
+impl std::ops::Index<usize> for TextLine
+{
+	type Output = Token;
+	/// Allows `TextLine`s to be indexed using the index operator `[]` to retrieve individual tokens.
+	fn index(&self, index: usize) -> &Self::Output { &self.tokens[index] }
+}
+
+impl TextLine
+{
+	// /// Creates a new empty text line.
+	// pub fn new(current_font_variant: FontVariant) -> Self
+	// {
+	// 	Self::with_capacity(0, current_font_variant)
+	// }
+
+	/// Creates a new text line with a given capacity for its vec of tokens.
+	pub fn with_capacity(size: usize, current_font_variant: FontVariant) -> Self
+	{
+		Self
+		{
+			tokens: Vec::with_capacity(size),
+			width: 0.0,
+			current_font_variant: current_font_variant,
+			previous_font_variant: current_font_variant
+		}
+	}
+
+	// Setters
+
+	// /// Adds a token to the line.
+	// pub fn add_token(&mut self, token: Token, measurer: &dyn TextMeasurer)
+	// {
+	// 	match token
+	// 	{
+	// 		Token::Text(text) => self.add_text(text, measurer),
+	// 		Token::FontTag(tag) => self.add_font_tag(tag)
+	// 	}
+	// }
+
+	/// Adds a font tag to the line.
+	pub fn add_font_tag(&mut self, tag: FontVariant)
+	{
+		if self.tokens.len() > 0
+		{
+			let last_index = self.tokens.len() - 1;
+			match self.tokens[last_index]
+			{
+				Token::FontTag(_) => self.tokens[last_index] = Token::FontTag(tag),
+				Token::Text(_) =>
+				{
+					self.previous_font_variant = self.current_font_variant;
+					self.tokens.push(Token::FontTag(tag));
+				},
+				_ => self.tokens.push(Token::FontTag(tag))
+			}
+		}
+		else { self.tokens.push(Token::FontTag(tag)); }
+		self.current_font_variant = tag;
+	}
+
+	/// Adds the start of a link to the line. Does not change the width of the line.
+	pub fn add_link_start(&mut self, target: LinkTarget) { self.tokens.push(Token::LinkStart(target)); }
+
+	/// Adds the end of a link to the line. Does not change the width of the line.
+	pub fn add_link_end(&mut self) { self.tokens.push(Token::LinkEnd); }
+
+	/// Adds a change to the color of the following text to the line. Does not change the width of the line.
+	pub fn add_color_start(&mut self, color: (u8, u8, u8)) { self.tokens.push(Token::ColorStart(color)); }
+
+	/// Adds a change back to the normal text color to the line. Does not change the width of the line.
+	pub fn add_color_end(&mut self) { self.tokens.push(Token::ColorEnd); }
+
+	/// Adds text to the line.
+	pub fn add_text(&mut self, text: TextToken, measurer: &dyn TextMeasurer)
+	{
+		if self.width > 0.0
+		{
+			self.width += measurer.space_width(self.previous_font_variant);
+		}
+		self.previous_font_variant = self.current_font_variant;
+		// Adds the width of the token to the line's width before adding the token itself to the line.
+		self.width += text.width;
+		self.tokens.push(Token::Text(text));
+	}
+
+	// /// Adds extra width to the line (usually used for adding the width of a space character to the line).
+	// pub fn add_width(&mut self, width: f32) { self.width += width; }
+	/// Shrinks the capacity of the vec of tokens to fit its size.
+	pub fn shrink_to_fit(&mut self) { self.tokens.shrink_to_fit(); }
+
+	// Getters
+
+	/// Returns the vec of all the tokens in the line.
+	pub fn tokens(&self) -> &Vec<Token> { &self.tokens }
+	/// Returns the width of the line.
+	pub fn width(&self) -> f32 { self.width }
+	// /// Returns the number of tokens in the line
+	// pub fn len(&self) -> usize { self.tokens.len() }
+	/// Returns whether or not the vec of tokens in this line is empty.
+	pub fn is_empty(&self) -> bool { self.tokens.is_empty() }
+	// /// Returns whether or not the vec of tokens in this line is not empty.
+	// pub fn not_empty(&self) -> bool { self.tokens.len() > 0 }
+
+	/// Returns the space width using the font data of the previous token
+	pub fn get_last_space_width(&self, measurer: &dyn TextMeasurer) -> f32
+	{
+		measurer.space_width(self.previous_font_variant)
+	}
+}
+
+/// Where a link in text goes.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum LinkTarget
+{
+	/// A website.
+	Url(String),
+	/// A named spot in the spellbook (like a glossary entry) that might not have been written yet.
+	Anchor(String)
+}
+
+impl fmt::Display for LinkTarget
+{
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
+	{
+		match self
+		{
+			Self::Url(url) => write!(f, "{}", url),
+			Self::Anchor(anchor) => write!(f, "#{}", anchor)
+		}
+	}
+}
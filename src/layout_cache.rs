@@ -0,0 +1,166 @@
+//////////////////////////////////////////////////////////////////////////////////////////////////////////////
+//
+//	Measurements of spells that can be reused between spellbooks
+//
+//////////////////////////////////////////////////////////////////////////////////////////////////////////////
+
+use std::collections::HashMap;
+use std::collections::hash_map::DefaultHasher;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use crate::spells::Spell;
+use crate::spellbook_options::AdditionalOptions;
+use crate::layout::TextLine;
+
+/// What's known about the layout of a spell from previous spellbooks.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct SpellLayout
+{
+	/// The estimated distance (in printpdf Mm) between the first line of the spell's name and the last line of its
+	/// description (`None` if it hasn't been measured yet).
+	pub height: Option<f32>,
+	/// The number of pages the spell took up the last time it was added to a spellbook (`None` if it hasn't been
+	/// added to one yet).
+	pub page_count: Option<usize>
+}
+
+/// Remembers measurements of spells and the lines that text got split into between spellbooks so that making a
+/// spellbook again after changing a few spells doesn't have to lay out every spell again. Each measurement is stored
+/// under a hash of the spell and every option that affects its layout, and each set of lines is stored under a hash
+/// of its text, the width it was split to fit in, and those options, so changed spells or options just get laid out
+/// again.
+///
+/// Clones of a layout cache share the same measurements, so a clone can be put in `AdditionalOptions` for each
+/// spellbook while keeping the original. Measurements are used for compact density spacing and for the pages of
+/// spells that are left out of previews (which use the real page counts of spells from previous spellbooks instead
+/// of estimates). Lines of text that are already in the cache get written without being split into lines again.
+/// Changes to font files aren't noticed unless their file paths change too.
+#[derive(Clone, Default)]
+pub struct LayoutCache
+{
+	layouts: Arc<Mutex<HashMap<u64, SpellLayout>>>,
+	lines: Arc<Mutex<HashMap<u64, Vec<TextLine>>>>,
+	// The number of times something was found in the cache
+	hits: Arc<AtomicUsize>
+}
+
+impl LayoutCache
+{
+	/// Constructs an empty layout cache.
+	pub fn new() -> Self { Self::default() }
+
+	/// The number of spell layouts in the cache.
+	pub fn len(&self) -> usize { self.layouts.lock().unwrap().len() }
+
+	/// Whether or not there aren't any spell layouts in the cache.
+	pub fn is_empty(&self) -> bool { self.len() == 0 }
+
+	/// The number of times a spell's measurements or the lines of some text were reused from the cache.
+	pub fn hits(&self) -> usize { self.hits.load(Ordering::Relaxed) }
+
+	/// Forgets every spell layout and line of text in the cache.
+	pub fn clear(&self)
+	{
+		self.layouts.lock().unwrap().clear();
+		self.lines.lock().unwrap().clear();
+	}
+
+	/// Gets what's known about the layout of a spell with the options that have the given key.
+	pub(crate) fn get(&self, key: u64) -> SpellLayout
+	{
+		let layout = self.layouts.lock().unwrap().get(&key).copied();
+		if layout.is_some() { self.hits.fetch_add(1, Ordering::Relaxed); }
+		layout.unwrap_or_default()
+	}
+
+	/// Changes what's known about the layout of a spell with the options that have the given key.
+	pub(crate) fn update(&self, key: u64, update: impl FnOnce(&mut SpellLayout))
+	{
+		update(self.layouts.lock().unwrap().entry(key).or_default());
+	}
+
+	/// Gets the lines that some text was split into with the given key (`None` if it hasn't been split yet).
+	pub(crate) fn get_lines(&self, key: u64) -> Option<Vec<TextLine>>
+	{
+		let lines = self.lines.lock().unwrap().get(&key).cloned();
+		if lines.is_some() { self.hits.fetch_add(1, Ordering::Relaxed); }
+		lines
+	}
+
+	/// Remembers the lines that some text was split into under the given key.
+	pub(crate) fn insert_lines(&self, key: u64, lines: Vec<TextLine>) { self.lines.lock().unwrap().insert(key, lines); }
+}
+
+impl fmt::Debug for LayoutCache
+{
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
+	{
+		write!(f, "LayoutCache ({} spell layouts)", self.len())
+	}
+}
+
+// Caches are only equal if they share the same measurements
+impl PartialEq for LayoutCache
+{
+	fn eq(&self, other: &Self) -> bool { Arc::ptr_eq(&self.layouts, &other.layouts) }
+}
+
+/// Returns a copy of some additional options without the options that can't change how spells get laid out (like
+/// caches, checks that only give warnings, and colors), so changing them doesn't make spells get laid out again and
+/// previews can use the layouts of spells from full spellbooks. Every other option gets hashed.
+pub(crate) fn layout_options(options: &AdditionalOptions) -> AdditionalOptions
+{
+	AdditionalOptions
+	{
+		school_colors: None,
+		bookmark_names: Default::default(),
+		page_hooks: Default::default(),
+		debug_layout: false,
+		limits: None,
+		spell_check: None,
+		contrast_check: None,
+		preview: None,
+		layout_cache: None,
+		image_cache: None,
+		fingerprint: None,
+		encryption: None,
+		search_index: None,
+		named_destinations: false,
+		..options.clone()
+	}
+}
+
+/// Returns a hash of everything that affects how spells get laid out (from the debug text of each option).
+pub(crate) fn options_hash(options: &[&dyn fmt::Debug]) -> u64
+{
+	let mut hasher = DefaultHasher::new();
+	for option in options { format!("{:?}", option).hash(&mut hasher); }
+	hasher.finish()
+}
+
+/// Returns the key that a spell's layout is stored under for the options with the given hash.
+pub(crate) fn spell_layout_key(spell: &Spell, options_hash: u64) -> u64
+{
+	let mut hasher = DefaultHasher::new();
+	options_hash.hash(&mut hasher);
+	format!("{:?}", spell).hash(&mut hasher);
+	hasher.finish()
+}
+
+/// Returns the key that the lines some text gets split into are stored under for the options with the given hash.
+/// Also takes the widths the lines have to fit in and everything about the writer's current state that changes how
+/// the text gets split (the type of text, the font variant it starts in, and whether it's part of a spell
+/// description).
+pub(crate) fn text_lines_key(options_hash: u64, text: &str, widths: (f32, f32), state: &dyn fmt::Debug) -> u64
+{
+	let mut hasher = DefaultHasher::new();
+	options_hash.hash(&mut hasher);
+	text.hash(&mut hasher);
+	widths.0.to_bits().hash(&mut hasher);
+	widths.1.to_bits().hash(&mut hasher);
+	format!("{:?}", state).hash(&mut hasher);
+	hasher.finish()
+}
@@ -8,6 +8,32 @@ pub mod spells;
 mod spellbook_options;
 mod spellbook_gen_types;
 mod spellbook_writer;
+mod spellbook_output;
+mod spellbook_hooks;
+mod spell_database;
+mod doc_stats;
+mod layout_cache;
+mod image_cache;
+mod fingerprint;
+mod pdf_encryption;
+mod pdf_merging;
+mod search_index;
+mod qr_codes;
+mod hyphenation;
+mod rich_text;
+mod kerning;
+mod ligatures;
+mod shaping;
+mod glyph_fallbacks;
+#[cfg(feature = "variable-fonts")]
+mod font_instancing;
+pub mod layout;
+pub mod vtt_export;
+pub mod latex_export;
+#[cfg(feature = "epub")]
+pub mod epub_export;
+#[cfg(any(test, feature = "test-utils"))]
+pub mod golden;
 #[cfg(test)]
 mod tests;
 
@@ -0,0 +1,117 @@
+//////////////////////////////////////////////////////////////////////////////////////////////////////////////
+//
+//	Ligature data read from font files so text can be measured and written with ligatures
+//
+//////////////////////////////////////////////////////////////////////////////////////////////////////////////
+
+use std::collections::HashMap;
+
+use ttf_parser::{Face, GlyphId, Tag};
+use ttf_parser::gsub::SubstitutionSubtable;
+
+// The ligatures of a lookup, mapping the first glyph of each ligature to the rest of the glyphs that make it up and the
+// glyph that replaces them
+type LigatureLookup = HashMap<u16, Vec<(Vec<u16>, u16)>>;
+
+/// The standard ligatures of a font (like "fi" and "fl"), by glyph id.
+///
+/// Ligatures come from the ligature substitution lookups of the font's `liga` feature (in its GSUB table).
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct LigatureTable
+{
+	/// The ligatures of each lookup in the order the lookups get applied. Each lookup maps the first glyph of a
+	/// ligature to the rest of the glyphs that make it up and the glyph that replaces them (in the order they get
+	/// checked).
+	lookups: Vec<LigatureLookup>
+}
+
+impl LigatureTable
+{
+	/// Reads the standard ligatures from the bytes of a font file.
+	/// Gives an empty table if the font can't be parsed or doesn't have any standard ligatures.
+	pub fn from_font_bytes(font_bytes: &[u8]) -> Self
+	{
+		let face = match Face::parse(font_bytes, 0)
+		{
+			Ok(face) => face,
+			Err(_) => return Self::default()
+		};
+		let gsub = match face.tables().gsub
+		{
+			Some(gsub) => gsub,
+			None => return Self::default()
+		};
+		// Get every lookup the liga feature uses in any script and language (in the order they get applied)
+		let liga_tag = Tag::from_bytes(b"liga");
+		let mut lookup_indices: Vec<u16> = gsub.features.into_iter()
+			.filter(|feature| feature.tag == liga_tag)
+			.flat_map(|feature| feature.lookup_indices.into_iter())
+			.collect();
+		lookup_indices.sort_unstable();
+		lookup_indices.dedup();
+		let glyph_count = face.number_of_glyphs();
+		let mut lookups = Vec::new();
+		for lookup_index in lookup_indices
+		{
+			let lookup = match gsub.lookups.get(lookup_index)
+			{
+				Some(lookup) => lookup,
+				None => continue
+			};
+			let mut ligatures: LigatureLookup = HashMap::new();
+			for subtable in lookup.subtables.into_iter::<SubstitutionSubtable>()
+			{
+				let substitution = match subtable
+				{
+					SubstitutionSubtable::Ligature(substitution) => substitution,
+					_ => continue
+				};
+				for first in 0..glyph_count
+				{
+					let set = match substitution.coverage.get(GlyphId(first))
+						.and_then(|index| substitution.ligature_sets.get(index))
+					{
+						Some(set) => set,
+						None => continue
+					};
+					let entry = ligatures.entry(first).or_default();
+					for ligature in set
+					{
+						let components = ligature.components.into_iter().map(|glyph| glyph.0).collect();
+						entry.push((components, ligature.glyph.0));
+					}
+				}
+			}
+			if !ligatures.is_empty() { lookups.push(ligatures); }
+		}
+		Self { lookups: lookups }
+	}
+
+	/// Replaces the glyphs that make up ligatures with the glyphs for those ligatures in a list of glyph ids and how
+	/// many characters each glyph stands for (the number of characters of each ligature glyph is the total of the
+	/// glyphs it replaced).
+	pub fn apply(&self, glyphs: &mut Vec<(u16, usize)>)
+	{
+		for ligatures in &self.lookups
+		{
+			let mut index = 0;
+			while index < glyphs.len()
+			{
+				let rest = &glyphs[index + 1..];
+				// Finds the first ligature that starts with this glyph and has the rest of its glyphs next
+				let ligature = ligatures.get(&glyphs[index].0).and_then(|candidates| candidates.iter().find
+				(
+					|(components, _)| rest.len() >= components.len() &&
+						rest.iter().zip(components).all(|((glyph_id, _), component)| glyph_id == component)
+				));
+				if let Some((components, ligature_glyph)) = ligature
+				{
+					let end = index + components.len() + 1;
+					let char_count = glyphs[index..end].iter().map(|(_, char_count)| char_count).sum();
+					glyphs.splice(index..end, [(*ligature_glyph, char_count)]);
+				}
+				index += 1;
+			}
+		}
+	}
+}
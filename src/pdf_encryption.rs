@@ -0,0 +1,179 @@
+//////////////////////////////////////////////////////////////////////////////////////////////////////////////
+//
+//	Password protection for saved spellbooks
+//
+//////////////////////////////////////////////////////////////////////////////////////////////////////////////
+
+use std::error::Error;
+
+use printpdf::lopdf::{self, Object, ObjectId, Dictionary, StringFormat};
+
+use crate::spellbook_options::EncryptionOptions;
+
+/// The bytes that passwords get padded to 32 bytes with (from the PDF specification).
+const PASSWORD_PADDING: [u8; 32] =
+[
+	0x28, 0xbf, 0x4e, 0x5e, 0x4e, 0x75, 0x8a, 0x41, 0x64, 0x00, 0x4e, 0x56, 0xff, 0xfa, 0x01, 0x08,
+	0x2e, 0x2e, 0x00, 0xb6, 0xd0, 0x68, 0x3e, 0x80, 0x2f, 0x0c, 0xa9, 0xfe, 0x64, 0x53, 0x69, 0x7a
+];
+/// The length of the encryption key in bytes (128 bits).
+const KEY_LENGTH: usize = 16;
+
+// Bits of the permissions value for each thing that can be allowed (bit 1 is the lowest bit)
+const PERMISSION_PRINT: u32 = 1 << 2;
+const PERMISSION_MODIFY: u32 = 1 << 3;
+const PERMISSION_COPY: u32 = 1 << 4;
+const PERMISSION_ANNOTATE: u32 = 1 << 5;
+const PERMISSION_FILL_FORMS: u32 = 1 << 8;
+const PERMISSION_ACCESSIBILITY: u32 = 1 << 9;
+const PERMISSION_ASSEMBLE: u32 = 1 << 10;
+const PERMISSION_HIGH_QUALITY_PRINT: u32 = 1 << 11;
+/// The bits of the permissions value that are reserved and have to be 1.
+const PERMISSION_RESERVED: u32 = 0xfffff0c0;
+
+/// Encrypts or decrypts some bytes with RC4 (the same function does both).
+fn rc4(key: &[u8], data: &[u8]) -> Vec<u8>
+{
+	let mut state: [u8; 256] = std::array::from_fn(|index| index as u8);
+	let mut j: u8 = 0;
+	for i in 0..256
+	{
+		j = j.wrapping_add(state[i]).wrapping_add(key[i % key.len()]);
+		state.swap(i, j as usize);
+	}
+	let (mut i, mut j) = (0u8, 0u8);
+	data.iter().map(|byte|
+	{
+		i = i.wrapping_add(1);
+		j = j.wrapping_add(state[i as usize]);
+		state.swap(i as usize, j as usize);
+		byte ^ state[state[i as usize].wrapping_add(state[j as usize]) as usize]
+	})
+	.collect()
+}
+
+/// Encrypts some bytes with RC4 20 times, using the key with each byte xor'd by the number of the pass.
+fn rc4_20_passes(key: &[u8], data: &[u8]) -> Vec<u8>
+{
+	let mut data = rc4(key, data);
+	for pass in 1..=19u8
+	{
+		let pass_key: Vec<u8> = key.iter().map(|byte| byte ^ pass).collect();
+		data = rc4(&pass_key, &data);
+	}
+	data
+}
+
+/// Pads or cuts off a password so it's 32 bytes long.
+fn pad_password(password: &str) -> Vec<u8>
+{
+	let password = password.as_bytes();
+	let length = password.len().min(32);
+	let mut padded = password[..length].to_vec();
+	padded.extend_from_slice(&PASSWORD_PADDING[..32 - length]);
+	padded
+}
+
+/// Hashes some bytes with md5 and then hashes the result 50 more times.
+fn md5_51_times(data: &[u8]) -> [u8; KEY_LENGTH]
+{
+	let mut hash = md5::compute(data).0;
+	for _ in 0..50 { hash = md5::compute(hash).0; }
+	hash
+}
+
+/// The permissions value for the things that encryption options allow.
+fn permissions(options: &EncryptionOptions) -> u32
+{
+	// Text can always be read by screen readers
+	let mut permissions = PERMISSION_RESERVED | PERMISSION_ACCESSIBILITY;
+	if options.allow_printing { permissions |= PERMISSION_PRINT | PERMISSION_HIGH_QUALITY_PRINT; }
+	if options.allow_modifying { permissions |= PERMISSION_MODIFY | PERMISSION_ASSEMBLE; }
+	if options.allow_copying { permissions |= PERMISSION_COPY; }
+	if options.allow_annotations { permissions |= PERMISSION_ANNOTATE | PERMISSION_FILL_FORMS; }
+	permissions
+}
+
+/// Encrypts every string and stream in a pdf document with the standard security handler (128 bit RC4, revision 3)
+/// so it needs the user password to be opened and the owner password to get around the permissions.
+pub(crate) fn encrypt_document(doc: &mut lopdf::Document, options: &EncryptionOptions)
+-> Result<(), Box<dyn Error + Send + Sync>>
+{
+	// The first part of the file id goes into the encryption key
+	if doc.trailer.get(b"ID").is_err()
+	{
+		let id = Object::String(md5::compute(format!("{:?}", doc.trailer)).0.to_vec(), StringFormat::Hexadecimal);
+		doc.trailer.set("ID", Object::Array(vec![id.clone(), id]));
+	}
+	let file_id = doc.trailer.get(b"ID")?.as_array()?.first().ok_or(lopdf::Error::DictKey)?.as_str()?.to_vec();
+	// The owner password gets used to encrypt the user password (the user password is the owner password if there
+	// isn't one)
+	let owner_password = if options.owner_password.is_empty() { &options.user_password }
+	else { &options.owner_password };
+	let owner_key = md5_51_times(&pad_password(owner_password));
+	let user_password = pad_password(&options.user_password);
+	let owner_value = rc4_20_passes(&owner_key, &user_password);
+	// The encryption key comes from the user password, owner value, permissions, and file id
+	let permissions = permissions(options);
+	let mut key_data = user_password;
+	key_data.extend_from_slice(&owner_value);
+	key_data.extend_from_slice(&permissions.to_le_bytes());
+	key_data.extend_from_slice(&file_id);
+	let key = md5_51_times(&key_data);
+	// The user value is used to check if the user password is right
+	let mut id_hash_data = PASSWORD_PADDING.to_vec();
+	id_hash_data.extend_from_slice(&file_id);
+	let mut user_value = rc4_20_passes(&key, &md5::compute(id_hash_data).0);
+	user_value.extend_from_slice(&PASSWORD_PADDING[..16]);
+	// Encrypt every object before adding the encryption dictionary (which doesn't get encrypted)
+	for (id, object) in doc.objects.iter_mut()
+	{
+		// Cross reference streams don't get encrypted
+		if let Object::Stream(stream) = object
+		{
+			if matches!(stream.dict.get(b"Type").and_then(Object::as_name), Ok(b"XRef")) { continue; }
+		}
+		encrypt_object(&key, *id, object);
+	}
+	let mut encryption_dict = Dictionary::new();
+	encryption_dict.set("Filter", Object::Name(b"Standard".to_vec()));
+	encryption_dict.set("V", Object::Integer(2));
+	encryption_dict.set("R", Object::Integer(3));
+	encryption_dict.set("Length", Object::Integer(KEY_LENGTH as i64 * 8));
+	encryption_dict.set("O", Object::String(owner_value, StringFormat::Hexadecimal));
+	encryption_dict.set("U", Object::String(user_value, StringFormat::Hexadecimal));
+	// The permissions are a signed 32 bit number
+	encryption_dict.set("P", Object::Integer(permissions as i32 as i64));
+	let encryption_dict_id = doc.add_object(encryption_dict);
+	doc.trailer.set("Encrypt", Object::Reference(encryption_dict_id));
+	Ok(())
+}
+
+/// Encrypts every string and stream in an object (including ones inside of dictionaries and arrays) with the key for
+/// the object's id.
+fn encrypt_object(key: &[u8], id: ObjectId, object: &mut Object)
+{
+	// Each object gets its own key made from the document's key and the object's id
+	let mut object_key_data = key.to_vec();
+	object_key_data.extend_from_slice(&id.0.to_le_bytes()[..3]);
+	object_key_data.extend_from_slice(&id.1.to_le_bytes()[..2]);
+	let object_key = md5::compute(object_key_data).0;
+	encrypt_strings(&object_key, object);
+}
+
+/// Encrypts every string and stream in an object with an object's key.
+fn encrypt_strings(object_key: &[u8], object: &mut Object)
+{
+	match object
+	{
+		Object::String(bytes, _) => *bytes = rc4(object_key, bytes),
+		Object::Array(objects) => for object in objects { encrypt_strings(object_key, object); },
+		Object::Dictionary(dict) => for (_, object) in dict.iter_mut() { encrypt_strings(object_key, object); },
+		Object::Stream(stream) =>
+		{
+			for (_, object) in stream.dict.iter_mut() { encrypt_strings(object_key, object); }
+			stream.content = rc4(object_key, &stream.content);
+		},
+		_ => ()
+	}
+}
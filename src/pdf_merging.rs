@@ -0,0 +1,428 @@
+//////////////////////////////////////////////////////////////////////////////////////////////////////////////
+//
+//	Merging saved spellbooks into one pdf document
+//
+//////////////////////////////////////////////////////////////////////////////////////////////////////////////
+
+use std::collections::{HashMap, HashSet};
+use std::error::Error;
+
+use printpdf::lopdf::{self, Object, ObjectId, Dictionary, StringFormat};
+
+use crate::spellbook_options::MergeOptions;
+
+/// Error for when spellbooks couldn't be merged.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MergeError(pub(crate) String);
+// Makes the struct displayable
+impl std::fmt::Display for MergeError
+{
+	fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result
+	{
+		write!(f, "{}", self.0)
+	}
+}
+// Makes the struct officially an error
+impl Error for MergeError {}
+
+// Page attributes that pages can get from the page tree nodes above them instead of having themselves
+const INHERITED_PAGE_ATTRIBUTES: [&[u8]; 4] = [b"Resources", b"MediaBox", b"CropBox", b"Rotate"];
+
+/// Merges saved spellbooks into one pdf document, in order (ex: to combine spellbooks for each class that were made
+/// separately into one book).
+///
+/// The bookmarks of each spellbook still go to the same pages, and links between pages and fillable checkboxes keep
+/// working (checkboxes with the same name in different spellbooks don't get linked together). Named destinations
+/// keep going to the same pages too, with the number of the spellbook in front of names that an earlier spellbook
+/// already has (ex: "spellbook2-fireball"). The page numbers
+/// printed on the pages don't change, so a spellbook's printed page numbers only line up with the page numbers that
+/// pdf readers show if `MergeOptions::renumber_pages` is off.
+///
+/// Spellbooks can be loaded for this with `lopdf::Document::load()` or `lopdf::Document::load_mem()` on the bytes
+/// from `Spellbook::save_to_bytes()`. The merged document can be saved with `lopdf::Document::save()`.
+///
+/// # Parameters
+///
+/// - `docs` The pdf documents of the spellbooks to merge.
+/// - `options` How page numbers, bookmarks, and resources are merged.
+///
+/// # Output
+///
+/// - `Ok` The merged pdf document.
+/// - `Err` There were no spellbooks, one of the spellbooks is password protected, or one of them couldn't be read.
+pub fn merge_spellbooks(docs: Vec<lopdf::Document>, options: &MergeOptions)
+-> Result<lopdf::Document, Box<dyn Error + Send + Sync>>
+{
+	if docs.is_empty() { return Err(Box::new(MergeError(String::from("There are no spellbooks to merge.")))); }
+	let mut merged = lopdf::Document::with_version(docs[0].version.clone());
+	let pages_id = merged.new_object_id();
+	let outlines_id = merged.new_object_id();
+	// The pages of every spellbook and the index of the first page of each one
+	let mut page_ids = Vec::new();
+	let mut first_page_indexes = Vec::with_capacity(docs.len());
+	// The top level bookmarks of the merged document and the fillable form field of each spellbook
+	let mut bookmark_ids = Vec::new();
+	let mut field_ids = Vec::new();
+	// The named destinations of every spellbook
+	let mut destinations: Vec<(Vec<u8>, Object)> = Vec::new();
+	let mut info = None;
+	for (book_index, mut doc) in docs.into_iter().enumerate()
+	{
+		if doc.is_encrypted()
+		{
+			return Err(Box::new(MergeError(format!
+				("Spellbook {} is password protected, so it can't be merged.", book_index + 1))));
+		}
+		// Give every object in the spellbook an id that isn't used yet
+		doc.renumber_objects_with(merged.max_id + 1);
+		merged.max_id = doc.max_id;
+		let catalog = doc.catalog()?.clone();
+		// The document info of the first spellbook gets used for the merged document
+		if info.is_none() { info = doc.trailer.get(b"Info").and_then(Object::as_reference).ok(); }
+		let title = doc.trailer.get(b"Info").and_then(Object::as_reference).and_then(|id| doc.get_dictionary(id))
+			.and_then(|info| info.get(b"Title")).cloned()
+			.unwrap_or_else(|_| Object::string_literal(format!("Spellbook {}", book_index + 1)));
+		// Move the pages of the spellbook into the page tree of the merged document
+		let book_page_ids: Vec<ObjectId> = doc.page_iter().collect();
+		if book_page_ids.is_empty() { continue; }
+		first_page_indexes.push(page_ids.len());
+		for page_id in &book_page_ids
+		{
+			let inherited = inherited_page_attributes(&doc, *page_id);
+			let page = doc.get_dictionary_mut(*page_id)?;
+			for (key, value) in inherited
+			{
+				if !page.has(key) { page.set(key, value); }
+			}
+			page.set("Parent", Object::Reference(pages_id));
+		}
+		// Move the bookmarks of the spellbook into the bookmarks of the merged document
+		let book_bookmark_ids = top_level_bookmarks(&doc, &catalog);
+		if options.group_bookmarks
+		{
+			let group_id = merged.new_object_id();
+			let mut group = Dictionary::from_iter(vec!
+			[
+				("Title", title.clone()),
+				("Parent", Object::Reference(outlines_id)),
+				("Dest", Object::Array(vec![Object::Reference(book_page_ids[0]), Object::Name(b"Fit".to_vec())]))
+			]);
+			if !book_bookmark_ids.is_empty()
+			{
+				link_bookmarks(&mut doc, &book_bookmark_ids, group_id)?;
+				group.set("First", Object::Reference(book_bookmark_ids[0]));
+				group.set("Last", Object::Reference(book_bookmark_ids[book_bookmark_ids.len() - 1]));
+				group.set("Count", Object::Integer(visible_bookmark_count(&doc, &book_bookmark_ids)));
+			}
+			doc.objects.insert(group_id, Object::Dictionary(group));
+			bookmark_ids.push(group_id);
+		}
+		else { bookmark_ids.extend(book_bookmark_ids); }
+		// Put the fillable form fields of the spellbook under a field of their own so that fields with the same name in
+		// different spellbooks don't get linked together
+		let book_field_ids = form_fields(&doc, &catalog);
+		if !book_field_ids.is_empty()
+		{
+			let field_id = merged.new_object_id();
+			for book_field_id in &book_field_ids
+			{
+				doc.get_dictionary_mut(*book_field_id)?.set("Parent", Object::Reference(field_id));
+			}
+			doc.objects.insert(field_id, Object::Dictionary(Dictionary::from_iter(vec!
+			[
+				("T", Object::String(format!("spellbook{}", book_index + 1).into_bytes(), StringFormat::Literal)),
+				("Kids", Object::Array(book_field_ids.into_iter().map(Object::Reference).collect()))
+			])));
+			field_ids.push(field_id);
+		}
+		// Keep the named destinations of the spellbook, with the number of the spellbook in front of names that an
+		// earlier spellbook already has (ex: "spellbook2-fireball")
+		for (name, destination) in named_destinations(&doc, &catalog)
+		{
+			let name = if destinations.iter().any(|(used_name, _)| *used_name == name)
+			{
+				[format!("spellbook{}-", book_index + 1).into_bytes(), name].concat()
+			}
+			else { name };
+			destinations.push((name, destination));
+		}
+		page_ids.extend(book_page_ids);
+		// The catalog and page tree of the spellbook get left behind and removed at the end
+		merged.objects.extend(doc.objects);
+	}
+	// Add the page tree of the merged document
+	merged.objects.insert(pages_id, Object::Dictionary(Dictionary::from_iter(vec!
+	[
+		("Type", Object::Name(b"Pages".to_vec())),
+		("Count", Object::Integer(page_ids.len() as i64)),
+		("Kids", Object::Array(page_ids.iter().copied().map(Object::Reference).collect()))
+	])));
+	let mut catalog = Dictionary::from_iter(vec!
+	[
+		("Type", Object::Name(b"Catalog".to_vec())),
+		("Pages", Object::Reference(pages_id))
+	]);
+	// Add the bookmarks of the merged document
+	if !bookmark_ids.is_empty()
+	{
+		link_bookmarks(&mut merged, &bookmark_ids, outlines_id)?;
+		let visible_count = visible_bookmark_count(&merged, &bookmark_ids);
+		merged.objects.insert(outlines_id, Object::Dictionary(Dictionary::from_iter(vec!
+		[
+			("Type", Object::Name(b"Outlines".to_vec())),
+			("First", Object::Reference(bookmark_ids[0])),
+			("Last", Object::Reference(bookmark_ids[bookmark_ids.len() - 1])),
+			("Count", Object::Integer(visible_count))
+		])));
+		catalog.set("Outlines", Object::Reference(outlines_id));
+		catalog.set("PageMode", Object::Name(b"UseOutlines".to_vec()));
+	}
+	// Add the fillable form fields of the merged document
+	if !field_ids.is_empty()
+	{
+		let fields = Object::Array(field_ids.into_iter().map(Object::Reference).collect());
+		catalog.set("AcroForm", Object::Dictionary(Dictionary::from_iter(vec![("Fields", fields)])));
+	}
+	// Add the named destinations of the merged document (name trees have to be sorted by name)
+	if !destinations.is_empty()
+	{
+		destinations.sort_by(|(a, _), (b, _)| a.cmp(b));
+		let names = destinations.into_iter()
+			.flat_map(|(name, destination)| [Object::String(name, StringFormat::Literal), destination])
+			.collect();
+		let dests_id = merged.add_object(Dictionary::from_iter(vec![("Names", Object::Array(names))]));
+		catalog.set("Names", Object::Dictionary(Dictionary::from_iter(vec![("Dests", Object::Reference(dests_id))])));
+	}
+	// Start the page numbers over at the first page of each spellbook (if they aren't renumbered)
+	if !options.renumber_pages
+	{
+		let mut numbers = Vec::with_capacity(first_page_indexes.len() * 2);
+		for first_page_index in first_page_indexes
+		{
+			numbers.push(Object::Integer(first_page_index as i64));
+			numbers.push(Object::Dictionary(Dictionary::from_iter(vec![("S", Object::Name(b"D".to_vec()))])));
+		}
+		catalog.set("PageLabels", Object::Dictionary(Dictionary::from_iter(vec![("Nums", Object::Array(numbers))])));
+	}
+	let catalog_id = merged.add_object(catalog);
+	merged.trailer.set("Root", Object::Reference(catalog_id));
+	if let Some(info) = info { merged.trailer.set("Info", Object::Reference(info)); }
+	if options.deduplicate_resources { deduplicate_resources(&mut merged); }
+	// Remove everything that nothing uses anymore (like the catalog and page tree of each spellbook)
+	merged.prune_objects();
+	Ok(merged)
+}
+
+// Gets the attributes that a page gets from the page tree nodes above it and doesn't have itself
+fn inherited_page_attributes(doc: &lopdf::Document, page_id: ObjectId) -> Vec<(&'static [u8], Object)>
+{
+	let mut attributes: Vec<(&'static [u8], Object)> = Vec::new();
+	let mut visited = HashSet::new();
+	let mut node_id = page_id;
+	while visited.insert(node_id)
+	{
+		let node = match doc.get_dictionary(node_id)
+		{
+			Ok(node) => node,
+			Err(_) => break
+		};
+		// The closest node with an attribute is the one it comes from
+		for key in INHERITED_PAGE_ATTRIBUTES
+		{
+			if attributes.iter().all(|(inherited_key, _)| *inherited_key != key)
+			{
+				if let Ok(value) = node.get(key) { attributes.push((key, value.clone())); }
+			}
+		}
+		node_id = match node.get(b"Parent").and_then(Object::as_reference)
+		{
+			Ok(parent_id) => parent_id,
+			Err(_) => break
+		};
+	}
+	attributes
+}
+
+// Gets the ids of the top level bookmarks of a document, in order
+fn top_level_bookmarks(doc: &lopdf::Document, catalog: &Dictionary) -> Vec<ObjectId>
+{
+	let outlines = match catalog.get(b"Outlines").and_then(|outlines| doc.dereference(outlines))
+	{
+		Ok((_, outlines)) => outlines,
+		Err(_) => return Vec::new()
+	};
+	let mut bookmark_ids = Vec::new();
+	let mut next = outlines.as_dict().and_then(|outlines| outlines.get(b"First")).and_then(Object::as_reference);
+	while let Ok(bookmark_id) = next
+	{
+		// Stop if the bookmarks loop back around
+		if bookmark_ids.contains(&bookmark_id) { break; }
+		bookmark_ids.push(bookmark_id);
+		next = doc.get_dictionary(bookmark_id).and_then(|bookmark| bookmark.get(b"Next"))
+			.and_then(Object::as_reference);
+	}
+	bookmark_ids
+}
+
+// Links bookmarks to each other in order and puts them under a parent
+fn link_bookmarks(doc: &mut lopdf::Document, bookmark_ids: &[ObjectId], parent_id: ObjectId) -> Result<(), lopdf::Error>
+{
+	for (index, bookmark_id) in bookmark_ids.iter().enumerate()
+	{
+		let bookmark = doc.get_dictionary_mut(*bookmark_id)?;
+		bookmark.set("Parent", Object::Reference(parent_id));
+		bookmark.remove(b"Prev");
+		bookmark.remove(b"Next");
+		if index > 0 { bookmark.set("Prev", Object::Reference(bookmark_ids[index - 1])); }
+		if index + 1 < bookmark_ids.len() { bookmark.set("Next", Object::Reference(bookmark_ids[index + 1])); }
+	}
+	Ok(())
+}
+
+// The number of bookmarks that show up under a parent when it's opened (the bookmarks plus the bookmarks under each
+// one that's open)
+fn visible_bookmark_count(doc: &lopdf::Document, bookmark_ids: &[ObjectId]) -> i64
+{
+	bookmark_ids.iter().map(|bookmark_id|
+	{
+		let count = doc.get_dictionary(*bookmark_id).and_then(|bookmark| bookmark.get(b"Count"))
+			.and_then(Object::as_i64).unwrap_or(0);
+		// Closed bookmarks have negative counts
+		1 + count.max(0)
+	})
+	.sum()
+}
+
+// Gets the names and destinations in the named destinations tree of a document
+fn named_destinations(doc: &lopdf::Document, catalog: &Dictionary) -> Vec<(Vec<u8>, Object)>
+{
+	let mut destinations = Vec::new();
+	let root = catalog.get(b"Names").and_then(|names| doc.dereference(names))
+		.and_then(|(_, names)| names.as_dict())
+		.and_then(|names| names.get(b"Dests"));
+	let mut nodes = match root
+	{
+		Ok(root) => vec![root],
+		Err(_) => return destinations
+	};
+	let mut visited = HashSet::new();
+	while let Some(node) = nodes.pop()
+	{
+		let node = match doc.dereference(node)
+		{
+			Ok((id, Object::Dictionary(node))) if id.is_none_or(|id| visited.insert(id)) => node,
+			_ => continue
+		};
+		// Leaf nodes have pairs of names and destinations and other nodes have the nodes under them
+		if let Ok(names) = node.get(b"Names").and_then(Object::as_array)
+		{
+			for pair in names.chunks_exact(2)
+			{
+				if let (Ok(name), Ok((_, destination))) = (pair[0].as_str(), doc.dereference(&pair[1]))
+				{
+					destinations.push((name.to_vec(), destination.clone()));
+				}
+			}
+		}
+		if let Ok(kids) = node.get(b"Kids").and_then(Object::as_array) { nodes.extend(kids.iter().rev()); }
+	}
+	destinations
+}
+
+// Gets the ids of the top level fillable form fields of a document
+fn form_fields(doc: &lopdf::Document, catalog: &Dictionary) -> Vec<ObjectId>
+{
+	catalog.get(b"AcroForm").and_then(|form| doc.dereference(form))
+		.and_then(|(_, form)| form.as_dict())
+		.and_then(|form| form.get(b"Fields"))
+		.and_then(|fields| doc.dereference(fields))
+		.and_then(|(_, fields)| fields.as_array())
+		.map(|fields| fields.iter().filter_map(|field| field.as_reference().ok()).collect())
+		.unwrap_or_default()
+}
+
+// Makes every font and image that's exactly the same as an earlier one use the earlier one instead, and removes the
+// copies
+fn deduplicate_resources(doc: &mut lopdf::Document)
+{
+	// Fonts that use copies of the same font files and images become copies themselves once those are removed, so
+	// keep going until there aren't any copies left
+	loop
+	{
+		let resource_ids = resource_ids(doc);
+		let mut originals = HashMap::new();
+		let mut replacements = HashMap::new();
+		for (id, object) in &doc.objects
+		{
+			if !resource_ids.contains(id) { continue; }
+			let key = match object
+			{
+				Object::Stream(stream) => (format!("{:?}", stream.dict), stream.content.as_slice()),
+				_ => (format!("{:?}", object), [].as_slice())
+			};
+			match originals.get(&key)
+			{
+				Some(original_id) => { replacements.insert(*id, *original_id); },
+				None => { originals.insert(key, *id); }
+			}
+		}
+		if replacements.is_empty() { return; }
+		for id in replacements.keys() { doc.objects.remove(id); }
+		for object in doc.objects.values_mut() { replace_references(object, &replacements); }
+		for (_, object) in doc.trailer.iter_mut() { replace_references(object, &replacements); }
+	}
+}
+
+// Gets the ids of every image, font, and object that belongs to a font (like embedded font files) in a document
+fn resource_ids(doc: &lopdf::Document) -> HashSet<ObjectId>
+{
+	let mut resource_ids = HashSet::new();
+	for (id, object) in &doc.objects
+	{
+		let dict = match object
+		{
+			Object::Stream(stream) =>
+			{
+				if matches!(stream.dict.get(b"Subtype").and_then(Object::as_name), Ok(b"Image"))
+				{
+					resource_ids.insert(*id);
+				}
+				continue;
+			},
+			Object::Dictionary(dict) => dict,
+			_ => continue
+		};
+		if !matches!(dict.get(b"Type").and_then(Object::as_name), Ok(b"Font") | Ok(b"FontDescriptor")) { continue; }
+		resource_ids.insert(*id);
+		// The font files, character maps, and other fonts that fonts are made from
+		for key in [&b"FontFile"[..], b"FontFile2", b"FontFile3", b"ToUnicode", b"DescendantFonts", b"FontDescriptor"]
+		{
+			match dict.get(key)
+			{
+				Ok(Object::Reference(id)) => { resource_ids.insert(*id); },
+				Ok(Object::Array(objects)) =>
+				{
+					resource_ids.extend(objects.iter().filter_map(|object| object.as_reference().ok()));
+				},
+				_ => ()
+			}
+		}
+	}
+	resource_ids
+}
+
+// Replaces references to objects in an object (including in dictionaries and arrays inside of it)
+fn replace_references(object: &mut Object, replacements: &HashMap<ObjectId, ObjectId>)
+{
+	match object
+	{
+		Object::Reference(id) => if let Some(replacement) = replacements.get(id) { *id = *replacement; },
+		Object::Array(objects) => for object in objects { replace_references(object, replacements); },
+		Object::Dictionary(dict) => for (_, object) in dict.iter_mut() { replace_references(object, replacements); },
+		Object::Stream(stream) =>
+		{
+			for (_, object) in stream.dict.iter_mut() { replace_references(object, replacements); }
+		},
+		_ => ()
+	}
+}
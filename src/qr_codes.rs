@@ -0,0 +1,167 @@
+//////////////////////////////////////////////////////////////////////////////////////////////////////////////
+//
+//	QR codes that link to spells and spellbooks
+//
+//////////////////////////////////////////////////////////////////////////////////////////////////////////////
+
+use std::collections::HashMap;
+use std::collections::hash_map::Entry;
+use std::error::Error;
+
+use crate::spellbook_options::QrCodeOptions;
+use crate::spells::{Spell, name_slug};
+
+/// Error for when a QR code couldn't be made for a url.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct QrCodeError(pub(crate) String);
+// Makes the struct displayable
+impl std::fmt::Display for QrCodeError
+{
+	fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result
+	{
+		write!(f, "{}", self.0)
+	}
+}
+// Makes the struct officially an error
+impl Error for QrCodeError {}
+
+/// The squares of a QR code.
+#[derive(Clone, Debug)]
+pub(crate) struct QrModules
+{
+	// How many squares wide and tall the code is
+	size: usize,
+	// Whether each square is dark, row by row from the top left corner
+	dark: Vec<bool>
+}
+
+impl QrModules
+{
+	/// How many squares wide and tall the code is.
+	pub fn size(&self) -> usize { self.size }
+
+	/// Whether the square in a column and row of the code (counting from the top left corner) is dark.
+	pub fn is_dark(&self, column: usize, row: usize) -> bool { self.dark[row * self.size + column] }
+}
+
+/// Makes a QR code with some text in it.
+#[cfg(feature = "qr")]
+fn encode(text: &str) -> Result<QrModules, QrCodeError>
+{
+	use qrcodegen::{QrCode, QrCodeEcc};
+
+	let code = QrCode::encode_text(text, QrCodeEcc::Medium)
+		.map_err(|error| QrCodeError(format!("{} can't be put in a QR code ({}).", text, error)))?;
+	let size = code.size();
+	let dark = (0..size).flat_map(|row| (0..size).map(move |column| (column, row)))
+		.map(|(column, row)| code.get_module(column, row))
+		.collect();
+	Ok(QrModules { size: size as usize, dark: dark })
+}
+
+/// QR codes can't be made without the `qr` feature.
+#[cfg(not(feature = "qr"))]
+fn encode(text: &str) -> Result<QrModules, QrCodeError>
+{
+	Err(QrCodeError(format!("The `qr` feature is needed to make a QR code for {}.", text)))
+}
+
+/// Escapes every character in some text that can't go in a url as is (ex: spaces become "%20").
+fn url_encode(text: &str) -> String
+{
+	let mut encoded = String::with_capacity(text.len());
+	for byte in text.bytes()
+	{
+		if byte.is_ascii_alphanumeric() || b"-_.~".contains(&byte) { encoded.push(byte as char); }
+		else { encoded.push_str(&format!("%{:02X}", byte)); }
+	}
+	encoded
+}
+
+/// Turns a spell name into lowercase words separated by dashes with no punctuation (ex: "Tasha's Hideous Laughter"
+/// becomes "tashas-hideous-laughter").
+fn url_slug(name: &str) -> String { name_slug(name, '-') }
+
+/// The QR codes that go in a spellbook. They all get made before the spellbook does so that urls that can't be put in
+/// QR codes are found before anything gets written.
+pub(crate) struct SpellbookQrCodes
+{
+	options: QrCodeOptions,
+	// The code on the title page
+	book_code: Option<(String, QrModules)>,
+	// The code for each url that goes next to a spell name
+	spell_codes: HashMap<String, QrModules>
+}
+
+impl SpellbookQrCodes
+{
+	/// Makes the QR codes for the title page and each spell.
+	///
+	/// # Parameters
+	///
+	/// - `options` The urls that go in the codes and how big the codes are.
+	/// - `title` The title of the spellbook.
+	/// - `spells` The spells in the spellbook.
+	///
+	/// # Output
+	///
+	/// - `Ok` The QR codes.
+	/// - `Err` A url that can't be put in a QR code (or the `qr` feature is missing).
+	pub fn new(options: &QrCodeOptions, title: &str, spells: &[Spell]) -> Result<Self, QrCodeError>
+	{
+		let book_code = match &options.book_url_template
+		{
+			Some(template) =>
+			{
+				let url = template.replace("{title}", &url_encode(title));
+				let code = encode(&url)?;
+				Some((url, code))
+			},
+			None => None
+		};
+		let mut qr_codes = Self { options: options.clone(), book_code: book_code, spell_codes: HashMap::new() };
+		qr_codes.add_spells(spells)?;
+		Ok(qr_codes)
+	}
+
+	/// Makes the QR codes for more spells (like spells that get added to the end of a spellbook).
+	pub fn add_spells(&mut self, spells: &[Spell]) -> Result<(), QrCodeError>
+	{
+		for spell in spells
+		{
+			if let Some(url) = spell_url(&self.options, spell)
+			{
+				// Spells with the same url share a code
+				if let Entry::Vacant(entry) = self.spell_codes.entry(url)
+				{
+					let code = encode(entry.key())?;
+					entry.insert(code);
+				}
+			}
+		}
+		Ok(())
+	}
+
+	/// The settings for the codes.
+	pub fn options(&self) -> &QrCodeOptions { &self.options }
+
+	/// The url and QR code that go on the title page (if there is one).
+	pub fn book_code(&self) -> Option<&(String, QrModules)> { self.book_code.as_ref() }
+
+	/// The url and QR code that go next to the name of a spell (if there is one).
+	pub fn spell_code(&self, spell: &Spell) -> Option<(String, &QrModules)>
+	{
+		let url = spell_url(&self.options, spell)?;
+		let code = self.spell_codes.get(&url)?;
+		Some((url, code))
+	}
+}
+
+/// The url that goes in the QR code next to the name of a spell (if spells get QR codes).
+fn spell_url(options: &QrCodeOptions, spell: &Spell) -> Option<String>
+{
+	options.spell_url_template.as_ref().map(|template|
+	{
+		template.replace("{name}", &url_encode(&spell.name)).replace("{slug}", &url_slug(&spell.name))
+	})
+}
@@ -0,0 +1,129 @@
+//////////////////////////////////////////////////////////////////////////////////////////////////////////////
+//
+//	Splitting spell text into paragraphs and styled runs for exporters that don't make pdf documents
+//
+//////////////////////////////////////////////////////////////////////////////////////////////////////////////
+
+use crate::spellbook_options::{FontVariant, UpcastOptions};
+use crate::layout::{color_from_tag, url_from_tag};
+use crate::spells::{self, Spell, SpellField, Level, Table};
+
+/// A block of text in a description.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) enum Block<'a>
+{
+	/// A normal paragraph.
+	Paragraph(&'a str),
+	/// Neighboring bullet points (without the bullet symbols).
+	Bullets(Vec<&'a str>),
+	/// A table from a table tag.
+	Table(&'a Table)
+}
+
+/// Splits text that can have paragraphs, bullet points, and table tags in it (like spell descriptions) into blocks.
+/// Table tags with an index that's out of range are treated like normal text. Empty paragraphs and page break tags are
+/// skipped.
+pub(crate) fn blocks<'a>(text: &'a str, tables: &'a Vec<Table>) -> Vec<Block<'a>>
+{
+	let mut blocks = Vec::new();
+	for paragraph in text.split('\n')
+	{
+		let trimmed = paragraph.trim_start();
+		// Bullet points start with a dash or bullet character followed by whitespace
+		let bullet_text = ["- ", "\u{2022} ", "-\t", "\u{2022}\t"].iter().find_map(|bullet| trimmed.strip_prefix(bullet));
+		if let Some(bullet_text) = bullet_text
+		{
+			// Add the bullet point to the list before it if there is one
+			match blocks.last_mut()
+			{
+				Some(Block::Bullets(bullets)) => bullets.push(bullet_text),
+				_ => blocks.push(Block::Bullets(vec![bullet_text]))
+			}
+			continue;
+		}
+		// Table tags only work at the start of a paragraph and the rest of the paragraph is skipped
+		let first_token = trimmed.split_whitespace().next().unwrap_or_default();
+		match spells::table_tag_index(first_token).and_then(|index| tables.get(index))
+		{
+			Some(table) => blocks.push(Block::Table(table)),
+			None if trimmed.is_empty() => (),
+			// Page break tags only matter for pages, so they get skipped
+			None if spells::is_page_break_tag(trimmed.trim_end()) => (),
+			None => blocks.push(Block::Paragraph(paragraph))
+		}
+	}
+	blocks
+}
+
+/// Returns the description of a spell with its upcast description at the end of it the same way spellbooks put it
+/// there (in its own paragraph that starts with a bold-italic prefix).
+pub(crate) fn description_with_upcast(spell: &Spell, upcast: &UpcastOptions) -> String
+{
+	// Adds different text at the start of the upcast description based on whether the spell is a cantrip or not
+	let upcast_prefix = match &spell.level
+	{
+		SpellField::Controlled(Level::Cantrip) => &upcast.cantrip_prefix,
+		_ => &upcast.leveled_prefix
+	};
+	match &spell.upcast_description
+	{
+		Some(upcast_description) => format!("{}\n<bi> {}. <r> {}", spell.description, upcast_prefix, upcast_description),
+		None => spell.description.clone()
+	}
+}
+
+/// How text looks at a certain point in a paragraph.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct InlineStyle
+{
+	pub font_variant: FontVariant,
+	/// The url the text links to (if it's a link).
+	pub link: Option<String>,
+	/// The RGB color of the text from a color tag (if there is one).
+	pub color: Option<(u8, u8, u8)>
+}
+
+/// Splits a paragraph of text with font tags, url tags, and color tags into runs of words that all have the same
+/// style. The words in each run are separated by single spaces and escaped tags have their first backslash removed.
+pub(crate) fn styled_runs(text: &str) -> Vec<(InlineStyle, String)>
+{
+	let tokens: Vec<_> = text.split_whitespace().collect();
+	let mut runs: Vec<(InlineStyle, String)> = Vec::new();
+	let mut style = InlineStyle { font_variant: FontVariant::Regular, link: None, color: None };
+	for (index, token) in tokens.iter().enumerate()
+	{
+		match *token
+		{
+			"<r>" => style.font_variant = FontVariant::Regular,
+			"<b>" => style.font_variant = FontVariant::Bold,
+			"<i>" => style.font_variant = FontVariant::Italic,
+			"<bi>" | "<ib>" => style.font_variant = FontVariant::BoldItalic,
+			"</url>" => style.link = None,
+			"</color>" => style.color = None,
+			// The url is all of the text up to the end tag (or the end of the paragraph)
+			"<url>" =>
+			{
+				let url_end = tokens[index + 1..].iter().position(|token| *token == "</url>")
+					.map_or(tokens.len(), |position| index + 1 + position);
+				style.link = Some(tokens[index + 1..url_end].concat());
+			},
+			token if url_from_tag(token).is_some() => style.link = url_from_tag(token).map(String::from),
+			token if color_from_tag(token).is_some() => style.color = color_from_tag(token),
+			token =>
+			{
+				let word = spells::unescape_token(token, false);
+				// Add the word to the last run if it has the same style, otherwise start a new run
+				match runs.last_mut()
+				{
+					Some((run_style, run_text)) if *run_style == style =>
+					{
+						run_text.push(' ');
+						run_text.push_str(word);
+					},
+					_ => runs.push((style.clone(), String::from(word)))
+				}
+			}
+		}
+	}
+	runs
+}
@@ -0,0 +1,91 @@
+//////////////////////////////////////////////////////////////////////////////////////////////////////////////
+//
+//	Indexes of the words on each page of a spellbook for searching
+//
+//////////////////////////////////////////////////////////////////////////////////////////////////////////////
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::error::Error;
+use std::fs;
+
+use serde::Serialize;
+
+use crate::spellbook_options::SearchIndexOptions;
+
+/// The pages that each word in a spellbook is on, made from the text as it was laid out (so it doesn't need the
+/// text to be extracted from the pdf). Meant to be saved as a json file next to the spellbook so apps that show the
+/// spellbook can search it instantly.
+///
+/// The json is an object with each word (in lowercase) as a key and a list of the page indexes that the word is on
+/// (0 for the title page) as its value (ex: `{"fireball": [4, 12]}`).
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize)]
+#[serde(transparent)]
+pub struct SearchIndex
+{
+	terms: BTreeMap<String, BTreeSet<usize>>
+}
+
+impl SearchIndex
+{
+	/// Adds each word in some text that was written on a page to the index.
+	pub(crate) fn add_text(&mut self, text: &str, page_index: usize, options: &SearchIndexOptions)
+	{
+		for word in search_words(text)
+		{
+			if word.chars().count() < options.min_word_length { continue; }
+			if options.ignored_words.iter().any(|ignored_word| ignored_word.eq_ignore_ascii_case(&word)) { continue; }
+			self.terms.entry(word).or_default().insert(page_index);
+		}
+	}
+
+	/// The indexes of the pages that a word is on (0 for the title page). The word is looked up without caring about
+	/// capitalization.
+	pub fn pages(&self, word: &str) -> Vec<usize>
+	{
+		match self.terms.get(&word.to_lowercase())
+		{
+			Some(pages) => pages.iter().copied().collect(),
+			None => Vec::new()
+		}
+	}
+
+	/// Iterates over every word in the index in alphabetical order along with the indexes of the pages it's on.
+	pub fn terms(&self) -> impl Iterator<Item = (&str, Vec<usize>)>
+	{
+		self.terms.iter().map(|(term, pages)| (term.as_str(), pages.iter().copied().collect()))
+	}
+
+	/// The number of different words in the index.
+	pub fn len(&self) -> usize { self.terms.len() }
+
+	/// Whether or not there aren't any words in the index.
+	pub fn is_empty(&self) -> bool { self.terms.is_empty() }
+
+	/// Turns the index into json text.
+	pub fn to_json(&self) -> String { serde_json::to_string(self).unwrap() }
+
+	/// Saves the index to a json file (like one next to the spellbook's pdf file).
+	///
+	/// # Parameters
+	///
+	/// - `file_path` The file path to save the index to.
+	///
+	/// # Output
+	///
+	/// - `Ok` Nothing if there were no errors.
+	/// - `Err` Any errors that occurred while writing the file.
+	pub fn save_json(&self, file_path: &str) -> Result<(), Box<dyn Error + Send + Sync>>
+	{
+		fs::write(file_path, self.to_json())?;
+		Ok(())
+	}
+}
+
+/// Splits text into lowercase words for searching. Words are made of letters and numbers, and apostrophes inside of
+/// words get dropped (so "Tasha's" becomes "tashas").
+fn search_words(text: &str) -> impl Iterator<Item = String> + '_
+{
+	text.split(|c: char| !c.is_alphanumeric() && c != '\'' && c != '\u{2019}')
+		.map(|word| word.chars().filter(|c| c.is_alphanumeric()).collect::<String>().to_lowercase())
+		.filter(|word| !word.is_empty())
+}
@@ -0,0 +1,215 @@
+//////////////////////////////////////////////////////////////////////////////////////////////////////////////
+//
+//	Shaping text in scripts whose letters change shape or combine depending on the letters around them
+//
+//////////////////////////////////////////////////////////////////////////////////////////////////////////////
+
+use std::error::Error;
+use std::fmt;
+#[cfg(feature = "shaping")]
+use std::collections::HashMap;
+#[cfg(feature = "shaping")]
+use std::sync::{Arc, Mutex};
+
+/// Error for when text can't be shaped.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ShapingError(pub(crate) String);
+// Makes the struct displayable
+impl fmt::Display for ShapingError
+{
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
+	{
+		write!(f, "{}", self.0)
+	}
+}
+// Makes the struct officially an error
+impl Error for ShapingError {}
+
+/// A glyph that text got shaped into, with its position in the font's design units.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ShapedGlyph
+{
+	/// The id of the glyph in the font.
+	pub id: u16,
+	/// How far the next glyph goes after this one.
+	pub advance: i32,
+	/// How far the glyph gets moved right of where it would go.
+	pub x_offset: i32,
+	/// How far the glyph gets moved up from the baseline.
+	pub y_offset: i32
+}
+
+/// Shapes text with a font using the font's substitution and positioning rules (like the joined forms of Arabic
+/// letters and the conjuncts of Devanagari) so it gets the right glyphs in the right places. Only text with letters
+/// from scripts that need shaping gets shaped (see `needs_shaping()`). Needs the `shaping` feature.
+///
+/// Shaped text keeps the order of its glyphs within each piece of text that gets written, so a run of right-to-left
+/// text comes out right-to-left, but lines that mix right-to-left and left-to-right text aren't reordered.
+#[derive(Clone)]
+pub struct TextShaper
+{
+	#[cfg(feature = "shaping")]
+	font_bytes: Arc<Vec<u8>>,
+	// The advance width of each glyph the way pdf documents have them (in thousandths of an em)
+	#[cfg(feature = "shaping")]
+	pdf_widths: Arc<Vec<i64>>,
+	#[cfg(feature = "shaping")]
+	units_per_em: u16,
+	// Text that has already been shaped, so text that gets measured more than once only gets shaped once
+	#[cfg(feature = "shaping")]
+	cache: Arc<Mutex<HashMap<String, Arc<Vec<ShapedGlyph>>>>>
+}
+
+impl TextShaper
+{
+	/// Constructor
+	///
+	/// # Parameters
+	///
+	/// - `font_bytes` The bytes of the font file to shape text with.
+	///
+	/// # Output
+	///
+	/// - `Ok` A TextShaper instance.
+	/// - `Err` The font couldn't be read (or the `shaping` feature is missing).
+	#[cfg(feature = "shaping")]
+	pub fn from_font_bytes(font_bytes: &[u8]) -> Result<Self, ShapingError>
+	{
+		let face = match rustybuzz::Face::from_slice(font_bytes, 0)
+		{
+			Some(face) => face,
+			None => return Err(ShapingError(String::from("A font file couldn't be read to shape text with.")))
+		};
+		let units_per_em = face.units_per_em().max(1) as u16;
+		// printpdf scales glyph widths to thousandths of an em and drops the fraction
+		let scale = 1000.0 / units_per_em as f32;
+		let pdf_widths = (0..face.number_of_glyphs())
+			.map(|id| (face.glyph_hor_advance(rustybuzz::ttf_parser::GlyphId(id)).unwrap_or(0) as f32 * scale) as i64)
+			.collect();
+		Ok(Self
+		{
+			font_bytes: Arc::new(font_bytes.to_vec()),
+			pdf_widths: Arc::new(pdf_widths),
+			units_per_em: units_per_em,
+			cache: Arc::new(Mutex::new(HashMap::new()))
+		})
+	}
+
+	/// Text can't be shaped without the `shaping` feature.
+	#[cfg(not(feature = "shaping"))]
+	pub fn from_font_bytes(_font_bytes: &[u8]) -> Result<Self, ShapingError>
+	{
+		Err(ShapingError(String::from("The `shaping` feature is needed to shape text.")))
+	}
+
+	/// Shapes some text into glyphs in the order they get written from left to right.
+	#[cfg(feature = "shaping")]
+	pub fn shape(&self, text: &str) -> Arc<Vec<ShapedGlyph>>
+	{
+		if let Some(glyphs) = self.cache.lock().unwrap().get(text) { return glyphs.clone(); }
+		let glyphs = match rustybuzz::Face::from_slice(&self.font_bytes, 0)
+		{
+			Some(face) =>
+			{
+				let mut buffer = rustybuzz::UnicodeBuffer::new();
+				buffer.push_str(text);
+				let output = rustybuzz::shape(&face, &[], buffer);
+				output.glyph_infos().iter().zip(output.glyph_positions()).map(|(info, position)| ShapedGlyph
+				{
+					id: info.glyph_id as u16,
+					advance: position.x_advance,
+					x_offset: position.x_offset,
+					y_offset: position.y_offset
+				})
+				.collect()
+			},
+			None => Vec::new()
+		};
+		let glyphs = Arc::new(glyphs);
+		self.cache.lock().unwrap().insert(String::from(text), glyphs.clone());
+		glyphs
+	}
+
+	/// Text can't be shaped without the `shaping` feature.
+	#[cfg(not(feature = "shaping"))]
+	pub fn shape(&self, _text: &str) -> std::sync::Arc<Vec<ShapedGlyph>> { Default::default() }
+
+	/// The width of some shaped text in the font's design units.
+	pub fn width(&self, text: &str) -> i32 { self.shape(text).iter().map(|glyph| glyph.advance).sum() }
+
+	/// The number of design units in the font's em square.
+	#[cfg(feature = "shaping")]
+	pub fn units_per_em(&self) -> u16 { self.units_per_em }
+
+	/// Text can't be shaped without the `shaping` feature.
+	#[cfg(not(feature = "shaping"))]
+	pub fn units_per_em(&self) -> u16 { 1000 }
+
+	/// Turns shaped glyphs into runs of glyph ids for pdf text operations. Each glyph has the adjustment that goes
+	/// before it in the run (in thousandths of an em, where positive numbers move the glyph left) to move it from
+	/// where the width of the glyph in the pdf would put it to where shaping put it. A new run starts wherever glyphs
+	/// have to be raised or lowered, with the amount to raise all of the glyphs in the run (in design units).
+	pub fn pdf_runs(&self, glyphs: &[ShapedGlyph]) -> Vec<(i32, Vec<(i64, u16)>)>
+	{
+		let scale = 1000.0 / self.units_per_em() as f32;
+		let to_pdf = |units: i32| (units as f32 * scale).round() as i64;
+		let mut runs: Vec<(i32, Vec<(i64, u16)>)> = Vec::new();
+		// How far left the pen has to move to get from where the last glyph left it to where the next glyph starts
+		let mut correction = 0;
+		for glyph in glyphs
+		{
+			let adjustment = correction - to_pdf(glyph.x_offset);
+			match runs.last_mut()
+			{
+				Some((rise, run)) if *rise == glyph.y_offset => run.push((adjustment, glyph.id)),
+				_ => runs.push((glyph.y_offset, vec![(adjustment, glyph.id)]))
+			}
+			correction = to_pdf(glyph.x_offset) + self.pdf_width(glyph.id) - to_pdf(glyph.advance);
+		}
+		runs
+	}
+
+	/// The width of a glyph the way pdf documents have it (in thousandths of an em).
+	#[cfg(feature = "shaping")]
+	fn pdf_width(&self, glyph_id: u16) -> i64 { self.pdf_widths.get(glyph_id as usize).copied().unwrap_or(0) }
+
+	/// Text can't be shaped without the `shaping` feature.
+	#[cfg(not(feature = "shaping"))]
+	fn pdf_width(&self, _glyph_id: u16) -> i64 { 0 }
+}
+
+impl fmt::Debug for TextShaper
+{
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
+	{
+		write!(f, "TextShaper")
+	}
+}
+
+// Shapers are equal if they shape text with the same font
+impl PartialEq for TextShaper
+{
+	#[cfg(feature = "shaping")]
+	fn eq(&self, other: &Self) -> bool { self.font_bytes == other.font_bytes }
+
+	#[cfg(not(feature = "shaping"))]
+	fn eq(&self, _other: &Self) -> bool { true }
+}
+
+/// Whether or not some text has letters from a script that needs shaping to be written correctly (like Arabic,
+/// Hebrew, and the scripts of South and Southeast Asia) or combining marks that have to be placed over other letters.
+pub fn needs_shaping(text: &str) -> bool
+{
+	text.chars().any(|c| matches!(c as u32,
+		// Combining diacritical marks
+		0x0300..=0x036F |
+		// Hebrew, Arabic, Syriac, Thaana, and N'Ko
+		0x0590..=0x07FF | 0x0860..=0x08FF |
+		// The scripts of India, Sri Lanka, and Southeast Asia (Devanagari through Lao), Tibetan, and Myanmar
+		0x0900..=0x0EFF | 0x0F00..=0x109F |
+		// Hangul jamo, Khmer, and Mongolian
+		0x1100..=0x11FF | 0x1780..=0x18AF |
+		// Arabic and Hebrew presentation forms
+		0xFB1D..=0xFDFF | 0xFE70..=0xFEFF
+	))
+}
@@ -0,0 +1,555 @@
+//////////////////////////////////////////////////////////////////////////////////////////////////////////////
+//
+//	Collections of spells that can be looked up by name
+//
+//////////////////////////////////////////////////////////////////////////////////////////////////////////////
+
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::sync::OnceLock;
+
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::spells::{self, SpellField, Level, MagicSchool, Rarity};
+use crate::utils::SpellFileNameReadError;
+
+/// The fields of a spell that spell databases index spells by. Spell files only get read up to these fields until
+/// the rest of the spell is needed.
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+pub struct SpellHeader
+{
+	pub name: String,
+	pub level: SpellField<Level>,
+	pub school: SpellField<MagicSchool>,
+	#[serde(default, alias = "availability")]
+	pub rarity: Option<Rarity>
+}
+
+impl SpellHeader
+{
+	/// Gets the header of a spell.
+	fn from_spell(spell: &spells::Spell) -> Self
+	{
+		Self
+		{
+			name: spell.name.clone(),
+			level: spell.level.clone(),
+			school: spell.school.clone(),
+			rarity: spell.rarity
+		}
+	}
+}
+
+// A spell in a spell database
+#[derive(Clone, Debug)]
+struct SpellEntry
+{
+	header: SpellHeader,
+	// The spell file the spell comes from (`None` if the spell was added directly)
+	file_path: Option<String>,
+	// How much the spell takes priority over other spells with the same name
+	priority: i32,
+	// The whole spell once it's been loaded
+	spell: OnceLock<spells::Spell>
+}
+
+/// A collection of spells from folders of spell files (or lists of spells) that can be looked up by name, level,
+/// school, and rarity.
+///
+/// Only the name, level, school, and rarity of each spell in a folder get read when the folder is added. The rest of each
+/// spell gets read from its file the first time it's needed.
+///
+/// Names are looked up without caring about capitalization, punctuation, or whether words are separated by spaces or
+/// underscores (so "Tasha's Hideous Laughter" and "tashas_hideous_laughter" find the same spell). If more than one
+/// spell has the same name, the one with the highest priority gets used (or the one that was added first if they
+/// have the same priority).
+#[derive(Clone, Debug, Default)]
+pub struct SpellDatabase
+{
+	// Every spell in the database in the order they were added
+	entries: Vec<SpellEntry>,
+	// The index of the spell in `entries` that gets used for each normalized name
+	index: HashMap<String, usize>,
+	// The locale that the text of spells gets read in (`None` for the default text in spell files)
+	locale: Option<String>,
+	// The fields that override files replace in each spell (by normalized name)
+	overrides: HashMap<String, Vec<FieldOverride>>,
+	// Fields that more than one override file replaces with different values
+	override_conflicts: Vec<OverrideConflict>
+}
+
+// A field of a spell that an override file replaces
+#[derive(Clone, Debug)]
+struct FieldOverride
+{
+	field: String,
+	value: Value,
+	// The override file the value comes from
+	file_path: String
+}
+
+impl SpellDatabase
+{
+	/// Constructs an empty spell database.
+	pub fn new() -> Self { Self::default() }
+
+	/// Constructs an empty spell database that reads the text of spells in a certain locale (language) wherever spell
+	/// files have it (see `Spell::from_json_file_with_locale()`). Spells get looked up by their names in the locale.
+	pub fn with_locale(locale: &str) -> Self
+	{
+		Self { locale: Some(locale.to_string()), ..Self::default() }
+	}
+
+	/// Constructs an empty spell database that replaces fields of spells with the ones in a folder of override files
+	/// (see `add_overrides()`).
+	///
+	/// # Parameters
+	///
+	/// - `folder_path` The file path to the folder of override files.
+	///
+	/// # Output
+	///
+	/// - `Ok` An empty spell database with the overrides in the folder.
+	/// - `Err` Any errors that occurred while reading the override files.
+	pub fn with_overrides(folder_path: &str) -> Result<Self, Box<dyn Error + Send + Sync>>
+	{
+		let mut database = Self::new();
+		database.add_overrides(folder_path)?;
+		Ok(database)
+	}
+
+	/// Constructs a spell database from every json spell file in a folder.
+	///
+	/// # Parameters
+	///
+	/// - `folder_path` The file path to the folder of spell files.
+	///
+	/// # Output
+	///
+	/// - `Ok` A spell database with every spell in the folder.
+	/// - `Err` Any errors that occurred while reading the spell files.
+	pub fn from_folder(folder_path: &str) -> Result<Self, Box<dyn Error + Send + Sync>>
+	{
+		let mut database = Self::new();
+		database.add_folder(folder_path)?;
+		Ok(database)
+	}
+
+	/// Adds every json spell file in a folder to the database with a priority of 0.
+	///
+	/// # Parameters
+	///
+	/// - `folder_path` The file path to the folder of spell files.
+	///
+	/// # Output
+	///
+	/// - `Ok` Nothing if the header of every spell file could be read.
+	/// - `Err` Any errors that occurred while reading the spell files (no spells get added if there are any).
+	pub fn add_folder(&mut self, folder_path: &str) -> Result<(), Box<dyn Error + Send + Sync>>
+	{
+		self.add_folder_with_priority(folder_path, 0)
+	}
+
+	/// Adds every json spell file in a folder to the database.
+	///
+	/// # Parameters
+	///
+	/// - `folder_path` The file path to the folder of spell files.
+	/// - `priority` How much the spells in the folder take priority over other spells with the same names (ex: a
+	///   folder of homebrew versions of spells can be given a higher priority than the official spells).
+	///
+	/// # Output
+	///
+	/// - `Ok` Nothing if the header of every spell file could be read.
+	/// - `Err` Any errors that occurred while reading the spell files (no spells get added if there are any).
+	pub fn add_folder_with_priority(&mut self, folder_path: &str, priority: i32)
+	-> Result<(), Box<dyn Error + Send + Sync>>
+	{
+		let file_paths = json_file_paths(folder_path)?;
+		// Read the header of each spell file before adding any of them so nothing gets added if one is invalid
+		let mut headers = Vec::with_capacity(file_paths.len());
+		for file_path in &file_paths
+		{
+			let header: SpellHeader = serde_json::from_value(self.read_spell_json(file_path)?)?;
+			headers.push(header);
+		}
+		for (header, file_path) in headers.into_iter().zip(file_paths)
+		{
+			self.add_entry(SpellEntry
+			{
+				header: header,
+				file_path: Some(file_path),
+				priority: priority,
+				spell: OnceLock::new()
+			});
+		}
+		Ok(())
+	}
+
+	/// Adds a list of spells (like a pack of homebrew spells) to the database with a priority of 0.
+	pub fn add_spells(&mut self, spell_list: Vec<spells::Spell>)
+	{
+		self.add_spells_with_priority(spell_list, 0);
+	}
+
+	/// Adds a list of spells (like a pack of homebrew spells) to the database. `priority` is how much the spells take
+	/// priority over other spells with the same names.
+	pub fn add_spells_with_priority(&mut self, spell_list: Vec<spells::Spell>, priority: i32)
+	{
+		for mut spell in spell_list
+		{
+			if self.overrides.contains_key(&normalize_spell_name(&spell.name))
+			{
+				let mut spell_json = serde_json::to_value(&spell).unwrap();
+				self.apply_overrides(&mut spell_json);
+				// Spells that overrides make invalid are added without them
+				if let Ok(overridden_spell) = serde_json::from_value(spell_json) { spell = overridden_spell; }
+			}
+			self.add_entry(SpellEntry
+			{
+				header: SpellHeader::from_spell(&spell),
+				file_path: None,
+				priority: priority,
+				spell: OnceLock::from(spell)
+			});
+		}
+	}
+
+	/// Adds every json override file in a folder to the database. Override files replace fields of spells without
+	/// having to edit the spell files (ex: errata that fixes a typo in a spell's description).
+	///
+	/// Each override file has a json object (or a list of them) with the name of the spell it changes and the fields
+	/// to replace, which are written the same way as in spell files (ex: `{"name": "Fireball", "description": "..."}`).
+	/// Spells are matched by the name in their spell files (not the name for the database's locale). Fields for other
+	/// locales (like "description@de") can be replaced too. Overrides only apply to spells that get added to the
+	/// database after them.
+	///
+	/// If more than one override file replaces the same field of the same spell with different values, the file that
+	/// comes last gets used (files in a folder go in alphabetical order) and the conflict gets added to
+	/// `override_conflicts()`.
+	///
+	/// # Parameters
+	///
+	/// - `folder_path` The file path to the folder of override files.
+	///
+	/// # Output
+	///
+	/// - `Ok` Nothing if every override file could be read.
+	/// - `Err` Any errors that occurred while reading the override files (no overrides get added if there are any).
+	pub fn add_overrides(&mut self, folder_path: &str) -> Result<(), Box<dyn Error + Send + Sync>>
+	{
+		// Read every override file before adding any of them so nothing gets added if one is invalid
+		let mut overrides = Vec::new();
+		for file_path in json_file_paths(folder_path)?
+		{
+			let file_json: Value = serde_json::from_str(&fs::read_to_string(&file_path)?)?;
+			let file_overrides = match file_json
+			{
+				Value::Array(file_overrides) => file_overrides,
+				file_override => vec![file_override]
+			};
+			for file_override in file_overrides
+			{
+				let mut fields = match file_override
+				{
+					Value::Object(fields) => fields,
+					_ => return Err(Box::new(InvalidOverrideFileError(file_path)))
+				};
+				let name = match fields.remove("name")
+				{
+					Some(Value::String(name)) => name,
+					_ => return Err(Box::new(InvalidOverrideFileError(file_path)))
+				};
+				overrides.push((name, fields, file_path.clone()));
+			}
+		}
+		for (name, fields, file_path) in overrides
+		{
+			let spell_overrides = self.overrides.entry(normalize_spell_name(&name)).or_default();
+			for (field, value) in fields
+			{
+				match spell_overrides.iter_mut().find(|spell_override| spell_override.field == field)
+				{
+					Some(spell_override) =>
+					{
+						if spell_override.value != value
+						{
+							self.override_conflicts.push(OverrideConflict
+							{
+								spell_name: name.clone(),
+								field: field,
+								replaced_file_path: spell_override.file_path.clone(),
+								used_file_path: file_path.clone()
+							});
+						}
+						spell_override.value = value;
+						spell_override.file_path = file_path.clone();
+					},
+					None => spell_overrides.push(FieldOverride
+					{
+						field: field,
+						value: value,
+						file_path: file_path.clone()
+					})
+				}
+			}
+		}
+		Ok(())
+	}
+
+	/// Every field that more than one override file replaced with different values (in the order they were found).
+	pub fn override_conflicts(&self) -> &[OverrideConflict] { &self.override_conflicts }
+
+	// Replaces the fields of the json of a spell with the ones from the override files for it (if there are any)
+	fn apply_overrides(&self, spell_json: &mut Value)
+	{
+		let name = match spell_json.get("name").and_then(Value::as_str)
+		{
+			Some(name) => normalize_spell_name(name),
+			None => return
+		};
+		if let (Some(spell_overrides), Some(fields)) = (self.overrides.get(&name), spell_json.as_object_mut())
+		{
+			for spell_override in spell_overrides
+			{
+				fields.insert(spell_override.field.clone(), spell_override.value.clone());
+			}
+		}
+	}
+
+	// Reads the json of a spell file (with any csv tables loaded into it) with its overrides and the fields for the
+	// database's locale (if it has one)
+	fn read_spell_json(&self, file_path: &str) -> Result<Value, Box<dyn Error + Send + Sync>>
+	{
+		let mut spell_json = serde_json::from_str(&fs::read_to_string(file_path)?)?;
+		spells::load_csv_tables(&mut spell_json, file_path)?;
+		self.apply_overrides(&mut spell_json);
+		if let Some(locale) = &self.locale { spells::localize_spell_json(&mut spell_json, locale); }
+		Ok(spell_json)
+	}
+
+	// Adds a spell to the database and makes it the spell that gets used for its name if it has a higher priority than
+	// the spell that currently does
+	fn add_entry(&mut self, entry: SpellEntry)
+	{
+		let entry_index = self.entries.len();
+		let name = normalize_spell_name(&entry.header.name);
+		match self.index.get(&name)
+		{
+			Some(index) if self.entries[*index].priority >= entry.priority => (),
+			_ => { self.index.insert(name, entry_index); }
+		}
+		self.entries.push(entry);
+	}
+
+	// Gets the whole spell of an entry, reading it from its file if it hasn't been yet
+	fn load(&self, entry_index: usize) -> Result<&spells::Spell, Box<dyn Error + Send + Sync>>
+	{
+		let entry = &self.entries[entry_index];
+		if let Some(spell) = entry.spell.get() { return Ok(spell); }
+		// Entries without a file always have their spell already
+		let spell = match &entry.file_path
+		{
+			Some(file_path) => serde_json::from_value(self.read_spell_json(file_path)?)?,
+			None => unreachable!()
+		};
+		Ok(entry.spell.get_or_init(|| spell))
+	}
+
+	/// Gets the spell with a certain name.
+	///
+	/// # Output
+	///
+	/// - `Ok` The spell with the name (`None` if there isn't one).
+	/// - `Err` Any errors that occurred while reading the spell's file.
+	pub fn get(&self, name: &str) -> Result<Option<&spells::Spell>, Box<dyn Error + Send + Sync>>
+	{
+		match self.index.get(&normalize_spell_name(name))
+		{
+			Some(index) => Ok(Some(self.load(*index)?)),
+			None => Ok(None)
+		}
+	}
+
+	/// Gets the header of the spell with a certain name without reading the rest of the spell (`None` if there isn't
+	/// one).
+	pub fn get_header(&self, name: &str) -> Option<&SpellHeader>
+	{
+		self.index.get(&normalize_spell_name(name)).map(|index| &self.entries[*index].header)
+	}
+
+	/// Gets the spells with each of the given names in the same order.
+	///
+	/// # Output
+	///
+	/// - `Ok` The spells with each name.
+	/// - `Err` An `UnresolvedSpellNamesError` listing every name that isn't in the database, or any errors that
+	///   occurred while reading the spell files.
+	pub fn get_all(&self, names: &[&str]) -> Result<Vec<spells::Spell>, Box<dyn Error + Send + Sync>>
+	{
+		let mut unresolved_names = Vec::new();
+		let mut entry_indices = Vec::with_capacity(names.len());
+		for name in names
+		{
+			match self.index.get(&normalize_spell_name(name))
+			{
+				Some(index) => entry_indices.push(*index),
+				None => unresolved_names.push(name.to_string())
+			}
+		}
+		if !unresolved_names.is_empty() { return Err(Box::new(UnresolvedSpellNamesError(unresolved_names))); }
+		entry_indices.into_iter().map(|index| self.load(index).cloned()).collect()
+	}
+
+	/// Iterates over the headers of the spells in the database in the order they were added (skipping spells that
+	/// are overridden by other spells with the same name).
+	pub fn headers(&self) -> impl Iterator<Item = &SpellHeader>
+	{
+		self.used_entry_indices().map(|index| &self.entries[index].header)
+	}
+
+	/// Iterates over the headers of the spells of a certain level.
+	pub fn headers_with_level(&self, level: Level) -> impl Iterator<Item = &SpellHeader>
+	{
+		self.headers().filter(move |header| header.level == SpellField::Controlled(level))
+	}
+
+	/// Iterates over the headers of the spells of a certain school of magic.
+	pub fn headers_with_school(&self, school: MagicSchool) -> impl Iterator<Item = &SpellHeader>
+	{
+		let school = SpellField::Controlled(school);
+		self.headers().filter(move |header| header.school == school)
+	}
+
+	/// Iterates over the headers of the spells with a certain rarity.
+	pub fn headers_with_rarity(&self, rarity: Rarity) -> impl Iterator<Item = &SpellHeader>
+	{
+		self.headers().filter(move |header| header.rarity == Some(rarity))
+	}
+
+	/// Gets every spell whose header matches a condition (in the order they were added). Only the spells that match
+	/// get read from their files.
+	///
+	/// # Output
+	///
+	/// - `Ok` The spells that match the condition.
+	/// - `Err` Any errors that occurred while reading the spell files.
+	pub fn filter<F>(&self, condition: F) -> Result<Vec<spells::Spell>, Box<dyn Error + Send + Sync>>
+	where F: Fn(&SpellHeader) -> bool
+	{
+		self.used_entry_indices()
+			.filter(|index| condition(&self.entries[*index].header))
+			.map(|index| self.load(index).cloned())
+			.collect()
+	}
+
+	// Iterates over the indices of the entries that get used for their names in the order they were added
+	fn used_entry_indices(&self) -> impl Iterator<Item = usize> + '_
+	{
+		(0..self.entries.len()).filter(|index|
+		{
+			self.index.get(&normalize_spell_name(&self.entries[*index].header.name)) == Some(index)
+		})
+	}
+
+	/// The number of spells in the database (including spells with duplicate names).
+	pub fn len(&self) -> usize { self.entries.len() }
+
+	/// Whether or not the database has no spells in it.
+	pub fn is_empty(&self) -> bool { self.entries.is_empty() }
+}
+
+// Gets the file path of every json file in a folder in alphabetical order (so which file gets used when files in a
+// folder conflict doesn't depend on the file system)
+fn json_file_paths(folder_path: &str) -> Result<Vec<String>, Box<dyn Error + Send + Sync>>
+{
+	let mut file_paths = Vec::new();
+	for file_path in fs::read_dir(folder_path)?
+	{
+		let file_path = file_path?.path();
+		let file_path = match file_path.to_str()
+		{
+			Some(file_path) => file_path.to_string(),
+			None => return Err(Box::new(SpellFileNameReadError))
+		};
+		if file_path.ends_with(".json") { file_paths.push(file_path); }
+	}
+	file_paths.sort();
+	Ok(file_paths)
+}
+
+/// Turns a spell name into the form spell databases look it up by. Gets rid of apostrophes, makes it lowercase, and
+/// turns every run of other characters that aren't letters or numbers into a single space.
+pub fn normalize_spell_name(name: &str) -> String
+{
+	name.chars()
+		.filter(|c| *c != '\'' && *c != '\u{2019}')
+		.map(|c| if c.is_alphanumeric() { c } else { ' ' })
+		.collect::<String>()
+		.split_whitespace()
+		.map(str::to_lowercase)
+		.collect::<Vec<_>>()
+		.join(" ")
+}
+
+/// Error for when spells with certain names couldn't be found in a spell database.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct UnresolvedSpellNamesError(pub Vec<String>);
+// Makes the struct displayable
+impl fmt::Display for UnresolvedSpellNamesError
+{
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
+	{
+		write!(f, "Couldn't find these spells: {}.", self.0.join(", "))
+	}
+}
+// Makes the struct officially an error
+impl Error for UnresolvedSpellNamesError {}
+
+/// A field of a spell that more than one override file replaced with different values.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct OverrideConflict
+{
+	/// The name of the spell that the field belongs to.
+	pub spell_name: String,
+	/// The name of the field.
+	pub field: String,
+	/// The override file whose value for the field got replaced.
+	pub replaced_file_path: String,
+	/// The override file whose value for the field gets used.
+	pub used_file_path: String
+}
+// Makes the struct displayable
+impl fmt::Display for OverrideConflict
+{
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
+	{
+		write!
+		(
+			f,
+			"The \"{}\" field of {} in {} got replaced by the one in {}.",
+			self.field,
+			self.spell_name,
+			self.replaced_file_path,
+			self.used_file_path
+		)
+	}
+}
+
+/// Error for when an override file has something other than objects with the name of the spell they change. Holds the
+/// file path of the override file.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct InvalidOverrideFileError(pub String);
+// Makes the struct displayable
+impl fmt::Display for InvalidOverrideFileError
+{
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
+	{
+		write!(f, "Every override in {} needs the name of the spell it changes.", self.0)
+	}
+}
+// Makes the struct officially an error
+impl Error for InvalidOverrideFileError {}
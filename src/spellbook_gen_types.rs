@@ -4,20 +4,37 @@
 //
 //////////////////////////////////////////////////////////////////////////////////////////////////////////////
 
-use std::fs;
 use std::error::Error;
-use std::fmt;
+use std::borrow::Cow;
+use std::sync::Arc;
 
 pub use image::DynamicImage;
-pub use rusttype::{Font, Scale, point};
-pub use printpdf::{PdfDocumentReference, IndirectFontRef, Color, Rgb};
+use image::GenericImageView;
+pub use rusttype::{Font, Scale};
+pub use printpdf::{PdfDocumentReference, IndirectFontRef, Color, Rgb, Pt};
+use printpdf::ImageXObject;
+// Positions and sizes on pages are in `Mm` and font sizes and line thicknesses are in `Pt` (`Mm` is re-exported from
+// `spellbook_options`). Values only get unwrapped into raw numbers when they're handed to code that doesn't know
+// about units (like the layout module) so that the two never get mixed up.
 
 pub use crate::spellbook_options::*;
+use crate::spellbook_options::{srgb_to_linear, linear_to_srgb};
+use crate::layout::{TextMeasurer, TextLine};
+use crate::kerning::KerningTable;
+use crate::ligatures::LigatureTable;
+use crate::shaping::{TextShaper, ShapingError, needs_shaping};
+use crate::glyph_fallbacks::FontGlyphFallbacks;
+use crate::image_cache::{ImageCache, CachedImage};
+use crate::utils::load_font_bytes;
 
 pub const SPACE: &str = " ";
+// How many points across and down an area of a page get sampled for the average color of a background image in it
+const BACKGROUND_SAMPLE_COUNT: usize = 48;
+// How many printpdf Mm are in an inch
+const MM_PER_INCH: f32 = 25.4;
 
 /// Converts rgb byte values into a `printpdf::Color` struct.
-fn bytes_to_color(rgb: &(u8, u8, u8)) -> Color
+pub fn bytes_to_color(rgb: &(u8, u8, u8)) -> Color
 {
 	const BYTE_MAX: f32 = 255.0;
 	Color::Rgb(Rgb::new
@@ -29,6 +46,18 @@ fn bytes_to_color(rgb: &(u8, u8, u8)) -> Color
 	))
 }
 
+/// Converts a `printpdf::Color` struct into rgb byte values (black for colors that aren't RGB or greyscale).
+pub(crate) fn color_to_bytes(color: &Color) -> (u8, u8, u8)
+{
+	let byte = |value: f32| (value.clamp(0.0, 1.0) * 255.0).round() as u8;
+	match color
+	{
+		Color::Rgb(rgb) => (byte(rgb.r), byte(rgb.g), byte(rgb.b)),
+		Color::Greyscale(grey) => (byte(grey.percent), byte(grey.percent), byte(grey.percent)),
+		_ => (0, 0, 0)
+	}
+}
+
 /// Conveys the type of text that is being used.
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
 #[repr(usize)]
@@ -73,6 +102,117 @@ pub struct FontSizeData<'a>
 	pub bold_italic: Font<'a>
 }
 
+/// Holds the kerning data for each font type of a font.
+#[derive(Clone, Debug, PartialEq)]
+pub struct FontKerning
+{
+	pub regular: KerningTable,
+	pub bold: KerningTable,
+	pub italic: KerningTable,
+	pub bold_italic: KerningTable
+}
+
+impl FontKerning
+{
+	/// Reads the kerning data for each font type from the bytes of their font files.
+	pub fn from_font_bytes(font_bytes: &FontBytes) -> Self
+	{
+		Self
+		{
+			regular: KerningTable::from_font_bytes(&font_bytes.regular),
+			bold: KerningTable::from_font_bytes(&font_bytes.bold),
+			italic: KerningTable::from_font_bytes(&font_bytes.italic),
+			bold_italic: KerningTable::from_font_bytes(&font_bytes.bold_italic)
+		}
+	}
+
+	/// Returns the kerning data for a specific font variant.
+	pub fn get_for(&self, font_variant: FontVariant) -> &KerningTable
+	{
+		match font_variant
+		{
+			FontVariant::Regular => &self.regular,
+			FontVariant::Bold => &self.bold,
+			FontVariant::Italic => &self.italic,
+			FontVariant::BoldItalic => &self.bold_italic
+		}
+	}
+}
+
+/// Holds the standard ligatures for each font type of a font.
+#[derive(Clone, Debug, PartialEq)]
+pub struct FontLigatures
+{
+	pub regular: LigatureTable,
+	pub bold: LigatureTable,
+	pub italic: LigatureTable,
+	pub bold_italic: LigatureTable
+}
+
+impl FontLigatures
+{
+	/// Reads the standard ligatures for each font type from the bytes of their font files.
+	pub fn from_font_bytes(font_bytes: &FontBytes) -> Self
+	{
+		Self
+		{
+			regular: LigatureTable::from_font_bytes(&font_bytes.regular),
+			bold: LigatureTable::from_font_bytes(&font_bytes.bold),
+			italic: LigatureTable::from_font_bytes(&font_bytes.italic),
+			bold_italic: LigatureTable::from_font_bytes(&font_bytes.bold_italic)
+		}
+	}
+
+	/// Returns the ligatures for a specific font variant.
+	pub fn get_for(&self, font_variant: FontVariant) -> &LigatureTable
+	{
+		match font_variant
+		{
+			FontVariant::Regular => &self.regular,
+			FontVariant::Bold => &self.bold,
+			FontVariant::Italic => &self.italic,
+			FontVariant::BoldItalic => &self.bold_italic
+		}
+	}
+}
+
+/// Holds text shapers for each font type of a font.
+#[derive(Clone, Debug, PartialEq)]
+pub struct FontShapers
+{
+	pub regular: TextShaper,
+	pub bold: TextShaper,
+	pub italic: TextShaper,
+	pub bold_italic: TextShaper
+}
+
+impl FontShapers
+{
+	/// Makes text shapers for each font type from the bytes of their font files.
+	pub fn from_font_bytes(font_bytes: &FontBytes) -> Result<Self, ShapingError>
+	{
+		Ok(Self
+		{
+			regular: TextShaper::from_font_bytes(&font_bytes.regular)?,
+			bold: TextShaper::from_font_bytes(&font_bytes.bold)?,
+			italic: TextShaper::from_font_bytes(&font_bytes.italic)?,
+			bold_italic: TextShaper::from_font_bytes(&font_bytes.bold_italic)?
+		})
+	}
+
+	/// Returns the text shaper for a specific font variant.
+	pub fn get_for(&self, font_variant: FontVariant) -> &TextShaper
+	{
+		match font_variant
+		{
+			FontVariant::Regular => &self.regular,
+			FontVariant::Bold => &self.bold,
+			FontVariant::Italic => &self.italic,
+			FontVariant::BoldItalic => &self.bold_italic
+		}
+	}
+}
+
 /// Holds scale size data for each type of text.
 #[derive(Clone, Debug, PartialEq)]
 pub struct FontScales
@@ -127,12 +267,32 @@ pub struct FontData<'a>
 	size_data: FontSizeData<'a>,
 	scales: FontScales,
 	spacing_options: SpacingOptions,
-	text_colors: TextColors
+	text_colors: TextColors,
+	font_sources: [(FontVariant, SyntheticStyle); FONTVARIANT_VARIANTS],
+	kerning: Option<FontKerning>,
+	ligatures: Option<FontLigatures>,
+	shaping: Option<FontShapers>,
+	glyph_fallbacks: FontGlyphFallbacks,
+	display_font: Option<DisplayFontData<'a>>
+}
+
+/// Holds the data for a display font that title text and spell names use instead of the main font.
+#[derive(Clone, Debug)]
+pub struct DisplayFontData<'a>
+{
+	font_refs: FontRefs,
+	scalars: FontScalars,
+	size_data: FontSizeData<'a>,
+	font_sources: [(FontVariant, SyntheticStyle); FONTVARIANT_VARIANTS],
+	kerning: Option<FontKerning>,
+	ligatures: Option<FontLigatures>,
+	shaping: Option<FontShapers>,
+	glyph_fallbacks: FontGlyphFallbacks
 }
 
 /// Error for when font size data couldn't be converted from bytes read from a font file to an object in rust.
 #[derive(Clone, Debug, PartialEq, Eq)]
-pub struct BytesToFontSizeDataConversionError(String);
+pub struct BytesToFontSizeDataConversionError(pub(crate) String);
 
 impl std::fmt::Display for BytesToFontSizeDataConversionError
 {
@@ -155,6 +315,9 @@ impl <'a> FontData<'a>
 	/// - `font_scalars` Scalar values for each font variant so their sizes can be calculated correctly.
 	/// - `spacing_options` Tab sizes and newline sizes for each type of text.
 	/// - `text_colors` RGB color values for each type of text.
+	/// - `additional_options` Optional features. Only `kerning`, `ligatures`, and `shaping` get used, which read the
+	///   kerning data, standard ligatures, and shaping data (for scripts like Arabic and Devanagari) from the font
+	///   files so text gets measured and written with them.
 	pub fn new
 	(
 		doc: &PdfDocumentReference,
@@ -162,58 +325,44 @@ impl <'a> FontData<'a>
 		font_sizes: FontSizes,
 		font_scalars: FontScalars,
 		spacing_options: SpacingOptions,
-		text_colors: TextColorOptions
+		text_colors: TextColorOptions,
+		additional_options: &AdditionalOptions
 	)
-	-> Result<Self, Box<dyn std::error::Error>>
+	-> Result<Self, Box<dyn std::error::Error + Send + Sync>>
 	{
-		// Read the data from the font files
-		let regular_font_bytes = fs::read(&font_paths.regular)?;
-		let bold_font_bytes = fs::read(&font_paths.bold)?;
-		let italic_font_bytes = fs::read(&font_paths.italic)?;
-		let bold_italic_font_bytes = fs::read(&font_paths.bold_italic)?;
-
-		// Put the bytes into a struct to reuse them if new font refs need to be created when a new pdf document is created.
-		let font_bytes = FontBytes
-		{
-			regular: regular_font_bytes.clone(),
-			bold: bold_font_bytes.clone(),
-			italic: italic_font_bytes.clone(),
-			bold_italic: bold_italic_font_bytes.clone()
-		};
-
-		// Create font size data for each font variant
-		let regular_font_size_data = match Font::try_from_vec(regular_font_bytes.clone())
-		{
-			Some(d) => d,
-			None => return Err(Box::new(BytesToFontSizeDataConversionError(String::from
-				("Could not convert regular font size data from bytes."))))
-		};
-		let bold_font_size_data = match Font::try_from_vec(bold_font_bytes.clone())
-		{
-			Some(d) => d,
-			None => return Err(Box::new(BytesToFontSizeDataConversionError(String::from
-				("Could not convert bold font size data from bytes."))))
-		};
-		let italic_font_size_data = match Font::try_from_vec(italic_font_bytes.clone())
-		{
-			Some(d) => d,
-			None => return Err(Box::new(BytesToFontSizeDataConversionError(String::from
-				("Could not convert italic font size data from bytes."))))
-		};
-		let bold_italic_font_size_data = match Font::try_from_vec(bold_italic_font_bytes.clone())
-		{
-			Some(d) => d,
-			None => return Err(Box::new(BytesToFontSizeDataConversionError(String::from
-				("Could not convert bold italic font size data from bytes."))))
-		};
-
-		// Combine all size data into one struct
-		let size_data = FontSizeData
+		let kerning = additional_options.kerning;
+		let ligatures = additional_options.ligatures;
+		let shaping = additional_options.shaping;
+		// Read the font files and add the fonts to the document
+		let main_paths = [&font_paths.regular, &font_paths.bold, &font_paths.italic, &font_paths.bold_italic];
+		let main_axes = font_paths.variable_axes.as_ref();
+		let (font_bytes, size_data, font_refs, font_sources) = load_font_family(doc, main_paths, main_axes, "")?;
+		let main_kerning = if kerning { Some(FontKerning::from_font_bytes(&font_bytes)) } else { None };
+		let main_ligatures = if ligatures { Some(FontLigatures::from_font_bytes(&font_bytes)) } else { None };
+		let main_shaping = if shaping { Some(FontShapers::from_font_bytes(&font_bytes)?) } else { None };
+		let main_glyph_fallbacks = FontGlyphFallbacks::from_size_data(&size_data);
+		// Do the same for the display font if there is one
+		let display_font = match &font_paths.display
 		{
-			regular: regular_font_size_data,
-			bold: bold_font_size_data,
-			italic: italic_font_size_data,
-			bold_italic: bold_italic_font_size_data
+			Some(display) =>
+			{
+				let display_paths = [&display.regular, &display.bold, &display.italic, &display.bold_italic];
+				let display_axes = display.variable_axes.as_ref();
+				let (display_bytes, size_data, font_refs, font_sources) =
+					load_font_family(doc, display_paths, display_axes, "display ")?;
+				Some(DisplayFontData
+				{
+					font_refs: font_refs,
+					scalars: display.scalars,
+					glyph_fallbacks: FontGlyphFallbacks::from_size_data(&size_data),
+					size_data: size_data,
+					font_sources: font_sources,
+					kerning: if kerning { Some(FontKerning::from_font_bytes(&display_bytes)) } else { None },
+					ligatures: if ligatures { Some(FontLigatures::from_font_bytes(&display_bytes)) } else { None },
+					shaping: if shaping { Some(FontShapers::from_font_bytes(&display_bytes)?) } else { None }
+				})
+			},
+			None => None
 		};
 
 		// Create font scale objects for each font size
@@ -233,21 +382,6 @@ impl <'a> FontData<'a>
 			table_body: table_body_font_scale
 		};
 
-		// Add all custom font variants to the document and get references to them
-		let regular_font_ref = doc.add_external_font(&*regular_font_bytes)?;
-		let bold_font_ref = doc.add_external_font(&*bold_font_bytes)?;
-		let italic_font_ref = doc.add_external_font(&*italic_font_bytes)?;
-		let bold_italic_font_ref = doc.add_external_font(&*bold_italic_font_bytes)?;
-
-		// Combine all font references into one struct
-		let font_refs = FontRefs
-		{
-			regular: regular_font_ref,
-			italic: italic_font_ref,
-			bold: bold_font_ref,
-			bold_italic: bold_italic_font_ref
-		};
-
 		// Construct and return
 		Ok(Self
 		{
@@ -261,7 +395,13 @@ impl <'a> FontData<'a>
 			size_data: size_data,
 			scales: scales,
 			spacing_options: spacing_options,
-			text_colors: TextColors::from(text_colors)
+			text_colors: TextColors::from(text_colors),
+			font_sources: font_sources,
+			kerning: main_kerning,
+			ligatures: main_ligatures,
+			shaping: main_shaping,
+			glyph_fallbacks: main_glyph_fallbacks,
+			display_font: display_font
 		})
 	}
 
@@ -272,12 +412,13 @@ impl <'a> FontData<'a>
 	pub fn bytes(&self) -> &FontBytes { &self.font_bytes }
 	pub fn all_font_refs(&self) -> &FontRefs { &self.font_refs }
 	// pub fn all_font_sizes(&self) -> &FontSizes { &self.font_sizes }
-	pub fn all_scalars(&self) -> &FontScalars { &self.scalars }
+	// pub fn all_scalars(&self) -> &FontScalars { &self.scalars }
 	// pub fn all_size_data(&self) -> &FontSizeData { &self.size_data }
 	// pub fn all_scales(&self) -> &FontScales { &self.scales }
 	// pub fn all_spacing_options(&self) -> &SpacingOptions { &self.spacing_options }
 	// pub fn all_text_colors(&self) -> &TextColors { &self.text_colors }
-	pub fn tab_amount(&self) -> f32 { self.spacing_options.tab_amount() }
+	pub fn tab_amount(&self) -> Mm { Mm(self.spacing_options.tab_amount()) }
+	pub fn bullet_indent(&self) -> Option<Mm> { self.spacing_options.bullet_indent().map(Mm) }
 
 	// /// Returns a vec of bytes that were used to construct certain fields for a specific font variant.
 	// pub fn get_bytes_for(&self, font_variant: FontVariant) -> &Vec<u8>
@@ -315,90 +456,199 @@ impl <'a> FontData<'a>
 	// 	}
 	// }
 
+	/// Returns the font refs, size data, scalars, and font sources (see `synthetic_font_sources()`) of the font family
+	/// that a specific text type uses (the display font for title text and spell names if there is one, otherwise the
+	/// main font).
+	fn family_for(&self, text_type: TextType)
+	-> (&FontRefs, &FontSizeData<'a>, &FontScalars, &[(FontVariant, SyntheticStyle); FONTVARIANT_VARIANTS])
+	{
+		match (&self.display_font, text_type)
+		{
+			(Some(display), TextType::Title | TextType::Header) =>
+				(&display.font_refs, &display.size_data, &display.scalars, &display.font_sources),
+			_ => (&self.font_refs, &self.size_data, &self.scalars, &self.font_sources)
+		}
+	}
+
+	/// Returns the kerning data for a specific font variant of the font a specific text type uses (`None` if text isn't
+	/// being kerned).
+	pub fn get_kerning_for(&self, text_type: TextType, font_variant: FontVariant) -> Option<&KerningTable>
+	{
+		let kerning = match (&self.display_font, text_type)
+		{
+			(Some(display), TextType::Title | TextType::Header) => &display.kerning,
+			_ => &self.kerning
+		};
+		kerning.as_ref().map(|kerning| kerning.get_for(font_variant))
+	}
+
+	/// Returns the kerning data for the current font variant being used (`None` if text isn't being kerned).
+	pub fn current_kerning(&self) -> Option<&KerningTable>
+	{
+		self.get_kerning_for(self.current_text_type, self.current_font_variant)
+	}
+
+	/// Returns the standard ligatures for a specific font variant of the font a specific text type uses (`None` if
+	/// ligatures aren't being used).
+	pub fn get_ligatures_for(&self, text_type: TextType, font_variant: FontVariant) -> Option<&LigatureTable>
+	{
+		let ligatures = match (&self.display_font, text_type)
+		{
+			(Some(display), TextType::Title | TextType::Header) => &display.ligatures,
+			_ => &self.ligatures
+		};
+		ligatures.as_ref().map(|ligatures| ligatures.get_for(font_variant))
+	}
+
+	/// Returns the standard ligatures for the current font variant being used (`None` if ligatures aren't being used).
+	pub fn current_ligatures(&self) -> Option<&LigatureTable>
+	{
+		self.get_ligatures_for(self.current_text_type, self.current_font_variant)
+	}
+
+	/// Returns the text shaper for a specific font variant of the font a specific text type uses (`None` if text isn't
+	/// being shaped).
+	pub fn get_shaper_for(&self, text_type: TextType, font_variant: FontVariant) -> Option<&TextShaper>
+	{
+		let shaping = match (&self.display_font, text_type)
+		{
+			(Some(display), TextType::Title | TextType::Header) => &display.shaping,
+			_ => &self.shaping
+		};
+		shaping.as_ref().map(|shaping| shaping.get_for(font_variant))
+	}
+
+	/// Returns text with the characters that a specific font variant of the font a specific text type uses doesn't
+	/// have (like bullet points and dashes) replaced with plainer text (see `GLYPH_FALLBACKS`).
+	pub fn fallback_text_for<'t>(&self, text: &'t str, text_type: TextType, font_variant: FontVariant) -> Cow<'t, str>
+	{
+		let glyph_fallbacks = match (&self.display_font, text_type)
+		{
+			(Some(display), TextType::Title | TextType::Header) => &display.glyph_fallbacks,
+			_ => &self.glyph_fallbacks
+		};
+		glyph_fallbacks.get_for(font_variant).apply(text)
+	}
+
+	/// Returns text with the characters that the current font variant being used doesn't have replaced with plainer
+	/// text.
+	pub fn current_fallback_text<'t>(&self, text: &'t str) -> Cow<'t, str>
+	{
+		self.fallback_text_for(text, self.current_text_type, self.current_font_variant)
+	}
+
+	/// Returns the text shaper for the current font variant being used (`None` if text isn't being shaped).
+	pub fn current_shaper(&self) -> Option<&TextShaper>
+	{
+		self.get_shaper_for(self.current_text_type, self.current_font_variant)
+	}
+
 	/// Returns the font ref to the current font variant bring used.
 	pub fn current_font_ref(&self) -> &IndirectFontRef
 	{
+		let (font_refs, _, _, _) = self.family_for(self.current_text_type);
 		match self.current_font_variant
 		{
-			FontVariant::Regular => &self.font_refs.regular,
-			FontVariant::Bold => &self.font_refs.bold,
-			FontVariant::Italic => &self.font_refs.italic,
-			FontVariant::BoldItalic => &self.font_refs.bold_italic
+			FontVariant::Regular => &font_refs.regular,
+			FontVariant::Bold => &font_refs.bold,
+			FontVariant::Italic => &font_refs.italic,
+			FontVariant::BoldItalic => &font_refs.bold_italic
 		}
 	}
 
-	// /// Returns the font size of a specific text type.
-	// pub fn get_font_size_for(&self, text_type: TextType) -> f32
-	// {
-	// 	match text_type
-	// 	{
-	// 		TextType::Title => self.font_sizes.title_font_size(),
-	// 		TextType::Header => self.font_sizes.header_font_size(),
-	// 		TextType::Body => self.font_sizes.body_font_size(),
-	// 		TextType::TableTitle => self.font_sizes.table_title_font_size(),
-	// 		TextType::TableBody => self.font_sizes.table_body_font_size()
-	// 	}
-	// }
-
-	/// Returns the font size of the current text type bring used.
-	pub fn current_font_size(&self) -> f32
+	/// Returns the font size of a specific text type.
+	pub fn get_font_size_for(&self, text_type: TextType) -> Pt
 	{
-		match self.current_text_type
+		Pt(match text_type
 		{
 			TextType::Title => self.font_sizes.title_font_size(),
 			TextType::Header => self.font_sizes.header_font_size(),
 			TextType::Body => self.font_sizes.body_font_size(),
 			TextType::TableTitle => self.font_sizes.table_title_font_size(),
 			TextType::TableBody => self.font_sizes.table_body_font_size()
-		}
+		})
 	}
 
-	/// Returns the scalar value for a specific font variant.
-	pub fn get_scalar_for(&self, font_variant: FontVariant) -> f32
+	/// Returns the font size of the current text type bring used.
+	pub fn current_font_size(&self) -> Pt
 	{
-		match font_variant
+		Pt(match self.current_text_type
 		{
-			FontVariant::Regular => self.scalars.regular_scalar(),
-			FontVariant::Bold => self.scalars.bold_scalar(),
-			FontVariant::Italic => self.scalars.italic_scalar(),
-			FontVariant::BoldItalic => self.scalars.bold_italic_scalar()
-		}
+			TextType::Title => self.font_sizes.title_font_size(),
+			TextType::Header => self.font_sizes.header_font_size(),
+			TextType::Body => self.font_sizes.body_font_size(),
+			TextType::TableTitle => self.font_sizes.table_title_font_size(),
+			TextType::TableBody => self.font_sizes.table_body_font_size()
+		})
 	}
 
-	/// Returns the scalar value for the current font variant being used.
-	pub fn current_scalar(&self) -> f32
+	/// Returns the scalar value for a specific font variant of the font a specific text type uses (synthesized font
+	/// variants use the scalar of the font variant they're made from).
+	pub fn get_scalar_for(&self, text_type: TextType, font_variant: FontVariant) -> f32
 	{
-		match self.current_font_variant
+		let (_, _, scalars, font_sources) = self.family_for(text_type);
+		match font_sources[font_variant as usize].0
 		{
-			FontVariant::Regular => self.scalars.regular_scalar(),
-			FontVariant::Bold => self.scalars.bold_scalar(),
-			FontVariant::Italic => self.scalars.italic_scalar(),
-			FontVariant::BoldItalic => self.scalars.bold_italic_scalar()
+			FontVariant::Regular => scalars.regular_scalar(),
+			FontVariant::Bold => scalars.bold_scalar(),
+			FontVariant::Italic => scalars.italic_scalar(),
+			FontVariant::BoldItalic => scalars.bold_italic_scalar()
 		}
 	}
 
-	/// Returns size data for a specific font variant.
-	pub fn get_size_data_for(&self, font_variant: FontVariant) -> &Font
+	/// Returns the scalar value for the current font variant being used.
+	pub fn current_scalar(&self) -> f32 { self.get_scalar_for(self.current_text_type, self.current_font_variant) }
+
+	/// Returns size data for a specific font variant of the font a specific text type uses.
+	pub fn get_size_data_for(&self, text_type: TextType, font_variant: FontVariant) -> &Font<'a>
 	{
+		let (_, size_data, _, _) = self.family_for(text_type);
 		match font_variant
 		{
-			FontVariant::Regular => &self.size_data.regular,
-			FontVariant::Bold => &self.size_data.bold,
-			FontVariant::Italic => &self.size_data.italic,
-			FontVariant::BoldItalic => &self.size_data.bold_italic
+			FontVariant::Regular => &size_data.regular,
+			FontVariant::Bold => &size_data.bold,
+			FontVariant::Italic => &size_data.italic,
+			FontVariant::BoldItalic => &size_data.bold_italic
 		}
 	}
 
 	/// Returns the size data for the current font variant being used.
-	pub fn current_size_data(&self) -> &Font
+	pub fn current_size_data(&self) -> &Font<'a>
 	{
-		match self.current_font_variant
+		self.get_size_data_for(self.current_text_type, self.current_font_variant)
+	}
+
+	/// Returns the styles that have to be synthesized for a specific font variant of the font a specific text type
+	/// uses.
+	pub fn get_synthetic_style_for(&self, text_type: TextType, font_variant: FontVariant) -> SyntheticStyle
+	{
+		let (_, _, _, font_sources) = self.family_for(text_type);
+		font_sources[font_variant as usize].1
+	}
+
+	/// Returns the styles that have to be synthesized for the current font variant being used.
+	pub fn current_synthetic_style(&self) -> SyntheticStyle
+	{
+		self.get_synthetic_style_for(self.current_text_type, self.current_font_variant)
+	}
+
+	/// Returns how much wider some text is because of synthetic bold in a specific text type and font variant.
+	pub fn synthetic_extra_width_for(&self, text: &str, text_type: TextType, font_variant: FontVariant) -> Mm
+	{
+		if self.get_synthetic_style_for(text_type, font_variant).bold
 		{
-			FontVariant::Regular => &self.size_data.regular,
-			FontVariant::Bold => &self.size_data.bold,
-			FontVariant::Italic => &self.size_data.italic,
-			FontVariant::BoldItalic => &self.size_data.bold_italic
+			let glyph_count = match self.get_shaper_for(text_type, font_variant)
+			{
+				Some(shaper) if needs_shaping(text) => shaper.shape(text).len(),
+				_ =>
+				{
+					let ligatures = self.get_ligatures_for(text_type, font_variant);
+					text_glyphs(text, self.get_size_data_for(text_type, font_variant), ligatures).len()
+				}
+			};
+			synthetic_bold_extra_width(glyph_count, self.get_font_size_for(text_type))
 		}
+		else { Mm(0.0) }
 	}
 
 	/// Returns the font scale for a specific text type.
@@ -428,29 +678,42 @@ impl <'a> FontData<'a>
 	}
 
 	/// Returns the newline amount for a specific text type.
-	pub fn get_newline_amount_for(&self, text_type: TextType) -> f32
+	pub fn get_newline_amount_for(&self, text_type: TextType) -> Mm
 	{
-		match text_type
+		Mm(match text_type
 		{
 			TextType::Title => self.spacing_options.title_newline_amount(),
 			TextType::Header => self.spacing_options.header_newline_amount(),
 			TextType::Body => self.spacing_options.body_newline_amount(),
 			TextType::TableTitle => self.spacing_options.table_title_newline_amount(),
 			TextType::TableBody => self.spacing_options.table_body_newline_amount()
-		}
+		})
 	}
 
 	/// Returns the newline amount of the current text type being used.
-	pub fn current_newline_amount(&self) -> f32
+	pub fn current_newline_amount(&self) -> Mm
 	{
-		match self.current_text_type
+		Mm(match self.current_text_type
 		{
 			TextType::Title => self.spacing_options.title_newline_amount(),
 			TextType::Header => self.spacing_options.header_newline_amount(),
 			TextType::Body => self.spacing_options.body_newline_amount(),
 			TextType::TableTitle => self.spacing_options.table_title_newline_amount(),
 			TextType::TableBody => self.spacing_options.table_body_newline_amount()
-		}
+		})
+	}
+
+	/// Returns the paragraph tab size of the current text type being used.
+	pub fn current_tab_amount(&self) -> Mm
+	{
+		Mm(match self.current_text_type
+		{
+			TextType::Title => self.spacing_options.title_tab_amount(),
+			TextType::Header => self.spacing_options.header_tab_amount(),
+			TextType::Body => self.spacing_options.body_tab_amount(),
+			TextType::TableTitle => self.spacing_options.table_title_tab_amount(),
+			TextType::TableBody => self.spacing_options.table_body_tab_amount()
+		})
 	}
 
 	// /// Returns the font the RGB values for the font color of a specific text type.
@@ -479,8 +742,13 @@ impl <'a> FontData<'a>
 		}
 	}
 
+	/// Returns the RGB values for the font color of header text.
+	pub fn header_color(&self) -> &Color { &self.text_colors.header_color }
+
 	// Setters
 
+	/// Sets the font color of header text.
+	pub fn set_header_color(&mut self, color: Color) { self.text_colors.header_color = color; }
 	/// Sets the current font variant being used (regular, bold, italic, bold-italic).
 	pub fn set_current_font_variant(&mut self, font_type: FontVariant) { self.current_font_variant = font_type; }
 	/// Sets the current text type of the text.
@@ -492,19 +760,19 @@ impl <'a> FontData<'a>
 pub struct PageSizeData
 {
 	// Entire page dimensions
-	page_width: f32,
-	page_height: f32,
+	page_width: Mm,
+	page_height: Mm,
 	// Left
-	x_min: f32,
+	x_min: Mm,
 	// Right
-	x_max: f32,
+	x_max: Mm,
 	// Bottom
-	y_min: f32,
+	y_min: Mm,
 	// Top
-	y_max: f32,
+	y_max: Mm,
 	// Dimensions that text can fit inside
-	text_width: f32,
-	text_height: f32
+	text_width: Mm,
+	text_height: Mm
 }
 
 /// Allows page limit coordinates to be constructed from the `PageSizeOptions` user input type.
@@ -515,14 +783,14 @@ impl From<PageSizeOptions> for PageSizeData
 	{
 		Self
 		{
-			page_width: data.width(),
-			page_height: data.height(),
-			x_min: data.left_margin(),
-			x_max: data.width() - data.right_margin(),
-			y_min: data.bottom_margin(),
-			y_max: data.height() - data.top_margin(),
-			text_width: data.width() - (data.left_margin() + data.right_margin()),
-			text_height: data.height() - (data.bottom_margin() + data.top_margin())
+			page_width: Mm(data.width()),
+			page_height: Mm(data.height()),
+			x_min: Mm(data.left_margin()),
+			x_max: Mm(data.width() - data.right_margin()),
+			y_min: Mm(data.bottom_margin()),
+			y_max: Mm(data.height() - data.top_margin()),
+			text_width: Mm(data.width() - (data.left_margin() + data.right_margin())),
+			text_height: Mm(data.height() - (data.bottom_margin() + data.top_margin()))
 		}
 	}
 }
@@ -532,19 +800,19 @@ impl PageSizeData
 		// Getters
 
 		// Entire page dimensions
-		pub fn page_width(&self) -> f32 { self.page_width }
-		pub fn page_height(&self) -> f32 { self.page_height }
+		pub fn page_width(&self) -> Mm { self.page_width }
+		pub fn page_height(&self) -> Mm { self.page_height }
 		/// Left
-		pub fn x_min(&self) -> f32 { self.x_min }
+		pub fn x_min(&self) -> Mm { self.x_min }
 		/// Right
-		pub fn x_max(&self) -> f32 { self.x_max }
+		pub fn x_max(&self) -> Mm { self.x_max }
 		/// Bottom
-		pub fn y_min(&self) -> f32 { self.y_min }
+		pub fn y_min(&self) -> Mm { self.y_min }
 		/// Top
-		pub fn y_max(&self) -> f32 { self.y_max }
+		pub fn y_max(&self) -> Mm { self.y_max }
 		// // Dimensions that text can fit inside
-		// pub fn text_width(&self) -> f32 { self.text_width }
-		// pub fn text_height(&self) -> f32 { self.text_height }
+		// pub fn text_width(&self) -> Mm { self.text_width }
+		// pub fn text_height(&self) -> Mm { self.text_height }
 }
 
 /// Holds all page number data needed for writing them into spellbooks.
@@ -569,15 +837,16 @@ impl <'a> PageNumberData<'a>
 	/// - `options` Options for how the page numbers should be displayed.
 	/// - `font_data` Data for how fonts are displayed in the spellbook.
 	pub fn new(options: PageNumberOptions, font_data: &FontData<'_>)
-	-> Result<Self, Box<dyn std::error::Error>>
+	-> Result<Self, Box<dyn std::error::Error + Send + Sync>>
 	{
 		// Gets copies of all of the font data the page numbers need based on the font variant they will use.
+		// Page numbers use the main font, which is the font body text uses.
 		let (font_ref, font_scalar, font_size_data) = match options.font_variant()
 		{
 			FontVariant::Regular =>
 			(
 				font_data.all_font_refs().regular.clone(),
-				font_data.all_scalars().regular_scalar(),
+				font_data.get_scalar_for(TextType::Body, FontVariant::Regular),
 				// Create new font size data for this struct since it has problems with holding references
 				// to font_data's fields
 				match Font::try_from_vec(font_data.bytes().regular.clone())
@@ -590,7 +859,7 @@ impl <'a> PageNumberData<'a>
 			FontVariant::Bold =>
 			(
 				font_data.all_font_refs().bold.clone(),
-				font_data.all_scalars().bold_scalar(),
+				font_data.get_scalar_for(TextType::Body, FontVariant::Bold),
 				// Create new font size data for this struct since it has problems with holding references
 				// to font_data's fields
 				match Font::try_from_vec(font_data.bytes().bold.clone())
@@ -603,7 +872,7 @@ impl <'a> PageNumberData<'a>
 			FontVariant::Italic =>
 			(
 				font_data.all_font_refs().italic.clone(),
-				font_data.all_scalars().italic_scalar(),
+				font_data.get_scalar_for(TextType::Body, FontVariant::Italic),
 				// Create new font size data for this struct since it has problems with holding references
 				// to font_data's fields
 				match Font::try_from_vec(font_data.bytes().italic.clone())
@@ -616,7 +885,7 @@ impl <'a> PageNumberData<'a>
 			FontVariant::BoldItalic =>
 			(
 				font_data.all_font_refs().bold_italic.clone(),
-				font_data.all_scalars().bold_italic_scalar(),
+				font_data.get_scalar_for(TextType::Body, FontVariant::BoldItalic),
 				// Create new font size data for this struct since it has problems with holding references
 				// to font_data's fields
 				match Font::try_from_vec(font_data.bytes().bold_italic.clone())
@@ -650,10 +919,10 @@ impl <'a> PageNumberData<'a>
 	pub fn flips_sides(&self) -> bool { self.options.flips_sides() }
 	// pub fn starting_num(&self) -> i64 { self.options.starting_num() }
 	// pub fn font_variant(&self) -> FontVariant { self.options.font_variant() }
-	pub fn font_size(&self) -> f32 { self.options.font_size() }
-	// pub fn newline_amount(&self) -> f32 { self.options.newline_amount() }
-	pub fn side_margin(&self) -> f32 { self.options.side_margin() }
-	pub fn bottom_margin(&self) -> f32 { self.options.bottom_margin() }
+	pub fn font_size(&self) -> Pt { Pt(self.options.font_size()) }
+	// pub fn newline_amount(&self) -> Mm { Mm(self.options.newline_amount()) }
+	pub fn side_margin(&self) -> Mm { Mm(self.options.side_margin()) }
+	pub fn bottom_margin(&self) -> Mm { Mm(self.options.bottom_margin()) }
 	// pub fn options(&self) -> &PageNumberOptions { &self.options }
 	pub fn current_side(&self) -> HSide { self.current_side }
 	pub fn font_ref(&self) -> &IndirectFontRef { &self.font_ref }
@@ -668,12 +937,15 @@ impl <'a> PageNumberData<'a>
 	pub fn flip_side(&mut self) { self.current_side = !self.current_side; }
 }
 
-/// Holds the background image and the transform data for it (positioning, size, rotation, etc.).
-#[derive(Debug, Clone, PartialEq)]
+/// Holds the background image and the transform data for each copy of it on a page (positioning, size, rotation,
+/// etc.). The image data that goes in the pdf document is only made once and shared by every page.
+#[derive(Debug, Clone)]
 pub struct BackgroundImage
 {
-	image: DynamicImage,
-	transform: ImageTransform
+	image: Arc<DynamicImage>,
+	pdf_image: Arc<ImageXObject>,
+	transforms: Vec<ImageTransform>,
+	alternate_transforms: Vec<ImageTransform>
 }
 
 impl BackgroundImage
@@ -684,36 +956,138 @@ impl BackgroundImage
 	///
 	/// - `image_path` A filepath to an image to use.
 	/// - `transform` Transform data for how the image should be placed on pages (positioning, size, rotation, etc.).
+	/// - `fit` How to automatically size and position the image on pages instead of using `transform`
+	///   (`None` to use `transform`).
+	/// - `alternation` How to change the image on every other page (`None` to keep it the same on every page).
+	/// - `page_width` The width of each page.
+	/// - `page_height` The height of each page.
+	/// - `cache` Images that have already been opened (the image gets added to it if it isn't in it yet).
 	///
 	/// # Output
 	///
 	/// - `Ok` A `BackgroundImage` instance.
 	/// - `Err` Any errors that occured.
-	pub fn new(image_path: &str, transform: ImageTransform) -> Result<Self, Box<dyn Error>>
+	pub fn new
+	(
+		image_path: &str,
+		transform: ImageTransform,
+		fit: Option<BackgroundFit>,
+		alternation: Option<BackgroundAlternation>,
+		page_width: Mm,
+		page_height: Mm,
+		cache: &ImageCache
+	)
+	-> Result<Self, Box<dyn Error + Send + Sync>>
 	{
+		// printpdf uses 300 dpi for images that don't have one
+		let dpi = transform.dpi.unwrap_or(300.0);
+		// Gets the image at the given filepath (rasterizing svg files at that dpi) unless it's already been opened
+		let CachedImage { image, pdf_image } = cache.get_or_load(image_path, &transform)?;
+		let transforms = match fit
+		{
+			Some(fit) => fit.transforms
+			(
+				image.width(),
+				image.height(),
+				dpi,
+				page_width.0,
+				page_height.0
+			),
+			None => vec![transform]
+		};
+		// Figure out where the image goes on pages that get changed
+		let alternate_transforms = match alternation
+		{
+			Some(alternation) => transforms.iter()
+				.map(|transform| alternation.apply(transform, page_width.0, page_height.0))
+				.collect(),
+			None => transforms.clone()
+		};
 		// Construct and return
 		Ok(Self
 		{
-			// Constructs a `image::DynamicImage` from the file at the given filepath
-			image: image::open(image_path)?,
-			transform: transform
+			image: image,
+			pdf_image: pdf_image,
+			transforms: transforms,
+			alternate_transforms: alternate_transforms
 		})
 	}
 
 	// Getters
 
-	pub fn image(&self) -> &DynamicImage { &self.image }
-	pub fn transform(&self) -> &ImageTransform { &self.transform }
+	// pub fn image(&self) -> &DynamicImage { &self.image }
+	pub fn pdf_image(&self) -> &ImageXObject { &self.pdf_image }
+	// pub fn transforms(&self) -> &Vec<ImageTransform> { &self.transforms }
+	// pub fn alternate_transforms(&self) -> &Vec<ImageTransform> { &self.alternate_transforms }
+
+	/// Returns the transforms for the image on the page at the given index.
+	pub fn transforms_for_page(&self, page_index: usize) -> &Vec<ImageTransform>
+	{
+		// Pages with odd indexes are the left-hand pages of a printed book
+		if page_index % 2 == 1 { &self.alternate_transforms }
+		else { &self.transforms }
+	}
+
+	/// Returns the average color of the image over an area of the pages (on both the right-hand and left-hand pages).
+	/// Transparent parts of the image and parts of the area that the image doesn't cover count as the white of the
+	/// page. Copies of the image that are rotated aren't sampled.
+	pub fn average_color(&self, x_min: Mm, y_min: Mm, x_max: Mm, y_max: Mm) -> (u8, u8, u8)
+	{
+		let mut total = [0.0; 3];
+		let mut sample_count = 0;
+		for page_index in 0..2
+		{
+			for row in 0..BACKGROUND_SAMPLE_COUNT
+			{
+				for column in 0..BACKGROUND_SAMPLE_COUNT
+				{
+					// Sample the middle of each cell of a grid over the area
+					let x = x_min + (x_max - x_min) * ((column as f32 + 0.5) / BACKGROUND_SAMPLE_COUNT as f32);
+					let y = y_min + (y_max - y_min) * ((row as f32 + 0.5) / BACKGROUND_SAMPLE_COUNT as f32);
+					let color = self.color_at(page_index, x, y);
+					// Average the colors as light so dark and light spots balance out like they do to the eye
+					for channel in 0..3 { total[channel] += srgb_to_linear(color[channel]); }
+					sample_count += 1;
+				}
+			}
+		}
+		let channel = |total: f32| (linear_to_srgb(total / sample_count as f32) * 255.0).round() as u8;
+		(channel(total[0]), channel(total[1]), channel(total[2]))
+	}
+
+	/// Returns the sRGB color (0.0 to 1.0) of the pixel at a point on a page, blended with the white of the page.
+	fn color_at(&self, page_index: usize, x: Mm, y: Mm) -> [f32; 3]
+	{
+		// Later copies of the image go on top of earlier ones
+		for transform in self.transforms_for_page(page_index).iter().rev()
+		{
+			if transform.rotate.is_some() { continue; }
+			let dpi = transform.dpi.unwrap_or(300.0);
+			let width = self.image.width() as f32 / dpi * MM_PER_INCH * transform.scale_x.unwrap_or(1.0);
+			let height = self.image.height() as f32 / dpi * MM_PER_INCH * transform.scale_y.unwrap_or(1.0);
+			// How far across the image the point is (negative scales flip the image over its bottom left corner)
+			let u = (x - transform.translate_x.unwrap_or(Mm(0.0))).0 / width;
+			let v = (y - transform.translate_y.unwrap_or(Mm(0.0))).0 / height;
+			if !(0.0..1.0).contains(&u) || !(0.0..1.0).contains(&v) { continue; }
+			// Pixel rows go from the top of the image down
+			let pixel_x = ((u * self.image.width() as f32) as u32).min(self.image.width() - 1);
+			let pixel_y = (((1.0 - v) * self.image.height() as f32) as u32).min(self.image.height() - 1);
+			let pixel = self.image.get_pixel(pixel_x, pixel_y);
+			let alpha = pixel[3] as f32 / 255.0;
+			return [0, 1, 2].map(|channel| pixel[channel] as f32 / 255.0 * alpha + 1.0 - alpha);
+		}
+		[1.0; 3]
+	}
 }
 
 /// Holds the extra data needed for making tables inside of spellbooks.
 #[derive(Clone, Debug, PartialEq)]
 pub struct TableData
 {
-	horizontal_cell_margin: f32,
-	vertical_cell_margin: f32,
-	outer_horizontal_margin: f32,
-	outer_vertical_margin: f32,
+	horizontal_cell_margin: Mm,
+	vertical_cell_margin: Mm,
+	outer_horizontal_margin: Mm,
+	outer_vertical_margin: Mm,
 	off_row_color_lines_y_adjust_scalar: f32,
 	off_row_color_lines_height_scalar: f32,
 	off_row_color: Color
@@ -726,10 +1100,10 @@ impl From<TableOptions> for TableData
 	{
 		Self
 		{
-			horizontal_cell_margin: options.horizontal_cell_margin(),
-			vertical_cell_margin: options.vertical_cell_margin(),
-			outer_horizontal_margin: options.outer_horizontal_margin(),
-			outer_vertical_margin: options.outer_vertical_margin(),
+			horizontal_cell_margin: Mm(options.horizontal_cell_margin()),
+			vertical_cell_margin: Mm(options.vertical_cell_margin()),
+			outer_horizontal_margin: Mm(options.outer_horizontal_margin()),
+			outer_vertical_margin: Mm(options.outer_vertical_margin()),
 			off_row_color_lines_y_adjust_scalar: options.off_row_color_lines_y_adjust_scalar(),
 			off_row_color_lines_height_scalar: options.off_row_color_lines_height_scalar(),
 			off_row_color: bytes_to_color(&options.off_row_color())
@@ -740,10 +1114,10 @@ impl From<TableOptions> for TableData
 impl TableData
 {
 	// Getters
-	pub fn horizontal_cell_margin(&self) -> f32 { self.horizontal_cell_margin }
-	pub fn vertical_cell_margin(&self) -> f32 { self.vertical_cell_margin }
-	pub fn outer_horizontal_margin(&self) -> f32 { self.outer_horizontal_margin }
-	pub fn outer_vertical_margin(&self) -> f32 { self.outer_vertical_margin }
+	pub fn horizontal_cell_margin(&self) -> Mm { self.horizontal_cell_margin }
+	pub fn vertical_cell_margin(&self) -> Mm { self.vertical_cell_margin }
+	pub fn outer_horizontal_margin(&self) -> Mm { self.outer_horizontal_margin }
+	pub fn outer_vertical_margin(&self) -> Mm { self.outer_vertical_margin }
 	pub fn off_row_color_lines_y_adjust_scalar(&self) -> f32 { self.off_row_color_lines_y_adjust_scalar }
 	pub fn off_row_color_lines_height_scalar(&self) -> f32 { self.off_row_color_lines_height_scalar }
 	pub fn off_row_color(&self) -> &Color { &self.off_row_color }
@@ -758,228 +1132,16 @@ pub enum TableTagCheckResult
 	NotTableTag
 }
 
-/// Holds a single token in a spellbook
-#[derive(Clone, Debug, PartialEq)]
-pub enum Token
-{
-	/// A symbol that changes the font variant that the following text uses.
-	// Ex: Regular: "<r>", Bold: "<b>", Italic: "<i>", Bold-Italic: "<bi>" or "<ib>".
-	FontTag(FontVariant),
-	/// Tokens that are treated like text and are applied to the page.
-	Text(TextToken)
-}
-
-impl Token
-{
-	/// Gets a string of this token as it will appear in the spellbook.
-	/// Font tags will return an empty string, text tokens will return the string they are holding.
-	pub fn as_spellbook_string(&self) -> &str
-	{
-		static EMPTY_STR: &str = "";
-		match self
-		{
-			Self::FontTag(_) => EMPTY_STR,
-			Self::Text(token) => &token.text()
-		}
-	}
-}
-
-impl fmt::Display for Token
-{
-	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
-	{
-		match self
-		{
-			Self::FontTag(tag) => tag.fmt(f),
-			Self::Text(token) => token.fmt(f)
-		}
-	}
-}
-
-/// Holds a single token of text in a spellbook along with the width of the text in the font it will be applied with.
-#[derive(Clone, Debug, PartialEq)]
-pub struct TextToken
-{
-	/// The actual text.
-	text: String,
-	/// The width of the token in `printpdf::Mm` units.
-	width: f32
-}
-
-impl TextToken
-{
-	// /// Creates a new text token from a string and font data. Calculates width automatically.
-	// pub fn new(text: &str, font_size_data: &Font, font_scale: &Scale, font_scalar: f32) -> Self
-	// {
-	// 	let width = calc_text_width(text, font_size_data, font_scale, font_scalar);
-	// 	Self
-	// 	{
-	// 		text: String::from(text),
-	// 		width: width
-	// 	}
-	// }
-
-	/// Creates a new text token from a string and a precalculated width of that string. Does not check to make sure
-	/// the given width is correct.
-	pub fn with_width(text: &str, width: f32) -> Self
-	{
-		Self
-		{
-			text: String::from(text),
-			width: width
-		}
-	}
-
-	/// Creates a new empty text token with no text and a width of 0.0.
-	pub fn empty() -> Self
-	{
-		Self
-		{
-			text: String::new(),
-			width: 0.0
-		}
-	}
-
-	// Getters
-
-	/// Returns the text this object is holding.
-	pub fn text(&self) -> &str {&self.text.as_str() }
-	// /// Returns the width of the text his object is holding.
-	// pub fn width(&self) -> f32 { self.width }
-}
-
-impl fmt::Display for TextToken
-{
-	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
-	{
-		write!(f, "{}", self.text)
-	}
-}
-
-/// Holds a line of tokens that will be applied to a spellbook along with the width of the entire line.
-#[derive(Clone, Debug, PartialEq)]
-pub struct TextLine
-{
-	/// The line of tokens that will be applied to the spellbook.
-	tokens: Vec<Token>,
-	/// The width of the entire line in `printpdf::Mm` units.
-	width: f32,
-	/// Holds the text type of this line (used for calculating space widths)
-	text_type: TextType,
-	/// Holds the current font variant of the line (used for calculating space widths)
-	current_font_variant: FontVariant,
-	/// Holds the font variant of the previous token in the line (used for calculating space widths)
-	previous_font_variant: FontVariant
-}
-
-impl std::ops::Index<usize> for TextLine
-{
-	type Output = Token;
-	/// Allows `TextLine`s to be indexed using the index operator `[]` to retrieve individual tokens.
-	fn index(&self, index: usize) -> &Self::Output { &self.tokens[index] }
-}
-
-impl TextLine
-{
-	// /// Creates a new empty text line.
-	// pub fn new(text_type: TextType, current_font_variant: FontVariant) -> Self
-	// {
-	// 	Self::with_capacity(0, text_type, current_font_variant)
-	// }
-
-	/// Creates a new text line with a given capacity for its vec of tokens.
-	pub fn with_capacity(size: usize, text_type: TextType, current_font_variant: FontVariant) -> Self
-	{
-		Self
-		{
-			tokens: Vec::with_capacity(size),
-			width: 0.0,
-			text_type: text_type,
-			current_font_variant: current_font_variant,
-			previous_font_variant: current_font_variant
-		}
-	}
-
-	// Setters
-
-	// /// Adds a token to the line.
-	// pub fn add_token(&mut self, token: Token, space_widths: &SpaceWidths)
-	// {
-	// 	match token
-	// 	{
-	// 		Token::Text(text) => self.add_text(text, space_widths),
-	// 		Token::FontTag(tag) => self.add_font_tag(tag)
-	// 	}
-	// }
-
-	/// Adds a font tag to the line.
-	pub fn add_font_tag(&mut self, tag: FontVariant)
-	{
-		if self.tokens.len() > 0
-		{
-			let last_index = self.tokens.len() - 1;
-			match self.tokens[last_index]
-			{
-				Token::FontTag(_) => self.tokens[last_index] = Token::FontTag(tag),
-				Token::Text(_) =>
-				{
-					self.previous_font_variant = self.current_font_variant;
-					self.tokens.push(Token::FontTag(tag));
-				}
-			}
-		}
-		else { self.tokens.push(Token::FontTag(tag)); }
-		self.current_font_variant = tag;
-	}
-
-	/// Adds text to the line.
-	pub fn add_text(&mut self, text: TextToken, space_widths: &SpaceWidths)
-	{
-		if self.width > 0.0
-		{
-			self.width += space_widths.get_width_for(self.text_type, self.previous_font_variant);
-		}
-		self.previous_font_variant = self.current_font_variant;
-		// Adds the width of the token to the line's width before adding the token itself to the line.
-		self.width += text.width;
-		self.tokens.push(Token::Text(text));
-	}
-
-	// /// Adds extra width to the line (usually used for adding the width of a space character to the line).
-	// pub fn add_width(&mut self, width: f32) { self.width += width; }
-	/// Shrinks the capacity of the vec of tokens to fit its size.
-	pub fn shrink_to_fit(&mut self) { self.tokens.shrink_to_fit(); }
-
-	// Getters
-
-	/// Returns the vec of all the tokens in the line.
-	pub fn tokens(&self) -> &Vec<Token> { &self.tokens }
-	/// Returns the width of the line.
-	pub fn width(&self) -> f32 { self.width }
-	// /// Returns the number of tokens in the line
-	// pub fn len(&self) -> usize { self.tokens.len() }
-	/// Returns whether or not the vec of tokens in this line is empty.
-	pub fn is_empty(&self) -> bool { self.tokens.is_empty() }
-	// /// Returns whether or not the vec of tokens in this line is not empty.
-	// pub fn not_empty(&self) -> bool { self.tokens.len() > 0 }
-
-	/// Returns the space width using the font data of the previous token
-	pub fn get_last_space_width(&self, space_widths: &SpaceWidths) -> f32
-	{
-		space_widths.get_width_for(self.text_type, self.previous_font_variant)
-	}
-}
-
-/// Keeps track of the width of spaces in spellbooks using `printpdf::Mm` units.
+/// Keeps track of the width of spaces in spellbooks.
 #[derive(Clone, Debug, PartialEq)]
 pub struct SpaceWidths
 {
 	// Outer dimension represents font scales, inner dimension represents font variants
-	widths: [[f32; FONTVARIANT_VARIANTS]; TEXTTYPE_VARIANTS]
+	widths: [[Mm; FONTVARIANT_VARIANTS]; TEXTTYPE_VARIANTS]
 }
 
 /// Used for constructing empty width arrays in `SpaceWidths`.
-const DEFAULT_WIDTHS: [f32; FONTVARIANT_VARIANTS] = [0.0; FONTVARIANT_VARIANTS];
+const DEFAULT_WIDTHS: [Mm; FONTVARIANT_VARIANTS] = [Mm(0.0); FONTVARIANT_VARIANTS];
 
 impl SpaceWidths
 {
@@ -1000,25 +1162,21 @@ impl SpaceWidths
 		{
 			widths[i] = match i
 			{
-				TITLE =>
-				Self::construct_widths_for(font_data.get_font_scale_for(TextType::Title), font_data),
-				HEADER =>
-				Self::construct_widths_for(font_data.get_font_scale_for(TextType::Header), font_data),
-				BODY =>
-				Self::construct_widths_for(font_data.get_font_scale_for(TextType::Body), font_data),
-				TABLE_TITLE =>
-				Self::construct_widths_for(font_data.get_font_scale_for(TextType::TableTitle), font_data),
-				TABLE_BODY =>
-				Self::construct_widths_for(font_data.get_font_scale_for(TextType::TableBody), font_data),
+				TITLE => Self::construct_widths_for(TextType::Title, font_data),
+				HEADER => Self::construct_widths_for(TextType::Header, font_data),
+				BODY => Self::construct_widths_for(TextType::Body, font_data),
+				TABLE_TITLE => Self::construct_widths_for(TextType::TableTitle, font_data),
+				TABLE_BODY => Self::construct_widths_for(TextType::TableBody, font_data),
 				_ => panic!("Invalid TextType variant / usize / index in `dnd_spellbook_maker::spellbook_gen_types::SpaceWidths::new`")
 			}
 		}
 		SpaceWidths { widths: widths }
 	}
 
-	/// Gives the font widths for each font variant using a specific font scale.
-	fn construct_widths_for(scale: &Scale, font_data: &FontData) -> [f32; FONTVARIANT_VARIANTS]
+	/// Gives the font widths for each font variant using the font and font scale of a specific text type.
+	fn construct_widths_for(text_type: TextType, font_data: &FontData) -> [Mm; FONTVARIANT_VARIANTS]
 	{
+		let scale = font_data.get_font_scale_for(text_type);
 		const REGULAR: usize = FontVariant::Regular as usize;
 		const BOLD: usize = FontVariant::Bold as usize;
 		const ITALIC: usize = FontVariant::Italic as usize;
@@ -1034,31 +1192,43 @@ impl SpaceWidths
 				REGULAR => calc_text_width
 				(
 					SPACE,
-					font_data.get_size_data_for(FontVariant::Regular),
+					font_data.get_size_data_for(text_type, FontVariant::Regular),
 					scale,
-					font_data.get_scalar_for(FontVariant::Regular)
-				),
+					font_data.get_scalar_for(text_type, FontVariant::Regular),
+					None,
+					None,
+					None
+				) + font_data.synthetic_extra_width_for(SPACE, text_type, FontVariant::Regular),
 				BOLD => calc_text_width
 				(
 					SPACE,
-					font_data.get_size_data_for(FontVariant::Bold),
+					font_data.get_size_data_for(text_type, FontVariant::Bold),
 					scale,
-					font_data.get_scalar_for(FontVariant::Bold)
-				),
+					font_data.get_scalar_for(text_type, FontVariant::Bold),
+					None,
+					None,
+					None
+				) + font_data.synthetic_extra_width_for(SPACE, text_type, FontVariant::Bold),
 				ITALIC => calc_text_width
 				(
 					SPACE,
-					font_data.get_size_data_for(FontVariant::Italic),
+					font_data.get_size_data_for(text_type, FontVariant::Italic),
 					scale,
-					font_data.get_scalar_for(FontVariant::Italic)
-				),
+					font_data.get_scalar_for(text_type, FontVariant::Italic),
+					None,
+					None,
+					None
+				) + font_data.synthetic_extra_width_for(SPACE, text_type, FontVariant::Italic),
 				BOLD_ITALIC => calc_text_width
 				(
 					SPACE,
-					font_data.get_size_data_for(FontVariant::BoldItalic),
+					font_data.get_size_data_for(text_type, FontVariant::BoldItalic),
 					scale,
-					font_data.get_scalar_for(FontVariant::BoldItalic)
-				),
+					font_data.get_scalar_for(text_type, FontVariant::BoldItalic),
+					None,
+					None,
+					None
+				) + font_data.synthetic_extra_width_for(SPACE, text_type, FontVariant::BoldItalic),
 				_ => panic!("Invalid FontVariant / usize / index in `dnd_spellbook_maker::spellbook_gen_types::SpaceWidths::construct_widths_for`")
 			}
 		}
@@ -1066,13 +1236,45 @@ impl SpaceWidths
 	}
 
 	/// Gets the width of a space for a given `TextType` and `FontVariant`.
-	pub fn get_width_for(&self, text_type: TextType, font_variant: FontVariant) -> f32
+	pub fn get_width_for(&self, text_type: TextType, font_variant: FontVariant) -> Mm
 	{
 		self.widths[text_type as usize][font_variant as usize]
 	}
 
 	// /// Gives all space width values in an unlabeled 2D array.
-	// pub fn all_widths(&self) -> &[[f32; FONTVARIANT_VARIANTS]; TEXTTYPE_VARIANTS] { &self.widths }
+	// pub fn all_widths(&self) -> &[[Mm; FONTVARIANT_VARIANTS]; TEXTTYPE_VARIANTS] { &self.widths }
+}
+
+/// Measures text for a type of text in a spellbook using the spellbook's font data.
+pub struct SpellbookTextMeasurer<'f, 'a>
+{
+	pub font_data: &'f FontData<'a>,
+	pub space_widths: &'f SpaceWidths,
+	pub text_type: TextType
+}
+
+impl TextMeasurer for SpellbookTextMeasurer<'_, '_>
+{
+	fn text_width(&self, text: &str, font_variant: FontVariant) -> f32
+	{
+		let text = &*self.font_data.fallback_text_for(text, self.text_type, font_variant);
+		let width = calc_text_width
+		(
+			text,
+			self.font_data.get_size_data_for(self.text_type, font_variant),
+			self.font_data.get_font_scale_for(self.text_type),
+			self.font_data.get_scalar_for(self.text_type, font_variant),
+			self.font_data.get_kerning_for(self.text_type, font_variant),
+			self.font_data.get_ligatures_for(self.text_type, font_variant),
+			self.font_data.get_shaper_for(self.text_type, font_variant)
+		) + self.font_data.synthetic_extra_width_for(text, self.text_type, font_variant);
+		width.0
+	}
+
+	fn space_width(&self, font_variant: FontVariant) -> f32
+	{
+		self.space_widths.get_width_for(self.text_type, font_variant).0
+	}
 }
 
 /// Holds data about a column in a table in a spellbook.
@@ -1080,35 +1282,507 @@ impl SpaceWidths
 pub struct TableColumnData
 {
 	/// The starting x position of the text in the column.
-	pub x_min: f32,
+	pub x_min: Mm,
 	/// The ending x position of the text in the column.
-	pub x_max: f32,
+	pub x_max: Mm,
 	/// Whether or not the text in the column is centered.
 	pub centered: bool
 }
 
-/// Calculates the width of some text based with given font data.
-pub fn calc_text_width(text: &str, font_size_data: &Font, font_scale: &Scale, font_scalar: f32) -> f32
+/// The lines of text in a table in a spellbook along with everything about its size that's needed to write it.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TableLayout
+{
+	/// The lines of the table's title.
+	pub title_lines: Vec<TextLine>,
+	/// The lines of each column label.
+	pub column_label_lines: Vec<Vec<TextLine>>,
+	/// The lines of each cell in each row.
+	pub cell_lines: Vec<Vec<Vec<TextLine>>>,
+	/// The horizontal bounds of each column and whether or not its text is centered.
+	pub column_data: Vec<TableColumnData>,
+	/// The number of lines in the column label row.
+	pub label_line_count: usize,
+	/// The number of lines in each row of cells.
+	pub cell_line_counts: Vec<usize>,
+	/// The height of the table's title.
+	pub title_height: Mm,
+	/// The height of the whole table (including its title).
+	pub height: Mm
+}
+
+/// Keeps track of the columns text is being written in when pages are split into multiple columns.
+/// All text positions are calculated as if they were in the first column and get moved over when applied.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ColumnLayout
+{
+	/// The number of columns on each page.
+	pub column_count: usize,
+	/// The width of each column.
+	pub column_width: Mm,
+	/// The space between columns.
+	pub column_gap: Mm,
+	/// The column text is currently being written in (0 for the first column).
+	pub current_column: usize,
+	/// The y position that the columns after the first one start at on the current page (`None` for the top of the
+	/// page). Used when something (like a heading) goes above the columns.
+	pub top_y: Option<Mm>,
+	/// The lowest y position that text can go in the columns before the last one on the current page when the columns
+	/// are balanced (`None` to fill each column to the bottom of the page).
+	pub balanced_y_min: Option<Mm>
+}
+
+impl ColumnLayout
 {
-	let width = font_size_data.layout(text, *font_scale, point(0.0, 0.0))
-		.map(|g| g.position().x + g.unpositioned().h_metrics().advance_width)
-		.last()
-		.unwrap_or(0.0);
-	width * font_scalar
+	/// How far to the right of the first column the current column is.
+	pub fn x_offset(&self) -> Mm { (self.column_width + self.column_gap) * self.current_column as f32 }
+}
+
+/// A clickable area on one page of a spellbook that jumps to another page in the same spellbook.
+/// These can't be made with `printpdf` directly, so they get added to the document when it is saved.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct InternalLink
+{
+	/// The index of the page the clickable area is on.
+	pub page_index: usize,
+	/// The left side of the clickable area.
+	pub x_min: Mm,
+	/// The bottom of the clickable area.
+	pub y_min: Mm,
+	/// The right side of the clickable area.
+	pub x_max: Mm,
+	/// The top of the clickable area.
+	pub y_max: Mm,
+	/// The index of the page the link goes to.
+	pub target_page_index: usize
+}
+
+/// A fillable checkbox on one page of a spellbook that can be checked in pdf readers.
+/// These can't be made with `printpdf` directly, so they get added to the document when it is saved.
+#[derive(Clone, Debug, PartialEq)]
+pub struct FormCheckbox
+{
+	/// The index of the page the checkbox is on.
+	pub page_index: usize,
+	/// The left side of the checkbox.
+	pub x_min: Mm,
+	/// The bottom of the checkbox.
+	pub y_min: Mm,
+	/// The right side of the checkbox.
+	pub x_max: Mm,
+	/// The top of the checkbox.
+	pub y_max: Mm,
+	/// The name of the form field. Checkboxes with the same name are linked so checking one checks all of them.
+	pub name: String
+}
+
+/// A popup on one page of a spellbook that shows some text when the mouse is over an area of the page (like the
+/// definition of a glossary term). These can't be made with `printpdf` directly, so they get added to the document when
+/// it is saved.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Tooltip
+{
+	/// The index of the page the tooltip is on.
+	pub page_index: usize,
+	/// The left side of the area the mouse has to be over.
+	pub x_min: Mm,
+	/// The bottom of the area the mouse has to be over.
+	pub y_min: Mm,
+	/// The right side of the area the mouse has to be over.
+	pub x_max: Mm,
+	/// The top of the area the mouse has to be over.
+	pub y_max: Mm,
+	/// The title of the popup.
+	pub title: String,
+	/// The text in the popup.
+	pub text: String
+}
+
+/// The kinds of pages that can have their own background images.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum PageKind
+{
+	/// Any page that isn't one of the others.
+	#[default]
+	Body,
+	/// The title page.
+	TitlePage,
+	/// The page at the start of a class chapter.
+	ChapterPage
+}
+
+/// Something that goes in a spellbook after the title page and table of contents (in the order they're added).
+#[derive(Clone, Debug, PartialEq)]
+pub enum SpellbookEntry
+{
+	/// The start of a class chapter (a page with its title on it or a list of its spells).
+	Chapter(ClassChapter),
+	/// A spell (the index of the spell in the list of spells given to the spellbook).
+	Spell(usize)
+}
+
+/// A line in a table of contents along with where it goes in the spellbook.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TableOfContentsLine
+{
+	/// The text in this line.
+	pub line: TextLine,
+	/// The type of text this line is.
+	pub text_type: TextType,
+	/// Whether or not this line is horizontally centered (only the heading is).
+	pub centered: bool,
+	/// Which page of the table of contents this line goes on (0 for the first page).
+	pub page_offset: usize,
+	/// Which column of its page this line goes in (0 for the first column).
+	pub column: usize,
+	/// The y position of this line on its page.
+	pub y: Mm,
+	/// The index of the spell this line is an entry for (`None` for the heading and chapters).
+	pub spell_index: Option<usize>,
+	/// The index of the chapter this line is an entry for (`None` for the heading and spells).
+	pub chapter_index: Option<usize>,
+	/// Whether or not this is the first line of an entry (which is where a preparation checkbox goes).
+	pub is_entry_start: bool,
+	/// Whether or not this is the last line of an entry (which is where the page number goes).
+	pub is_entry_end: bool
+}
+
+/// A page reference in a spell description that has space saved for the page number of its anchor, which gets written
+/// once every page is laid out.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PageReference
+{
+	/// The name of the anchor the way it was written in the reference tag.
+	pub name: String,
+	/// The index of the page the reference is on.
+	pub page_index: usize,
+	/// The x position of the space for the page number.
+	pub x: Mm,
+	/// The y position of the line the reference is on.
+	pub y: Mm,
+	/// The type of text the reference is in.
+	pub text_type: TextType,
+	/// The font variant the reference is in.
+	pub font_variant: FontVariant,
+	/// The color of the text from a color tag that the reference is in (if it's in one).
+	pub color: Option<Color>
+}
+
+/// Error for when page references (like `[ref:chromatic-orb-damage]`) point to anchors that aren't in a spellbook.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PageReferenceError(pub Vec<String>);
+
+impl std::fmt::Display for PageReferenceError
+{
+	fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result
+	{
+		write!(f, "Couldn't find the tables or sections that these page references point to: {}.", self.0.join(", "))
+	}
+}
+impl std::error::Error for PageReferenceError {}
+
+/// Styles that get faked when writing text in a font variant that didn't have its own font file.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct SyntheticStyle
+{
+	/// Whether the text gets stroked to make it thicker.
+	pub bold: bool,
+	/// Whether the text gets skewed to make it slanted.
+	pub italic: bool
+}
+
+/// How much thicker text gets made for synthetic bold text (as a fraction of the font size).
+pub const SYNTHETIC_BOLD_STROKE: f32 = 0.03;
+/// How far synthetic italic text gets slanted to the right for every unit of height (about 12 degrees).
+pub const SYNTHETIC_ITALIC_SKEW: f32 = 0.21;
+
+/// Marks where superscript text (like a footnote number after a word) starts in a token. Fonts don't have glyphs for
+/// private use characters like this one, so it can't get mixed up with real text.
+pub const SUPERSCRIPT_START: char = '\u{E000}';
+/// Marks where superscript text started by `SUPERSCRIPT_START` ends in a token.
+pub const SUPERSCRIPT_END: char = '\u{E001}';
+/// How big superscript text is compared to the text around it.
+pub const SUPERSCRIPT_SCALE: f32 = 0.65;
+/// How far superscript text gets raised above the baseline (as a fraction of the height of capital letters).
+pub const SUPERSCRIPT_RISE: f32 = 0.5;
+
+/// Marks where a word that gets hidden behind a shaded bar (like a word of a secret in a player edition) starts in a
+/// token.
+pub const REDACTED_START: char = '\u{E002}';
+/// Marks where a word started by `REDACTED_START` ends in a token.
+pub const REDACTED_END: char = '\u{E003}';
+
+/// Marks where the name of the anchor that a page reference points to starts in a token. The page number of the
+/// anchor gets written in its place once every page is laid out.
+pub const PAGE_REFERENCE_START: char = '\u{E004}';
+/// Marks where an anchor name started by `PAGE_REFERENCE_START` ends in a token.
+pub const PAGE_REFERENCE_END: char = '\u{E005}';
+/// Width of the text that the space for the page number of a page reference is made for.
+pub const PAGE_REFERENCE_NUMBER_SPACE: &str = "000";
+
+/// Splits text into the parts that are and aren't superscript (in order) along with whether each part is
+/// superscript. The characters that mark superscript text get left out.
+pub fn superscript_segments(text: &str) -> Vec<(&str, bool)>
+{
+	marked_segments(text, SUPERSCRIPT_START, SUPERSCRIPT_END)
+}
+
+/// Splits text into the parts that are and aren't hidden behind shaded bars (in order) along with whether each part
+/// is hidden. The characters that mark hidden text get left out.
+pub fn redacted_segments(text: &str) -> Vec<(&str, bool)>
+{
+	marked_segments(text, REDACTED_START, REDACTED_END)
+}
+
+/// Splits text into the parts that are and aren't the anchor names of page references (in order) along with whether
+/// each part is an anchor name. The characters that mark anchor names get left out.
+pub fn page_reference_segments(text: &str) -> Vec<(&str, bool)>
+{
+	marked_segments(text, PAGE_REFERENCE_START, PAGE_REFERENCE_END)
+}
+
+/// Splits text into the parts that are and aren't between a start marker and an end marker (in order) along with
+/// whether each part is between them. The markers get left out.
+fn marked_segments(text: &str, start_marker: char, end_marker: char) -> Vec<(&str, bool)>
+{
+	let mut segments = Vec::new();
+	let mut rest = text;
+	while let Some(start) = rest.find(start_marker)
+	{
+		if start > 0 { segments.push((&rest[..start], false)); }
+		rest = &rest[start + start_marker.len_utf8()..];
+		let end = rest.find(end_marker).unwrap_or(rest.len());
+		segments.push((&rest[..end], true));
+		rest = &rest[(end + end_marker.len_utf8()).min(rest.len())..];
+	}
+	if !rest.is_empty() { segments.push((rest, false)); }
+	segments
+}
+
+/// Figures out which font file each font variant of a font family gets made from. Variants with an empty file path
+/// (other than regular) get made from the closest variant that has a font file with synthetic styles for the rest.
+///
+/// # Parameters
+///
+/// - `paths` File paths to the regular, bold, italic, and bold-italic font files (in that order).
+///
+/// # Output
+///
+/// The variant whose font file each variant uses and the styles that have to be synthesized for it.
+pub fn synthetic_font_sources(paths: [&String; FONTVARIANT_VARIANTS])
+-> [(FontVariant, SyntheticStyle); FONTVARIANT_VARIANTS]
+{
+	let has_bold = !paths[FontVariant::Bold as usize].is_empty();
+	let has_italic = !paths[FontVariant::Italic as usize].is_empty();
+	let has_bold_italic = !paths[FontVariant::BoldItalic as usize].is_empty();
+	let bold = SyntheticStyle { bold: true, italic: false };
+	let italic = SyntheticStyle { bold: false, italic: true };
+	let regular = (FontVariant::Regular, SyntheticStyle::default());
+	[
+		regular,
+		if has_bold { (FontVariant::Bold, SyntheticStyle::default()) } else { (FontVariant::Regular, bold) },
+		if has_italic { (FontVariant::Italic, SyntheticStyle::default()) } else { (FontVariant::Regular, italic) },
+		if has_bold_italic { (FontVariant::BoldItalic, SyntheticStyle::default()) }
+		else if has_bold { (FontVariant::Bold, italic) }
+		else if has_italic { (FontVariant::Italic, bold) }
+		else { (FontVariant::Regular, SyntheticStyle { bold: true, italic: true }) }
+	]
+}
+
+/// How much wider synthetic bold text gets than the font it's made from (every glyph gets moved over by the thickness
+/// of the stroke around the text).
+pub fn synthetic_bold_extra_width(glyph_count: usize, font_size: Pt) -> Mm
+{
+	let stroke_width: Mm = (font_size * SYNTHETIC_BOLD_STROKE).into();
+	stroke_width * glyph_count as f32
+}
+
+// The bytes, size data, and references of each font variant in a font family along with the font variant each variant
+// is made from and the styles that have to be synthesized for it
+type FontFamily<'a> = (FontBytes, FontSizeData<'a>, FontRefs, [(FontVariant, SyntheticStyle); FONTVARIANT_VARIANTS]);
+
+/// Reads the font files of a font family and adds each font variant to a pdf document.
+///
+/// # Parameters
+///
+/// - `doc` The pdf document the fonts will be used in.
+/// - `paths` File paths to the regular, bold, italic, and bold-italic font files (in that order). Variants with an
+///   empty path get synthesized from another variant (see `synthetic_font_sources()`).
+/// - `variable_axes` Axis values to instance each font variant at if the files are variable fonts.
+/// - `family_label` Text that goes before the font variant in error messages (ex: "display ").
+///
+/// # Output
+///
+/// - `Ok` The bytes of the font file each variant uses, the size data of each font variant, references to each font
+///   variant, and the font variant each variant is made from with the styles that have to be synthesized for it.
+/// - `Err` Any errors that occurred while reading the font files or adding them to the document.
+fn load_font_family<'a>
+(
+	doc: &PdfDocumentReference,
+	paths: [&String; FONTVARIANT_VARIANTS],
+	variable_axes: Option<&VariableFontAxes>,
+	family_label: &str
+)
+-> Result<FontFamily<'a>, Box<dyn std::error::Error + Send + Sync>>
+{
+	let sources = synthetic_font_sources(paths);
+	// Read each font file that gets used once (instancing it if it's a variable font) and add it to the document
+	let mut loaded_fonts: Vec<(FontVariant, Vec<u8>, IndirectFontRef)> = Vec::new();
+	for (source, _) in sources
+	{
+		if loaded_fonts.iter().any(|(variant, _, _)| *variant == source) { continue; }
+		let bytes = load_font_bytes(paths[source as usize], variable_axes.map(|axes| axes.axes_for(source)))?;
+		let font_ref = doc.add_external_font(&*bytes)?;
+		loaded_fonts.push((source, bytes, font_ref));
+	}
+	// Gets the bytes and font reference of the font file a font variant uses
+	let loaded_font = |font_variant: FontVariant| -> (&Vec<u8>, &IndirectFontRef)
+	{
+		let source = sources[font_variant as usize].0;
+		let (_, bytes, font_ref) = loaded_fonts.iter()
+			.find(|(variant, _, _)| *variant == source)
+			.expect("Every font variant's source font gets loaded in `load_font_family`");
+		(bytes, font_ref)
+	};
+
+	// Creates font size data for a font variant from its bytes
+	let to_size_data = |font_variant: FontVariant, variant_name: &str|
+	-> Result<Font<'a>, BytesToFontSizeDataConversionError>
+	{
+		match Font::try_from_vec(loaded_font(font_variant).0.clone())
+		{
+			Some(d) => Ok(d),
+			None => Err(BytesToFontSizeDataConversionError(format!
+				("Could not convert {}{} font size data from bytes.", family_label, variant_name)))
+		}
+	};
+
+	// Combine all size data into one struct
+	let size_data = FontSizeData
+	{
+		regular: to_size_data(FontVariant::Regular, "regular")?,
+		bold: to_size_data(FontVariant::Bold, "bold")?,
+		italic: to_size_data(FontVariant::Italic, "italic")?,
+		bold_italic: to_size_data(FontVariant::BoldItalic, "bold italic")?
+	};
+
+	// Combine all font references into one struct (variants made from the same font file share a reference)
+	let font_refs = FontRefs
+	{
+		regular: loaded_font(FontVariant::Regular).1.clone(),
+		bold: loaded_font(FontVariant::Bold).1.clone(),
+		italic: loaded_font(FontVariant::Italic).1.clone(),
+		bold_italic: loaded_font(FontVariant::BoldItalic).1.clone()
+	};
+
+	// Put the bytes into a struct to reuse them if new font refs need to be created when a new pdf document is created.
+	let font_bytes = FontBytes
+	{
+		regular: loaded_font(FontVariant::Regular).0.clone(),
+		bold: loaded_font(FontVariant::Bold).0.clone(),
+		italic: loaded_font(FontVariant::Italic).0.clone(),
+		bold_italic: loaded_font(FontVariant::BoldItalic).0.clone()
+	};
+
+	Ok((font_bytes, size_data, font_refs, sources))
+}
+
+/// Gives the glyph ids of some text in a font along with how many characters of the text each glyph stands for (more
+/// than 1 for ligatures). Glyphs that make up ligatures get replaced with the ligature glyphs if ligatures are given.
+pub fn text_glyphs(text: &str, font_size_data: &Font, ligatures: Option<&LigatureTable>) -> Vec<(u16, usize)>
+{
+	let mut glyphs: Vec<_> = font_size_data.glyphs_for(text.chars()).map(|glyph| (glyph.id().0, 1)).collect();
+	if let Some(ligatures) = ligatures { ligatures.apply(&mut glyphs); }
+	glyphs
+}
+
+/// Calculates the width of some text based with given font data (the font scalar converts it to `Mm`).
+/// Kerning between each pair of glyphs gets added to the width if kerning data is given, and ligatures get measured
+/// instead of the glyphs they replace if ligatures are given. Text in scripts that need shaping gets measured by the
+/// glyphs it gets shaped into if a text shaper is given.
+pub fn calc_text_width
+(
+	text: &str,
+	font_size_data: &Font,
+	font_scale: &Scale,
+	font_scalar: f32,
+	kerning: Option<&KerningTable>,
+	ligatures: Option<&LigatureTable>,
+	shaper: Option<&TextShaper>
+)
+-> Mm
+{
+	// Page references take up the space saved for their page numbers instead of the space of their anchor names
+	if text.contains(PAGE_REFERENCE_START)
+	{
+		return page_reference_segments(text).into_iter().fold(Mm(0.0), |width, (segment, is_reference)|
+		{
+			let segment = if is_reference { PAGE_REFERENCE_NUMBER_SPACE } else { segment };
+			width + calc_text_width(segment, font_size_data, font_scale, font_scalar, kerning, ligatures, shaper)
+		});
+	}
+	// Superscript text is smaller than the text around it
+	if text.contains(SUPERSCRIPT_START)
+	{
+		return superscript_segments(text).into_iter().fold(Mm(0.0), |width, (segment, is_superscript)|
+		{
+			let segment_width =
+				calc_text_width(segment, font_size_data, font_scale, font_scalar, kerning, ligatures, shaper);
+			if is_superscript { width + segment_width * SUPERSCRIPT_SCALE } else { width + segment_width }
+		});
+	}
+	// Hidden words take up the same space as they would if they were written
+	if text.contains(REDACTED_START)
+	{
+		return redacted_segments(text).into_iter().fold(Mm(0.0), |width, (segment, _)|
+		{
+			width + calc_text_width(segment, font_size_data, font_scale, font_scalar, kerning, ligatures, shaper)
+		});
+	}
+	// Scales kerning from font units the same way glyph advance widths get scaled
+	let kerning_scale = font_size_data.scale_for_pixel_height(font_scale.y) * font_scale.x / font_scale.y;
+	// Shaped text already has kerning and ligatures in the advances of its glyphs
+	if let Some(shaper) = shaper.filter(|_| needs_shaping(text))
+	{
+		return Mm(shaper.width(text) as f32 * kerning_scale * font_scalar);
+	}
+	let mut width = 0.0;
+	let mut last_glyph_id = None;
+	for (glyph_id, _) in text_glyphs(text, font_size_data, ligatures)
+	{
+		if let (Some(kerning), Some(last_glyph_id)) = (kerning, last_glyph_id)
+		{
+			width += f32::from(kerning.kerning(last_glyph_id, glyph_id)) * kerning_scale;
+		}
+		width += font_size_data.glyph(rusttype::GlyphId(glyph_id)).scaled(*font_scale).h_metrics().advance_width;
+		last_glyph_id = Some(glyph_id);
+	}
+	Mm(width * font_scalar)
+}
+
+/// Calculates the height of capital letters above the baseline of text based on given font data.
+pub fn calc_cap_height(font_size_data: &Font, font_scale: &Scale, font_scalar: f32) -> Mm
+{
+	match font_size_data.glyph('H').scaled(*font_scale).exact_bounding_box()
+	{
+		// Glyph coordinates go down as y increases, so the top of the glyph is its negative minimum y value
+		Some(bounding_box) => Mm(-bounding_box.min.y * font_scalar),
+		None => Mm(0.0)
+	}
 }
 
 /// Calculates the height of some text based on given font data.
 pub fn calc_text_height
 (
-	newline_amount: f32,
+	newline_amount: Mm,
 	lines: usize
 )
--> f32
+-> Mm
 {
 	// If there are no lines, return 0 for the height
-	if lines == 0 { return 0.0; }
+	if lines == 0 { return Mm(0.0); }
 	// Calculate the amount of space every newline takes up
-	let newlines_height = (lines as f32) * newline_amount;
+	let newlines_height = newline_amount * lines as f32;
 	newlines_height
 }
 
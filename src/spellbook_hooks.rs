@@ -0,0 +1,277 @@
+//////////////////////////////////////////////////////////////////////////////////////////////////////////////
+//
+//	Extension points for customizing how spellbooks are generated
+//
+//////////////////////////////////////////////////////////////////////////////////////////////////////////////
+
+use std::fmt;
+use std::sync::Arc;
+
+use printpdf::{PdfLayerReference, Mm};
+
+use crate::spells::{self, Spell};
+use crate::spellbook_output::SpellbookWarning;
+
+/// Where a custom tag is in a spellbook when its handler gets called.
+pub struct TagContext<'a>
+{
+	/// The index of the page the tag is on (0 for the title page).
+	pub page_index: usize,
+	/// The x position of the text where the tag is.
+	/// Exact for tags at the start of a paragraph, otherwise it's the start of the paragraph the tag is in.
+	pub x: Mm,
+	/// The y position of the baseline of the text where the tag is.
+	/// Exact for tags at the start of a paragraph, otherwise it's the start of the paragraph the tag is in.
+	pub y: Mm,
+	/// The left side of the area the text is being written in.
+	pub x_min: Mm,
+	/// The right side of the area the text is being written in.
+	pub x_max: Mm,
+	/// The lowest text can go on the page.
+	pub y_min: Mm,
+	/// The highest text can go on the page.
+	pub y_max: Mm,
+	/// Whether or not the tag is the first token of a paragraph.
+	pub at_paragraph_start: bool,
+	/// The layer of the page the tag is on, for drawing things onto the page.
+	pub layer: &'a PdfLayerReference
+}
+
+/// What a custom tag turns into after its handler gets called.
+#[derive(Clone, Debug, PartialEq)]
+pub enum TagOutput
+{
+	/// Text that replaces the tag. It can have font tags and url tags in it.
+	Text(String),
+	/// The handler drew something onto the page that takes up this much vertical space.
+	/// Only moves the text down for tags at the start of a paragraph, and the rest of that paragraph is skipped
+	/// (like with table tags). Anywhere else the tag is just removed.
+	Block(Mm)
+}
+
+/// Lets applications define their own tags for spell descriptions (ex: "<dc>", "[map][0]").
+///
+/// Like font tags, custom tags have to be their own token with whitespace around them.
+///
+/// Handlers have to be `Send` and `Sync` so spellbooks can be made on other threads.
+pub trait DescriptionTagHandler: Send + Sync
+{
+	/// Whether or not this handler is for the given token.
+	fn handles(&self, token: &str) -> bool;
+
+	/// Turns a token that this handler is for into text or draws something onto the page.
+	fn handle(&self, token: &str, context: &TagContext) -> TagOutput;
+}
+
+/// The custom tag handlers used when creating a spellbook. The first handler registered for a token is used.
+#[derive(Clone, Default)]
+pub struct DescriptionTagHandlers
+{
+	handlers: Vec<Arc<dyn DescriptionTagHandler>>
+}
+
+impl DescriptionTagHandlers
+{
+	/// Constructor
+	pub fn new() -> Self { Self::default() }
+
+	/// Adds a handler for custom tags.
+	pub fn register(&mut self, handler: impl DescriptionTagHandler + 'static)
+	{
+		self.handlers.push(Arc::new(handler));
+	}
+
+	/// Whether or not there are no handlers.
+	pub fn is_empty(&self) -> bool { self.handlers.is_empty() }
+
+	/// Returns the handler for a token (if there is one).
+	pub(crate) fn get(&self, token: &str) -> Option<&Arc<dyn DescriptionTagHandler>>
+	{
+		self.handlers.iter().find(|handler| handler.handles(token))
+	}
+}
+
+impl fmt::Debug for DescriptionTagHandlers
+{
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
+	{
+		write!(f, "DescriptionTagHandlers ({} handlers)", self.handlers.len())
+	}
+}
+
+// Handlers can't be compared, so they're only equal if they're the same handlers
+impl PartialEq for DescriptionTagHandlers
+{
+	fn eq(&self, other: &Self) -> bool
+	{
+		self.handlers.len() == other.handlers.len() &&
+		self.handlers.iter().zip(&other.handlers).all(|(a, b)| Arc::ptr_eq(a, b))
+	}
+}
+
+/// A page that was just added to a spellbook.
+pub struct PageContext<'a>
+{
+	/// The index of the page (0 for the title page).
+	pub page_index: usize,
+	/// The page number of the page (even if page numbers aren't being shown).
+	pub page_number: i64,
+	/// The width of the page.
+	pub page_width: Mm,
+	/// The height of the page.
+	pub page_height: Mm,
+	/// The layer of the page, for drawing things onto the page.
+	pub layer: &'a PdfLayerReference
+}
+
+// A callback that gets called every time a new page is added to a spellbook
+type PageCreatedHook = Arc<dyn Fn(&PageContext) + Send + Sync>;
+
+/// Callbacks that get called while a spellbook is being created.
+#[derive(Clone, Default)]
+pub struct PageHooks
+{
+	page_created_hooks: Vec<PageCreatedHook>
+}
+
+impl PageHooks
+{
+	/// Constructor
+	pub fn new() -> Self { Self::default() }
+
+	/// Adds a callback that gets called every time a new page is added to the spellbook (after its background and
+	/// page number are added). Callbacks get called in the order they were added. Callbacks have to be `Send` and
+	/// `Sync` so spellbooks can be made on other threads (use `Arc<Mutex<_>>` instead of `Rc<RefCell<_>>` for any
+	/// state they share).
+	///
+	/// Ex: `hooks.on_page_created(|page| println!("Made page {}", page.page_number));`
+	pub fn on_page_created(&mut self, hook: impl Fn(&PageContext) + Send + Sync + 'static)
+	{
+		self.page_created_hooks.push(Arc::new(hook));
+	}
+
+	/// Calls every callback for new pages.
+	pub(crate) fn page_created(&self, context: &PageContext)
+	{
+		for hook in &self.page_created_hooks { hook(context); }
+	}
+}
+
+impl fmt::Debug for PageHooks
+{
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
+	{
+		write!(f, "PageHooks ({} page created hooks)", self.page_created_hooks.len())
+	}
+}
+
+// Callbacks can't be compared, so they're only equal if they're the same callbacks
+impl PartialEq for PageHooks
+{
+	fn eq(&self, other: &Self) -> bool
+	{
+		self.page_created_hooks.len() == other.page_created_hooks.len() &&
+		self.page_created_hooks.iter().zip(&other.page_created_hooks).all(|(a, b)| Arc::ptr_eq(a, b))
+	}
+}
+
+/// A word that a spell checker thinks is misspelled.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SpellingSuggestion
+{
+	/// The byte position where the word starts in the text that was checked.
+	pub start: usize,
+	/// The byte position right after the end of the word in the text that was checked.
+	pub end: usize,
+	/// Words that it might have been meant to be (can be empty).
+	pub suggestions: Vec<String>
+}
+
+/// Lets applications check the spelling of spell descriptions with their own spell checker (like a dictionary or a
+/// spell checking library).
+///
+/// Checkers have to be `Send` and `Sync` so spellbooks can be made on other threads.
+pub trait SpellChecker: Send + Sync
+{
+	/// Returns the words in some text that are misspelled. The text has its tags taken out, so positions are for the
+	/// text that was given (they get mapped back to the spell's text in the warnings).
+	fn check(&self, text: &str) -> Vec<SpellingSuggestion>;
+}
+
+/// Any function that takes text and returns misspelled words can be used as a spell checker.
+impl<F> SpellChecker for F where F: Fn(&str) -> Vec<SpellingSuggestion> + Send + Sync
+{
+	fn check(&self, text: &str) -> Vec<SpellingSuggestion> { self(text) }
+}
+
+/// The spell checker that checks the descriptions of spells while a spellbook is being created. Each misspelled word
+/// it finds becomes a warning in `Spellbook::warnings`.
+#[derive(Clone)]
+pub struct SpellCheckHook
+{
+	checker: Arc<dyn SpellChecker>
+}
+
+impl SpellCheckHook
+{
+	/// Constructor
+	///
+	/// Ex: `SpellCheckHook::new(|text: &str| my_dictionary.check(text))`
+	pub fn new(checker: impl SpellChecker + 'static) -> Self
+	{
+		Self { checker: Arc::new(checker) }
+	}
+
+	/// Returns a warning for each word that the spell checker thinks is misspelled in a spell's description and
+	/// upcast description. The spell checker gets each text with its tags taken out, and the positions of the words
+	/// it finds are mapped back to where they are in the spell's text. Words with positions that aren't in the text
+	/// are left out.
+	pub fn check(&self, spell: &Spell) -> Vec<SpellbookWarning>
+	{
+		let fields =
+		[
+			("description", Some(&spell.description)),
+			("upcast description", spell.upcast_description.as_ref())
+		];
+		let mut warnings = Vec::new();
+		for (field, text) in fields
+		{
+			let text = match text
+			{
+				Some(text) => text,
+				None => continue
+			};
+			let (plain_text, offsets) = spells::plain_text_with_offsets(text);
+			for suggestion in self.checker.check(&plain_text)
+			{
+				let (start, end) = (suggestion.start, suggestion.end);
+				if start >= end || !plain_text.is_char_boundary(start) || !plain_text.is_char_boundary(end) { continue; }
+				warnings.push(SpellbookWarning::PossibleMisspelling
+				{
+					spell_name: spell.name.clone(),
+					field: String::from(field),
+					word: String::from(&plain_text[start..end]),
+					start: offsets[start],
+					// The end of the last byte of the word
+					end: offsets[end - 1] + 1,
+					suggestions: suggestion.suggestions
+				});
+			}
+		}
+		warnings
+	}
+}
+
+impl fmt::Debug for SpellCheckHook
+{
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
+	{
+		write!(f, "SpellCheckHook")
+	}
+}
+
+// Spell checkers can't be compared, so they're only equal if they're the same spell checker
+impl PartialEq for SpellCheckHook
+{
+	fn eq(&self, other: &Self) -> bool { Arc::ptr_eq(&self.checker, &other.checker) }
+}
@@ -5,9 +5,24 @@
 //////////////////////////////////////////////////////////////////////////////////////////////////////////////
 
 use std::fmt;
+use std::collections::{BTreeMap, HashMap};
+use std::ops::Range;
 
 pub use printpdf::{ImageTransform, ImageRotation, Mm};
 
+use crate::spellbook_hooks::{DescriptionTagHandlers, PageHooks, SpellCheckHook};
+use crate::spellbook_output::SpellbookWarning;
+use crate::spells::{Spell, SpellField, Level, Table, MagicSchool, DistanceConversion, ComponentStyle,
+	MaterialComponent};
+use crate::layout::{REGULAR_FONT_TAG, BOLD_FONT_TAG};
+use crate::layout_cache::LayoutCache;
+use crate::image_cache::ImageCache;
+
+// How many printpdf Mm are in an inch
+const MM_PER_INCH: f32 = 25.4;
+// The lowest contrast ratio spell names can have with the bar behind them before they get written in black or white
+const NAME_BAR_MINIMUM_CONTRAST: f32 = 4.5;
+
 /// Conveys which variant of a font is being used.
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 #[repr(usize)]
@@ -36,13 +51,86 @@ impl fmt::Display for FontVariant
 }
 
 /// File paths to all the font files needed for `generate_spellbook()`.
-#[derive(Clone, Debug, PartialEq, Eq)]
+///
+/// The bold, italic, and bold-italic paths can be left empty for font families that don't have those variants. Those
+/// variants get synthesized from the closest variant that has a file (bold text gets outlined to make it thicker and
+/// italic text gets slanted). Page numbers don't get synthesized styles.
+#[derive(Clone, Debug, PartialEq)]
 pub struct FontPaths
 {
 	pub regular: String,
 	pub bold: String,
 	pub italic: String,
-	pub bold_italic: String
+	pub bold_italic: String,
+	/// A different font family for title text and spell names (`None` to use the font above for all text).
+	pub display: Option<DisplayFontPaths>,
+	/// Axis values to instance each font variant at if the font files are variable fonts (`None` for static fonts).
+	pub variable_axes: Option<VariableFontAxes>
+}
+
+impl FontPaths
+{
+	/// Uses a single variable font file for every font variant, each one instanced at its own axis values when the
+	/// font is loaded. Requires the `variable-fonts` feature.
+	///
+	/// # Parameters
+	///
+	/// - `path` File path to the variable font file.
+	/// - `axes` The axis values for each font variant.
+	pub fn variable(path: &str, axes: VariableFontAxes) -> Self
+	{
+		Self
+		{
+			regular: String::from(path),
+			bold: String::from(path),
+			italic: String::from(path),
+			bold_italic: String::from(path),
+			display: None,
+			variable_axes: Some(axes)
+		}
+	}
+}
+
+/// File paths to the font files of a display font that title text and spell names use instead of the main font
+/// (body text, tables, and page numbers still use the main font). Like `FontPaths`, the bold, italic, and bold-italic
+/// paths can be left empty to synthesize them.
+#[derive(Clone, Debug, PartialEq)]
+pub struct DisplayFontPaths
+{
+	pub regular: String,
+	pub bold: String,
+	pub italic: String,
+	pub bold_italic: String,
+	/// Scalar values for the display font's variants (each font needs its own since their sizes are different).
+	pub scalars: FontScalars,
+	/// Axis values to instance each font variant at if the font files are variable fonts (`None` for static fonts).
+	pub variable_axes: Option<VariableFontAxes>
+}
+
+/// Axis values for each font variant of a variable font (ex: `(String::from("wght"), 700.0)` for the bold variant).
+/// Axes that aren't given use the font's default value. Requires the `variable-fonts` feature.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct VariableFontAxes
+{
+	pub regular: Vec<(String, f32)>,
+	pub bold: Vec<(String, f32)>,
+	pub italic: Vec<(String, f32)>,
+	pub bold_italic: Vec<(String, f32)>
+}
+
+impl VariableFontAxes
+{
+	/// Returns the axis values for a specific font variant.
+	pub fn axes_for(&self, font_variant: FontVariant) -> &Vec<(String, f32)>
+	{
+		match font_variant
+		{
+			FontVariant::Regular => &self.regular,
+			FontVariant::Bold => &self.bold,
+			FontVariant::Italic => &self.italic,
+			FontVariant::BoldItalic => &self.bold_italic
+		}
+	}
 }
 
 /// Data for what font sizes to use and how large tabs and various newline sizes should be.
@@ -170,7 +258,13 @@ pub struct SpacingOptions
 	header_newline_amount: f32,
 	body_newline_amount: f32,
 	table_title_newline_amount: f32,
-	table_body_newline_amount: f32
+	table_body_newline_amount: f32,
+	title_tab_amount: f32,
+	header_tab_amount: f32,
+	body_tab_amount: f32,
+	table_title_tab_amount: f32,
+	table_body_tab_amount: f32,
+	bullet_indent: Option<f32>
 }
 
 impl SpacingOptions
@@ -179,7 +273,8 @@ impl SpacingOptions
 	///
 	/// Parameters
 	///
-	/// - `tab_amount` Tab size in printpdf Mm.
+	/// - `tab_amount` Tab size in printpdf Mm. Every type of text starts out using this tab size for indenting
+	///   paragraphs (use `with_tab_amounts()` to give them different ones).
 	/// - `title_newline_amount` Newline size for title text in printpdf Mm.
 	/// - `header_newline_amount` Newline size for spell header text in printpdf Mm.
 	/// - `body_newline_amount` Newline size for spell fields and description in printpdf Mm.
@@ -217,11 +312,81 @@ impl SpacingOptions
 				header_newline_amount: header_newline_amount,
 				body_newline_amount: body_newline_amount,
 				table_title_newline_amount: table_title_newline_amount,
-				table_body_newline_amount: table_body_newline_amount
+				table_body_newline_amount: table_body_newline_amount,
+				title_tab_amount: tab_amount,
+				header_tab_amount: tab_amount,
+				body_tab_amount: tab_amount,
+				table_title_tab_amount: tab_amount,
+				table_body_tab_amount: tab_amount,
+				bullet_indent: None
+			})
+		}
+	}
+
+	/// Gives each type of text its own tab size for indenting paragraphs.
+	/// `tab_amount` is still used for spacing that isn't tied to a type of text (like the gaps between columns).
+	///
+	/// Parameters
+	///
+	/// - `title_tab_amount` Tab size for title text in printpdf Mm.
+	/// - `header_tab_amount` Tab size for spell header text in printpdf Mm.
+	/// - `body_tab_amount` Tab size for spell fields and description in printpdf Mm.
+	/// - `table_title_tab_amount` Tab size for table title text in printpdf Mm.
+	/// - `table_body_tab_amount` Tab size for table cell text in printpdf Mm.
+	///
+	/// Output
+	///
+	/// - `Ok` This `SpacingOptions` object with the new tab sizes.
+	/// - `Err` An error message saying which parameter was invalid. Occurs for negative values.
+	pub fn with_tab_amounts
+	(
+		self,
+		title_tab_amount: f32,
+		header_tab_amount: f32,
+		body_tab_amount: f32,
+		table_title_tab_amount: f32,
+		table_body_tab_amount: f32
+	)
+	-> Result<Self, String>
+	{
+		// Makes sure no values are below 0
+		if title_tab_amount < 0.0 { Err(String::from("Invalid title_tab_amount.")) }
+		else if header_tab_amount < 0.0 { Err(String::from("Invalid header_tab_amount.")) }
+		else if body_tab_amount < 0.0 { Err(String::from("Invalid body_tab_amount.")) }
+		else if table_title_tab_amount < 0.0 { Err(String::from("Invalid table_title_tab_amount.")) }
+		else if table_body_tab_amount < 0.0 { Err(String::from("Invalid table_body_tab_amount.")) }
+		else
+		{
+			Ok(Self
+			{
+				title_tab_amount: title_tab_amount,
+				header_tab_amount: header_tab_amount,
+				body_tab_amount: body_tab_amount,
+				table_title_tab_amount: table_title_tab_amount,
+				table_body_tab_amount: table_body_tab_amount,
+				..self
 			})
 		}
 	}
 
+	/// Sets how far the lines of bullet points hang in from the bullet symbol.
+	/// Without this, the lines of bullet points line up with the end of the bullet symbol.
+	/// Bullet indents smaller than the width of the bullet symbol will make text overlap with it.
+	///
+	/// Parameters
+	///
+	/// - `bullet_indent` Distance from the left side of the text to the start of bullet point text in printpdf Mm.
+	///
+	/// Output
+	///
+	/// - `Ok` This `SpacingOptions` object with the new bullet indent.
+	/// - `Err` An error message if `bullet_indent` is negative.
+	pub fn with_bullet_indent(self, bullet_indent: f32) -> Result<Self, String>
+	{
+		if bullet_indent < 0.0 { Err(String::from("Invalid bullet_indent.")) }
+		else { Ok(Self { bullet_indent: Some(bullet_indent), ..self }) }
+	}
+
 	// Getters
 
 	pub fn tab_amount(&self) -> f32 { self.tab_amount }
@@ -230,6 +395,12 @@ impl SpacingOptions
 	pub fn body_newline_amount(&self) -> f32 { self.body_newline_amount }
 	pub fn table_title_newline_amount(&self) -> f32 { self.table_title_newline_amount }
 	pub fn table_body_newline_amount(&self) -> f32 { self.table_body_newline_amount }
+	pub fn title_tab_amount(&self) -> f32 { self.title_tab_amount }
+	pub fn header_tab_amount(&self) -> f32 { self.header_tab_amount }
+	pub fn body_tab_amount(&self) -> f32 { self.body_tab_amount }
+	pub fn table_title_tab_amount(&self) -> f32 { self.table_title_tab_amount }
+	pub fn table_body_tab_amount(&self) -> f32 { self.table_body_tab_amount }
+	pub fn bullet_indent(&self) -> Option<f32> { self.bullet_indent }
 }
 
 /// RGB colors for types of text in the spellbook.
@@ -387,7 +558,7 @@ impl PageNumberOptions
 	/// # Parameters
 	///
 	/// - `starting_side` Whether or not the page numbers start on the left side.
-	/// If the page numbers do not flip sides, this determines what side all page numbers are on.
+	///   If the page numbers do not flip sides, this determines what side all page numbers are on.
 	/// - `flips_sides` Whether or not the page numbers flip sides every page.
 	/// - `starting_num` What number to have the page numbers start on for the first page.
 	/// - `font_variant` The font variant of the page numbers (regular, bold, italic, bold-italic).
@@ -542,3 +713,2319 @@ impl TableOptions
 	// RGB
 	pub fn off_row_color(&self) -> (u8, u8, u8) { self.off_row_color }
 }
+
+/// Settings for a large print version of a spellbook that's easier to read (like for readers with low vision or
+/// dyslexia). The text gets bigger with more space between its lines, the margins get wider, and the text can use a
+/// different font (like a dyslexia-friendly font). Spacing that depends on the size of the text (like tabs, table cell
+/// margins, and the space between lines) gets scaled along with it.
+///
+/// `create_accessible_spellbook()` makes a spellbook with these settings, or each of the methods can be used to get
+/// the large print version of a spellbook option.
+#[derive(Clone, Debug, PartialEq)]
+pub struct AccessibilityPreset
+{
+	/// How much bigger the text gets (ex: 1.4 for text that's 40% bigger). Title text stays the same size.
+	pub text_scale: f32,
+	/// How much more space goes between lines of text on top of the space that bigger text already needs.
+	pub line_spacing: f32,
+	/// How much wider the page margins get. Margins don't get any wider than a quarter of the page.
+	pub margin_scale: f32,
+	/// Font files (and the scalar values for them) to use for all of the text instead of the spellbook's fonts
+	/// (`None` to keep the spellbook's fonts).
+	pub font: Option<(FontPaths, FontScalars)>
+}
+
+impl AccessibilityPreset
+{
+	/// The large print version of a spellbook's font sizes.
+	pub fn font_sizes(&self, font_sizes: &FontSizes) -> FontSizes
+	{
+		FontSizes
+		{
+			header_font_size: font_sizes.header_font_size * self.text_scale,
+			body_font_size: font_sizes.body_font_size * self.text_scale,
+			table_title_font_size: font_sizes.table_title_font_size * self.text_scale,
+			table_body_font_size: font_sizes.table_body_font_size * self.text_scale,
+			..*font_sizes
+		}
+	}
+
+	/// The large print version of a spellbook's tab and newline sizes.
+	pub fn spacing_options(&self, spacing_options: &SpacingOptions) -> SpacingOptions
+	{
+		let newline_scale = self.text_scale * self.line_spacing;
+		SpacingOptions
+		{
+			tab_amount: spacing_options.tab_amount * self.text_scale,
+			header_newline_amount: spacing_options.header_newline_amount * newline_scale,
+			body_newline_amount: spacing_options.body_newline_amount * newline_scale,
+			table_title_newline_amount: spacing_options.table_title_newline_amount * newline_scale,
+			table_body_newline_amount: spacing_options.table_body_newline_amount * newline_scale,
+			header_tab_amount: spacing_options.header_tab_amount * self.text_scale,
+			body_tab_amount: spacing_options.body_tab_amount * self.text_scale,
+			table_title_tab_amount: spacing_options.table_title_tab_amount * self.text_scale,
+			table_body_tab_amount: spacing_options.table_body_tab_amount * self.text_scale,
+			bullet_indent: spacing_options.bullet_indent.map(|bullet_indent| bullet_indent * self.text_scale),
+			..*spacing_options
+		}
+	}
+
+	/// The large print version of a spellbook's page size and margins.
+	pub fn page_size_options(&self, page_size_options: &PageSizeOptions) -> PageSizeOptions
+	{
+		let scale = |margin: f32, page_length: f32| (margin * self.margin_scale).min(page_length / 4.0).max(margin);
+		PageSizeOptions
+		{
+			left_margin: scale(page_size_options.left_margin, page_size_options.width),
+			right_margin: scale(page_size_options.right_margin, page_size_options.width),
+			top_margin: scale(page_size_options.top_margin, page_size_options.height),
+			bottom_margin: scale(page_size_options.bottom_margin, page_size_options.height),
+			..*page_size_options
+		}
+	}
+
+	/// The large print version of a spellbook's page number settings.
+	pub fn page_number_options(&self, page_number_options: &PageNumberOptions) -> PageNumberOptions
+	{
+		PageNumberOptions
+		{
+			font_size: page_number_options.font_size * self.text_scale,
+			newline_amount: page_number_options.newline_amount * self.text_scale * self.line_spacing,
+			..*page_number_options
+		}
+	}
+
+	/// The large print version of a spellbook's table settings.
+	pub fn table_options(&self, table_options: &TableOptions) -> TableOptions
+	{
+		TableOptions
+		{
+			horizontal_cell_margin: table_options.horizontal_cell_margin * self.text_scale,
+			vertical_cell_margin: table_options.vertical_cell_margin * self.text_scale,
+			outer_horizontal_margin: table_options.outer_horizontal_margin * self.text_scale,
+			outer_vertical_margin: table_options.outer_vertical_margin * self.text_scale,
+			..*table_options
+		}
+	}
+}
+
+impl Default for AccessibilityPreset
+{
+	fn default() -> Self
+	{
+		Self
+		{
+			text_scale: 1.4,
+			line_spacing: 1.2,
+			margin_scale: 1.25,
+			font: None
+		}
+	}
+}
+
+/// Options for the table of contents that goes after the title page.
+/// Each entry in the table of contents is a clickable link to the first page of its spell.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TableOfContentsOptions
+{
+	/// The heading at the top of the table of contents.
+	pub title: String,
+	/// The most columns that the entries can be split into when they don't all fit on one page (1 to keep them in one
+	/// column). The table of contents uses the fewest columns that fit it on one page, or this many if it doesn't fit
+	/// on one page with any of them.
+	pub max_columns: usize,
+	/// The order of the entries (which doesn't have to be the order of the spells in the spellbook).
+	pub order: TableOfContentsOrder
+}
+
+impl Default for TableOfContentsOptions
+{
+	fn default() -> Self
+	{
+		Self
+		{
+			title: String::from("Contents"),
+			max_columns: 1,
+			order: TableOfContentsOrder::Spellbook
+		}
+	}
+}
+
+/// The order of the entries in a table of contents. Spells are only sorted within their class chapters (if there are
+/// any).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum TableOfContentsOrder
+{
+	/// The same order as the spells in the spellbook.
+	#[default]
+	Spellbook,
+	/// Alphabetical by spell name.
+	Alphabetical,
+	/// By level and then by name, with a heading before the spells of each level (ex: "Cantrips", "1st Level").
+	Level
+}
+
+/// Short summaries of the conditions from the SRD, used by `GlossaryOptions::srd_conditions()`.
+const SRD_CONDITIONS: [(&str, &str); 15] =
+[
+	("Blinded", "You can't see and automatically fail any ability check that requires sight. Attack rolls against you have advantage, and your attack rolls have disadvantage."),
+	("Charmed", "You can't attack the charmer or target them with damaging abilities or magical effects. The charmer has advantage on ability checks to interact with you socially."),
+	("Deafened", "You can't hear and automatically fail any ability check that requires hearing."),
+	("Exhaustion", "Each level of exhaustion makes your d20 tests worse and your speed lower. Finishing a long rest removes one level, and you die if the levels build up too high."),
+	("Frightened", "You have disadvantage on ability checks and attack rolls while the source of your fear is within line of sight, and you can't willingly move closer to it."),
+	("Grappled", "Your speed is 0 and can't increase. You have disadvantage on attack rolls against anything other than the grappler, and the grappler can drag you along when they move."),
+	("Incapacitated", "You can't take actions, bonus actions, or reactions, and your concentration is broken."),
+	("Invisible", "You can't be seen without the aid of magic or a special sense. Attack rolls against you have disadvantage, and your attack rolls have advantage."),
+	("Paralyzed", "You are incapacitated and can't move or speak. You automatically fail Strength and Dexterity saving throws, attack rolls against you have advantage, and hits from within 5 feet are critical hits."),
+	("Petrified", "You are turned to an inanimate substance along with your nonmagical gear. You are incapacitated, have resistance to all damage, and are immune to poison."),
+	("Poisoned", "You have disadvantage on attack rolls and ability checks."),
+	("Prone", "Your only movement option is to crawl unless you stand up. Your attack rolls have disadvantage, and attack rolls against you have advantage from within 5 feet but disadvantage from farther away."),
+	("Restrained", "Your speed is 0 and can't increase. Attack rolls against you have advantage, your attack rolls have disadvantage, and you have disadvantage on Dexterity saving throws."),
+	("Stunned", "You are incapacitated and automatically fail Strength and Dexterity saving throws. Attack rolls against you have advantage."),
+	("Unconscious", "You are incapacitated and prone, drop whatever you're holding, and are unaware of your surroundings. You automatically fail Strength and Dexterity saving throws, and hits from within 5 feet are critical hits.")
+];
+
+/// Options for a glossary of terms (like conditions) at the end of a spellbook.
+/// Terms get sorted alphabetically and written in two columns.
+#[derive(Clone, Debug, PartialEq)]
+pub struct GlossaryOptions
+{
+	/// The heading at the top of the glossary.
+	pub title: String,
+	/// Each term in the glossary along with its definition.
+	pub entries: BTreeMap<String, String>,
+	/// Whether or not words in spell descriptions that match a term in the glossary link to that term's page.
+	pub link_keywords: bool,
+	/// Whether or not words in spell descriptions that match a term in the glossary show the term's definition in a
+	/// popup when the mouse is over them (in pdf readers that support it). The popups don't have note icons on the
+	/// page and don't get printed.
+	pub tooltips: bool
+}
+
+impl GlossaryOptions
+{
+	/// Glossary options containing short summaries of each condition from the SRD.
+	pub fn srd_conditions() -> Self
+	{
+		let entries = SRD_CONDITIONS.iter()
+		.map(|(term, definition)| (String::from(*term), String::from(*definition)))
+		.collect();
+		Self
+		{
+			entries: entries,
+			..Self::default()
+		}
+	}
+}
+
+impl Default for GlossaryOptions
+{
+	fn default() -> Self
+	{
+		Self
+		{
+			title: String::from("Glossary"),
+			entries: BTreeMap::new(),
+			link_keywords: true,
+			tooltips: false
+		}
+	}
+}
+
+/// Options for empty checkboxes next to each spell name so that printed spellbooks can be used to keep track of
+/// which spells are prepared.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PreparationCheckboxOptions
+{
+	/// Whether or not each entry in the table of contents also gets a checkbox.
+	pub in_table_of_contents: bool,
+	/// The thickness of the lines of each checkbox in printpdf Pt.
+	pub line_thickness: f32
+}
+
+impl Default for PreparationCheckboxOptions
+{
+	fn default() -> Self
+	{
+		Self
+		{
+			in_table_of_contents: false,
+			line_thickness: 0.75
+		}
+	}
+}
+
+/// Options for fillable form fields so spellbooks can be used to keep track of things in pdf readers.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct FormFieldOptions
+{
+	/// Whether or not the preparation checkboxes (see `AdditionalOptions::preparation_checkboxes`) can be checked in
+	/// pdf readers. A spell's checkbox in the table of contents is linked to the one on its page, so checking one
+	/// checks both.
+	pub fillable_preparation_checkboxes: bool,
+	/// Settings for a grid of checkboxes on the title page for tracking used spell slots (`None` for no grid).
+	pub slot_tracker: Option<SlotTrackerOptions>
+}
+
+impl Default for FormFieldOptions
+{
+	fn default() -> Self
+	{
+		Self
+		{
+			fillable_preparation_checkboxes: true,
+			slot_tracker: Some(SlotTrackerOptions::default())
+		}
+	}
+}
+
+/// Options for the grid of spell slot checkboxes at the bottom of the title page.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SlotTrackerOptions
+{
+	/// How many checkboxes each spell level gets (index 0 is level 1). Levels with 0 slots are left out of the grid.
+	pub slots_per_level: [u8; 9],
+	/// The thickness of the lines of each checkbox in printpdf Pt.
+	pub line_thickness: f32
+}
+
+impl Default for SlotTrackerOptions
+{
+	/// The spell slots of a level 20 full caster.
+	fn default() -> Self
+	{
+		Self
+		{
+			slots_per_level: [4, 3, 3, 3, 3, 2, 2, 1, 1],
+			line_thickness: 0.75
+		}
+	}
+}
+
+/// Where the notes area for each spell goes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum NotesAreaPlacement
+{
+	/// Right after the end of the spell's description.
+	EndOfSpell,
+	/// On a page of its own right after the spell.
+	FacingPage
+}
+
+/// Options for the thin tapered rule under each spell name (like the one in the Player's Handbook). The rule is
+/// thickest on the left side and tapers to a point on the right side.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct NameRuleOptions
+{
+	/// The RGB color of the rule.
+	pub color: (u8, u8, u8),
+	/// How thick the rule is at its thick end in printpdf Mm.
+	pub thickness: f32,
+	/// How much of the width of the page's text area the rule goes across (0.0 to 1.0).
+	pub length: f32
+}
+
+impl Default for NameRuleOptions
+{
+	fn default() -> Self
+	{
+		Self
+		{
+			color: (88, 24, 13),
+			thickness: 0.6,
+			length: 1.0
+		}
+	}
+}
+
+/// Options for a filled bar behind each spell name that goes across the width of the page's text area. The spell name
+/// gets written in black or white on top of the bar if its color doesn't have enough contrast with the bar.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct NameBarOptions
+{
+	/// The RGB color of the bar (the color of its left side if it has a gradient).
+	pub color: (u8, u8, u8),
+	/// The RGB color that the bar fades into from left to right (`None` for a solid bar).
+	pub gradient_color: Option<(u8, u8, u8)>,
+	/// The RGB color of spell names on the bar (`None` to use black or white if the spell name color doesn't have
+	/// enough contrast with the bar).
+	pub text_color: Option<(u8, u8, u8)>,
+	/// How far the bar goes past the spell name on each side in printpdf Mm.
+	pub padding: f32
+}
+
+impl Default for NameBarOptions
+{
+	fn default() -> Self
+	{
+		Self
+		{
+			color: (88, 24, 13),
+			gradient_color: None,
+			text_color: None,
+			padding: 1.5
+		}
+	}
+}
+
+impl NameBarOptions
+{
+	/// Returns the color that a spell name with the given color gets written in on top of the bar.
+	pub(crate) fn name_color(&self, color: (u8, u8, u8)) -> (u8, u8, u8)
+	{
+		if let Some(text_color) = self.text_color { return text_color; }
+		// Check the contrast against the color in the middle of the bar
+		let end_color = self.gradient_color.unwrap_or(self.color);
+		let middle = |start: u8, end: u8| ((start as u16 + end as u16) / 2) as u8;
+		let bar_color =
+		(middle(self.color.0, end_color.0), middle(self.color.1, end_color.1), middle(self.color.2, end_color.2));
+		if contrast_ratio(color, bar_color) >= NAME_BAR_MINIMUM_CONTRAST { color }
+		else if contrast_ratio((0, 0, 0), bar_color) >= contrast_ratio((255, 255, 255), bar_color) { (0, 0, 0) }
+		else { (255, 255, 255) }
+	}
+}
+
+/// Options for a footer on each page of a spell after its first page with the spell's level and small icons for
+/// whether it needs concentration ("C") and whether it can be cast as a ritual ("R"), so readers flipping through the
+/// book can tell what spell a page is part of.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ContinuationFooterOptions
+{
+	/// The RGB color of the footer's text and icons (`None` to use the color of body text).
+	pub color: Option<(u8, u8, u8)>,
+	/// The size of the footer's text compared to body text.
+	pub scale: f32
+}
+
+impl Default for ContinuationFooterOptions
+{
+	fn default() -> Self
+	{
+		Self
+		{
+			color: None,
+			scale: 0.8
+		}
+	}
+}
+
+/// Options for writing the casting time, range, components, and duration of each spell in a boxed grid under its level
+/// and school, with the name of each field on the left and its value on the right, instead of on their own lines.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct QuickStatsOptions
+{
+	/// The RGB color of the lines around and between the cells of the grid.
+	pub line_color: (u8, u8, u8),
+	/// How thick the lines of the grid are in printpdf Pt.
+	pub line_thickness: f32,
+	/// The RGB color that the column of field names gets shaded with (`None` for no shading).
+	pub label_fill_color: Option<(u8, u8, u8)>
+}
+
+impl Default for QuickStatsOptions
+{
+	fn default() -> Self
+	{
+		Self
+		{
+			line_color: (88, 24, 13),
+			line_thickness: 0.5,
+			label_fill_color: Some((232, 220, 200))
+		}
+	}
+}
+
+/// Options for an outline around the letters of the title on the title page (for two-tone titles like the ones on book
+/// covers). The outline is stroked around the letters of the text itself, so the title keeps crisp edges at any size
+/// and can still be searched and copied in pdf readers.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TitleOutlineOptions
+{
+	/// The RGB color of the outline.
+	pub color: (u8, u8, u8),
+	/// How thick the outline is in printpdf Pt. Half of the outline goes inside of the letters and half goes outside.
+	pub thickness: f32,
+	/// Whether the insides of the letters are filled in with the title text color (`true`) or left hollow so only the
+	/// outline is drawn (`false`).
+	pub filled: bool
+}
+
+impl Default for TitleOutlineOptions
+{
+	fn default() -> Self
+	{
+		Self
+		{
+			color: (0, 0, 0),
+			thickness: 1.5,
+			filled: true
+		}
+	}
+}
+
+/// Which way the title reads on the spine of a spellbook.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SpineDirection
+{
+	/// The title reads from the top of the spine to the bottom (like most books printed in English).
+	#[default]
+	TopToBottom,
+	/// The title reads from the bottom of the spine to the top (like most books printed in mainland Europe).
+	BottomToTop
+}
+
+/// Options for a narrow page at the very end of a spellbook with the title written sideways on it, for printing out and
+/// gluing to the spine of a bound copy of the spellbook. The page is as tall as the other pages, doesn't get a page
+/// number or background image, and doesn't count towards the page numbers.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SpineOptions
+{
+	/// How wide the spine is in printpdf Mm (depends on how many pages the spellbook has and how thick the paper is).
+	pub width: f32,
+	/// The font size of the title in printpdf Pt. The title gets shrunk if it doesn't fit on the spine.
+	pub font_size: f32,
+	/// Which way the title reads.
+	pub direction: SpineDirection,
+	/// The RGB color that the spine gets filled with (`None` to leave it blank).
+	pub background_color: Option<(u8, u8, u8)>
+}
+
+impl Default for SpineOptions
+{
+	fn default() -> Self
+	{
+		Self
+		{
+			width: 20.0,
+			font_size: 18.0,
+			direction: SpineDirection::TopToBottom,
+			background_color: None
+		}
+	}
+}
+
+/// Options for a cover wrap: a single wide page with the back cover on the left, the spine in the middle, and the front
+/// cover on the right, which is what print-on-demand services usually want for the cover of a bound book.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CoverWrapOptions
+{
+	/// How wide the spine is and how its title looks. The spine's `background_color` fills in the space between the
+	/// covers.
+	pub spine: SpineOptions,
+	/// How wide the front and back covers are in printpdf Mm (`None` to make them as wide as the pages of the
+	/// spellbook).
+	pub cover_width: Option<f32>,
+	/// How far the artwork and background color go past the edges of the cover in printpdf Mm so that nothing white
+	/// shows when the cover gets trimmed. Added to every side of the page.
+	pub bleed: f32,
+	/// A file path to an image that gets stretched over the front cover (`None` for no artwork).
+	pub front_image: Option<String>,
+	/// A file path to an image that gets stretched over the back cover (`None` for no artwork).
+	pub back_image: Option<String>,
+	/// Text that gets written on the back cover between the margins (like a description of the spellbook). Uses the
+	/// same formatting as spell descriptions.
+	pub back_text: String,
+	/// The RGB color that the whole page gets filled with under the artwork (`None` to leave it blank).
+	pub background_color: Option<(u8, u8, u8)>
+}
+
+impl Default for CoverWrapOptions
+{
+	fn default() -> Self
+	{
+		Self
+		{
+			spine: SpineOptions::default(),
+			cover_width: None,
+			bleed: 3.175,
+			front_image: None,
+			back_image: None,
+			back_text: String::new(),
+			background_color: None
+		}
+	}
+}
+
+/// Options for QR codes that link to where spells (or the whole spellbook) can be found online. The codes are drawn as
+/// vector shapes so they print cleanly at any size, and they're also clickable links in pdf readers. Needs the `qr`
+/// feature.
+#[derive(Clone, Debug, PartialEq)]
+pub struct QrCodeOptions
+{
+	/// The url in the QR code in the top right corner of each spell's first page (`None` for no codes next to spell
+	/// names). "{name}" gets replaced with the spell's name and "{slug}" gets replaced with the spell's name in
+	/// lowercase with dashes between words (ex: "https://example.com/spells/{slug}" for Fire Bolt becomes
+	/// "https://example.com/spells/fire-bolt").
+	pub spell_url_template: Option<String>,
+	/// The url in the QR code in the bottom right corner of the title page (`None` for no code on the title page).
+	/// "{title}" gets replaced with the title of the spellbook.
+	pub book_url_template: Option<String>,
+	/// The width and height of the codes next to spell names in printpdf Mm. Spell names wrap before they reach the
+	/// codes.
+	pub spell_code_size: f32,
+	/// The width and height of the code on the title page in printpdf Mm.
+	pub book_code_size: f32,
+	/// The RGB color of the dark squares of the codes.
+	pub color: (u8, u8, u8)
+}
+
+impl Default for QrCodeOptions
+{
+	fn default() -> Self
+	{
+		Self
+		{
+			spell_url_template: None,
+			book_url_template: None,
+			spell_code_size: 12.0,
+			book_code_size: 30.0,
+			color: (0, 0, 0)
+		}
+	}
+}
+
+/// Options for hyphenating words that are too long to fit on a line between their syllables (using the Knuth-Liang
+/// hyphenation patterns of a language) instead of wherever the line runs out. Words that can't be split between
+/// syllables in a way that fits still get split wherever the line runs out. Needs the `hyphenation` feature.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HyphenationOptions
+{
+	/// The two letter ISO 639-1 code of the language the text is in (ex: "en" for English or "de" for German).
+	pub language: String,
+	/// The fewest letters that can go before a hyphen in a word.
+	pub min_prefix: usize,
+	/// The fewest letters that can go after a hyphen in a word.
+	pub min_suffix: usize
+}
+
+impl Default for HyphenationOptions
+{
+	fn default() -> Self
+	{
+		Self
+		{
+			language: String::from("en"),
+			min_prefix: 2,
+			min_suffix: 3
+		}
+	}
+}
+
+/// Options for a small diagram of the shape of a spell's area of effect (a triangle for cones, a circle for spheres,
+/// etc.) that goes after the range of spells with a range of self and an area of effect. The diagram is as tall as a
+/// capital letter and is drawn in the same color as the text.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct AoeGlyphOptions
+{
+	/// Whether the shapes are filled in (`true`) or just outlined (`false`).
+	pub filled: bool,
+	/// The thickness of the outlines of the shapes in printpdf Pt (only used if the shapes aren't filled in).
+	pub line_thickness: f32
+}
+
+impl Default for AoeGlyphOptions
+{
+	fn default() -> Self
+	{
+		Self
+		{
+			filled: false,
+			line_thickness: 0.75
+		}
+	}
+}
+
+/// Options for a ruled blank area after each spell for writing notes in printed spellbooks.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct NotesAreaOptions
+{
+	/// Where the notes area goes.
+	pub placement: NotesAreaPlacement,
+	/// The height of the notes area in printpdf Mm (only used for `NotesAreaPlacement::EndOfSpell`, notes areas on
+	/// their own page fill the whole page). Notes areas never get split across pages.
+	pub height: f32,
+	/// The space between each ruled line in printpdf Mm.
+	pub line_spacing: f32,
+	/// The thickness of each ruled line in printpdf Pt.
+	pub line_thickness: f32,
+	/// The RGB color of the ruled lines.
+	pub line_color: (u8, u8, u8)
+}
+
+impl Default for NotesAreaOptions
+{
+	fn default() -> Self
+	{
+		Self
+		{
+			placement: NotesAreaPlacement::EndOfSpell,
+			height: 40.0,
+			line_spacing: 8.0,
+			line_thickness: 0.5,
+			line_color: (190, 190, 190)
+		}
+	}
+}
+
+/// Options for writing spells as a dense list of their stats (split into columns) instead of giving each spell its
+/// own pages with its full description. Useful for a quick reference to go along with a full spellbook.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct CompactListOptions
+{
+	/// How many columns the list is split into.
+	pub column_count: usize,
+	/// Whether or not the first sentence of each spell's description goes after its stats.
+	pub include_summary: bool
+}
+
+impl Default for CompactListOptions
+{
+	fn default() -> Self
+	{
+		Self
+		{
+			column_count: 2,
+			include_summary: true
+		}
+	}
+}
+
+/// Options for tighter spacing in spells that would otherwise spill a little bit onto a mostly blank page.
+/// Each spell gets measured before it's written, and the tighter spacing is only used if it makes the spell take up
+/// fewer pages.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct CompactDensityOptions
+{
+	/// How much of the normal space between the spell name and its level and school is kept (0.0 to 1.0).
+	pub header_spacing: f32,
+	/// How much of the normal space around the casting time, range, components, and duration (between them and the
+	/// level and school above them and the description below them) is kept (0.0 to 1.0).
+	pub field_spacing: f32
+}
+
+impl Default for CompactDensityOptions
+{
+	fn default() -> Self
+	{
+		Self
+		{
+			header_spacing: 0.75,
+			field_spacing: 0.75
+		}
+	}
+}
+
+/// Something that fills the empty space at the bottom of a spell's last page.
+#[derive(Clone, Debug, PartialEq)]
+pub enum PageFiller
+{
+	/// A quote written in italics and centered in the empty space, with an attribution under it if there is one
+	/// (ex: "Elminster").
+	Quote
+	{
+		text: String,
+		attribution: Option<String>
+	},
+	/// An image file (svg files need the `svg` feature) centered in the empty space. `width` is the width of the
+	/// image in printpdf Mm (the image gets shrunk down if it's too tall to fit in the empty space).
+	Image
+	{
+		path: String,
+		width: f32
+	},
+	/// A small ornament (a diamond between two tapered rules) centered in the empty space. `width` is the width of
+	/// the ornament in printpdf Mm and `color` is its RGB color.
+	Ornament
+	{
+		width: f32,
+		color: (u8, u8, u8)
+	}
+}
+
+/// Options for filling the empty space at the bottom of spells' last pages so mostly empty pages don't look sparse.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PageFillOptions
+{
+	/// What fills the empty space on each page that gets filled. They get used in order and start over from the first
+	/// one once they run out.
+	pub fillers: Vec<PageFiller>,
+	/// How much of the height of the text area has to be empty at the bottom of a spell's last page for the space to
+	/// get filled (0.0 to 1.0).
+	pub min_empty_space: f32
+}
+
+impl Default for PageFillOptions
+{
+	fn default() -> Self
+	{
+		Self
+		{
+			fillers: vec![PageFiller::Ornament { width: 40.0, color: (88, 24, 13) }],
+			min_empty_space: 0.4
+		}
+	}
+}
+
+/// The number of spell slots of each spell level (1st to 9th) that full spellcasters (bards, clerics, druids,
+/// sorcerers, and wizards) have at each class level.
+const FULL_CASTER_SPELL_SLOTS: [[u8; 9]; 20] =
+[
+	[2, 0, 0, 0, 0, 0, 0, 0, 0],
+	[3, 0, 0, 0, 0, 0, 0, 0, 0],
+	[4, 2, 0, 0, 0, 0, 0, 0, 0],
+	[4, 3, 0, 0, 0, 0, 0, 0, 0],
+	[4, 3, 2, 0, 0, 0, 0, 0, 0],
+	[4, 3, 3, 0, 0, 0, 0, 0, 0],
+	[4, 3, 3, 1, 0, 0, 0, 0, 0],
+	[4, 3, 3, 2, 0, 0, 0, 0, 0],
+	[4, 3, 3, 3, 1, 0, 0, 0, 0],
+	[4, 3, 3, 3, 2, 0, 0, 0, 0],
+	[4, 3, 3, 3, 2, 1, 0, 0, 0],
+	[4, 3, 3, 3, 2, 1, 0, 0, 0],
+	[4, 3, 3, 3, 2, 1, 1, 0, 0],
+	[4, 3, 3, 3, 2, 1, 1, 0, 0],
+	[4, 3, 3, 3, 2, 1, 1, 1, 0],
+	[4, 3, 3, 3, 2, 1, 1, 1, 0],
+	[4, 3, 3, 3, 2, 1, 1, 1, 1],
+	[4, 3, 3, 3, 3, 1, 1, 1, 1],
+	[4, 3, 3, 3, 3, 2, 1, 1, 1],
+	[4, 3, 3, 3, 3, 2, 2, 1, 1]
+];
+
+/// The number of spell slots of each spell level (1st to 5th) that half spellcasters (paladins and rangers) have at
+/// each class level.
+const HALF_CASTER_SPELL_SLOTS: [[u8; 5]; 20] =
+[
+	[2, 0, 0, 0, 0],
+	[2, 0, 0, 0, 0],
+	[3, 0, 0, 0, 0],
+	[3, 0, 0, 0, 0],
+	[4, 2, 0, 0, 0],
+	[4, 2, 0, 0, 0],
+	[4, 3, 0, 0, 0],
+	[4, 3, 0, 0, 0],
+	[4, 3, 2, 0, 0],
+	[4, 3, 2, 0, 0],
+	[4, 3, 3, 0, 0],
+	[4, 3, 3, 0, 0],
+	[4, 3, 3, 1, 0],
+	[4, 3, 3, 1, 0],
+	[4, 3, 3, 2, 0],
+	[4, 3, 3, 2, 0],
+	[4, 3, 3, 3, 1],
+	[4, 3, 3, 3, 1],
+	[4, 3, 3, 3, 2],
+	[4, 3, 3, 3, 2]
+];
+
+/// The number of pact magic spell slots warlocks have at each class level and the spell level of those slots.
+const PACT_MAGIC_SPELL_SLOTS: [(u8, u8); 20] =
+[
+	(1, 1), (2, 1), (2, 2), (2, 2), (2, 3), (2, 3), (2, 4), (2, 4), (2, 5), (2, 5),
+	(3, 5), (3, 5), (3, 5), (3, 5), (3, 5), (3, 5), (4, 5), (4, 5), (4, 5), (4, 5)
+];
+
+/// A summary of the rules for concentrating on spells, used by the reference appendix.
+const CONCENTRATION_RULES: [(&str, &str); 4] =
+[
+	("Concentration", "Some spells require you to maintain concentration to keep their magic active. If you lose concentration, the spell ends. You can end concentration at any time (no action required)."),
+	("One Spell at a Time", "You lose concentration on a spell if you cast another spell that requires concentration. You can't concentrate on two spells at once."),
+	("Taking Damage", "Whenever you take damage while concentrating, you must succeed on a Constitution saving throw to keep concentrating. The DC equals 10 or half the damage taken, whichever number is higher, up to a maximum DC of 30."),
+	("Incapacitated or Killed", "Your concentration ends if you have the incapacitated condition or you die.")
+];
+
+/// Turns a number into an ordinal (ex: "1st", "2nd", "3rd", "4th").
+fn ordinal(number: u8) -> String
+{
+	let suffix = match (number % 10, number % 100)
+	{
+		(_, 11..=13) => "th",
+		(1, _) => "st",
+		(2, _) => "nd",
+		(3, _) => "rd",
+		_ => "th"
+	};
+	format!("{}{}", number, suffix)
+}
+
+/// Turns rows of spell slot counts into the cells of a table with the class level at the start of each row.
+fn spell_slot_cells<const N: usize>(slots: &[[u8; N]; 20]) -> Vec<Vec<String>>
+{
+	slots.iter().enumerate().map(|(level, row)|
+	{
+		let mut cells = vec![ordinal(level as u8 + 1)];
+		cells.extend(row.iter().map(|count| if *count == 0 { String::from("\u{2014}") } else { count.to_string() }));
+		cells
+	})
+	.collect()
+}
+
+/// Options for reference pages at the end of a spellbook (like tables of spell slots) so the spellbook can be used at
+/// the table without a rulebook.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ReferenceAppendixOptions
+{
+	/// The heading at the top of the reference pages.
+	pub title: String,
+	/// Whether or not to add tables of the spell slots each spellcasting class has at each class level.
+	pub spell_slot_tables: bool,
+	/// Whether or not to add a summary of the rules for concentration.
+	pub concentration_rules: bool
+}
+
+impl ReferenceAppendixOptions
+{
+	/// The tables that go in the reference pages.
+	pub(crate) fn tables(&self) -> Vec<Table>
+	{
+		if !self.spell_slot_tables { return Vec::new(); }
+		let mut tables = Vec::with_capacity(3);
+		let labels = |max_spell_level: u8|
+		{
+			let mut labels = vec![String::from("Level")];
+			labels.extend((1..=max_spell_level).map(ordinal));
+			labels
+		};
+		tables.push(Table
+		{
+			title: String::from("Bard, Cleric, Druid, Sorcerer, and Wizard Spell Slots"),
+			column_labels: labels(9),
+			cells: spell_slot_cells(&FULL_CASTER_SPELL_SLOTS)
+		});
+		tables.push(Table
+		{
+			title: String::from("Paladin and Ranger Spell Slots"),
+			column_labels: labels(5),
+			cells: spell_slot_cells(&HALF_CASTER_SPELL_SLOTS)
+		});
+		tables.push(Table
+		{
+			title: String::from("Warlock Pact Magic"),
+			column_labels: vec![String::from("Level"), String::from("Spell Slots"), String::from("Slot Level")],
+			cells: PACT_MAGIC_SPELL_SLOTS.iter().enumerate()
+				.map(|(level, (count, slot_level))|
+					vec![ordinal(level as u8 + 1), count.to_string(), ordinal(*slot_level)])
+				.collect()
+		});
+		tables
+	}
+
+	/// The text of the reference pages, with table tags for each of the tables from `tables()`.
+	pub(crate) fn text(&self) -> String
+	{
+		let mut paragraphs: Vec<String> = (0..self.tables().len()).map(|index| format!("[table][{}]", index)).collect();
+		if self.concentration_rules
+		{
+			paragraphs.extend(CONCENTRATION_RULES.iter()
+				.map(|(heading, rule)| format!("{} {}. {} {}", BOLD_FONT_TAG, heading, REGULAR_FONT_TAG, rule)));
+		}
+		paragraphs.join("\n")
+	}
+}
+
+impl Default for ReferenceAppendixOptions
+{
+	fn default() -> Self
+	{
+		Self
+		{
+			title: String::from("Spellcasting Reference"),
+			spell_slot_tables: true,
+			concentration_rules: true
+		}
+	}
+}
+
+/// Options for an appendix with a table of how long each spell takes to scribe into a wizard's spellbook and how much
+/// gold it costs (with totals at the bottom), for keeping track of the spellbook in the game. Cantrips and spells
+/// with custom levels don't have a cost.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ScribingCostOptions
+{
+	/// The heading at the top of the appendix.
+	pub title: String,
+	/// The number of hours it takes to scribe a spell for each of its levels.
+	pub hours_per_level: f32,
+	/// The number of gold pieces it costs to scribe a spell for each of its levels.
+	pub gold_per_level: u32
+}
+
+impl ScribingCostOptions
+{
+	/// The table of scribing costs for a list of spells.
+	pub(crate) fn table(&self, spells: &[Spell]) -> Table
+	{
+		let mut cells = Vec::with_capacity(spells.len() + 1);
+		let mut total_hours = 0.0;
+		let mut total_gold = 0;
+		for spell in spells
+		{
+			let level = match &spell.level
+			{
+				SpellField::Controlled(level) if *level != Level::Cantrip => u8::from(level),
+				_ =>
+				{
+					cells.push(vec![spell.name.clone(), spell.level.to_string(), String::from("\u{2014}"),
+						String::from("\u{2014}")]);
+					continue;
+				}
+			};
+			let hours = self.hours_per_level * level as f32;
+			let gold = self.gold_per_level as u64 * level as u64;
+			total_hours += hours;
+			total_gold += gold;
+			cells.push(vec![spell.name.clone(), spell.level.to_string(), hours_text(hours), gold_text(gold)]);
+		}
+		cells.push(vec![String::from("Total"), String::new(), hours_text(total_hours), gold_text(total_gold)]);
+		Table
+		{
+			title: String::new(),
+			column_labels: vec![String::from("Spell"), String::from("Level"), String::from("Time"),
+				String::from("Cost")],
+			cells: cells
+		}
+	}
+}
+
+impl Default for ScribingCostOptions
+{
+	fn default() -> Self
+	{
+		Self
+		{
+			title: String::from("Scribing Costs"),
+			hours_per_level: 2.0,
+			gold_per_level: 50
+		}
+	}
+}
+
+/// How quickly a class gets access to higher level spells as it gains class levels.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub enum CasterProgression
+{
+	/// Bards, clerics, druids, sorcerers, and wizards (1st-level spells at class level 1 and 9th-level spells at class
+	/// level 17).
+	#[default]
+	Full,
+	/// Paladins and rangers (1st-level spells at class level 1 and 5th-level spells at class level 17).
+	Half,
+	/// Warlocks (pact magic up to 5th-level spells at class level 9 and then a mystic arcanum for each spell level
+	/// from 6th at class level 11 to 9th at class level 17).
+	Pact,
+	/// The highest spell level the class can cast at each class level, starting at class level 1 (0 for only
+	/// cantrips). Class levels after the end of the list use the last one.
+	Custom(Vec<u8>)
+}
+
+impl CasterProgression
+{
+	/// The highest spell level a class can cast at a class level (0 for only cantrips). Class levels above 20 are
+	/// treated as 20.
+	pub fn max_spell_level(&self, class_level: u8) -> u8
+	{
+		let index = class_level.clamp(1, 20) as usize - 1;
+		// The highest spell level with any spell slots
+		let highest_slot = |slots: &[u8]| slots.iter().rposition(|count| *count > 0).map_or(0, |index| index as u8 + 1);
+		match self
+		{
+			Self::Full => highest_slot(&FULL_CASTER_SPELL_SLOTS[index]),
+			Self::Half => highest_slot(&HALF_CASTER_SPELL_SLOTS[index]),
+			// Mystic arcanum spells come every other level after pact magic tops out at 5th level
+			Self::Pact if index >= 10 => (6 + (index as u8 - 10) / 2).min(9),
+			Self::Pact => PACT_MAGIC_SPELL_SLOTS[index].1,
+			Self::Custom(levels) => match levels.get(index).or(levels.last())
+			{
+				Some(level) => *level,
+				None => 0
+			}
+		}
+	}
+
+	/// The lowest class level (1 to 20) that a class can cast spells of a certain level at (`None` if it never can).
+	pub fn class_level_for(&self, level: &Level) -> Option<u8>
+	{
+		let spell_level = u8::from(level);
+		(1..=20).find(|class_level| self.max_spell_level(*class_level) >= spell_level)
+	}
+}
+
+/// Options for an appendix with a table of the class level that each spell in the spellbook becomes available at for
+/// a character (with the page each spell is on), for planning which spells to learn as the character levels up.
+#[derive(Clone, Debug, PartialEq)]
+pub struct LevelPlanOptions
+{
+	/// The heading at the top of the appendix.
+	pub title: String,
+	/// Only spells with this class in their `classes` field go in the table (`None` for every spell). Capitalization
+	/// doesn't matter.
+	pub class: Option<String>,
+	/// How quickly the character's class gets access to higher level spells.
+	pub progression: CasterProgression
+}
+
+impl LevelPlanOptions
+{
+	/// The table of the class level each spell becomes available at (sorted by class level and then by spell level
+	/// and name), given the page number of each spell. Spells the class never gets access to (or that have custom
+	/// levels) go at the end.
+	pub(crate) fn table(&self, spells: &[Spell], page_numbers: &[String]) -> Table
+	{
+		// The class level each spell becomes available at, its spell level and name (for sorting), and its cells
+		type Row = (Option<u8>, (u8, String), Vec<String>);
+		let mut rows: Vec<Row> = spells.iter().zip(page_numbers)
+			.filter(|(spell, _)| match &self.class
+			{
+				Some(class) => spell.has_class(class),
+				None => true
+			})
+			.map(|(spell, page_number)|
+			{
+				let class_level = match &spell.level
+				{
+					SpellField::Controlled(level) => self.progression.class_level_for(level),
+					SpellField::Custom(_) => None
+				};
+				let class_level_text = match class_level
+				{
+					Some(class_level) => ordinal(class_level),
+					None => String::from("\u{2014}")
+				};
+				let cells = vec![class_level_text, spell.name.clone(), spell.level.to_string(), page_number.clone()];
+				(class_level, spell.level_sort_key(), cells)
+			})
+			.collect();
+		rows.sort_by(|(class_level_a, key_a, _), (class_level_b, key_b, _)|
+			(class_level_a.unwrap_or(u8::MAX), key_a).cmp(&(class_level_b.unwrap_or(u8::MAX), key_b)));
+		Table
+		{
+			title: String::new(),
+			column_labels: vec![String::from("Class Level"), String::from("Spell"), String::from("Spell Level"),
+				String::from("Page")],
+			cells: rows.into_iter().map(|(_, _, cells)| cells).collect()
+		}
+	}
+}
+
+impl Default for LevelPlanOptions
+{
+	fn default() -> Self
+	{
+		Self
+		{
+			title: String::from("Spell Progression"),
+			class: None,
+			progression: CasterProgression::Full
+		}
+	}
+}
+
+/// Options for an appendix with a checklist of the material components of the spells in a spellbook, like a shopping
+/// list for a component pouch. Components that are written the same way in more than one spell are only listed once,
+/// and the costs of components in gold pieces get added up at the bottom.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ComponentListOptions
+{
+	/// The heading at the top of the appendix.
+	pub title: String,
+	/// Whether or not components without a cost in gold pieces get listed (a component pouch already has those).
+	pub include_free_components: bool
+}
+
+impl ComponentListOptions
+{
+	/// The checklist of material components for a list of spells (sorted by the text of each component) with the
+	/// total cost at the bottom. The first column is left empty for checking components off.
+	pub(crate) fn table(&self, spells: &[Spell]) -> Table
+	{
+		// Each component along with the names of the spells that need it
+		let mut components: Vec<(MaterialComponent, Vec<String>)> = Vec::new();
+		for spell in spells
+		{
+			let component = match spell.material_component()
+			{
+				Some(component) if self.include_free_components || component.cost.is_some() => component,
+				_ => continue
+			};
+			let existing = components.iter_mut()
+				.find(|(other, _)| other.description.eq_ignore_ascii_case(&component.description));
+			match existing
+			{
+				Some((other, spell_names)) =>
+				{
+					other.consumed |= component.consumed;
+					if !spell_names.contains(&spell.name) { spell_names.push(spell.name.clone()); }
+				},
+				None => components.push((component, vec![spell.name.clone()]))
+			}
+		}
+		components.sort_by_key(|(component, _)| component.description.to_lowercase());
+		let total_cost: u64 = components.iter().filter_map(|(component, _)| component.cost).sum();
+		let mut cells: Vec<Vec<String>> = components.into_iter().map(|(component, spell_names)|
+		{
+			let cost = match (component.cost, component.consumed)
+			{
+				(Some(cost), true) => format!("{} (consumed)", gold_text(cost)),
+				(Some(cost), false) => gold_text(cost),
+				(None, _) => String::from("\u{2014}")
+			};
+			vec![String::new(), component.description, cost, spell_names.join(", ")]
+		})
+		.collect();
+		cells.push(vec![String::new(), String::from("Total"), gold_text(total_cost), String::new()]);
+		Table
+		{
+			title: String::new(),
+			column_labels: vec![String::from("Got"), String::from("Component"), String::from("Cost"),
+				String::from("Spells")],
+			cells: cells
+		}
+	}
+}
+
+impl Default for ComponentListOptions
+{
+	fn default() -> Self
+	{
+		Self
+		{
+			title: String::from("Material Components"),
+			include_free_components: true
+		}
+	}
+}
+
+/// Turns a number of hours into text (ex: "1 hour" or "4.5 hours").
+fn hours_text(hours: f32) -> String
+{
+	// Round off any floating point error from adding up the hours
+	let hours = (hours * 100.0).round() / 100.0;
+	if hours == 1.0 { String::from("1 hour") } else { format!("{} hours", hours) }
+}
+
+/// Turns a number of gold pieces into text with commas between every 3 digits (ex: "1,350 gp").
+fn gold_text(gold: u64) -> String
+{
+	let digits: Vec<char> = gold.to_string().chars().collect();
+	let groups: Vec<String> = digits.rchunks(3).rev().map(|group| group.iter().collect()).collect();
+	format!("{} gp", groups.join(","))
+}
+
+/// Options for coloring the names of spells by their school of magic.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SchoolColorOptions
+{
+	/// The RGB color of the names of spells in each school (including homebrew schools). Spells in schools that
+	/// aren't in here (and spells with custom schools) use the normal header color.
+	pub name_colors: HashMap<MagicSchool, (u8, u8, u8)>
+}
+
+impl Default for SchoolColorOptions
+{
+	fn default() -> Self
+	{
+		let name_colors = HashMap::from
+		([
+			(MagicSchool::Abjuration, (40, 90, 160)),
+			(MagicSchool::Conjuration, (175, 120, 20)),
+			(MagicSchool::Divination, (110, 110, 120)),
+			(MagicSchool::Enchantment, (170, 50, 130)),
+			(MagicSchool::Evocation, (175, 40, 30)),
+			(MagicSchool::Illusion, (100, 60, 160)),
+			(MagicSchool::Necromancy, (40, 100, 60)),
+			(MagicSchool::Transmutation, (150, 90, 40))
+		]);
+		Self { name_colors: name_colors }
+	}
+}
+
+/// Options for a row of small colored chips under the level and school of each spell with an abbreviation of each
+/// class that has the spell on its spell list (ex: "WIZ" and "SOR" for a spell that wizards and sorcerers can learn).
+/// Spells without any classes don't get chips.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ClassChipOptions
+{
+	/// The RGB color of the chips of each class (capital letters don't matter). Classes that aren't in here (like
+	/// homebrew classes) use `default_color`.
+	pub colors: BTreeMap<String, (u8, u8, u8)>,
+	/// The RGB color of the chips of classes that aren't in `colors`.
+	pub default_color: (u8, u8, u8),
+	/// The RGB color of the abbreviations on the chips.
+	pub text_color: (u8, u8, u8),
+	/// How many letters from the start of each class name go in its abbreviation.
+	pub abbreviation_length: usize,
+	/// How round the corners of the chips are, from 0 for square corners to 1 for fully round ends.
+	pub roundness: f32
+}
+
+impl ClassChipOptions
+{
+	/// The color of the chips of a class.
+	pub(crate) fn color(&self, class: &str) -> (u8, u8, u8)
+	{
+		self.colors.iter()
+		.find(|(name, _)| name.eq_ignore_ascii_case(class))
+		.map_or(self.default_color, |(_, color)| *color)
+	}
+
+	/// The abbreviation of a class that goes on its chip (ex: "WIZ" for "Wizard").
+	pub(crate) fn abbreviation(&self, class: &str) -> String
+	{
+		class.trim().chars().take(self.abbreviation_length.max(1)).collect::<String>().to_uppercase()
+	}
+}
+
+impl Default for ClassChipOptions
+{
+	fn default() -> Self
+	{
+		let colors = BTreeMap::from
+		([
+			(String::from("Artificer"), (165, 105, 40)),
+			(String::from("Bard"), (160, 60, 140)),
+			(String::from("Cleric"), (185, 140, 30)),
+			(String::from("Druid"), (70, 120, 50)),
+			(String::from("Paladin"), (45, 110, 170)),
+			(String::from("Ranger"), (35, 105, 85)),
+			(String::from("Sorcerer"), (175, 40, 35)),
+			(String::from("Warlock"), (95, 50, 135)),
+			(String::from("Wizard"), (40, 65, 150))
+		]);
+		Self
+		{
+			colors: colors,
+			default_color: (110, 110, 110),
+			text_color: (255, 255, 255),
+			abbreviation_length: 3,
+			roundness: 0.5
+		}
+	}
+}
+
+/// Where the upcast description of a spell goes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum UpcastPlacement
+{
+	/// In the last paragraph of the description, starting with the prefix in bold-italics
+	/// (ex: "*Using a Higher-Level Spell Slot.* The damage increases by 1d6...").
+	Inline,
+	/// In its own block after the description with the prefix as a heading on its own line.
+	SeparateBlock
+}
+
+/// Options for how the upcast description of each spell is written.
+#[derive(Clone, Debug, PartialEq)]
+pub struct UpcastOptions
+{
+	/// The text before the upcast description of cantrips.
+	pub cantrip_prefix: String,
+	/// The text before the upcast description of spells that aren't cantrips.
+	pub leveled_prefix: String,
+	/// Where the upcast description goes.
+	pub placement: UpcastPlacement
+}
+
+impl UpcastOptions
+{
+	/// The prefixes used in the 2024 Player's Handbook ("Cantrip Upgrade" and "Using a Higher-Level Spell Slot").
+	pub fn players_handbook_2024() -> Self
+	{
+		Self
+		{
+			cantrip_prefix: String::from("Cantrip Upgrade"),
+			leveled_prefix: String::from("Using a Higher-Level Spell Slot"),
+			placement: UpcastPlacement::Inline
+		}
+	}
+
+	/// The prefix used in the 2014 Player's Handbook ("At Higher Levels").
+	pub fn players_handbook_2014() -> Self
+	{
+		Self
+		{
+			cantrip_prefix: String::from("At Higher Levels"),
+			leveled_prefix: String::from("At Higher Levels"),
+			placement: UpcastPlacement::Inline
+		}
+	}
+}
+
+impl Default for UpcastOptions
+{
+	fn default() -> Self { Self::players_handbook_2024() }
+}
+
+/// Options for a small table under each cantrip whose damage increases when characters reach certain levels, with the
+/// damage at each tier of character levels (ex: 1d10 at levels 1-4, 2d10 at levels 5-10, etc.). The tiers are picked
+/// out of the text about the increase, so the table goes after the upcast description if that's where the text is.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CantripScalingOptions
+{
+	/// The title above each table (empty for no title).
+	pub title: String,
+	/// The label above the column of character levels.
+	pub level_label: String,
+	/// The label above the column of damage.
+	pub damage_label: String
+}
+
+impl CantripScalingOptions
+{
+	/// The table of damage for tiers of character levels from `Spell::cantrip_damage_tiers()`. The last tier goes up
+	/// to level 20.
+	pub(crate) fn table(&self, tiers: &[(u8, String)]) -> Table
+	{
+		let cells = tiers.iter().enumerate().map(|(index, (level, damage))|
+		{
+			let last_level = tiers.get(index + 1).map_or(20, |(next_level, _)| next_level - 1);
+			let levels = if last_level > *level { format!("{}\u{2013}{}", level, last_level) }
+			else { level.to_string() };
+			vec![levels, damage.clone()]
+		})
+		.collect();
+		Table
+		{
+			title: self.title.clone(),
+			column_labels: vec![self.level_label.clone(), self.damage_label.clone()],
+			cells: cells
+		}
+	}
+}
+
+impl Default for CantripScalingOptions
+{
+	fn default() -> Self
+	{
+		Self
+		{
+			title: String::new(),
+			level_label: String::from("Character Level"),
+			damage_label: String::from("Damage")
+		}
+	}
+}
+
+/// Determines how the bookmark for each spell in a spellbook gets named.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum BookmarkNamePolicy
+{
+	/// Every bookmark is just the name of its spell, even if multiple spells have the same name.
+	SpellName,
+	/// Spells that share a name with another spell get their source added to their bookmark (ex: "Fireball (XGE)").
+	/// If bookmarks are still the same after that, a number is added to the end of each one after the first
+	/// (ex: "Fireball (2)").
+	#[default]
+	DisambiguateDuplicates,
+	/// Every spell with a source gets it added to its bookmark, then bookmarks that are still the same get numbered
+	/// like `DisambiguateDuplicates`.
+	AlwaysIncludeSource
+}
+
+/// How spells that belong to class chapters end up in a spellbook.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ClassChapterLayout
+{
+	/// Each chapter starts with a page with its title on it followed by a copy of every spell the class has (sorted
+	/// by level and then by name). Spells that belong to more than one class show up in each of their chapters.
+	#[default]
+	RepeatSpells,
+	/// Each spell is only written once (in the order the spells were given) and each chapter is a list of the spells
+	/// the class has (grouped by level) that link to the spells. The lists go after the spells.
+	ClassListPages
+}
+
+/// A chapter of a spellbook for the spells of one class.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ClassChapter
+{
+	/// The class whose spells go in this chapter (ex: "Wizard"). Compared to the `classes` of each spell without
+	/// caring about capitalization.
+	pub class: String,
+	/// The title of the chapter (used on its first page, in the table of contents, and for its bookmark).
+	pub title: String,
+	/// Text that goes before the page numbers of the chapter (ex: "W-" for page numbers like "W-1").
+	/// Page numbers start over at the starting page number in each chapter (only used for
+	/// `ClassChapterLayout::RepeatSpells`).
+	pub page_number_prefix: String
+}
+
+impl ClassChapter
+{
+	/// Makes a chapter for a class titled "<class> Spells" with page numbers that start with the first letter of the
+	/// class (ex: "Wizard" becomes "Wizard Spells" with page numbers like "W-1").
+	pub fn new(class: &str) -> Self
+	{
+		let initial: String = class.chars().take(1).flat_map(char::to_uppercase).collect();
+		Self
+		{
+			class: String::from(class),
+			title: format!("{} Spells", class),
+			page_number_prefix: if initial.is_empty() { String::new() } else { format!("{}-", initial) }
+		}
+	}
+}
+
+/// Options for splitting a spellbook into a chapter for each class.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ClassChapterOptions
+{
+	/// The chapters in the order they go in the spellbook. If this is empty, every class any of the spells have gets a
+	/// chapter (made with `ClassChapter::new()`) in alphabetical order.
+	pub chapters: Vec<ClassChapter>,
+	/// Whether spells get repeated in each chapter or listed on a page for each chapter.
+	pub layout: ClassChapterLayout
+}
+
+impl ClassChapterOptions
+{
+	/// The chapters of a list of spells (see `chapters`).
+	pub fn chapters_for(&self, spells: &Vec<Spell>) -> Vec<ClassChapter>
+	{
+		if !self.chapters.is_empty() { return self.chapters.clone(); }
+		let mut classes: Vec<&String> = spells.iter().flat_map(|spell| &spell.classes).collect();
+		classes.sort_by_key(|class| class.to_lowercase());
+		classes.dedup_by_key(|class| class.to_lowercase());
+		classes.into_iter().map(|class| ClassChapter::new(class)).collect()
+	}
+}
+
+/// Soft limits on how big parts of a spell can get before a warning is given about it. Spells that go over these
+/// limits still get added to the spellbook, but they tend to make giant or unreadable pages.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SpellLimits
+{
+	/// The most characters a spell's name can have.
+	pub max_name_length: usize,
+	/// The most columns a table in a spell's description can have.
+	pub max_table_columns: usize,
+	/// The most characters a spell's description and upcast description can have combined.
+	pub max_description_length: usize
+}
+
+impl Default for SpellLimits
+{
+	/// Limits that no spell in the official books goes over.
+	fn default() -> Self
+	{
+		Self
+		{
+			max_name_length: 50,
+			max_table_columns: 8,
+			max_description_length: 6000
+		}
+	}
+}
+
+impl SpellLimits
+{
+	/// Returns a warning for each limit that a spell goes over.
+	pub fn check(&self, spell: &Spell) -> Vec<SpellbookWarning>
+	{
+		let mut warnings = Vec::new();
+		let name_length = spell.name.chars().count();
+		if name_length > self.max_name_length
+		{
+			warnings.push(SpellbookWarning::NameTooLong
+			{
+				spell_name: spell.name.clone(),
+				length: name_length,
+				limit: self.max_name_length
+			});
+		}
+		for (table_index, table) in spell.tables.iter().enumerate()
+		{
+			// The widest row of the table (including the column labels)
+			let columns = table.cells.iter().map(|row| row.len()).chain([table.column_labels.len()]).max().unwrap_or(0);
+			if columns > self.max_table_columns
+			{
+				warnings.push(SpellbookWarning::TooManyTableColumns
+				{
+					spell_name: spell.name.clone(),
+					table_index: table_index,
+					columns: columns,
+					limit: self.max_table_columns
+				});
+			}
+		}
+		let description_length = spell.description.chars().count() +
+		spell.upcast_description.as_ref().map_or(0, |upcast| upcast.chars().count());
+		if description_length > self.max_description_length
+		{
+			warnings.push(SpellbookWarning::DescriptionTooLong
+			{
+				spell_name: spell.name.clone(),
+				length: description_length,
+				limit: self.max_description_length
+			});
+		}
+		warnings
+	}
+}
+
+/// Options for checking that text can be read on top of the background image. The part of the background image under
+/// the text area of the pages (between the margins) gets averaged into one color, and each text color that doesn't
+/// have enough contrast with it gets a warning in `Spellbook::warnings`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ContrastCheckOptions
+{
+	/// The lowest contrast ratio allowed between a text color and the background (from 1.0 for no contrast to 21.0
+	/// for black on white). 4.5 is the minimum that the Web Content Accessibility Guidelines give for normal text.
+	pub minimum_ratio: f32,
+	/// Darkens text colors that don't have enough contrast until they do (or lightens them on dark backgrounds)
+	/// instead of only giving warnings about them.
+	pub adjust_colors: bool
+}
+
+impl Default for ContrastCheckOptions
+{
+	fn default() -> Self
+	{
+		Self
+		{
+			minimum_ratio: 4.5,
+			adjust_colors: false
+		}
+	}
+}
+
+impl ContrastCheckOptions
+{
+	/// Returns a warning for each text color that doesn't have enough contrast with the average color of the
+	/// background behind it. The title is checked against the background of the title page and everything else is
+	/// checked against the background of the other pages. Colors get changed to have enough contrast if
+	/// `adjust_colors` is true.
+	pub(crate) fn check
+	(
+		&self,
+		text_colors: &mut TextColorOptions,
+		background_color: (u8, u8, u8),
+		title_page_background_color: (u8, u8, u8)
+	)
+	-> Vec<SpellbookWarning>
+	{
+		let colors =
+		[
+			("title", &mut text_colors.title_color, title_page_background_color),
+			("header", &mut text_colors.header_color, background_color),
+			("body", &mut text_colors.body_color, background_color),
+			("table title", &mut text_colors.table_title_color, background_color),
+			("table body", &mut text_colors.table_body_color, background_color)
+		];
+		let mut warnings = Vec::new();
+		for (text, color, background_color) in colors
+		{
+			if contrast_ratio(*color, background_color) >= self.minimum_ratio { continue; }
+			let original_color = *color;
+			let adjusted_color = if self.adjust_colors
+			{
+				*color = self.adjusted_color(original_color, background_color);
+				Some(*color)
+			}
+			else { None };
+			warnings.push(SpellbookWarning::LowContrast
+			{
+				text: String::from(text),
+				color: original_color,
+				background_color: background_color,
+				adjusted_color: adjusted_color
+			});
+		}
+		warnings
+	}
+
+	/// Returns a text color moved toward black (or toward white if the background is dark) just far enough to have
+	/// the minimum contrast with the background. Returns black or white if even they don't have enough contrast.
+	fn adjusted_color(&self, color: (u8, u8, u8), background_color: (u8, u8, u8)) -> (u8, u8, u8)
+	{
+		// Go toward whichever of black and white has more contrast with the background
+		let target = if contrast_ratio((0, 0, 0), background_color) >= contrast_ratio((255, 255, 255), background_color)
+		{ 0.0 }
+		else { 255.0 };
+		let mix = |amount: f32|
+		{
+			let channel = |value: u8| (value as f32 + (target - value as f32) * amount).round() as u8;
+			(channel(color.0), channel(color.1), channel(color.2))
+		};
+		// Find the smallest amount of mixing that has enough contrast
+		let mut amounts = (0.0, 1.0);
+		for _ in 0..16
+		{
+			let middle = (amounts.0 + amounts.1) / 2.0;
+			if contrast_ratio(mix(middle), background_color) >= self.minimum_ratio { amounts.1 = middle; }
+			else { amounts.0 = middle; }
+		}
+		mix(amounts.1)
+	}
+}
+
+/// Converts a channel of an sRGB color (0.0 to 1.0) into linear light.
+pub(crate) fn srgb_to_linear(value: f32) -> f32
+{
+	if value <= 0.04045 { value / 12.92 } else { ((value + 0.055) / 1.055).powf(2.4) }
+}
+
+/// Converts a channel of linear light (0.0 to 1.0) into an sRGB color channel.
+pub(crate) fn linear_to_srgb(value: f32) -> f32
+{
+	if value <= 0.0031308 { value * 12.92 } else { 1.055 * value.powf(1.0 / 2.4) - 0.055 }
+}
+
+/// The relative luminance of an RGB color (0.0 for black to 1.0 for white).
+fn relative_luminance(color: (u8, u8, u8)) -> f32
+{
+	let channel = |value: u8| srgb_to_linear(value as f32 / 255.0);
+	0.2126 * channel(color.0) + 0.7152 * channel(color.1) + 0.0722 * channel(color.2)
+}
+
+/// The contrast ratio between two RGB colors (from 1.0 for the same color to 21.0 for black and white).
+pub(crate) fn contrast_ratio(color_1: (u8, u8, u8), color_2: (u8, u8, u8)) -> f32
+{
+	let (luminance_1, luminance_2) = (relative_luminance(color_1), relative_luminance(color_2));
+	(luminance_1.max(luminance_2) + 0.05) / (luminance_1.min(luminance_2) + 0.05)
+}
+
+/// Ways to automatically size and position a background image on each page so the scale factors of its
+/// `ImageTransform` don't have to be figured out by hand.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BackgroundFit
+{
+	/// Stretches the image to be exactly the size of the page (the image can get squished).
+	Stretch,
+	/// Scales the image to cover the whole page without squishing it, centered on the page.
+	/// Parts of the image can go past the edges of the page.
+	Cover,
+	/// Scales the image to be as large as it can be while still fitting on the page without squishing it,
+	/// centered on the page.
+	Contain,
+	/// Repeats the image at its actual size across the whole page, starting at the top left corner.
+	Tile,
+	/// Places the image at its actual size in the top left corner of the page.
+	ActualSize
+}
+
+impl BackgroundFit
+{
+	/// Calculates where a background image should go on a page.
+	///
+	/// # Parameters
+	///
+	/// - `image_width` The width of the image in pixels.
+	/// - `image_height` The height of the image in pixels.
+	/// - `dpi` How many pixels of the image go in an inch of the page at the image's actual size.
+	/// - `page_width` The width of the page in printpdf Mm.
+	/// - `page_height` The height of the page in printpdf Mm.
+	///
+	/// # Output
+	///
+	/// The transform for each copy of the image that goes on the page (only `Tile` has more than one).
+	pub fn transforms(&self, image_width: u32, image_height: u32, dpi: f32, page_width: f32, page_height: f32)
+	-> Vec<ImageTransform>
+	{
+		// The size of the image on the page at its actual size
+		let actual_width = image_width as f32 / dpi * MM_PER_INCH;
+		let actual_height = image_height as f32 / dpi * MM_PER_INCH;
+		// Empty images can't be placed anywhere
+		if actual_width <= 0.0 || actual_height <= 0.0 || !actual_width.is_finite() || !actual_height.is_finite()
+		{
+			return Vec::new();
+		}
+		// Makes the transform for an image with a bottom left corner at (x, y) and the given scale factors
+		let transform = |x: f32, y: f32, scale_x: f32, scale_y: f32| ImageTransform
+		{
+			translate_x: Some(Mm(x)),
+			translate_y: Some(Mm(y)),
+			scale_x: Some(scale_x),
+			scale_y: Some(scale_y),
+			dpi: Some(dpi),
+			..Default::default()
+		};
+		// Makes the transform for an image scaled evenly to be centered on the page
+		let centered = |scale: f32| transform
+		(
+			(page_width - actual_width * scale) / 2.0,
+			(page_height - actual_height * scale) / 2.0,
+			scale,
+			scale
+		);
+		match self
+		{
+			Self::Stretch => vec![transform(0.0, 0.0, page_width / actual_width, page_height / actual_height)],
+			Self::Cover => vec![centered((page_width / actual_width).max(page_height / actual_height))],
+			Self::Contain => vec![centered((page_width / actual_width).min(page_height / actual_height))],
+			Self::Tile =>
+			{
+				let columns = (page_width / actual_width).ceil() as usize;
+				let rows = (page_height / actual_height).ceil() as usize;
+				let mut transforms = Vec::with_capacity(columns * rows);
+				// Go down the page from the top so the top left corner of the page has the top left corner of an image
+				for row in 1..=rows
+				{
+					for column in 0..columns
+					{
+						transforms.push(transform
+						(
+							column as f32 * actual_width,
+							page_height - row as f32 * actual_height,
+							1.0,
+							1.0
+						));
+					}
+				}
+				transforms
+			},
+			Self::ActualSize => vec![transform(0.0, page_height - actual_height, 1.0, 1.0)]
+		}
+	}
+}
+
+/// Background images for the title page and the title pages of class chapters that are different from the background
+/// of the rest of the spellbook (ex: cover art on the title page and parchment everywhere else). `background_fit` is
+/// used for these images too, but they don't alternate between pages.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SectionBackgroundOptions
+{
+	/// The file path to the background image of the title page and the transform for placing it (`None` to use the
+	/// normal background).
+	pub title_page: Option<(String, ImageTransform)>,
+	/// The file path to the background image of the page at the start of each class chapter and the transform for
+	/// placing it (`None` to use the normal background). Pages that list the spells of a class don't use it.
+	pub chapter_pages: Option<(String, ImageTransform)>
+}
+
+/// Ways to change the background image on every other page (pages with an odd index, which are the left-hand pages
+/// of a printed book) so both sides of a spread match (ex: the darker edge of the image always faces the outer margin).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BackgroundAlternation
+{
+	/// Flips the image left to right across the middle of the page.
+	MirrorHorizontally,
+	/// Flips the image top to bottom across the middle of the page.
+	MirrorVertically,
+	/// Turns the image upside down around the middle of the page.
+	Rotate180
+}
+
+impl BackgroundAlternation
+{
+	/// Calculates the transform of a background image for the pages that get changed.
+	///
+	/// # Parameters
+	///
+	/// - `transform` The transform of the image on the other pages.
+	/// - `page_width` The width of the page in printpdf Mm.
+	/// - `page_height` The height of the page in printpdf Mm.
+	///
+	/// # Output
+	///
+	/// The transform of the image on the pages that get changed.
+	pub fn apply(&self, transform: &ImageTransform, page_width: f32, page_height: f32) -> ImageTransform
+	{
+		let (mirror_x, mirror_y) = match self
+		{
+			Self::MirrorHorizontally => (true, false),
+			Self::MirrorVertically => (false, true),
+			Self::Rotate180 => (true, true)
+		};
+		let mut alternate = *transform;
+		// A negative scale flips the image over its own bottom left corner, so the corner has to be moved to the other
+		// side of the page too
+		if mirror_x
+		{
+			alternate.translate_x = Some(Mm(page_width - transform.translate_x.unwrap_or(Mm(0.0)).0));
+			alternate.scale_x = Some(-transform.scale_x.unwrap_or(1.0));
+		}
+		if mirror_y
+		{
+			alternate.translate_y = Some(Mm(page_height - transform.translate_y.unwrap_or(Mm(0.0)).0));
+			alternate.scale_y = Some(-transform.scale_y.unwrap_or(1.0));
+		}
+		alternate
+	}
+}
+
+/// Options for justified text, where the spaces in every line of a paragraph except the last get stretched so that the
+/// line reaches the right side of the text box. Lines that would need their spaces stretched too far to do that (like
+/// lines with only a couple of long words in a narrow table cell or column) are left ragged-right instead of getting
+/// big gaps between their words.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct JustificationOptions
+{
+	/// The most a space can be stretched to as a multiple of its normal width (ex: 2.0 lets spaces be twice as wide).
+	pub max_space_stretch: f32,
+	/// The fewest spaces a line needs to have to be justified. Lines with fewer spaces are left ragged-right.
+	pub min_spaces: usize,
+	/// Whether the text in table cells gets justified too (`false` to keep table cells ragged-right).
+	pub tables: bool
+}
+
+impl Default for JustificationOptions
+{
+	fn default() -> Self
+	{
+		Self
+		{
+			max_space_stretch: 2.0,
+			min_spaces: 3,
+			tables: false
+		}
+	}
+}
+
+/// Minimum numbers of lines of a paragraph that have to stay together when the paragraph goes across a page break.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ParagraphBreakOptions
+{
+	/// The fewest lines of a paragraph that can be left at the bottom of a page before a page break (orphans).
+	/// Paragraphs that would leave fewer lines than this get moved to the next page.
+	pub min_lines_before_break: usize,
+	/// The fewest lines of a paragraph that can be carried over to the top of the next page (widows).
+	/// Paragraphs that would carry over fewer lines than this move the page break up to carry over more lines.
+	pub min_lines_after_break: usize
+}
+
+impl Default for ParagraphBreakOptions
+{
+	fn default() -> Self
+	{
+		Self
+		{
+			min_lines_before_break: 2,
+			min_lines_after_break: 2
+		}
+	}
+}
+
+/// Where page breaks go around spells and their tables. Spells can override these with the `layout` field in their
+/// spell files (see `spells::LayoutHints`).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SpellLayoutOptions
+{
+	/// Whether each spell starts on a new page (`false` to start spells right after the spell before them when
+	/// there's room for their name and fields on the same page).
+	pub start_on_new_page: bool,
+	/// Whether tables can be split across a page break (`false` to move tables that fit on a single page to the next
+	/// page instead). Tables that are too tall to fit on a single page always get split.
+	pub allow_table_split: bool
+}
+
+impl Default for SpellLayoutOptions
+{
+	fn default() -> Self
+	{
+		Self
+		{
+			start_on_new_page: true,
+			allow_table_split: false
+		}
+	}
+}
+
+/// Which footnotes share a sequence of footnote numbers.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FootnoteNumbering
+{
+	/// Footnote numbers start over at 1 on each page.
+	PerPage,
+	/// Footnote numbers start over at 1 in each spell.
+	PerSpell
+}
+
+/// Options for footnotes in spell descriptions. Footnotes are written with `<fn>` and `</fn>` tags around their text
+/// (ex: "Each target <fn>Including the caster.</fn> takes 3d6 fire damage."), which gets replaced with a superscript
+/// number after the word before it. The text goes at the bottom of the page the number is on, in an area that the
+/// rest of the page's text stays above.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FootnoteOptions
+{
+	/// Which footnotes share a sequence of footnote numbers.
+	pub numbering: FootnoteNumbering,
+	/// Whether a short line gets drawn between the text of the page and its footnotes.
+	pub separator_rule: bool
+}
+
+impl Default for FootnoteOptions
+{
+	fn default() -> Self
+	{
+		Self
+		{
+			numbering: FootnoteNumbering::PerPage,
+			separator_rule: true
+		}
+	}
+}
+
+/// Options for a references section at the end of a spellbook that the text of footnotes goes in instead of the
+/// bottom of the page their numbers are on. The section lists each spell with endnotes or a source under its name,
+/// and each endnote and spell name links back to the page it came from. Endnote numbers link to their endnotes.
+/// Endnotes are numbered through the whole spellbook unless footnotes are numbered by spell.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EndnoteOptions
+{
+	/// The heading at the top of the references section.
+	pub title: String,
+	/// Whether or not the source of each spell (from its `source` field) gets listed under its name.
+	pub cite_sources: bool
+}
+
+impl Default for EndnoteOptions
+{
+	fn default() -> Self
+	{
+		Self
+		{
+			title: String::from("References"),
+			cite_sources: true
+		}
+	}
+}
+
+/// How the text between `<secret>` and `</secret>` tags in spell descriptions gets hidden in the player edition of a
+/// spellbook. The DM edition shows it like any other text.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum RedactionStyle
+{
+	/// Each word of a secret gets covered by a shaded bar as wide as the word, so the rest of the text stays where it
+	/// is in the DM edition.
+	#[default]
+	ShadedBar,
+	/// Secrets get left out completely.
+	Omit
+}
+
+/// How the paragraphs of a paragraph style get written (like boxed read-aloud text, designer notes, or warnings).
+/// Paragraphs in spell descriptions use a style by starting with the style's class between double colons (ex:
+/// "::readaloud:: A cold wind blows through the room.").
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ParagraphStyle
+{
+	/// The font variant the paragraph starts in (`None` to keep the font variant of the text before it). Font tags in
+	/// the paragraph still work.
+	pub font_variant: Option<FontVariant>,
+	/// The RGB color of the paragraph's text (`None` for the normal body text color). Text goes back to this color
+	/// after color tags in the paragraph.
+	pub color: Option<(u8, u8, u8)>,
+	/// How far the paragraph is indented in from both sides of the text in printpdf Mm. Indents wider than a quarter
+	/// of the text get shrunk down to that.
+	pub indent: f32,
+	/// The RGB color of a box that gets filled in behind the paragraph (`None` for no box). The box goes across the
+	/// whole width of the text, so the indent is the space between the sides of the box and the text.
+	pub background_color: Option<(u8, u8, u8)>
+}
+
+/// Options for quickly making a preview of part of a spellbook (like after changing some options in an app) instead
+/// of the whole thing.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct PreviewOptions
+{
+	/// The indexes of the spells (in the order they're given) that get pages (`None` for every spell). Other spells
+	/// are left out, but the page numbers after them stay the same by counting how many pages they would take up
+	/// (estimated from the height of each spell). Spells left out of compact lists don't take up any space.
+	pub spells: Option<Range<usize>>,
+	/// The number of pages to stop at, including the title page and table of contents (`None` for no limit). Nothing
+	/// else gets added once there are this many pages (other than the rest of the spell that reaches the limit).
+	pub max_pages: Option<usize>
+}
+
+impl PreviewOptions
+{
+	/// Whether or not the spell at an index gets a page in the preview.
+	pub fn includes_spell(&self, spell_index: usize) -> bool
+	{
+		match &self.spells
+		{
+			Some(spells) => spells.contains(&spell_index),
+			None => true
+		}
+	}
+
+	/// Whether or not a preview with a certain number of pages is long enough.
+	pub fn page_limit_reached(&self, page_count: usize) -> bool
+	{
+		match self.max_pages
+		{
+			Some(max_pages) => page_count >= max_pages,
+			None => false
+		}
+	}
+}
+
+/// Options for a fingerprint line on the back of the title page. The fingerprint is a hash of every spell and option
+/// that goes into the spellbook (including the contents of the font files and background image), so a group sharing
+/// a spellbook can check that everyone has the same edition of it by comparing their fingerprints.
+#[derive(Clone, Debug, PartialEq)]
+pub struct FingerprintOptions
+{
+	/// The text that goes before the fingerprint (ex: "Edition").
+	pub label: String,
+	/// Whether or not to put the version of this crate that made the spellbook after the fingerprint.
+	pub show_version: bool
+}
+
+impl Default for FingerprintOptions
+{
+	fn default() -> Self
+	{
+		Self
+		{
+			label: String::from("Edition"),
+			show_version: true
+		}
+	}
+}
+
+/// Options for password protecting a spellbook when it gets saved (ex: so a homebrew compendium can be shared as a
+/// handout that can be read but not copied or edited). Spellbooks are encrypted with 128 bit RC4, which every pdf
+/// reader supports but isn't strong enough to keep out someone determined to break it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EncryptionOptions
+{
+	/// The password needed to open the spellbook (empty for anyone to be able to open it).
+	pub user_password: String,
+	/// The password that lets pdf readers ignore the permissions below (the user password is used if this is empty,
+	/// which lets anyone who can open the spellbook ignore them).
+	pub owner_password: String,
+	/// Whether or not the spellbook can be printed.
+	pub allow_printing: bool,
+	/// Whether or not text and images can be copied out of the spellbook.
+	pub allow_copying: bool,
+	/// Whether or not the pages and contents of the spellbook can be changed.
+	pub allow_modifying: bool,
+	/// Whether or not comments can be added and fillable checkboxes can be checked.
+	pub allow_annotations: bool
+}
+
+impl Default for EncryptionOptions
+{
+	fn default() -> Self
+	{
+		Self
+		{
+			user_password: String::new(),
+			owner_password: String::new(),
+			allow_printing: true,
+			allow_copying: false,
+			allow_modifying: false,
+			allow_annotations: true
+		}
+	}
+}
+
+/// Options for merging saved spellbooks into one pdf document with `merge_spellbooks()`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MergeOptions
+{
+	/// Whether the page numbers that pdf readers show count up through the whole merged document (`true`) or start
+	/// over at 1 on the first page of each spellbook (`false`) to match the page numbers printed on the pages. The
+	/// printed page numbers stay the same either way.
+	pub renumber_pages: bool,
+	/// Whether the bookmarks of each spellbook get put under a bookmark with the title of that spellbook (`true`) or
+	/// all go in one list (`false`).
+	pub group_bookmarks: bool,
+	/// Whether fonts and images that are exactly the same in more than one spellbook only get stored once.
+	pub deduplicate_resources: bool
+}
+
+impl Default for MergeOptions
+{
+	fn default() -> Self
+	{
+		Self
+		{
+			renumber_pages: true,
+			group_bookmarks: true,
+			deduplicate_resources: true
+		}
+	}
+}
+
+/// Options for an index of the pages that each word in a spellbook is on (see `SearchIndex`).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SearchIndexOptions
+{
+	/// The fewest letters a word can have to be put in the index.
+	pub min_word_length: usize,
+	/// Words that are too common to be worth searching for (ex: "the"). Capitalization doesn't matter.
+	pub ignored_words: Vec<String>
+}
+
+impl Default for SearchIndexOptions
+{
+	fn default() -> Self
+	{
+		Self
+		{
+			min_word_length: 3,
+			ignored_words: ["and", "the", "for", "you", "your", "that", "with", "can", "its", "are", "this", "from"]
+				.iter().map(|word| word.to_string()).collect()
+		}
+	}
+}
+
+/// Optional features for `create_spellbook_with_options()` that go beyond the parameters of `create_spellbook()`.
+/// Every feature is turned off by default and everything else works the same as `create_spellbook()` by default.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct AdditionalOptions
+{
+	/// Settings for a table of contents after the title page (`None` for no table of contents).
+	pub table_of_contents: Option<TableOfContentsOptions>,
+	/// Settings for a glossary of terms at the end of the spellbook (`None` for no glossary).
+	pub glossary: Option<GlossaryOptions>,
+	/// Settings for reference pages (like spell slot tables) at the very end of the spellbook (`None` for no
+	/// reference pages).
+	pub reference_appendix: Option<ReferenceAppendixOptions>,
+	/// Settings for an appendix with the time and gold it takes to scribe each spell into a wizard's spellbook (`None`
+	/// for no scribing costs). Goes after the glossary and before the reference pages.
+	pub scribing_costs: Option<ScribingCostOptions>,
+	/// Settings for an appendix with the class level each spell becomes available at for a character (`None` for no
+	/// level plan). Goes after the scribing costs.
+	pub level_plan: Option<LevelPlanOptions>,
+	/// Settings for an appendix with a checklist of the material components of every spell and what they cost (`None`
+	/// for no component list). Goes after the level plan.
+	pub component_list: Option<ComponentListOptions>,
+	/// Colors for the names of spells in each school of magic (`None` to use the header color for every spell name).
+	pub school_colors: Option<SchoolColorOptions>,
+	/// Chips with the classes of each spell under its level and school (`None` for no chips).
+	pub class_chips: Option<ClassChipOptions>,
+	/// How distances in spell ranges get converted before they're displayed (ex: to meters for tables that use the
+	/// metric system). `None` displays them the way they're written in the spell files.
+	pub distance_conversion: Option<DistanceConversion>,
+	/// How the components of each spell are displayed (shorthand letters by default).
+	pub component_style: ComponentStyle,
+	/// Settings for a small diagram of the area of effect after the range of spells with a range of self and an area
+	/// of effect (`None` for no diagrams).
+	pub aoe_glyphs: Option<AoeGlyphOptions>,
+	/// Settings for empty checkboxes next to spell names for tracking prepared spells (`None` for no checkboxes).
+	pub preparation_checkboxes: Option<PreparationCheckboxOptions>,
+	/// Settings for ruled space for handwritten notes after each spell (`None` for no notes areas).
+	pub notes_area: Option<NotesAreaOptions>,
+	/// Settings for how the upcast description of each spell is written.
+	pub upcast: UpcastOptions,
+	/// Adds a table of damage by character level under cantrips whose damage increases at certain levels (`None` for
+	/// no tables).
+	pub cantrip_scaling: Option<CantripScalingOptions>,
+	/// How the bookmark for each spell gets named.
+	pub bookmark_names: BookmarkNamePolicy,
+	/// Handlers for custom tags in spell descriptions (ex: "<dc>").
+	pub tag_handlers: DescriptionTagHandlers,
+	/// Callbacks for adding custom decorations to pages.
+	pub page_hooks: PageHooks,
+	/// Draws outlines of the text margins, column boundaries, table cells, and the baseline of each line of text on
+	/// every page. Useful for figuring out font scalars, margins, and table options.
+	pub debug_layout: bool,
+	/// Limits on how big parts of each spell can be before a warning about it is added to `Spellbook::warnings`
+	/// (`None` for no limits).
+	pub limits: Option<SpellLimits>,
+	/// A spell checker that checks the description and upcast description of each spell and adds a warning about each
+	/// word it thinks is misspelled to `Spellbook::warnings` (`None` to not check spelling).
+	pub spell_check: Option<SpellCheckHook>,
+	/// Checks that the text colors have enough contrast with the background image (or background color if there's no
+	/// image) and adds a warning about each one that doesn't to `Spellbook::warnings` (`None` to not check).
+	pub contrast_check: Option<ContrastCheckOptions>,
+	/// The RGB color that each page gets filled with before anything else goes on it (`None` to leave pages white).
+	/// Background images go on top of it.
+	pub background_color: Option<(u8, u8, u8)>,
+	/// The RGB color that the background color fades into at the bottom of each page (`None` for a solid color). Only
+	/// used with `background_color`.
+	pub background_gradient: Option<(u8, u8, u8)>,
+	/// Automatically sizes and positions the background image on each page (`None` to use the transform given with
+	/// the background image). Only the `dpi` of the given transform is used with this (300 if it isn't set).
+	pub background_fit: Option<BackgroundFit>,
+	/// Mirrors or rotates the background image on every other page (`None` for the same background on every page).
+	pub background_alternation: Option<BackgroundAlternation>,
+	/// Different background images for the title page and class chapter pages (`None` to use the normal background
+	/// on every page).
+	pub section_backgrounds: Option<SectionBackgroundOptions>,
+	/// Settings for fillable checkboxes that can be checked in pdf readers (`None` for no fillable fields).
+	pub form_fields: Option<FormFieldOptions>,
+	/// Splits the spellbook into a chapter for each class (`None` for the spells to just go in the order given).
+	pub class_chapters: Option<ClassChapterOptions>,
+	/// Writes only the stats of each spell in a list split into columns instead of giving each spell its own pages
+	/// (`None` for full spell pages). Notes areas aren't added to spells in the list.
+	pub compact_list: Option<CompactListOptions>,
+	/// Balances the columns on the last page of the glossary and each compact list of spells so they end at about the
+	/// same height, instead of filling each column to the bottom of the page before starting the next one.
+	pub balance_columns: bool,
+	/// Settings for a tapered rule between each spell's name and its level and school (`None` for no rule).
+	pub name_rule: Option<NameRuleOptions>,
+	/// Settings for a filled bar behind each spell name (`None` for no bar).
+	pub name_bar: Option<NameBarOptions>,
+	/// Settings for a footer with the spell's level and concentration and ritual icons on each page of a spell after
+	/// its first one (`None` for no footer).
+	pub continuation_footer: Option<ContinuationFooterOptions>,
+	/// Writes the casting time, range, components, and duration of each spell in a boxed grid (`None` to write them
+	/// on their own lines).
+	pub quick_stats: Option<QuickStatsOptions>,
+	/// Settings for an outline around the letters of the title on the title page (`None` for no outline).
+	pub title_outline: Option<TitleOutlineOptions>,
+	/// Leaves out the title page so the spellbook starts with the table of contents or the first spell. The spell slot
+	/// tracker, the QR code for the whole spellbook, and the fingerprint page go with the title page, so they get left
+	/// out too.
+	pub skip_title_page: bool,
+	/// Settings for a page with the title written sideways on it for the spine of a bound copy (`None` for no spine
+	/// page). Goes after everything else in the spellbook.
+	pub spine: Option<SpineOptions>,
+	/// Settings for QR codes with links to each spell or the whole spellbook (`None` for no QR codes). Needs the `qr`
+	/// feature.
+	pub qr_codes: Option<QrCodeOptions>,
+	/// Moves pairs of letters closer together or further apart using the kerning in the font files (ex: "AV" gets
+	/// written tighter). Text gets measured with the same kerning, so lines are wrapped the way they look in the pdf.
+	pub kerning: bool,
+	/// Replaces letters with the standard ligatures in the font files (ex: "fi" and "fl" get joined into single
+	/// glyphs). Text gets measured with the same ligatures, so lines are wrapped the way they look in the pdf.
+	pub ligatures: bool,
+	/// Shapes text in scripts whose letters change shape or combine depending on the letters around them (like Arabic
+	/// and Devanagari) with the font files so it gets written and measured with the right glyphs. Right-to-left text
+	/// gets written right-to-left within each word, but lines that mix it with left-to-right text aren't reordered.
+	/// Needs the `shaping` feature.
+	pub shaping: bool,
+	/// Hyphenates words that are too long to fit on a line between their syllables (`None` to split them wherever the
+	/// line runs out). Needs the `hyphenation` feature.
+	pub hyphenation: Option<HyphenationOptions>,
+	/// Justifies paragraphs so that lines reach the right side of the text box, as long as spaces don't get
+	/// stretched too far (`None` for ragged-right text).
+	pub justification: Option<JustificationOptions>,
+	/// Keeps a minimum number of lines of spell description paragraphs together at page breaks (`None` to break
+	/// paragraphs wherever the page runs out).
+	pub paragraph_breaks: Option<ParagraphBreakOptions>,
+	/// Whether spells start on new pages and whether their tables can be split across page breaks. Spells can
+	/// override these in their spell files.
+	pub spell_layout: SpellLayoutOptions,
+	/// How footnotes in spell descriptions get numbered and separated from the rest of the page.
+	pub footnotes: FootnoteOptions,
+	/// Puts the text of footnotes in a references section at the end of the spellbook along with the source of each
+	/// spell (`None` for footnotes at the bottom of pages).
+	pub endnotes: Option<EndnoteOptions>,
+	/// Hides the text between `<secret>` and `</secret>` tags in spell descriptions to make a player edition of the
+	/// spellbook (`None` for a DM edition that shows secrets like any other text). `create_dm_and_player_spellbooks()`
+	/// makes both editions at once.
+	pub redaction: Option<RedactionStyle>,
+	/// The paragraph style of each paragraph class (ex: "readaloud" for paragraphs that start with "::readaloud::").
+	/// Paragraphs that start with the marker of a class that isn't in here are written with the marker as normal text.
+	pub paragraph_styles: BTreeMap<String, ParagraphStyle>,
+	/// Tightens the spacing between the name, fields, and description of spells that take up fewer pages with it
+	/// (`None` for the same spacing in every spell).
+	pub compact_density: Option<CompactDensityOptions>,
+	/// Fills the empty space at the bottom of spells' last pages with quotes, images, or ornaments if there's enough
+	/// of it (`None` to leave empty space blank).
+	pub page_fill: Option<PageFillOptions>,
+	/// Only makes part of the spellbook for a quick preview (`None` to make the whole spellbook).
+	pub preview: Option<PreviewOptions>,
+	/// Measurements of spells from previous spellbooks that can be reused (`None` to measure every spell).
+	pub layout_cache: Option<LayoutCache>,
+	/// Background images from previous spellbooks that can be reused (`None` to open each background image again).
+	pub image_cache: Option<ImageCache>,
+	/// Settings for a page after the title page with a fingerprint of everything that went into the spellbook
+	/// (`None` for no fingerprint page). The fingerprint also goes in `Spellbook::fingerprint`.
+	pub fingerprint: Option<FingerprintOptions>,
+	/// Password protects the spellbook when it gets saved (`None` for no protection). Can also be changed with
+	/// `Spellbook::encryption` before saving.
+	pub encryption: Option<EncryptionOptions>,
+	/// Settings for an index of the pages that each word is on, made from the text as it gets laid out and put in
+	/// `Spellbook::search_index` (`None` for no index).
+	pub search_index: Option<SearchIndexOptions>,
+	/// Adds a named destination for each spell, table, and section of the spellbook to the pdf so links to the file
+	/// can jump straight to them (ex: "spellbook.pdf#nameddest=magic-missile"). Names are written the same way as the
+	/// names in page reference tags.
+	pub named_destinations: bool
+}
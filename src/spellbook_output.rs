@@ -0,0 +1,503 @@
+//////////////////////////////////////////////////////////////////////////////////////////////////////////////
+//
+//	Output types for create_spellbook_with_options
+//
+//////////////////////////////////////////////////////////////////////////////////////////////////////////////
+
+use std::fs;
+use std::fmt;
+use std::error::Error;
+use std::io::{BufWriter, Write};
+
+use printpdf::{PdfDocument, PdfDocumentReference, PdfLayerReference, PdfPageIndex, Pt};
+use printpdf::lopdf::{self, Object, ObjectId, Dictionary, Stream, StringFormat};
+
+use crate::spellbook_gen_types::{InternalLink, FormCheckbox, Tooltip};
+use crate::spellbook_writer::SpellbookState;
+use crate::spells;
+use crate::spellbook_options::{EncryptionOptions, contrast_ratio};
+use crate::pdf_encryption;
+use crate::search_index::SearchIndex;
+
+/// Error for when spells can't be added to a spellbook.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AppendSpellsError(pub(crate) String);
+// Makes the struct displayable
+impl fmt::Display for AppendSpellsError
+{
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
+	{
+		write!(f, "{}", self.0)
+	}
+}
+// Makes the struct officially an error
+impl Error for AppendSpellsError {}
+
+/// A spellbook that was created by `create_spellbook_with_options()`.
+///
+/// Some of the features in a spellbook (like links between pages and fillable checkboxes) can't be added to the pdf
+/// document until it gets saved, so use `save()` or `save_to_bytes()` instead of saving `doc` directly to keep those
+/// features.
+///
+/// Spellbooks can't be sent between threads because `printpdf` documents can't be. To make a spellbook on another
+/// thread (like in the background of an app), make and save it on that thread with `save_to_bytes()` and send the
+/// bytes back instead. The options and spells that go into spellbooks can all be sent between threads.
+pub struct Spellbook
+{
+	/// The `printpdf` PDF document of the spellbook.
+	pub doc: PdfDocumentReference,
+	/// The layer of each page in the spellbook.
+	pub layers: Vec<PdfLayerReference>,
+	/// The index of each page in the spellbook.
+	pub pages: Vec<PdfPageIndex>,
+	/// Problems with the spells in the spellbook that didn't stop it from being made (like spells that go over
+	/// `AdditionalOptions::limits`).
+	pub warnings: Vec<SpellbookWarning>,
+	/// A fingerprint of every spell and option that went into the spellbook (`None` if
+	/// `AdditionalOptions::fingerprint` wasn't set).
+	pub fingerprint: Option<String>,
+	/// Password protection that gets applied when the spellbook is saved (`None` for no protection).
+	pub encryption: Option<EncryptionOptions>,
+	/// The pages that each word in the spellbook is on (`None` if `AdditionalOptions::search_index` wasn't set).
+	pub search_index: Option<SearchIndex>,
+	// Links between pages that get added when the spellbook is saved
+	pub(crate) internal_links: Vec<InternalLink>,
+	// Fillable checkboxes that get added when the spellbook is saved
+	pub(crate) form_checkboxes: Vec<FormCheckbox>,
+	// Popups (like the definitions of glossary terms) that get added when the spellbook is saved
+	pub(crate) tooltips: Vec<Tooltip>,
+	// The name and page index of each named destination that gets added when the spellbook is saved
+	pub(crate) named_destinations: Vec<(String, usize)>,
+	// The writer that made the spellbook (for adding more spells to it)
+	pub(crate) state: Option<SpellbookState>
+}
+
+impl Spellbook
+{
+	/// Adds spells to the end of the spellbook (like spells that a character learned after the spellbook was made).
+	/// The spells are written with the same options as the rest of the spellbook, and their page numbers and
+	/// bookmarks continue on from the last page. Bookmark names get numbered if they're the same as one already in
+	/// the spellbook, and the new spells get checked against `AdditionalOptions::limits` and
+	/// `AdditionalOptions::spell_check` with any warnings added to `warnings`. The table of contents and class
+	/// chapters stay the same, and each spell gets its own pages even if the rest of the spellbook is a compact list.
+	///
+	/// Spells can only be added to spellbooks that end with their spells, so spellbooks that have anything after
+	/// their spells (a glossary, scribing costs, a level plan, a component list, a reference appendix, endnotes, or a
+	/// spine page) give an `AppendSpellsError`. That includes spellbooks that got endnotes from spells added to them
+	/// before.
+	///
+	/// # Parameters
+	///
+	/// - `spells` The spells to add to the end of the spellbook.
+	///
+	/// # Output
+	///
+	/// - `Ok` Returns nothing.
+	/// - `Err` An `AppendSpellsError` if the spellbook has pages after its spells or can't have spells added to it,
+	///   or a QR code that couldn't be made for one of the spells (nothing gets added if there is either).
+	pub fn append_spells(&mut self, spells: &Vec<spells::Spell>) -> Result<(), Box<dyn Error + Send + Sync>>
+	{
+		// Take the state out of the spellbook while it writes to the spellbook
+		let mut state = match self.state.take()
+		{
+			Some(state) => state,
+			None => return Err(Box::new(AppendSpellsError(String::from
+			(
+				"Spells can't be added to this spellbook because the writer that made it is gone."
+			))))
+		};
+		let result = state.append_spells(self, spells);
+		self.state = Some(state);
+		result
+	}
+
+	/// Saves the spellbook to a file as a pdf document.
+	///
+	/// # Parameters
+	///
+	/// - `file_name` The name to give to the file that the spellbook will be saved to.
+	///
+	/// # Output
+	///
+	/// - `Ok` Returns nothing.
+	/// - `Err` Returns any errors that occurred.
+	pub fn save(self, file_name: &str) -> Result<(), Box<dyn Error + Send + Sync>>
+	{
+		let bytes = self.save_to_bytes()?;
+		let mut file = BufWriter::new(fs::File::create(file_name)?);
+		file.write_all(&bytes)?;
+		file.flush()?;
+		Ok(())
+	}
+
+	/// Turns the spellbook into the bytes of a pdf document.
+	///
+	/// # Output
+	///
+	/// - `Ok` The bytes of the pdf document.
+	/// - `Err` Returns any errors that occurred.
+	pub fn save_to_bytes(mut self) -> Result<Vec<u8>, Box<dyn Error + Send + Sync>>
+	{
+		// printpdf documents get used up when they're saved, so an empty one takes its place
+		let pdf_doc = std::mem::replace(&mut self.doc, PdfDocument::empty(""));
+		let bytes = pdf_doc.save_to_bytes()?;
+		// If there's nothing that printpdf can't do, just use the bytes printpdf made
+		if !self.needs_lopdf() { return Ok(bytes); }
+		// Otherwise reopen the document so the missing features can be added to it
+		let mut doc = lopdf::Document::load_mem(&bytes)?;
+		self.finish_document(&mut doc)?;
+		let mut bytes = Vec::with_capacity(bytes.len());
+		doc.save_to(&mut bytes)?;
+		Ok(bytes)
+	}
+
+	/// Adds everything that printpdf can't add to the document of the spellbook after it's been reopened with lopdf.
+	fn finish_document(&self, doc: &mut lopdf::Document) -> Result<(), Box<dyn Error + Send + Sync>>
+	{
+		add_internal_links(doc, &self.internal_links)?;
+		add_form_checkboxes(doc, &self.form_checkboxes)?;
+		add_tooltips(doc, &self.tooltips)?;
+		add_named_destinations(doc, &self.named_destinations)?;
+		if let Some(encryption) = &self.encryption { pdf_encryption::encrypt_document(doc, encryption)?; }
+		Ok(())
+	}
+
+	/// Whether or not the spellbook has anything that printpdf can't add (so the document has to be reopened with
+	/// lopdf to add it when it gets saved).
+	fn needs_lopdf(&self) -> bool
+	{
+		!self.internal_links.is_empty() || !self.form_checkboxes.is_empty() || !self.tooltips.is_empty() ||
+		!self.named_destinations.is_empty() || self.encryption.is_some()
+	}
+}
+
+/// A problem with a spell that didn't stop a spellbook from being made.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SpellbookWarning
+{
+	/// A spell's name has more characters than the limit.
+	NameTooLong { spell_name: String, length: usize, limit: usize },
+	/// A table in a spell's description has more columns than the limit.
+	TooManyTableColumns { spell_name: String, table_index: usize, columns: usize, limit: usize },
+	/// A spell's description has more characters than the limit.
+	DescriptionTooLong { spell_name: String, length: usize, limit: usize },
+	/// A text color doesn't have enough contrast with the average color of the background behind the text. Has the
+	/// color it was changed to if text colors get adjusted.
+	LowContrast
+	{
+		text: String,
+		color: (u8, u8, u8),
+		background_color: (u8, u8, u8),
+		adjusted_color: Option<(u8, u8, u8)>
+	},
+	/// A spell checker thinks a word in a spell's text is misspelled. `field` is the text the word is in
+	/// ("description" or "upcast description") and `start` and `end` are the byte positions of the word in that text
+	/// (with its tags still in it).
+	PossibleMisspelling
+	{
+		spell_name: String,
+		field: String,
+		word: String,
+		start: usize,
+		end: usize,
+		suggestions: Vec<String>
+	}
+}
+
+impl SpellbookWarning
+{
+	/// The name of the spell that the warning is about (empty for warnings that aren't about a spell).
+	pub fn spell_name(&self) -> &str
+	{
+		match self
+		{
+			Self::NameTooLong { spell_name, .. } => spell_name,
+			Self::TooManyTableColumns { spell_name, .. } => spell_name,
+			Self::DescriptionTooLong { spell_name, .. } => spell_name,
+			Self::LowContrast { .. } => "",
+			Self::PossibleMisspelling { spell_name, .. } => spell_name
+		}
+	}
+}
+
+impl fmt::Display for SpellbookWarning
+{
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
+	{
+		// Long names get cut off so warnings stay readable
+		let short_name = |name: &str| -> String
+		{
+			if name.chars().count() <= 40 { return String::from(name); }
+			format!("{}...", name.chars().take(40).collect::<String>())
+		};
+		match self
+		{
+			Self::NameTooLong { spell_name, length, limit } => write!
+			(
+				f,
+				"The name of \"{}\" is {} characters long (limit is {}). Try shortening it.",
+				short_name(spell_name), length, limit
+			),
+			Self::TooManyTableColumns { spell_name, table_index, columns, limit } => write!
+			(
+				f,
+				"Table {} in \"{}\" has {} columns (limit is {}). Try splitting it into multiple tables.",
+				table_index, short_name(spell_name), columns, limit
+			),
+			Self::DescriptionTooLong { spell_name, length, limit } => write!
+			(
+				f,
+				"The description of \"{}\" is {} characters long (limit is {}). Try shortening it.",
+				short_name(spell_name), length, limit
+			),
+			Self::LowContrast { text, color, background_color, adjusted_color } =>
+			{
+				write!
+				(
+					f,
+					"The {} text color {:?} has a contrast ratio of {:.2}:1 with the background {:?}.",
+					text, color, contrast_ratio(*color, *background_color), background_color
+				)?;
+				match adjusted_color
+				{
+					Some(adjusted_color) => write!(f, " It was changed to {:?}.", adjusted_color),
+					None => write!(f, " Try a color that's further from the background's color.")
+				}
+			},
+			Self::PossibleMisspelling { spell_name, field, word, start, end, suggestions } =>
+			{
+				write!
+				(
+					f,
+					"\"{}\" in the {} of \"{}\" (bytes {} to {}) might be misspelled.",
+					word, field, short_name(spell_name), start, end
+				)?;
+				if suggestions.is_empty() { return Ok(()); }
+				let suggestions: Vec<_> = suggestions.iter().map(|suggestion| format!("\"{}\"", suggestion)).collect();
+				write!(f, " Did you mean {}?", suggestions.join(" or "))
+			}
+		}
+	}
+}
+
+/// Adds link annotations that go to other pages in the document.
+fn add_internal_links(doc: &mut lopdf::Document, links: &Vec<InternalLink>) -> Result<(), Box<dyn Error + Send + Sync>>
+{
+	// Object ids of each page in order (lopdf page numbers start at 1)
+	let page_ids: Vec<_> = doc.get_pages().into_values().collect();
+	for link in links
+	{
+		// Skip links to or from pages that don't exist
+		if link.page_index >= page_ids.len() || link.target_page_index >= page_ids.len() { continue; }
+		// Create the link annotation that jumps to the top of the target page
+		let annotation = Dictionary::from_iter(vec!
+		[
+			("Type", Object::Name(b"Annot".to_vec())),
+			("Subtype", Object::Name(b"Link".to_vec())),
+			("Rect", Object::Array(vec!
+			[
+				// Pdf files use Pt for positions
+				Pt::from(link.x_min).into(),
+				Pt::from(link.y_min).into(),
+				Pt::from(link.x_max).into(),
+				Pt::from(link.y_max).into()
+			])),
+			// No visible border around the link
+			("Border", Object::Array(vec![Object::Integer(0), Object::Integer(0), Object::Integer(0)])),
+			("Dest", Object::Array(vec![Object::Reference(page_ids[link.target_page_index]), Object::Name(b"Fit".to_vec())]))
+		]);
+		let annotation_id = doc.add_object(Object::Dictionary(annotation));
+		add_annotation_to_page(doc, page_ids[link.page_index], annotation_id)?;
+	}
+	Ok(())
+}
+
+/// Adds popups that show their text when the mouse is over them. They're square annotations with nothing in them so
+/// that pdf readers don't draw note icons for them, and they don't get printed.
+fn add_tooltips(doc: &mut lopdf::Document, tooltips: &Vec<Tooltip>) -> Result<(), Box<dyn Error + Send + Sync>>
+{
+	if tooltips.is_empty() { return Ok(()); }
+	// Object ids of each page in order (lopdf page numbers start at 1)
+	let page_ids: Vec<_> = doc.get_pages().into_values().collect();
+	// Every tooltip uses the same empty appearance so nothing gets drawn over the text
+	let appearance = Stream::new(Dictionary::from_iter(vec!
+	[
+		("Type", Object::Name(b"XObject".to_vec())),
+		("Subtype", Object::Name(b"Form".to_vec())),
+		("BBox", Object::Array(vec![0.into(), 0.into(), 0.into(), 0.into()]))
+	]), Vec::new());
+	let appearance_id = doc.add_object(Object::Stream(appearance));
+	for tooltip in tooltips.iter().filter(|tooltip| tooltip.page_index < page_ids.len())
+	{
+		let annotation = Dictionary::from_iter(vec!
+		[
+			("Type", Object::Name(b"Annot".to_vec())),
+			("Subtype", Object::Name(b"Square".to_vec())),
+			("Rect", Object::Array(vec!
+			[
+				Pt::from(tooltip.x_min).into(),
+				Pt::from(tooltip.y_min).into(),
+				Pt::from(tooltip.x_max).into(),
+				Pt::from(tooltip.y_max).into()
+			])),
+			("T", text_string(&tooltip.title)),
+			("Contents", text_string(&tooltip.text)),
+			// No border or fill color
+			("Border", Object::Array(vec![Object::Integer(0), Object::Integer(0), Object::Integer(0)])),
+			("BS", Object::Dictionary(Dictionary::from_iter(vec![("W", Object::Integer(0))]))),
+			("C", Object::Array(Vec::new())),
+			("AP", Object::Dictionary(Dictionary::from_iter(vec![("N", Object::Reference(appearance_id))])))
+		]);
+		let annotation_id = doc.add_object(Object::Dictionary(annotation));
+		add_annotation_to_page(doc, page_ids[tooltip.page_index], annotation_id)?;
+	}
+	Ok(())
+}
+
+/// Turns text into a pdf text string (encoded as UTF-16 so any character can be in it).
+fn text_string(text: &str) -> Object
+{
+	let bytes = [0xFEFF].into_iter().chain(text.encode_utf16()).flat_map(u16::to_be_bytes).collect();
+	Object::String(bytes, StringFormat::Hexadecimal)
+}
+
+/// Adds named destinations that go to pages in the document so links to the document can jump straight to them (ex:
+/// "spellbook.pdf#nameddest=fireball"). The destinations have to be sorted by name.
+fn add_named_destinations(doc: &mut lopdf::Document, destinations: &Vec<(String, usize)>)
+-> Result<(), Box<dyn Error + Send + Sync>>
+{
+	if destinations.is_empty() { return Ok(()); }
+	// Object ids of each page in order (lopdf page numbers start at 1)
+	let page_ids: Vec<_> = doc.get_pages().into_values().collect();
+	// The names and destinations go in one array of alternating names and destinations
+	let mut names = Vec::with_capacity(destinations.len() * 2);
+	for (name, page_index) in destinations.iter().filter(|(_, page_index)| *page_index < page_ids.len())
+	{
+		names.push(Object::String(name.as_bytes().to_vec(), StringFormat::Literal));
+		names.push(Object::Array(vec![Object::Reference(page_ids[*page_index]), Object::Name(b"Fit".to_vec())]));
+	}
+	let dests_id = doc.add_object(Object::Dictionary(Dictionary::from_iter(vec![("Names", Object::Array(names))])));
+	// Put the destinations in the name dictionary of the document catalog (making one if there isn't one)
+	let catalog_id = doc.trailer.get(b"Root")?.as_reference()?;
+	let catalog = doc.get_dictionary_mut(catalog_id)?;
+	match catalog.get_mut(b"Names").and_then(Object::as_dict_mut)
+	{
+		Ok(name_dictionary) => name_dictionary.set("Dests", Object::Reference(dests_id)),
+		Err(_) =>
+		{
+			let name_dictionary = Dictionary::from_iter(vec![("Dests", Object::Reference(dests_id))]);
+			catalog.set("Names", Object::Dictionary(name_dictionary));
+		}
+	}
+	Ok(())
+}
+
+/// Adds fillable checkboxes to the document along with the interactive form that holds them.
+/// Checkboxes with the same name become widgets of the same field so checking one checks all of them.
+fn add_form_checkboxes(doc: &mut lopdf::Document, checkboxes: &Vec<FormCheckbox>)
+-> Result<(), Box<dyn Error + Send + Sync>>
+{
+	if checkboxes.is_empty() { return Ok(()); }
+	// Object ids of each page in order (lopdf page numbers start at 1)
+	let page_ids: Vec<_> = doc.get_pages().into_values().collect();
+	// Group the checkboxes by name while keeping them in the order they were made
+	let mut fields: Vec<(&str, Vec<&FormCheckbox>)> = Vec::new();
+	for checkbox in checkboxes.iter().filter(|checkbox| checkbox.page_index < page_ids.len())
+	{
+		match fields.iter_mut().find(|(name, _)| *name == checkbox.name)
+		{
+			Some((_, widgets)) => widgets.push(checkbox),
+			None => fields.push((&checkbox.name, vec![checkbox]))
+		}
+	}
+	let mut field_ids = Vec::with_capacity(fields.len());
+	for (name, widgets) in fields
+	{
+		// The field holds the name and value that all of its widgets share
+		let field_id = doc.add_object(Object::Dictionary(Dictionary::from_iter(vec!
+		[
+			("FT", Object::Name(b"Btn".to_vec())),
+			("T", Object::String(name.as_bytes().to_vec(), StringFormat::Literal)),
+			("V", Object::Name(b"Off".to_vec()))
+		])));
+		let mut kids = Vec::with_capacity(widgets.len());
+		for widget in widgets
+		{
+			let width = Pt::from(widget.x_max - widget.x_min);
+			let height = Pt::from(widget.y_max - widget.y_min);
+			// What the checkbox looks like when it's checked and unchecked
+			let checked_id = doc.add_object(checkbox_appearance(width, height, true));
+			let unchecked_id = doc.add_object(checkbox_appearance(width, height, false));
+			let annotation = Dictionary::from_iter(vec!
+			[
+				("Type", Object::Name(b"Annot".to_vec())),
+				("Subtype", Object::Name(b"Widget".to_vec())),
+				("Parent", Object::Reference(field_id)),
+				("Rect", Object::Array(vec!
+				[
+					Pt::from(widget.x_min).into(),
+					Pt::from(widget.y_min).into(),
+					Pt::from(widget.x_max).into(),
+					Pt::from(widget.y_max).into()
+				])),
+				// Printable
+				("F", Object::Integer(4)),
+				("AS", Object::Name(b"Off".to_vec())),
+				("AP", Object::Dictionary(Dictionary::from_iter(vec!
+				[
+					("N", Object::Dictionary(Dictionary::from_iter(vec!
+					[
+						("Yes", Object::Reference(checked_id)),
+						("Off", Object::Reference(unchecked_id))
+					])))
+				])))
+			]);
+			let annotation_id = doc.add_object(Object::Dictionary(annotation));
+			add_annotation_to_page(doc, page_ids[widget.page_index], annotation_id)?;
+			kids.push(Object::Reference(annotation_id));
+		}
+		doc.get_dictionary_mut(field_id)?.set("Kids", Object::Array(kids));
+		field_ids.push(Object::Reference(field_id));
+	}
+	// Add the fields to the interactive form of the document
+	let form_id = doc.add_object(Object::Dictionary(Dictionary::from_iter(vec![("Fields", Object::Array(field_ids))])));
+	let catalog_id = doc.trailer.get(b"Root")?.as_reference()?;
+	doc.get_dictionary_mut(catalog_id)?.set("AcroForm", Object::Reference(form_id));
+	Ok(())
+}
+
+/// Makes the appearance of a checkbox with the given size. Checked checkboxes have an X in them and unchecked
+/// checkboxes are blank (the square around them is part of the page).
+fn checkbox_appearance(width: Pt, height: Pt, checked: bool) -> Stream
+{
+	let content = if checked
+	{
+		// Keep the X away from the edges so it doesn't cover the square
+		let (x_inset, y_inset) = (width * 0.2, height * 0.2);
+		format!
+		(
+			"q 0 g 0 G 1 w {0} {1} m {2} {3} l S {0} {3} m {2} {1} l S Q",
+			x_inset.0, y_inset.0, (width - x_inset).0, (height - y_inset).0
+		)
+	}
+	else { String::new() };
+	Stream::new(Dictionary::from_iter(vec!
+	[
+		("Type", Object::Name(b"XObject".to_vec())),
+		("Subtype", Object::Name(b"Form".to_vec())),
+		("BBox", Object::Array(vec![Object::Real(0.0), Object::Real(0.0), width.into(), height.into()]))
+	]), content.into_bytes())
+}
+
+/// Adds an annotation to the list of annotations on a page.
+fn add_annotation_to_page(doc: &mut lopdf::Document, page_id: ObjectId, annotation_id: ObjectId)
+-> Result<(), Box<dyn Error + Send + Sync>>
+{
+	let page = doc.get_dictionary_mut(page_id)?;
+	match page.get_mut(b"Annots")
+	{
+		Ok(Object::Array(annotations)) => annotations.push(Object::Reference(annotation_id)),
+		_ => page.set("Annots", Object::Array(vec![Object::Reference(annotation_id)]))
+	}
+	Ok(())
+}
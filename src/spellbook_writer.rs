@@ -5,7 +5,8 @@
 //////////////////////////////////////////////////////////////////////////////////////////////////////////////
 
 use std::error::Error;
-use std::ops::Range;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::borrow::Cow;
 
 extern crate image;
 use printpdf::
@@ -18,28 +19,528 @@ use printpdf::
 	Point,
 	Line,
 	PdfPageIndex,
-	Image
+	Image,
+	Rect,
+	LinkAnnotation,
+	Actions,
+	BorderArray,
+	ColorArray,
+	BlendMode,
+	SeperableBlendMode,
+	Polygon,
+	TextMatrix,
+	TextRenderingMode
 };
+use printpdf::path::{PaintMode, WindingOrder};
+use printpdf::lopdf::{Dictionary, Object, StringFormat};
+use printpdf::lopdf::content::Operation;
 use regex::Regex;
 
 use crate::spellbook_gen_types::*;
+use crate::spellbook_output::{Spellbook, SpellbookWarning, AppendSpellsError};
+use crate::spellbook_hooks::*;
+use crate::layout::{self, TextLine, Token, LinkTarget, REGULAR_FONT_TAG, BOLD_FONT_TAG};
 use crate::spells;
+use crate::kerning::KerningTable;
+use crate::ligatures::LigatureTable;
+use crate::shaping::{TextShaper, needs_shaping};
+use crate::utils::load_image;
+use crate::layout_cache::{self, LayoutCache};
+use crate::image_cache::ImageCache;
+use crate::fingerprint;
+use crate::search_index::SearchIndex;
+use crate::qr_codes::{SpellbookQrCodes, QrModules};
+use crate::hyphenation::Hyphenator;
 
 const LAYER_NAME_PREFIX: &str = "Page";
 const DEFAULT_SPELLBOOK_TITLE: &str = "Spellbook";
 const TITLE_LAYER_NAME: &str = "Title Layer";
 const TITLE_PAGE_NAME: &str = "Title Page";
 
-const REGULAR_FONT_TAG: &str = "<r>";
-const BOLD_FONT_TAG: &str = "<b>";
-const ITALIC_FONT_TAG: &str = "<i>";
-const BOLD_ITALIC_FONT_TAG: &str = "<bi>";
-const ITALIC_BOLD_FONT_TAG: &str = "<ib>";
-
 const DOT: &str = "•";
 const DOT_SPACE: &str = "• ";
 const DASH: &str = "-";
 
+// Width of the text that the space for page numbers in the table of contents is made for
+const TABLE_OF_CONTENTS_NUMBER_SPACE: &str = "00000";
+// Number of columns the glossary is split into
+const GLOSSARY_COLUMN_COUNT: usize = 2;
+// Start of the name of each glossary term's anchor
+const GLOSSARY_ANCHOR_PREFIX: &str = "glossary:";
+// Start of the name of each endnote's anchor
+const ENDNOTE_ANCHOR_PREFIX: &str = "endnote:";
+// Start of a tag that gets replaced with the page number of a table or section (ex: "[ref:chromatic-orb-damage]")
+const PAGE_REFERENCE_TAG_START: &str = "[ref:";
+
+// Colors and line thickness of the outlines drawn when debugging the layout of a spellbook
+const DEBUG_MARGIN_COLOR: (u8, u8, u8) = (230, 60, 60);
+const DEBUG_COLUMN_COLOR: (u8, u8, u8) = (60, 120, 230);
+const DEBUG_TABLE_CELL_COLOR: (u8, u8, u8) = (60, 180, 90);
+const DEBUG_BASELINE_COLOR: (u8, u8, u8) = (220, 120, 220);
+const DEBUG_LINE_THICKNESS: Pt = Pt(0.25);
+
+// Resolution that images which fill empty space at the end of spells get loaded at
+const PAGE_FILL_IMAGE_DPI: f32 = 300.0;
+// The dpi that cover wrap artwork gets opened at before it gets stretched over its cover
+const COVER_IMAGE_DPI: f32 = 300.0;
+const MM_PER_INCH: f32 = 25.4;
+// Sizes (in millimeters) of the diamond in the middle of ornaments and the thickest part of the rules beside it
+const ORNAMENT_DIAMOND_SIZE: f32 = 1.5;
+const ORNAMENT_RULE_THICKNESS: f32 = 0.8;
+// Number of points used for the circles and ellipses in area of effect diagrams
+const AOE_GLYPH_CIRCLE_POINTS: usize = 24;
+// Size of the circles around component icons compared to the height of a capital letter
+const COMPONENT_ICON_SIZE: f32 = 1.6;
+const COMPONENT_ICON_LINE_THICKNESS: Pt = Pt(0.5);
+// Number of squares of blank space that QR codes need around them to be scanned
+const QR_CODE_QUIET_ZONE: f32 = 4.0;
+// Biggest font size of the title on a spine page compared to the width of the spine
+const SPINE_MAX_FONT_SIZE: f32 = 0.6;
+// Length of the rule above the footnotes on a page compared to the width of the text and how thick it is
+const FOOTNOTE_RULE_LENGTH: f32 = 0.33;
+const FOOTNOTE_RULE_THICKNESS: Pt = Pt(0.5);
+// Size of the text in rarity tags compared to the text around them, the space between their text and their outlines
+// compared to the height of their capital letters, and how thick their outlines are
+const RARITY_TAG_SCALE: f32 = 0.7;
+const RARITY_TAG_PADDING: f32 = 0.5;
+const RARITY_TAG_LINE_THICKNESS: Pt = Pt(0.5);
+// Size of the text in class chips compared to the text around them, the space around their text compared to the height
+// of their capital letters, and the space between chips (in millimeters)
+const CLASS_CHIP_SCALE: f32 = 0.7;
+const CLASS_CHIP_PADDING: f32 = 0.6;
+const CLASS_CHIP_GAP: f32 = 1.5;
+// How many bands of color gradients are made of
+const GRADIENT_BANDS: usize = 64;
+// The space between the text in each cell of the quick stats grid and the lines around it (in newlines)
+const QUICK_STATS_PADDING: f32 = 0.2;
+
+/// Returns a closed line that outlines a rectangle.
+fn outline_rect(x_min: Mm, y_min: Mm, x_max: Mm, y_max: Mm) -> Line
+{
+	let points = vec!
+	[
+		(Point::new(x_min, y_min), false),
+		(Point::new(x_max, y_min), false),
+		(Point::new(x_max, y_max), false),
+		(Point::new(x_min, y_max), false)
+	];
+	Line { points: points, is_closed: true }
+}
+
+/// Returns the points of a rectangle with rounded corners going counterclockwise from the bottom right corner.
+fn rounded_rect_points(x_min: Mm, y_min: Mm, x_max: Mm, y_max: Mm, radius: Mm) -> Vec<(Point, bool)>
+{
+	let radius = radius.max(Mm(0.0)).min((x_max - x_min) / 2.0).min((y_max - y_min) / 2.0);
+	let quarter_turn = std::f32::consts::PI / 2.0;
+	let corners =
+	[
+		(x_max - radius, y_min + radius),
+		(x_max - radius, y_max - radius),
+		(x_min + radius, y_max - radius),
+		(x_min + radius, y_min + radius)
+	];
+	corners.into_iter().enumerate()
+	.flat_map(|(index, center)|
+	{
+		let start_angle = quarter_turn * (index as f32 - 1.0);
+		ellipse_points(center, (radius, radius), start_angle, start_angle + quarter_turn)
+	})
+	.collect()
+}
+
+/// Returns the points of an ellipse (or part of one) going counterclockwise from the start angle to the end angle
+/// (in radians).
+fn ellipse_points(center: (Mm, Mm), radii: (Mm, Mm), start_angle: f32, end_angle: f32) -> Vec<(Point, bool)>
+{
+	(0..=AOE_GLYPH_CIRCLE_POINTS).map(|i|
+	{
+		let angle = start_angle + (end_angle - start_angle) * i as f32 / AOE_GLYPH_CIRCLE_POINTS as f32;
+		(Point::new(center.0 + radii.0 * angle.cos(), center.1 + radii.1 * angle.sin()), false)
+	})
+	.collect()
+}
+
+/// Returns the shapes that make up a diagram of an area of effect that fits in a square with its bottom left corner
+/// at the given position.
+fn aoe_glyph_rings(aoe: &spells::Aoe, x_min: Mm, y_min: Mm, size: Mm) -> Vec<Vec<(Point, bool)>>
+{
+	let x_max = x_min + size;
+	let y_max = y_min + size;
+	let center = (x_min + size / 2.0, y_min + size / 2.0);
+	let full_turn = std::f32::consts::PI * 2.0;
+	match aoe
+	{
+		// A thin bar going across the square
+		spells::Aoe::Line(_) => vec![vec!
+		[
+			(Point::new(x_min, center.1 - size / 8.0), false),
+			(Point::new(x_max, center.1 - size / 8.0), false),
+			(Point::new(x_max, center.1 + size / 8.0), false),
+			(Point::new(x_min, center.1 + size / 8.0), false)
+		]],
+		// A triangle that spreads out from a point on the left
+		spells::Aoe::Cone(_) => vec![vec!
+		[
+			(Point::new(x_min, center.1), false),
+			(Point::new(x_max, y_min), false),
+			(Point::new(x_max, y_max), false)
+		]],
+		spells::Aoe::Cube(_) => vec![vec!
+		[
+			(Point::new(x_min, y_min), false),
+			(Point::new(x_max, y_min), false),
+			(Point::new(x_max, y_max), false),
+			(Point::new(x_min, y_max), false)
+		]],
+		spells::Aoe::Sphere(_) | spells::Aoe::Emanation(_) =>
+		vec![ellipse_points(center, (size / 2.0, size / 2.0), 0.0, full_turn)],
+		// The top half of a circle sitting on a flat bottom
+		spells::Aoe::Hemisphere(_) =>
+		vec![ellipse_points((center.0, y_min + size / 4.0), (size / 2.0, size / 2.0), 0.0, full_turn / 2.0)],
+		// A flat ellipse on top of the sides and rounded bottom of the cylinder
+		spells::Aoe::Cylinder(_, _) =>
+		{
+			let radii = (size / 2.0, size / 6.0);
+			let mut body = ellipse_points((center.0, y_min + radii.1), radii, full_turn / 2.0, full_turn);
+			body.push((Point::new(x_max, y_max - radii.1), false));
+			body.push((Point::new(x_min, y_max - radii.1), false));
+			vec![body, ellipse_points((center.0, y_max - radii.1), radii, 0.0, full_turn)]
+		}
+	}
+}
+
+/// Returns the color part of the way from one color to another (0 for the first color and 1 for the second).
+fn mix_colors(from: (u8, u8, u8), to: (u8, u8, u8), amount: f32) -> (u8, u8, u8)
+{
+	let mix = |from: u8, to: u8| (from as f32 + (to as f32 - from as f32) * amount).round() as u8;
+	(mix(from.0, to.0), mix(from.1, to.1), mix(from.2, to.2))
+}
+
+/// How far a paragraph in a paragraph style gets indented in from both sides of a textbox (at most a quarter of the
+/// width of the textbox).
+fn paragraph_indent(style: &ParagraphStyle, x_min: Mm, x_max: Mm) -> Mm
+{
+	Mm(style.indent.max(0.0)).min((x_max - x_min) / 4.0)
+}
+
+/// Returns the heading that goes before the spells of a spell's level in lists of spells grouped by level.
+fn level_heading(spell: &spells::Spell) -> String
+{
+	match &spell.level
+	{
+		spells::SpellField::Controlled(spells::Level::Cantrip) => String::from("Cantrips"),
+		spells::SpellField::Controlled(level) => level.to_string(),
+		spells::SpellField::Custom(_) => String::from("Other")
+	}
+}
+
+/// Returns the text of each entry in a table of contents along with the index of the spell (in the order spells get
+/// added to the spellbook) or chapter that it links to. Spells get sorted in the given order within each chapter, and
+/// spells sorted by level get a heading before each level that doesn't link anywhere.
+fn get_table_of_contents_entries
+(
+	spells: &Vec<spells::Spell>,
+	entries: &Vec<SpellbookEntry>,
+	order: TableOfContentsOrder
+)
+-> Vec<(String, Option<usize>, Option<usize>)>
+{
+	let mut table_of_contents_entries = Vec::with_capacity(entries.len());
+	// The spells since the last chapter along with their index in the order spells get added
+	let mut group: Vec<(&spells::Spell, usize)> = Vec::new();
+	// Sorts the spells since the last chapter and adds their entries
+	let add_group = |group: &mut Vec<(&spells::Spell, usize)>, entries: &mut Vec<_>|
+	{
+		match order
+		{
+			TableOfContentsOrder::Spellbook => (),
+			TableOfContentsOrder::Alphabetical => group.sort_by_key(|(spell, _)| spell.name.to_lowercase()),
+			TableOfContentsOrder::Level => group.sort_by_key(|(spell, _)| spell.level_sort_key())
+		}
+		let mut current_level = None;
+		for (spell, spell_index) in group.drain(..)
+		{
+			if order == TableOfContentsOrder::Level && current_level != Some(spell.level_sort_key().0)
+			{
+				current_level = Some(spell.level_sort_key().0);
+				entries.push((format!("{} {}", BOLD_FONT_TAG, level_heading(spell)), None, None));
+			}
+			entries.push((spell.name.clone(), Some(spell_index), None));
+		}
+	};
+	// Spells and chapters are numbered in the order they're added to the spellbook (spells can be added more than once
+	// if they're in multiple chapters)
+	let mut spell_count = 0;
+	let mut chapter_count = 0;
+	for entry in entries
+	{
+		match entry
+		{
+			// Chapter entries are bold and don't have checkboxes
+			SpellbookEntry::Chapter(chapter) =>
+			{
+				add_group(&mut group, &mut table_of_contents_entries);
+				chapter_count += 1;
+				let text = format!("{} {}", BOLD_FONT_TAG, chapter.title);
+				table_of_contents_entries.push((text, None, Some(chapter_count - 1)));
+			},
+			SpellbookEntry::Spell(index) =>
+			{
+				spell_count += 1;
+				group.push((&spells[*index], spell_count - 1));
+			}
+		}
+	}
+	add_group(&mut group, &mut table_of_contents_entries);
+	table_of_contents_entries
+}
+
+/// Returns the name of the anchor for a term in the glossary.
+fn glossary_anchor(term: &str) -> String { format!("{}{}", GLOSSARY_ANCHOR_PREFIX, term.to_lowercase()) }
+
+/// Returns the name of the anchor for an endnote in the references section.
+fn endnote_anchor(id: usize) -> String { format!("{}{}", ENDNOTE_ANCHOR_PREFIX, id) }
+
+/// Returns the name of the anchor that page references to a table or section with the given title point to. Letters
+/// and numbers are kept in lowercase with a dash between each group of them (ex: "Chromatic Orb Damage" becomes
+/// "chromatic-orb-damage").
+fn reference_anchor(title: &str) -> String
+{
+	let words: Vec<String> = title.split(|c: char| !c.is_alphanumeric())
+		.filter(|word| !word.is_empty())
+		.map(str::to_lowercase)
+		.collect();
+	words.join("-")
+}
+
+/// Replaces each page reference tag in some text (ex: "[ref:chromatic-orb-damage]") with "see page" followed by the
+/// name of its anchor between page reference markers, which saves space for the page number of the anchor.
+/// Tags that don't end before the end of the word they're in are left as they are.
+fn replace_page_references(text: &str) -> Cow<'_, str>
+{
+	if !text.contains(PAGE_REFERENCE_TAG_START) { return Cow::Borrowed(text); }
+	let mut replaced = String::with_capacity(text.len());
+	let mut rest = text;
+	while let Some(start) = rest.find(PAGE_REFERENCE_TAG_START)
+	{
+		let name_start = start + PAGE_REFERENCE_TAG_START.len();
+		let after_tag = &rest[name_start..];
+		match after_tag.find(|c: char| c == ']' || c.is_whitespace())
+		{
+			Some(end) if end > 0 && after_tag[end..].starts_with(']') =>
+			{
+				replaced.push_str(&rest[..start]);
+				replaced.push_str("see page ");
+				replaced.push(PAGE_REFERENCE_START);
+				replaced.push_str(&after_tag[..end]);
+				replaced.push(PAGE_REFERENCE_END);
+				rest = &after_tag[end + 1..];
+			},
+			_ =>
+			{
+				replaced.push_str(&rest[..name_start]);
+				rest = after_tag;
+			}
+		}
+	}
+	replaced.push_str(rest);
+	Cow::Owned(replaced)
+}
+
+/// Returns a word with the punctuation around it removed and in lowercase so it can be compared to glossary terms
+/// (ex: "frightened" from "Frightened,").
+fn keyword_form(token: &str) -> String
+{
+	token.trim_matches(|c: char| !c.is_alphanumeric()).to_lowercase()
+}
+
+/// Returns the name and value of the casting time, range, components, and duration of a spell.
+fn spell_field_values
+(
+	spell: &spells::Spell,
+	distance_conversion: Option<spells::DistanceConversion>,
+	component_style: spells::ComponentStyle
+) -> [(&'static str, String); 4]
+{
+	[
+		("Casting Time", spell.get_casting_time_text()),
+		("Range", spell.get_range_text(distance_conversion)),
+		("Components", spell.get_component_text(component_style)),
+		("Duration", spell.duration.to_string())
+	]
+}
+
+/// Returns the text of the casting time, range, components, and duration lines of a spell's page (with the name of
+/// each field in bold).
+fn spell_field_texts
+(
+	spell: &spells::Spell,
+	distance_conversion: Option<spells::DistanceConversion>,
+	component_style: spells::ComponentStyle
+) -> [String; 4]
+{
+	spell_field_values(spell, distance_conversion, component_style)
+	.map(|(name, value)| format!("{}: <r> {}", name, value))
+}
+
+/// Returns the name of the bookmark for each spell based on the given policy.
+pub(crate) fn get_bookmark_names(spells: &Vec<spells::Spell>, policy: BookmarkNamePolicy) -> Vec<String>
+{
+	// Counts how many spells have each name
+	let mut name_counts: HashMap<&str, usize> = HashMap::new();
+	for spell in spells { *name_counts.entry(&spell.name).or_insert(0) += 1; }
+	let mut bookmark_names: Vec<String> = spells.iter().map(|spell|
+	{
+		// Add the source to the name if the policy says it should be there
+		let include_source = match policy
+		{
+			BookmarkNamePolicy::SpellName => false,
+			BookmarkNamePolicy::DisambiguateDuplicates => name_counts[spell.name.as_str()] > 1,
+			BookmarkNamePolicy::AlwaysIncludeSource => true
+		};
+		match &spell.source
+		{
+			Some(source) if include_source => format!("{} ({})", spell.name, source),
+			_ => spell.name.clone()
+		}
+	}).collect();
+	if policy == BookmarkNamePolicy::SpellName { return bookmark_names; }
+	// Number any bookmarks that are still the same as an earlier one
+	number_duplicate_names(&mut bookmark_names, &mut HashSet::new());
+	bookmark_names
+}
+
+/// Puts a number after each bookmark name that's the same as a name that's already used or an earlier name in the
+/// list (ex: "Fireball (2)"), and adds each name to the used names.
+fn number_duplicate_names(bookmark_names: &mut [String], used_names: &mut HashSet<String>)
+{
+	for bookmark_name in bookmark_names
+	{
+		let mut number = 1;
+		let mut unique_name = bookmark_name.clone();
+		while used_names.contains(&unique_name)
+		{
+			number += 1;
+			unique_name = format!("{} ({})", bookmark_name, number);
+		}
+		used_names.insert(unique_name.clone());
+		*bookmark_name = unique_name;
+	}
+}
+
+/// Returns the order that spells and class chapters go in a spellbook.
+/// Without class chapters, the spells just go in the order they were given.
+fn get_spellbook_entries(spells: &Vec<spells::Spell>, class_chapters: &Option<ClassChapterOptions>)
+-> Vec<SpellbookEntry>
+{
+	let options = match class_chapters
+	{
+		Some(options) => options,
+		None => return (0..spells.len()).map(SpellbookEntry::Spell).collect()
+	};
+	let chapters = options.chapters_for(spells);
+	match options.layout
+	{
+		// Each chapter is followed by its spells sorted by level and then by name
+		ClassChapterLayout::RepeatSpells => chapters.into_iter().flat_map(|chapter|
+		{
+			let spell_indexes = get_class_spell_indexes(spells, &chapter.class);
+			std::iter::once(SpellbookEntry::Chapter(chapter))
+				.chain(spell_indexes.into_iter().map(SpellbookEntry::Spell))
+		}).collect(),
+		// Every spell goes in the order given and the class lists go after them
+		ClassChapterLayout::ClassListPages => (0..spells.len()).map(SpellbookEntry::Spell)
+			.chain(chapters.into_iter().map(SpellbookEntry::Chapter))
+			.collect()
+	}
+}
+
+/// Returns the index of each spell a class has sorted by level and then by name.
+fn get_class_spell_indexes(spells: &Vec<spells::Spell>, class: &str) -> Vec<usize>
+{
+	let mut spell_indexes: Vec<_> = (0..spells.len()).filter(|index| spells[*index].has_class(class)).collect();
+	spell_indexes.sort_by_key(|index| spells[*index].level_sort_key());
+	spell_indexes
+}
+
+/// The writer that made a spellbook, kept in the spellbook so more spells can be added to it later.
+pub(crate) struct SpellbookState
+{
+	// The document, layers, pages, and everything else that goes in the spellbook get moved into the spellbook
+	// instead of staying here
+	writer: SpellbookWriter<'static>,
+	bookmark_names: BookmarkNamePolicy,
+	// The spells already in the spellbook and the bookmark names they got
+	spells: Vec<spells::Spell>,
+	used_bookmark_names: Vec<String>,
+	limits: Option<SpellLimits>,
+	spell_check: Option<SpellCheckHook>,
+	// Whether or not anything (like the glossary or the spine page) comes after the spells in the spellbook
+	has_back_matter: bool
+}
+
+impl SpellbookState
+{
+	/// Writes spells after the last page of a spellbook with the writer that made it and checks them the same way
+	/// the spells it was made with were checked.
+	pub fn append_spells(&mut self, spellbook: &mut Spellbook, spells: &Vec<spells::Spell>)
+	-> Result<(), Box<dyn Error + Send + Sync>>
+	{
+		// Spells would end up after the back matter instead of with the other spells
+		if self.has_back_matter
+		{
+			return Err(Box::new(AppendSpellsError(String::from
+			(
+				"Spells can't be added to a spellbook that has pages after its spells (like a glossary, appendices, \
+				endnotes, or a spine page)."
+			))));
+		}
+		// Give the new spells bookmark names that aren't the same as the ones already in the spellbook
+		let mut all_spells = self.spells.clone();
+		all_spells.extend(spells.iter().cloned());
+		let mut bookmark_names = get_bookmark_names(&all_spells, self.bookmark_names).split_off(self.spells.len());
+		if self.bookmark_names != BookmarkNamePolicy::SpellName
+		{
+			let mut used_names = self.used_bookmark_names.iter().cloned().collect();
+			number_duplicate_names(&mut bookmark_names, &mut used_names);
+		}
+		let writer = &mut self.writer;
+		// Gives the writer back the document and everything else it adds to (and takes them back afterwards)
+		let swap = |writer: &mut SpellbookWriter, spellbook: &mut Spellbook|
+		{
+			std::mem::swap(&mut writer.doc, &mut spellbook.doc);
+			std::mem::swap(&mut writer.layers, &mut spellbook.layers);
+			std::mem::swap(&mut writer.pages, &mut spellbook.pages);
+			if let (Some((search_index, _)), Some(spellbook_search_index)) =
+			(&mut writer.search_index, &mut spellbook.search_index)
+			{
+				std::mem::swap(search_index, spellbook_search_index);
+			}
+		};
+		swap(writer, spellbook);
+		let result = writer.append_spells(spells, &bookmark_names);
+		swap(writer, spellbook);
+		spellbook.internal_links.append(&mut writer.internal_links);
+		spellbook.form_checkboxes.append(&mut writer.form_checkboxes);
+		spellbook.tooltips.append(&mut writer.tooltips);
+		spellbook.named_destinations = writer.named_destinations();
+		// The new spells can give the spellbook back matter of its own (like a references section for endnotes)
+		self.has_back_matter = result?;
+		// Check the new spells against the limits and their spelling (if they get checked)
+		if let Some(limits) = &self.limits
+		{
+			spellbook.warnings.extend(spells.iter().flat_map(|spell| limits.check(spell)));
+		}
+		if let Some(spell_check) = &self.spell_check
+		{
+			spellbook.warnings.extend(spells.iter().flat_map(|spell| spell_check.check(spell)));
+		}
+		self.spells.extend(spells.iter().cloned());
+		self.used_bookmark_names.append(&mut bookmark_names);
+		Ok(())
+	}
+}
+
 /// All data needed to write spells to a pdf document.
 // Can't derive clone or debug unfortunately.
 pub struct SpellbookWriter<'a>
@@ -49,21 +550,161 @@ pub struct SpellbookWriter<'a>
 	pages: Vec<PdfPageIndex>,
 	current_page_index: usize,
 	current_page_num: i64,
+	// The page number that the spellbook (and each chapter) starts on
+	starting_page_num: i64,
+	// Text that goes before each page number (changes for each chapter)
+	page_number_prefix: String,
 	font_data: FontData<'a>,
 	page_size_data: PageSizeData,
 	page_number_data: Option<PageNumberData<'a>>,
 	background: Option<BackgroundImage>,
+	// Background images that replace the normal background on the title page and chapter pages (if they have their
+	// own)
+	title_page_background: Option<BackgroundImage>,
+	chapter_page_background: Option<BackgroundImage>,
+	// The kind of page being made (for picking which background it gets)
+	page_kind: PageKind,
 	table_data: TableData,
 	// Stored here so the width of various types of spaces doesn't need to be continually recalculated
 	space_widths: SpaceWidths,
 	// Regex patterns are stored since they consume lots of runtime being reconstructed continutally
-	escaped_font_tag_regex: Regex,
 	table_tag_regex: Regex,
-	backslashes_regex: Regex,
+	table_of_contents: Option<TableOfContentsOptions>,
+	// Index of the first page of the table of contents
+	table_of_contents_page_index: usize,
+	// The columns that the entries of the table of contents are split into (`None` for one column across the page)
+	table_of_contents_columns: Option<ColumnLayout>,
+	// Page index and page number text of the first page of each spell
+	spell_pages: Vec<(usize, String)>,
+	// Page index and page number text of the first page of each chapter
+	chapter_pages: Vec<(usize, String)>,
+	// Links between pages that need to be added to the document when it gets saved
+	internal_links: Vec<InternalLink>,
+	glossary: Option<GlossaryOptions>,
+	reference_appendix: Option<ReferenceAppendixOptions>,
+	scribing_costs: Option<ScribingCostOptions>,
+	level_plan: Option<LevelPlanOptions>,
+	component_list: Option<ComponentListOptions>,
+	school_colors: Option<SchoolColorOptions>,
+	class_chips: Option<ClassChipOptions>,
+	// The color that pages get filled with and the color it fades into at the bottom of the page (if there is one)
+	background_color: Option<(u8, u8, u8)>,
+	background_gradient: Option<(u8, u8, u8)>,
+	distance_conversion: Option<spells::DistanceConversion>,
+	component_style: spells::ComponentStyle,
+	layout_cache: Option<LayoutCache>,
+	// The pages that each word has been written on so far and the options for which words go in it (if there's a
+	// search index)
+	search_index: Option<(SearchIndex, SearchIndexOptions)>,
+	// A hash of every option that affects how spells get laid out (for finding spells in the layout cache)
+	layout_options_hash: u64,
+	aoe_glyphs: Option<AoeGlyphOptions>,
+	// The words of each glossary term that gets linked to in spell descriptions along with the term's anchor
+	// Sorted so that terms with more words get matched first
+	glossary_terms: Vec<(Vec<String>, String)>,
+	// The term and definition of each glossary term by its anchor (empty if glossary terms don't get tooltips)
+	glossary_tooltips: HashMap<String, (String, String)>,
+	// Popups with the definitions of glossary terms that get added when the spellbook is saved
+	tooltips: Vec<Tooltip>,
+	// Whether or not the text currently being processed is part of a spell description
+	// (glossary terms and custom tags only get processed in spell descriptions)
+	in_description: bool,
+	tag_handlers: DescriptionTagHandlers,
+	page_hooks: PageHooks,
+	// The columns text is being written in (`None` if pages aren't being split into columns)
+	columns: Option<ColumnLayout>,
+	// Page index of each named spot in the spellbook that can be linked to
+	anchors: HashMap<String, usize>,
+	// Links to anchors that get turned into links between pages once every anchor is known
+	anchor_links: Vec<(InternalLink, String)>,
+	// Page index and page number text of each table and section that page references can point to
+	reference_anchors: HashMap<String, (usize, String)>,
+	// Page references that get their page numbers written once every page is laid out
+	page_references: Vec<PageReference>,
+	// Whether the anchors of spells, tables, and sections become named destinations in the pdf
+	named_destinations: bool,
+	preparation_checkboxes: Option<PreparationCheckboxOptions>,
+	form_fields: Option<FormFieldOptions>,
+	// Fillable checkboxes that need to be added to the document when it gets saved
+	form_checkboxes: Vec<FormCheckbox>,
+	notes_area: Option<NotesAreaOptions>,
+	name_rule: Option<NameRuleOptions>,
+	name_bar: Option<NameBarOptions>,
+	continuation_footer: Option<ContinuationFooterOptions>,
+	// The level of the spell that's being written and whether it needs concentration and can be cast as a ritual (for
+	// the footers on its pages after its first one)
+	continued_spell: Option<(String, bool, bool)>,
+	quick_stats: Option<QuickStatsOptions>,
+	// Warnings about text colors that don't have enough contrast with the background (taken out when the spellbook
+	// is done)
+	contrast_warnings: Vec<SpellbookWarning>,
+	// Whether or not the first page of the document is still blank and gets used for the next page that gets made
+	// (when there's no title page)
+	unused_first_page: bool,
+	title_outline: Option<TitleOutlineOptions>,
+	// The QR codes that go on the title page and next to spell names (if there are any)
+	qr_codes: Option<SpellbookQrCodes>,
+	// Finds the syllables of words that are too long to fit on a line (if words get hyphenated between syllables)
+	hyphenator: Option<Hyphenator>,
+	justification: Option<JustificationOptions>,
+	// How much wider than normal each space in the line being written is (if the line is justified)
+	word_spacing: Mm,
+	paragraph_breaks: Option<ParagraphBreakOptions>,
+	spell_layout: SpellLayoutOptions,
+	// Whether the tables of the spell being written can be split across page breaks
+	allow_table_split: bool,
+	// The page the last spell ended on (`None` if something else has gone after it or the page got filled)
+	last_spell_end: Option<usize>,
+	footnotes: FootnoteOptions,
+	// The text of every footnote so far (the ids in footnote numbers are indexes into this)
+	footnote_texts: Vec<String>,
+	// The number that each footnote got when its number was written
+	footnote_numbers: HashMap<usize, usize>,
+	// The ids of the footnotes that go at the bottom of the current page (or column)
+	page_footnotes: Vec<usize>,
+	// The height of the space saved at the bottom of the current page (or column) for its footnotes
+	footnote_area_height: Mm,
+	// The last footnote number that was used and the page it was used on
+	footnote_count: usize,
+	footnote_page_index: usize,
+	endnotes: Option<EndnoteOptions>,
+	redaction: Option<RedactionStyle>,
+	paragraph_styles: BTreeMap<String, ParagraphStyle>,
+	// The text color of the paragraph being written if its paragraph style changed it (text goes back to this color
+	// after color tags)
+	paragraph_color: Option<Color>,
+	// The color and left and right sides of the box behind the paragraph being written (if its paragraph style has
+	// one)
+	paragraph_box: Option<(Color, Mm, Mm)>,
+	// The page that each endnote's number was written on along with the text of that page's number
+	endnote_pages: HashMap<usize, (usize, String)>,
+	// The spells that go in the references section with the name, source, index in `spell_pages`, and endnote ids of
+	// each one
+	endnote_spells: Vec<(String, Option<String>, usize, std::ops::Range<usize>)>,
+	compact_density: Option<CompactDensityOptions>,
+	page_fill: Option<PageFillOptions>,
+	// The image of each page filler that is an image (`None` for the other fillers)
+	page_fill_images: Vec<Option<DynamicImage>>,
+	// The number of pages that have been filled so far (for picking the next filler)
+	filled_page_count: usize,
+	upcast: UpcastOptions,
+	cantrip_scaling: Option<CantripScalingOptions>,
+	// Where the text currently being applied links to (if anywhere)
+	active_link: Option<LinkTarget>,
+	// The x position where the text of the active link starts on the current line
+	link_start_x: Mm,
+	// The color of the text currently being applied if a color tag changed it
+	color_override: Option<Color>,
+	debug_layout: bool,
+	// Whether the columns on the last page of the glossary and compact spell lists end at about the same height
+	balance_columns: bool,
+	// Outlines for debugging the layout along with the page index and color of each one
+	// (drawn after everything else so they end up on top)
+	debug_lines: Vec<(usize, Line, (u8, u8, u8))>,
 	// Current x position of text
-	x: f32,
+	x: Mm,
 	// Current y position of text
-	y: f32
+	y: Mm
 }
 
 impl <'a> SpellbookWriter<'a>
@@ -81,11 +722,13 @@ impl <'a> SpellbookWriter<'a>
 	/// - `background` An image filepath to use as backgrounds for each page and transform data to make it fit on
 	/// the page the way you want.
 	/// - `table_options` Sizing and color options for tables in spell descriptions.
+	/// - `additional_options` Optional features (like a table of contents).
 	///
 	/// # Output
 	///
-	/// - `Ok` Returns a `printpdf` PDF document of a spellbook and a vec of the layers in the document.
+	/// - `Ok` Returns a spellbook holding the `printpdf` PDF document, its layers, and its pages.
 	/// - `Err` Returns any errors that occured.
+	#[cfg_attr(feature = "tracing", tracing::instrument(skip_all, fields(title = title, spell_count = spells.len())))]
 	pub fn create_spellbook
 	(
 		title: &str,
@@ -98,10 +741,56 @@ impl <'a> SpellbookWriter<'a>
 		page_size_options: PageSizeOptions,
 		page_number_options: Option<PageNumberOptions>,
 		background: Option<(&str, ImageTransform)>,
-		table_options: TableOptions
+		table_options: TableOptions,
+		additional_options: &AdditionalOptions
 	)
-	-> Result<(PdfDocumentReference, Vec<PdfLayerReference>, Vec<PdfPageIndex>), Box<dyn Error>>
+	-> Result<Spellbook, Box<dyn Error + Send + Sync>>
 	{
+		// Check each spell against the limits (if there are any)
+		let mut warnings: Vec<SpellbookWarning> = match &additional_options.limits
+		{
+			Some(limits) => spells.iter().flat_map(|spell| limits.check(spell)).collect(),
+			None => Vec::new()
+		};
+		// Check the spelling of each spell (if there's a spell checker)
+		if let Some(spell_check) = &additional_options.spell_check
+		{
+			warnings.extend(spells.iter().flat_map(|spell| spell_check.check(spell)));
+		}
+		// Make the QR codes before anything gets written so urls that can't be put in QR codes get caught right away
+		// (if there are QR codes)
+		let qr_codes = match &additional_options.qr_codes
+		{
+			Some(options) =>
+			{
+				let title = if title.is_empty() { DEFAULT_SPELLBOOK_TITLE } else { title };
+				Some(SpellbookQrCodes::new(options, title, spells)?)
+			},
+			None => None
+		};
+		// Fingerprint everything that goes into the spellbook before the options get used up (if a fingerprint is
+		// desired)
+		let fingerprint = additional_options.fingerprint.as_ref().map(|_|
+		{
+			fingerprint::spellbook_fingerprint
+			(
+				title,
+				spells,
+				&font_paths,
+				background.map(|(file_path, _)| file_path),
+				additional_options,
+				&[
+					&font_sizes,
+					&font_scalars,
+					&spacing_options,
+					&text_colors,
+					&page_size_options,
+					&page_number_options,
+					&background.map(|(_, transform)| transform),
+					&table_options
+				]
+			)
+		});
 		// Construct a spellbook writer
 		let mut writer = SpellbookWriter::new
 		(
@@ -114,14 +803,244 @@ impl <'a> SpellbookWriter<'a>
 			page_size_options,
 			page_number_options,
 			background,
-			table_options
+			table_options,
+			additional_options
 		)?;
-		// Turn the first page into the title page
-		writer.make_title_page(title);
-		// Add each spell to the spellbook
-		for spell in spells { writer.add_spell(spell); }
+		writer.qr_codes = qr_codes;
+		// Add the warnings about the contrast of the text colors (if it was checked)
+		warnings.append(&mut writer.contrast_warnings);
+		#[cfg(feature = "tracing")]
+		for warning in &warnings { tracing::warn!(spell = warning.spell_name(), "{}", warning); }
+		// Leave the first page blank for the first page of spells if there's no title page
+		if additional_options.skip_title_page { writer.unused_first_page = true; }
+		else
+		{
+			// Turn the first page into the title page
+			writer.make_title_page(title);
+			// Put the fingerprint on the back of the title page (if there is one)
+			if let (Some(options), Some(fingerprint)) = (&additional_options.fingerprint, &fingerprint)
+			{
+				writer.add_fingerprint_page(options, fingerprint);
+			}
+		}
+		// Figure out what order the spells (and chapters if there are any) go in
+		let entries = get_spellbook_entries(spells, &additional_options.class_chapters);
+		// The index of each spell in the order the spells get added (the same order as their pages in `spell_pages`)
+		let spell_order: Vec<usize> = entries.iter()
+			.filter_map(|entry| match entry { SpellbookEntry::Spell(index) => Some(*index), _ => None })
+			.collect();
+		// Make pages for the table of contents (if there is one) and figure out where its lines go
+		let table_of_contents_lines = writer.reserve_table_of_contents(spells, &entries);
+		// Add each chapter and spell to the spellbook
+		let bookmark_names = get_bookmark_names(spells, additional_options.bookmark_names);
+		let preview = additional_options.preview.clone().unwrap_or_default();
+		// The number of lines in each spell of a compact list and the ones after it in the same list (for balancing the
+		// columns on the last page of each list)
+		let compact_remaining_lines = match additional_options.compact_list
+		{
+			Some(options) if writer.balance_columns =>
+				writer.compact_remaining_lines(spells, &entries, &preview, options),
+			_ => vec![0; entries.len()]
+		};
+		for (entry, remaining_lines) in entries.into_iter().zip(compact_remaining_lines)
+		{
+			// Stop adding things once a preview has enough pages
+			if preview.page_limit_reached(writer.pages.len()) { break; }
+			match entry
+			{
+				SpellbookEntry::Chapter(chapter) =>
+				{
+					// Chapters start on full width pages (even if the spells before them were in a compact list)
+					writer.columns = None;
+					match &additional_options.class_chapters
+					{
+						Some(options) if options.layout == ClassChapterLayout::ClassListPages =>
+							writer.add_class_list(spells, &chapter),
+						_ => writer.start_chapter(&chapter)
+					}
+				},
+				SpellbookEntry::Spell(index) =>
+				{
+					let bookmark_name = bookmark_names[index].clone();
+					match additional_options.compact_list
+					{
+						// Spells that are left out of previews don't take up any space in compact lists
+						Some(_) if !preview.includes_spell(index) => (),
+						Some(options) =>
+							writer.add_compact_spell(&spells[index], bookmark_name, options, remaining_lines),
+						None if !preview.includes_spell(index) => writer.skip_spell(&spells[index]),
+						None => writer.add_spell(&spells[index], bookmark_name)
+					}
+				}
+			}
+		}
+		// Write the footnotes of the last page of spells (if it has any)
+		writer.apply_footnotes();
+		writer.columns = None;
+		let spells_end = writer.pages.len();
+		if !preview.page_limit_reached(writer.pages.len())
+		{
+			// Add the glossary after the spells (if there is one)
+			writer.add_glossary();
+			// Add the scribing costs of the spells after the glossary (if they're desired)
+			writer.add_scribing_costs(spells);
+			// Add the class level each spell becomes available at after the scribing costs (if it's desired)
+			writer.add_level_plan(spells, &spell_order);
+			// Add the checklist of material components after the level plan (if it's desired)
+			writer.add_component_list(spells);
+			// Add the reference pages after the scribing costs (if there are any)
+			writer.add_reference_appendix();
+			// Add the endnotes and sources of the spells at the very end (if they're desired)
+			writer.add_endnotes();
+		}
+		// Fill in the table of contents now that the page of each spell is known
+		writer.apply_table_of_contents(&table_of_contents_lines);
+		// Point links to anchors (like glossary terms) at the pages the anchors ended up on
+		writer.resolve_anchor_links();
+		// Write the page numbers of page references now that the page of every table and section is known
+		writer.apply_page_references()?;
+		// Draw the layout outlines on top of everything (if they're desired)
+		writer.apply_debug_layout(0);
+		// Add the spine page after everything else so nothing meant for the other pages goes on it (if there is one)
+		if let Some(options) = &additional_options.spine { writer.add_spine_page(title, options); }
+		let has_back_matter = writer.pages.len() > spells_end;
+		// Move the document and everything else that goes in the spellbook out of the writer so the writer can be
+		// kept for adding more spells later
+		let doc = std::mem::replace(&mut writer.doc, PdfDocument::empty(""));
+		let layers = std::mem::take(&mut writer.layers);
+		let pages = std::mem::take(&mut writer.pages);
+		let search_index = writer.search_index.as_mut().map(|(search_index, _)| std::mem::take(search_index));
+		let internal_links = std::mem::take(&mut writer.internal_links);
+		let form_checkboxes = std::mem::take(&mut writer.form_checkboxes);
+		let tooltips = std::mem::take(&mut writer.tooltips);
+		let named_destinations = writer.named_destinations();
 		// Return the document that was created, its layers, and its pages
-		Ok((writer.doc, writer.layers, writer.pages))
+		Ok(Spellbook
+		{
+			doc: doc,
+			layers: layers,
+			pages: pages,
+			warnings: warnings,
+			fingerprint: fingerprint,
+			encryption: additional_options.encryption.clone(),
+			search_index: search_index,
+			internal_links: internal_links,
+			form_checkboxes: form_checkboxes,
+			tooltips: tooltips,
+			named_destinations: named_destinations,
+			state: Some(SpellbookState
+			{
+				writer: writer,
+				bookmark_names: additional_options.bookmark_names,
+				spells: spells.clone(),
+				used_bookmark_names: bookmark_names,
+				limits: additional_options.limits,
+				spell_check: additional_options.spell_check.clone(),
+				has_back_matter: has_back_matter
+			})
+		})
+	}
+
+	/// Makes a cover wrap for a spellbook: a single wide page with the back cover, spine, and front cover of the
+	/// spellbook side by side (in that order from left to right).
+	///
+	/// # Parameters
+	///
+	/// - `title` The title of the spellbook.
+	/// - `font_paths` File paths to all of the font variants (regular, bold, italic, bold-italic).
+	/// - `font_sizes` Font sizes for each type of text in the spellbook (except page numbers).
+	/// - `font_scalars` Scalar values to make sure text width can be calculated correctly for each font variant.
+	/// - `spacing_options` Tab size and newline sizes for each type of text (except page numbers).
+	/// - `text_colors` The RGB color values for each type of text (except page numbers).
+	/// - `page_size_options` Page width, height, and margin values of the spellbook.
+	/// - `table_options` Sizing and color options for tables in the back cover text.
+	/// - `additional_options` Optional features (only the ones for how text looks and the image cache get used).
+	/// - `cover_options` The widths and artwork of each part of the cover.
+	///
+	/// # Output
+	///
+	/// - `Ok` Returns the `printpdf` PDF document with the cover wrap on its only page.
+	/// - `Err` Returns any errors that occured.
+	#[allow(clippy::too_many_arguments)]
+	pub fn create_cover_wrap
+	(
+		title: &str,
+		font_paths: FontPaths,
+		font_sizes: FontSizes,
+		font_scalars: FontScalars,
+		spacing_options: SpacingOptions,
+		text_colors: TextColorOptions,
+		page_size_options: PageSizeOptions,
+		table_options: TableOptions,
+		additional_options: &AdditionalOptions,
+		cover_options: &CoverWrapOptions
+	)
+	-> Result<PdfDocumentReference, Box<dyn Error + Send + Sync>>
+	{
+		// The page is wide enough for both covers and the spine with the bleed around all of it
+		let bleed = cover_options.bleed.max(0.0);
+		let cover_width = cover_options.cover_width.unwrap_or(page_size_options.width());
+		let wrap_size_options = PageSizeOptions::new
+		(
+			cover_width * 2.0 + cover_options.spine.width + bleed * 2.0,
+			page_size_options.height() + bleed * 2.0,
+			page_size_options.left_margin() + bleed,
+			page_size_options.right_margin() + bleed,
+			page_size_options.top_margin() + bleed,
+			page_size_options.bottom_margin() + bleed
+		)?;
+		// Only keep the options that change how text looks (there are no spells or extra pages on the cover)
+		let wrap_additional_options = AdditionalOptions
+		{
+			kerning: additional_options.kerning,
+			ligatures: additional_options.ligatures,
+			shaping: additional_options.shaping,
+			justification: additional_options.justification,
+			image_cache: additional_options.image_cache.clone(),
+			..Default::default()
+		};
+		let mut writer = SpellbookWriter::new
+		(
+			title,
+			font_paths,
+			font_sizes,
+			font_scalars,
+			spacing_options,
+			text_colors,
+			wrap_size_options,
+			None,
+			None,
+			table_options,
+			&wrap_additional_options
+		)?;
+		let image_cache = wrap_additional_options.image_cache.unwrap_or_default();
+		writer.make_cover_wrap(title, cover_options, Mm(cover_width), Mm(bleed), &image_cache)?;
+		Ok(std::mem::replace(&mut writer.doc, PdfDocument::empty("")))
+	}
+
+	/// Adds spells after the last page of a spellbook that this writer already made, continuing its page numbers.
+	/// Gives whether or not any pages got added after the new spells (like a references section for their endnotes).
+	fn append_spells(&mut self, spells: &Vec<spells::Spell>, bookmark_names: &[String])
+	-> Result<bool, Box<dyn Error + Send + Sync>>
+	{
+		// Make the QR codes for the new spells before anything gets written (if spells have QR codes)
+		if let Some(qr_codes) = &mut self.qr_codes { qr_codes.add_spells(spells)?; }
+		let first_new_page_index = self.layers.len();
+		for (spell, bookmark_name) in spells.iter().zip(bookmark_names)
+		{
+			self.add_spell(spell, bookmark_name.clone());
+		}
+		self.apply_footnotes();
+		let spells_end = self.pages.len();
+		// Give the new spells their own references section (if there are endnotes)
+		self.add_endnotes();
+		// Link glossary terms and endnote numbers in the new spells to the glossary and endnotes
+		self.resolve_anchor_links();
+		// Fill in the page numbers of page references in the new spells
+		self.apply_page_references()?;
+		// Draw the layout outlines of the new spells (if they're desired)
+		self.apply_debug_layout(first_new_page_index);
+		Ok(self.pages.len() > spells_end)
 	}
 
 	/// Constructor
@@ -137,14 +1056,15 @@ impl <'a> SpellbookWriter<'a>
 	/// - `page_size_options` Page width, height, and margin values.
 	/// - `page_number_options` Settings for how page numbers look (`None` for no page numbers).
 	/// - `background` An image filepath to use as backgrounds for each page and transform data to make it fit on
-	/// the page the way you want.
+	///   the page the way you want.
 	/// - `table_options` Sizing and color options for tables in spell descriptions.
+	/// - `additional_options` Optional features (like a table of contents).
 	///
 	/// # Output
 	///
 	/// - `Ok` A SpellbookWriter instance.
 	/// - `Err` Returns any errors that occured.
-	fn new
+	pub(crate) fn new
 	(
 		title: &str,
 		font_paths: FontPaths,
@@ -155,13 +1075,94 @@ impl <'a> SpellbookWriter<'a>
 		page_size_options: PageSizeOptions,
 		page_number_options: Option<PageNumberOptions>,
 		background: Option<(&str, ImageTransform)>,
-		table_options: TableOptions
+		table_options: TableOptions,
+		additional_options: &AdditionalOptions
 	)
-	-> Result<Self, Box<dyn Error>>
+	-> Result<Self, Box<dyn Error + Send + Sync>>
 	{
+		// Hash the options that affect how spells get laid out before they get used up
+		let layout_options_hash = layout_cache::options_hash
+		(
+			&[
+				&font_paths,
+				&font_sizes,
+				&font_scalars,
+				&spacing_options,
+				&page_size_options,
+				&table_options,
+				&layout_cache::layout_options(additional_options)
+			]
+		);
+
 		// Gets a new document and title page.
 		let (doc, title_page, title_layer) =
-		Self::create_new_doc(title, page_size_options.width(), page_size_options.height());
+		Self::create_new_doc(title, Mm(page_size_options.width()), Mm(page_size_options.height()));
+
+		// Data for text margins and page dimensions
+		let page_size_data = PageSizeData::from(page_size_options);
+
+		// Open the background images (unless they're already in the image cache)
+		let image_cache = additional_options.image_cache.clone().unwrap_or_default();
+		// Determine whether or not a background image is desired
+		let background = match background 
+		{
+			// If it is, construct background image data from the options given
+			Some((file_path, transform)) => Some(BackgroundImage::new
+			(
+				file_path,
+				transform,
+				additional_options.background_fit,
+				additional_options.background_alternation,
+				page_size_data.page_width(),
+				page_size_data.page_height(),
+				&image_cache
+			)?),
+			// If no background image was given, don't use a background
+			None => None
+		};
+		// Load the backgrounds of the title page and chapter pages (if they have their own)
+		let section_background = |background: Option<&(String, ImageTransform)>|
+		{
+			match background
+			{
+				Some((file_path, transform)) => Ok(Some(BackgroundImage::new
+				(
+					file_path,
+					*transform,
+					additional_options.background_fit,
+					None,
+					page_size_data.page_width(),
+					page_size_data.page_height(),
+					&image_cache
+				)?)),
+				None => Ok::<_, Box<dyn Error + Send + Sync>>(None)
+			}
+		};
+		let section_backgrounds = additional_options.section_backgrounds.clone().unwrap_or_default();
+		let title_page_background = section_background(section_backgrounds.title_page.as_ref())?;
+		let chapter_page_background = section_background(section_backgrounds.chapter_pages.as_ref())?;
+		// Check that the text can be read on top of the backgrounds and adjust its colors if they need to be (if the
+		// contrast is checked and there's a background to check it against)
+		let mut text_colors = text_colors;
+		// Only the part of the backgrounds between the margins has text on it
+		let average_color = |background: &BackgroundImage| background.average_color
+		(page_size_data.x_min(), page_size_data.y_min(), page_size_data.x_max(), page_size_data.y_max());
+		// Without a background image, the text is on top of the background color (halfway through its gradient)
+		let background_color = match (&background, additional_options.background_color)
+		{
+			(Some(background), _) => Some(average_color(background)),
+			(None, Some(color)) => Some(mix_colors(color, additional_options.background_gradient.unwrap_or(color), 0.5)),
+			(None, None) => None
+		};
+		let contrast_warnings = match (&additional_options.contrast_check, background_color)
+		{
+			(Some(options), Some(background_color)) =>
+			{
+				let title_page_background_color = title_page_background.as_ref().map_or(background_color, average_color);
+				options.check(&mut text_colors, background_color, title_page_background_color)
+			},
+			_ => Vec::new()
+		};
 
 		// Combined data for all font options along with font references to the pdf doc
 		let font_data = FontData::new
@@ -171,12 +1172,10 @@ impl <'a> SpellbookWriter<'a>
 			font_sizes,
 			font_scalars,
 			spacing_options,
-			text_colors
+			text_colors,
+			additional_options
 		)?;
 
-		// Data for text margins and page dimensions
-		let page_size_data = PageSizeData::from(page_size_options);
-
 		// Determine whether or not page numbers are desired
 		let (page_number_data, starting_page_num) = match page_number_options
 		{
@@ -186,36 +1185,28 @@ impl <'a> SpellbookWriter<'a>
 			None => (None, 1)
 		};
 
-		// Determine whether or not a background image is desired
-		let background = match background 
+		// Load the hyphenation patterns of the text's language (if words get hyphenated between syllables)
+		let hyphenator = match &additional_options.hyphenation
 		{
-			// If it is, construct background image data from the options given
-			Some((file_path, transform)) => Some(BackgroundImage::new(file_path, transform)?),
-			// If no background image was given, don't use a background
+			Some(options) => Some(Hyphenator::new(options)?),
 			None => None
 		};
+		// Load the images that can fill empty space at the end of spells (if there are any)
+		let mut page_fill_images = Vec::new();
+		if let Some(page_fill) = &additional_options.page_fill
+		{
+			for filler in &page_fill.fillers
+			{
+				page_fill_images.push(match filler
+				{
+					PageFiller::Image { path, .. } => Some(load_image(path, PAGE_FILL_IMAGE_DPI)?),
+					_ => None
+				});
+			}
+		}
 		// Calculate the width of each variation of a space character
 		let space_widths = SpaceWidths::new(&font_data);
 		let table_data = TableData::from(table_options);
-		// Create a regex pattern for escaped font tags (font tags preceeded by backslashes)
-		// Ex: "\<r>", "\\\<bi>", "\\<i>", etc.
-		// Use this regex pattern to remove the first backslash from escaped font tags so that font tags are allowed
-		// to actually appear in spell text AND not affect the font at all
-		let escaped_font_tag_pattern = format!
-		(
-			"(\\\\)+({}|{}|{}|{}|{})",
-			REGULAR_FONT_TAG,
-			BOLD_FONT_TAG,
-			ITALIC_FONT_TAG,
-			BOLD_ITALIC_FONT_TAG,
-			ITALIC_BOLD_FONT_TAG
-		);
-		let escaped_font_tag_regex = Regex::new(&escaped_font_tag_pattern)
-		.expect(format!
-		(
-			"Failed to build regex pattern \"{}\" in `dnd_spellbook_maker::spellbook_writer::SpellbookWriter::new`",
-			escaped_font_tag_pattern
-		).as_str());
 		// Create a regex pattern to find table tags which are used for inserting tables into spell descriptions
 		// Ex: "[table][5]", "[table][0]", "[table][2]", etc.
 		let table_tag_pattern = "\\[table\\]\\[[0-9]+\\]";
@@ -225,173 +1216,2572 @@ impl <'a> SpellbookWriter<'a>
 			"Failed to build regex pattern \"{}\" in `dnd_spellbook_maker::spellbook_writer::SpellbookWriter::new`",
 			table_tag_pattern
 		).as_str());
-		// Create a regex pattern to find repeating backslashes which areused for finding escaped table tags
-		let backslashes_pattern = "\\\\+";
-		let backslashes_regex = Regex::new(backslashes_pattern)
-		.expect(format!
-		(
-			"Failed to build regex pattern \"{}\" in `dnd_spellbook_maker::spellbook_writer::SpellbookWriter::new`",
-			backslashes_pattern
-		).as_str());
 
-		// Construct instance of self and return
-		Ok(Self
+		// Get the words of each glossary term that should be linked to (or get tooltips) in spell descriptions
+		let mut glossary_terms: Vec<(Vec<String>, String)> = match &additional_options.glossary
+		{
+			Some(glossary) if glossary.link_keywords || glossary.tooltips => glossary.entries.keys()
+			.map(|term| (term.split_whitespace().map(keyword_form).collect::<Vec<_>>(), glossary_anchor(term)))
+			.filter(|(words, _)| !words.is_empty() && words.iter().all(|word| !word.is_empty()))
+			.collect(),
+			_ => Vec::new()
+		};
+		glossary_terms.sort_by_key(|(words, _)| std::cmp::Reverse(words.len()));
+		let glossary_tooltips = match &additional_options.glossary
+		{
+			Some(glossary) if glossary.tooltips => glossary.entries.iter()
+			.map(|(term, definition)| (glossary_anchor(term), (term.clone(), definition.clone())))
+			.collect(),
+			_ => HashMap::new()
+		};
+
+		// Construct instance of self and return
+		Ok(Self
 		{
 			doc: doc,
 			layers: vec![title_layer],
 			pages: vec![title_page],
 			current_page_index: 0,
 			current_page_num: starting_page_num,
+			starting_page_num: starting_page_num,
+			page_number_prefix: String::new(),
 			font_data: font_data,
 			page_size_data: page_size_data,
 			page_number_data: page_number_data,
 			background: background,
+			title_page_background: title_page_background,
+			chapter_page_background: chapter_page_background,
+			page_kind: PageKind::Body,
 			space_widths: space_widths,
 			table_data: table_data,
-			escaped_font_tag_regex: escaped_font_tag_regex,
 			table_tag_regex: table_tag_regex,
-			backslashes_regex: backslashes_regex,
+			table_of_contents: additional_options.table_of_contents.clone(),
+			table_of_contents_page_index: 0,
+			table_of_contents_columns: None,
+			spell_pages: Vec::new(),
+			chapter_pages: Vec::new(),
+			internal_links: Vec::new(),
+			glossary: additional_options.glossary.clone(),
+			reference_appendix: additional_options.reference_appendix.clone(),
+			scribing_costs: additional_options.scribing_costs.clone(),
+			level_plan: additional_options.level_plan.clone(),
+			component_list: additional_options.component_list.clone(),
+			school_colors: additional_options.school_colors.clone(),
+			class_chips: additional_options.class_chips.clone(),
+			background_color: additional_options.background_color,
+			background_gradient: additional_options.background_gradient,
+			distance_conversion: additional_options.distance_conversion,
+			component_style: additional_options.component_style,
+			layout_cache: additional_options.layout_cache.clone(),
+			search_index: additional_options.search_index.clone().map(|options| (SearchIndex::default(), options)),
+			layout_options_hash: layout_options_hash,
+			aoe_glyphs: additional_options.aoe_glyphs,
+			glossary_terms: glossary_terms,
+			glossary_tooltips: glossary_tooltips,
+			tooltips: Vec::new(),
+			in_description: false,
+			tag_handlers: additional_options.tag_handlers.clone(),
+			page_hooks: additional_options.page_hooks.clone(),
+			columns: None,
+			anchors: HashMap::new(),
+			anchor_links: Vec::new(),
+			reference_anchors: HashMap::new(),
+			page_references: Vec::new(),
+			named_destinations: additional_options.named_destinations,
+			preparation_checkboxes: additional_options.preparation_checkboxes,
+			form_fields: additional_options.form_fields,
+			form_checkboxes: Vec::new(),
+			notes_area: additional_options.notes_area,
+			name_rule: additional_options.name_rule,
+			name_bar: additional_options.name_bar,
+			continuation_footer: additional_options.continuation_footer,
+			continued_spell: None,
+			quick_stats: additional_options.quick_stats,
+			contrast_warnings: contrast_warnings,
+			unused_first_page: false,
+			title_outline: additional_options.title_outline,
+			qr_codes: None,
+			hyphenator: hyphenator,
+			justification: additional_options.justification,
+			word_spacing: Mm(0.0),
+			paragraph_breaks: additional_options.paragraph_breaks,
+			spell_layout: additional_options.spell_layout,
+			allow_table_split: additional_options.spell_layout.allow_table_split,
+			last_spell_end: None,
+			footnotes: additional_options.footnotes,
+			footnote_texts: Vec::new(),
+			footnote_numbers: HashMap::new(),
+			page_footnotes: Vec::new(),
+			footnote_area_height: Mm(0.0),
+			footnote_count: 0,
+			footnote_page_index: 0,
+			endnotes: additional_options.endnotes.clone(),
+			redaction: additional_options.redaction,
+			paragraph_styles: additional_options.paragraph_styles.clone(),
+			paragraph_color: None,
+			paragraph_box: None,
+			endnote_pages: HashMap::new(),
+			endnote_spells: Vec::new(),
+			compact_density: additional_options.compact_density,
+			page_fill: additional_options.page_fill.clone(),
+			page_fill_images: page_fill_images,
+			filled_page_count: 0,
+			upcast: additional_options.upcast.clone(),
+			cantrip_scaling: additional_options.cantrip_scaling.clone(),
+			active_link: None,
+			link_start_x: Mm(0.0),
+			color_override: None,
+			debug_layout: additional_options.debug_layout,
+			balance_columns: additional_options.balance_columns,
+			debug_lines: Vec::new(),
 			x: page_size_data.x_min(),
 			y: page_size_data.y_max()
 		})
 	}
 
-	/// Creates a new pdf document with a given title and width / height dimensions and returns the reference to
-	/// it and layer for the title page. Returns the pdf document and the layer for the first page.
-	fn create_new_doc(title: &str, width: f32, height: f32)
-	-> (PdfDocumentReference, PdfPageIndex, PdfLayerReference)
+	/// Creates a new pdf document with a given title and width / height dimensions and returns the reference to
+	/// it and layer for the title page. Returns the pdf document and the layer for the first page.
+	fn create_new_doc(title: &str, width: Mm, height: Mm)
+	-> (PdfDocumentReference, PdfPageIndex, PdfLayerReference)
+	{
+		// Create the pdf document and the first page
+		let (doc, title_page, title_layer_index) =
+		// If no title was given for the spellbook (the given title string is empty)
+		if title.is_empty()
+		{
+			// Create pdf document with a default title
+			PdfDocument::new(DEFAULT_SPELLBOOK_TITLE, width, height, TITLE_LAYER_NAME)
+		}
+		else
+		{
+			// Create pdf document with the given title
+			PdfDocument::new(title, width, height, TITLE_LAYER_NAME)
+		};
+
+		// Get PdfLayerReference (title_layer_ref) from PdfLayerIndex (title_layer_index)
+		let title_layer_ref = doc.get_page(title_page).get_layer(title_layer_index);
+
+		(doc, title_page, title_layer_ref)
+	}
+
+	/// Turns the current page into a title page with the given title.
+	fn make_title_page(&mut self, mut title: &str)
+	{
+		// Use the default spellbook title if none was given
+		if title.is_empty() { title = DEFAULT_SPELLBOOK_TITLE; }
+		// Create bookmark for title page
+		self.doc.add_bookmark(TITLE_PAGE_NAME, self.pages[self.current_page_index]);
+		// Adds a background image to the page (if they are desired)
+		self.page_kind = PageKind::TitlePage;
+		self.add_background();
+		self.page_kind = PageKind::Body;
+		// Store the page number data and set it to None so page numbers don't appear in any title pages created
+		let page_number_data = self.page_number_data.clone();
+		self.page_number_data = None;
+		// Write the title to the page
+		self.write_centered_textbox(title, self.x_min(), self.x_max(), self.y_bottom(), self.y_top());
+		// Put the spell slot tracker at the bottom of the page (if there is one)
+		if let Some(slot_tracker) = self.form_fields.and_then(|options| options.slot_tracker)
+		{
+			self.apply_slot_tracker(slot_tracker);
+		}
+		// Put the QR code for the spellbook in the bottom right corner of the page (if there is one)
+		if let Some(qr_codes) = &self.qr_codes
+		{
+			if let Some((url, code)) = qr_codes.book_code()
+			{
+				let size = Mm(qr_codes.options().book_code_size);
+				self.apply_qr_code(url, code, self.x_max() - size, self.y_min() + size, size, qr_codes.options().color);
+			}
+		}
+		// Reset the page number data to what it was before
+		self.page_number_data = page_number_data;
+	}
+
+	/// Turns the current page into a cover wrap with the back cover, spine, and front cover of the spellbook on it.
+	fn make_cover_wrap
+	(
+		&mut self,
+		mut title: &str,
+		options: &CoverWrapOptions,
+		cover_width: Mm,
+		bleed: Mm,
+		image_cache: &ImageCache
+	)
+	-> Result<(), Box<dyn Error + Send + Sync>>
+	{
+		if title.is_empty() { title = DEFAULT_SPELLBOOK_TITLE; }
+		self.doc.add_bookmark(TITLE_PAGE_NAME, self.pages[self.current_page_index]);
+		// Where the back cover ends and the front cover starts (the artwork goes all the way into the bleed)
+		let spine_x_min = bleed + cover_width;
+		let front_x_min = spine_x_min + Mm(options.spine.width);
+		if let Some(color) = &options.background_color
+		{
+			let layer = self.current_layer();
+			layer.save_graphics_state();
+			layer.set_fill_color(bytes_to_color(color));
+			let rect = Rect::new(Mm(0.0), Mm(0.0), self.page_width(), self.page_height());
+			layer.add_rect(rect.with_mode(PaintMode::Fill));
+			layer.restore_graphics_state();
+		}
+		if let Some(path) = &options.back_image { self.apply_cover_image(path, Mm(0.0), spine_x_min, image_cache)?; }
+		if let Some(path) = &options.front_image
+		{
+			self.apply_cover_image(path, front_x_min, self.page_width(), image_cache)?;
+		}
+		self.apply_spine(title, &options.spine, spine_x_min);
+		// The margins of the spellbook's pages are kept on the inside edge of each cover too
+		let left_margin = self.x_min() - bleed;
+		let right_margin = self.page_width() - bleed - self.x_max();
+		// Write the title in the middle of the front cover
+		self.set_current_text_type(TextType::Title);
+		self.set_current_font_variant(FontVariant::Regular);
+		self.write_centered_textbox(title, front_x_min + left_margin, self.x_max(), self.y_bottom(), self.y_top());
+		// Write the back cover text from the top of the back cover down
+		if !options.back_text.is_empty()
+		{
+			self.set_current_text_type(TextType::Body);
+			self.set_current_font_variant(FontVariant::Regular);
+			self.x = self.x_min();
+			self.y = self.y_max();
+			self.write_textbox
+			(
+				&options.back_text,
+				self.x_min(),
+				spine_x_min - right_margin,
+				self.y_min(),
+				self.y_max(),
+				false,
+				&Vec::new()
+			);
+		}
+		Ok(())
+	}
+
+	/// Stretches an image over the whole height of the current page between two x positions.
+	fn apply_cover_image(&mut self, image_path: &str, x_min: Mm, x_max: Mm, image_cache: &ImageCache)
+	-> Result<(), Box<dyn Error + Send + Sync>>
+	{
+		let dpi = COVER_IMAGE_DPI;
+		let image = image_cache.get_or_load(image_path, &ImageTransform { dpi: Some(dpi), ..Default::default() })?;
+		// The size of the image on the page at its actual size
+		let actual_width = Mm(image.image.width() as f32 / dpi * MM_PER_INCH);
+		let actual_height = Mm(image.image.height() as f32 / dpi * MM_PER_INCH);
+		if actual_width <= Mm(0.0) || actual_height <= Mm(0.0) { return Ok(()); }
+		let transform = ImageTransform
+		{
+			translate_x: Some(x_min),
+			translate_y: Some(Mm(0.0)),
+			scale_x: Some((x_max - x_min) / actual_width),
+			scale_y: Some(self.page_height() / actual_height),
+			dpi: Some(dpi),
+			..Default::default()
+		};
+		Image::from(image.pdf_image.as_ref().clone()).add_to_layer(self.current_layer().clone(), transform);
+		Ok(())
+	}
+
+	/// Adds a page after the title page with a line at the bottom of it for the fingerprint of the spellbook.
+	fn add_fingerprint_page(&mut self, options: &FingerprintOptions, fingerprint: &str)
+	{
+		// The back of the title page doesn't get a page number or count towards the page numbers (like the title page)
+		let page_number_data = self.page_number_data.clone();
+		self.page_number_data = None;
+		self.make_new_page();
+		self.current_page_num -= 1;
+		self.page_number_data = page_number_data;
+		let mut text = format!("{} {}", options.label, fingerprint).trim().to_string();
+		if options.show_version
+		{
+			text = format!("{} ({} {})", text, env!("CARGO_PKG_NAME"), env!("CARGO_PKG_VERSION"));
+		}
+		self.set_current_text_type(TextType::Body);
+		self.set_current_font_variant(FontVariant::Italic);
+		let textbox_width = self.x_max() - self.x_min();
+		let lines = self.get_textbox_lines(&text, textbox_width, textbox_width);
+		// Put the last line at the bottom of the page
+		self.y = self.y_bottom() + self.current_newline_amount() * (lines.len() - 1) as f32;
+		self.apply_centered_text_lines(&lines, self.x_min(), self.x_max());
+	}
+
+	/// Makes the pages for the table of contents right after the title page (if a table of contents is desired) and
+	/// figures out where each line of it will go.
+	/// Returns the lines of the table of contents so they can be applied once the page each spell starts on is known.
+	fn reserve_table_of_contents(&mut self, spells: &Vec<spells::Spell>, entries: &Vec<SpellbookEntry>)
+	-> Vec<TableOfContentsLine>
+	{
+		// If there is no table of contents, do nothing
+		let options = match &self.table_of_contents
+		{
+			Some(options) => options.clone(),
+			None => return Vec::new()
+		};
+		let table_of_contents_entries = get_table_of_contents_entries(spells, entries, options.order);
+		// Use the fewest columns that fit the table of contents on one page (or as many as are allowed if it doesn't
+		// fit on one page)
+		let mut columns = None;
+		let mut table_of_contents_lines = self.table_of_contents_lines(&options.title, &table_of_contents_entries, None);
+		for column_count in 2..=options.max_columns
+		{
+			if table_of_contents_lines.last().map_or(0, |line| line.page_offset) == 0 { break; }
+			columns = Some(self.table_of_contents_columns(column_count));
+			table_of_contents_lines = self.table_of_contents_lines(&options.title, &table_of_contents_entries, columns);
+		}
+		self.table_of_contents_columns = columns;
+		// Make the pages for the table of contents
+		for page in 0..=table_of_contents_lines.last().map_or(0, |line| line.page_offset)
+		{
+			self.make_new_page();
+			// Bookmark the first page and remember where it is
+			if page == 0
+			{
+				self.table_of_contents_page_index = self.current_page_index;
+				self.doc.add_bookmark(options.title.clone(), self.pages[self.current_page_index]);
+				self.add_reference_anchor(&options.title);
+			}
+		}
+		table_of_contents_lines
+	}
+
+	/// Returns the columns that the entries of the table of contents get split into with a space the size of two tabs
+	/// between them.
+	fn table_of_contents_columns(&mut self, column_count: usize) -> ColumnLayout
+	{
+		self.set_current_text_type(TextType::Body);
+		let column_gap = self.tab_amount() * 2.0;
+		let column_width = (self.x_max() - self.x_min() - column_gap * (column_count - 1) as f32) / column_count as f32;
+		ColumnLayout
+		{
+			column_count: column_count,
+			column_width: column_width,
+			column_gap: column_gap,
+			current_column: 0,
+			top_y: None,
+			balanced_y_min: None
+		}
+	}
+
+	/// Figures out where each line of the table of contents goes with its entries split into the given columns (or
+	/// across the whole page if there aren't any).
+	fn table_of_contents_lines
+	(
+		&mut self,
+		title: &str,
+		table_of_contents_entries: &[(String, Option<usize>, Option<usize>)],
+		columns: Option<ColumnLayout>
+	)
+	-> Vec<TableOfContentsLine>
+	{
+		// Holds each line of the table of contents along with where it goes
+		let mut table_of_contents_lines = Vec::with_capacity(table_of_contents_entries.len() + 1);
+		// Keeps track of which page and column of the table of contents the lines are going on
+		let mut page_offset = 0;
+		let mut column = 0;
+		let textbox_width = self.x_max() - self.x_min();
+		// Split the heading into lines that fit on the page
+		self.set_current_text_type(TextType::Header);
+		self.set_current_font_variant(FontVariant::Regular);
+		let heading_lines = self.get_textbox_lines(title, textbox_width, textbox_width);
+		// Keeps track of where the next line goes
+		let mut y = self.y_top();
+		// Figure out where each line of the heading goes
+		for line in heading_lines
+		{
+			// Go to the next page if this line would be too low
+			if y < self.y_min() { page_offset += 1; y = self.y_top(); }
+			table_of_contents_lines.push(TableOfContentsLine
+			{
+				line: line,
+				text_type: TextType::Header,
+				centered: true,
+				page_offset: page_offset,
+				column: 0,
+				y: y,
+				spell_index: None,
+				chapter_index: None,
+				is_entry_start: false,
+				is_entry_end: false
+			});
+			y -= self.current_newline_amount();
+		}
+		// Each entry leaves space on the right side for page numbers (and on the left side for checkboxes if there are
+		// any)
+		self.set_current_text_type(TextType::Body);
+		let column_count = columns.map_or(1, |columns| columns.column_count);
+		let column_width = columns.map_or(textbox_width, |columns| columns.column_width);
+		let entry_width = column_width - self.calc_text_width(TABLE_OF_CONTENTS_NUMBER_SPACE) - self.tab_amount()
+		- self.table_of_contents_checkbox_width();
+		// The columns start under the heading on the first page and at the top of the other pages
+		let mut column_top = y;
+		// Figure out where each line of each entry goes
+		for (entry_index, (text, spell_index, chapter_index)) in table_of_contents_entries.iter().enumerate()
+		{
+			// Leave a line before each level heading (unless it's right after a chapter or the top of the table)
+			let is_level_heading = spell_index.is_none() && chapter_index.is_none();
+			if is_level_heading && entry_index > 0 && table_of_contents_entries[entry_index - 1].1.is_some()
+			{
+				y -= self.current_newline_amount();
+			}
+			let lines = self.get_textbox_lines(text, entry_width, entry_width);
+			let line_count = lines.len();
+			for (line_index, line) in lines.into_iter().enumerate()
+			{
+				// Go to the next column (or page) if this line would be too low
+				if y < self.y_min()
+				{
+					column += 1;
+					if column == column_count
+					{
+						column = 0;
+						page_offset += 1;
+						column_top = self.y_top();
+					}
+					y = column_top;
+				}
+				table_of_contents_lines.push(TableOfContentsLine
+				{
+					line: line,
+					text_type: TextType::Body,
+					centered: false,
+					page_offset: page_offset,
+					column: column,
+					y: y,
+					spell_index: *spell_index,
+					chapter_index: *chapter_index,
+					is_entry_start: line_index == 0 && spell_index.is_some(),
+					is_entry_end: line_index == line_count - 1
+				});
+				y -= self.current_newline_amount();
+			}
+		}
+		table_of_contents_lines
+	}
+
+	/// Applies the lines of the table of contents to the pages made for it and makes each entry link to the page its
+	/// spell starts on.
+	fn apply_table_of_contents(&mut self, table_of_contents_lines: &Vec<TableOfContentsLine>)
+	{
+		// If there is no table of contents, do nothing
+		if table_of_contents_lines.is_empty() { return; }
+		let starting_columns = self.columns;
+		for table_of_contents_line in table_of_contents_lines
+		{
+			// Go to where this line goes
+			self.current_page_index = self.table_of_contents_page_index + table_of_contents_line.page_offset;
+			self.y = table_of_contents_line.y;
+			// The heading goes across the whole page and entries go in their columns (if there are any)
+			self.columns = match self.table_of_contents_columns
+			{
+				Some(columns) if !table_of_contents_line.centered =>
+					Some(ColumnLayout { current_column: table_of_contents_line.column, ..columns }),
+				_ => None
+			};
+			let x_max = match self.columns
+			{
+				Some(columns) => self.x_min() + columns.column_width,
+				None => self.x_max()
+			};
+			self.set_current_text_type(table_of_contents_line.text_type);
+			self.set_current_font_variant(FontVariant::Regular);
+			// Apply the line
+			if table_of_contents_line.centered
+			{
+				self.apply_centered_text_lines(&vec![table_of_contents_line.line.clone()], self.x_min(), self.x_max());
+			}
+			else
+			{
+				self.x = self.x_min();
+				// Put a checkbox at the start of each entry (if there are checkboxes in the table of contents)
+				if table_of_contents_line.is_entry_start
+				{
+					self.apply_table_of_contents_checkbox(table_of_contents_line.spell_index);
+				}
+				else { self.x += self.table_of_contents_checkbox_width(); }
+				self.apply_text_line(&table_of_contents_line.line);
+			}
+			// The page that this line's entry goes to (if it's part of a spell's or chapter's entry)
+			let target = match (table_of_contents_line.spell_index, table_of_contents_line.chapter_index)
+			{
+				(Some(spell_index), _) => Some(self.spell_pages[spell_index].clone()),
+				(None, Some(chapter_index)) => Some(self.chapter_pages[chapter_index].clone()),
+				(None, None) => None
+			};
+			if let Some((target_page_index, page_num_text)) = target
+			{
+				// Put the page number on the right side of the last line of the entry
+				if table_of_contents_line.is_entry_end
+				{
+					self.set_current_font_variant(FontVariant::Regular);
+					self.x = x_max - self.calc_text_width(&page_num_text);
+					self.apply_text(&page_num_text);
+				}
+				// Make the whole line link to the first page of the spell
+				let (y_min, y_max) = self.current_line_y_bounds();
+				self.internal_links.push(InternalLink
+				{
+					page_index: self.current_page_index,
+					x_min: self.x_min() + self.column_x_offset(),
+					y_min: y_min,
+					x_max: x_max + self.column_x_offset(),
+					y_max: y_max,
+					target_page_index: target_page_index
+				});
+			}
+		}
+		self.columns = starting_columns;
+		// Go back to the last page so anything added afterwards goes at the end
+		self.current_page_index = self.layers.len() - 1;
+	}
+
+	/// Starts a class chapter with a page that has the chapter's title on it. Page numbers start over in each chapter
+	/// with the chapter's prefix in front of them.
+	fn start_chapter(&mut self, chapter: &ClassChapter)
+	{
+		self.page_number_prefix = chapter.page_number_prefix.clone();
+		self.current_page_num = self.starting_page_num;
+		// Make a new page for the chapter (with the chapter page background if there is one) and bookmark it
+		self.page_kind = PageKind::ChapterPage;
+		self.make_new_page();
+		self.page_kind = PageKind::Body;
+		self.doc.add_bookmark(chapter.title.clone(), self.pages[self.current_page_index]);
+		self.chapter_pages.push((self.current_page_index, self.page_number_text(self.current_page_num - 1)));
+		// Write the title of the chapter in the middle of the page
+		self.set_current_text_type(TextType::Header);
+		self.set_current_font_variant(FontVariant::Regular);
+		self.write_centered_textbox(&chapter.title, self.x_min(), self.x_max(), self.y_bottom(), self.y_top());
+	}
+
+	/// Adds a page / pages that list the spells of a class chapter grouped by level, with each spell linking to its
+	/// page and having its page number next to it.
+	fn add_class_list(&mut self, spells: &Vec<spells::Spell>, chapter: &ClassChapter)
+	{
+		// The index of each spell the class has (which is also the index of its page in `spell_pages` since spells are
+		// only added once when there are class lists)
+		let spell_indexes = get_class_spell_indexes(spells, &chapter.class);
+		// Make a new page for the list and bookmark it
+		self.make_new_page();
+		self.doc.add_bookmark(chapter.title.clone(), self.pages[self.current_page_index]);
+		self.chapter_pages.push((self.current_page_index, self.page_number_text(self.current_page_num - 1)));
+		// Write the heading centered at the top of the page
+		self.set_current_text_type(TextType::Header);
+		self.set_current_font_variant(FontVariant::Regular);
+		self.y = self.y_top();
+		let textbox_width = self.x_max() - self.x_min();
+		let heading_lines = self.get_textbox_lines(&chapter.title, textbox_width, textbox_width);
+		self.apply_centered_text_lines(&heading_lines, self.x_min(), self.x_max());
+		self.y -= self.font_data.get_newline_amount_for(TextType::Header);
+		// Spell names leave room for page numbers on the right side
+		self.set_current_text_type(TextType::Body);
+		let entry_width = textbox_width - self.calc_text_width(TABLE_OF_CONTENTS_NUMBER_SPACE)
+		- self.tab_amount() * 2.0;
+		let mut current_level = None;
+		for index in spell_indexes
+		{
+			let spell = &spells[index];
+			// Put a heading before the first spell of each level
+			let (level, _) = spell.level_sort_key();
+			if current_level != Some(level)
+			{
+				// Leave a line between each level
+				if current_level.is_some() { self.y -= self.current_newline_amount(); }
+				current_level = Some(level);
+				self.check_for_new_page();
+				self.x = self.x_min();
+				self.set_current_font_variant(FontVariant::Bold);
+				self.apply_text(&level_heading(spell));
+				self.y -= self.current_newline_amount();
+			}
+			// Write the spell name (indented under the level heading) with its page number after it
+			self.set_current_font_variant(FontVariant::Regular);
+			let (target_page_index, page_num_text) = self.spell_pages[index].clone();
+			let lines = self.get_textbox_lines(&spell.name, entry_width, entry_width);
+			let line_count = lines.len();
+			for (line_index, line) in lines.into_iter().enumerate()
+			{
+				self.check_for_new_page();
+				self.x = self.x_min() + self.tab_amount();
+				self.apply_text_line(&line);
+				if line_index == line_count - 1
+				{
+					self.set_current_font_variant(FontVariant::Regular);
+					self.x = self.x_max() - self.calc_text_width(&page_num_text);
+					self.apply_text(&page_num_text);
+				}
+				// Make the whole line link to the first page of the spell
+				let (y_min, y_max) = self.current_line_y_bounds();
+				self.internal_links.push(InternalLink
+				{
+					page_index: self.current_page_index,
+					x_min: self.x_min(),
+					y_min: y_min,
+					x_max: self.x_max(),
+					y_max: y_max,
+					target_page_index: target_page_index
+				});
+				self.y -= self.current_newline_amount();
+			}
+		}
+	}
+
+	/// Adds the stats of a spell (and optionally the first sentence of its description) to a compact list of spells
+	/// split into columns. Starts a new list on a new page if there isn't one on the current page already.
+	/// `remaining_lines` is the number of lines in this spell and the ones after it in the list, which is used to
+	/// balance the columns on the last page of the list.
+	#[cfg_attr(feature = "tracing", tracing::instrument(skip_all, fields(spell = %spell.name)))]
+	fn add_compact_spell
+	(
+		&mut self,
+		spell: &spells::Spell,
+		bookmark_name: String,
+		options: CompactListOptions,
+		remaining_lines: usize
+	)
+	{
+		let textbox_width = self.x_max() - self.x_min();
+		self.set_current_text_type(TextType::Body);
+		// Split the page into columns with a space the size of two tabs between them if the list is just starting
+		let column_count = options.column_count.max(1);
+		let column_gap = self.tab_amount() * 2.0;
+		let column_width = (textbox_width - column_gap * (column_count - 1) as f32) / column_count as f32;
+		if self.columns.is_none()
+		{
+			self.make_new_page();
+			self.columns = Some(ColumnLayout
+			{
+				column_count: column_count,
+				column_width: column_width,
+				column_gap: column_gap,
+				current_column: 0,
+				top_y: None,
+				balanced_y_min: None
+			});
+			self.add_debug_columns();
+			self.y = self.y_top();
+		}
+		// Leave a line between each spell
+		else { self.y -= self.current_newline_amount(); }
+		self.balance_columns(remaining_lines);
+		// Move to the next column before bookmarking the spell if it won't fit in this one
+		self.check_for_new_page();
+		self.doc.add_bookmark(bookmark_name, self.pages[self.current_page_index]);
+		self.add_reference_anchor(&spell.name);
+		self.spell_pages.push((self.current_page_index, self.page_number_text(self.current_page_num - 1)));
+		let x_max = self.x_min() + column_width;
+		// Write the spell name in bold (with a checkbox before it if there are checkboxes)
+		self.x = self.x_min();
+		self.set_current_font_variant(FontVariant::Bold);
+		if let Some(checkbox_options) = self.preparation_checkboxes
+		{
+			let field_name = self.preparation_field_name(self.spell_pages.len() - 1);
+			self.apply_checkbox(Pt(checkbox_options.line_thickness), field_name);
+		}
+		self.write_textbox(&spell.name, self.x, x_max, self.y_bottom(), self.y_top(), false, &spell.tables);
+		// Write the level and school in italics followed by each stat with its name in bold
+		for (font_variant, line) in self.compact_spell_stats(spell, options)
+		{
+			if line.is_empty() { continue; }
+			self.y -= self.current_newline_amount();
+			self.x = self.x_min();
+			self.set_current_font_variant(font_variant);
+			self.write_textbox(&line, self.x_min(), x_max, self.y_bottom(), self.y_top(), false, &spell.tables);
+		}
+		// List the spell's source in the references section (if there is one)
+		self.add_endnote_spell(spell, self.footnote_texts.len());
+	}
+
+	/// The level and school of a spell followed by each of its stats (and its summary if it's included) with the font
+	/// variant each one starts in, the way they're written in a compact list.
+	fn compact_spell_stats(&self, spell: &spells::Spell, options: CompactListOptions) -> Vec<(FontVariant, String)>
+	{
+		let range = spell.get_range_text(self.distance_conversion);
+		let components = spell.get_component_text(self.component_style);
+		let mut lines = vec!
+		[
+			(FontVariant::Italic, spell.get_level_school_text()),
+			(FontVariant::Bold, format!("Casting Time: {} {}", REGULAR_FONT_TAG, spell.get_casting_time_text())),
+			(FontVariant::Bold, format!("Range: {} {}", REGULAR_FONT_TAG, range)),
+			(FontVariant::Bold, format!("Components: {} {}", REGULAR_FONT_TAG, components)),
+			(FontVariant::Bold, format!("Duration: {} {}", REGULAR_FONT_TAG, spell.duration))
+		];
+		if options.include_summary
+		{
+			lines.push((FontVariant::Regular, spells::redact_secrets(&spell.summary(), self.redaction)));
+		}
+		lines
+	}
+
+	/// Returns the number of lines that each spell entry takes up in a compact list plus the lines of the spells after
+	/// it in the same list. Lists end at chapters, and spells that are left out of
+	/// previews don't take up any lines. Entries that aren't spells have 0 lines.
+	fn compact_remaining_lines
+	(
+		&mut self,
+		spells: &Vec<spells::Spell>,
+		entries: &[SpellbookEntry],
+		preview: &PreviewOptions,
+		options: CompactListOptions
+	)
+	-> Vec<usize>
+	{
+		let textbox_width = self.x_max() - self.x_min();
+		let column_count = options.column_count.max(1);
+		let column_gap = self.tab_amount() * 2.0;
+		let column_width = (textbox_width - column_gap * (column_count - 1) as f32) / column_count as f32;
+		let starting_text_type = *self.current_text_type();
+		let starting_font_variant = *self.current_font_variant();
+		self.set_current_text_type(TextType::Body);
+		let mut remaining_lines = vec![0; entries.len()];
+		let mut lines_after = 0;
+		for (index, entry) in entries.iter().enumerate().rev()
+		{
+			let spell = match entry
+			{
+				SpellbookEntry::Spell(spell_index) if preview.includes_spell(*spell_index) => &spells[*spell_index],
+				SpellbookEntry::Spell(_) => continue,
+				// Spells before a chapter are the end of their list
+				SpellbookEntry::Chapter(_) =>
+				{
+					lines_after = 0;
+					continue;
+				}
+			};
+			self.set_current_font_variant(FontVariant::Bold);
+			let mut line_count = self.get_textbox_lines(&spell.name, column_width, column_width).len();
+			for (font_variant, line) in self.compact_spell_stats(spell, options)
+			{
+				if line.is_empty() { continue; }
+				self.set_current_font_variant(font_variant);
+				line_count += self.get_textbox_lines(&line, column_width, column_width).len();
+			}
+			lines_after += line_count;
+			remaining_lines[index] = lines_after;
+		}
+		self.set_current_text_type(starting_text_type);
+		self.set_current_font_variant(starting_font_variant);
+		remaining_lines
+	}
+
+	/// Draws a rule that tapers from its thickness on the left to a point on the right below the current line of text
+	/// (in the space between it and the next line).
+	fn apply_name_rule(&mut self, options: NameRuleOptions)
+	{
+		let x_min = self.x_min() + self.column_x_offset();
+		let x_max = x_min + (self.x_max() - self.x_min()) * options.length.clamp(0.0, 1.0);
+		// Below the descenders of the current line but above the capital letters of the next one
+		let y = self.y - self.current_newline_amount() * 0.35;
+		let half_thickness = Mm(options.thickness) / 2.0;
+		let rule = Polygon
+		{
+			rings: vec![vec!
+			[
+				(Point::new(x_min, y + half_thickness), false),
+				(Point::new(x_max, y), false),
+				(Point::new(x_min, y - half_thickness), false)
+			]],
+			mode: PaintMode::Fill,
+			winding_order: WindingOrder::NonZero
+		};
+		let layer = self.current_layer();
+		layer.save_graphics_state();
+		layer.set_fill_color(bytes_to_color(&options.color));
+		layer.add_polygon(rule);
+		layer.restore_graphics_state();
+	}
+
+	/// The right side of the space a spell's name gets written in, which is before the blank border around the spell's
+	/// QR code if it has one.
+	fn spell_name_x_max(&self, spell: &spells::Spell) -> Mm
+	{
+		if let Some(qr_codes) = &self.qr_codes
+		{
+			if let Some((_, code)) = qr_codes.spell_code(spell)
+			{
+				let size = Mm(qr_codes.options().spell_code_size);
+				// Leave the blank border that QR codes need to be scanned between the spell name and the code
+				return self.x_max() - size - size / code.size() as f32 * QR_CODE_QUIET_ZONE;
+			}
+		}
+		self.x_max()
+	}
+
+	/// Draws a bar behind the lines that a spell name will take up starting at the current position. The bar stops
+	/// at `name_x_max` if it's before the right side of the text area.
+	fn apply_name_bar(&mut self, name: &str, name_x_max: Mm, options: NameBarOptions)
+	{
+		// Get the lines the name wraps into (after the preparation checkbox if there is one)
+		let name_x_min = match self.preparation_checkboxes
+		{
+			Some(_) => self.x + self.checkbox_width(),
+			None => self.x
+		};
+		let line_count = self.get_textbox_lines(name, name_x_max - name_x_min, name_x_max - name_x_min).len().max(1);
+		let padding = Mm(options.padding);
+		let x_offset = self.column_x_offset();
+		let x_min = self.x_min() + x_offset - padding;
+		let x_max = if name_x_max < self.x_max() { name_x_max } else { name_x_max + padding } + x_offset;
+		// From the top of the first line of the name to the bottom of its last line
+		let (y_min, y_max) = self.current_line_y_bounds();
+		let y_min = y_min - self.current_newline_amount() * (line_count - 1) as f32;
+		let bounds = (x_min, y_min - padding, x_max, y_max + padding);
+		self.apply_gradient_rect(bounds, options.color, options.gradient_color, false);
+	}
+
+	/// Adds a page / pages about a spell into the spellbook.
+	#[cfg_attr(feature = "tracing", tracing::instrument(skip_all, fields(spell = %spell.name)))]
+	fn add_spell(&mut self, spell: &spells::Spell, bookmark_name: String)
+	{
+		// Add the table of damage by character level to the spell (if it gets one)
+		let spell = &*self.with_cantrip_scaling_table(spell);
+		// Get the spell description with the upcast description in it (if it goes there) and the upcast prefix
+		let (description, upcast_prefix) = self.spell_description(spell);
+		// Take the footnotes out of the description and leave their numbers behind
+		let first_footnote_id = self.footnote_texts.len();
+		let description = self.take_footnotes(&description);
+		if self.footnotes.numbering == FootnoteNumbering::PerSpell { self.footnote_count = 0; }
+
+		// Get the space after the spell name and around the spell fields (tighter if it makes the spell shorter)
+		let (name_spacing, field_spacing) = self.spell_spacing(spell, &description, &upcast_prefix);
+
+		// Use the spell's own page break settings where it has them
+		let hints = spell.layout.unwrap_or_default();
+		let starts_new_page = hints.start_on_new_page.unwrap_or(self.spell_layout.start_on_new_page);
+		self.allow_table_split = hints.allow_table_split.unwrap_or(self.spell_layout.allow_table_split);
+
+		// Make a new page for the spell (unless it goes right after the last spell and there's room for it there)
+		let continues_page = !starts_new_page && self.has_room_for_spell();
+		if !continues_page { self.make_new_page(); }
+		let first_page_index = self.current_page_index;
+		// Add a bookmark for the first page of this spell
+		self.doc.add_bookmark(bookmark_name, self.pages[self.current_page_index]);
+		self.add_reference_anchor(&spell.name);
+		// Keep track of where this spell starts for the table of contents
+		// (the page number was already increased for the next page in `make_new_page`)
+		self.spell_pages.push((self.current_page_index, self.page_number_text(self.current_page_num - 1)));
+		// Give the rest of the spell's pages footers about it (if there are continuation footers)
+		if self.continuation_footer.is_some()
+		{
+			self.continued_spell = Some((spell.level.to_string(), spell.requires_concentration(), spell.is_ritual));
+		}
+
+		// Writes the spell name to the document
+		self.set_current_text_type(TextType::Header);
+		self.set_current_font_variant(FontVariant::Regular);
+		self.x = self.x_min();
+		// Leave the space of a couple of spell name lines between this spell and the last one if they share a page
+		self.y = if continues_page { self.y - self.current_newline_amount() * 2.0 } else { self.y_top() };
+		// Wrap the spell name before the QR code (if the spell has one)
+		let name_x_max = self.spell_name_x_max(spell);
+		// Draw a bar behind the spell name (if there is one) before the things that go on top of it
+		if let Some(options) = self.name_bar { self.apply_name_bar(&spell.name, name_x_max, options); }
+		// Put a checkbox before the spell name (if there are checkboxes) and line the spell name up after it
+		if let Some(options) = self.preparation_checkboxes
+		{
+			let field_name = self.preparation_field_name(self.spell_pages.len() - 1);
+			self.apply_checkbox(Pt(options.line_thickness), field_name);
+		}
+		// Put a QR code in the top right corner of the page (if spells have QR codes)
+		if let Some(qr_codes) = &self.qr_codes
+		{
+			if let Some((url, code)) = qr_codes.spell_code(spell)
+			{
+				let size = Mm(qr_codes.options().spell_code_size);
+				// Line the code up with the spell name (which is lower than the top of the page if the spell starts
+				// under another spell)
+				let y_max = self.y_max() - (self.y_top() - self.y);
+				self.apply_qr_code(&url, code, self.x_max() - size, y_max, size, qr_codes.options().color);
+			}
+		}
+		// Use the color of the spell's school for its name (if spell names are colored by school)
+		let school_color = match (&self.school_colors, &spell.school)
+		{
+			(Some(options), spells::SpellField::Controlled(school)) => options.name_colors.get(school).copied(),
+			_ => None
+		};
+		let header_color = self.font_data.header_color().clone();
+		// Switch to a color that shows up on the name bar (if there is one)
+		let name_color = match self.name_bar
+		{
+			Some(options) => Some(options.name_color(school_color.unwrap_or_else(|| color_to_bytes(&header_color)))),
+			None => school_color
+		};
+		if let Some(color) = name_color { self.font_data.set_header_color(bytes_to_color(&color)); }
+		self.write_textbox
+		(&spell.name, self.x, name_x_max, self.y_bottom(), self.y_top(), false, &spell.tables);
+		self.font_data.set_header_color(header_color);
+
+		// Draws a rule under the spell name (if there is one)
+		if let Some(options) = self.name_rule { self.apply_name_rule(options); }
+
+		// Writes the level and school of the spell to the document
+		self.y -= name_spacing;
+		self.x = self.x_min();
+		self.set_current_text_type(TextType::Body);
+		// Put the spell's rarity in a tag on the right side of the line (if it has one) and wrap the line before it
+		let tag_width = match spell.rarity
+		{
+			Some(rarity) => self.apply_rarity_tag(rarity) + self.tab_amount(),
+			None => Mm(0.0)
+		};
+		self.set_current_font_variant(FontVariant::Italic);
+		self.write_textbox
+		(
+			&spell.get_level_school_text(),
+			self.x_min(),
+			self.x_max() - tag_width,
+			self.y_bottom(),
+			self.y_top(),
+			false,
+			&spell.tables
+		);
+		// Writes the classes of the spell in chips under the level and school (if there are class chips)
+		if let Some(options) = self.class_chips.clone()
+		{
+			if !spell.classes.is_empty()
+			{
+				self.y -= self.current_newline_amount();
+				self.write_class_chips(&spell.classes, &options);
+			}
+		}
+
+		// Writes the casting time, range, components, and duration to the document (in a grid if there is one)
+		self.y -= field_spacing;
+		if let Some(options) = self.quick_stats { self.write_quick_stats(spell, options); }
+		else
+		{
+			// Writes the casting time to the document
+			let [casting_time, range, components, duration] =
+			spell_field_texts(spell, self.distance_conversion, self.component_style);
+			self.x = self.x_min();
+			self.set_current_font_variant(FontVariant::Bold);
+			self.write_textbox
+			(&casting_time, self.x_min(), self.x_max(), self.y_bottom(), self.y_top(), false, &spell.tables);
+
+			// Writes the range to the document
+			self.y -= self.font_data.current_newline_amount();
+			self.x = self.x_min();
+			self.set_current_font_variant(FontVariant::Bold);
+			self.write_textbox
+			(&range, self.x_min(), self.x_max(), self.y_bottom(), self.y_top(), false, &spell.tables);
+			// Draws a diagram of the area of effect after the range (if the range has one and there are diagrams)
+			if let spells::SpellField::Controlled(spells::Range::Yourself(Some(aoe))) = &spell.range
+			{
+				self.apply_aoe_glyph(aoe);
+			}
+
+			// Writes the components to the document (as icons if components are shown as icons)
+			self.y -= self.font_data.current_newline_amount();
+			self.x = self.x_min();
+			self.set_current_font_variant(FontVariant::Bold);
+			if self.component_style == spells::ComponentStyle::Icons { self.write_component_icons(spell); }
+			else
+			{
+				self.write_textbox
+				(&components, self.x_min(), self.x_max(), self.y_bottom(), self.y_top(), false, &spell.tables);
+			}
+
+			// Writes the duration to the document
+			self.y -= self.font_data.current_newline_amount();
+			self.x = self.x_min();
+			self.set_current_font_variant(FontVariant::Bold);
+			self.write_textbox
+			(&duration, self.x_min(), self.x_max(), self.y_bottom(), self.y_top(), false, &spell.tables);
+		}
+
+		// Writes the flavor quote between the fields and the description (if the spell has one)
+		if let Some((quote, attribution)) = spell.flavor_quote()
+		{
+			self.y -= field_spacing;
+			self.write_flavor_quote(quote, attribution, &spell.tables);
+		}
+
+		// Writes the description to the document (with glossary terms in it linking to the glossary)
+		self.y -= field_spacing;
+		self.x = self.x_min();
+		self.set_current_font_variant(FontVariant::Regular);
+		self.in_description = true;
+		self.write_textbox
+		(&description, self.x_min(), self.x_max(), self.y_bottom(), self.y_top(), false, &spell.tables);
+		self.in_description = false;
+
+		// Writes the upcast description in its own block with the prefix as its heading (if it goes there)
+		if let (Some(upcast_description), UpcastPlacement::SeparateBlock) =
+		(&spell.upcast_description, self.upcast.placement)
+		{
+			self.y -= self.font_data.get_newline_amount_for(TextType::Header);
+			self.x = self.x_min();
+			self.set_current_font_variant(FontVariant::BoldItalic);
+			self.write_textbox
+			(&upcast_prefix, self.x_min(), self.x_max(), self.y_bottom(), self.y_top(), false, &spell.tables);
+			self.y -= self.current_newline_amount();
+			self.x = self.x_min();
+			self.set_current_font_variant(FontVariant::Regular);
+			self.in_description = true;
+			let upcast_description = spells::redact_secrets(upcast_description, self.redaction);
+			let upcast_description = self.take_footnotes(&upcast_description);
+			self.write_textbox
+			(&upcast_description, self.x_min(), self.x_max(), self.y_bottom(), self.y_top(), false, &spell.tables);
+			self.in_description = false;
+		}
+
+		// Pages after this don't continue the spell
+		self.continued_spell = None;
+		// Leave space for notes after the spell (if notes areas are desired)
+		self.add_notes_area();
+		// List the spell in the references section (if there is one)
+		self.add_endnote_spell(spell, first_footnote_id);
+
+		// Fill the empty space at the bottom of the page (if there's enough of it and page fill is desired)
+		let filled_page_count = self.filled_page_count;
+		self.fill_empty_space();
+		// The next spell can start on the page this spell ended on (unless its empty space got filled)
+		self.last_spell_end =
+		if self.filled_page_count == filled_page_count { Some(self.current_page_index) } else { None };
+		self.allow_table_split = self.spell_layout.allow_table_split;
+
+		// Remember how many pages the spell took up (if there's a layout cache)
+		if let Some(cache) = &self.layout_cache
+		{
+			let key = layout_cache::spell_layout_key(spell, self.layout_options_hash);
+			let page_count = self.current_page_index + 1 - first_page_index;
+			cache.update(key, |layout| layout.page_count = Some(page_count));
+		}
+	}
+
+	/// Takes the footnotes out of some spell text so they can go at the bottom of the pages their numbers end up on.
+	/// Returns the text with the id of each footnote in superscript where it was.
+	fn take_footnotes(&mut self, text: &str) -> String
+	{
+		let (text, footnote_texts) = spells::extract_footnotes(text, self.footnote_texts.len());
+		self.footnote_texts.extend(footnote_texts);
+		text
+	}
+
+	/// Saves space at the bottom of the current page (or column) for the footnotes whose numbers are in a line that's
+	/// about to be written and gives them their numbers. Moves to a new page first if the line and its footnotes
+	/// don't both fit on this one.
+	fn reserve_footnotes(&mut self, line: &TextLine)
+	{
+		let ids: Vec<usize> = line.tokens().iter()
+			.flat_map(|token| superscript_segments(token.as_spellbook_string()))
+			.filter_map(|(segment, is_superscript)| if is_superscript { segment.parse().ok() } else { None })
+			.filter(|id| *id < self.footnote_texts.len() && !self.footnote_numbers.contains_key(id))
+			.collect();
+		if ids.is_empty() { return; }
+		// Endnotes just need their numbers and the page they're on
+		if self.endnotes.is_some()
+		{
+			for id in ids
+			{
+				self.footnote_count += 1;
+				self.footnote_numbers.insert(id, self.footnote_count);
+				let page_num_text = self.page_number_text(self.current_page_num - 1);
+				self.endnote_pages.insert(id, (self.current_page_index, page_num_text));
+			}
+			return;
+		}
+		// Footnotes go on the same page as their numbers (unless they're too tall to fit on any page)
+		if self.y - self.footnotes_height(&ids) < self.y_min() && self.y < self.y_top() { self.move_to_new_page(); }
+		let height = self.footnotes_height(&ids);
+		for id in ids
+		{
+			// Footnote numbers start over on each page (if they're numbered by page)
+			let new_page = self.footnote_page_index != self.current_page_index;
+			if self.footnotes.numbering == FootnoteNumbering::PerPage && new_page
+			{
+				self.footnote_count = 0;
+			}
+			self.footnote_page_index = self.current_page_index;
+			self.footnote_count += 1;
+			self.footnote_numbers.insert(id, self.footnote_count);
+			self.page_footnotes.push(id);
+		}
+		self.footnote_area_height += height;
+	}
+
+	/// The height that some footnotes add to the footnotes at the bottom of the current page (or column).
+	fn footnotes_height(&mut self, ids: &[usize]) -> Mm
+	{
+		let newline_amount = self.font_data.get_newline_amount_for(TextType::Body);
+		let line_count: usize = ids.iter().map(|id| self.footnote_lines(*id).len()).sum();
+		// The first footnotes on a page also need a blank line between them and the rest of the page
+		let gap = if self.page_footnotes.is_empty() { newline_amount } else { Mm(0.0) };
+		newline_amount * line_count as f32 + gap
+	}
+
+	/// The lines of a footnote (starting with its number) in the width of the current page (or column).
+	fn footnote_lines(&mut self, id: usize) -> Vec<TextLine>
+	{
+		let starting_text_type = *self.current_text_type();
+		let starting_font_variant = *self.current_font_variant();
+		self.set_current_text_type(TextType::Body);
+		self.set_current_font_variant(FontVariant::Regular);
+		let text = format!("{}{}{} {}", SUPERSCRIPT_START, id, SUPERSCRIPT_END, self.footnote_texts[id]);
+		let width = self.x_max() - self.x_min();
+		let lines: Vec<TextLine> = self.get_textbox_lines(&text, width, width).into_iter()
+			.filter(|line| !line.is_empty())
+			.collect();
+		self.set_current_text_type(starting_text_type);
+		self.set_current_font_variant(starting_font_variant);
+		lines
+	}
+
+	/// Writes the footnotes of the current page (or column) in the space saved for them at the bottom of it.
+	fn apply_footnotes(&mut self)
+	{
+		if self.page_footnotes.is_empty() { return; }
+		let ids = std::mem::take(&mut self.page_footnotes);
+		let area_height = std::mem::replace(&mut self.footnote_area_height, Mm(0.0));
+		// Keep the footnotes from changing where the text that comes after them goes or what it looks like
+		let (x, y) = (self.x, self.y);
+		let starting_text_type = *self.current_text_type();
+		let starting_font_variant = *self.current_font_variant();
+		let active_link = self.active_link.take();
+		let color_override = self.color_override.take();
+		self.set_current_text_type(TextType::Body);
+		let newline_amount = self.current_newline_amount();
+		// The first footnote goes under the blank line at the top of the space saved for the footnotes
+		self.y = self.y_min() + area_height - newline_amount * 2.0;
+		if self.footnotes.separator_rule
+		{
+			// Between the descenders of the text above and the capital letters of the first footnote
+			let rule_y = self.y + newline_amount * 1.25;
+			let x_min = self.x_min() + self.column_x_offset();
+			let x_max = x_min + (self.x_max() - self.x_min()) * FOOTNOTE_RULE_LENGTH;
+			let points = vec![(Point::new(x_min, rule_y), false), (Point::new(x_max, rule_y), false)];
+			let layer = self.current_layer();
+			layer.save_graphics_state();
+			layer.set_outline_color(self.current_text_color().clone());
+			layer.set_outline_thickness(FOOTNOTE_RULE_THICKNESS.0);
+			layer.add_line(Line { points: points, is_closed: false });
+			layer.restore_graphics_state();
+		}
+		for id in ids
+		{
+			let lines = self.footnote_lines(id);
+			self.set_current_font_variant(FontVariant::Regular);
+			self.x = self.x_min();
+			self.apply_text_lines(&lines, self.x_min(), false, None);
+			self.y -= newline_amount;
+		}
+		self.x = x;
+		self.y = y;
+		self.set_current_text_type(starting_text_type);
+		self.set_current_font_variant(starting_font_variant);
+		self.active_link = active_link;
+		self.color_override = color_override;
+	}
+
+	/// Remembers the spell that was just added so it gets listed in the references section with its source and
+	/// endnotes (the footnotes from an id on) if there is a references section.
+	fn add_endnote_spell(&mut self, spell: &spells::Spell, first_footnote_id: usize)
+	{
+		let cite_sources = match &self.endnotes
+		{
+			Some(options) => options.cite_sources,
+			None => return
+		};
+		let source = spell.source.clone().filter(|source| cite_sources && !source.is_empty());
+		let footnote_ids = first_footnote_id..self.footnote_texts.len();
+		// Spells with nothing to list get left out
+		if source.is_none() && footnote_ids.is_empty() { return; }
+		self.endnote_spells.push((spell.name.clone(), source, self.spell_pages.len() - 1, footnote_ids));
+	}
+
+	/// Adds a references section to the end of the spellbook with the sources and endnotes of each spell that has
+	/// been added since the last references section (if endnotes are desired).
+	fn add_endnotes(&mut self)
+	{
+		let options = match &self.endnotes
+		{
+			Some(options) if !self.endnote_spells.is_empty() => options.clone(),
+			_ => return
+		};
+		let endnote_spells = std::mem::take(&mut self.endnote_spells);
+		// Make a new page for the references and bookmark it
+		self.make_new_page();
+		self.doc.add_bookmark(options.title.clone(), self.pages[self.current_page_index]);
+		self.add_reference_anchor(&options.title);
+		// Write the heading centered at the top of the page
+		self.set_current_text_type(TextType::Header);
+		self.set_current_font_variant(FontVariant::Regular);
+		self.y = self.y_top();
+		let textbox_width = self.x_max() - self.x_min();
+		let heading_lines = self.get_textbox_lines(&options.title, textbox_width, textbox_width);
+		self.apply_centered_text_lines(&heading_lines, self.x_min(), self.x_max());
+		self.y -= self.font_data.get_newline_amount_for(TextType::Header);
+		// Entries leave room on the right side for the page numbers they link back to
+		self.set_current_text_type(TextType::Body);
+		let entry_width = textbox_width - self.calc_text_width(TABLE_OF_CONTENTS_NUMBER_SPACE) - self.tab_amount();
+		let mut first_line = true;
+		for (name, source, spell_page_index, footnote_ids) in endnote_spells
+		{
+			// Leave a line between each spell
+			if !first_line { self.y -= self.current_newline_amount(); }
+			// Write the spell name in bold with the page the spell starts on
+			self.set_current_font_variant(FontVariant::Bold);
+			let spell_page = self.spell_pages[spell_page_index].clone();
+			self.apply_endnote_entry(&name, entry_width, Some(spell_page), &mut first_line);
+			if let Some(source) = source
+			{
+				self.set_current_font_variant(FontVariant::Italic);
+				self.apply_endnote_entry(&format!("Source: {}", source), entry_width, None, &mut first_line);
+			}
+			for id in footnote_ids
+			{
+				// Endnotes whose numbers never got written don't go anywhere
+				let number_page = match self.endnote_pages.get(&id)
+				{
+					Some(number_page) => number_page.clone(),
+					None => continue
+				};
+				// Write each endnote after its number with the page its number is on
+				self.set_current_font_variant(FontVariant::Regular);
+				let text = format!("{}. {}", self.footnote_numbers[&id], self.footnote_texts[id]);
+				let page_index = self.apply_endnote_entry(&text, entry_width, Some(number_page), &mut first_line);
+				self.anchors.insert(endnote_anchor(id), page_index);
+			}
+		}
+	}
+
+	/// Writes an entry in the references section on the lines after the current one. Entries that link back to a page
+	/// have the number of that page on the right side of their last line. Returns the index of the page the entry
+	/// starts on.
+	fn apply_endnote_entry
+	(
+		&mut self,
+		text: &str,
+		entry_width: Mm,
+		target: Option<(usize, String)>,
+		first_line: &mut bool
+	)
+	-> usize
+	{
+		let lines: Vec<TextLine> = self.get_textbox_lines(text, entry_width, entry_width).into_iter()
+			.filter(|line| !line.is_empty())
+			.collect();
+		let mut first_page_index = None;
+		for (index, line) in lines.iter().enumerate()
+		{
+			// The first line of the section goes right under the heading
+			if !*first_line { self.y -= self.current_newline_amount(); }
+			*first_line = false;
+			self.x = self.x_min();
+			self.apply_text_line(line);
+			first_page_index.get_or_insert(self.current_page_index);
+			if let Some((target_page_index, page_num_text)) = &target
+			{
+				// Put the page number on the right side of the last line of the entry
+				if index == lines.len() - 1
+				{
+					self.set_current_font_variant(FontVariant::Regular);
+					self.x = self.x_max() - self.calc_text_width(page_num_text);
+					self.apply_text(page_num_text);
+				}
+				// Make the whole line link back to the page
+				let (y_min, y_max) = self.current_line_y_bounds();
+				self.internal_links.push(InternalLink
+				{
+					page_index: self.current_page_index,
+					x_min: self.x_min(),
+					y_min: y_min,
+					x_max: self.x_max(),
+					y_max: y_max,
+					target_page_index: *target_page_index
+				});
+			}
+		}
+		first_page_index.unwrap_or(self.current_page_index)
+	}
+
+	/// Writes the rarity of a spell in small capital letters with an outline around them on the right side of the
+	/// current line. Returns the width of the tag.
+	fn apply_rarity_tag(&mut self, rarity: spells::Rarity) -> Mm
+	{
+		let text = rarity.to_string().to_uppercase();
+		self.set_current_font_variant(FontVariant::Regular);
+		let text_width = self.calc_text_width(&text) * RARITY_TAG_SCALE;
+		let line_cap_height = self.calc_cap_height();
+		let cap_height = line_cap_height * RARITY_TAG_SCALE;
+		let padding = cap_height * RARITY_TAG_PADDING;
+		let x_max = self.x_max() + self.column_x_offset();
+		let x_min = x_max - text_width - padding * 2.0;
+		// Line the middle of the tag's letters up with the middle of the capital letters of the line
+		let baseline = self.y + (line_cap_height - cap_height) / 2.0;
+		let color = self.current_text_color().clone();
+		let layer = self.current_layer();
+		layer.save_graphics_state();
+		layer.set_outline_color(color.clone());
+		layer.set_outline_thickness(RARITY_TAG_LINE_THICKNESS.0);
+		layer.add_line(outline_rect(x_min, baseline - padding, x_max, baseline + cap_height + padding));
+		layer.restore_graphics_state();
+		layer.begin_text_section();
+		layer.set_text_cursor(x_min + padding, baseline);
+		layer.set_font(self.current_font_ref(), self.current_font_size().0 * RARITY_TAG_SCALE);
+		layer.set_fill_color(color);
+		layer.write_text(&text, self.current_font_ref());
+		layer.end_text_section();
+		// Add the rarity to the search index (if there is one)
+		if let Some((search_index, options)) = &mut self.search_index
+		{
+			search_index.add_text(&text, self.current_page_index, options);
+		}
+		x_max - x_min
+	}
+
+	/// Writes a chip with the abbreviation of each class in a row starting at the current y position, going onto more
+	/// rows if they don't fit on one.
+	fn write_class_chips(&mut self, classes: &[String], options: &ClassChipOptions)
+	{
+		let chips = self.class_chip_layout(classes, options);
+		self.set_current_font_variant(FontVariant::Bold);
+		let line_cap_height = self.calc_cap_height();
+		let cap_height = line_cap_height * CLASS_CHIP_SCALE;
+		let padding = cap_height * CLASS_CHIP_PADDING;
+		let radius = (cap_height / 2.0 + padding) * options.roundness.clamp(0.0, 1.0);
+		let mut x_min = self.x_min();
+		let mut current_row = 0;
+		for ((text, width, row), class) in chips.into_iter().zip(classes)
+		{
+			if row != current_row
+			{
+				self.y -= self.current_newline_amount();
+				x_min = self.x_min();
+				current_row = row;
+			}
+			// Line the middle of the chip's letters up with the middle of the capital letters of the line
+			let baseline = self.y + (line_cap_height - cap_height) / 2.0;
+			let x = x_min + self.column_x_offset();
+			let (y_min, y_max) = (baseline - padding, baseline + cap_height + padding);
+			let rings = vec![rounded_rect_points(x, y_min, x + width, y_max, radius)];
+			let layer = self.current_layer();
+			layer.save_graphics_state();
+			layer.set_fill_color(bytes_to_color(&options.color(class)));
+			layer.add_polygon(Polygon { rings: rings, mode: PaintMode::Fill, winding_order: WindingOrder::NonZero });
+			layer.restore_graphics_state();
+			layer.begin_text_section();
+			layer.set_text_cursor(x + padding * 2.0, baseline);
+			layer.set_font(self.current_font_ref(), self.current_font_size().0 * CLASS_CHIP_SCALE);
+			layer.set_fill_color(bytes_to_color(&options.text_color));
+			layer.write_text(&text, self.current_font_ref());
+			layer.end_text_section();
+			x_min += width + Mm(CLASS_CHIP_GAP);
+		}
+		self.x = self.x_min();
+	}
+
+	/// Returns the abbreviation on each class chip, how wide the chip is, and which row it goes in (starting at 0).
+	fn class_chip_layout(&mut self, classes: &[String], options: &ClassChipOptions) -> Vec<(String, Mm, usize)>
+	{
+		let starting_font_variant = *self.current_font_variant();
+		self.set_current_font_variant(FontVariant::Bold);
+		let padding = self.calc_cap_height() * CLASS_CHIP_SCALE * CLASS_CHIP_PADDING;
+		let row_width = self.x_max() - self.x_min();
+		let mut chips = Vec::with_capacity(classes.len());
+		let mut x = Mm(0.0);
+		let mut row = 0;
+		for class in classes
+		{
+			let text = options.abbreviation(class);
+			let width = self.calc_text_width(&text) * CLASS_CHIP_SCALE + padding * 4.0;
+			if x > Mm(0.0) && x + width > row_width
+			{
+				row += 1;
+				x = Mm(0.0);
+			}
+			chips.push((text, width, row));
+			x += width + Mm(CLASS_CHIP_GAP);
+		}
+		self.set_current_font_variant(starting_font_variant);
+		chips
+	}
+
+	/// Writes a spell's flavor quote in italics indented on both sides, with who said it after an em dash on its own
+	/// line lined up with the right side of the quote.
+	fn write_flavor_quote(&mut self, quote: &str, attribution: Option<&str>, tables: &Vec<spells::Table>)
+	{
+		let indent = self.tab_amount();
+		let (x_min, x_max) = (self.x_min() + indent, self.x_max() - indent);
+		self.x = x_min;
+		self.set_current_font_variant(FontVariant::Italic);
+		let quote = spells::redact_secrets(quote, self.redaction);
+		self.write_textbox(&quote, x_min, x_max, self.y_bottom(), self.y_top(), false, tables);
+		if let Some(attribution) = attribution
+		{
+			let attribution = format!("— {}", attribution);
+			self.y -= self.current_newline_amount();
+			self.set_current_font_variant(FontVariant::Regular);
+			// Wrap the attribution from the left side of the quote if it's too long to fit on one line
+			let width = self.calc_text_width(&attribution);
+			self.x = if x_max - width > x_min { x_max - width } else { x_min };
+			self.write_textbox(&attribution, self.x, x_max, self.y_bottom(), self.y_top(), false, tables);
+		}
+	}
+
+	/// Whether a spell can start right after the last spell on the current page. The last spell has to have ended on
+	/// this page with room under it for the name and fields of another spell (and the space between them).
+	fn has_room_for_spell(&self) -> bool
+	{
+		if self.last_spell_end != Some(self.current_page_index) { return false; }
+		let name_height = self.font_data.get_newline_amount_for(TextType::Header) * 3.0;
+		// The quick stats grid is a little taller than the lines of the fields
+		let mut field_line_count = if self.quick_stats.is_some() { 6.0 } else { 5.0 };
+		if self.class_chips.is_some() { field_line_count += 1.0; }
+		let fields_height = self.font_data.get_newline_amount_for(TextType::Body) * field_line_count;
+		self.y - name_height - fields_height >= self.y_min()
+	}
+
+	/// Returns a spell with a table of its damage at each tier of character levels at the end of its upcast description
+	/// (or its description if it doesn't have one) if there are cantrip scaling tables and the spell is a cantrip whose
+	/// damage increases at certain levels.
+	fn with_cantrip_scaling_table<'s>(&self, spell: &'s spells::Spell) -> Cow<'s, spells::Spell>
+	{
+		let (options, tiers) = match (&self.cantrip_scaling, spell.cantrip_damage_tiers())
+		{
+			(Some(options), Some(tiers)) => (options, tiers),
+			_ => return Cow::Borrowed(spell)
+		};
+		let mut spell = spell.clone();
+		let table_tag = format!("\n[table][{}]", spell.tables.len());
+		spell.tables.push(options.table(&tiers));
+		match &mut spell.upcast_description
+		{
+			Some(upcast_description) => upcast_description.push_str(&table_tag),
+			None => spell.description.push_str(&table_tag)
+		}
+		Cow::Owned(spell)
+	}
+
+	/// Returns the description of a spell with its upcast description at the end (if it goes in the description)
+	/// along with the text that goes before the upcast description.
+	fn spell_description(&self, spell: &spells::Spell) -> (String, String)
+	{
+		// Adds different text at the start of the upcast description based on whether the spell is a cantrip or not
+		let upcast_prefix = match &spell.level
+		{
+			spells::SpellField::Controlled(spells::Level::Cantrip) => self.upcast.cantrip_prefix.clone(),
+			_ => self.upcast.leveled_prefix.clone()
+		};
+		// Get the upcast description prepared if it goes in the description
+		let upcast_description = match (&spell.upcast_description, self.upcast.placement)
+		{
+			// Create the upcast description with a newline and font tags
+			(Some(upcast_description), UpcastPlacement::Inline) =>
+			format!("\n<bi> {}. <r> {}", upcast_prefix, &upcast_description),
+			_ => String::new()
+		};
+
+		// Add the upcast description to the end of the rest of the spell description
+		// Handle the secrets in it for this edition of the spellbook
+		(spells::redact_secrets(&format!("{}{}", &spell.description, upcast_description), self.redaction), upcast_prefix)
+	}
+
+	/// Leaves a spell out of the spellbook (for previews) but still counts the pages it would take up so the page
+	/// numbers after it stay the same. The number of pages is estimated from the height of the spell.
+	#[cfg_attr(feature = "tracing", tracing::instrument(skip_all, fields(spell = %spell.name)))]
+	fn skip_spell(&mut self, spell: &spells::Spell)
+	{
+		let spell = &*self.with_cantrip_scaling_table(spell);
+		let (description, upcast_prefix) = self.spell_description(spell);
+		// Use the number of pages the spell took up last time if it's in the layout cache
+		let cached_page_count = self.cached_spell_layout(spell).and_then(|layout| layout.page_count);
+		// Spells after this one can't start on the page the last spell ended on since this spell would be between them
+		self.last_spell_end = None;
+		let page_count = match cached_page_count
+		{
+			Some(page_count) => page_count,
+			None =>
+			{
+				let height = self.cached_spell_height(spell, &description, &upcast_prefix);
+				self.spell_page_count(height)
+			}
+		};
+		// The spell would have started on the next page
+		// (links to it go to the last page before where it would be since its pages don't exist)
+		self.spell_pages.push((self.current_page_index, self.page_number_text(self.current_page_num)));
+		self.current_page_num += page_count as i64;
+		// Page numbers switch sides for each skipped page like they would for pages that exist
+		if let Some(data) = &mut self.page_number_data
+		{
+			if data.flips_sides() && page_count % 2 == 1 { data.flip_side(); }
+		}
+	}
+
+	/// Returns the space between a spell's name and its level and school and the space around its casting time,
+	/// range, components, and duration (and its flavor quote). Uses the compact density spacing if there is any and it
+	/// makes the spell take up fewer pages.
+	fn spell_spacing(&mut self, spell: &spells::Spell, description: &str, upcast_prefix: &str) -> (Mm, Mm)
+	{
+		let header_newline_amount = self.font_data.get_newline_amount_for(TextType::Header);
+		// The fields and the flavor quote each have space after them
+		let field_gap_count = if spell.flavor_quote().is_some() { 3.0 } else { 2.0 };
+		let options = match self.compact_density
+		{
+			Some(options) => options,
+			None => return (header_newline_amount, header_newline_amount)
+		};
+		let compact_name_spacing = header_newline_amount * options.header_spacing.clamp(0.0, 1.0);
+		let compact_field_spacing = header_newline_amount * options.field_spacing.clamp(0.0, 1.0);
+		// Measure the spell without the spacing between its parts so it only has to be measured once
+		let height = self.cached_spell_height(spell, description, upcast_prefix);
+		let normal_page_count = self.spell_page_count(height + header_newline_amount * (field_gap_count + 1.0));
+		let compact_page_count =
+		self.spell_page_count(height + compact_name_spacing + compact_field_spacing * field_gap_count);
+		if compact_page_count < normal_page_count { (compact_name_spacing, compact_field_spacing) }
+		else { (header_newline_amount, header_newline_amount) }
+	}
+
+	/// Gets what the layout cache knows about a spell with the current options (`None` if there's no layout cache).
+	fn cached_spell_layout(&self, spell: &spells::Spell) -> Option<layout_cache::SpellLayout>
+	{
+		let key = layout_cache::spell_layout_key(spell, self.layout_options_hash);
+		self.layout_cache.as_ref().map(|cache| cache.get(key))
+	}
+
+	/// Returns the height of a spell from `measure_spell_height()`, using the height from the layout cache if it's in
+	/// there and putting it in there if it's not.
+	fn cached_spell_height(&mut self, spell: &spells::Spell, description: &str, upcast_prefix: &str) -> Mm
+	{
+		if let Some(height) = self.cached_spell_layout(spell).and_then(|layout| layout.height) { return Mm(height); }
+		let height = self.measure_spell_height(spell, description, upcast_prefix);
+		if let Some(cache) = &self.layout_cache
+		{
+			let key = layout_cache::spell_layout_key(spell, self.layout_options_hash);
+			cache.update(key, |layout| layout.height = Some(height.0));
+		}
+		height
+	}
+
+	/// Estimates the distance between the first line of a spell's name and the last line of its description on its
+	/// pages without the space after its name and around its casting time, range, components, duration, and flavor
+	/// quote.
+	/// Doesn't include notes areas.
+	fn measure_spell_height(&mut self, spell: &spells::Spell, description: &str, upcast_prefix: &str) -> Mm
+	{
+		let starting_text_type = *self.current_text_type();
+		let starting_font_variant = *self.current_font_variant();
+		let x_min = self.x_min();
+		let x_max = self.x_max();
+		// The spell name (lined up after the preparation checkbox if there is one)
+		self.set_current_text_type(TextType::Header);
+		self.set_current_font_variant(FontVariant::Regular);
+		let name_x_min = match self.preparation_checkboxes
+		{
+			Some(_) => x_min + self.checkbox_width(),
+			None => x_min
+		};
+		let name_lines = self.get_textbox_lines(&spell.name, x_max - name_x_min, x_max - x_min);
+		let mut height = self.current_newline_amount() * name_lines.len().saturating_sub(1) as f32;
+		// The level and school
+		self.set_current_text_type(TextType::Body);
+		self.set_current_font_variant(FontVariant::Italic);
+		let level_school_lines = self.get_textbox_lines(&spell.get_level_school_text(), x_max - x_min, x_max - x_min);
+		let mut line_count = level_school_lines.len().saturating_sub(1);
+		// The rows of class chips (if there are any)
+		if let Some(options) = self.class_chips.clone()
+		{
+			if let Some((_, _, row)) = self.class_chip_layout(&spell.classes, &options).last() { line_count += row + 1; }
+		}
+		// The casting time, range, components, and duration (with a newline between each of them)
+		self.set_current_font_variant(FontVariant::Bold);
+		if self.quick_stats.is_some()
+		{
+			// The rows of the grid minus the line that the grid starts on
+			let values = spell_field_values(spell, self.distance_conversion, self.component_style);
+			let (_, line_counts) = self.quick_stats_layout(&values);
+			let padding = self.current_newline_amount() * QUICK_STATS_PADDING;
+			let row_line_count: usize = line_counts.iter().sum();
+			height += self.current_newline_amount() * (row_line_count - 1) as f32 + padding * 8.0;
+		}
+		else
+		{
+			let field_texts = spell_field_texts(spell, self.distance_conversion, self.component_style);
+			for (field_index, field_text) in field_texts.iter().enumerate()
+			{
+				if field_index > 0 { line_count += 1; }
+				line_count += self.get_textbox_lines(field_text, x_max - x_min, x_max - x_min).len().saturating_sub(1);
+			}
+		}
+		height += self.current_newline_amount() * line_count as f32;
+		// The flavor quote and the line with who said it (the estimate includes a newline before it that isn't there)
+		if let Some((quote, attribution)) = spell.flavor_quote()
+		{
+			let indent = self.tab_amount();
+			self.set_current_font_variant(FontVariant::Italic);
+			let quote = spells::redact_secrets(quote, self.redaction);
+			let paragraphs: Vec<_> = quote.split('\n').collect();
+			height += self.estimate_paragraphs_height(&paragraphs, &spell.tables, x_min + indent, x_max - indent)
+			- self.current_newline_amount();
+			if attribution.is_some() { height += self.current_newline_amount(); }
+		}
+		// The description (the estimate includes a newline before it that isn't there)
+		self.set_current_font_variant(FontVariant::Regular);
+		let paragraphs: Vec<_> = description.split('\n').collect();
+		height += self.estimate_paragraphs_height(&paragraphs, &spell.tables, x_min, x_max)
+		- self.current_newline_amount();
+		// The upcast description in its own block with the prefix as its heading (if it goes there)
+		if let (Some(upcast_description), UpcastPlacement::SeparateBlock) =
+		(&spell.upcast_description, self.upcast.placement)
+		{
+			self.set_current_font_variant(FontVariant::BoldItalic);
+			let prefix_lines = self.get_textbox_lines(upcast_prefix, x_max - x_min, x_max - x_min);
+			height += self.font_data.get_newline_amount_for(TextType::Header)
+			+ self.current_newline_amount() * prefix_lines.len().saturating_sub(1) as f32;
+			self.set_current_font_variant(FontVariant::Regular);
+			let upcast_description = spells::redact_secrets(upcast_description, self.redaction);
+			let paragraphs: Vec<_> = upcast_description.split('\n').collect();
+			height += self.estimate_paragraphs_height(&paragraphs, &spell.tables, x_min, x_max);
+		}
+		self.set_current_text_type(starting_text_type);
+		self.set_current_font_variant(starting_font_variant);
+		height
+	}
+
+	/// Returns how many pages a spell takes up based on the distance between the first line of its name and the last
+	/// line of its description.
+	fn spell_page_count(&mut self, height: Mm) -> usize
+	{
+		let starting_text_type = *self.current_text_type();
+		// Spell names start at the top of the first page and descriptions continue at the top of the other pages
+		self.set_current_text_type(TextType::Header);
+		let first_page_height = self.y_top() - self.y_min();
+		self.set_current_text_type(TextType::Body);
+		// Each page after the first also has a newline of space before its first line
+		let page_height = self.y_top() - self.y_min() + self.current_newline_amount();
+		self.set_current_text_type(starting_text_type);
+		if height <= first_page_height { 1 }
+		else { 1 + ((height - first_page_height) / page_height).ceil() as usize }
+	}
+
+	/// Puts the next page filler in the middle of the empty space below the current position on the page if there's
+	/// enough of it (and page fill is desired).
+	fn fill_empty_space(&mut self)
+	{
+		let options = match &self.page_fill
+		{
+			Some(options) if !options.fillers.is_empty() => options,
+			_ => return
+		};
+		let min_empty_space = (self.y_max() - self.y_min()) * options.min_empty_space.clamp(0.0, 1.0);
+		// Leave a line of space between the text above and the empty space
+		let top = self.y - self.current_newline_amount();
+		let bottom = self.y_min();
+		if top - bottom < min_empty_space || top <= bottom { return; }
+		let filler_index = self.filled_page_count % options.fillers.len();
+		let filler = options.fillers[filler_index].clone();
+		self.filled_page_count += 1;
+		let center_x = (self.x_min() + self.x_max()) / 2.0 + self.column_x_offset();
+		let center_y = (top + bottom) / 2.0;
+		match filler
+		{
+			PageFiller::Quote { text, attribution } =>
+			{
+				let starting_font_variant = *self.current_font_variant();
+				// Quotes are narrower than the text area so they stand out from the text
+				let x_min = self.x_min() + (self.x_max() - self.x_min()) / 8.0;
+				let x_max = self.x_max() - (self.x_max() - self.x_min()) / 8.0;
+				self.set_current_font_variant(FontVariant::Italic);
+				let quote_lines = self.get_textbox_lines(&text, x_max - x_min, x_max - x_min);
+				self.set_current_font_variant(FontVariant::Regular);
+				let attribution_lines = match &attribution
+				{
+					Some(attribution) =>
+					self.get_textbox_lines(&format!("\u{2014} {}", attribution), x_max - x_min, x_max - x_min),
+					None => Vec::new()
+				};
+				// Center the lines from the top of the capital letters in the first line to the baseline of the last
+				let line_count = quote_lines.len() + attribution_lines.len();
+				let lines_height = self.current_newline_amount() * line_count.saturating_sub(1) as f32;
+				self.y = center_y + (lines_height - self.calc_cap_height()) / 2.0;
+				self.set_current_font_variant(FontVariant::Italic);
+				self.apply_centered_text_lines(&quote_lines, x_min, x_max);
+				if !attribution_lines.is_empty()
+				{
+					self.y -= self.current_newline_amount();
+					self.set_current_font_variant(FontVariant::Regular);
+					self.apply_centered_text_lines(&attribution_lines, x_min, x_max);
+				}
+				self.set_current_font_variant(starting_font_variant);
+			},
+			PageFiller::Image { width, .. } =>
+			{
+				let image = match &self.page_fill_images[filler_index]
+				{
+					Some(image) => image,
+					None => return
+				};
+				// The size of the image on the page at its actual size
+				let actual_width = Mm(image.width() as f32 / PAGE_FILL_IMAGE_DPI * MM_PER_INCH);
+				let actual_height = Mm(image.height() as f32 / PAGE_FILL_IMAGE_DPI * MM_PER_INCH);
+				if actual_width <= Mm(0.0) || actual_height <= Mm(0.0) { return; }
+				// Scale the image to the given width or down to the height of the empty space
+				let scale = (Mm(width) / actual_width).min((top - bottom) / actual_height);
+				let transform = ImageTransform
+				{
+					translate_x: Some(center_x - actual_width * scale / 2.0),
+					translate_y: Some(center_y - actual_height * scale / 2.0),
+					scale_x: Some(scale),
+					scale_y: Some(scale),
+					dpi: Some(PAGE_FILL_IMAGE_DPI),
+					..Default::default()
+				};
+				Image::from_dynamic_image(&image.clone()).add_to_layer(self.current_layer().clone(), transform);
+			},
+			PageFiller::Ornament { width, color } =>
+			{
+				let half_width = Mm(width) / 2.0;
+				let diamond_size = Mm(ORNAMENT_DIAMOND_SIZE);
+				let half_thickness = Mm(ORNAMENT_RULE_THICKNESS) / 2.0;
+				// A diamond in the middle with a rule on each side of it that tapers to a point
+				let rings = vec!
+				[
+					vec!
+					[
+						(Point::new(center_x, center_y + diamond_size), false),
+						(Point::new(center_x + diamond_size, center_y), false),
+						(Point::new(center_x, center_y - diamond_size), false),
+						(Point::new(center_x - diamond_size, center_y), false)
+					],
+					vec!
+					[
+						(Point::new(center_x - diamond_size * 1.5, center_y + half_thickness), false),
+						(Point::new(center_x - half_width, center_y), false),
+						(Point::new(center_x - diamond_size * 1.5, center_y - half_thickness), false)
+					],
+					vec!
+					[
+						(Point::new(center_x + diamond_size * 1.5, center_y + half_thickness), false),
+						(Point::new(center_x + half_width, center_y), false),
+						(Point::new(center_x + diamond_size * 1.5, center_y - half_thickness), false)
+					]
+				];
+				let layer = self.current_layer();
+				layer.save_graphics_state();
+				layer.set_fill_color(bytes_to_color(&color));
+				layer.add_polygon(Polygon
+				{
+					rings: rings,
+					mode: PaintMode::Fill,
+					winding_order: WindingOrder::NonZero
+				});
+				layer.restore_graphics_state();
+			}
+		}
+		// Nothing else goes on the page after the filler
+		self.y = bottom;
+	}
+
+	/// Adds a ruled area for handwritten notes after the current spell (if notes areas are desired).
+	fn add_notes_area(&mut self)
+	{
+		let options = match self.notes_area
+		{
+			Some(options) => options,
+			None => return
+		};
+		// Figure out where the top of the notes area is and how tall it is
+		let height = match options.placement
+		{
+			NotesAreaPlacement::EndOfSpell =>
+			{
+				// Leave a line of space between the description and the notes area
+				self.y -= self.current_newline_amount();
+				let height = Mm(options.height).min(self.y_max() - self.y_min());
+				// Go to the next page if the notes area won't fit on this one
+				if self.y - height < self.y_min() { self.move_to_new_page(); }
+				// Line up the top of the notes area with the top of the text that was on this line
+				self.y += self.current_newline_amount() / 2.0;
+				height
+			},
+			NotesAreaPlacement::FacingPage =>
+			{
+				self.make_new_page();
+				self.y = self.y_max();
+				self.y_max() - self.y_min()
+			}
+		};
+		// The y position of the bottom of the notes area
+		let y_bottom = self.y - height;
+		// Leave the area blank if there's no space between lines
+		if options.line_spacing <= 0.0 { self.y = y_bottom; return; }
+		self.current_layer().set_outline_color(bytes_to_color(&options.line_color));
+		self.current_layer().set_outline_thickness(options.line_thickness);
+		// Draw a line at the bottom of each row of the notes area
+		let line_spacing = Mm(options.line_spacing);
+		let mut y = self.y - line_spacing;
+		while y >= y_bottom
+		{
+			let points = vec!
+			[
+				(Point::new(self.x_min() + self.column_x_offset(), y), false),
+				(Point::new(self.x_max() + self.column_x_offset(), y), false)
+			];
+			self.current_layer().add_line(Line { points: points, is_closed: false });
+			y -= line_spacing;
+		}
+		self.y = y_bottom;
+	}
+
+	/// Adds a glossary with each term sorted alphabetically in two columns to the end of the spellbook (if a glossary
+	/// is desired).
+	fn add_glossary(&mut self)
+	{
+		// If there is no glossary or nothing in it, do nothing
+		let options = match &self.glossary
+		{
+			Some(options) if !options.entries.is_empty() => options.clone(),
+			_ => return
+		};
+		// Make a new page for the glossary and bookmark it
+		self.make_new_page();
+		self.doc.add_bookmark(options.title.clone(), self.pages[self.current_page_index]);
+		self.add_reference_anchor(&options.title);
+		// Write the heading centered at the top of the page
+		self.set_current_text_type(TextType::Header);
+		self.set_current_font_variant(FontVariant::Regular);
+		self.y = self.y_top();
+		let textbox_width = self.x_max() - self.x_min();
+		let heading_lines = self.get_textbox_lines(&options.title, textbox_width, textbox_width);
+		self.apply_centered_text_lines(&heading_lines, self.x_min(), self.x_max());
+		self.y -= self.font_data.get_newline_amount_for(TextType::Header);
+		// Split the rest of the glossary into columns with a space the size of two tabs between them
+		self.set_current_text_type(TextType::Body);
+		let column_gap = self.tab_amount() * 2.0;
+		let column_width = (textbox_width - column_gap * (GLOSSARY_COLUMN_COUNT - 1) as f32)
+		/ GLOSSARY_COLUMN_COUNT as f32;
+		self.columns = Some(ColumnLayout
+		{
+			column_count: GLOSSARY_COLUMN_COUNT,
+			column_width: column_width,
+			column_gap: column_gap,
+			current_column: 0,
+			top_y: Some(self.y),
+			balanced_y_min: None
+		});
+		self.add_debug_columns();
+		// Sort the terms alphabetically regardless of capitalization
+		let mut entries: Vec<_> = options.entries.iter()
+			.map(|(term, definition)| (term, format!("{}. {} {}", term, REGULAR_FONT_TAG, definition)))
+			.collect();
+		entries.sort_by_key(|(term, _)| term.to_lowercase());
+		// The number of lines in each entry and the ones after it for balancing the columns on the last page
+		self.set_current_font_variant(FontVariant::Bold);
+		let mut remaining_lines: Vec<usize> = entries.iter()
+			.map(|(_, entry)| self.get_textbox_lines(entry, column_width, column_width).len())
+			.collect();
+		for index in (0..remaining_lines.len().saturating_sub(1)).rev()
+		{
+			remaining_lines[index] += remaining_lines[index + 1];
+		}
+		let mut first_entry = true;
+		for ((term, entry), remaining_lines) in entries.into_iter().zip(remaining_lines)
+		{
+			// Leave a line between each entry
+			if !first_entry { self.y -= self.current_newline_amount(); }
+			first_entry = false;
+			self.balance_columns(remaining_lines);
+			// Move to the next column before remembering where this term is if it won't fit in this one
+			self.check_for_new_page();
+			self.anchors.insert(glossary_anchor(term), self.current_page_index);
+			// Write the term in bold followed by its definition
+			self.x = self.x_min();
+			self.set_current_font_variant(FontVariant::Bold);
+			self.write_textbox
+			(&entry, self.x_min(), self.x_min() + column_width, self.y_bottom(), self.y_top(), false, &Vec::new());
+		}
+		// Stop splitting pages into columns
+		self.columns = None;
+	}
+
+	/// Adds reference pages (like spell slot tables and a summary of the concentration rules) to the end of the
+	/// spellbook (if they're desired).
+	fn add_reference_appendix(&mut self)
+	{
+		let options = match &self.reference_appendix
+		{
+			Some(options) if options.spell_slot_tables || options.concentration_rules => options.clone(),
+			_ => return
+		};
+		// Make a new page for the reference pages and bookmark it
+		self.make_new_page();
+		self.doc.add_bookmark(options.title.clone(), self.pages[self.current_page_index]);
+		self.add_reference_anchor(&options.title);
+		// Write the heading centered at the top of the page
+		self.set_current_text_type(TextType::Header);
+		self.set_current_font_variant(FontVariant::Regular);
+		self.y = self.y_top();
+		let textbox_width = self.x_max() - self.x_min();
+		let heading_lines = self.get_textbox_lines(&options.title, textbox_width, textbox_width);
+		self.apply_centered_text_lines(&heading_lines, self.x_min(), self.x_max());
+		self.y -= self.font_data.get_newline_amount_for(TextType::Header);
+		// Write the tables and rules the same way as a spell description
+		self.set_current_text_type(TextType::Body);
+		self.x = self.x_min();
+		self.write_textbox
+		(&options.text(), self.x_min(), self.x_max(), self.y_bottom(), self.y_top(), false, &options.tables());
+	}
+
+	/// Adds an appendix with a table of the time and gold it takes to scribe each spell (if scribing costs are
+	/// desired).
+	fn add_scribing_costs(&mut self, spells: &[spells::Spell])
+	{
+		let options = match &self.scribing_costs
+		{
+			Some(options) if !spells.is_empty() => options.clone(),
+			_ => return
+		};
+		// Make a new page for the appendix and bookmark it
+		self.make_new_page();
+		self.doc.add_bookmark(options.title.clone(), self.pages[self.current_page_index]);
+		self.add_reference_anchor(&options.title);
+		// Write the heading centered at the top of the page
+		self.set_current_text_type(TextType::Header);
+		self.set_current_font_variant(FontVariant::Regular);
+		self.y = self.y_top();
+		let textbox_width = self.x_max() - self.x_min();
+		let heading_lines = self.get_textbox_lines(&options.title, textbox_width, textbox_width);
+		self.apply_centered_text_lines(&heading_lines, self.x_min(), self.x_max());
+		self.y -= self.font_data.get_newline_amount_for(TextType::Header);
+		// Write the table the same way as a table in a spell description
+		self.set_current_text_type(TextType::Body);
+		self.x = self.x_min();
+		self.write_textbox
+		("[table][0]", self.x_min(), self.x_max(), self.y_bottom(), self.y_top(), false, &vec![options.table(spells)]);
+	}
+
+	/// Adds a narrow page to the end of the spellbook with the title written sideways on it for the spine of a bound
+	/// copy of the spellbook.
+	fn add_spine_page(&mut self, title: &str, options: &SpineOptions)
+	{
+		// Make a page as tall as the other pages but only as wide as the spine (without a background, page number, or
+		// any page decorations since those are made for full size pages)
+		let spine_width = Mm(options.width);
+		let (page, layer) = self.doc.add_page
+		(
+			spine_width,
+			self.page_height(),
+			format!("{} {}", LAYER_NAME_PREFIX, self.layers.len())
+		);
+		let layer = self.doc.get_page(page).get_layer(layer);
+		self.layers.push(layer.clone());
+		self.pages.push(page);
+		self.current_page_index = self.layers.len() - 1;
+		self.apply_spine(title, options, Mm(0.0));
+	}
+
+	/// Fills in a spine that starts at the given x position on the current page and writes the title sideways in the
+	/// middle of it.
+	fn apply_spine(&mut self, mut title: &str, options: &SpineOptions, x_min: Mm)
+	{
+		if title.is_empty() { title = DEFAULT_SPELLBOOK_TITLE; }
+		// The whole title goes on one line
+		let title = title.split_whitespace().collect::<Vec<&str>>().join(" ");
+		let spine_width = Mm(options.width);
+		let layer = self.current_layer().clone();
+		if let Some(color) = &options.background_color
+		{
+			layer.save_graphics_state();
+			layer.set_fill_color(bytes_to_color(color));
+			let rect = Rect::new(x_min, Mm(0.0), x_min + spine_width, self.page_height());
+			layer.add_rect(rect.with_mode(PaintMode::Fill));
+			layer.restore_graphics_state();
+		}
+		// Measure the title with the title font and scale it down to the spine's font size
+		self.set_current_text_type(TextType::Title);
+		self.set_current_font_variant(FontVariant::Regular);
+		let title_font_size = self.current_font_size().0;
+		let title_width = self.calc_text_width(&title);
+		// Shrink the title if it's too big for the spine or longer than the space between the top and bottom margins
+		let max_text_width = self.y_max() - self.y_min();
+		let font_size = options.font_size
+			.min(Pt::from(spine_width * SPINE_MAX_FONT_SIZE).0)
+			.min(title_font_size * max_text_width.0 / title_width.0);
+		let text_width = title_width * (font_size / title_font_size);
+		let cap_height = self.calc_cap_height() * (font_size / title_font_size);
+		// Center the title on the spine with the tops of the letters facing the front cover
+		let center_x = x_min + spine_width / 2.0;
+		let center_y = self.page_height() / 2.0;
+		let matrix = match options.direction
+		{
+			SpineDirection::TopToBottom =>
+			{
+				let (x, y) = (center_x - cap_height / 2.0, center_y + text_width / 2.0);
+				[0.0, -1.0, 1.0, 0.0, Pt::from(x).0, Pt::from(y).0]
+			},
+			SpineDirection::BottomToTop =>
+			{
+				let (x, y) = (center_x + cap_height / 2.0, center_y - text_width / 2.0);
+				[0.0, 1.0, -1.0, 0.0, Pt::from(x).0, Pt::from(y).0]
+			}
+		};
+		layer.save_graphics_state();
+		layer.begin_text_section();
+		layer.set_text_matrix(TextMatrix::Raw(matrix));
+		layer.set_font(self.current_font_ref(), font_size);
+		layer.set_fill_color(self.current_text_color().clone());
+		layer.write_text(title, self.current_font_ref());
+		layer.end_text_section();
+		layer.restore_graphics_state();
+	}
+
+	/// Adds an appendix with a table of the class level each spell becomes available at and the page it's on (if a
+	/// level plan is desired). `spell_order` is the index of each spell in the order they were added.
+	fn add_level_plan(&mut self, spells: &[spells::Spell], spell_order: &[usize])
+	{
+		let options = match &self.level_plan
+		{
+			Some(options) if !spells.is_empty() => options.clone(),
+			_ => return
+		};
+		// The page number of each spell (the first one if it was added more than once)
+		let page_numbers: Vec<String> = (0..spells.len()).map(|spell_index|
+		{
+			let position = spell_order.iter().position(|index| *index == spell_index);
+			match position.and_then(|position| self.spell_pages.get(position))
+			{
+				Some((_, page_number)) => page_number.clone(),
+				None => String::from("\u{2014}")
+			}
+		})
+		.collect();
+		// Make a new page for the appendix and bookmark it
+		self.make_new_page();
+		self.doc.add_bookmark(options.title.clone(), self.pages[self.current_page_index]);
+		self.add_reference_anchor(&options.title);
+		// Write the heading centered at the top of the page
+		self.set_current_text_type(TextType::Header);
+		self.set_current_font_variant(FontVariant::Regular);
+		self.y = self.y_top();
+		let textbox_width = self.x_max() - self.x_min();
+		let heading_lines = self.get_textbox_lines(&options.title, textbox_width, textbox_width);
+		self.apply_centered_text_lines(&heading_lines, self.x_min(), self.x_max());
+		self.y -= self.font_data.get_newline_amount_for(TextType::Header);
+		// Write the table the same way as a table in a spell description
+		self.set_current_text_type(TextType::Body);
+		self.x = self.x_min();
+		let table = options.table(spells, &page_numbers);
+		self.write_textbox("[table][0]", self.x_min(), self.x_max(), self.y_bottom(), self.y_top(), false, &vec![table]);
+	}
+
+	/// Adds an appendix with a checklist of the material components of every spell in the spellbook (if it's desired).
+	fn add_component_list(&mut self, spells: &[spells::Spell])
+	{
+		let options = match &self.component_list
+		{
+			Some(options) => options.clone(),
+			None => return
+		};
+		let table = options.table(spells);
+		// Don't add the appendix if none of the spells have any material components to list (just the total row)
+		if table.cells.len() <= 1 { return; }
+		// Make a new page for the appendix and bookmark it
+		self.make_new_page();
+		self.doc.add_bookmark(options.title.clone(), self.pages[self.current_page_index]);
+		self.add_reference_anchor(&options.title);
+		// Write the heading centered at the top of the page
+		self.set_current_text_type(TextType::Header);
+		self.set_current_font_variant(FontVariant::Regular);
+		self.y = self.y_top();
+		let textbox_width = self.x_max() - self.x_min();
+		let heading_lines = self.get_textbox_lines(&options.title, textbox_width, textbox_width);
+		self.apply_centered_text_lines(&heading_lines, self.x_min(), self.x_max());
+		self.y -= self.font_data.get_newline_amount_for(TextType::Header);
+		// Write the table the same way as a table in a spell description
+		self.set_current_text_type(TextType::Body);
+		self.x = self.x_min();
+		self.write_textbox("[table][0]", self.x_min(), self.x_max(), self.y_bottom(), self.y_top(), false, &vec![table]);
+	}
+
+	/// Outlines each column on the current page (if the layout is being debugged and pages are split into columns).
+	fn add_debug_columns(&mut self)
+	{
+		if !self.debug_layout { return; }
+		let columns = match &self.columns
+		{
+			Some(columns) => *columns,
+			None => return
+		};
+		for column in 0..columns.column_count
+		{
+			let x_min = self.x_min() + (columns.column_width + columns.column_gap) * column as f32;
+			let x_max = x_min + columns.column_width;
+			self.debug_lines.push
+			((self.current_page_index, outline_rect(x_min, self.y_min(), x_max, self.y_max()), DEBUG_COLUMN_COLOR));
+		}
+	}
+
+	/// Draws the text margins of every page from a page index on and all of the other layout outlines that haven't
+	/// been drawn yet onto the spellbook (if the layout is being debugged).
+	fn apply_debug_layout(&mut self, first_page_index: usize)
+	{
+		if !self.debug_layout { return; }
+		let debug_lines = std::mem::take(&mut self.debug_lines);
+		for page_index in 0..self.layers.len()
+		{
+			// Pages before the first page already have their margins drawn
+			let draws_margins = page_index >= first_page_index;
+			if !draws_margins && debug_lines.iter().all(|(line_page_index, _, _)| *line_page_index != page_index)
+			{
+				continue;
+			}
+			let layer = self.layers[page_index].clone();
+			// Multiply the outlines with whatever is under them so the text they go over can still be seen
+			layer.save_graphics_state();
+			layer.set_blend_mode(BlendMode::Seperable(SeperableBlendMode::Multiply));
+			layer.set_outline_thickness(DEBUG_LINE_THICKNESS.0);
+			if draws_margins
+			{
+				layer.set_outline_color(bytes_to_color(&DEBUG_MARGIN_COLOR));
+				layer.add_line(outline_rect(self.x_min(), self.y_min(), self.x_max(), self.y_max()));
+			}
+			for (line_page_index, line, color) in &debug_lines
+			{
+				if *line_page_index != page_index { continue; }
+				layer.set_outline_color(bytes_to_color(color));
+				layer.add_line(line.clone());
+			}
+			layer.restore_graphics_state();
+		}
+	}
+
+	/// Saves the current page as the page that page references to a table or section with the given title point to
+	/// (unless something with the same title came before it).
+	fn add_reference_anchor(&mut self, title: &str)
+	{
+		let anchor = reference_anchor(title);
+		if anchor.is_empty() { return; }
+		let page_num_text = self.page_number_text(self.current_page_num - 1);
+		self.reference_anchors.entry(anchor).or_insert((self.current_page_index, page_num_text));
+	}
+
+	/// The name and page index of each spell, table, and section that gets a named destination in the pdf (sorted by
+	/// name), or nothing if there aren't supposed to be named destinations.
+	fn named_destinations(&self) -> Vec<(String, usize)>
+	{
+		if !self.named_destinations { return Vec::new(); }
+		let mut named_destinations: Vec<_> = self.reference_anchors.iter()
+			.map(|(anchor, (page_index, _))| (anchor.clone(), *page_index))
+			.collect();
+		named_destinations.sort();
+		named_destinations
+	}
+
+	/// Writes the page number of the table or section that each page reference points to in the space that was saved
+	/// for it, and makes each page number link to that page.
+	/// Returns an error with the names of any anchors that aren't in the spellbook.
+	fn apply_page_references(&mut self) -> Result<(), Box<dyn Error + Send + Sync>>
+	{
+		let page_references = std::mem::take(&mut self.page_references);
+		let mut missing_names: Vec<String> = Vec::new();
+		for page_reference in &page_references
+		{
+			let is_missing = !self.reference_anchors.contains_key(&reference_anchor(&page_reference.name));
+			if is_missing && !missing_names.contains(&page_reference.name)
+			{
+				missing_names.push(page_reference.name.clone());
+			}
+		}
+		if !missing_names.is_empty() { return Err(Box::new(PageReferenceError(missing_names))); }
+		// Save the text settings so they can be set back after the page numbers are written
+		let starting_text_type = *self.current_text_type();
+		let starting_font_variant = *self.current_font_variant();
+		// Page references store where they go on the page with the column offset already in it
+		let columns = self.columns.take();
+		for page_reference in page_references
+		{
+			let anchor = reference_anchor(&page_reference.name);
+			let (target_page_index, page_num_text) = self.reference_anchors[&anchor].clone();
+			self.current_page_index = page_reference.page_index;
+			self.set_current_text_type(page_reference.text_type);
+			self.set_current_font_variant(page_reference.font_variant);
+			self.color_override = page_reference.color;
+			self.x = page_reference.x;
+			self.y = page_reference.y;
+			self.apply_text(&page_num_text);
+			// Make the page number link to the page it's the number of
+			let (y_min, y_max) = self.current_line_y_bounds();
+			self.internal_links.push(InternalLink
+			{
+				page_index: self.current_page_index,
+				x_min: page_reference.x,
+				y_min: y_min,
+				x_max: self.x,
+				y_max: y_max,
+				target_page_index: target_page_index
+			});
+		}
+		self.color_override = None;
+		self.columns = columns;
+		self.set_current_text_type(starting_text_type);
+		self.set_current_font_variant(starting_font_variant);
+		// Go back to the last page so anything added afterwards goes at the end
+		self.current_page_index = self.layers.len() - 1;
+		Ok(())
+	}
+
+	/// Turns every link to an anchor into a link to the page that the anchor is on.
+	/// Links to anchors that don't exist are dropped.
+	fn resolve_anchor_links(&mut self)
+	{
+		for (link, anchor) in std::mem::take(&mut self.anchor_links)
+		{
+			if let Some(page_index) = self.anchors.get(&anchor)
+			{
+				self.internal_links.push(InternalLink { target_page_index: *page_index, ..link });
+			}
+		}
+	}
+
+	/// Draws an empty square that sits on the baseline of the current line and is as tall as a capital letter, then
+	/// moves the x position past it. If a field name is given, the square also becomes a fillable checkbox.
+	fn apply_checkbox(&mut self, line_thickness: Pt, field_name: Option<String>)
+	{
+		let size = self.calc_cap_height();
+		let x_min = self.x + self.column_x_offset();
+		let x_max = x_min + size;
+		let y_min = self.y;
+		let y_max = self.y + size;
+		let points = vec!
+		[
+			(Point::new(x_min, y_min), false),
+			(Point::new(x_max, y_min), false),
+			(Point::new(x_max, y_max), false),
+			(Point::new(x_min, y_max), false)
+		];
+		let square = Line
+		{
+			points: points,
+			is_closed: true
+		};
+		// Draw the square in the same color as the text
+		self.current_layer().set_outline_color(self.current_text_color().clone());
+		self.current_layer().set_outline_thickness(line_thickness.0);
+		self.current_layer().add_line(square);
+		if let Some(name) = field_name
+		{
+			self.form_checkboxes.push(FormCheckbox
+			{
+				page_index: self.current_page_index,
+				x_min: x_min,
+				y_min: y_min,
+				x_max: x_max,
+				y_max: y_max,
+				name: name
+			});
+		}
+		self.x += self.checkbox_width();
+	}
+
+	/// Writes the components line of a spell with the letter of each component inside of a circle instead of
+	/// separated by commas. Any material components go after the circles in parentheses.
+	fn write_component_icons(&mut self, spell: &spells::Spell)
 	{
-		// Create the pdf document and the first page
-		let (doc, title_page, title_layer_index) =
-		// If no title was given for the spellbook (the given title string is empty)
-		if title.is_empty()
+		self.write_textbox
+		("Components:", self.x_min(), self.x_max(), self.y_bottom(), self.y_top(), false, &spell.tables);
+		self.apply_component_icons(spell, self.x_max());
+	}
+
+	/// Writes the letter of each component of a spell inside of a circle at the current position, followed by any
+	/// material components in parentheses (which wrap before `x_max`).
+	fn apply_component_icons(&mut self, spell: &spells::Spell, x_max: Mm)
+	{
+		let letters: Vec<&str> =
+		[
+			(spell.has_v_component, "V"),
+			(spell.has_s_component, "S"),
+			(spell.m_components.is_some(), "M")
+		]
+		.into_iter()
+		.filter_map(|(has_component, letter)| if has_component { Some(letter) } else { None })
+		.collect();
+		let cap_height = self.calc_cap_height();
+		let diameter = cap_height * COMPONENT_ICON_SIZE;
+		for letter in &letters
 		{
-			// Create pdf document with a default title
-			PdfDocument::new(DEFAULT_SPELLBOOK_TITLE, Mm(width), Mm(height), TITLE_LAYER_NAME)
+			self.x += self.calc_text_width(SPACE);
+			let x_min = self.x;
+			// Draw a circle around where the letter goes in the same color as the text
+			let center = (x_min + diameter / 2.0 + self.column_x_offset(), self.y + cap_height / 2.0);
+			let circle = ellipse_points(center, (diameter / 2.0, diameter / 2.0), 0.0, std::f32::consts::PI * 2.0);
+			let color = self.current_text_color().clone();
+			let layer = self.current_layer();
+			layer.save_graphics_state();
+			layer.set_outline_color(color);
+			layer.set_outline_thickness(COMPONENT_ICON_LINE_THICKNESS.0);
+			layer.add_line(Line { points: circle, is_closed: true });
+			layer.restore_graphics_state();
+			// Write the letter in the middle of the circle
+			self.x = x_min + (diameter - self.calc_text_width(letter)) / 2.0;
+			self.apply_text(letter);
+			self.x = x_min + diameter;
 		}
-		else
+		// Write the material components after the circles (or "None" if there are no components)
+		let text = match (&spell.m_components, letters.is_empty())
 		{
-			// Create pdf document with the given title
-			PdfDocument::new(title, Mm(width), Mm(height), TITLE_LAYER_NAME)
+			(Some(m), _) => format!("({})", m),
+			(None, true) => String::from("None"),
+			(None, false) => return
 		};
+		self.x += self.calc_text_width(SPACE);
+		self.set_current_font_variant(FontVariant::Regular);
+		self.write_textbox(&text, self.x, x_max, self.y_bottom(), self.y_top(), false, &spell.tables);
+	}
 
-		// Get PdfLayerReference (title_layer_ref) from PdfLayerIndex (title_layer_index)
-		let title_layer_ref = doc.get_page(title_page).get_layer(title_layer_index);
+	/// Writes the casting time, range, components, and duration of a spell in a grid with a row for each of them, with
+	/// the name of the field on the left and its value on the right. The top of the grid is at the top of the current
+	/// line, and the current line ends up with its bottom at the bottom of the grid.
+	fn write_quick_stats(&mut self, spell: &spells::Spell, options: QuickStatsOptions)
+	{
+		let values = spell_field_values(spell, self.distance_conversion, self.component_style);
+		let (label_width, line_counts) = self.quick_stats_layout(&values);
+		let newline_amount = self.current_newline_amount();
+		let padding = newline_amount * QUICK_STATS_PADDING;
+		let x_min = self.x_min();
+		let x_max = self.x_max();
+		let value_x_min = x_min + label_width + padding * 2.0;
+		let mut row_y_max = self.y + newline_amount * 0.75;
+		for ((label, value), line_count) in values.iter().zip(line_counts)
+		{
+			let mut row_y_min = row_y_max - newline_amount * line_count as f32 - padding * 2.0;
+			// Move the row to the next page if its last line doesn't fit on this one
+			if row_y_min + padding + newline_amount * 0.25 < self.y_min()
+			{
+				self.move_to_new_page();
+				row_y_max = self.y + newline_amount * 0.75;
+				row_y_min = row_y_max - newline_amount * line_count as f32 - padding * 2.0;
+			}
+			// Draw the cells of the row before the text so the shading doesn't cover it
+			let offset = self.column_x_offset();
+			let layer = self.current_layer();
+			layer.save_graphics_state();
+			if let Some(color) = options.label_fill_color
+			{
+				layer.set_fill_color(bytes_to_color(&color));
+				layer.add_rect
+				(Rect::new(x_min + offset, row_y_min, value_x_min + offset, row_y_max).with_mode(PaintMode::Fill));
+			}
+			layer.set_outline_color(bytes_to_color(&options.line_color));
+			layer.set_outline_thickness(options.line_thickness);
+			layer.add_line(outline_rect(x_min + offset, row_y_min, x_max + offset, row_y_max));
+			let divider_x = value_x_min + offset;
+			let divider = vec![(Point::new(divider_x, row_y_min), false), (Point::new(divider_x, row_y_max), false)];
+			layer.add_line(Line { points: divider, is_closed: false });
+			layer.restore_graphics_state();
+			// Write the name of the field
+			let baseline = row_y_max - padding - newline_amount * 0.75;
+			self.y = baseline;
+			self.x = x_min + padding;
+			self.set_current_font_variant(FontVariant::Bold);
+			self.write_textbox
+			(label, x_min + padding, value_x_min - padding, self.y_bottom(), self.y_top(), false, &spell.tables);
+			// Write the value of the field next to it (with the components as icons if they're shown as icons)
+			self.y = baseline;
+			self.x = value_x_min + padding;
+			self.set_current_font_variant(FontVariant::Regular);
+			if *label == "Components" && self.component_style == spells::ComponentStyle::Icons
+			{
+				self.x -= self.calc_text_width(SPACE);
+				self.apply_component_icons(spell, x_max - padding);
+			}
+			else
+			{
+				self.write_textbox
+				(value, value_x_min + padding, x_max - padding, self.y_bottom(), self.y_top(), false, &spell.tables);
+			}
+			// Draws a diagram of the area of effect after the range (if the range has one and there are diagrams)
+			if let spells::SpellField::Controlled(spells::Range::Yourself(Some(aoe))) = &spell.range
+			{
+				if *label == "Range" { self.apply_aoe_glyph(aoe); }
+			}
+			row_y_max = row_y_min;
+		}
+		self.y = row_y_max + newline_amount * 0.25;
+	}
 
-		(doc, title_page, title_layer_ref)
+	/// Returns the width of the column of field names in the quick stats grid and the number of lines that the value
+	/// of each field takes up next to it.
+	fn quick_stats_layout(&mut self, values: &[(&str, String); 4]) -> (Mm, Vec<usize>)
+	{
+		let starting_font_variant = *self.current_font_variant();
+		let padding = self.current_newline_amount() * QUICK_STATS_PADDING;
+		self.set_current_font_variant(FontVariant::Bold);
+		let label_width = values.iter().map(|(label, _)| self.calc_text_width(label)).fold(Mm(0.0), Mm::max);
+		let value_width = self.x_max() - self.x_min() - label_width - padding * 4.0;
+		self.set_current_font_variant(FontVariant::Regular);
+		let line_counts = values.iter()
+		.map(|(_, value)| self.get_textbox_lines(value, value_width, value_width).len().max(1))
+		.collect();
+		self.set_current_font_variant(starting_font_variant);
+		(label_width, line_counts)
 	}
 
-	/// Turns the current page into a title page with the given title.
-	fn make_title_page(&mut self, mut title: &str)
+	/// Draws a small diagram of the shape of an area of effect that sits on the baseline of the current line after a
+	/// space and is as tall as a capital letter (if there are area of effect diagrams and there's room for it).
+	fn apply_aoe_glyph(&mut self, aoe: &spells::Aoe)
 	{
-		// Use the default spellbook title if none was given
-		if title.is_empty() { title = DEFAULT_SPELLBOOK_TITLE; }
-		// Create bookmark for title page
-		self.doc.add_bookmark(TITLE_PAGE_NAME, self.pages[self.current_page_index]);
-		// Adds a background image to the page (if they are desired)
-		self.add_background();
-		// Store the page number data and set it to None so page numbers don't appear in any title pages created
-		let page_number_data = self.page_number_data.clone();
-		self.page_number_data = None;
-		// Write the title to the page
-		self.write_centered_textbox(title, self.x_min(), self.x_max(), self.y_bottom(), self.y_top());
-		// Reset the page number data to what it was before
-		self.page_number_data = page_number_data;
+		let options = match self.aoe_glyphs
+		{
+			Some(options) => options,
+			None => return
+		};
+		let size = self.calc_cap_height();
+		let x_min = self.x + self.calc_text_width(SPACE);
+		if x_min + size > self.x_max() { return; }
+		let rings = aoe_glyph_rings(aoe, x_min + self.column_x_offset(), self.y, size);
+		let mode = if options.filled { PaintMode::Fill } else { PaintMode::Stroke };
+		let color = self.current_text_color().clone();
+		let layer = self.current_layer();
+		layer.save_graphics_state();
+		layer.set_fill_color(color.clone());
+		layer.set_outline_color(color);
+		layer.set_outline_thickness(options.line_thickness);
+		layer.add_polygon(Polygon { rings: rings, mode: mode, winding_order: WindingOrder::NonZero });
+		layer.restore_graphics_state();
+		self.x = x_min + size;
 	}
 
-	/// Adds a page / pages about a spell into the spellbook.
-	fn add_spell(&mut self, spell: &spells::Spell)
+	/// Applies a checkbox at the current position if there are checkboxes in the table of contents.
+	fn apply_table_of_contents_checkbox(&mut self, spell_index: Option<usize>)
 	{
-		// Make a new page for the spell
-		self.make_new_page();
-		// Add a bookmark for the first page of this spell
-		self.doc.add_bookmark(spell.name.clone(), self.pages[self.current_page_index]);
+		match self.preparation_checkboxes
+		{
+			Some(options) if options.in_table_of_contents =>
+			{
+				let field_name = spell_index.and_then(|spell_index| self.preparation_field_name(spell_index));
+				self.apply_checkbox(Pt(options.line_thickness), field_name);
+			},
+			_ => ()
+		}
+	}
 
-		// Writes the spell name to the document
-		self.set_current_text_type(TextType::Header);
-		self.set_current_font_variant(FontVariant::Regular);
-		self.x = self.x_min();
-		self.y = self.y_top();
-		self.write_textbox
-		(&spell.name, self.x_min(), self.x_max(), self.y_bottom(), self.y_top(), false, &spell.tables);
+	/// The name of the fillable preparation checkbox of a spell (`None` if preparation checkboxes aren't fillable).
+	/// The checkbox on a spell's page and its checkbox in the table of contents share the same name.
+	fn preparation_field_name(&self, spell_index: usize) -> Option<String>
+	{
+		match self.form_fields
+		{
+			Some(options) if options.fillable_preparation_checkboxes => Some(format!("prepared_{}", spell_index + 1)),
+			_ => None
+		}
+	}
 
-		// Writes the level and school of the spell to the document
-		self.y -= self.current_newline_amount();
-		self.x = self.x_min();
+	/// Writes a grid of fillable checkboxes for tracking used spell slots at the bottom of the current page with a row
+	/// for each spell level that has slots.
+	fn apply_slot_tracker(&mut self, options: SlotTrackerOptions)
+	{
+		let levels: Vec<_> = (1..=9).zip(options.slots_per_level).filter(|(_, slots)| *slots > 0).collect();
+		if levels.is_empty() { return; }
 		self.set_current_text_type(TextType::Body);
-		self.set_current_font_variant(FontVariant::Italic);
-		self.write_textbox
-		(
-			&spell.get_level_school_text(),
-			self.x_min(),
-			self.x_max(),
-			self.y_bottom(),
-			self.y_top(),
-			false,
-			&spell.tables
-		);
-
-		// Writes the casting time to the document
-		self.y -= self.font_data.get_newline_amount_for(TextType::Header);
+		self.set_current_font_variant(FontVariant::Regular);
+		// Line the checkboxes of every row up after the widest label
+		let labels: Vec<_> = levels.iter().map(|(level, _)| format!("Level {}", level)).collect();
+		let label_width = labels.iter().map(|label| self.calc_text_width(label)).fold(Mm(0.0), Mm::max) +
+			self.calc_text_width(SPACE);
+		// The heading goes above the rows and the last row goes at the bottom of the page
+		let newline_amount = self.current_newline_amount();
 		self.x = self.x_min();
+		self.y = self.y_bottom() + newline_amount * levels.len() as f32;
 		self.set_current_font_variant(FontVariant::Bold);
-		let casting_time = format!("Casting Time: <r> {}", spell.get_casting_time_text());
-		self.write_textbox
-		(&casting_time, self.x_min(), self.x_max(), self.y_bottom(), self.y_top(), false, &spell.tables);
+		self.apply_text("Spell Slots");
+		self.set_current_font_variant(FontVariant::Regular);
+		for ((level, slots), label) in levels.into_iter().zip(labels)
+		{
+			self.y -= newline_amount;
+			self.x = self.x_min();
+			self.apply_text(&label);
+			self.x = self.x_min() + label_width;
+			for slot in 1..=slots
+			{
+				self.apply_checkbox(Pt(options.line_thickness), Some(format!("slot_level_{}_{}", level, slot)));
+			}
+		}
+	}
 
-		// Writes the range to the document
-		self.y -= self.font_data.current_newline_amount();
-		self.x = self.x_min();
-		self.set_current_font_variant(FontVariant::Bold);
-		let range = format!("Range: <r> {}", spell.range.to_string());
-		self.write_textbox
-		(&range, self.x_min(), self.x_max(), self.y_bottom(), self.y_top(), false, &spell.tables);
+	/// The width of a checkbox and the space after it using the current font.
+	fn checkbox_width(&self) -> Mm { self.calc_cap_height() + self.calc_text_width(SPACE) }
 
-		// Writes the components to the document
-		self.y -= self.font_data.current_newline_amount();
-		self.x = self.x_min();
-		self.set_current_font_variant(FontVariant::Bold);
-		let components = format!("Components: <r> {}", spell.get_component_string());
-		self.write_textbox
-		(&components, self.x_min(), self.x_max(), self.y_bottom(), self.y_top(), false, &spell.tables);
+	/// The width of the checkbox at the start of each table of contents entry (0 if there are none).
+	fn table_of_contents_checkbox_width(&self) -> Mm
+	{
+		match self.preparation_checkboxes
+		{
+			Some(options) if options.in_table_of_contents => self.checkbox_width(),
+			_ => Mm(0.0)
+		}
+	}
 
-		// Writes the duration to the document
-		self.y -= self.font_data.current_newline_amount();
-		self.x = self.x_min();
-		self.set_current_font_variant(FontVariant::Bold);
-		let duration = format!("Duration: <r> {}", &spell.duration.to_string());
-		self.write_textbox
-		(&duration, self.x_min(), self.x_max(), self.y_bottom(), self.y_top(), false, &spell.tables);
+	/// Calls the handler of the custom tag at the start of a paragraph (if there is one and the paragraph is in a spell
+	/// description). Returns the length of the tag and what the handler turned it into.
+	fn handle_paragraph_tag(&mut self, paragraph: &str, x_min: Mm, x_max: Mm, y_min: Mm, y_max: Mm)
+	-> Option<(usize, TagOutput)>
+	{
+		if !self.in_description || self.tag_handlers.is_empty() { return None; }
+		let tag = paragraph.split(char::is_whitespace).next().unwrap_or_default();
+		let handler = self.tag_handlers.get(tag)?.clone();
+		// Make sure the tag is on the page it will be applied to
+		self.check_for_new_page();
+		let context = TagContext
+		{
+			page_index: self.current_page_index,
+			x: x_min + self.column_x_offset(),
+			y: self.y,
+			x_min: x_min + self.column_x_offset(),
+			x_max: x_max + self.column_x_offset(),
+			y_min: y_min,
+			y_max: y_max,
+			at_paragraph_start: true,
+			layer: self.current_layer()
+		};
+		Some((tag.len(), handler.handle(tag, &context)))
+	}
 
-		// Get the upcast description prepared if there is one
-		let upcast_description = if let Some(upcast_description) = &spell.upcast_description
+	/// Replaces every custom tag in some text from a spell description with the text its handler gives.
+	/// Tags that get turned into drawings are removed from the text.
+	fn replace_inline_tags<'t>(&self, text: &'t str) -> Cow<'t, str>
+	{
+		if !self.in_description || self.tag_handlers.is_empty() { return Cow::Borrowed(text); }
+		// If there are no custom tags in the text, don't make a new string
+		if !text.split_whitespace().any(|token| self.tag_handlers.get(token).is_some())
 		{
-			// Adds different text at the start based on whether the spell is a cantrip or not
-			let cantrip_upcast_prefix = "Cantrip Upgrade";
-			let leveled_upcast_prefix = "Using a Higher-Level Spell Slot";
-			let upcast_prefix = match &spell.level
-			{
-				spells::SpellField::Controlled(level) => match level
-				{
-					spells::Level::Cantrip => cantrip_upcast_prefix,
-					_ => leveled_upcast_prefix
-				},
-				_ => leveled_upcast_prefix
-			};
-			// Create the upcast description with a newline and font tags
-			format!("\n<bi> {}. <r> {}", upcast_prefix, &upcast_description)
+			return Cow::Borrowed(text);
 		}
-		else { String::new() };
+		let context = TagContext
+		{
+			page_index: self.current_page_index,
+			x: self.x + self.column_x_offset(),
+			y: self.y,
+			x_min: self.x_min() + self.column_x_offset(),
+			x_max: self.x_max() + self.column_x_offset(),
+			y_min: self.y_min(),
+			y_max: self.y_max(),
+			at_paragraph_start: false,
+			layer: self.current_layer()
+		};
+		let tokens: Vec<_> = text.split_whitespace().map(|token| match self.tag_handlers.get(token)
+		{
+			Some(handler) => match handler.handle(token, &context)
+			{
+				TagOutput::Text(text) => text,
+				TagOutput::Block(_) => String::new()
+			},
+			None => String::from(token)
+		}).collect();
+		Cow::Owned(tokens.join(SPACE))
+	}
 
-		// Add the upcast description to the end of the rest of the spell description
-		let description = format!("{}{}", &spell.description, upcast_description);
-		
-		// Writes the description to the document
-		self.y -= self.font_data.get_newline_amount_for(TextType::Header);
-		self.x = self.x_min();
-		self.set_current_font_variant(FontVariant::Regular);
-		self.write_textbox
-		(&description, self.x_min(), self.x_max(), self.y_bottom(), self.y_top(), false, &spell.tables);
+	/// Returns the number of tokens in the glossary term that starts at the first of the given tokens and the anchor
+	/// of that term (if a glossary term starts there and glossary terms are being linked right now).
+	fn glossary_term_at(&self, tokens: &[&str]) -> Option<(usize, String)>
+	{
+		if !self.in_description { return None; }
+		for (words, anchor) in &self.glossary_terms
+		{
+			if tokens.len() < words.len() { continue; }
+			if words.iter().zip(tokens).all(|(word, token)| *word == keyword_form(token))
+			{
+				return Some((words.len(), anchor.clone()));
+			}
+		}
+		None
 	}
 
 	/// Writes text to the current page inside the given dimensions, starting at the x_min value and current y value.
@@ -404,10 +3794,10 @@ impl <'a> SpellbookWriter<'a>
 	(
 		&mut self,
 		text: &str,
-		x_min: f32,
-		x_max: f32,
-		y_min: f32,
-		y_max: f32,
+		x_min: Mm,
+		x_max: Mm,
+		y_min: Mm,
+		y_max: Mm,
 		starting_tab: bool,
 		tables: &Vec<spells::Table>
 	)
@@ -429,11 +3819,11 @@ impl <'a> SpellbookWriter<'a>
 		// The amount to tab the text in by at the start of a paragraph
 		// Is 0.0 for the first non-bullet-point paragraph if `starting_tab` is false 
 		// (to match the Player's Handbook formatting)
-		// Is equal to `self.tab_amount()` for all other paragraphs
+		// Is equal to the tab amount of the current text type for all other paragraphs
 		let mut current_tab_amount = match starting_tab
 		{
-			true => self.tab_amount(),
-			false => 0.0
+			true => self.current_tab_amount(),
+			false => Mm(0.0)
 		};
 		// Split the text into paragraphs by newlines
 		// Collects it into a vec so the `is_empty` method can be used without having to clone a new iterator.
@@ -443,15 +3833,78 @@ impl <'a> SpellbookWriter<'a>
 		// If there is text and the x position is beyond the x_max, reset the x position to x_min and go to a new line
 		else if self.x > x_max { self.x = x_min; self.y -= self.current_newline_amount(); }
 		// Loop through each paragraph
-		for mut paragraph in paragraphs
+		for (paragraph_index, paragraph) in paragraphs.iter().copied().enumerate()
 		{
+			// Page break tags go on their own lines and don't get written
+			let page_break = match paragraph.trim()
+			{
+				// Always start the rest of the text on a new page
+				spells::PAGE_BREAK_TAG => Some(true),
+				// Start the text on a new page if the paragraphs up to the end tag don't fit on this one (unless
+				// they're already at the top of a page)
+				spells::NO_BREAK_START_TAG =>
+				{
+					let region = &paragraphs[paragraph_index + 1..];
+					let region_end = region.iter().position(|paragraph| paragraph.trim() == spells::NO_BREAK_END_TAG)
+						.unwrap_or(region.len());
+					let region_height =
+					self.estimate_paragraphs_height(&region[..region_end], tables, x_min, x_max);
+					Some(self.y - region_height < self.y_min() && self.y < self.y_top())
+				},
+				spells::NO_BREAK_END_TAG => Some(false),
+				_ => None
+			};
+			if let Some(move_to_new_page) = page_break
+			{
+				if move_to_new_page
+				{
+					self.move_to_new_page();
+					// Start the next paragraph at the top of the new page without the extra space that separates it
+					// from bullet lists and tables
+					x_reset = x_min;
+					in_bullet_list = false;
+					in_table = false;
+					paragraph_newline_scalar = 0.0;
+				}
+				continue;
+			}
 			// If a table was just being processed, move down an extra newline amount to keep the table separated
 			// (to match the Player's Handbook Formatting)
 			if in_table { self.y -= self.table_outer_vertical_margin(); }
 			// Move the y position down by 0 or 1 newline amounts
 			// 0 newlines for the first paragraph (so the entire textbox doesn't get moved down by an extra newline)
 			// 1 newline for all other paragraphs
-			else { self.y -= paragraph_newline_scalar * self.current_newline_amount(); }
+			else { self.y -= self.current_newline_amount() * paragraph_newline_scalar; }
+			// Holds the paragraph if a custom tag at the start of it gets replaced with text
+			let replaced_paragraph;
+			let mut paragraph = paragraph;
+			// If the paragraph starts with a custom tag, let its handler decide what to do with it
+			match self.handle_paragraph_tag(paragraph, x_min, x_max, y_min, y_max)
+			{
+				// If the handler drew something, move below it and skip the rest of the paragraph (like tables)
+				Some((_, TagOutput::Block(height))) =>
+				{
+					if in_bullet_list
+					{
+						x_reset = x_min;
+						in_bullet_list = false;
+					}
+					in_paragraph = false;
+					in_table = false;
+					self.x = x_min;
+					self.y -= height;
+					// Make sure the next paragraph starts on a new line below the drawing
+					paragraph_newline_scalar = 1.0;
+					continue;
+				},
+				// If the handler gave text, put it in place of the tag
+				Some((tag_len, TagOutput::Text(text))) =>
+				{
+					replaced_paragraph = format!("{}{}", text, &paragraph[tag_len..]);
+					paragraph = &replaced_paragraph;
+				},
+				None => ()
+			}
 			// Extract the first token from the paragraph to see if this paragraph is a bullet point or a table
 			let (first_token, rest_of_paragraph) = match paragraph.split_once(char::is_whitespace)
 			{
@@ -470,7 +3923,8 @@ impl <'a> SpellbookWriter<'a>
 					// Zero the paragraph flag
 					in_paragraph = false;
 					// Set the value that the x position resets to so it lines up after the bullet point
-					x_reset = self.calc_text_width(DOT_SPACE) + x_min;
+					// (or hangs in by the bullet indent if there is one)
+					x_reset = self.bullet_x_reset(x_min);
 					// If a table was being processed before, zero the table flag and don't go down annother extra
 					// newline since that was already done above
 					if in_table { in_table = false; }
@@ -480,19 +3934,40 @@ impl <'a> SpellbookWriter<'a>
 						// Move the y position down an extra newline amount to separate it from normal paragraphs
 						// (to match the Player's Handbook formatting)
 						// Moves the y position down 0 newlines on the first paragraph, 0 on all others.
-						self.y -= paragraph_newline_scalar * self.current_newline_amount();
+						self.y -= self.current_newline_amount() * paragraph_newline_scalar;
 					}
 				}
+				// Calculate the width that the rest of the text in the bullet point will have to fit inside
+				let width = x_max - x_reset;
+				// Get lines of the rest of the text in this bullet point
+				let lines = self.get_textbox_lines(rest_of_paragraph, width, width);
+				// Move to a new page before the bullet point if its lines need to be kept together there
+				self.keep_paragraph_lines_together(&lines);
 				// Reset the x position to the left side of the text box
 				self.x = x_min;
 				// Checks to see if the text should be applied to the next page or if a new page should be created.
 				self.check_for_new_page();
 				// Applies a bullet point to the page (using a dot even if a dash was used in the string)
 				self.apply_text(DOT_SPACE);
-				// Calculate the width that the rest of the text in the bullet point will have to fit inside
-				let width = x_max - x_reset;
-				// Get lines of the rest of the text in this bullet point
-				self.get_textbox_lines(rest_of_paragraph, width, width)
+				// Start the text of the bullet point at the bullet indent if there is one
+				if self.bullet_indent().is_some() { self.x = x_reset; }
+				lines
+			}
+			// If the paragraph starts with the marker of a paragraph style, write the rest of it in that style
+			else if let Some(style) = self.paragraph_style(first_token).cloned()
+			{
+				// If this paragraph is right after a bullet list, separate it from the list like other paragraphs
+				if in_bullet_list
+				{
+					self.y -= self.current_newline_amount();
+					x_reset = x_min;
+					in_bullet_list = false;
+				}
+				in_table = false;
+				in_paragraph = false;
+				self.write_styled_paragraph(rest_of_paragraph, &style, x_min, x_max);
+				paragraph_newline_scalar = 1.0;
+				continue;
 			}
 			else
 			{
@@ -510,7 +3985,7 @@ impl <'a> SpellbookWriter<'a>
 							// Move the y position down an extra newline amount to separate it more from
 							// normal paragraphs (to match the Player's Handbook formatting)
 							// Moves the y position down 0 newlines on the first paragraph, 0 on all others.
-							self.y -= paragraph_newline_scalar * self.current_newline_amount();
+							self.y -= self.current_newline_amount() * paragraph_newline_scalar;
 							// Set the table flag to signal that a table is being processed
 							in_table = true;
 						}
@@ -538,6 +4013,8 @@ impl <'a> SpellbookWriter<'a>
 					},
 					// If this is an escaped table tag, remove the first backslash
 					TableTagCheckResult::EscapedTableTag => paragraph = &paragraph[1..],
+					// If this is an escaped page break tag, remove the first backslash
+					_ if spells::is_escaped_page_break_tag(first_token) => paragraph = &paragraph[1..],
 					// If this is not a table tag, do nothing
 					_ => ()
 				}
@@ -568,22 +4045,105 @@ impl <'a> SpellbookWriter<'a>
 				self.get_textbox_lines(paragraph, x_max - self.x, x_max - x_reset)
 			};
 			// Apply the lines of text of this paragraph to the spellbook
-			self.apply_text_lines(&lines, x_reset);
+			self.apply_text_lines(&lines, x_reset, true, Some(x_max));
 			// Make it so all paragraphs after the first get moved down a newline amount before being processed
 			paragraph_newline_scalar = 1.0;
 			// If this was a paragraph, set the current tab amount to be the normal tab amount so all paragraphs
 			// after the first are tabbed in on the first line
-			if in_paragraph { current_tab_amount = self.tab_amount(); }
+			if in_paragraph { current_tab_amount = self.current_tab_amount(); }
 		}
 		// If a table was the last thing that was applied to the page, move down an extra newline amount to keep
 		// whatever comes next more separated from the table (to match the Player's Handbook formatting)
 		if in_table { self.y -= self.current_newline_amount(); }
 	}
 
+	/// Returns the paragraph style that a token is the marker of (ex: "::readaloud::") if a spell description is being
+	/// written and the style is in the paragraph styles.
+	fn paragraph_style(&self, token: &str) -> Option<&ParagraphStyle>
+	{
+		if !self.in_description { return None; }
+		spells::paragraph_class(token).and_then(|class| self.paragraph_styles.get(class))
+	}
+
+	/// Writes a paragraph in a paragraph style. The paragraph gets indented on both sides and written in the style's
+	/// font variant and text color with its box behind it (if the style has them).
+	fn write_styled_paragraph(&mut self, paragraph: &str, style: &ParagraphStyle, x_min: Mm, x_max: Mm)
+	{
+		let indent = paragraph_indent(style, x_min, x_max);
+		let starting_font_variant = *self.current_font_variant();
+		if let Some(font_variant) = style.font_variant { self.set_current_font_variant(font_variant); }
+		self.paragraph_color = style.color.map(|color| bytes_to_color(&color));
+		self.color_override = self.paragraph_color.clone();
+		self.paragraph_box = style.background_color.map(|color| (bytes_to_color(&color), x_min, x_max));
+		let width = x_max - x_min - indent * 2.0;
+		let lines = self.get_textbox_lines(paragraph, width, width);
+		self.x = x_min + indent;
+		self.apply_text_lines(&lines, x_min + indent, true, Some(x_max - indent));
+		self.paragraph_box = None;
+		self.paragraph_color = None;
+		self.color_override = None;
+		self.set_current_font_variant(starting_font_variant);
+	}
+
+	/// The x position that lines of bullet points in a textbox get reset to (lined up after the bullet symbol or
+	/// hung in by the bullet indent if there is one).
+	fn bullet_x_reset(&self, x_min: Mm) -> Mm
+	{
+		match self.bullet_indent()
+		{
+			Some(bullet_indent) => bullet_indent + x_min,
+			None => self.calc_text_width(DOT_SPACE) + x_min
+		}
+	}
+
+	/// Estimates how far down the page some paragraphs of a textbox go (including the newline before the first one)
+	/// so they can be kept on the same page.
+	fn estimate_paragraphs_height(&mut self, paragraphs: &[&str], tables: &Vec<spells::Table>, x_min: Mm, x_max: Mm)
+	-> Mm
+	{
+		let mut line_count = 0;
+		let mut tables_height = Mm(0.0);
+		for paragraph in paragraphs
+		{
+			let (first_token, rest_of_paragraph) = match paragraph.split_once(char::is_whitespace)
+			{
+				Some((token_1, token_2)) => (token_1, token_2.trim()),
+				None => (*paragraph, "")
+			};
+			let lines = if first_token == DOT || first_token == DASH
+			{
+				let width = x_max - self.bullet_x_reset(x_min);
+				self.get_textbox_lines(rest_of_paragraph, width, width)
+			}
+			else if let Some(table) = spells::table_tag_index(first_token).and_then(|index| tables.get(index))
+			{
+				// Tables get an extra newline of space before them
+				let starting_text_type = *self.current_text_type();
+				let starting_font_variant = *self.current_font_variant();
+				tables_height += self.layout_table(table, x_min, x_max).height;
+				self.set_current_text_type(starting_text_type);
+				self.set_current_font_variant(starting_font_variant);
+				line_count += 1;
+				continue;
+			}
+			else if spells::is_page_break_tag(paragraph.trim()) { continue; }
+			else if let Some(style) = self.paragraph_style(first_token)
+			{
+				let width = x_max - x_min - paragraph_indent(style, x_min, x_max) * 2.0;
+				self.get_textbox_lines(rest_of_paragraph, width, width)
+			}
+			else { self.get_textbox_lines(paragraph, x_max - x_min - self.current_tab_amount(), x_max - x_min) };
+			line_count += lines.iter().filter(|line| !line.is_empty()).count();
+		}
+		self.current_newline_amount() * line_count as f32 + tables_height
+	}
+
 	/// Returns whether a token is a table tag, an escaped table tag, or neither. Takes a token and the number of
 	/// tables in the current spell as inputs.
 	fn table_tag_check(&self, token: &str, table_count: usize) -> TableTagCheckResult
 	{
+		// If the token is a table tag with backslashes before it (ex: "\[table][0]"), it's an escaped table tag
+		if spells::is_escaped_table_tag(token) { return TableTagCheckResult::EscapedTableTag; }
 		// If there is a table tag in this token (ex: "[table][5]", "[table][0]", etc.)
 		if let Some(pat_match) = self.table_tag_regex.find(token)
 		{
@@ -609,21 +4169,6 @@ impl <'a> SpellbookWriter<'a>
 					// It's a table tag
 					return TableTagCheckResult::TableTag(table_index);
 				}
-				// Check to see if this is an escaped table tag (a backslash or multiple backslashes
-				// before the table tag)
-				// If there is at least one backslash in the first token
-				else if let Some(backslashes_match) = self.backslashes_regex.find(token)
-				{
-					// Get the index range of the backslash pattern match
-					let backslashes_range = backslashes_match.range();
-					// If the backslashes are at the start of the token and right before the table tag
-					// (if the entire token is backslashes followed by a table tag)
-					if backslashes_range.start == 0 && backslashes_range.end == table_tag_range.start
-					{
-						// It's an escaped table tag
-						return TableTagCheckResult::EscapedTableTag;
-					}
-				}
 			}
 		}
 		// In all other cases, it's not a table tag
@@ -634,7 +4179,7 @@ impl <'a> SpellbookWriter<'a>
 	/// If the text is too big to fit in the textbox, it continues into the next page from the top of the page going
 	/// to the bottom and staying within the same horizontal bounds.
 	/// This method can also process font variant changes in the text.
-	fn write_centered_textbox(&mut self, text: &str, x_min: f32, x_max: f32, y_min: f32, y_max: f32)
+	fn write_centered_textbox(&mut self, text: &str, x_min: Mm, x_max: Mm, y_min: Mm, y_max: Mm)
 	{
 		// If either dimensional bounds overlap with each other, do nothing
 		if x_min >= x_max || y_min >= y_max { return; }
@@ -652,16 +4197,53 @@ impl <'a> SpellbookWriter<'a>
 		if lines.len() > max_lines { self.y = y_max; }
 		// If all the lines can fit on one page, calculate what y value to start the text at so it is vertically
 		// centered in the textbox and set the y value to that
-		else { self.y = (y_max / 2.0) + (lines.len() - 1) as f32 / 2.0 * self.current_newline_amount(); }
+		else { self.y = (y_max / 2.0) + self.current_newline_amount() * ((lines.len() - 1) as f32 / 2.0); }
 		// Apply the text lines to the spellbook
 		self.apply_centered_text_lines(&lines, x_min, x_max);
 	}
 
 	/// Parses a table and applies it to the spellbook.
-	fn write_table(&mut self, table: &spells::Table, x_min: f32, x_max: f32, y_min: f32, y_max: f32)
+	#[cfg_attr(feature = "tracing", tracing::instrument(skip_all, fields(table = %table.title)))]
+	fn write_table(&mut self, table: &spells::Table, x_min: Mm, x_max: Mm, y_min: Mm, y_max: Mm)
 	{
 		let starting_text_type = *self.current_text_type();
 		let starting_font_variant = *self.current_font_variant();
+		// Split the table into lines and calculate its size
+		let layout = self.layout_table(table, x_min, x_max);
+		// Calculate the height of the entire page to use it to see if the table / title will fit on a single page
+		let page_height = y_max - y_min;
+		// If either the entire table (unless it can be split) or just the title can fit on a single page but not this
+		// page
+		if (!self.allow_table_split && self.y - layout.height < y_min && layout.height <= page_height) ||
+		(self.y - layout.title_height < y_min && layout.title_height <= page_height)
+		{
+			// Make a new page
+			self.make_new_page();
+			self.y = y_max;
+		}
+		// Page references to the table point to the page its title is on
+		self.add_reference_anchor(&table.title);
+		// Apply the table to the spellbook
+		self.apply_table
+		(
+			&layout.title_lines,
+			&layout.column_label_lines,
+			&layout.cell_lines,
+			&layout.column_data,
+			layout.label_line_count,
+			&layout.cell_line_counts,
+			x_min,
+			x_max
+		);
+		// Reset the text type and font variant so it is the same as what it was before the table
+		self.set_current_text_type(starting_text_type);
+		self.set_current_font_variant(starting_font_variant);
+	}
+
+	/// Splits a table into lines that fit between the given x bounds and calculates its size.
+	/// Leaves the text type and font variant set to the ones for table titles.
+	fn layout_table(&mut self, table: &spells::Table, x_min: Mm, x_max: Mm) -> TableLayout
+	{
 		// Set the text type to table body mode
 		// No need to set the font variant, it resets at the start processing each cell
 		self.set_current_text_type(TextType::TableBody);
@@ -695,45 +4277,31 @@ impl <'a> SpellbookWriter<'a>
 		let title_lines = self.get_textbox_lines(&table.title, total_width, total_width);
 		// Calculate the height of the title text (if there is any)
 		let title_height =
-		if title_lines.len() > 0 { self.calc_text_height(title_lines.len()) } else { 0.0 };
+		if title_lines.len() > 0 { self.calc_text_height(title_lines.len()) } else { Mm(0.0) };
 		// Calculates the height of the whole table to see if it can fit on the current page or even on a single page
 		// Uses if-statements to add margin space between textboxes
 		let table_height =
-		title_height + if labels_height > 0.0 || cell_lines.len() > 0 { self.current_newline_amount() }
-		else { 0.0 } + labels_height + row_heights.iter().sum::<f32>() +
-		(((row_heights.len() - if labels_height > 0.0 {1} else {0}) as f32) * self.table_vertical_cell_margin());
-		// Calculate the height of the entire page to use it to see if the table / title will fit on a single page
-		let page_height = y_max - y_min;
-		// If either the entire table or just the title can fit on a single page but not this page
-		if (self.y - table_height < y_min && table_height <= page_height) ||
-		(self.y - title_height < y_min && title_height <= page_height)
+		title_height + if labels_height > Mm(0.0) || cell_lines.len() > 0 { self.current_newline_amount() }
+		else { Mm(0.0) } + labels_height + row_heights.iter().fold(Mm(0.0), |sum, height| sum + *height) +
+		(self.table_vertical_cell_margin() * (row_heights.len() - if labels_height > Mm(0.0) {1} else {0}) as f32);
+		TableLayout
 		{
-			// Make a new page
-			self.make_new_page();
-			self.y = y_max;
+			title_lines: title_lines,
+			column_label_lines: column_label_lines,
+			cell_lines: cell_lines,
+			column_data: column_data,
+			label_line_count: label_line_count,
+			cell_line_counts: cell_line_counts,
+			title_height: title_height,
+			height: table_height
 		}
-		// Apply the table to the spellbook
-		self.apply_table
-		(
-			&title_lines,
-			&column_label_lines,
-			&cell_lines,
-			&column_data,
-			label_line_count,
-			&cell_line_counts,
-			x_min,
-			x_max
-		);
-		// Reset the text type and font variant so it is the same as what it was before the table
-		self.set_current_text_type(starting_text_type);
-		self.set_current_font_variant(starting_font_variant);
 	}
 
 	/// Gets the widths of the widest cells in each column and returns those widths along with the index of the
 	/// column that width belongs to so the vec can be sorted by width later and the widths can still be tracable
 	/// to which column that is the width of.
 	fn get_max_table_column_widths(&mut self, column_labels: &Vec<String>, cells: &Vec<Vec<String>>)
-	-> Vec<(usize, f32)>
+	-> Vec<(usize, Mm)>
 	{
 		// Create a vec to hold the column widths and their associated indexes
 		let mut column_widths = Vec::with_capacity(column_labels.len());
@@ -760,11 +4328,11 @@ impl <'a> SpellbookWriter<'a>
 				let cell_lines = self.get_textbox_lines
 				(
 					&cells[row_index][column_index],
-					f32::INFINITY,
-					f32::INFINITY
+					Mm(f32::INFINITY),
+					Mm(f32::INFINITY)
 				);
 				// Calculate the width of the cell (taking font switches into account) or use 0 if its empty
-				let cell_width = if cell_lines.len() > 0 { cell_lines[0].width() } else { 0.0 };
+				let cell_width = if cell_lines.len() > 0 { Mm(cell_lines[0].width()) } else { Mm(0.0) };
 				// If a max width for this column already exists
 				if column_index < column_widths.len()
 				{
@@ -785,11 +4353,11 @@ impl <'a> SpellbookWriter<'a>
 	fn get_table_column_width_data
 	(
 		&self,
-		max_column_widths: &Vec<(usize, f32)>,
-		x_min: f32,
-		x_max: f32
+		max_column_widths: &Vec<(usize, Mm)>,
+		x_min: Mm,
+		x_max: Mm
 	)
-	-> Vec<(f32, bool)>
+	-> Vec<(Mm, bool)>
 	{
 		// Keeps track of the number of columns in `usize` and `f32`
 		let column_count = max_column_widths.len();
@@ -797,7 +4365,7 @@ impl <'a> SpellbookWriter<'a>
 		// Vec that stores the data for each column (width and whether its centered or not)
 		// It's pointless to use `default_column_width` as the default width value instead here of 0.0 in this vec
 		// since `default_column_width` changes over the course of the loop and needs to be reassigned anyways
-		let mut column_data = vec![(0.0, false); column_count];
+		let mut column_data = vec![(Mm(0.0), false); column_count];
 		// Sort the max width of each column in order of least to greatest
 		// MUST parse columns in order of thinnest to widest because the default column width widens as it goes, and
 		// that might make it so a column that might've been made skinnier could've actually been wider if the
@@ -806,7 +4374,7 @@ impl <'a> SpellbookWriter<'a>
 		sorted_max_widths.sort_by(|(_, a), (_, b)| a.partial_cmp(&b).expect(format!
 		(
 			"Failed to compare 2 `f32`s in `dnd_spellbook_maker::spellbook_writer::SpellbookWriter::get_column_width_data`: {} and {}",
-			a, b
+			a.0, b.0
 		).as_str()));
 		// Calculate the maximum width of a table within the given x and y boundries along with the outer margin
 		// option
@@ -846,10 +4414,10 @@ impl <'a> SpellbookWriter<'a>
 	}
 
 	/// Calculates the width of a table based on the width of its columns and the margin space between cells.
-	fn get_table_width(&self, column_data: &Vec<(f32, bool)>) -> f32
+	fn get_table_width(&self, column_data: &Vec<(Mm, bool)>) -> Mm
 	{
 		// Adds up all of the column widths together
-		let mut column_width_sum = 0.0;
+		let mut column_width_sum = Mm(0.0);
 		for column in column_data { column_width_sum += column.0; }
 		// Returns the sum of the column widths plus the margin space between each cell
 		column_width_sum + self.table_horizontal_cell_margin() * ((column_data.len() as f32) - 1.0)
@@ -858,7 +4426,7 @@ impl <'a> SpellbookWriter<'a>
 	/// Takes a vec of tuples containing column widths and bools of whether or not that column is centered, the width
 	/// of the entire table, and returns a vec of data for each column (horizontal column bounds (x_min and x_max
 	/// values) and the bool of whether or not that column has centered text).
-	fn get_column_data(&self, column_width_data: &Vec<(f32, bool)>, table_width: f32)
+	fn get_column_data(&self, column_width_data: &Vec<(Mm, bool)>, table_width: Mm)
 	-> Vec<TableColumnData>
 	{
 		// Vec that holds the x_min and x_max values along with a bool that tells whether or not the column
@@ -888,7 +4456,7 @@ impl <'a> SpellbookWriter<'a>
 
 	/// Takes a 2D vec of cells from a table and the widths of each column in the table, divides each cell into
 	/// lines, and returns a 3D vec of those lines for each cell along with the width of each line.
-	fn get_table_cells_lines(&mut self, cells: &Vec<Vec<String>>, column_width_data: &Vec<(f32, bool)>)
+	fn get_table_cells_lines(&mut self, cells: &Vec<Vec<String>>, column_width_data: &Vec<(Mm, bool)>)
 	-> Vec<Vec<Vec<TextLine>>>
 	{
 		// Create the vec of lines to be returned along with their widths
@@ -913,7 +4481,7 @@ impl <'a> SpellbookWriter<'a>
 	(
 		&mut self,
 		row: &Vec<String>,
-		column_width_data: &Vec<(f32, bool)>,
+		column_width_data: &Vec<(Mm, bool)>,
 		start_font_variant: FontVariant
 	)
 	-> Vec<Vec<TextLine>>
@@ -961,7 +4529,7 @@ impl <'a> SpellbookWriter<'a>
 	}
 
 	/// Calculates the height of each row in a table and returns the height for each of those rows.
-	fn calc_table_row_heights(&self, row_line_counts: &Vec<usize>) -> Vec<f32>
+	fn calc_table_row_heights(&self, row_line_counts: &Vec<usize>) -> Vec<Mm>
 	{
 		// Keeps track of the height of each row
 		let mut row_heights = Vec::with_capacity(row_line_counts.len());
@@ -983,8 +4551,8 @@ impl <'a> SpellbookWriter<'a>
 		column_data: &Vec<TableColumnData>,
 		label_line_count: usize,
 		row_line_counts: &Vec<usize>,
-		x_min: f32,
-		x_max: f32
+		x_min: Mm,
+		x_max: Mm
 	)
 	{
 		// If there's no column data, no nothing
@@ -1020,14 +4588,15 @@ impl <'a> SpellbookWriter<'a>
 		&mut self,
 		label_line_count: usize,
 		row_line_counts: &Vec<usize>,
-		x_min: f32,
-		x_max: f32
+		x_min: Mm,
+		x_max: Mm
 	)
 	{
 		// Keeps track of whether or not to put a line on this row (true to put a line)
 		let mut off_row = false;
 		// Moves the y position by a bit when a line is applied
-		let y_adjuster = self.current_font_size() * self.table_off_row_color_lines_y_adjust_scalar();
+		// (the scalar is tuned to turn the font size into an offset in Mm, so the font size is used as a plain number)
+		let y_adjuster = Mm(self.current_font_size().0 * self.table_off_row_color_lines_y_adjust_scalar());
 		// Makes the y position move down each time a new line is being traversed
 		// Makes it so the y position doesn't go down on the first line but goes down every row after that
 		let mut newline_scalar = 0.0;
@@ -1096,14 +4665,14 @@ impl <'a> SpellbookWriter<'a>
 	}
 
 	/// Applies a single table color line to the table.
-	fn apply_table_color_line(&mut self, line_height: f32, x_min: f32, x_max: f32, y_adjust: f32)
+	fn apply_table_color_line(&mut self, line_height: Mm, x_min: Mm, x_max: Mm, y_adjust: Mm)
 	{
 		// Creates the points of each end of the line (a bit higher than normal to compensate for all lines being a
 		// bit off vertically)
 		let points = vec!
 		[
-			(Point::new(Mm(x_min), Mm(self.y + y_adjust)), false),
-			(Point::new(Mm(x_max), Mm(self.y + y_adjust)), false)
+			(Point::new(x_min + self.column_x_offset(), self.y + y_adjust), false),
+			(Point::new(x_max + self.column_x_offset(), self.y + y_adjust), false)
 		];
 		// Create the line
 		let line = Line
@@ -1114,7 +4683,8 @@ impl <'a> SpellbookWriter<'a>
 		// Set the color of the line
 		self.current_layer().set_outline_color(self.table_off_row_color().clone());
 		// Set the thickness of the line
-		self.current_layer().set_outline_thickness(line_height * self.table_off_row_color_lines_height_scalar());
+		// (the scalar is tuned to turn the line height into a thickness in Pt, so the height is used as a plain number)
+		self.current_layer().set_outline_thickness(line_height.0 * self.table_off_row_color_lines_height_scalar());
 		// Apply the line to the page
 		self.current_layer().add_line(line);
 	}
@@ -1129,7 +4699,7 @@ impl <'a> SpellbookWriter<'a>
 	)
 	{
 		// Makes it so the first line doesn't move down at all at the start
-		let mut row_vertical_adjuster = 0.0;
+		let mut row_vertical_adjuster = Mm(0.0);
 		// If there are column labels
 		if column_label_lines.len() > 0
 		{
@@ -1200,6 +4770,9 @@ impl <'a> SpellbookWriter<'a>
 	/// Applies a single cell from a table to the spellbook.
 	fn apply_table_cell(&mut self, cell: &Vec<TextLine>, column_data: &TableColumnData)
 	{
+		// Remember where the top of the cell is so it can be outlined
+		let start_page_index = self.current_page_index;
+		let (_, start_y_max) = self.current_line_y_bounds();
 		// If the column this cell is in is a centered text column
 		if column_data.centered
 		{
@@ -1210,8 +4783,33 @@ impl <'a> SpellbookWriter<'a>
 		{
 			// Set the x position to the left side of the cell
 			self.x = column_data.x_min;
-			// Write this cell's text to the document in a left-aligned textbox
-			self.apply_text_lines(cell, column_data.x_min);
+			// Write this cell's text to the document in a left-aligned textbox (justified if table cells get justified)
+			let justify_to = match self.justification
+			{
+				Some(options) if options.tables => Some(column_data.x_max),
+				_ => None
+			};
+			self.apply_text_lines(cell, column_data.x_min, false, justify_to);
+		}
+		self.add_debug_table_cell(column_data, start_page_index, start_y_max);
+	}
+
+	/// Outlines a table cell that starts at the top of the given page and ends at the current position (if the
+	/// layout is being debugged). Cells that go across multiple pages get an outline on each page.
+	fn add_debug_table_cell(&mut self, column_data: &TableColumnData, start_page_index: usize, start_y_max: Mm)
+	{
+		if !self.debug_layout { return; }
+		let x_min = column_data.x_min + self.column_x_offset();
+		let x_max = column_data.x_max + self.column_x_offset();
+		let (end_y_min, _) = self.current_line_y_bounds();
+		let newline_amount = self.current_newline_amount();
+		for page_index in start_page_index..=self.current_page_index
+		{
+			let y_max = if page_index == start_page_index { start_y_max }
+			else { self.y_top() + newline_amount * 3.0 / 4.0 };
+			let y_min = if page_index == self.current_page_index { end_y_min }
+			else { self.y_min() - newline_amount / 4.0 };
+			self.debug_lines.push((page_index, outline_rect(x_min, y_min, x_max, y_max), DEBUG_TABLE_CELL_COLOR));
 		}
 	}
 
@@ -1219,356 +4817,150 @@ impl <'a> SpellbookWriter<'a>
 	/// Applies lines to a text box so that the text is left aligned.
 	/// `x_reset` is the value that the x position gets reset to after it applies each line.
 	/// `y_min` is the minimum y value on the page.
-	fn apply_text_lines(&mut self, text_lines: &Vec<TextLine>, x_reset: f32)
+	/// `keep_together` is whether or not the lines are a paragraph that follows the paragraph break options (if there
+	/// are any) when it goes across a page break.
+	/// `justify_to` is the right side of the text box that lines get justified to (if there are justification options)
+	/// or `None` to leave the lines ragged-right.
+	fn apply_text_lines(&mut self, text_lines: &Vec<TextLine>, x_reset: Mm, keep_together: bool, justify_to: Option<Mm>)
 	{
+		// The right side of the text box and the justification options (if the lines get justified)
+		let justification = match (justify_to, self.justification)
+		{
+			(Some(x_max), Some(options)) => Some((x_max, options)),
+			_ => None
+		};
+		// The last line of a paragraph doesn't get justified
+		let last_line_index = text_lines.iter().rposition(|line| !line.is_empty());
+		// The number of lines to apply before moving to a new page early (if there aren't enough lines left over to go
+		// at the top of the next page)
+		let early_break = match keep_together
+		{
+			true => self.keep_paragraph_lines_together(text_lines),
+			false => None
+		};
 		// The number of newlines to go down by before each line is printed
 		// Is 0.0 for the first line (so the textbox doesn't get moved down by an extra newline)
 		// Is 1.0 for all other lines
 		let mut newline_scalar = 0.0;
+		// The number of lines that have been applied so far
+		let mut line_count = 0;
 		// Loop through each line to apply it to the document
-		for line in text_lines
+		for (index, line) in text_lines.iter().enumerate()
 		{
 			if line.is_empty() { continue; }
+			// Move to a new page if the page break needs to come before this line
+			if early_break == Some(line_count) { self.move_to_new_page(); }
 			// Move the y position down by 0 or 1 newline amounts
 			// 0 newlines for the first line (so the textbox doesn't get moved down by an extra newline)
 			// 1 newline for all other lines
-			self.y -= newline_scalar * self.current_newline_amount();
+			else { self.y -= self.current_newline_amount() * newline_scalar; }
 			// Make it so all lines after the first will move down 1 newline amount before being applied to the page
 			newline_scalar = 1.0;
-			// Apply the line to the page
-			self.apply_text_line(line);
-			self.x = x_reset;
-		}
-	}
-
-	/// Takes a string along with a maximum width for lines to fit into, separates the string into lines of tokens
-	/// that fit within the max width, and returns a vec of those lines.
-	fn get_textbox_lines(&mut self, text: &str, first_line_width: f32, textbox_width: f32) -> Vec<TextLine>
-	{
-		// Get all tokens separated by whitespace
-		// Collects it into a vec so the `is_empty` method can be used without having to clone a new iterator.
-		let mut tokens: Vec<_> = text.split_whitespace().collect();
-		// If there is no text, do nothing
-		if tokens.is_empty() { return Vec::new(); }
-		// Store the font variant at the start so the current font variant can be reset to it after constructing the
-		// lines of text since the current font variant will change while calculating line widths
-		let start_font_variant = *self.current_font_variant();
-		// Keeps track of the current max textbox width
-		// Uses `first_line_width` for the first line and `textbox_width` for all lines after that
-		let mut current_line_max_width = first_line_width;
-		// Vec containing each line of text to write to the textbox
-		let mut lines: Vec<TextLine> = Vec::with_capacity(1);
-		// Keeps track of the next line of tokens to fill up and add to the vec of lines
-		let mut line = TextLine::with_capacity
-		(
-			tokens.len(),
-			*self.current_text_type(),
-			*self.current_font_variant()
-		);
-		// Loop through each token to measure how many lines there will be and how long each line is
-		for i in 0..tokens.len()
-		{
-			match tokens[i]
+			line_count += 1;
+			// Stretch the spaces in the line so it reaches the right side of the text box (if it gets justified)
+			if let Some((x_max, options)) = &justification
 			{
-				// If It's a font tag, add the tag to the line and switch the current font variant so width can be
-				// calculated correctly for the following tokens
-				REGULAR_FONT_TAG =>
-				{
-					line.add_font_tag(FontVariant::Regular,);
-					self.set_current_font_variant(FontVariant::Regular);
-				},
-				BOLD_FONT_TAG =>
-				{
-					line.add_font_tag(FontVariant::Bold,);
-					self.set_current_font_variant(FontVariant::Bold);
-				},
-				ITALIC_FONT_TAG =>
+				if Some(index) != last_line_index
 				{
-					line.add_font_tag(FontVariant::Italic);
-					self.set_current_font_variant(FontVariant::Italic);
-				},
-				BOLD_ITALIC_FONT_TAG | ITALIC_BOLD_FONT_TAG =>
-				{
-					line.add_font_tag(FontVariant::BoldItalic);
-					self.set_current_font_variant(FontVariant::BoldItalic);
-				},
-				// If it's not a special token, calculate its width and determine what to do from there
-				_ =>
-				{
-					// If the token is an escaped font tag, remove the first backslash at the start
-					if self.is_escaped_font_tag(tokens[i]) { tokens[i] = &tokens[i][1..]; }
-					// Declare a width variable that will be calculated when the tokens is hyphenated
-					#[allow(unused_assignments)]
-					let mut width = 0.0;
-					// Hyphenate the token if it's too long to fit on a line and compute its width
-					(tokens[i], width) = self.hyphenate_token
-					(
-						tokens[i],
-						&mut current_line_max_width,
-						textbox_width,
-						&mut line,
-						&mut lines
-					);
-					// If the line is currently empty
-					if line.width() == 0.0
-					{
-						// Put the token into the line
-						let text_token = TextToken::with_width(tokens[i], width);
-						line.add_text(text_token, self.space_widths());
-					}
-					// If the line is not empty
-					else if line.width() > 0.0
-					{
-						// Calculate the width of the current token with a space in front of it
-						let padded_width = line.get_last_space_width(self.space_widths()) + width;
-						// If adding this token to the line would make it go outside the textbox,
-						// apply the current line and set it to just the current token
-						if line.width() + padded_width > current_line_max_width
-						{
-							// Make sure the line doesn't have any excess capacity in its vec
-							line.shrink_to_fit();
-							// Add the current line to the vec of lines
-							lines.push(line);
-							// Create a new line with the capacity of the number of remaining tokens
-							line = TextLine::with_capacity
-							(
-								tokens.len() - i,
-								*self.current_text_type(),
-								*self.current_font_variant()
-							);
-							// Add the token to the start of the new line
-							let text_token = TextToken::with_width(tokens[i], width);
-							line.add_text(text_token, self.space_widths());
-							// Set the max width width to the textbox width in case the previous line was the first
-							// line
-							current_line_max_width = textbox_width;
-						}
-						// If this token can fit on the line, add it to the line
-						else
-						{
-							// Add this token to the line
-							let text_token = TextToken::with_width(tokens[i], width);
-							line.add_text(text_token, self.space_widths());
-						}
-					}
-					// If the line has a negative width
-					else
-					{
-						panic!("Line width is less than 0.0 in `dnd_spellbook_maker::spellbook_writer::SpellbookWriter::get_textbox_lines`");
-					}
+					self.word_spacing = self.justified_word_spacing(line, *x_max, options);
 				}
 			}
+			// Apply the line to the page
+			self.apply_text_line(line);
+			self.word_spacing = Mm(0.0);
+			self.x = x_reset;
 		}
-		// Make sure the line doesn't have any excess capacity in its vec
-		line.shrink_to_fit();
-		// Push the remaining text in the last line to the vec of lines
-		lines.push(line);
-		// Set the font variant back to what it's supposed to be at the start of the text
-		self.set_current_font_variant(start_font_variant);
-		// Return the lines of text
-		lines
 	}
 
-	/// Constructs and returns a text token without a precalculated width.
-	// fn get_text_token(&self, token: &str, font_variant: FontVariant) -> TextToken
-	// {
-	// 	let font_size_data = self.font_data.get_size_data_for(font_variant);
-	// 	let scalar = self.font_data.get_scalar_for(font_variant);
-	// 	TextToken::new(token, font_size_data, self.current_font_scale(), scalar)
-	// }
-
-	/// Returns whether or not a token / string is an escaped font tag (font tag with any amount of backslashes
-	/// before it).
-	fn is_escaped_font_tag(&self, token: &str) -> bool
+	/// The extra width each space in a line needs to reach the right side of a text box from the current x position.
+	/// Returns 0 if the line has too few spaces or if they would have to be stretched too far, so the line is left
+	/// ragged-right instead.
+	fn justified_word_spacing(&self, line: &TextLine, x_max: Mm, options: &JustificationOptions) -> Mm
 	{
-		// Determine whether or not there is an escaped font tag in the token
-		match self.escaped_font_tag_regex.find(token)
+		// Every word in the line has a space between it and the next word
+		let spaces = line.tokens().iter().filter(|token| matches!(token, Token::Text(_))).count().saturating_sub(1);
+		if spaces == 0 || spaces < options.min_spaces { return Mm(0.0); }
+		let space_width = self.calc_text_width(SPACE);
+		let word_spacing = (x_max - self.x - Mm(line.width())) / spaces as f32;
+		// Big gaps between words look worse than a ragged edge
+		if word_spacing <= Mm(0.0) || (space_width + word_spacing) / space_width > options.max_space_stretch
 		{
-			// If there is an escaped font tag in the token
-			Some(pat_match) =>
-			{
-				// If the escaped font tag is the entire token
-				if pat_match.range() == (Range { start: 0, end: token.len() }) { true }
-				else { false }
-			},
-			None => false
+			Mm(0.0)
 		}
+		else { word_spacing }
 	}
 
-	/// If the given token is too wide to fit on a single line within the given textbox constraints, hyphenate it and
-	/// apply it to the spellbook until the end of it is reached and it can fit in a single line without being
-	/// hyphenated.
-	/// Takes the current max width (which might be shorter on the first line of a textbox) and sets it to the
-	/// textbox width if the token is hyphenated and a line is applied.
-	/// Takes the current line and the vec of lines being processed to modify them if the token is hyphenated.
-	/// Returns the token and its calculated width if it was short enough to fit on a line, otherwise it returns the
-	/// end of the hyphenated token and its width.
-	fn hyphenate_token<'t>
-	(
-		&mut self,
-		mut token: &'t str,
-		current_line_max_width: &mut f32,
-		textbox_width: f32,
-		current_line: &mut TextLine,
-		lines: &mut Vec<TextLine>
-	)
-	-> (&'t str, f32)
+	/// Looks ahead at how many lines of a paragraph fit in the space left on the page before its first line gets
+	/// applied so the paragraph break options can be followed.
+	/// Moves to a new page if too few lines of the paragraph would be left at the bottom of this page.
+	/// Returns the number of lines to apply before moving to a new page if too few lines would be carried over to
+	/// the top of the next page (`None` if the page break doesn't need to be moved up).
+	fn keep_paragraph_lines_together(&mut self, text_lines: &Vec<TextLine>) -> Option<usize>
 	{
-		// Calculate the width of the token
-		let mut width = self.calc_text_width(token);
-		// If the line is empty and the token is wider than the current line
-		if current_line.width() == 0.0 && width > *current_line_max_width
-		{
-			// Hyphenate the token using the current line's width
-			(token, width) = self.hyphenate_once
-			(
-				token,
-				width,
-				*current_line_max_width,
-				current_line,
-				lines
-			);
-		}
-		// If the token is wider than the textbox width
-		else if width > textbox_width
+		let options = self.paragraph_breaks?;
+		// If the first line is going on a new page anyway, there isn't a page break to worry about
+		if self.y < self.y_min() { return None; }
+		let line_count = text_lines.iter().filter(|line| !line.is_empty()).count();
+		// The number of lines that fit between the current y position and the bottom of the page
+		let lines_that_fit = ((self.y - self.y_min()) / self.current_newline_amount()) as usize + 1;
+		// If the whole paragraph fits on this page, there's no page break in it
+		if lines_that_fit >= line_count { return None; }
+		// The number of lines left on this page if the page break gets moved up to carry enough lines over to the
+		// next page
+		let lines_before_break = lines_that_fit.min(line_count.saturating_sub(options.min_lines_after_break.max(1)));
+		if lines_before_break >= options.min_lines_before_break.max(1)
 		{
-			// Hyphenate the token using the remaining width on the current line
-			let remaining_width =
-			*current_line_max_width - current_line.width() - current_line.get_last_space_width(self.space_widths());
-			(token, width) = self.hyphenate_once
-			(
-				token,
-				width,
-				remaining_width,
-				current_line,
-				lines
-			);
+			if lines_before_break < lines_that_fit { Some(lines_before_break) }
+			else { None }
 		}
-		// If the token fits on the current line and doesn't need to be hyphenated, just return it and its width
-		else { return (token, width); }
-		// Reset the current line width to the width of the textbox since a line just had to have been applied to get
-		// to this point and it is no longer the first line (which is the only line that could have a different
-		// width than the rest)
-		*current_line_max_width = textbox_width;
-		// Hyphenate the token until just the end of it remains and it can fit on a single line
-		while width > textbox_width
+		// If not enough lines would be left on this page, move the whole paragraph to the next page (unless it's
+		// already at the top of a page, where it would just run out of room again)
+		else
 		{
-			(token, width) = self.hyphenate_once(token, width, textbox_width, current_line, lines);
+			if self.y < self.y_top() { self.move_to_new_page(); }
+			None
 		}
-		// Return the end of the token and its width
-		(token, width)
 	}
 
-	/// Hyphenates it a single time, applies and resets the current line, and returns the rest of the hyphenated
-	/// token along with its width if the token is too big to fit on a line. Otherwise it just returns the token the
-	/// way it is along with its width.
-	fn hyphenate_once<'t>
-	(
-		&mut self,
-		mut token: &'t str,
-		mut width: f32,
-		textbox_width: f32,
-		current_line: &mut TextLine,
-		lines: &mut Vec<TextLine>
-	)
-	-> (&'t str, f32)
-	{
-		// If its small enough to fit on the line, return it the way it is along with its width
-		if width <= textbox_width { return (token, width); }
-		// Hyphenates the string and gets the hyphenated part as a `TextToken` and an index for where the rest of it
-		// starts in the string
-		let (hyphenated_token, index) = self.get_hyphen_str(token, width, textbox_width);
-		// If the token could be hyphenated to fit on the line (if the returned index is 0, that means the token was
-		// either too close to the end of the line to be hyphenated or has characters that are too wide to fit in the
-		// textbox)
-		if index > 0
-		{
-			// If the token was hyphenated
-			if index < token.len()
-			{
-				// Add the hyphenated part of the token to the current line
-				current_line.add_text(hyphenated_token, self.space_widths());
-				// Make sure there isn't any extra capacity in the line's token vec
-				current_line.shrink_to_fit();
-			}
-			// If the returned index was the length of the token (which means it could fit on the line without being
-			// hyphenated)
-			else
-			{
-				// Return the token the way it is along with its width
-				return (token, width);
-			}
-		}
-		// Apply the line
-		lines.push(current_line.clone());
-		// Empty the line
-		*current_line = TextLine::with_capacity(1, *self.current_text_type(), *self.current_font_variant());
-		// Chop off the hyphenated part from the token
-		token = &token[index..];
-		// Recalculate the width of the token
-		width = self.calc_text_width(token);
-		// Return the token and its width
-		(token, width)
-	}
-
-	/// Takes a string that is too wide to fit on a single line in a textbox and finds the cutoff / delimiter index
-	/// so that `&text[0..index] + '-'` fits inside the textbox, along with that hyphenated string itself
-	fn get_hyphen_str(&self, text: &str, token_width: f32, textbox_width: f32) -> (TextToken, usize)
-	{
-		// Keeps track of the last hyphenated part of the text that was measured
-		#[allow(unused_assignments)]
-		let mut hyphenated_string = String::new();
-		// Keeps track of the width of the hyphenated part of the text
-		let mut hyphen_str_width = token_width;
-		// If the string can fit in the textbox, return an empty text token and the inputted token's length
-		if hyphen_str_width <= textbox_width { return (TextToken::empty(), text.len()); }
-		// Lower and upper possible bounds for what the index could be
-		let mut lower_bound = 0;
-		let mut upper_bound = text.len();
-		// The current index being tested
-		let mut index = upper_bound / 2;
-		// Do - While loop until index and last_index are equal
-		// Binary search for the index where the text plus a hyphen at the end is as long as possible without going
-		// outside the textbox
-		while
-		{
-			// Store index in last index so the loop can know when to end
-			let last_index = index;
-			// Get a string of the start of the text up to the index with a hyphen at the end
-			hyphenated_string = format!("{}-", &text[0..index]);
-			// Calculate the width of the hyphenated string
-			hyphen_str_width = self.calc_text_width(&hyphenated_string);
-			// If the width is exactly the width of the textbox, return the current hyphen string data
-			if hyphen_str_width == textbox_width
-			{
-				let new_token = TextToken::with_width(&hyphenated_string, hyphen_str_width);
-				return (new_token, index);
-			}
-			// If the width is less than the width of the textbox
-			else if hyphen_str_width < textbox_width
-			{
-				// Increase the lower bound to the index
-				lower_bound = index;
-				// Increase the index to be between the lower bound and upper bound
-				index += (upper_bound - lower_bound) / 2;
-			}
-			// If the width is greater than the width of the textbox
-			else
-			{
-				// Decrease the upper bound to the index
-				upper_bound = index;
-				// Decrease the index to be between the lower and upper bound
-				index -= (upper_bound - lower_bound) / 2;
-			}
-			// Do - While condition
-			index != last_index
-		}{}
-		// If the index is 0, set the return token to be empty
-		let new_token = if index == 0 { TextToken::empty() }
-		// Otherwise set the return token to be the part of the string that was hyphenated in the last loop iteration
-		else { TextToken::with_width(&hyphenated_string, hyphen_str_width) };
-		// Return the token and index
-		(new_token, index)
+	/// Takes a string along with a maximum width for lines to fit into, separates the string into lines of tokens
+	/// that fit within the max width, and returns a vec of those lines.
+	/// Custom tags get replaced and glossary terms get linked in spell descriptions.
+	pub(crate) fn get_textbox_lines(&self, text: &str, first_line_width: Mm, textbox_width: Mm) -> Vec<TextLine>
+	{
+		// Replace any custom tags with the text their handlers give
+		let text = self.replace_inline_tags(text);
+		// Replace page references with text that saves space for their page numbers
+		let text = replace_page_references(&text);
+		let measurer = SpellbookTextMeasurer
+		{
+			font_data: &self.font_data,
+			space_widths: &self.space_widths,
+			text_type: *self.current_text_type()
+		};
+		// Use the lines the text was split into last time (if it's in the layout cache)
+		let key = self.layout_cache.as_ref().map(|cache|
+		{
+			let state = (*self.current_text_type(), *self.current_font_variant(), self.in_description);
+			let key = layout_cache::text_lines_key
+			(self.layout_options_hash, &text, (first_line_width.0, textbox_width.0), &state);
+			(cache, key)
+		});
+		if let Some(lines) = key.and_then(|(cache, key)| cache.get_lines(key)) { return lines; }
+		// Link glossary terms to the glossary
+		let keyword_link = |tokens: &[&str]| self.glossary_term_at(tokens)
+			.map(|(word_count, anchor)| (word_count, LinkTarget::Anchor(anchor)));
+		let lines = layout::wrap_text_with_features
+		(
+			&text,
+			first_line_width.0,
+			textbox_width.0,
+			*self.current_font_variant(),
+			&measurer,
+			&keyword_link,
+			self.hyphenator.as_ref()
+		);
+		if let Some((cache, key)) = key { cache.insert_lines(key, lines.clone()); }
+		lines
 	}
 
 	/// Applies lines of text to the spellbook so that each line is centered horizontally.
@@ -1576,8 +4968,8 @@ impl <'a> SpellbookWriter<'a>
 	(
 		&mut self,
 		text_lines: &Vec<TextLine>,
-		x_min: f32,
-		x_max: f32
+		x_min: Mm,
+		x_max: Mm
 	)
 	{
 		let textbox_width = x_max - x_min;
@@ -1592,12 +4984,12 @@ impl <'a> SpellbookWriter<'a>
 			// Move the y position down by 0 or 1 newline amounts
 			// 0 newlines for the first line (so the textbox doesn't get moved down by an extra newline)
 			// 1 newline for all other lines
-			self.y -= newline_scalar * self.current_newline_amount();
+			self.y -= self.current_newline_amount() * newline_scalar;
 			// Make it so all lines after the first will move down 1 newline amount before being applied to the page
 			newline_scalar = 1.0;
 			// Calculate where to set the x position so that the line is horizontally centered in the textbox and set
 			// the x value to that
-			self.x = (textbox_width / 2.0) - (line.width() / 2.0) + x_min;
+			self.x = (textbox_width / 2.0) - (Mm(line.width()) / 2.0) + x_min;
 			// Apply the line to the page
 			self.apply_text_line(line);
 		}
@@ -1610,6 +5002,32 @@ impl <'a> SpellbookWriter<'a>
 		if line.is_empty() { return; }
 		// Checks to see if the text should can fit on this page or needs to move to a new page.
 		self.check_for_new_page();
+		// Save space at the bottom of the page for the footnotes of any footnote numbers in the line
+		self.reserve_footnotes(line);
+		// Fill in the part of the paragraph's box that's behind the line (if the paragraph has a box)
+		if let Some((color, x_min, x_max)) = self.paragraph_box.clone()
+		{
+			let (y_min, y_max) = self.current_line_y_bounds();
+			let offset = self.column_x_offset();
+			let layer = self.current_layer();
+			layer.save_graphics_state();
+			layer.set_fill_color(color);
+			layer.add_rect(Rect::new(x_min + offset, y_min, x_max + offset, y_max).with_mode(PaintMode::Fill));
+			layer.restore_graphics_state();
+		}
+		// Outline the baseline of the line
+		if self.debug_layout
+		{
+			let points = vec!
+			[
+				(Point::new(self.x + self.column_x_offset(), self.y), false),
+				(Point::new(self.x + Mm(line.width()) + self.column_x_offset(), self.y), false)
+			];
+			self.debug_lines.push
+			((self.current_page_index, Line { points: points, is_closed: false }, DEBUG_BASELINE_COLOR));
+		}
+		// If a link is continuing from the previous line, have it start at the beginning of this line
+		self.link_start_x = self.x;
 		// Keeps track of what index in the line to start at when applying tokens to the page
 		let mut last_index = 0;
 		let tokens = line.tokens();
@@ -1640,6 +5058,64 @@ impl <'a> SpellbookWriter<'a>
 						last_index = index + 1;
 					}
 				},
+				// If the current token starts a link, apply previous text and start the link after it
+				Token::LinkStart(target) =>
+				{
+					// Get a vec of strings of all the previous tokens
+					let next_line: &Vec<_> =
+					&tokens[last_index..index].iter().map(|token| token.as_spellbook_string()).collect();
+					// Join those tokens together with spaces and apply them to the page
+					let next_line = next_line.join(SPACE);
+					self.apply_text(&next_line);
+					// If there was text before the link, apply a space between it and the link
+					if !next_line.is_empty() { self.apply_text(SPACE); }
+					// Start the link at the current position
+					self.active_link = Some(target.clone());
+					self.link_start_x = self.x;
+					last_index = index + 1;
+				},
+				// If the current token ends a link, apply the text of the link and make it clickable
+				Token::LinkEnd =>
+				{
+					// Get a vec of strings of all the tokens in the link
+					let next_line: &Vec<_> =
+					&tokens[last_index..index].iter().map(|token| token.as_spellbook_string()).collect();
+					// Join those tokens together with spaces and apply them to the page
+					self.apply_text(next_line.join(SPACE).as_str());
+					// Make the text of the link clickable
+					self.add_active_link_annotation();
+					self.active_link = None;
+					// If this isn't the last token in the line, apply a space after the link
+					if index < tokens.len() - 1 { self.apply_text(SPACE); }
+					last_index = index + 1;
+				},
+				// If the current token changes the text color, apply previous text in the old color and switch colors
+				Token::ColorStart(color) =>
+				{
+					// Get a vec of strings of all the previous tokens
+					let next_line: &Vec<_> =
+					&tokens[last_index..index].iter().map(|token| token.as_spellbook_string()).collect();
+					// Join those tokens together with spaces and apply them to the page
+					let next_line = next_line.join(SPACE);
+					self.apply_text(&next_line);
+					// If there was text before the color change, apply a space between it and the colored text
+					if !next_line.is_empty() { self.apply_text(SPACE); }
+					self.color_override = Some(bytes_to_color(color));
+					last_index = index + 1;
+				},
+				// If the current token ends a color change, apply the colored text and go back to the normal color
+				Token::ColorEnd =>
+				{
+					// Get a vec of strings of all the colored tokens
+					let next_line: &Vec<_> =
+					&tokens[last_index..index].iter().map(|token| token.as_spellbook_string()).collect();
+					// Join those tokens together with spaces and apply them to the page
+					self.apply_text(next_line.join(SPACE).as_str());
+					self.color_override = self.paragraph_color.clone();
+					// If this isn't the last token in the line, apply a space after the colored text
+					if index < tokens.len() - 1 { self.apply_text(SPACE); }
+					last_index = index + 1;
+				},
 				Token::Text(_) => ()
 			}
 		}
@@ -1648,6 +5124,122 @@ impl <'a> SpellbookWriter<'a>
 		&tokens[last_index..].iter().map(|token| token.as_spellbook_string()).collect();
 		// Join those tokens together withs spaces and apply them to the page
 		self.apply_text(next_line.join(SPACE).as_str());
+		// If a link goes past the end of this line, make the part of it on this line clickable
+		self.add_active_link_annotation();
+	}
+
+	/// Makes the text between the start of the active link and the current x position clickable (if there is an
+	/// active link).
+	fn add_active_link_annotation(&mut self)
+	{
+		// If there is no active link or no text in it on this line, do nothing
+		let target = match &self.active_link
+		{
+			Some(target) => target.clone(),
+			None => return
+		};
+		if self.x <= self.link_start_x { return; }
+		// Get the bounds of the link text on the page
+		let (y_min, y_max) = self.current_line_y_bounds();
+		let x_min = self.link_start_x + self.column_x_offset();
+		let x_max = self.x + self.column_x_offset();
+		let url = match target
+		{
+			LinkTarget::Url(url) => url,
+			// Links to anchors get added once the page of every anchor is known
+			LinkTarget::Anchor(anchor) =>
+			{
+				let link = InternalLink
+				{
+					page_index: self.current_page_index,
+					x_min: x_min,
+					y_min: y_min,
+					x_max: x_max,
+					y_max: y_max,
+					target_page_index: 0
+				};
+				// Show the definitions of glossary terms when the mouse is over them (if they get tooltips)
+				if let Some((term, definition)) = self.glossary_tooltips.get(&anchor)
+				{
+					self.tooltips.push(Tooltip
+					{
+						page_index: self.current_page_index,
+						x_min: x_min,
+						y_min: y_min,
+						x_max: x_max,
+						y_max: y_max,
+						title: term.clone(),
+						text: definition.clone()
+					});
+					// Glossary terms can get tooltips without linking to the glossary
+					if !self.glossary.as_ref().is_some_and(|glossary| glossary.link_keywords) { return; }
+				}
+				self.anchor_links.push((link, anchor));
+				return;
+			}
+		};
+		// Create a rectangle around the link text that has no border
+		let annotation = LinkAnnotation::new
+		(
+			Rect::new(x_min, y_min, x_max, y_max),
+			Some(BorderArray::Solid([0.0, 0.0, 0.0])),
+			Some(ColorArray::Transparent),
+			Actions::uri(url),
+			None
+		);
+		// Add the link to the page
+		self.current_layer().add_link_annotation(annotation);
+	}
+
+	/// Draws a QR code on the current page as a filled shape with its top left corner at a point and makes it a link
+	/// to its url.
+	fn apply_qr_code(&self, url: &str, code: &QrModules, x_min: Mm, y_max: Mm, size: Mm, color: (u8, u8, u8))
+	{
+		let module_size = size / code.size() as f32;
+		// Each run of dark squares in a row of the code becomes one rectangle
+		let mut rings = Vec::new();
+		for row in 0..code.size()
+		{
+			let top = y_max - module_size * row as f32;
+			let bottom = top - module_size;
+			let mut column = 0;
+			while column < code.size()
+			{
+				if !code.is_dark(column, row) { column += 1; continue; }
+				let left = x_min + module_size * column as f32;
+				while column < code.size() && code.is_dark(column, row) { column += 1; }
+				let right = x_min + module_size * column as f32;
+				rings.push(vec!
+				[
+					(Point::new(left, top), false),
+					(Point::new(right, top), false),
+					(Point::new(right, bottom), false),
+					(Point::new(left, bottom), false)
+				]);
+			}
+		}
+		let layer = self.current_layer();
+		layer.save_graphics_state();
+		layer.set_fill_color(bytes_to_color(&color));
+		layer.add_polygon(Polygon { rings: rings, mode: PaintMode::Fill, winding_order: WindingOrder::NonZero });
+		layer.restore_graphics_state();
+		// Make the code a link to its url that has no border
+		let annotation = LinkAnnotation::new
+		(
+			Rect::new(x_min, y_max - size, x_min + size, y_max),
+			Some(BorderArray::Solid([0.0, 0.0, 0.0])),
+			Some(ColorArray::Transparent),
+			Actions::uri(url.to_string()),
+			None
+		);
+		layer.add_link_annotation(annotation);
+	}
+
+	/// Returns the bottom and top of the line of text at the current y position (for making clickable areas).
+	fn current_line_y_bounds(&self) -> (Mm, Mm)
+	{
+		let newline_amount = self.current_newline_amount();
+		(self.y - newline_amount / 4.0, self.y + newline_amount * 3.0 / 4.0)
 	}
 
 	/// Checks if the current layer should move to the next page if the text y position is below given `y_min` value.
@@ -1655,13 +5247,73 @@ impl <'a> SpellbookWriter<'a>
 	/// Creates a new page if the page index goes beyond the number of layers that exist.
 	fn check_for_new_page(&mut self)
 	{
-		// If the y level is below the bottom of where text is allowed on the page, go to a new page
-		if self.y < self.y_min() { self.move_to_new_page(); }
+		// If the y level is below the bottom of where text is allowed on the page (or in the current column if the
+		// columns are balanced), go to a new page
+		if self.y < self.column_y_min() { self.move_to_new_page(); }
+	}
+
+	/// The lowest y position text can go in the current column. Columns before the last one end higher up when the
+	/// columns on the page are balanced.
+	fn column_y_min(&self) -> Mm
+	{
+		match &self.columns
+		{
+			Some(ColumnLayout { current_column, column_count, balanced_y_min: Some(y_min), .. })
+			if current_column + 1 < *column_count => self.y_min().max(*y_min),
+			_ => self.y_min()
+		}
+	}
+
+	/// Balances the columns on the current page so they all end at about the same height if the given number of lines
+	/// are the last ones that go in the columns and they fit on this page along with the lines already in the first
+	/// column. Does nothing if column balancing is off or text isn't in the first column.
+	fn balance_columns(&mut self, remaining_lines: usize)
+	{
+		if !self.balance_columns { return; }
+		let newline_amount = self.current_newline_amount();
+		let y_top = self.y_top();
+		let y_min = self.y_min();
+		let y = self.y;
+		let columns = match &mut self.columns
+		{
+			Some(columns) if columns.current_column == 0 && columns.column_count > 1 => columns,
+			_ => return
+		};
+		let top_y = columns.top_y.unwrap_or(y_top);
+		// The number of lines that fit in each column and the number of lines already in the first one
+		let lines_per_column = ((top_y - y_min) / newline_amount).floor().max(0.0) as usize + 1;
+		let used_lines = ((top_y - y) / newline_amount).round().max(0.0) as usize;
+		let total_lines = used_lines + remaining_lines;
+		if total_lines > lines_per_column * columns.column_count
+		{
+			columns.balanced_y_min = None;
+			return;
+		}
+		// End the columns before the last one half a line below the last line that goes in them
+		let balanced_lines = total_lines.div_ceil(columns.column_count).max(1);
+		columns.balanced_y_min = Some(top_y - newline_amount * (balanced_lines as f32 - 0.5));
 	}
 
 	// Move to a new page. Sets the y position to the top of the page and creates a new page if needed.
+	// Moves to the top of the next column instead if pages are split into columns and this isn't the last one.
 	fn move_to_new_page(&mut self)
 	{
+		// Finish the page (or column) with its footnotes
+		self.apply_footnotes();
+		if let Some(columns) = &mut self.columns
+		{
+			if columns.current_column + 1 < columns.column_count
+			{
+				columns.current_column += 1;
+				let top_y = columns.top_y;
+				self.y = top_y.unwrap_or(self.y_top());
+				return;
+			}
+			// Columns on the next page start at the top of the page and aren't balanced until they need to be
+			columns.current_column = 0;
+			columns.top_y = None;
+			columns.balanced_y_min = None;
+		}
 		// Increase the current page index to the layer for the next page
 		self.current_page_index += 1;
 		// If the index is beyond the number of layers in the document
@@ -1670,51 +5322,141 @@ impl <'a> SpellbookWriter<'a>
 			// Create a new page
 			self.make_new_page();
 		}
+		// Outline the columns on the new page
+		self.add_debug_columns();
 		// Move the y position of the text to the top of the page
 		self.y = self.y_top();
 	}
 
 	/// Adds a new page to the pdf document, including the background image and page number if options for those were
 	/// given. Sets `current_page_index` to the new page.
+	#[cfg_attr
+	(
+		feature = "tracing",
+		tracing::instrument(skip_all, fields(page_index = self.layers.len(), page_number = self.current_page_num))
+	)]
 	fn make_new_page(&mut self)
 	{
-		// Create a new page
-		let (page, layer) = self.doc.add_page
-		(
-			Mm(self.page_width()),
-			Mm(self.page_height()),
-			format!("{} {}", LAYER_NAME_PREFIX, self.layers.len())
-		);
-		// Get the layer for the new page
-		let layer_ref = self.doc.get_page(page).get_layer(layer);
-		// Add the new layer and page to the vecs holding them
-		self.layers.push(layer_ref);
-		self.pages.push(page);
-		// Update the current page index to point to the new page
-		self.current_page_index = self.layers.len() - 1;
+		// Finish the last page with its footnotes
+		self.apply_footnotes();
+		// Use the first page of the document if nothing has been put on it (when there's no title page)
+		if self.unused_first_page
+		{
+			self.unused_first_page = false;
+			self.current_page_index = 0;
+		}
+		else
+		{
+			// Create a new page
+			let (page, layer) = self.doc.add_page
+			(
+				self.page_width(),
+				self.page_height(),
+				format!("{} {}", LAYER_NAME_PREFIX, self.layers.len())
+			);
+			// Get the layer for the new page
+			let layer_ref = self.doc.get_page(page).get_layer(layer);
+			// Add the new layer and page to the vecs holding them
+			self.layers.push(layer_ref);
+			self.pages.push(page);
+			// Update the current page index to point to the new page
+			self.current_page_index = self.layers.len() - 1;
+		}
 		// Add a background image (if there is a background to add)
 		self.add_background();
 		// Adds a page number to the new page (if there are page numbers)
 		self.add_page_number();
+		// Adds a footer about the spell the new page continues (if it continues one and there are continuation footers)
+		if let Some(options) = self.continuation_footer { self.apply_continuation_footer(options); }
+		// Let any callbacks add their own decorations to the page
+		self.page_hooks.page_created(&PageContext
+		{
+			page_index: self.current_page_index,
+			page_number: self.current_page_num,
+			page_width: self.page_width(),
+			page_height: self.page_height(),
+			layer: self.current_layer()
+		});
 		// Increases the page number count by 1
 		self.current_page_num += 1;
 	}
 
-	/// Adds the background image to the current layer (if a background image was given to use).
+	/// Adds the background image to the current layer (if a background image was given to use). Title pages and
+	/// chapter pages get their own background images instead if they have them.
 	fn add_background(&mut self)
 	{
+		// Fill the page with the background color first so background images go on top of it (if there is one)
+		if let Some(color) = self.background_color { self.apply_background_color(color); }
+		let section_background = match self.page_kind
+		{
+			PageKind::Body => None,
+			PageKind::TitlePage => self.title_page_background.as_ref(),
+			PageKind::ChapterPage => self.chapter_page_background.as_ref()
+		};
 		// If there is a background image
-		if let Some(background) = &self.background
+		if let Some(background) = section_background.or(self.background.as_ref())
+		{
+			// Every other page can have the image mirrored or rotated
+			for transform in background.transforms_for_page(self.current_page_index)
+			{
+				// Construct a `printpdf::Image` from the image data that was made when the background was opened
+				// Note: Cannot store a `printpdf::Image` in the background struct because `add_to_layer()` consumes it
+				// and it can't be cloned.
+				let image = Image::from(background.pdf_image().clone());
+				// Add the image to the current layer with the given transform data
+				image.add_to_layer(self.current_layer().clone(), *transform);
+			}
+		}
+	}
+
+	/// Fills the current page with a color that fades into the background gradient color from the top of the page to
+	/// the bottom (if there is one).
+	fn apply_background_color(&mut self, color: (u8, u8, u8))
+	{
+		let (width, height) = (self.page_width(), self.page_height());
+		self.apply_gradient_rect((Mm(0.0), Mm(0.0), width, height), color, self.background_gradient, true);
+	}
+
+	/// Fills a rectangle (x min, y min, x max, y max) with a color that fades into another color (if one is given)
+	/// from top to bottom if `vertical` is true or from left to right if it's false.
+	fn apply_gradient_rect
+	(
+		&self,
+		(x_min, y_min, x_max, y_max): (Mm, Mm, Mm, Mm),
+		color: (u8, u8, u8),
+		gradient_color: Option<(u8, u8, u8)>,
+		vertical: bool
+	)
+	{
+		let band_count = if gradient_color.is_some() { GRADIENT_BANDS } else { 1 };
+		let band_size = if vertical { y_max - y_min } else { x_max - x_min } / band_count as f32;
+		let layer = self.current_layer();
+		layer.save_graphics_state();
+		for band in 0..band_count
 		{
-			// Construct a `printpdf::Image` from the `image::DynamicImage`
-			// Note: Cannot store a `printpdf::Image` in the background struct because of ownership issues and
-			// lacking implementations of the `printpdf::Image` struct from the `printpdf` crate.
-			let image = Image::from_dynamic_image(&background.image().clone());
-			// Add the image to the current layer with the given transform data
-			image.add_to_layer(self.current_layer().clone(), *background.transform());
+			// Each band is the color of the gradient at its middle
+			let amount = (band as f32 + 0.5) / band_count as f32;
+			let band_color = mix_colors(color, gradient_color.unwrap_or(color), amount);
+			layer.set_fill_color(bytes_to_color(&band_color));
+			// Bands go a little into the next band so no gaps show between them
+			let rect = if vertical
+			{
+				let band_y_max = y_max - band_size * band as f32;
+				Rect::new(x_min, (band_y_max - band_size * 1.5).max(y_min), x_max, band_y_max)
+			}
+			else
+			{
+				let band_x_min = x_min + band_size * band as f32;
+				Rect::new(band_x_min, y_min, (band_x_min + band_size * 1.5).min(x_max), y_max)
+			};
+			layer.add_rect(rect.with_mode(PaintMode::Fill));
 		}
+		layer.restore_graphics_state();
 	}
 
+	/// The text of a page number with the prefix of the current chapter in front of it (ex: "W-3").
+	fn page_number_text(&self, page_num: i64) -> String { format!("{}{}", self.page_number_prefix, page_num) }
+
 	/// Adds the page number to the current layer (if page number options were given).
 	fn add_page_number(&mut self)
 	{
@@ -1725,7 +5467,7 @@ impl <'a> SpellbookWriter<'a>
 			Some(data) =>
 			{
 				// Convert the current page number into a string
-				let text = self.current_page_num.to_string();
+				let text = self.page_number_text(self.current_page_num);
 				// Determine the x position of the page number based on if it will be on the left or right side of the
 				// page
 				let x = match data.current_side()
@@ -1745,9 +5487,9 @@ impl <'a> SpellbookWriter<'a>
 				self.layers[self.current_page_index].use_text
 				(
 					&text,
-					data.font_size(),
-					Mm(x),
-					Mm(data.bottom_margin()),
+					data.font_size().0,
+					x,
+					data.bottom_margin(),
 					data.font_ref()
 				);
 			},
@@ -1764,37 +5506,345 @@ impl <'a> SpellbookWriter<'a>
 		};
 	}
 
+	/// Writes the level of the spell that's being written in the middle of the bottom of the current page, followed by
+	/// an icon for whether it needs concentration and an icon for whether it can be cast as a ritual (if it does / can).
+	fn apply_continuation_footer(&mut self, options: ContinuationFooterOptions)
+	{
+		let (level, concentration, ritual) = match &self.continued_spell
+		{
+			Some(continued_spell) => continued_spell.clone(),
+			None => return
+		};
+		let starting_text_type = *self.current_text_type();
+		let starting_font_variant = *self.current_font_variant();
+		self.set_current_text_type(TextType::Body);
+		self.set_current_font_variant(FontVariant::Regular);
+		let scale = options.scale.max(0.0);
+		let color = match options.color
+		{
+			Some(color) => bytes_to_color(&color),
+			None => self.current_text_color().clone()
+		};
+		let letters: Vec<(&str, Mm)> = [(concentration, "C"), (ritual, "R")]
+			.into_iter()
+			.filter(|(has_icon, _)| *has_icon)
+			.map(|(_, letter)| (letter, self.calc_text_width(letter) * scale))
+			.collect();
+		let cap_height = self.calc_cap_height() * scale;
+		let diameter = cap_height * COMPONENT_ICON_SIZE;
+		let space_width = self.calc_text_width(SPACE) * scale;
+		let level_width = self.calc_text_width(&level) * scale;
+		// Center the footer on the page on the same line as the page numbers (or halfway up the bottom margin)
+		let width = level_width + (space_width + diameter) * letters.len() as f32;
+		let mut x = (self.page_width() - width) / 2.0;
+		let baseline = match &self.page_number_data
+		{
+			Some(data) => data.bottom_margin(),
+			None => self.page_size_data.y_min() / 2.0
+		};
+		let font_ref = self.current_font_ref().clone();
+		let font_size = self.current_font_size().0 * scale;
+		let layer = self.current_layer();
+		layer.save_graphics_state();
+		layer.set_fill_color(color.clone());
+		layer.set_outline_color(color);
+		layer.set_outline_thickness(COMPONENT_ICON_LINE_THICKNESS.0);
+		layer.use_text(&level, font_size, x, baseline, &font_ref);
+		x += level_width;
+		// Each icon is a letter inside of a circle like the icons for spell components
+		for (letter, letter_width) in letters
+		{
+			x += space_width;
+			let center = (x + diameter / 2.0, baseline + cap_height / 2.0);
+			let circle = ellipse_points(center, (diameter / 2.0, diameter / 2.0), 0.0, std::f32::consts::PI * 2.0);
+			layer.add_line(Line { points: circle, is_closed: true });
+			layer.use_text(letter, font_size, x + (diameter - letter_width) / 2.0, baseline, &font_ref);
+			x += diameter;
+		}
+		layer.restore_graphics_state();
+		self.set_current_text_type(starting_text_type);
+		self.set_current_font_variant(starting_font_variant);
+	}
+
 	/// Writes a line of text to a page.
 	/// Moves to a new page / creates a new page if the text is below a certain y value.
 	fn apply_text(&mut self, text: &str)
 	{
 		// If there is no text to apply, do nothing
 		if text.is_empty() { return; }
+		// If the line is justified, write each word on its own with the extra width after each space
+		if self.word_spacing > Mm(0.0) && text.contains(' ')
+		{
+			let word_spacing = std::mem::replace(&mut self.word_spacing, Mm(0.0));
+			for (index, word) in text.split(' ').enumerate()
+			{
+				if index > 0 { self.x += self.calc_text_width(SPACE) + word_spacing; }
+				self.apply_text(word);
+			}
+			self.word_spacing = word_spacing;
+			return;
+		}
+		// Save space for the page numbers of page references so they can be written once every page is laid out
+		if text.contains(PAGE_REFERENCE_START)
+		{
+			for (segment, is_reference) in page_reference_segments(text)
+			{
+				if is_reference { self.reserve_page_reference(segment); }
+				else { self.apply_text(segment); }
+			}
+			return;
+		}
+		// Write superscript text (like footnote numbers) smaller and higher than the text around it
+		if text.contains(SUPERSCRIPT_START)
+		{
+			for (segment, is_superscript) in superscript_segments(text)
+			{
+				if is_superscript { self.apply_superscript(segment); }
+				else { self.apply_text(segment); }
+			}
+			return;
+		}
+		// Hide the words of secrets behind shaded bars
+		if text.contains(REDACTED_START)
+		{
+			for (segment, is_redacted) in redacted_segments(text)
+			{
+				if is_redacted { self.apply_redaction_bar(segment); }
+				else { self.apply_text(segment); }
+			}
+			return;
+		}
+		// Write plainer text in place of characters the font doesn't have (like bullet points and dashes)
+		let fallback_text = self.font_data.current_fallback_text(text);
+		let text: &str = &fallback_text;
+		// Font variants that didn't have their own font file get faked
+		let synthetic_style = self.font_data.current_synthetic_style();
+		let is_synthetic = synthetic_style.bold || synthetic_style.italic;
+		// Title text gets outlined if there are settings for a title outline
+		let title_outline = match self.title_outline
+		{
+			Some(options) if *self.current_text_type() == TextType::Title => Some(options),
+			_ => None
+		};
+		// Keep the stroke and text settings for synthetic styles and outlines from affecting anything else
+		let saves_state = is_synthetic || title_outline.is_some();
+		if saves_state { self.layers[self.current_page_index].save_graphics_state(); }
 		// Create a new text section on the page
 		self.layers[self.current_page_index].begin_text_section();
+		let x = self.x + self.column_x_offset();
+		if synthetic_style.italic
+		{
+			// Slant the text by skewing it to the right
+			let matrix = [1.0, 0.0, SYNTHETIC_ITALIC_SKEW, 1.0, Pt::from(x).0, Pt::from(self.y).0];
+			self.layers[self.current_page_index].set_text_matrix(TextMatrix::Raw(matrix));
+		}
 		// Set the text cursor to the current x and y position of the text
-		self.layers[self.current_page_index].set_text_cursor(Mm(self.x), Mm(self.y));
+		else { self.layers[self.current_page_index].set_text_cursor(x, self.y); }
 		// Set the font and font size of the text
-		self.layers[self.current_page_index].set_font(self.current_font_ref(), self.current_font_size());
-		// Set the text color
-		self.layers[self.current_page_index].set_fill_color(self.current_text_color().clone());
-		// Write the text to the page
-		self.layers[self.current_page_index].write_text(text, self.current_font_ref());
+		self.layers[self.current_page_index].set_font(self.current_font_ref(), self.current_font_size().0);
+		// Set the text color (or the color from a color tag if there is one)
+		let color = self.color_override.clone().unwrap_or_else(|| self.current_text_color().clone());
+		self.layers[self.current_page_index].set_fill_color(color.clone());
+		if synthetic_style.bold
+		{
+			// Thicken the text by outlining it in the same color and space the characters out to make room for that
+			let stroke_width = self.current_font_size() * SYNTHETIC_BOLD_STROKE;
+			self.layers[self.current_page_index].set_text_rendering_mode(TextRenderingMode::FillStroke);
+			self.layers[self.current_page_index].set_outline_color(color);
+			self.layers[self.current_page_index].set_outline_thickness(stroke_width.0);
+			self.layers[self.current_page_index].set_character_spacing(stroke_width.0);
+		}
+		if let Some(options) = title_outline
+		{
+			// Stroke the outline of the letters in the outline color (on top of filling them in unless they're hollow)
+			let mode = if options.filled { TextRenderingMode::FillStroke } else { TextRenderingMode::Stroke };
+			self.layers[self.current_page_index].set_text_rendering_mode(mode);
+			self.layers[self.current_page_index].set_outline_color(bytes_to_color(&options.color));
+			self.layers[self.current_page_index].set_outline_thickness(options.thickness);
+		}
+		// Write the text to the page (glyph by glyph if text is being shaped, kerned, or has ligatures)
+		if let Some(shaper) = self.current_shaper().filter(|_| needs_shaping(text))
+		{
+			self.write_shaped_glyphs(text, shaper);
+		}
+		else if self.current_kerning().is_some() || self.current_ligatures().is_some()
+		{
+			let glyphs = text_glyphs(text, self.current_size_data(), self.current_ligatures());
+			self.write_glyphs(text, &glyphs, self.current_kerning());
+		}
+		else { self.layers[self.current_page_index].write_text(text, self.current_font_ref()); }
 		// End the text section on the page
 		self.layers[self.current_page_index].end_text_section();
+		if saves_state { self.layers[self.current_page_index].restore_graphics_state(); }
+		// Add the words to the search index (if there is one)
+		if let Some((search_index, options)) = &mut self.search_index
+		{
+			search_index.add_text(text, self.current_page_index, options);
+		}
 		// Move the x position to be at the end of the newly applied line
 		self.x += self.calc_text_width(&text);
 	}
 
+	/// Saves space at the current position for the page number of the anchor with the given name so it can be written
+	/// once every page is laid out.
+	fn reserve_page_reference(&mut self, name: &str)
+	{
+		self.page_references.push(PageReference
+		{
+			name: String::from(name),
+			page_index: self.current_page_index,
+			x: self.x + self.column_x_offset(),
+			y: self.y,
+			text_type: *self.current_text_type(),
+			font_variant: *self.current_font_variant(),
+			color: self.color_override.clone()
+		});
+		self.x += self.calc_text_width(PAGE_REFERENCE_NUMBER_SPACE);
+	}
+
+	/// Draws a shaded bar over the space that some text would take up at the current position instead of writing it.
+	fn apply_redaction_bar(&mut self, text: &str)
+	{
+		let width = self.calc_text_width(text);
+		let x_min = self.x + self.column_x_offset();
+		let (y_min, y_max) = self.current_line_y_bounds();
+		let color = self.color_override.clone().unwrap_or_else(|| self.current_text_color().clone());
+		let layer = self.current_layer();
+		layer.save_graphics_state();
+		layer.set_fill_color(color);
+		layer.add_rect(Rect::new(x_min, y_min, x_min + width, y_max).with_mode(PaintMode::Fill));
+		layer.restore_graphics_state();
+		self.x += width;
+	}
+
+	/// Writes some superscript text (like a footnote number) at the current position. Footnote ids get written as the
+	/// number of their footnote.
+	fn apply_superscript(&mut self, text: &str)
+	{
+		let id = text.parse().ok().filter(|id: &usize| self.footnote_numbers.contains_key(id));
+		let text = match id
+		{
+			Some(id) => self.footnote_numbers[&id].to_string(),
+			None => String::from(text)
+		};
+		let x_min = self.x + self.column_x_offset();
+		let rise = self.calc_cap_height() * SUPERSCRIPT_RISE;
+		let color = self.color_override.clone().unwrap_or_else(|| self.current_text_color().clone());
+		let layer = &self.layers[self.current_page_index];
+		layer.begin_text_section();
+		layer.set_text_cursor(self.x + self.column_x_offset(), self.y + rise);
+		layer.set_font(self.current_font_ref(), self.current_font_size().0 * SUPERSCRIPT_SCALE);
+		layer.set_fill_color(color);
+		layer.write_text(&text, self.current_font_ref());
+		layer.end_text_section();
+		self.x += self.calc_text_width(&text) * SUPERSCRIPT_SCALE;
+		// Endnote numbers link to their endnotes once the page of each endnote is known
+		if let (Some(id), Some(_)) = (id, &self.endnotes)
+		{
+			let (y_min, y_max) = self.current_line_y_bounds();
+			let link = InternalLink
+			{
+				page_index: self.current_page_index,
+				x_min: x_min,
+				y_min: y_min,
+				x_max: self.x + self.column_x_offset(),
+				y_max: y_max,
+				target_page_index: 0
+			};
+			self.anchor_links.push((link, endnote_anchor(id)));
+		}
+	}
+
+	/// Writes the glyphs of some text (from `text_glyphs()`) in the current text section on the current page with
+	/// kerning between them if kerning data is given.
+	/// Ligatures get marked with the text they replace so that text can still be copied and searched in pdf readers.
+	fn write_glyphs(&self, text: &str, glyphs: &Vec<(u16, usize)>, kerning: Option<&KerningTable>)
+	{
+		let layer = &self.layers[self.current_page_index];
+		let mut chars = text.chars();
+		// Glyphs that get written together in a single text operation
+		let mut run = Vec::new();
+		let mut last_glyph_id = None;
+		for (glyph_id, char_count) in glyphs
+		{
+			let glyph_text: String = chars.by_ref().take(*char_count).collect();
+			// Skip characters the font doesn't have (the same as when printpdf writes text)
+			if *glyph_id == 0 { continue; }
+			let adjustment = match (kerning, last_glyph_id)
+			{
+				(Some(kerning), Some(last_glyph_id)) => kerning.pdf_adjustment(last_glyph_id, *glyph_id),
+				_ => 0
+			};
+			last_glyph_id = Some(*glyph_id);
+			if *char_count > 1
+			{
+				if !run.is_empty() { layer.write_positioned_codepoints(std::mem::take(&mut run)); }
+				// The text a ligature replaces has to be a utf-16 string with a byte order mark
+				let actual_text: Vec<u8> = [0xFEFF].into_iter().chain(glyph_text.encode_utf16())
+					.flat_map(|unit: u16| unit.to_be_bytes())
+					.collect();
+				let actual_text = Object::String(actual_text, StringFormat::Hexadecimal);
+				let properties = Object::Dictionary(Dictionary::from_iter([("ActualText", actual_text)]));
+				layer.add_operation(Operation::new("BDC", vec![Object::Name(b"Span".to_vec()), properties]));
+				layer.write_positioned_codepoints([(adjustment, *glyph_id)]);
+				layer.add_operation(Operation::new("EMC", Vec::new()));
+			}
+			else { run.push((adjustment, *glyph_id)); }
+		}
+		if !run.is_empty() { layer.write_positioned_codepoints(run); }
+	}
+
+	/// Writes some text in the current text section on the current page with the glyphs and positions it gets shaped
+	/// into. The glyphs get marked with the text they came from so that text can still be copied and searched in pdf
+	/// readers.
+	fn write_shaped_glyphs(&self, text: &str, shaper: &TextShaper)
+	{
+		let layer = &self.layers[self.current_page_index];
+		let glyphs = shaper.shape(text);
+		// The text the glyphs stand for has to be a utf-16 string with a byte order mark
+		let actual_text: Vec<u8> = [0xFEFF].into_iter().chain(text.encode_utf16())
+			.flat_map(|unit: u16| unit.to_be_bytes())
+			.collect();
+		let actual_text = Object::String(actual_text, StringFormat::Hexadecimal);
+		let properties = Object::Dictionary(Dictionary::from_iter([("ActualText", actual_text)]));
+		layer.add_operation(Operation::new("BDC", vec![Object::Name(b"Span".to_vec()), properties]));
+		let rise_scale = self.current_font_size().0 / shaper.units_per_em() as f32;
+		for (rise, run) in shaper.pdf_runs(&glyphs)
+		{
+			// Marks (like Arabic vowel marks) that sit above or below the letters they go with get raised or lowered
+			if rise != 0 { layer.add_operation(Operation::new("Ts", vec![Object::Real(rise as f32 * rise_scale)])); }
+			layer.write_positioned_codepoints(run);
+			if rise != 0 { layer.add_operation(Operation::new("Ts", vec![Object::Integer(0)])); }
+		}
+		layer.add_operation(Operation::new("EMC", Vec::new()));
+	}
+
 	/// Calculates the width of some text using the current state of this object's font data field.
-	fn calc_text_width(&self, text: &str) -> f32
+	pub(crate) fn calc_text_width(&self, text: &str) -> Mm
 	{
-		calc_text_width(text, self.current_size_data(), self.current_font_scale(), self.current_scalar())
+		let text = &*self.font_data.current_fallback_text(text);
+		calc_text_width
+		(
+			text,
+			self.current_size_data(),
+			self.current_font_scale(),
+			self.current_scalar(),
+			self.current_kerning(),
+			self.current_ligatures(),
+			self.current_shaper()
+		) +
+			self.font_data.synthetic_extra_width_for(text, *self.current_text_type(), *self.current_font_variant())
+	}
+
+	/// Calculates the height of capital letters using the current state of this object's font data field.
+	fn calc_cap_height(&self) -> Mm
+	{
+		calc_cap_height(self.current_size_data(), self.current_font_scale(), self.current_scalar())
 	}
 
 	/// Calculates the height of a certain number of lines of text using the current state of this object's font data
 	/// field.
-	fn calc_text_height(&self, lines: usize) -> f32
+	fn calc_text_height(&self, lines: usize) -> Mm
 	{
 		calc_text_height
 		(
@@ -1804,16 +5854,16 @@ impl <'a> SpellbookWriter<'a>
 	}
 
 	// /// Returns half the height of a single line with the current text / font state.
-	// fn half_line_height(&self) -> f32 { self.line_height() / 2.0 }
+	// fn half_line_height(&self) -> Mm { self.line_height() / 2.0 }
 
 	// /// Returns the height of a single line with the current text / font state.
-	// fn line_height(&self) -> f32
+	// fn line_height(&self) -> Mm
 	// {
 	// 	line_height(self.current_size_data(), self.current_font_scale(), self.current_font_size())
 	// }
 
 	/// Calculates the text width of a page number.
-	fn calc_page_number_width(&self, page_number_text: &str) -> f32
+	fn calc_page_number_width(&self, page_number_text: &str) -> Mm
 	{
 		// Attempt to retrive all necessary data, panic if there is no page number data
 		let font_size_data = self.page_number_font_size_data()
@@ -1823,7 +5873,7 @@ impl <'a> SpellbookWriter<'a>
 		let font_scalar = self.page_number_font_scalar()
 		.expect("Called `dnd_spellbook_maker::spellbook_writer::SpellbookWriter::calc_page_number_width` with no page number data.");
 		// Return the width of the page number
-		calc_text_width(page_number_text, font_size_data, font_scale, font_scalar)
+		calc_text_width(page_number_text, font_size_data, font_scale, font_scalar, None, None, None)
 	}
 
 	// General Field Getters
@@ -1832,17 +5882,26 @@ impl <'a> SpellbookWriter<'a>
 	// fn layers(&self) -> &Vec<PdfLayerReference> { &self.layers }
 	// fn pages(&self) -> &Vec<PdfPageIndex> { &self.pages }
 	fn current_page_index(&self) -> usize { self.current_page_index }
+	// How far to the right of where it was calculated to be text gets moved because of the column it's in
+	fn column_x_offset(&self) -> Mm
+	{
+		match &self.columns
+		{
+			Some(columns) => columns.x_offset(),
+			None => Mm(0.0)
+		}
+	}
 	// fn current_page_num(&self) -> i64 { self.current_page_num }
 	// fn font_data(&self) -> &FontData { &self.font_data }
 	// fn page_size_data(&self) -> &PageSizeData { &self.page_size_data }
 	// fn page_number_data(&self) -> &Option<PageNumberData> { &self.page_number_data }
 	// fn background(&self) -> &Option<BackgroundImage> { &self.background }
 	// fn table_data(&self) -> &TableData { &self.table_data }
-	fn space_widths(&self) -> &SpaceWidths { &self.space_widths }
+	// fn space_widths(&self) -> &SpaceWidths { &self.space_widths }
 	// /// Current x position of the text
-	// fn x(&self) -> &f32 { &self.x }
+	// fn x(&self) -> &Mm { &self.x }
 	// /// Current y position of the text
-	// fn y(&self) -> &f32 { &self.y }
+	// fn y(&self) -> &Mm { &self.y }
 
 	// Layer Getters
 
@@ -1871,43 +5930,53 @@ impl <'a> SpellbookWriter<'a>
 	// fn all_spacing_options(&self) -> &SpacingOptions { self.font_data.all_spacing_options() }
 	// /// RGB color values for each type of text.
 	// fn all_text_colors(&self) -> &TextColors { self.font_data.all_text_colors() }
-	/// Tab size in pringpdf Mm.
-	fn tab_amount(&self) -> f32 { self.font_data.tab_amount() }
+	/// Tab size.
+	fn tab_amount(&self) -> Mm { self.font_data.tab_amount() }
+	/// Paragraph tab size of the current type of text being used.
+	fn current_tab_amount(&self) -> Mm { self.font_data.current_tab_amount() }
+	/// How far the lines of bullet points hang in from the bullet symbol (if it's been set).
+	fn bullet_indent(&self) -> Option<Mm> { self.font_data.bullet_indent() }
 	/// The font object for the current font variant being used.
 	fn current_font_ref(&self) -> &IndirectFontRef { self.font_data.current_font_ref() }
 	/// Font size of the current type of text being used.
-	fn current_font_size(&self) -> f32 { self.font_data.current_font_size() }
+	fn current_font_size(&self) -> Pt { self.font_data.current_font_size() }
 	/// Scalar value of the current font variant being used (regular, bold, italic, bold-italic).
 	fn current_scalar(&self) -> f32 { self.font_data.current_scalar() }
 	/// Size data of the current font variant being used (regular, bold, italic, bold-italic).
 	fn current_size_data(&self) -> &Font { self.font_data.current_size_data() }
+	/// Kerning data of the current font variant being used (`None` if text isn't being kerned).
+	fn current_kerning(&self) -> Option<&KerningTable> { self.font_data.current_kerning() }
+	/// Standard ligatures of the current font variant being used (`None` if ligatures aren't being used).
+	fn current_ligatures(&self) -> Option<&LigatureTable> { self.font_data.current_ligatures() }
+	/// Text shaper of the current font variant being used (`None` if text isn't being shaped).
+	fn current_shaper(&self) -> Option<&TextShaper> { self.font_data.current_shaper() }
 	/// Scale sizing data of the current type of text being used.
 	fn current_font_scale(&self) -> &Scale { self.font_data.current_font_scale() }
-	/// Newline size in printpdf Mm of the current type of text being used.
-	fn current_newline_amount(&self) -> f32 { self.font_data.current_newline_amount() }
+	/// Newline size of the current type of text being used.
+	fn current_newline_amount(&self) -> Mm { self.font_data.current_newline_amount() }
 	/// RGB color values for the current type of text being used.
 	fn current_text_color(&self) -> &Color { self.font_data.current_text_color() }
 
 	// Page Size Getters
 
 	// Entire page dimensions
-	fn page_width(&self) -> f32 { self.page_size_data.page_width() }
-	fn page_height(&self) -> f32 { self.page_size_data.page_height() }
+	fn page_width(&self) -> Mm { self.page_size_data.page_width() }
+	fn page_height(&self) -> Mm { self.page_size_data.page_height() }
 	/// Left
-	fn x_min(&self) -> f32 { self.page_size_data.x_min() }
+	fn x_min(&self) -> Mm { self.page_size_data.x_min() }
 	/// Right
-	fn x_max(&self) -> f32 { self.page_size_data.x_max() }
+	fn x_max(&self) -> Mm { self.page_size_data.x_max() }
 	/// Bottom
-	fn y_min(&self) -> f32 { self.page_size_data.y_min() }
+	fn y_min(&self) -> Mm { self.page_size_data.y_min() + self.footnote_area_height }
 	/// Top
-	fn y_max(&self) -> f32 { self.page_size_data.y_max() }
+	fn y_max(&self) -> Mm { self.page_size_data.y_max() }
 	/// The highest point text with the current font state can be on a page.
-	fn y_top(&self) -> f32 { self.y_max() - self.current_newline_amount() / 2.0 }
+	fn y_top(&self) -> Mm { self.y_max() - self.current_newline_amount() / 2.0 }
 	/// The lowest point text with the current font state can be on a page.
-	fn y_bottom(&self) -> f32 { self.y_min() + self.current_newline_amount() / 2.0  }
+	fn y_bottom(&self) -> Mm { self.y_min() + self.current_newline_amount() / 2.0  }
 	// // Dimensions that text can fit inside
-	// pub fn text_width(&self) -> f32 { self.page_size_data.text_width() }
-	// pub fn text_height(&self) -> f32 { self.page_size_data.text_height() }
+	// pub fn text_width(&self) -> Mm { self.page_size_data.text_width() }
+	// pub fn text_height(&self) -> Mm { self.page_size_data.text_height() }
 
 	// Page Number Getters
 
@@ -1952,7 +6021,7 @@ impl <'a> SpellbookWriter<'a>
 	// }
 
 	// /// The font size of the page numbers.
-	// fn page_number_font_size(&self) -> Option<f32>
+	// fn page_number_font_size(&self) -> Option<Pt>
 	// {
 	// 	match &self.page_number_data
 	// 	{
@@ -1962,7 +6031,7 @@ impl <'a> SpellbookWriter<'a>
 	// }
 
 	// /// The amount of space between newlines for page numbers in case of overflow.
-	// fn page_number_newline_amount(&self) -> Option<f32>
+	// fn page_number_newline_amount(&self) -> Option<Mm>
 	// {
 	// 	match &self.page_number_data
 	// 	{
@@ -1971,8 +6040,8 @@ impl <'a> SpellbookWriter<'a>
 	// 	}
 	// }
 
-	// /// The amount of space between the side of the page and the page number in printpdf Mm.
-	// fn page_number_side_margin(&self) -> Option<f32>
+	// /// The amount of space between the side of the page and the page number.
+	// fn page_number_side_margin(&self) -> Option<Mm>
 	// {
 	// 	match &self.page_number_data
 	// 	{
@@ -1981,8 +6050,8 @@ impl <'a> SpellbookWriter<'a>
 	// 	}
 	// }
 	
-	// /// The amount of space between the bottom of the page and the page number in printpdf Mm.
-	// fn page_number_bottom_margin(&self) -> Option<f32>
+	// /// The amount of space between the bottom of the page and the page number.
+	// fn page_number_bottom_margin(&self) -> Option<Mm>
 	// {
 	// 	match &self.page_number_data
 	// 	{
@@ -2063,14 +6132,14 @@ impl <'a> SpellbookWriter<'a>
 
 	// Table Getters
 
-	/// Space between columns in printpdf Mm.
-	fn table_horizontal_cell_margin(&self) -> f32 { self.table_data.horizontal_cell_margin() }
-	/// Space between rows in printpdf Mm.
-	fn table_vertical_cell_margin(&self) -> f32 { self.table_data.vertical_cell_margin() }
-	/// Minimum space between sides of table and sides of pages in printpdf Mm.
-	fn table_outer_horizontal_margin(&self) -> f32 { self.table_data.outer_horizontal_margin() }
-	/// Space above and below table from other text / tables in printpdf Mm.
-	fn table_outer_vertical_margin(&self) -> f32 { self.table_data.outer_vertical_margin() }
+	/// Space between columns.
+	fn table_horizontal_cell_margin(&self) -> Mm { self.table_data.horizontal_cell_margin() }
+	/// Space between rows.
+	fn table_vertical_cell_margin(&self) -> Mm { self.table_data.vertical_cell_margin() }
+	/// Minimum space between sides of table and sides of pages.
+	fn table_outer_horizontal_margin(&self) -> Mm { self.table_data.outer_horizontal_margin() }
+	/// Space above and below table from other text / tables.
+	fn table_outer_vertical_margin(&self) -> Mm { self.table_data.outer_vertical_margin() }
 	/// Scalar value to adjust off-row color lines to line up with the rows vertically.
 	fn table_off_row_color_lines_y_adjust_scalar(&self) -> f32
 	{ self.table_data.off_row_color_lines_y_adjust_scalar() }
@@ -2082,7 +6151,7 @@ impl <'a> SpellbookWriter<'a>
 
 	// Space Width Getters
 
-	// fn get_current_space_width(&self) -> f32
+	// fn get_current_space_width(&self) -> Mm
 	// { self.space_widths.get_width_for(*self.current_text_type(), *self.current_font_variant()) }
 
 	// Font Setters
@@ -4,13 +4,22 @@
 //
 //////////////////////////////////////////////////////////////////////////////////////////////////////////////
 
+use std::collections::BTreeMap;
 use std::fmt;
 use std::fs;
 use std::io::BufReader;
 use std::error;
+use std::path::Path;
 
 use serde::{Serialize, Deserialize};
-use serde_json::{from_reader, to_writer, to_writer_pretty};
+use regex::Regex;
+use serde_json::{from_reader, from_value, to_writer, to_writer_pretty, Value};
+
+use crate::spell_database::{SpellDatabase, SpellHeader};
+use crate::spellbook_options::{FontVariant, RedactionStyle};
+use crate::spellbook_gen_types::{SUPERSCRIPT_START, SUPERSCRIPT_END, REDACTED_START, REDACTED_END};
+use crate::layout::{self, TextMeasurer};
+use crate::utils::get_all_spells_in_folder;
 
 /// Holds spell fields with either a controlled value or a custom value represented by a string.
 #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
@@ -126,7 +135,8 @@ impl From<Level> for u8
 }
 
 /// The school of magic a spell belongs to
-#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+// Homebrew schools get sorted after the standard schools by their names
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
 pub enum MagicSchool
 {
 	Abjuration,
@@ -136,7 +146,10 @@ pub enum MagicSchool
 	Evocation,
 	Illusion,
 	Necromancy,
-	Transmutation
+	Transmutation,
+	/// A school of magic that isn't one of the eight standard schools (ex: "Chronurgy"). Unlike custom values,
+	/// homebrew schools can be filtered by and themed like the standard schools.
+	Homebrew(String)
 }
 
 // Allows strings of magic schools to be converted to the MagicSchool type
@@ -175,7 +188,55 @@ impl fmt::Display for MagicSchool
 			Self::Evocation => String::from("Evocation"),
 			Self::Illusion => String::from("Illusion"),
 			Self::Necromancy => String::from("Necromancy"),
-			Self::Transmutation => String::from("Transmutation")
+			Self::Transmutation => String::from("Transmutation"),
+			Self::Homebrew(name) => name.clone()
+		};
+		write!(f, "{}", text)
+	}
+}
+
+/// How easy a spell is to get access to (for campaigns that restrict which spells characters can learn).
+// Sorted from the most available to the least available
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum Rarity
+{
+	Common,
+	Uncommon,
+	Rare,
+	/// A spell that only one caster (or a small group of casters) knows.
+	#[serde(alias = "Unique")]
+	Signature
+}
+
+// Allows strings of rarities to be converted to the Rarity type
+impl TryFrom<&str> for Rarity
+{
+	type Error = &'static str;
+
+	fn try_from(value: &str) -> Result<Self, Self::Error>
+	{
+		match value.to_lowercase().as_str()
+		{
+			"common" => Ok(Self::Common),
+			"uncommon" => Ok(Self::Uncommon),
+			"rare" => Ok(Self::Rare),
+			"signature" | "unique" => Ok(Self::Signature),
+			_ => Err("Invalid Rarity string.")
+		}
+	}
+}
+
+// Converts rarities into strings
+impl fmt::Display for Rarity
+{
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
+	{
+		let text = match self
+		{
+			Self::Common => "Common",
+			Self::Uncommon => "Uncommon",
+			Self::Rare => "Rare",
+			Self::Signature => "Signature"
 		};
 		write!(f, "{}", text)
 	}
@@ -210,7 +271,36 @@ pub enum CastingTime
 	Weeks(u16),
 	Months(u16),
 	Years(u16),
-	Special
+	Special,
+	/// A spell that can be cast with either of two casting times (ex: "Action or 8 hours").
+	Either(Box<CastingTime>, Box<CastingTime>)
+}
+
+impl CastingTime
+{
+	/// Every casting time a spell with this casting time can be cast with, in order (just itself unless it's an
+	/// `Either`).
+	pub fn alternatives(&self) -> Vec<&CastingTime>
+	{
+		match self
+		{
+			Self::Either(first, second) =>
+			{
+				let mut alternatives = first.alternatives();
+				alternatives.extend(second.alternatives());
+				alternatives
+			},
+			_ => vec![self]
+		}
+	}
+
+	/// Whether or not any of the casting times a spell with this casting time can be cast with match a condition.
+	/// Useful for filtering spells by casting time (ex: finding every spell that can be cast as a bonus action).
+	pub fn matches_any<F>(&self, condition: F) -> bool
+	where F: Fn(&CastingTime) -> bool
+	{
+		self.alternatives().into_iter().any(condition)
+	}
 }
 
 // Converts casting times into strings
@@ -250,7 +340,8 @@ impl fmt::Display for CastingTime
 			Self::Weeks(t) => get_amount_string(*t, "week"),
 			Self::Months(t) => get_amount_string(*t, "month"),
 			Self::Years(t) => get_amount_string(*t, "year"),
-			Self::Special => String::from("Special")
+			Self::Special => String::from("Special"),
+			Self::Either(first, second) => format!("{} or {}", first, second)
 		};
 		write!(f, "{}", text)
 	}
@@ -261,7 +352,100 @@ impl fmt::Display for CastingTime
 pub enum Distance
 {
 	Feet(u16),
-	Miles(u16)
+	Miles(u16),
+	Meters(u16),
+	Kilometers(u16),
+	/// Squares on a battle map grid (5 feet each).
+	Squares(u16)
+}
+
+/// How distances get converted before they're displayed in a spellbook.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DistanceConversion
+{
+	/// Meters and kilometers using the grid convention of 1.5 meters for every 5 feet and 1.5 kilometers for every
+	/// mile.
+	GridMeters,
+	/// Meters and kilometers using the exact lengths of feet and miles.
+	ExactMeters,
+	/// Battle map grid squares (5 feet each). Miles and kilometers don't get converted.
+	Squares
+}
+
+/// How the components of spells are displayed in a spellbook.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ComponentStyle
+{
+	/// The first letter of each component (ex: "V, S, M (a pinch of soot)").
+	#[default]
+	Shorthand,
+	/// The first letter of each component inside of a circle, with any material components after them in
+	/// parentheses. Anywhere that icons can't be drawn uses the shorthand instead.
+	Icons,
+	/// Full sentences (ex: "Verbal and somatic components, plus a material component: a pinch of soot").
+	Verbose
+}
+
+// The number of feet in a mile
+const FEET_PER_MILE: f32 = 5280.0;
+// The number of feet in a grid square
+const FEET_PER_SQUARE: f32 = 5.0;
+// The number of meters in a foot
+const METERS_PER_FOOT: f32 = 0.3048;
+// The number of meters in a foot using the grid convention (1.5 meters for every 5 feet)
+const GRID_METERS_PER_FOOT: f32 = 0.3;
+// The number of kilometers in a mile
+const KILOMETERS_PER_MILE: f32 = 1.609344;
+// The number of kilometers in a mile using the grid convention
+const GRID_KILOMETERS_PER_MILE: f32 = 1.5;
+
+// The units distances can be displayed in (singular, adjective form for areas of effect)
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum DistanceUnit
+{
+	Foot,
+	Mile,
+	Meter,
+	Kilometer,
+	Square
+}
+
+impl DistanceUnit
+{
+	// The name of the unit when there's a certain amount of it (ex: "foot" or "feet")
+	fn name(&self, amount: f32) -> &'static str
+	{
+		let singular = amount == 1.0;
+		match self
+		{
+			Self::Foot => if singular { "foot" } else { "feet" },
+			Self::Mile => if singular { "mile" } else { "miles" },
+			Self::Meter => if singular { "meter" } else { "meters" },
+			Self::Kilometer => if singular { "kilometer" } else { "kilometers" },
+			Self::Square => if singular { "square" } else { "squares" }
+		}
+	}
+
+	// The name of the unit used as an adjective (ex: "foot" in "15-foot cone")
+	fn adjective(&self) -> &'static str
+	{
+		match self
+		{
+			Self::Foot => "foot",
+			Self::Mile => "mile",
+			Self::Meter => "meter",
+			Self::Kilometer => "kilometer",
+			Self::Square => "square"
+		}
+	}
+}
+
+// Turns an amount into text with at most one decimal place (ex: "1.5", "18")
+fn format_amount(amount: f32) -> String
+{
+	let rounded = (amount * 10.0).round() / 10.0;
+	if rounded.fract() == 0.0 { format!("{}", rounded as i64) }
+	else { format!("{:.1}", rounded) }
 }
 
 impl Distance
@@ -271,11 +455,107 @@ impl Distance
 	///
 	/// Used in displaying distances for Aoe.
 	pub fn get_aoe_string(&self) -> String
+	{
+		self.get_aoe_string_with(None)
+	}
+
+	/// Same as `get_aoe_string()` but converts the distance first (if there's a conversion).
+	pub fn get_aoe_string_with(&self, conversion: Option<DistanceConversion>) -> String
+	{
+		let (amount, unit) = self.converted(conversion);
+		format!("{}-{}", format_amount(amount), unit.adjective())
+	}
+
+	/// Returns a string of the distance (like "60 feet"), converted first if there's a conversion.
+	pub fn to_string_with(&self, conversion: Option<DistanceConversion>) -> String
+	{
+		// Feet and miles have always been displayed in plural form, so keep them that way
+		match (self, conversion)
+		{
+			(Self::Feet(d), None) => format!("{} feet", d),
+			(Self::Miles(d), None) => format!("{} miles", d),
+			_ =>
+			{
+				let (amount, unit) = self.converted(conversion);
+				format!("{} {}", format_amount(amount), unit.name(amount))
+			}
+		}
+	}
+
+	/// The length of the distance in feet.
+	pub fn to_feet(&self) -> f32
+	{
+		match self
+		{
+			Self::Feet(d) => *d as f32,
+			Self::Miles(d) => *d as f32 * FEET_PER_MILE,
+			Self::Meters(d) => *d as f32 / METERS_PER_FOOT,
+			Self::Kilometers(d) => *d as f32 * 1000.0 / METERS_PER_FOOT,
+			Self::Squares(d) => *d as f32 * FEET_PER_SQUARE
+		}
+	}
+
+	/// The length of the distance in meters.
+	///
+	/// # Parameters
+	///
+	/// - `exact` True to use the exact length of feet and miles, false to use the grid convention of 1.5 meters for
+	///   every 5 feet and 1.5 kilometers for every mile.
+	pub fn to_meters(&self, exact: bool) -> f32
+	{
+		let (meters_per_foot, kilometers_per_mile) = if exact { (METERS_PER_FOOT, KILOMETERS_PER_MILE) }
+			else { (GRID_METERS_PER_FOOT, GRID_KILOMETERS_PER_MILE) };
+		match self
+		{
+			Self::Feet(d) => *d as f32 * meters_per_foot,
+			Self::Miles(d) => *d as f32 * kilometers_per_mile * 1000.0,
+			Self::Meters(d) => *d as f32,
+			Self::Kilometers(d) => *d as f32 * 1000.0,
+			Self::Squares(d) => *d as f32 * FEET_PER_SQUARE * meters_per_foot
+		}
+	}
+
+	/// The length of the distance in battle map grid squares (5 feet each).
+	pub fn to_squares(&self) -> f32
 	{
 		match self
 		{
-			Self::Feet(d) => format!("{}-foot", d),
-			Self::Miles(d) => format!("{}-mile", d)
+			Self::Squares(d) => *d as f32,
+			// Meters use the grid convention so 1.5 meters is one square
+			Self::Meters(d) => *d as f32 / (FEET_PER_SQUARE * GRID_METERS_PER_FOOT),
+			_ => self.to_feet() / FEET_PER_SQUARE
+		}
+	}
+
+	// The amount and unit of the distance after being converted
+	fn converted(&self, conversion: Option<DistanceConversion>) -> (f32, DistanceUnit)
+	{
+		match conversion
+		{
+			None => match self
+			{
+				Self::Feet(d) => (*d as f32, DistanceUnit::Foot),
+				Self::Miles(d) => (*d as f32, DistanceUnit::Mile),
+				Self::Meters(d) => (*d as f32, DistanceUnit::Meter),
+				Self::Kilometers(d) => (*d as f32, DistanceUnit::Kilometer),
+				Self::Squares(d) => (*d as f32, DistanceUnit::Square)
+			},
+			// Long distances don't fit on battle maps, so they stay the same
+			Some(DistanceConversion::Squares) => match self
+			{
+				Self::Miles(d) => (*d as f32, DistanceUnit::Mile),
+				Self::Kilometers(d) => (*d as f32, DistanceUnit::Kilometer),
+				_ => (self.to_squares(), DistanceUnit::Square)
+			},
+			Some(conversion) =>
+			{
+				let exact = conversion == DistanceConversion::ExactMeters;
+				match self
+				{
+					Self::Miles(_) | Self::Kilometers(_) => (self.to_meters(exact) / 1000.0, DistanceUnit::Kilometer),
+					_ => (self.to_meters(exact), DistanceUnit::Meter)
+				}
+			}
 		}
 	}
 }
@@ -285,12 +565,7 @@ impl fmt::Display for Distance
 {
 	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
 	{
-		let text = match self
-		{
-			Self::Feet(d) => format!("{} feet", d),
-			Self::Miles(d) => format!("{} miles", d)
-		};
-		write!(f, "{}", text)
+		write!(f, "{}", self.to_string_with(None))
 	}
 }
 
@@ -315,22 +590,36 @@ pub enum Aoe
 	Cylinder(Distance, Distance),
 }
 
+impl Aoe
+{
+	/// Returns a string of the area of effect (like "15-foot cone") with its distances converted first (if there's a
+	/// conversion).
+	pub fn to_string_with(&self, conversion: Option<DistanceConversion>) -> String
+	{
+		match self
+		{
+			Self::Line(l) => format!("{} line", l.get_aoe_string_with(conversion)),
+			Self::Cone(l) => format!("{} cone", l.get_aoe_string_with(conversion)),
+			Self::Cube(l) => format!("{} cube", l.get_aoe_string_with(conversion)),
+			Self::Sphere(r) => format!("{} radius", r.get_aoe_string_with(conversion)),
+			Self::Emanation(r) => format!("{} emanation", r.get_aoe_string_with(conversion)),
+			Self::Hemisphere(r) => format!("{} radius hemisphere", r.get_aoe_string_with(conversion)),
+			Self::Cylinder(r, h) => format!
+			(
+				"{} radius, {} height cylinder",
+				r.get_aoe_string_with(conversion),
+				h.get_aoe_string_with(conversion)
+			)
+		}
+	}
+}
+
 // Converts Aoes into strings
 impl fmt::Display for Aoe
 {
 	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
 	{
-		let text = match self
-		{
-			Self::Line(l) => format!("{} line", l.get_aoe_string()),
-			Self::Cone(l) => format!("{} cone", l.get_aoe_string()),
-			Self::Cube(l) => format!("{} cube", l.get_aoe_string()),
-			Self::Sphere(r) => format!("{} radius", r.get_aoe_string()),
-			Self::Emanation(r) => format!("{} emanation", r.get_aoe_string()),
-			Self::Hemisphere(r) => format!("{} radius hemisphere", r.get_aoe_string()),
-			Self::Cylinder(r, h) => format!("{} radius, {} height cylinder", r.get_aoe_string(), h.get_aoe_string())
-		};
-		write!(f, "{}", text)
+		write!(f, "{}", self.to_string_with(None))
 	}
 }
 
@@ -349,34 +638,140 @@ pub enum Range
 	Special
 }
 
-// Converts spell ranges into strings
-impl fmt::Display for Range
+impl Range
 {
-	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
+	/// Returns a string of the range (like "60 feet" or "Self (15-foot cone)") with its distances converted first (if
+	/// there's a conversion).
+	pub fn to_string_with(&self, conversion: Option<DistanceConversion>) -> String
 	{
-		let text = match self
+		match self
 		{
 			Self::Yourself(o) =>
 			{
 				match o
 				{
 					None => String::from("Self"),
-					Some(a) => format!("Self ({})", a)
+					Some(a) => format!("Self ({})", a.to_string_with(conversion))
 				}
 			}
 			Self::Touch => String::from("Touch"),
-			Self::Dist(d) => d.to_string(),
+			Self::Dist(d) => d.to_string_with(conversion),
 			Self::Sight => String::from("Sight"),
 			Self::Unlimited => String::from("Unlimited"),
 			Self::Special => String::from("Special")
+		}
+	}
+}
+
+// Converts spell ranges into strings
+impl fmt::Display for Range
+{
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
+	{
+		write!(f, "{}", self.to_string_with(None))
+	}
+}
+
+/// An amount of dice to roll (like the "1d4" in "1d4 hours").
+///
+/// Gets written in spell files as a string like "1d4" or "2d6".
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct DiceExpr
+{
+	/// The number of dice to roll.
+	pub count: u16,
+	/// The number of sides on each die.
+	pub sides: u16
+}
+
+// Allows strings of dice like "1d4" to be converted to the DiceExpr type
+impl TryFrom<&str> for DiceExpr
+{
+	type Error = &'static str;
+
+	fn try_from(value: &str) -> Result<Self, Self::Error>
+	{
+		let error = "Dice must be written like \"1d4\" (a number of dice, a 'd', then a number of sides).";
+		let (count, sides) = match value.trim().to_lowercase().split_once('d')
+		{
+			Some((count, sides)) => (count.parse::<u16>(), sides.parse::<u16>()),
+			None => return Err(error)
 		};
-		write!(f, "{}", text)
+		match (count, sides)
+		{
+			(Ok(count), Ok(sides)) if count > 0 && sides > 0 => Ok(Self { count: count, sides: sides }),
+			_ => Err(error)
+		}
+	}
+}
+
+// Allows dice to be read from spell files
+impl TryFrom<String> for DiceExpr
+{
+	type Error = &'static str;
+
+	fn try_from(value: String) -> Result<Self, Self::Error>
+	{
+		Self::try_from(value.as_str())
+	}
+}
+
+// Allows dice to be written to spell files
+impl From<DiceExpr> for String
+{
+	fn from(value: DiceExpr) -> Self
+	{
+		value.to_string()
+	}
+}
+
+// Converts dice into strings
+impl fmt::Display for DiceExpr
+{
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
+	{
+		write!(f, "{}d{}", self.count, self.sides)
+	}
+}
+
+/// Units of time for durations that don't have a fixed number of units.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub enum TimeUnit
+{
+	Seconds,
+	Rounds,
+	Minutes,
+	Hours,
+	Days,
+	Weeks,
+	Months,
+	Years
+}
+
+impl TimeUnit
+{
+	/// The singular name of the unit of time ("hour", "minute", etc.).
+	pub fn name(&self) -> &'static str
+	{
+		match self
+		{
+			Self::Seconds => "second",
+			Self::Rounds => "round",
+			Self::Minutes => "minute",
+			Self::Hours => "hour",
+			Self::Days => "day",
+			Self::Weeks => "week",
+			Self::Months => "month",
+			Self::Years => "year"
+		}
 	}
 }
 
 /// The length of time a spell's effect(s) lasts.
 ///
 /// u16 values are the number of units the spell can last.
+/// `Rolled` durations last for a random number of units (like "1d4 hours").
 /// Bool values are whether or not the spell requires concentration.
 #[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize, Deserialize)]
 pub enum Duration
@@ -390,12 +785,30 @@ pub enum Duration
 	Weeks(u16, bool),
 	Months(u16, bool),
 	Years(u16, bool),
-	DispelledOrTriggered(bool),
+	Rolled(DiceExpr, TimeUnit, bool),
+	// Spell files from before this was renamed still use "DispelledOrTriggered"
+	#[serde(alias = "DispelledOrTriggered")]
+	UntilDispelledOrTriggered(bool),
 	UntilDispelled(bool),
 	Permanent,
 	Special(bool)
 }
 
+impl Duration
+{
+	/// Whether or not the duration requires concentration.
+	pub fn requires_concentration(&self) -> bool
+	{
+		match self
+		{
+			Self::Instant | Self::Permanent => false,
+			Self::Seconds(_, c) | Self::Rounds(_, c) | Self::Minutes(_, c) | Self::Hours(_, c) | Self::Days(_, c) |
+			Self::Weeks(_, c) | Self::Months(_, c) | Self::Years(_, c) | Self::Rolled(_, _, c) |
+			Self::UntilDispelledOrTriggered(c) | Self::UntilDispelled(c) | Self::Special(c) => *c
+		}
+	}
+}
+
 // Converts spell durations into strings
 impl fmt::Display for Duration
 {
@@ -452,7 +865,13 @@ impl fmt::Display for Duration
 				if *c { format!("Concentration, up to {}", s) }
 				else { s }
 			},
-			Self::DispelledOrTriggered(c) =>
+			Self::Rolled(dice, unit, c) =>
+			{
+				let s = format!("{} {}s", dice, unit.name());
+				if *c { format!("Concentration, up to {}", s) }
+				else { s }
+			},
+			Self::UntilDispelledOrTriggered(c) =>
 			{
 				let s = String::from("Until dispelled or triggered");
 				if *c { format!("Concentration, up {}", s) }
@@ -506,64 +925,356 @@ fn get_amount_string(num: u16, unit: &str) -> String
 	}
 }
 
-/// Data containing all of the information about a spell needed to display it in a spellbook.
-#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
-pub struct Spell
+/// Replaces the fields of the json of a spell with their versions for a certain locale (see
+/// `Spell::from_json_file_with_locale()`). Fields for a locale have the locale after an "@" at the end of their names
+/// (ex: "name@de").
+pub fn localize_spell_json(spell_json: &mut Value, locale: &str)
 {
-	pub name: String,
-	/// Can be custom value or Level.
-	pub level: SpellField<Level>,
-	/// Can be custom value or MagicSchool.
-	pub school: SpellField<MagicSchool>,
-	/// Whether or not the spell can be casted as a ritual.
-	pub is_ritual: bool,
-	/// Can be custom value or CastingTime.
-	pub casting_time: SpellField<CastingTime>,
-	/// Can be custom value or Range.
-	pub range: SpellField<Range>,
-	/// Whether or not the spell requires a verbal component to be cast.
-	pub has_v_component: bool,
-	/// Whether or not the spell requires a somantic component to be cast.
-	pub has_s_component: bool,
-	/// Text that lists the material components a spell might need to be cast.
-	/// A value of `None` represents the spell not needing any material components.
-	pub m_components: Option<String>,
-	/// Can be custom value or Duration.
-	pub duration: SpellField<Duration>,
-	/// Text that describes the effects of the spell.
-	/// 
-	/// Can be formatted with font changing tags, bullet points, and tables.
-	///
-	/// See spell file documentation for more information (<https://github.com/ChandlerJayCalkins/dnd_spellbook_maker>).
-	pub description: String,
-	/// Optional text that describes the benefits a spell gains from being upcast (being cast at a level higher than
-	/// its base level if it's a non-cantrip or being cast by a character higher than a certain level if its a
-	/// cantrip).
-	pub upcast_description: Option<String>,
-	/// Any tables that the spell might have in its description
-	pub tables: Vec<Table>
+	let fields = match spell_json.as_object_mut()
+	{
+		Some(fields) => fields,
+		None => return
+	};
+	// Use the fields for just the language first so the fields for the language and region can replace them
+	let mut locales = vec![locale];
+	if let Some((language, _)) = locale.split_once(['-', '_']) { locales.insert(0, language); }
+	for locale in locales
+	{
+		let localized_fields: Vec<(String, Value)> = fields.iter()
+			.filter_map(|(name, value)| match name.split_once('@')
+			{
+				Some((field, field_locale)) if field_locale.eq_ignore_ascii_case(locale) =>
+				Some((field.to_string(), value.clone())),
+				_ => None
+			})
+			.collect();
+		for (field, value) in localized_fields { fields.insert(field, value); }
+	}
 }
 
-impl Spell
+/// Loads the cells of tables in the json of a spell from csv files, so big tables (like random effect tables with
+/// hundreds of rows) don't have to be written out as json arrays. Tables load a csv file by having a `cells_csv` field
+/// with the path to the file (relative to the spell file) instead of a `cells` field. If a table doesn't have
+/// `column_labels`, the first row of the csv file is used as the column labels. Tables for other locales (ex:
+/// "tables@de") get loaded the same way.
+///
+/// # Parameters
+///
+/// - `spell_json` The json of the spell.
+/// - `spell_file_path` The path to the spell file the json came from.
+///
+/// # Output
+///
+/// - `Ok` Returns nothing.
+/// - `Err` A `CsvTableError` if a csv file couldn't be read or parsed, if a row doesn't have a cell for each column
+///   label, or if a table has both `cells` and `cells_csv`.
+pub fn load_csv_tables(spell_json: &mut Value, spell_file_path: &str) -> Result<(), Box<dyn error::Error + Send + Sync>>
 {
-	/// Constructs a spell object from a json file.
-	///
-	/// # Parameters
-	///
-	/// - `file_path` The path to the json file to create the spell from.
-	///
-	/// # Output
-	///
-	/// - `Ok` A spell object.
-	/// - `Err` Any errors that occured.
-	pub fn from_json_file(file_path: &str) -> Result<Self, Box<dyn error::Error>>
+	let fields = match spell_json.as_object_mut()
 	{
-		let file = fs::File::open(file_path)?;
-		let reader = BufReader::new(file);
-		let spell = from_reader(reader)?;
-		Ok(spell)
-	}
-
+		Some(fields) => fields,
+		None => return Ok(())
+	};
+	let spell_folder = Path::new(spell_file_path).parent().unwrap_or(Path::new(""));
+	let table_lists = fields.iter_mut()
+		.filter(|(name, _)| name.as_str() == "tables" || name.starts_with("tables@"))
+		.filter_map(|(_, tables)| tables.as_array_mut());
+	for tables in table_lists
+	{
+		for table in tables.iter_mut().filter_map(Value::as_object_mut)
+		{
+			let csv_path = match table.get("cells_csv").and_then(Value::as_str)
+			{
+				Some(csv_path) => spell_folder.join(csv_path).to_string_lossy().to_string(),
+				None => continue
+			};
+			let error = |message: &str| CsvTableError { file_path: csv_path.clone(), message: String::from(message) };
+			if table.contains_key("cells")
+			{
+				return Err(Box::new(error("the table has both `cells` and `cells_csv`")));
+			}
+			let text = fs::read_to_string(&csv_path).map_err(|io_error| error(&io_error.to_string()))?;
+			let mut rows = parse_csv(&text).map_err(|message| error(&message))?;
+			let column_count = match table.get("column_labels").and_then(Value::as_array)
+			{
+				Some(column_labels) => column_labels.len(),
+				None =>
+				{
+					if rows.is_empty() { return Err(Box::new(error("there is no row of column labels"))); }
+					let (_, column_labels) = rows.remove(0);
+					let column_count = column_labels.len();
+					table.insert(String::from("column_labels"), Value::from(column_labels));
+					column_count
+				}
+			};
+			// Rows with the wrong number of cells would throw off every column after them
+			if let Some((line, row)) = rows.iter().find(|(_, row)| row.len() != column_count)
+			{
+				let message = format!("the row on line {} has {} cells but there are {} column labels", line, row.len(),
+					column_count);
+				return Err(Box::new(error(&message)));
+			}
+			let cells: Vec<Vec<String>> = rows.into_iter().map(|(_, row)| row).collect();
+			table.remove("cells_csv");
+			table.insert(String::from("cells"), Value::from(cells));
+		}
+	}
+	Ok(())
+}
+
+/// Splits the text of a csv file into rows of cells, along with the line each row starts on. Cells can be put in
+/// double quotes to have commas, line breaks, or double quotes (written twice) in them. Quoted cells are kept exactly
+/// as they're written, while the whitespace around unquoted cells is trimmed. Blank lines are left out.
+fn parse_csv(text: &str) -> Result<Vec<(usize, Vec<String>)>, String>
+{
+	let mut rows = Vec::new();
+	let mut row = Vec::new();
+	let mut cell = String::new();
+	// Whether the current cell started with a quote, whether that quote is still open, and whether it's been closed
+	let mut quoted = false;
+	let mut in_quotes = false;
+	let mut closed = false;
+	let mut line = 1;
+	let mut row_line = 1;
+	let end_cell = |cell: &mut String, quoted: bool|
+	{
+		let cell = std::mem::take(cell);
+		if quoted { cell } else { cell.trim().to_string() }
+	};
+	// Skip the byte order mark that some spreadsheet programs put at the start of csv files
+	let mut chars = text.trim_start_matches('\u{FEFF}').chars().peekable();
+	while let Some(c) = chars.next()
+	{
+		if c == '\n' { line += 1; }
+		if in_quotes
+		{
+			match c
+			{
+				// Two double quotes in a row are a double quote in the cell
+				'"' if chars.peek() == Some(&'"') =>
+				{
+					cell.push('"');
+					chars.next();
+				},
+				'"' =>
+				{
+					in_quotes = false;
+					closed = true;
+				},
+				_ => cell.push(c)
+			}
+			continue;
+		}
+		match c
+		{
+			'"' if !quoted && cell.trim().is_empty() =>
+			{
+				cell.clear();
+				quoted = true;
+				in_quotes = true;
+			},
+			',' =>
+			{
+				row.push(end_cell(&mut cell, quoted));
+				quoted = false;
+				closed = false;
+			},
+			'\n' =>
+			{
+				row.push(end_cell(&mut cell, quoted));
+				rows.push((row_line, std::mem::take(&mut row)));
+				row_line = line;
+				quoted = false;
+				closed = false;
+			},
+			'\r' => (),
+			_ if closed && c.is_whitespace() => (),
+			_ if closed =>
+			{
+				return Err(format!("the row on line {} has text after the closing quote of a cell", row_line));
+			},
+			_ => cell.push(c)
+		}
+	}
+	if in_quotes { return Err(String::from("a quoted cell never ends")); }
+	if quoted || !cell.is_empty() || !row.is_empty()
+	{
+		row.push(end_cell(&mut cell, quoted));
+		rows.push((row_line, row));
+	}
+	Ok(rows.into_iter().filter(|(_, row)| row.iter().any(|cell| !cell.is_empty())).collect())
+}
+
+/// Error for when the cells of a table couldn't be loaded from a csv file (see `load_csv_tables()`).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CsvTableError
+{
+	/// The path to the csv file.
+	pub file_path: String,
+	/// What went wrong.
+	pub message: String
+}
+
+impl fmt::Display for CsvTableError
+{
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
+	{
+		write!(f, "Couldn't load table cells from \"{}\": {}.", self.file_path, self.message)
+	}
+}
+
+impl error::Error for CsvTableError {}
+
+/// Layout settings for a single spell that override the `SpellLayoutOptions` of the spellbook it goes in. Settings
+/// that are `None` use the spellbook's setting.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+pub struct LayoutHints
+{
+	/// Whether the spell starts on a new page (`false` to start right after the spell before it if there's room).
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub start_on_new_page: Option<bool>,
+	/// Whether the spell's tables can be split across a page break (`false` to move tables that fit on a single page
+	/// to the next page instead).
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub allow_table_split: Option<bool>
+}
+
+/// Options for predicting how many lines the text of a spell takes up with `Spell::stats()`.
+pub struct SpellStatsOptions<'a>
+{
+	/// Measures the width of text (ex: a `layout::FontMeasurer` made with the fonts and body font size of a
+	/// spellbook).
+	pub measurer: &'a dyn TextMeasurer,
+	/// The width of the text box that spell descriptions go in (the page width minus the left and right margins in
+	/// spellbooks), in the units of the measurer.
+	pub textbox_width: f32,
+	/// How far the first line of every paragraph after the first one gets tabbed in, in the units of the measurer.
+	pub tab_amount: f32
+}
+
+/// Statistics about the text of a spell from `Spell::stats()`.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SpellStats
+{
+	/// The number of words in the description and upcast description (not counting tags).
+	pub word_count: usize,
+	/// The predicted number of lines that the description and upcast description take up (not counting tables).
+	pub line_count: usize,
+	/// The number of tables in the spell.
+	pub table_count: usize,
+	/// The widest word in the description and upcast description (the one most likely to need hyphenating in narrow
+	/// text boxes).
+	pub longest_token: String,
+	/// The width of the widest word, in the units of the measurer.
+	pub longest_token_width: f32
+}
+
+/// Data containing all of the information about a spell needed to display it in a spellbook.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct Spell
+{
+	pub name: String,
+	/// Can be custom value or Level.
+	pub level: SpellField<Level>,
+	/// Can be custom value or MagicSchool.
+	pub school: SpellField<MagicSchool>,
+	/// Whether or not the spell can be casted as a ritual.
+	pub is_ritual: bool,
+	/// Can be custom value or CastingTime.
+	pub casting_time: SpellField<CastingTime>,
+	/// Can be custom value or Range.
+	pub range: SpellField<Range>,
+	/// Whether or not the spell requires a verbal component to be cast.
+	pub has_v_component: bool,
+	/// Whether or not the spell requires a somantic component to be cast.
+	pub has_s_component: bool,
+	/// Text that lists the material components a spell might need to be cast.
+	/// A value of `None` represents the spell not needing any material components.
+	pub m_components: Option<String>,
+	/// Can be custom value or Duration.
+	pub duration: SpellField<Duration>,
+	/// Text that describes the effects of the spell.
+	/// 
+	/// Can be formatted with font changing tags, bullet points, and tables.
+	///
+	/// See spell file documentation for more information (<https://github.com/ChandlerJayCalkins/dnd_spellbook_maker>).
+	pub description: String,
+	/// Optional text that describes the benefits a spell gains from being upcast (being cast at a level higher than
+	/// its base level if it's a non-cantrip or being cast by a character higher than a certain level if its a
+	/// cantrip).
+	pub upcast_description: Option<String>,
+	/// Any tables that the spell might have in its description
+	pub tables: Vec<Table>,
+	/// Optional abbreviation of the book the spell comes from (ex: "XGE"). Used to tell spells with the same name
+	/// apart in spellbook bookmarks.
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub source: Option<String>,
+	/// The classes that have the spell on their spell list (ex: "Wizard"). Used to split spellbooks into a chapter
+	/// for each class.
+	#[serde(default, skip_serializing_if = "Vec::is_empty")]
+	pub classes: Vec<String>,
+	/// How easy the spell is to get access to (`None` if the campaign doesn't restrict it). Gets written in a small
+	/// tag at the end of the line with the spell's level and school. Can also be written as "availability" in spell
+	/// files.
+	#[serde(default, alias = "availability", skip_serializing_if = "Option::is_none")]
+	pub rarity: Option<Rarity>,
+	/// Page break settings for just this spell (`None` to use the settings of the spellbook).
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub layout: Option<LayoutHints>,
+	/// Optional quote about the spell that gets written in italics between its casting time, range, components, and
+	/// duration and its description. A last line that starts with an em dash (or "--") is the person who said it.
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub flavor: Option<String>
+}
+
+impl Spell
+{
+	/// Constructs a spell object from a json file.
+	///
+	/// # Parameters
+	///
+	/// - `file_path` The path to the json file to create the spell from.
+	///
+	/// # Output
+	///
+	/// - `Ok` A spell object.
+	/// - `Err` Any errors that occured.
+	pub fn from_json_file(file_path: &str) -> Result<Self, Box<dyn error::Error + Send + Sync>>
+	{
+		let file = fs::File::open(file_path)?;
+		let reader = BufReader::new(file);
+		let mut value = from_reader(reader)?;
+		load_csv_tables(&mut value, file_path)?;
+		Ok(from_value(value)?)
+	}
+
+	/// Constructs a spell object from a json file, using the text for a certain locale (language) wherever the file
+	/// has it.
+	///
+	/// Spell files can have a version of any field for another locale by putting the locale after an "@" at the end
+	/// of the field's name (ex: `"name@de": "Feuerball"`). Locales with a region (like "de-AT") fall back to just the
+	/// language ("de"), and fields without a version for the locale fall back to the default field.
+	///
+	/// # Parameters
+	///
+	/// - `file_path` The path to the json file to create the spell from.
+	/// - `locale` The locale to use the text for (ex: "de" or "pt-BR").
+	///
+	/// # Output
+	///
+	/// - `Ok` A spell object.
+	/// - `Err` Any errors that occured.
+	pub fn from_json_file_with_locale(file_path: &str, locale: &str)
+	-> Result<Self, Box<dyn error::Error + Send + Sync>>
+	{
+		let file = fs::File::open(file_path)?;
+		let reader = BufReader::new(file);
+		let mut value = from_reader(reader)?;
+		load_csv_tables(&mut value, file_path)?;
+		localize_spell_json(&mut value, locale);
+		Ok(from_value(value)?)
+	}
+
 	/// Saves a spell to a json file.
 	///
 	/// # Parameters
@@ -575,7 +1286,7 @@ impl Spell
 	///
 	/// - `Ok` Nothing if there were no errors.
 	/// - `Err` Any errors that occurred.
-	pub fn to_json_file(&self, file_path: &str, compress: bool) -> Result<(), Box<dyn error::Error>>
+	pub fn to_json_file(&self, file_path: &str, compress: bool) -> Result<(), Box<dyn error::Error + Send + Sync>>
 	{
 		let file = fs::File::create(file_path)?;
 		if compress { to_writer(file, self)?; }
@@ -583,6 +1294,11 @@ impl Spell
 		Ok(())
 	}
 
+	/// Gives the spell's name in lowercase with underscores between words and no punctuation, which is how spell files
+	/// are named (ex: "Antipathy / Sympathy" becomes "antipathy_sympathy" and "Bigby's Hand" becomes "bigbys_hand").
+	/// Use `utils::safe_filename()` on it to make sure it's a valid file name.
+	pub fn slug(&self) -> String { name_slug(&self.name, '_') }
+
 	/// Gets a string of the required components for a spell.
 	///
 	/// Ex: "V, S, M (a bit of sulfur and some wood bark)", "V, S", "V, M (a piece of hair)".
@@ -626,6 +1342,192 @@ impl Spell
 		component_string
 	}
 
+	/// Gets the components of the spell as text in a certain style. Icons can't be drawn in text, so the `Icons`
+	/// style gives the shorthand text.
+	///
+	/// Ex: "V, S, M (a pinch of soot)" or "Verbal and somatic components, plus a material component: a pinch of soot".
+	pub fn get_component_text(&self, style: ComponentStyle) -> String
+	{
+		if style != ComponentStyle::Verbose { return self.get_component_string(); }
+		// The verbal and somatic components
+		let text = match (self.has_v_component, self.has_s_component)
+		{
+			(true, true) => "Verbal and somatic components",
+			(true, false) => "Verbal component",
+			(false, true) => "Somatic component",
+			(false, false) => ""
+		};
+		// The material components
+		match (&self.m_components, text.is_empty())
+		{
+			(Some(m), true) => format!("A material component: {}", m),
+			(Some(m), false) => format!("{}, plus a material component: {}", text, m),
+			(None, true) => String::from("None"),
+			(None, false) => String::from(text)
+		}
+	}
+
+	/// The material component of the spell (if it has one) with its cost and whether the spell consumes it picked out
+	/// of its text.
+	pub fn material_component(&self) -> Option<MaterialComponent>
+	{
+		let text = self.m_components.as_ref()?;
+		// Take the part about the spell consuming the component off the end
+		let lowercase = text.to_lowercase();
+		let consume_start = [", all of which the spell consumes", ", which the spell consumes"].iter()
+			.find_map(|clause| lowercase.find(clause).map(|start| (start, clause.len())));
+		let description = match consume_start
+		{
+			Some((start, length)) => format!("{}{}", &text[..start], &text[start + length..]),
+			None => text.clone()
+		};
+		Some(MaterialComponent
+		{
+			cost: gold_cost(&description),
+			consumed: consume_start.is_some() || lowercase.contains("consumes"),
+			description: description.trim().to_string()
+		})
+	}
+
+	/// The damage of the spell at each tier of character levels if it's a cantrip whose damage increases when
+	/// characters reach certain levels (ex: "The damage increases by 1d10 when you reach levels 5 (2d10), 11 (3d10),
+	/// and 17 (4d10)."). Each tier has the character level it starts at (starting with 1) and the damage at that level.
+	/// Returns `None` if the spell isn't a cantrip or its damage doesn't increase that way.
+	pub fn cantrip_damage_tiers(&self) -> Option<Vec<(u8, String)>>
+	{
+		if self.level != SpellField::Controlled(Level::Cantrip) { return None; }
+		// Spells from the 2024 Player's Handbook have it in their upcast description and older ones have it at the end
+		// of their description
+		self.upcast_description.iter().chain([&self.description]).find_map(|text| damage_tiers(text))
+	}
+
+	/// Splits the spell's flavor quote into the quote and who said it (without the em dash in front of it). Returns
+	/// `None` if the spell doesn't have a flavor quote.
+	pub fn flavor_quote(&self) -> Option<(&str, Option<&str>)>
+	{
+		let flavor = self.flavor.as_deref()?.trim();
+		let (quote, last_line) = match flavor.rsplit_once('\n')
+		{
+			Some((quote, last_line)) => (quote.trim_end(), last_line.trim()),
+			None => ("", flavor)
+		};
+		match last_line.strip_prefix('—').or_else(|| last_line.strip_prefix("--"))
+		{
+			Some(attribution) if !quote.is_empty() => Some((quote, Some(attribution.trim_start()))),
+			_ => Some((flavor, None))
+		}
+	}
+
+	/// Whether or not the spell requires concentration (custom durations do if they start with "Concentration").
+	pub fn requires_concentration(&self) -> bool
+	{
+		match &self.duration
+		{
+			SpellField::Controlled(duration) => duration.requires_concentration(),
+			SpellField::Custom(text) => text.trim_start().to_lowercase().starts_with("concentration")
+		}
+	}
+
+	/// Whether or not the given class has this spell on its spell list (ignoring capitalization).
+	pub fn has_class(&self, class: &str) -> bool
+	{
+		self.classes.iter().any(|spell_class| spell_class.eq_ignore_ascii_case(class))
+	}
+
+	/// A key for sorting spells by level (cantrips first and custom levels last) and then by name.
+	pub fn level_sort_key(&self) -> (u8, String)
+	{
+		let level = match &self.level
+		{
+			SpellField::Controlled(level) => u8::from(level),
+			SpellField::Custom(_) => 10
+		};
+		(level, self.name.to_lowercase())
+	}
+
+	/// A key for sorting spells by rarity (spells without a rarity first, then from the most available to the least
+	/// available) and then by name.
+	pub fn rarity_sort_key(&self) -> (Option<Rarity>, String)
+	{
+		(self.rarity, self.name.to_lowercase())
+	}
+
+	/// The first sentence of the spell's description (skipping any tables and bullet points at the start of it).
+	/// Keeps any tags in it so it can be written the same way as the description.
+	pub fn summary(&self) -> String
+	{
+		let paragraph = self.description.split('\n').map(str::trim).find(|paragraph|
+		{
+			let first_token = paragraph.split_whitespace().next().unwrap_or_default();
+			!first_token.is_empty() && table_tag_index(first_token).is_none() &&
+			!["-", "\u{2022}"].contains(&first_token)
+		});
+		let paragraph = paragraph.unwrap_or_default();
+		// The sentence ends at the first period, exclamation mark, or question mark that's followed by whitespace
+		let end = paragraph.char_indices()
+			.find(|(index, c)| matches!(c, '.' | '!' | '?') &&
+				paragraph[index + 1..].starts_with(char::is_whitespace))
+			.map_or(paragraph.len(), |(index, _)| index + 1);
+		String::from(&paragraph[..end])
+	}
+
+	/// Counts the words and tables in the spell and predicts how many lines its description and upcast description
+	/// take up when they're split into lines the way spellbooks split them. Helps keep spells within a certain
+	/// length (like to fit them on a single page).
+	pub fn stats(&self, options: &SpellStatsOptions) -> SpellStats
+	{
+		let mut stats = SpellStats { table_count: self.tables.len(), ..Default::default() };
+		// The upcast description gets split into lines as its own paragraph after the description
+		let upcast_paragraphs = self.upcast_description.iter().flat_map(|text| text.split('\n'));
+		let paragraphs = self.description.split('\n').chain(upcast_paragraphs);
+		let mut tab_amount = 0.0;
+		for paragraph in paragraphs
+		{
+			let mut tokens = paragraph.split_whitespace().peekable();
+			let first_token = tokens.peek().copied().unwrap_or_default();
+			// Tables and page breaks don't have any lines of text
+			if is_paragraph_tag(first_token) { continue; }
+			let is_bullet = ["-", "\u{2022}"].contains(&first_token);
+			if is_bullet { tokens.next(); }
+			for (index, token) in tokens.enumerate()
+			{
+				if is_text_tag(token) { continue; }
+				let word = unescape_token(token, index == 0);
+				if !word.chars().any(char::is_alphanumeric) { continue; }
+				stats.word_count += 1;
+				let width = options.measurer.text_width(word, FontVariant::Regular);
+				if width > stats.longest_token_width
+				{
+					stats.longest_token = String::from(word);
+					stats.longest_token_width = width;
+				}
+			}
+			let text = if is_bullet { paragraph.trim_start()[first_token.len()..].trim_start() } else { paragraph };
+			let lines = if is_bullet
+			{
+				// The text of bullet points lines up after the bullet
+				let width = options.textbox_width - options.measurer.text_width("\u{2022} ", FontVariant::Regular);
+				layout::wrap_text(text, width, width, FontVariant::Regular, options.measurer)
+			}
+			else
+			{
+				let lines = layout::wrap_text
+				(
+					text,
+					options.textbox_width - tab_amount,
+					options.textbox_width,
+					FontVariant::Regular,
+					options.measurer
+				);
+				// Every paragraph after the first one with text in it gets tabbed in
+				if !lines.is_empty() { tab_amount = options.tab_amount; }
+				lines
+			};
+			stats.line_count += lines.len();
+		}
+		stats
+	}
+
 	/// Gets the school and level info from a spell and turns it into text that says something like "nth-Level School-Type".
 	///
 	/// Ex: "1st-Level abjuration", "8th-Level transmutation", "evocation cantrip".
@@ -643,6 +1545,17 @@ impl Spell
 		text
 	}
 
+	/// Gets the range of the spell as text (like "60 feet" or "Self (15-foot cone)") with its distances converted
+	/// first (if there's a conversion).
+	pub fn get_range_text(&self, conversion: Option<DistanceConversion>) -> String
+	{
+		match &self.range
+		{
+			SpellField::Controlled(range) => range.to_string_with(conversion),
+			SpellField::Custom(range) => range.clone()
+		}
+	}
+
 	/// Gets the casting time and ritual info from a spell and turns it into text that says something like
 	/// "1 action or Ritual", "1 bonus action", or "2 hours"
 	pub fn get_casting_time_text(&self) -> String
@@ -653,3 +1566,683 @@ impl Spell
 		else { self.casting_time.to_string() }
 	}
 }
+
+/// The material component of a spell with its cost picked out of its text (see `Spell::material_component()`).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MaterialComponent
+{
+	/// The text of the component without the part about the spell consuming it (ex: "a diamond worth 50+ GP").
+	pub description: String,
+	/// The least amount of gold pieces the component can be worth (`None` if no cost is written in gold pieces).
+	/// Components with more than one cost add them up (ex: 1,100 for "one jacinth worth 1,000+ GP and one silver bar
+	/// worth 100+ GP").
+	pub cost: Option<u64>,
+	/// Whether or not the spell consumes the component.
+	pub consumed: bool
+}
+
+/// Adds up every amount of gold pieces in some text (ex: 1,050 from "a gem worth 1,000+ GP and ink worth 50 gp").
+/// Returns `None` if there aren't any.
+fn gold_cost(text: &str) -> Option<u64>
+{
+	let tokens: Vec<&str> = text.split_whitespace().collect();
+	let mut cost = None;
+	for (index, token) in tokens.iter().enumerate().skip(1)
+	{
+		if !token.trim_end_matches([',', '.', ';']).eq_ignore_ascii_case("gp") { continue; }
+		// The amount is the token before "GP" (with commas between its digits and maybe a "+" after it)
+		let amount: String = tokens[index - 1].trim_end_matches('+').chars().filter(|c| *c != ',').collect();
+		if let Ok(amount) = amount.parse::<u64>() { cost = Some(cost.unwrap_or(0) + amount); }
+	}
+	cost
+}
+
+/// Picks the damage at each tier of character levels out of the sentence in some text that says how a cantrip's damage
+/// increases (ex: [(1, "1d8"), (5, "2d8"), (11, "3d8"), (17, "4d8")] from "The spell's damage increases by 1d8 when you
+/// reach 5th level (2d8), 11th level (3d8), and 17th level (4d8)."). The damage before the first increase is the
+/// damage of the first increase with one less of each die.
+fn damage_tiers(text: &str) -> Option<Vec<(u8, String)>>
+{
+	let sentence = text.split(['.', '\n']).find(|sentence|
+	{
+		let sentence = sentence.to_lowercase();
+		sentence.contains("damage increases") && sentence.contains("when you reach")
+	})?;
+	// Each level with the damage at that level in parentheses after it (ex: "levels 5 (2d10)" or "5th level (2d10)")
+	let tier_pattern = Regex::new(r"(\d+)(?:st|nd|rd|th)?(?: level)?\s*\(([^)]*\d+d\d+[^)]*)\)").unwrap();
+	let mut tiers: Vec<(u8, String)> = tier_pattern.captures_iter(sentence)
+		.filter_map(|captures| Some((captures[1].parse().ok()?, captures[2].trim().to_string())))
+		.collect();
+	// The tiers have to go up in order after level 1
+	if tiers.is_empty() || tiers[0].0 <= 1 || tiers.windows(2).any(|pair| pair[0].0 >= pair[1].0) { return None; }
+	// Take one die away from each amount of dice in the first increase (ex: "1d8 or 1d12" from "2d8 or 2d12")
+	let dice_pattern = Regex::new(r"(\d+)d(\d+)").unwrap();
+	let mut valid = true;
+	let first_damage = dice_pattern.replace_all(&tiers[0].1, |captures: &regex::Captures|
+	{
+		let count: u32 = captures[1].parse().unwrap_or(0);
+		if count < 2 { valid = false; }
+		format!("{}d{}", count.saturating_sub(1), &captures[2])
+	})
+	.into_owned();
+	if !valid { return None; }
+	tiers.insert(0, (1, first_damage));
+	Some(tiers)
+}
+
+/// Font tags, link tags, and color tags that have an effect when they are a whole token anywhere in spell text.
+const TEXT_TAGS: [&str; 8] = ["<r>", "<b>", "<i>", "<bi>", "<ib>", "<url>", "</url>", "</color>"];
+
+/// Returns whether or not a token is a font tag, link tag, or color tag
+/// (ex: "<b>", "<url=https://example.com>", "<color=#8b0000>").
+fn is_text_tag(token: &str) -> bool
+{
+	if TEXT_TAGS.contains(&token) || crate::layout::color_from_tag(token).is_some() { return true; }
+	// Link tags with the url inside of them
+	match token.strip_prefix("<url=").and_then(|rest| rest.strip_suffix('>'))
+	{
+		Some(url) => !url.is_empty(),
+		None => false
+	}
+}
+
+/// Tag that goes on its own line in a spell description to make the rest of the description start on a new page.
+pub(crate) const PAGE_BREAK_TAG: &str = "[pagebreak]";
+/// Tag that goes on its own line in a spell description to start a region of paragraphs that get kept on the same
+/// page.
+pub(crate) const NO_BREAK_START_TAG: &str = "[nobreak]";
+/// Tag that goes on its own line in a spell description to end a region started by `NO_BREAK_START_TAG`.
+pub(crate) const NO_BREAK_END_TAG: &str = "[/nobreak]";
+
+/// Tag that goes before the text of a footnote in spell text (ex: "fire damage<fn>Or cold damage.</fn>").
+pub(crate) const FOOTNOTE_START_TAG: &str = "<fn>";
+/// Tag that goes after the text of a footnote started by `FOOTNOTE_START_TAG`.
+pub(crate) const FOOTNOTE_END_TAG: &str = "</fn>";
+
+/// Takes the footnotes out of some spell text and replaces each one with its id in superscript after the word before
+/// it. Ids count up from `first_id`. Footnotes that don't end in the same paragraph they start in are left in the
+/// text as they are.
+pub(crate) fn extract_footnotes(text: &str, first_id: usize) -> (String, Vec<String>)
+{
+	let mut output = String::with_capacity(text.len());
+	let mut footnotes = Vec::new();
+	let mut rest = text;
+	while let Some(start) = rest.find(FOOTNOTE_START_TAG)
+	{
+		let footnote_start = &rest[start + FOOTNOTE_START_TAG.len()..];
+		let end = match footnote_start.find(FOOTNOTE_END_TAG)
+		{
+			Some(end) if !footnote_start[..end].contains('\n') => end,
+			_ => break
+		};
+		// The id goes right after the word before the footnote
+		output.push_str(rest[..start].trim_end_matches([' ', '\t']));
+		output.push(SUPERSCRIPT_START);
+		output.push_str(&(first_id + footnotes.len()).to_string());
+		output.push(SUPERSCRIPT_END);
+		footnotes.push(footnote_start[..end].split_whitespace().collect::<Vec<_>>().join(" "));
+		rest = &footnote_start[end + FOOTNOTE_END_TAG.len()..];
+	}
+	output.push_str(rest);
+	(output, footnotes)
+}
+
+/// Tag that goes before text in spell text that only the DM should see (ex: "<secret>The orb is a phylactery.</secret>").
+pub(crate) const SECRET_START_TAG: &str = "<secret>";
+/// Tag that goes after the text of a secret started by `SECRET_START_TAG`.
+pub(crate) const SECRET_END_TAG: &str = "</secret>";
+
+/// Handles the secrets in some spell text for one edition of a spellbook. With no redaction (the DM edition), secrets
+/// keep their text and lose their tags. Otherwise (the player edition), each word of a secret gets marked to be hidden
+/// behind a shaded bar or the secret gets left out, along with any paragraph that has nothing left in it. Secrets that
+/// don't end in the same paragraph they start in are left in the text as they are.
+pub(crate) fn redact_secrets(text: &str, redaction: Option<RedactionStyle>) -> String
+{
+	let mut paragraphs = Vec::new();
+	for paragraph in text.split('\n')
+	{
+		let mut output = String::with_capacity(paragraph.len());
+		let mut rest = paragraph;
+		while let Some(start) = rest.find(SECRET_START_TAG)
+		{
+			let secret_start = &rest[start + SECRET_START_TAG.len()..];
+			let end = match secret_start.find(SECRET_END_TAG)
+			{
+				Some(end) => end,
+				None => break
+			};
+			let secret = &secret_start[..end];
+			match redaction
+			{
+				None =>
+				{
+					output.push_str(&rest[..start]);
+					output.push_str(secret);
+				},
+				// Tags in the secret still have to work so the text after it looks the same as in the DM edition
+				Some(RedactionStyle::ShadedBar) =>
+				{
+					output.push_str(&rest[..start]);
+					output.push_str(&map_tokens(secret, |token, _|
+					{
+						if is_text_tag(token) { String::from(token) }
+						else { format!("{}{}{}", REDACTED_START, token, REDACTED_END) }
+					}));
+				},
+				Some(RedactionStyle::Omit) => output.push_str(rest[..start].trim_end_matches([' ', '\t']))
+			}
+			rest = &secret_start[end + SECRET_END_TAG.len()..];
+		}
+		// Paragraphs that were nothing but secrets don't leave a blank paragraph behind
+		if rest.len() < paragraph.len() && output.trim().is_empty() && rest.trim().is_empty()
+			&& redaction == Some(RedactionStyle::Omit)
+		{
+			continue;
+		}
+		output.push_str(rest);
+		paragraphs.push(output);
+	}
+	paragraphs.join("\n")
+}
+
+/// Returns whether or not a token is a page break tag ("[pagebreak]", "[nobreak]", or "[/nobreak]").
+pub(crate) fn is_page_break_tag(token: &str) -> bool
+{
+	[PAGE_BREAK_TAG, NO_BREAK_START_TAG, NO_BREAK_END_TAG].contains(&token)
+}
+
+/// Returns whether or not a token is one or more backslashes followed by a page break tag (ex: "\\[pagebreak]").
+pub(crate) fn is_escaped_page_break_tag(token: &str) -> bool
+{
+	token.starts_with('\\') && is_page_break_tag(token.trim_start_matches('\\'))
+}
+
+/// Returns whether or not a token is a table tag (ex: "[table][0]").
+fn is_table_tag(token: &str) -> bool
+{
+	match token.strip_prefix("[table][").and_then(|rest| rest.strip_suffix(']'))
+	{
+		Some(index) => !index.is_empty() && index.chars().all(|c| c.is_ascii_digit()),
+		None => false
+	}
+}
+
+/// Returns the index of the table in a table tag (ex: 5 from "[table][5]"). Returns `None` if the token isn't a table
+/// tag or the index is too large to be a table index.
+pub(crate) fn table_tag_index(token: &str) -> Option<usize>
+{
+	if !is_table_tag(token) { return None; }
+	token["[table][".len()..token.len() - 1].parse().ok()
+}
+
+/// Returns whether or not a token is one or more backslashes followed by a table tag (ex: "\\[table][0]").
+pub(crate) fn is_escaped_table_tag(token: &str) -> bool
+{
+	token.starts_with('\\') && is_table_tag(token.trim_start_matches('\\'))
+}
+
+/// Returns whether or not a token is a tag that only works at the start of a paragraph (table tags and page break
+/// tags).
+fn is_paragraph_tag(token: &str) -> bool
+{
+	is_table_tag(token) || is_page_break_tag(token)
+}
+
+/// Removes the first backslash from a token if it is an escaped tag (one or more backslashes followed by a font tag,
+/// link tag, or color tag, or a table tag or page break tag if `paragraph_start` is true since those only work at the
+/// start of a paragraph).
+/// Returns the token unchanged if it isn't an escaped tag.
+pub(crate) fn unescape_token(token: &str, paragraph_start: bool) -> &str
+{
+	let tag = token.trim_start_matches('\\');
+	if tag.len() < token.len() && (is_text_tag(tag) || (paragraph_start && is_paragraph_tag(tag))) { &token[1..] }
+	else { token }
+}
+
+/// Applies a function to every token (text separated by whitespace) in some text and returns the text with each
+/// token replaced by what the function returned. Whitespace is kept the way it was.
+/// The function also takes whether or not the token is at the very start of a paragraph.
+fn map_tokens(text: &str, map: impl Fn(&str, bool) -> String) -> String
+{
+	let mut mapped_text = String::with_capacity(text.len());
+	for (paragraph_index, paragraph) in text.split('\n').enumerate()
+	{
+		if paragraph_index > 0 { mapped_text.push('\n'); }
+		let mut rest = paragraph;
+		while !rest.is_empty()
+		{
+			// Copy the whitespace before the next token
+			let token_start = rest.find(|c: char| !c.is_whitespace()).unwrap_or(rest.len());
+			mapped_text.push_str(&rest[..token_start]);
+			rest = &rest[token_start..];
+			if rest.is_empty() { break; }
+			// Map the token
+			let paragraph_start = rest.len() == paragraph.len();
+			let token_end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+			mapped_text.push_str(&map(&rest[..token_end], paragraph_start));
+			rest = &rest[token_end..];
+		}
+	}
+	mapped_text
+}
+
+/// Returns the class of a paragraph style marker (ex: "readaloud" from "::readaloud::"). Markers go at the start of
+/// paragraphs in spell descriptions to write them in a paragraph style.
+pub(crate) fn paragraph_class(token: &str) -> Option<&str>
+{
+	let class = token.strip_prefix("::")?.strip_suffix("::")?;
+	if class.is_empty() || class.contains(':') { None }
+	else { Some(class) }
+}
+
+/// Takes the font tags, link tags, color tags, table tags, page break tags, paragraph style markers, footnote tags,
+/// and secret tags out of some spell text and removes the first backslash from escaped tags, leaving the text that
+/// gets read. Also returns the position in the original text of each byte of the text that's left, plus the length
+/// of the original text at the end.
+pub(crate) fn plain_text_with_offsets(text: &str) -> (String, Vec<usize>)
+{
+	// Tags that don't need whitespace around them
+	let inline_tags = [FOOTNOTE_START_TAG, FOOTNOTE_END_TAG, SECRET_START_TAG, SECRET_END_TAG];
+	let mut plain_text = String::with_capacity(text.len());
+	let mut offsets = Vec::with_capacity(text.len() + 1);
+	let mut keep = |start: usize, end: usize|
+	{
+		plain_text.push_str(&text[start..end]);
+		offsets.extend(start..end);
+	};
+	let mut paragraph_start_index = 0;
+	let mut index = 0;
+	while let Some(c) = text[index..].chars().next()
+	{
+		// Whitespace is kept the way it was
+		if c.is_whitespace()
+		{
+			keep(index, index + c.len_utf8());
+			index += c.len_utf8();
+			if c == '\n' { paragraph_start_index = index; }
+			continue;
+		}
+		let token_end = text[index..].find(char::is_whitespace).map_or(text.len(), |end| index + end);
+		let token = &text[index..token_end];
+		let paragraph_start = index == paragraph_start_index;
+		if is_text_tag(token) || (paragraph_start && (is_paragraph_tag(token) || paragraph_class(token).is_some())) {}
+		else if unescape_token(token, paragraph_start).len() < token.len() { keep(index + 1, token_end); }
+		else
+		{
+			let mut start = index;
+			let mut position = index;
+			while position < token_end
+			{
+				match inline_tags.iter().find(|tag| text[position..token_end].starts_with(*tag))
+				{
+					Some(tag) =>
+					{
+						keep(start, position);
+						position += tag.len();
+						start = position;
+					},
+					None => position += text[position..].chars().next().map_or(1, char::len_utf8)
+				}
+			}
+			keep(start, token_end);
+		}
+		index = token_end;
+	}
+	offsets.push(text.len());
+	(plain_text, offsets)
+}
+
+/// Escapes any font tags, link tags, color tags, table tags, and page break tags in some text so that it appears in a
+/// spellbook exactly as written when it is put into a spell description (or any other text that can have tags in it).
+///
+/// Tags get escaped by putting a backslash before them (ex: "<b>" becomes "\\<b>" and "\\<b>" becomes "\\\\<b>").
+/// Other backslashes and text are left alone since they already appear exactly as written.
+///
+/// Ex: `format!("{} says: {}", name, escape_rich_text(user_text))`
+pub fn escape_rich_text(text: &str) -> String
+{
+	map_tokens(text, |token, paragraph_start|
+	{
+		let tag = token.trim_start_matches('\\');
+		if is_text_tag(tag) || (paragraph_start && is_paragraph_tag(tag)) { format!("\\{}", token) }
+		else { String::from(token) }
+	})
+}
+
+/// Undoes `escape_rich_text()` by removing the first backslash before any escaped font tags, link tags, color tags,
+/// table tags, and page break tags. This is what happens to escaped tags when spell text is put into a spellbook.
+pub fn unescape_rich_text(text: &str) -> String
+{
+	map_tokens(text, |token, paragraph_start| String::from(unescape_token(token, paragraph_start)))
+}
+
+/// Turns a spell name into lowercase words with a separator between them and no punctuation.
+pub(crate) fn name_slug(name: &str, separator: char) -> String
+{
+	let mut slug = String::with_capacity(name.len());
+	for c in name.to_lowercase().chars()
+	{
+		if c.is_alphanumeric() { slug.push(c); }
+		// Apostrophes get dropped instead of splitting words
+		else if c != '\'' && c != '’' && !slug.is_empty() && !slug.ends_with(separator) { slug.push(separator); }
+	}
+	slug.trim_end_matches(separator).to_string()
+}
+
+/// What kind of spells `random_selection()` picks from and how many of them it picks.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct RandomSpec
+{
+	/// The levels that the spells can be (`None` for any level). Spells with custom levels are only picked if this is
+	/// `None`.
+	pub levels: Option<Vec<Level>>,
+	/// The schools of magic that the spells can be in (`None` for any school). Spells with custom schools are only
+	/// picked if this is `None`.
+	pub schools: Option<Vec<MagicSchool>>,
+	/// The rarities that the spells can have (`None` for any rarity). Spells without a rarity are only picked if this
+	/// is `None`.
+	pub rarities: Option<Vec<Rarity>>,
+	/// The number of spells to pick (every spell that matches gets picked if there aren't this many).
+	pub count: usize,
+	/// The seed for the random picks. The same seed with the same spells always picks the same spells.
+	pub seed: u64
+}
+
+impl RandomSpec
+{
+	/// Whether or not a spell with a certain header can be picked. Can be passed to `SpellDatabase::filter()` to get
+	/// every spell that could be picked.
+	pub fn matches(&self, header: &SpellHeader) -> bool
+	{
+		let level_matches = match (&self.levels, &header.level)
+		{
+			(None, _) => true,
+			(Some(levels), SpellField::Controlled(level)) => levels.contains(level),
+			(Some(_), SpellField::Custom(_)) => false
+		};
+		let school_matches = match (&self.schools, &header.school)
+		{
+			(None, _) => true,
+			(Some(schools), SpellField::Controlled(school)) => schools.contains(school),
+			(Some(_), SpellField::Custom(_)) => false
+		};
+		let rarity_matches = match (&self.rarities, &header.rarity)
+		{
+			(None, _) => true,
+			(Some(rarities), Some(rarity)) => rarities.contains(rarity),
+			(Some(_), None) => false
+		};
+		level_matches && school_matches && rarity_matches
+	}
+}
+
+/// A small SplitMix64 random number generator. Used instead of an outside crate so the same seed picks the same
+/// spells in every version of this crate.
+struct SplitMix64(u64);
+
+impl SplitMix64
+{
+	/// Gets the next random number.
+	fn next(&mut self) -> u64
+	{
+		self.0 = self.0.wrapping_add(0x9e3779b97f4a7c15);
+		let mut z = self.0;
+		z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+		z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+		z ^ (z >> 31)
+	}
+
+	/// Gets a random number from 0 up to (but not including) `bound`.
+	fn next_below(&mut self, bound: usize) -> usize { ((self.next() as u128 * bound as u128) >> 64) as usize }
+}
+
+/// Picks random spells from a spell database (ex: for the spellbook of an NPC wizard that the players find). The
+/// same spec always picks the same spells from the same database, so a random spellbook can be made again later.
+///
+/// # Parameters
+///
+/// - `database` The spell database to pick spells from. Only the spells that get picked are read from their files.
+/// - `spec` What kind of spells to pick, how many to pick, and the seed for the random picks.
+///
+/// # Output
+///
+/// - `Ok` The spells that were picked in the order they are in the database (ready to be passed to
+///   `create_spellbook()`).
+/// - `Err` Any errors that occurred while reading the spell files.
+pub fn random_selection(database: &SpellDatabase, spec: &RandomSpec)
+-> Result<Vec<Spell>, Box<dyn error::Error + Send + Sync>>
+{
+	let mut candidates: Vec<(usize, &SpellHeader)> =
+		database.headers().enumerate().filter(|(_, header)| spec.matches(header)).collect();
+	// Shuffle just enough of the candidates to the front to pick from them
+	let count = spec.count.min(candidates.len());
+	let mut rng = SplitMix64(spec.seed);
+	for index in 0..count
+	{
+		let picked_index = index + rng.next_below(candidates.len() - index);
+		candidates.swap(index, picked_index);
+	}
+	// Put the picked spells back in the order they are in the database
+	let mut picked = candidates[..count].to_vec();
+	picked.sort_by_key(|(index, _)| *index);
+	let names: Vec<&str> = picked.iter().map(|(_, header)| header.name.as_str()).collect();
+	database.get_all(&names)
+}
+
+/// A field that is different between two versions of a spell.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FieldChange
+{
+	/// The name of the field as it is in spell files (ex: "description").
+	pub field: String,
+	/// The json of the field in the old version of the spell ("null" if it wasn't there).
+	pub old: String,
+	/// The json of the field in the new version of the spell ("null" if it isn't there anymore).
+	pub new: String
+}
+
+/// A spell that is different between two folders of spells.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SpellChange
+{
+	/// The name of the spell.
+	pub name: String,
+	/// Each field that is different (in alphabetical order).
+	pub fields: Vec<FieldChange>
+}
+
+/// The differences between two folders of spells from `diff_folders()`. Spells are matched up by name and each list
+/// is sorted by name.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct FolderDiff
+{
+	/// The spells that are only in the new folder.
+	pub added: Vec<Spell>,
+	/// The spells that are only in the old folder.
+	pub removed: Vec<Spell>,
+	/// The spells that are in both folders but aren't the same.
+	pub changed: Vec<SpellChange>
+}
+
+impl FolderDiff
+{
+	/// Whether the folders have all of the same spells (so spellbooks made from them would be the same).
+	pub fn is_empty(&self) -> bool { self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty() }
+}
+
+impl fmt::Display for FolderDiff
+{
+	/// Lists added spells with a "+", removed spells with a "-", and changed spells with a "~" and the fields that
+	/// changed in them.
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
+	{
+		for spell in &self.added { writeln!(f, "+ {}", spell.name)?; }
+		for spell in &self.removed { writeln!(f, "- {}", spell.name)?; }
+		for change in &self.changed
+		{
+			let fields: Vec<&str> = change.fields.iter().map(|field| field.field.as_str()).collect();
+			writeln!(f, "~ {} ({})", change.name, fields.join(", "))?;
+		}
+		Ok(())
+	}
+}
+
+/// Compares the spells in two folders of spell files (ex: an old and a new copy of a homebrew collection) to find the
+/// spells that were added, removed, or changed, along with which fields changed in each changed spell. Spells are
+/// matched up by name, so a spell that was renamed shows up as removed and added.
+///
+/// # Parameters
+///
+/// - `old_folder_path` The file path to the folder with the old spell files.
+/// - `new_folder_path` The file path to the folder with the new spell files.
+///
+/// # Output
+///
+/// - `Ok` The differences between the folders.
+/// - `Err` Any errors that occurred while reading the spell files.
+pub fn diff_folders(old_folder_path: &str, new_folder_path: &str)
+-> Result<FolderDiff, Box<dyn error::Error + Send + Sync>>
+{
+	let spells_by_name = |folder_path: &str| -> Result<BTreeMap<String, Spell>, Box<dyn error::Error + Send + Sync>>
+	{
+		Ok(get_all_spells_in_folder(folder_path)?.into_iter().map(|spell| (spell.name.clone(), spell)).collect())
+	};
+	let old_spells = spells_by_name(old_folder_path)?;
+	let mut new_spells = spells_by_name(new_folder_path)?;
+	let mut diff = FolderDiff::default();
+	for (name, old_spell) in old_spells
+	{
+		match new_spells.remove(&name)
+		{
+			Some(new_spell) if new_spell != old_spell =>
+			{
+				diff.changed.push(SpellChange { name: name, fields: field_changes(&old_spell, &new_spell)? });
+			},
+			Some(_) => (),
+			None => diff.removed.push(old_spell)
+		}
+	}
+	// Every spell left over is only in the new folder
+	diff.added = new_spells.into_values().collect();
+	Ok(diff)
+}
+
+/// Finds the fields that are different between two versions of a spell by comparing their json.
+fn field_changes(old_spell: &Spell, new_spell: &Spell) -> Result<Vec<FieldChange>, Box<dyn error::Error + Send + Sync>>
+{
+	let old_json = serde_json::to_value(old_spell)?;
+	let new_json = serde_json::to_value(new_spell)?;
+	let empty = serde_json::Map::new();
+	let old_fields = old_json.as_object().unwrap_or(&empty);
+	let new_fields = new_json.as_object().unwrap_or(&empty);
+	// Fields that are left out of the json (like a spell without a source) count as null
+	let mut field_names: Vec<&String> = old_fields.keys().collect();
+	field_names.extend(new_fields.keys().filter(|field| !old_fields.contains_key(*field)));
+	Ok(field_names.into_iter().filter_map(|field|
+	{
+		let old = old_fields.get(field).unwrap_or(&Value::Null);
+		let new = new_fields.get(field).unwrap_or(&Value::Null);
+		if old == new { None }
+		else { Some(FieldChange { field: field.clone(), old: old.to_string(), new: new.to_string() }) }
+	})
+	.collect())
+}
+
+/// A field of a spell that can be compared between spells in `comparison_table()`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ComparisonField
+{
+	Level,
+	School,
+	CastingTime,
+	Range,
+	Components,
+	Duration,
+	Concentration,
+	Ritual,
+	Classes,
+	Source,
+	Rarity
+}
+
+impl ComparisonField
+{
+	/// The text of this field for a spell the way it goes in a comparison table.
+	fn value(&self, spell: &Spell) -> String
+	{
+		let yes_no = |value: bool| String::from(if value { "Yes" } else { "No" });
+		// Fields that a spell might not have get a dash
+		let or_dash = |value: Option<String>|
+		value.filter(|value| !value.is_empty()).unwrap_or_else(|| String::from("\u{2014}"));
+		match self
+		{
+			Self::Level => spell.level.to_string(),
+			Self::School => spell.school.to_string(),
+			Self::CastingTime => spell.casting_time.to_string(),
+			Self::Range => spell.get_range_text(None),
+			Self::Components => spell.get_component_string(),
+			Self::Duration => spell.duration.to_string(),
+			Self::Concentration => yes_no(spell.requires_concentration()),
+			Self::Ritual => yes_no(spell.is_ritual),
+			Self::Classes => or_dash(Some(spell.classes.join(", "))),
+			Self::Source => or_dash(spell.source.clone()),
+			Self::Rarity => or_dash(spell.rarity.map(|rarity| rarity.to_string()))
+		}
+	}
+}
+
+impl fmt::Display for ComparisonField
+{
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
+	{
+		let text = match self
+		{
+			Self::Level => "Level",
+			Self::School => "School",
+			Self::CastingTime => "Casting Time",
+			Self::Range => "Range",
+			Self::Components => "Components",
+			Self::Duration => "Duration",
+			Self::Concentration => "Concentration",
+			Self::Ritual => "Ritual",
+			Self::Classes => "Classes",
+			Self::Source => "Source",
+			Self::Rarity => "Rarity"
+		};
+		write!(f, "{}", text)
+	}
+}
+
+/// Makes a table that compares some fields of spells side by side (ex: the casting time, range, and duration of all
+/// the "Summon" spells). Each spell gets a column with its name at the top and each field gets a row with its name on
+/// the left. Text from the spells gets escaped so it shows up in the table exactly as it is.
+///
+/// The table doesn't have a title. It can go anywhere tables can go, like in the `tables` of a spell with a table tag
+/// for it in the spell's description.
+///
+/// # Parameters
+///
+/// - `spells` The spells to compare (in the order their columns go in).
+/// - `fields` The fields to compare (in the order their rows go in).
+///
+/// # Output
+///
+/// A table with a column for each spell and a row for each field.
+pub fn comparison_table(spells: &[&Spell], fields: &[ComparisonField]) -> Table
+{
+	let mut column_labels = vec![String::new()];
+	column_labels.extend(spells.iter().map(|spell| escape_rich_text(&spell.name)));
+	let cells = fields.iter().map(|field|
+	{
+		let mut row = vec![field.to_string()];
+		row.extend(spells.iter().map(|spell| escape_rich_text(&field.value(spell))));
+		row
+	})
+	.collect();
+	Table
+	{
+		title: String::new(),
+		column_labels: column_labels,
+		cells: cells
+	}
+}
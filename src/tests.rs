@@ -6,8 +6,20 @@
 
 use std::fs;
 use std::path::Path;
+use std::sync::{Arc, Mutex};
 
+use printpdf::lopdf;
+use proptest::prelude::*;
+use proptest::test_runner::{Config, TestRunner};
+
+use crate::golden;
+use crate::layout::{self, TextLine, Token};
+use crate::spellbook_writer::SpellbookWriter;
+use crate::vtt_export;
+use crate::latex_export::{self, LatexOptions};
 use crate::utils::*;
+use crate::spellbook_options::contrast_ratio;
+use crate::spellbook_gen_types::BackgroundImage;
 
 // Returns default values to pass to `create_spellbook()`
 fn default_spellbook_options() ->
@@ -30,7 +42,9 @@ fn default_spellbook_options() ->
 		regular: String::from("fonts/TeX-Gyre-Bonum/TeX-Gyre-Bonum-Regular.otf"),
 		bold: String::from("fonts/TeX-Gyre-Bonum/TeX-Gyre-Bonum-Bold.otf"),
 		italic: String::from("fonts/TeX-Gyre-Bonum/TeX-Gyre-Bonum-Italic.otf"),
-		bold_italic: String::from("fonts/TeX-Gyre-Bonum/TeX-Gyre-Bonum-BoldItalic.otf")
+		bold_italic: String::from("fonts/TeX-Gyre-Bonum/TeX-Gyre-Bonum-BoldItalic.otf"),
+		display: None,
+		variable_axes: None
 	};
 	// Parameters for determining font sizes
 	let font_sizes = FontSizes::new(32.0, 24.0, 12.0, 16.0, 12.0)
@@ -480,199 +494,5142 @@ fn necronomicon()
 //	let _ = save_spellbook(doc, "Spellbook.pdf").unwrap();
 // }
 
-// Makes sure that creating valid spell files works
-#[test]
-fn create_spell_files()
+// Creates a small spellbook with the default options (except for the background) and some additional options
+fn small_spellbook(spell_list: &Vec<spells::Spell>, additional_options: &AdditionalOptions) -> Spellbook
 {
-	// Path to hand-made spell files that are compared to generated spells
-	let comparison_folder = String::from("spells/necronomicon/");
+	// Get default spellbook options
+	let
+	(
+		font_paths,
+		font_sizes,
+		font_scalars,
+		spacing_options,
+		text_colors,
+		page_size_options,
+		page_number_options,
+		_,
+		_,
+		table_options
+	) = default_spellbook_options();
+	// Create the spellbook
+	create_spellbook_with_options
+	(
+		"Small Spellbook",
+		spell_list,
+		font_paths,
+		font_sizes,
+		font_scalars,
+		spacing_options,
+		text_colors,
+		page_size_options,
+		Some(page_number_options),
+		None,
+		table_options,
+		additional_options
+	).unwrap()
+}
 
-	// Create the spells (necronomicon spell duplicates)
-	let hell_spell = spells::Spell
+// Returns the annotations on each page of a pdf document
+fn get_annotations(bytes: &Vec<u8>) -> Vec<Vec<lopdf::Dictionary>>
+{
+	let doc = lopdf::Document::load_mem(bytes).unwrap();
+	let mut pages = Vec::new();
+	for (_, page_id) in doc.get_pages()
 	{
-		name: String::from("HELL SPELL AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA A A A A A A A A A A A AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA A A A A A A AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A"),
-		level: spells::SpellField::Custom(String::from("100TH-LEVEL")),
-		school: spells::SpellField::Custom(String::from("SUPER NECROMANCY")),
-		is_ritual: true,
-		casting_time: spells::SpellField::Controlled(spells::CastingTime::Reaction(Some(String::from("THAT YOU TAKE WHEN YOU FEEL LIKE CASTING SPELLS AND DOING MAGIC AND AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A")))),
-		range: spells::SpellField::Controlled(spells::Range::Yourself(Some(spells::Aoe::Cylinder(spells::Distance::Miles(63489), spells::Distance::Miles(49729))))),
-		has_v_component: true,
-		has_s_component: true,
-		m_components: Some(String::from("UNLIMITED POWAHHHHHHHHHHHHHHHHHHHHHHHHHHHHHHHHHHHHHHHHHHHHHHHHHHHHHHHHHHH H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H")),
-		duration: spells::SpellField::Controlled(spells::Duration::Years(57394, true)),
-		description: String::from("<ib> CASTING SPELLS AND CONJURING ABOMINATIONS <b> AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA <r> THIS SPELL ISN'T FOR <i> weak underpowered feeble wizards -_-. <r> THIS SPELL IS FOR ONLY THE MOST POWERFUL OF ARCHMAGES AND NECROMANCERS WHO CAN WIELD THE MIGHTIEST OF <bi> ARCANE ENERGY <r> WITH THE FORTITUDE OF A <ib> MOUNTAIN. <b> A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A\nA A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A \\<r> A A A A A \\<b> A A A A A A A \\<i> A A A A A A A \\<bi> A A A A \\<ib> A A A A A \\\\<r> A A A A \\\\\\<b> A A A A \\\\\\\\<i> A A A A \\\\\\\\\\<bi> A A A A \\\\\\\\\\\\<ib> A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A\n\\[table][1]\n\\\\[table[0]\n\\\\\\[table][1]\n\\\\\\\\[table][0]\nA A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A\nA A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A\n[table][0]\nMORE MAGIC SPELLS AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A\nA A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A\nA A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A\n[table][1]\nYOU CAN'T HANDLE THIS SPELL A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A\nA A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A\nA A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A"),
-		upcast_description: Some(String::from("HELL ON EARTH")),
-		tables: vec!
-		[
-			spells::Table
-			{
-				title: String::from("A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A"),
-				column_labels: vec![String::from("COLUMN OF CHAOS"), String::from("COLUMN OF NECROMANCY")],
-				cells: vec!
-				[
-					vec!
-					[
-						String::from("A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A"),
-						String::from("A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A")
-					],
-					vec!
-					[
-						String::from("B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B"),
-						String::from("B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B"),
-						String::from("B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B")
-					],
-					vec!
-					[
-						String::from("POWER"),
-						String::from("WIZARDRY")
-					],
-					vec!
-					[
-						String::new(),
-						String::from("C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C"),
-						String::from("C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C")
-					]
-				]
-			},
-			spells::Table
+		let mut annotations = Vec::new();
+		let page = doc.get_dictionary(page_id).unwrap();
+		if let Ok(lopdf::Object::Array(references)) = page.get(b"Annots")
+		{
+			for reference in references
 			{
-				title: String::from("THIS TABLE AGAIN A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A \\A \\\\A \\\\\\A \\<title> \\\\<title> \\\\\\<title> A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A"),
-				column_labels: vec![String::from("COLUMN OF CHAOS"), String::from("COLUMN OF NECROMANCY")],
-				cells: vec!
-				[
-					vec!
-					[
-						String::from("A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A"),
-						String::from("A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A")
-					],
-					vec!
-					[
-						String::from("B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B"),
-						String::from("B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B"),
-						String::from("B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B")
-					],
-					vec!
-					[
-						String::from("POWER"),
-						String::from("WIZARDRY")
-					],
-					vec!
-					[
-						String::new(),
-						String::from("C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C"),
-						String::from("C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C")
-					]
-				]
+				annotations.push(doc.dereference(reference).unwrap().1.as_dict().unwrap().clone());
 			}
-		]
+		}
+		pages.push(annotations);
+	}
+	pages
+}
+
+// Returns the number of fill operations and the fill colors on each page of a pdf document
+fn page_fills(bytes: &Vec<u8>) -> Vec<(usize, Vec<Vec<f32>>)>
+{
+	let doc = lopdf::Document::load_mem(bytes).unwrap();
+	doc.get_pages().into_values()
+		.map(|page_id| lopdf::content::Content::decode(&doc.get_page_content(page_id).unwrap()).unwrap())
+		.map(|content|
+		{
+			let fill_count = content.operations.iter().filter(|operation| operation.operator == "f").count();
+			let colors = content.operations.iter()
+				.filter(|operation| operation.operator == "rg")
+				.map(|operation| operation.operands.iter().map(|operand| operand.as_float().unwrap()).collect())
+				.collect();
+			(fill_count, colors)
+		})
+		.collect()
+}
+
+// Makes sure table of contents entries link to their spells and url tags link to their urls
+#[test]
+fn table_of_contents_links()
+{
+	let mut spell_list = vec!
+	[
+		spells::Spell::from_json_file("spells/players_handbook_2024/fire_bolt.json").unwrap(),
+		spells::Spell::from_json_file("spells/players_handbook_2024/alarm.json").unwrap(),
+		spells::Spell::from_json_file("spells/players_handbook_2024/fireball.json").unwrap()
+	];
+	spell_list[0].description.push_str
+	("\nSee <url=https://example.com/fire-bolt> the source </url> or <url> https://example.com </url> for more.");
+	let additional_options = AdditionalOptions
+	{
+		table_of_contents: Some(TableOfContentsOptions::default()),
+		..Default::default()
 	};
-	let power_word_scrunch = spells::Spell
+	let bytes = small_spellbook(&spell_list, &additional_options).save_to_bytes().unwrap();
+	let pages = get_annotations(&bytes);
+	// Title page, table of contents, and a page for each spell
+	assert_eq!(pages.len(), 5);
+	// Each table of contents entry links to a page
+	assert_eq!(pages[1].len(), 3);
+	for annotation in &pages[1] { assert!(annotation.get(b"Dest").is_ok()); }
+	// Both url tags became links to urls
+	let urls: Vec<_> = pages[2].iter()
+		.filter_map(|annotation| annotation.get(b"A").ok())
+		.map(|action| action.as_dict().unwrap().get(b"URI").unwrap().as_str().unwrap().to_vec())
+		.collect();
+	assert!(urls.contains(&b"https://example.com/fire-bolt".to_vec()));
+	assert!(urls.contains(&b"https://example.com".to_vec()));
+}
+
+// Makes sure long tables of contents get split into columns and entries can be sorted differently from the spells
+#[test]
+fn table_of_contents_layout()
+{
+	// Gets the target page and position of each table of contents link on each page of a spellbook
+	let links = |spell_list: &Vec<spells::Spell>, options: TableOfContentsOptions|
 	{
-		name: String::from("Power Word Scrunch"),
-		level: spells::SpellField::Controlled(spells::Level::Level9),
-		school: spells::SpellField::Controlled(spells::MagicSchool::Transmutation),
-		is_ritual: false,
-		casting_time: spells::SpellField::Controlled(spells::CastingTime::Actions(1)),
-		range: spells::SpellField::Controlled(spells::Range::Dist(spells::Distance::Feet(60))),
-		has_v_component: true,
-		has_s_component: false,
-		m_components: None,
-		duration: spells::SpellField::Controlled(spells::Duration::Instant),
-		description: String::from("Choose 1 target creature or object within range. That target gets scrunched.
-- Scrunching has these effects
-[table][0]
-- Scrunch balls (balls produced from scrunching) can be thrown and do 1d6 bludgeoning damage on hit.
-Scrunch ball funny lol."),
-		upcast_description: None,
-		tables: vec!
-		[
-			spells::Table
-			{
-				title: String::from("Scrunching Effects"),
-				column_labels: vec![String::from("Target"), String::from("Effect")],
-				cells: vec!
-				[
-					vec!
-					[
-						String::from("Creature"),
-						String::from("Flesh Ball")
-					],
-					vec!
-					[
-						String::from("Object"),
-						String::from("Ball of that object's material")
-					],
-					vec!
-					[
-						String::from("Creature not made of flesh"),
-						String::from("Ball of that creature's material")
-					]
-				]
-			}
-		]
+		let additional_options = AdditionalOptions { table_of_contents: Some(options), ..Default::default() };
+		let bytes = small_spellbook(spell_list, &additional_options).save_to_bytes().unwrap();
+		let doc = lopdf::Document::load_mem(&bytes).unwrap();
+		let page_ids: Vec<_> = doc.get_pages().into_values().collect();
+		get_annotations(&bytes).iter()
+			.map(|annotations| annotations.iter()
+				.filter_map(|annotation|
+				{
+					let target = annotation.get(b"Dest").ok()?.as_array().unwrap()[0].as_reference().unwrap();
+					let rect = annotation.get(b"Rect").unwrap().as_array().unwrap();
+					let target_index = page_ids.iter().position(|page_id| *page_id == target).unwrap();
+					Some((target_index, rect[0].as_float().unwrap(), rect[1].as_float().unwrap()))
+				})
+				.collect::<Vec<_>>())
+			.collect::<Vec<_>>()
 	};
-	let the_ten_hells = spells::Spell
+	// Enough spells that their entries don't fit on one page
+	let fire_bolt = spells::Spell::from_json_file("spells/players_handbook_2024/fire_bolt.json").unwrap();
+	let spell_list: Vec<_> = (0..60)
+		.map(|index| spells::Spell { name: format!("Spell {}", index), ..fire_bolt.clone() })
+		.collect();
+	let one_column = links(&spell_list, TableOfContentsOptions::default());
+	let columns = links(&spell_list, TableOfContentsOptions { max_columns: 3, ..Default::default() });
+	// Two columns are enough to fit every entry on one page
+	assert_eq!(columns.len(), one_column.len() - 1);
+	assert_eq!(columns[1].len(), 60);
+	let mut column_xs: Vec<_> = columns[1].iter().map(|(_, x, _)| x.round() as i64).collect();
+	column_xs.dedup();
+	assert_eq!(column_xs.len(), 2);
+	// Every entry still links to its spell
+	let targets: Vec<_> = columns[1].iter().map(|(target, _, _)| *target).collect();
+	assert_eq!(targets, (2..62).collect::<Vec<_>>());
+	// Fireball, Fire Bolt, and Alarm are on pages 2, 3, and 4
+	let spell_list = vec!
+	[
+		spells::Spell::from_json_file("spells/players_handbook_2024/fireball.json").unwrap(),
+		fire_bolt,
+		spells::Spell::from_json_file("spells/players_handbook_2024/alarm.json").unwrap()
+	];
+	let targets = |order: TableOfContentsOrder|
 	{
-		name: String::from("The Ten Hells"),
-		level: spells::SpellField::Controlled(spells::Level::Level9),
-		school: spells::SpellField::Controlled(spells::MagicSchool::Necromancy),
-		is_ritual: true,
-		casting_time: spells::SpellField::Controlled(spells::CastingTime::Actions(1)),
-		range: spells::SpellField::Controlled(spells::Range::Yourself(Some(spells::Aoe::Sphere(spells::Distance::Feet(90))))),
-		has_v_component: true,
-		has_s_component: false,
-		m_components: Some(String::from("the nail or claw of a creature from an evil plane")),
-		duration: spells::SpellField::Controlled(spells::Duration::Instant),
-		description: String::from("Choose any number of creatures made of tangible matter within range. Those creatures must all make a constitution saving throw against your spell save DC. All creatures that fail this saving throw get turned inside out, immediately die, and have their souls eternally damned to all nine hells simultaneously.
-Creatures that succeed the saving throw take 20d4 scrunching damage."),
-		upcast_description: None,
-		tables: Vec::new()
+		let options = TableOfContentsOptions { order: order, ..Default::default() };
+		let mut entries = links(&spell_list, options).swap_remove(1);
+		entries.sort_by(|a, b| b.2.total_cmp(&a.2));
+		entries.into_iter().map(|(target, _, _)| target).collect::<Vec<_>>()
 	};
+	assert_eq!(targets(TableOfContentsOrder::Spellbook), vec![2, 3, 4]);
+	assert_eq!(targets(TableOfContentsOrder::Alphabetical), vec![4, 3, 2]);
+	// The level headings don't link anywhere
+	assert_eq!(targets(TableOfContentsOrder::Level), vec![3, 4, 2]);
+}
 
-	// Create vec of test spells and their file names (without extension or path)
-	let spell_list = vec![(hell_spell, "hell_spell"), (power_word_scrunch, "power_word_scrunch"), (the_ten_hells, "the_ten_hells")];
-	// Test to make sure spell files can be created properly
-	json_file_test(&spell_list, false, "spells/tests/", &comparison_folder);
+// Makes sure the glossary gets added to the end and glossary terms in descriptions link to it
+#[test]
+fn glossary_keyword_links()
+{
+	let mut spell_list = vec!
+	[
+		spells::Spell::from_json_file("spells/players_handbook_2024/fire_bolt.json").unwrap(),
+		spells::Spell::from_json_file("spells/players_handbook_2024/fireball.json").unwrap()
+	];
+	spell_list[0].description.push_str("\nA creature hit by this spell is knocked Prone and is frightened until the end of its next turn.");
+	let additional_options = AdditionalOptions
+	{
+		glossary: Some(GlossaryOptions::srd_conditions()),
+		..Default::default()
+	};
+	let bytes = small_spellbook(&spell_list, &additional_options).save_to_bytes().unwrap();
+	let doc = lopdf::Document::load_mem(&bytes).unwrap();
+	let page_ids: Vec<_> = doc.get_pages().into_values().collect();
+	let pages = get_annotations(&bytes);
+	// Title page, a page for each spell, and at least one glossary page
+	assert!(pages.len() >= 4);
+	// Both glossary terms link to a glossary page
+	let targets: Vec<_> = pages[1].iter()
+		.filter_map(|annotation| annotation.get(b"Dest").ok())
+		.map(|dest| dest.as_array().unwrap()[0].as_reference().unwrap())
+		.collect();
+	assert_eq!(targets.len(), 2);
+	for target in targets { assert!(page_ids[3..].contains(&target)); }
+	// The other spell doesn't mention any conditions
+	assert!(pages[2].is_empty());
 }
 
-// Creates json files from a list of spells into the output folder and compares them to the same hand-crafted spells in the comparison folder
-fn json_file_test(spell_list: &Vec<(spells::Spell, &str)>, compress: bool, output_folder: &str, comparison_folder: &str)
+// Makes sure glossary terms in descriptions get popups with their definitions that can be used without the links
+#[test]
+fn glossary_tooltips()
 {
-	const FILE_EXTENSION: &str = ".json";
+	let mut spell_list = vec![spells::Spell::from_json_file("spells/players_handbook_2024/fire_bolt.json").unwrap()];
+	spell_list[0].description.push_str("\nA creature hit by this spell is knocked Prone.");
+	let annotations = |link_keywords: bool, tooltips: bool|
+	{
+		let glossary =
+			GlossaryOptions { link_keywords: link_keywords, tooltips: tooltips, ..GlossaryOptions::srd_conditions() };
+		let additional_options = AdditionalOptions { glossary: Some(glossary), ..Default::default() };
+		let bytes = small_spellbook(&spell_list, &additional_options).save_to_bytes().unwrap();
+		get_annotations(&bytes).swap_remove(1)
+	};
+	// Gets the title and text of each tooltip
+	let tooltips = |annotations: &Vec<lopdf::Dictionary>| annotations.iter()
+		.filter(|annotation| annotation.get(b"Subtype").unwrap().as_name().unwrap() == b"Square")
+		.map(|annotation|
+		{
+			let text = |key: &[u8]|
+			{
+				let units: Vec<u16> = annotation.get(key).unwrap().as_str().unwrap()
+					.chunks(2)
+					.map(|bytes| u16::from_be_bytes([bytes[0], bytes[1]]))
+					.collect();
+				String::from_utf16(&units[1..]).unwrap()
+			};
+			(text(b"T"), text(b"Contents"))
+		})
+		.collect::<Vec<_>>();
+	let link_count = |annotations: &Vec<lopdf::Dictionary>| annotations.iter()
+		.filter(|annotation| annotation.get(b"Dest").is_ok())
+		.count();
+	let links_only = annotations(true, false);
+	assert_eq!(link_count(&links_only), 1);
+	assert!(tooltips(&links_only).is_empty());
+	let both = annotations(true, true);
+	assert_eq!(link_count(&both), 1);
+	let prone = GlossaryOptions::srd_conditions().entries["Prone"].clone();
+	assert_eq!(tooltips(&both), vec![(String::from("Prone"), prone)]);
+	let tooltips_only = annotations(false, true);
+	assert_eq!(link_count(&tooltips_only), 0);
+	assert_eq!(tooltips(&tooltips_only).len(), 1);
+}
 
-	// If the output folder doesn't exist yet
-	if !Path::new(&output_folder).exists()
+// Makes sure the columns on the last page of the glossary and compact lists end at about the same height when they're
+// balanced
+#[test]
+fn column_balancing()
+{
+	let spell_list = vec!
+	[
+		spells::Spell::from_json_file("spells/players_handbook_2024/fire_bolt.json").unwrap(),
+		spells::Spell::from_json_file("spells/players_handbook_2024/fireball.json").unwrap(),
+		spells::Spell::from_json_file("spells/players_handbook_2024/alarm.json").unwrap(),
+		spells::Spell::from_json_file("spells/players_handbook_2024/burning_hands.json").unwrap()
+	];
+	let entries = (0..12).map(|index| (format!("Term {}", index), String::from("Short."))).collect();
+	let glossary = GlossaryOptions
 	{
-		// Create it
-		fs::create_dir(&output_folder).unwrap();
+		title: String::from("Glossary"),
+		entries: entries,
+		link_keywords: false,
+		tooltips: false
+	};
+	// The lowest text position in the left and right half of the last page (`None` for a half without text)
+	let column_bottoms = |additional_options: &AdditionalOptions|
+	{
+		let bytes = small_spellbook(&spell_list, additional_options).save_to_bytes().unwrap();
+		let doc = lopdf::Document::load_mem(&bytes).unwrap();
+		let page_id = *doc.get_pages().values().last().unwrap();
+		let content = lopdf::content::Content::decode(&doc.get_page_content(page_id).unwrap()).unwrap();
+		let half_width = Mm(215.9 / 2.0).into_pt().0;
+		let mut bottoms: (Option<f32>, Option<f32>) = (None, None);
+		for operation in content.operations.iter().filter(|operation| operation.operator == "Td")
+		{
+			let (x, y) = (operation.operands[0].as_float().unwrap(), operation.operands[1].as_float().unwrap());
+			// Leave out the page number at the bottom of the page
+			if y < Mm(15.0).into_pt().0 { continue; }
+			let bottom = if x < half_width { &mut bottoms.0 } else { &mut bottoms.1 };
+			*bottom = Some(bottom.map_or(y, |bottom: f32| bottom.min(y)));
+		}
+		bottoms
+	};
+	for (glossary, compact_list) in [(Some(glossary), None), (None, Some(CompactListOptions::default()))]
+	{
+		let additional_options = AdditionalOptions
+		{
+			glossary: glossary,
+			compact_list: compact_list,
+			..Default::default()
+		};
+		// Without balancing, everything fits in the first column
+		assert_eq!(column_bottoms(&additional_options).1, None);
+		let balanced_options = AdditionalOptions { balance_columns: true, ..additional_options };
+		let (left_bottom, right_bottom) = column_bottoms(&balanced_options);
+		let (left_bottom, right_bottom) = (left_bottom.unwrap(), right_bottom.unwrap());
+		// The columns end within a line or two of each other
+		assert!((left_bottom - right_bottom).abs() < Mm(10.0).into_pt().0);
 	}
+}
 
-	// Create vec of file paths to each spell that is going to be generated
-	let mut spell_paths = Vec::with_capacity(spell_list.len());
-	for spell in spell_list
+// Makes sure preparation checkboxes get drawn next to spell names and table of contents entries
+#[test]
+fn preparation_checkboxes()
+{
+	let spell_list = vec!
+	[
+		spells::Spell::from_json_file("spells/players_handbook_2024/fire_bolt.json").unwrap(),
+		spells::Spell::from_json_file("spells/players_handbook_2024/alarm.json").unwrap()
+	];
+	let additional_options = AdditionalOptions
 	{
-		// Get file path to the spell that's about to be generated and add it to the vec
-		let spell_path = output_folder.to_owned() + spell.1 + FILE_EXTENSION;
-		spell_paths.push(spell_path.clone());
-		// Generate the json file for this spell
-		spell.0.to_json_file(&spell_path, compress).unwrap();
-	}
+		table_of_contents: Some(TableOfContentsOptions::default()),
+		preparation_checkboxes: Some(PreparationCheckboxOptions { in_table_of_contents: true, ..Default::default() }),
+		..Default::default()
+	};
+	let bytes = small_spellbook(&spell_list, &additional_options).save_to_bytes().unwrap();
+	let doc = lopdf::Document::load_mem(&bytes).unwrap();
+	// Counts the closed outlines (checkboxes) drawn on each page
+	let checkbox_counts: Vec<_> = doc.get_pages().into_values()
+		.map(|page_id| lopdf::content::Content::decode(&doc.get_page_content(page_id).unwrap()).unwrap())
+		.map(|content| content.operations.iter().filter(|operation| operation.operator == "s").count())
+		.collect();
+	// None on the title page, one per entry in the table of contents, and one on each spell page
+	assert_eq!(checkbox_counts, vec![0, 2, 1, 1]);
+	// Table of contents entries still link to their spells
+	assert_eq!(get_annotations(&bytes)[1].len(), 2);
+}
 
-	// Create a list of just the spells and the file names from the spell_list parameter
-	let real_spell_list: Vec<spells::Spell> = spell_list.into_iter().map(|(s, _)| s.clone()).collect();
-	// Read all of the generated spell files into spell objects and put them into a list
-	let test_spell_list = get_all_spells_in_folder(&output_folder).unwrap();
-	// Compare if the spell objects are the same
-	assert_eq!(real_spell_list, test_spell_list);
+// Makes sure notes areas get ruled lines and go on their own pages when asked to
+#[test]
+fn notes_areas()
+{
+	let spell_list = vec!
+	[
+		spells::Spell::from_json_file("spells/players_handbook_2024/fire_bolt.json").unwrap(),
+		spells::Spell::from_json_file("spells/players_handbook_2024/alarm.json").unwrap()
+	];
+	// Counts the number of lines drawn on each page of a spellbook
+	let line_counts = |notes_area: NotesAreaOptions|
+	{
+		let additional_options = AdditionalOptions
+		{
+			notes_area: Some(notes_area),
+			..Default::default()
+		};
+		let bytes = small_spellbook(&spell_list, &additional_options).save_to_bytes().unwrap();
+		let doc = lopdf::Document::load_mem(&bytes).unwrap();
+		doc.get_pages().into_values()
+			.map(|page_id| lopdf::content::Content::decode(&doc.get_page_content(page_id).unwrap()).unwrap())
+			.map(|content| content.operations.iter().filter(|operation| operation.operator == "S").count())
+			.collect::<Vec<_>>()
+	};
+	// Notes areas after each spell on the same page
+	let notes_area = NotesAreaOptions { height: 40.0, line_spacing: 8.0, ..Default::default() };
+	assert_eq!(line_counts(notes_area), vec![0, 5, 5]);
+	// Notes areas on their own pages
+	let notes_area = NotesAreaOptions { placement: NotesAreaPlacement::FacingPage, ..notes_area };
+	let counts = line_counts(notes_area);
+	assert_eq!(counts.len(), 5);
+	assert_eq!(counts[1], 0);
+	assert!(counts[2] > 5);
+}
 
-	// Loop through spell file paths
-	for (spell, spell_path) in spell_list.iter().zip(spell_paths.iter())
+// Makes sure upcast descriptions can be put in their own block with a heading
+#[test]
+fn upcast_placement()
+{
+	let spell_list = vec![spells::Spell::from_json_file("spells/players_handbook_2024/fireball.json").unwrap()];
+	// Counts the number of lines of text on the spell's page
+	let line_count = |upcast: UpcastOptions|
 	{
-		// Read all of the bytes from the original spell that the generated one was based on
-		let real_spell_bytes = fs::read(&(comparison_folder.to_owned() + spell.1 + FILE_EXTENSION)).unwrap();
-		// Read all of the bytes from the generated spell file
-		let test_spell_bytes = fs::read(&spell_path).unwrap();
-		// Compare the bytes from both files to make sure they are the same
-		assert_eq!(real_spell_bytes, test_spell_bytes);
-	}
+		let additional_options = AdditionalOptions
+		{
+			upcast: upcast,
+			..Default::default()
+		};
+		let bytes = small_spellbook(&spell_list, &additional_options).save_to_bytes().unwrap();
+		let doc = lopdf::Document::load_mem(&bytes).unwrap();
+		let page_id = doc.get_pages()[&2];
+		let content = lopdf::content::Content::decode(&doc.get_page_content(page_id).unwrap()).unwrap();
+		let mut y_values: Vec<_> = content.operations.iter()
+			.filter(|operation| operation.operator == "Td")
+			.map(|operation| operation.operands[1].as_float().unwrap().to_bits())
+			.collect();
+		y_values.dedup();
+		y_values.len()
+	};
+	let inline = line_count(UpcastOptions::players_handbook_2014());
+	let separate = line_count(UpcastOptions { placement: UpcastPlacement::SeparateBlock, ..UpcastOptions::players_handbook_2014() });
+	// The heading gets its own line
+	assert!(separate > inline);
+}
+
+// Makes sure spells with the same name get different bookmarks
+#[test]
+fn bookmark_disambiguation()
+{
+	let fireball = spells::Spell::from_json_file("spells/players_handbook_2024/fireball.json").unwrap();
+	let alarm = spells::Spell::from_json_file("spells/players_handbook_2024/alarm.json").unwrap();
+	let spell_list = vec!
+	[
+		spells::Spell { source: Some(String::from("PHB")), ..fireball.clone() },
+		spells::Spell { source: Some(String::from("XGE")), ..fireball },
+		alarm.clone(),
+		alarm
+	];
+	// Gets the title of every bookmark in a spellbook
+	let bookmark_names = |policy: BookmarkNamePolicy|
+	{
+		let additional_options = AdditionalOptions
+		{
+			bookmark_names: policy,
+			..Default::default()
+		};
+		let bytes = small_spellbook(&spell_list, &additional_options).save_to_bytes().unwrap();
+		let doc = lopdf::Document::load_mem(&bytes).unwrap();
+		let mut names: Vec<_> = doc.objects.values()
+			.filter_map(|object| object.as_dict().ok())
+			// Bookmarks have a parent (unlike the document info which also has a title)
+			.filter(|dictionary| dictionary.has(b"Parent"))
+			.filter_map(|dictionary| dictionary.get(b"Title").ok())
+			.map(|title| String::from_utf8_lossy(title.as_str().unwrap()).into_owned())
+			.collect();
+		names.sort();
+		names
+	};
+	assert_eq!
+	(
+		bookmark_names(BookmarkNamePolicy::DisambiguateDuplicates),
+		vec!["Alarm", "Alarm (2)", "Fireball (PHB)", "Fireball (XGE)", "Title Page"]
+	);
+	assert_eq!
+	(
+		bookmark_names(BookmarkNamePolicy::SpellName),
+		vec!["Alarm", "Alarm", "Fireball", "Fireball", "Title Page"]
+	);
+}
+
+// Makes sure escaping text keeps tags from being processed and that unescaping undoes it
+#[test]
+fn rich_text_escaping()
+{
+	assert_eq!(spells::escape_rich_text("Use <b> for bold and \\<i> too"), "Use \\<b> for bold and \\\\<i> too");
+	assert_eq!
+	(
+		spells::escape_rich_text("[table][0] starts this\nbut not [table][1] or <url=https://example.com> </url>"),
+		"\\[table][0] starts this\nbut not [table][1] or \\<url=https://example.com> \\</url>"
+	);
+	// Text without tags stays the same, including whitespace and other backslashes
+	let plain = "  Some \\ text <notatag>\n\n- [table] b>  ";
+	assert_eq!(spells::escape_rich_text(plain), plain);
+	for text in ["<r> <b> <i> <bi> <ib>", "\\\\<url> a \\</url>\n\\[table][3]", plain, ""]
+	{
+		assert_eq!(spells::unescape_rich_text(&spells::escape_rich_text(text)), text);
+	}
+}
+
+// Turns "<dc>" into text and remembers whether each one was at the start of a paragraph
+struct DcTagHandler(Arc<Mutex<Vec<bool>>>);
+
+impl DescriptionTagHandler for DcTagHandler
+{
+	fn handles(&self, token: &str) -> bool { token == "<dc>" }
+
+	fn handle(&self, _: &str, context: &TagContext) -> TagOutput
+	{
+		self.0.lock().unwrap().push(context.at_paragraph_start);
+		TagOutput::Text(String::from("<b> DC <r>"))
+	}
+}
+
+// Draws a line across the text area in place of "[map][0]"
+struct MapTagHandler;
+
+impl DescriptionTagHandler for MapTagHandler
+{
+	fn handles(&self, token: &str) -> bool { token == "[map][0]" }
+
+	fn handle(&self, _: &str, context: &TagContext) -> TagOutput
+	{
+		let points = vec!
+		[
+			(printpdf::Point::new(context.x_min, context.y), false),
+			(printpdf::Point::new(context.x_max, context.y), false)
+		];
+		context.layer.add_line(printpdf::Line { points: points, is_closed: false });
+		TagOutput::Block(Mm(20.0))
+	}
+}
+
+// Makes sure custom tag handlers get called for their tags in spell descriptions
+#[test]
+fn custom_description_tags()
+{
+	let mut spell_list = vec![spells::Spell::from_json_file("spells/players_handbook_2024/fire_bolt.json").unwrap()];
+	spell_list[0].description.push_str("\n[map][0]\nMake a <dc> 15 Dexterity saving throw.\n<dc> starts this paragraph.");
+	let dc_calls = Arc::new(Mutex::new(Vec::new()));
+	let mut additional_options = AdditionalOptions::default();
+	additional_options.tag_handlers.register(DcTagHandler(dc_calls.clone()));
+	additional_options.tag_handlers.register(MapTagHandler);
+	let bytes = small_spellbook(&spell_list, &additional_options).save_to_bytes().unwrap();
+	// One tag in the middle of a paragraph and one at the start of a paragraph
+	assert_eq!(*dc_calls.lock().unwrap(), vec![false, true]);
+	// The map got drawn on the spell's page
+	let doc = lopdf::Document::load_mem(&bytes).unwrap();
+	let content = lopdf::content::Content::decode(&doc.get_page_content(doc.get_pages()[&2]).unwrap()).unwrap();
+	assert_eq!(content.operations.iter().filter(|operation| operation.operator == "S").count(), 1);
+}
+
+// Makes sure page hooks get called for each new page
+#[test]
+fn page_created_hooks()
+{
+	let spell_list = vec!
+	[
+		spells::Spell::from_json_file("spells/players_handbook_2024/fire_bolt.json").unwrap(),
+		spells::Spell::from_json_file("spells/players_handbook_2024/alarm.json").unwrap()
+	];
+	let pages = Arc::new(Mutex::new(Vec::new()));
+	let hook_pages = pages.clone();
+	let mut additional_options = AdditionalOptions::default();
+	additional_options.page_hooks.on_page_created(move |page|
+	{
+		hook_pages.lock().unwrap().push((page.page_index, page.page_number));
+		// Draw a line across the top of the page
+		let points = vec!
+		[
+			(printpdf::Point::new(Mm(0.0), page.page_height), false),
+			(printpdf::Point::new(page.page_width, page.page_height), false)
+		];
+		page.layer.add_line(printpdf::Line { points: points, is_closed: false });
+	});
+	let bytes = small_spellbook(&spell_list, &additional_options).save_to_bytes().unwrap();
+	// Called for every page except the title page
+	assert_eq!(*pages.lock().unwrap(), vec![(1, 1), (2, 2)]);
+	let doc = lopdf::Document::load_mem(&bytes).unwrap();
+	let line_counts: Vec<_> = doc.get_pages().into_values()
+		.map(|page_id| lopdf::content::Content::decode(&doc.get_page_content(page_id).unwrap()).unwrap())
+		.map(|content| content.operations.iter().filter(|operation| operation.operator == "S").count())
+		.collect();
+	assert_eq!(line_counts, vec![0, 1, 1]);
+}
+
+// Makes sure the layout outlines get drawn on every page when debugging the layout
+#[test]
+fn debug_layout()
+{
+	let spell_list = vec![spells::Spell::from_json_file("spells/players_handbook_2024/augury.json").unwrap()];
+	// Counts the number of rectangles and lines drawn on each page of a spellbook
+	let shape_counts = |debug_layout: bool|
+	{
+		let additional_options = AdditionalOptions
+		{
+			glossary: Some(GlossaryOptions::srd_conditions()),
+			debug_layout: debug_layout,
+			..Default::default()
+		};
+		let bytes = small_spellbook(&spell_list, &additional_options).save_to_bytes().unwrap();
+		let doc = lopdf::Document::load_mem(&bytes).unwrap();
+		doc.get_pages().into_values()
+			.map(|page_id| lopdf::content::Content::decode(&doc.get_page_content(page_id).unwrap()).unwrap())
+			.map(|content|
+			(
+				content.operations.iter().filter(|operation| operation.operator == "s").count(),
+				content.operations.iter().filter(|operation| operation.operator == "S").count()
+			))
+			.collect::<Vec<_>>()
+	};
+	// Nothing extra gets drawn normally
+	let counts = shape_counts(false);
+	assert!(counts.iter().all(|(rectangles, _)| *rectangles == 0));
+	let table_lines = counts[1].1;
+	// The title page only has its margins and the title's baseline
+	let counts = shape_counts(true);
+	assert_eq!(counts[0], (1, 1));
+	// The spell page has its margins, table cells, and the baselines of the text
+	assert!(counts[1].0 > 1);
+	assert!(counts[1].1 > table_lines);
+	// The glossary has its margins and both of its columns
+	assert_eq!(counts[counts.len() - 1].0, 3);
+}
+
+// Stand-in for a real pdf renderer that turns each page into a small image based on how many operations it has
+struct OperationCountRasterizer;
+
+impl golden::PageRasterizer for OperationCountRasterizer
+{
+	fn rasterize_page(&self, pdf_bytes: &[u8], page_index: usize, _dpi: f32)
+	-> Result<image::RgbaImage, Box<dyn std::error::Error + Send + Sync>>
+	{
+		let doc = lopdf::Document::load_mem(pdf_bytes)?;
+		let page_id = doc.get_pages()[&(page_index as u32 + 1)];
+		let content = lopdf::content::Content::decode(&doc.get_page_content(page_id)?)?;
+		let shade = (content.operations.len() % 256) as u8;
+		Ok(image::RgbaImage::from_pixel(8, 8, image::Rgba([shade, shade, shade, 255])))
+	}
+}
+
+// Makes sure rendered pages get compared to reference images correctly
+#[test]
+fn golden_images()
+{
+	// Pixels within the tolerance count as the same
+	let black = image::RgbaImage::from_pixel(4, 4, image::Rgba([0, 0, 0, 255]));
+	let mut almost_black = black.clone();
+	almost_black.put_pixel(0, 0, image::Rgba([10, 0, 0, 255]));
+	let difference = golden::compare_images(&almost_black, &black, 16).unwrap();
+	assert_eq!(difference.differing_pixels, 0);
+	assert_eq!(difference.max_channel_difference, 10);
+	let difference = golden::compare_images(&almost_black, &black, 5).unwrap();
+	assert_eq!(difference.differing_pixels, 1);
+	assert_eq!(difference.differing_pixel_ratio(), 1.0 / 16.0);
+	// Images of different sizes never match
+	assert!(golden::compare_images(&black, &image::RgbaImage::new(2, 2), 16).is_err());
+	// Spellbooks get checked against reference images of their pages
+	let reference_folder = std::env::temp_dir().join(format!("dnd_spellbook_maker_golden_test_{}", std::process::id()));
+	let _ = fs::remove_dir_all(&reference_folder);
+	let spellbook_bytes = |spell_file: &str|
+	{
+		let spell_list = vec![spells::Spell::from_json_file(spell_file).unwrap()];
+		small_spellbook(&spell_list, &AdditionalOptions::default()).save_to_bytes().unwrap()
+	};
+	let fireball = spellbook_bytes("spells/players_handbook_2024/fireball.json");
+	let options = golden::GoldenImageOptions { channel_tolerance: 0, max_differing_pixel_ratio: 0.0, ..Default::default() };
+	let check = |bytes: &[u8]| golden::check_pages_against_references
+	(bytes, &[0, 1], &reference_folder, "small", &OperationCountRasterizer, &options);
+	// The references get made the first time
+	check(&fireball).unwrap();
+	assert!(reference_folder.join("small_page_1.png").exists());
+	// The same spellbook matches them
+	check(&fireball).unwrap();
+	// A different spell doesn't match and its page gets saved next to the reference
+	assert!(check(&spellbook_bytes("spells/players_handbook_2024/fire_bolt.json")).is_err());
+	assert!(reference_folder.join("small_page_1_actual.png").exists());
+	assert!(!reference_folder.join("small_page_0_actual.png").exists());
+	fs::remove_dir_all(&reference_folder).unwrap();
+}
+
+// Creates a spellbook writer with the default options for testing how text gets split into lines
+fn test_writer() -> SpellbookWriter<'static>
+{
+	let
+	(
+		font_paths,
+		font_sizes,
+		font_scalars,
+		spacing_options,
+		text_colors,
+		page_size_options,
+		page_number_options,
+		_,
+		_,
+		table_options
+	) = default_spellbook_options();
+	SpellbookWriter::new
+	(
+		"Test",
+		font_paths,
+		font_sizes,
+		font_scalars,
+		spacing_options,
+		text_colors,
+		page_size_options,
+		Some(page_number_options),
+		None,
+		table_options,
+		&AdditionalOptions::default()
+	).unwrap()
+}
+
+// Characters for random text that don't have any special meaning in spell descriptions
+// Includes multi-byte characters since text gets split at the start of characters when it's hyphenated
+const PLAIN_CHARS: &str = "[a-zA-Z0-9.,;:!?'()éüßøçñ字魔法ΣπЖ🐉🔥]";
+
+// Random tokens of plain characters with a length in the given range (ex: "1,12")
+fn plain_token(length_range: &str) -> impl Strategy<Value = String>
+{
+	prop::string::string_regex(&format!("{}{{{}}}", PLAIN_CHARS, length_range)).unwrap()
+}
+
+// Random tokens that show up in spell descriptions, including ones with special meaning
+fn description_token() -> impl Strategy<Value = String>
+{
+	prop_oneof!
+	[
+		// Plain words
+		plain_token("1,12"),
+		// Long unbroken tokens that have to be hyphenated
+		plain_token("20,150"),
+		// Any characters at all
+		"\\PC{1,30}".prop_map(String::from),
+		// Font tags, url tags, and table tags that might be escaped any number of times
+		(0..4usize, prop::sample::select(vec!["<r>", "<b>", "<i>", "<bi>", "<ib>", "<url>", "</url>", "<url=https://example.com>", "[table][0]", "[table][7]"]))
+			.prop_map(|(backslashes, tag)| format!("{}{}", "\\".repeat(backslashes), tag))
+	]
+}
+
+// Random spell description text
+fn description_text(token: impl Strategy<Value = String>) -> impl Strategy<Value = String>
+{
+	prop::collection::vec((token, prop::sample::select(vec![" ", "  ", "\n", "\t"])), 0..60)
+		.prop_map(|tokens| tokens.into_iter().map(|(token, whitespace)| token + whitespace).collect())
+}
+
+// Gets all of the text in some lines
+fn lines_text(lines: &Vec<TextLine>) -> String
+{
+	lines.iter().flat_map(|line| line.tokens().iter().map(Token::as_spellbook_string)).collect()
+}
+
+// Makes sure text can be split into lines without panicking or losing any characters
+#[test]
+fn textbox_lines_fuzz()
+{
+	let writer = test_writer();
+	let mut runner = TestRunner::new(Config { cases: 512, ..Config::default() });
+	// Any text at all can be split into lines, even if the textbox is narrower than a single character
+	runner.run(&(description_text(description_token()), 0.0f32..200.0, 0.5f32..200.0), |(text, first_line_width, textbox_width)|
+	{
+		writer.get_textbox_lines(&text, Mm(first_line_width.min(textbox_width)), Mm(textbox_width));
+		Ok(())
+	}).unwrap();
+	// Every character in plain text ends up in a line (with hyphens added in between)
+	runner.run(&(description_text(plain_token("1,150")), 0.0f32..200.0, 0.5f32..200.0), |(text, first_line_width, textbox_width)|
+	{
+		let lines = writer.get_textbox_lines(&text, Mm(first_line_width.min(textbox_width)), Mm(textbox_width));
+		let expected: String = text.split_whitespace().collect();
+		prop_assert_eq!(lines_text(&lines).replace('-', ""), expected);
+		// Each line after the first fits in the textbox unless it's a single character that's too wide
+		for line in lines.iter().skip(1)
+		{
+			let line_text = lines_text(&vec![line.clone()]);
+			prop_assert!(line.width() <= textbox_width + 0.001 || line_text.trim_end_matches('-').chars().count() <= 1);
+		}
+		Ok(())
+	}).unwrap();
+}
+
+// Makes sure adding characters to text never makes it narrower
+#[test]
+fn text_width_monotonicity()
+{
+	let writer = test_writer();
+	let mut runner = TestRunner::default();
+	runner.run(&plain_token("1,80"), |text|
+	{
+		let mut previous_width = Mm(0.0);
+		for (index, _) in text.char_indices().skip(1).chain(std::iter::once((text.len(), ' ')))
+		{
+			let width = writer.calc_text_width(&text[..index]);
+			prop_assert!(width >= previous_width, "{:?} is narrower than the text before it", &text[..index]);
+			previous_width = width;
+		}
+		Ok(())
+	}).unwrap();
+}
+
+// Makes sure the layout module splits text into the same lines that spellbooks do
+#[test]
+fn layout_matches_spellbooks()
+{
+	let (font_paths, font_sizes, font_scalars, ..) = default_spellbook_options();
+	// Test spellbook writers start out writing title text
+	let measurer = layout::FontMeasurer::new(&font_paths, font_sizes.title_font_size(), font_scalars).unwrap();
+	let writer = test_writer();
+	let text = "A <b> bold </b> claim, an <url=https://example.com> example link </url> and \\<i> escaped tag, then \
+	Supercalifragilisticexpialidocious-Antidisestablishmentarianism.";
+	for width in [15.0, 40.0, 80.0, 200.0]
+	{
+		let lines = layout::wrap_text(text, width / 2.0, width, FontVariant::Regular, &measurer);
+		assert_eq!(lines, writer.get_textbox_lines(text, Mm(width / 2.0), Mm(width)));
+		// Every line after the first fits in the textbox unless it's a single character that's too wide
+		for line in lines.iter().skip(1)
+		{
+			let line_text = lines_text(&vec![line.clone()]);
+			assert!(line.width() <= width || line_text.trim_end_matches('-').chars().count() <= 1);
+		}
+	}
+	assert!(layout::wrap_text("", 10.0, 10.0, FontVariant::Regular, &measurer).is_empty());
+}
+
+// Makes sure words that are too long to fit on a line get hyphenated between syllables when there's a hyphenator
+#[cfg(feature = "hyphenation")]
+#[test]
+fn syllable_hyphenation()
+{
+	use crate::layout::TextMeasurer;
+
+	let hyphenator = Hyphenator::new(&HyphenationOptions::default()).unwrap();
+	// Punctuation is ignored and words with hyphens can also be split after them
+	assert_eq!(hyphenator.break_points("extensive"), vec![2, 5]);
+	assert_eq!(hyphenator.break_points("(extensive),"), vec![3, 6]);
+	assert_eq!(hyphenator.break_points("well-known"), vec![5]);
+	assert!(hyphenator.break_points("1,000").is_empty());
+	assert!(Hyphenator::new(&HyphenationOptions { language: String::from("xx"), ..Default::default() }).is_err());
+	let (font_paths, font_sizes, font_scalars, ..) = default_spellbook_options();
+	let measurer = layout::FontMeasurer::new(&font_paths, font_sizes.body_font_size(), font_scalars).unwrap();
+	let word = "Antidisestablishmentarianism";
+	let break_points = hyphenator.break_points(word);
+	for width in [10.0, 15.0, 25.0]
+	{
+		let lines =
+			layout::wrap_text_with_hyphenation(word, width, width, FontVariant::Regular, &measurer, &hyphenator);
+		assert!(lines.len() > 1);
+		// Every line but the last ends at a syllable boundary with a hyphen and fits in the textbox (unless not even
+		// the next syllable fits on the line)
+		let mut split_index = 0;
+		for line in &lines[..lines.len() - 1]
+		{
+			let line_text = lines_text(&vec![line.clone()]);
+			let part = line_text.strip_suffix('-').unwrap();
+			let next_syllable = break_points.iter().find(|index| **index > split_index).unwrap();
+			let next_syllable_fits =
+				measurer.text_width(&format!("{}-", &word[split_index..*next_syllable]), FontVariant::Regular) <= width;
+			split_index += part.len();
+			assert!(break_points.contains(&split_index) || !next_syllable_fits, "{:?} isn't split between syllables",
+				line_text);
+			assert!(line.width() <= width);
+		}
+		assert_eq!(lines_text(&lines).replace('-', ""), word);
+	}
+}
+
+// Makes sure spell stats count the words and tables in spells and predict how many lines their text takes up
+#[test]
+fn spell_stats()
+{
+	use crate::layout::TextMeasurer;
+
+	let (font_paths, font_sizes, font_scalars, ..) = default_spellbook_options();
+	let measurer = layout::FontMeasurer::new(&font_paths, font_sizes.body_font_size(), font_scalars).unwrap();
+	let mut spell = spells::Spell::from_json_file("spells/players_handbook_2024/fire_bolt.json").unwrap();
+	spell.description = String::from("A <b> incandescent <r> streak of fire.\n[table][0]\n\u{2022} Burns things.");
+	spell.upcast_description = Some(String::from("More damage."));
+	spell.tables = vec![spells::Table
+	{
+		title: String::from("Sparks"),
+		column_labels: Vec::new(),
+		cells: vec![vec![String::from("A spark")]]
+	}];
+	let stats = |textbox_width: f32| spell.stats(&spells::SpellStatsOptions
+	{
+		measurer: &measurer,
+		textbox_width: textbox_width,
+		tab_amount: 5.0
+	});
+	// Tags, bullets, and table tags aren't words
+	let wide = stats(1000.0);
+	assert_eq!(wide.word_count, 9);
+	assert_eq!(wide.table_count, 1);
+	assert_eq!(wide.longest_token, "incandescent");
+	assert_eq!(wide.longest_token_width, measurer.text_width("incandescent", FontVariant::Regular));
+	// Each paragraph takes up a line when nothing has to wrap
+	assert_eq!(wide.line_count, 3);
+	// Narrow text boxes need more lines
+	let narrow = stats(20.0);
+	assert!(narrow.line_count > wide.line_count);
+	assert_eq!(narrow.word_count, wide.word_count);
+}
+
+// Makes sure asking for hyphenation without the `hyphenation` feature is an error instead of ignoring it
+#[cfg(not(feature = "hyphenation"))]
+#[test]
+fn syllable_hyphenation_needs_feature()
+{
+	let error = Hyphenator::new(&HyphenationOptions::default()).unwrap_err();
+	assert!(error.to_string().contains("`hyphenation` feature"));
+	let additional_options =
+		AdditionalOptions { hyphenation: Some(HyphenationOptions::default()), ..Default::default() };
+	let spell_list = Vec::new();
+	let
+	(
+		font_paths,
+		font_sizes,
+		font_scalars,
+		spacing_options,
+		text_colors,
+		page_size_options,
+		page_number_options,
+		_,
+		_,
+		table_options
+	) = default_spellbook_options();
+	let result = create_spellbook_with_options
+	(
+		"Small Spellbook",
+		&spell_list,
+		font_paths,
+		font_sizes,
+		font_scalars,
+		spacing_options,
+		text_colors,
+		page_size_options,
+		Some(page_number_options),
+		None,
+		table_options,
+		&additional_options
+	);
+	assert!(result.err().unwrap().downcast_ref::<HyphenationError>().is_some());
+}
+
+// Makes sure spells that go over the limits get warnings
+#[test]
+fn spell_limit_warnings()
+{
+	// The stress test spells go over the default limits
+	let limits = SpellLimits::default();
+	let warnings: Vec<_> = get_all_spells_in_folder("spells/necronomicon").unwrap().iter()
+		.flat_map(|spell| limits.check(spell))
+		.collect();
+	assert!(warnings.iter().any(|warning| matches!(warning, SpellbookWarning::NameTooLong { .. })));
+	assert!(warnings.iter().any(|warning| matches!(warning, SpellbookWarning::DescriptionTooLong { .. })));
+	// Official spells don't
+	let spell_list = vec!
+	[
+		spells::Spell::from_json_file("spells/players_handbook_2024/augury.json").unwrap(),
+		spells::Spell::from_json_file("spells/players_handbook_2024/fireball.json").unwrap()
+	];
+	assert!(spell_list.iter().all(|spell| limits.check(spell).is_empty()));
+	// Warnings get returned with the spellbook
+	let additional_options = AdditionalOptions
+	{
+		limits: Some(SpellLimits { max_name_length: 6, max_table_columns: 1, ..Default::default() }),
+		..Default::default()
+	};
+	let spellbook = small_spellbook(&spell_list, &additional_options);
+	assert_eq!(spellbook.warnings, vec!
+	[
+		SpellbookWarning::TooManyTableColumns
+		{
+			spell_name: String::from("Augury"),
+			table_index: 0,
+			columns: 2,
+			limit: 1
+		},
+		SpellbookWarning::NameTooLong { spell_name: String::from("Fireball"), length: 8, limit: 6 }
+	]);
+	assert!(spellbook.warnings[1].to_string().contains("Fireball"));
+	// No limits means no warnings
+	assert!(small_spellbook(&spell_list, &AdditionalOptions::default()).warnings.is_empty());
+}
+
+// Makes sure background images get opened once and shared between spellbooks that use the same image cache
+#[test]
+fn image_cache()
+{
+	let spell_list = vec![spells::Spell::from_json_file("spells/players_handbook_2024/fire_bolt.json").unwrap()];
+	let (font_paths, font_sizes, font_scalars, spacing_options, text_colors, page_size_options, _, _, _, table_options) =
+	default_spellbook_options();
+	let cache = ImageCache::new();
+	let background = (String::from("img/parchment.jpg"), ImageTransform { dpi: Some(300.0), ..Default::default() });
+	let additional_options = AdditionalOptions
+	{
+		background_fit: Some(BackgroundFit::Stretch),
+		// The title page uses the same image with the same transform as every other page
+		section_backgrounds: Some(SectionBackgroundOptions
+		{
+			title_page: Some(background.clone()),
+			chapter_pages: None
+		}),
+		image_cache: Some(cache.clone()),
+		fingerprint: Some(FingerprintOptions::default()),
+		..Default::default()
+	};
+	let spellbook = || create_spellbook_with_options
+	(
+		"Image Cache Spellbook",
+		&spell_list,
+		font_paths.clone(),
+		font_sizes,
+		font_scalars,
+		spacing_options,
+		text_colors,
+		page_size_options,
+		None,
+		Some((&background.0, background.1)),
+		table_options,
+		&additional_options
+	).unwrap();
+	let first = spellbook();
+	assert_eq!(cache.len(), 1);
+	// The second spellbook reuses the image and comes out the same
+	let second = spellbook();
+	assert_eq!(cache.len(), 1);
+	assert_eq!(first.fingerprint, second.fingerprint);
+	assert_eq!(first.save_to_bytes().unwrap().len(), second.save_to_bytes().unwrap().len());
+	// A different dpi means the image could come out differently, so it gets opened again
+	let other_dpi = ImageTransform { dpi: Some(150.0), ..background.1 };
+	BackgroundImage::new(&background.0, other_dpi, None, None, Mm(210.0), Mm(297.0), &cache).unwrap();
+	assert_eq!(cache.len(), 2);
+	cache.clear();
+	assert!(cache.is_empty());
+}
+
+// Makes sure text colors get checked against the background behind them and adjusted when asked to
+#[test]
+fn contrast_check()
+{
+	// A dark brown background image
+	let folder = std::env::temp_dir().join(format!("dnd_spellbook_maker_contrast_test_{}", std::process::id()));
+	fs::create_dir_all(&folder).unwrap();
+	let dark_path = folder.join("dark.png");
+	image::RgbImage::from_pixel(20, 20, image::Rgb([40, 30, 20])).save(&dark_path).unwrap();
+	let dark_path = dark_path.to_str().unwrap();
+	let spell_list = vec![spells::Spell::from_json_file("spells/players_handbook_2024/fire_bolt.json").unwrap()];
+	let (font_paths, font_sizes, font_scalars, spacing_options, text_colors, page_size_options, _, _, _, table_options) =
+	default_spellbook_options();
+	// Makes a spellbook with a background image that gets stretched over the pages
+	let spellbook = |background_path: &str, contrast_check: ContrastCheckOptions|
+	{
+		let additional_options = AdditionalOptions
+		{
+			background_fit: Some(BackgroundFit::Stretch),
+			contrast_check: Some(contrast_check),
+			..Default::default()
+		};
+		create_spellbook_with_options
+		(
+			"Contrast Spellbook",
+			&spell_list,
+			font_paths.clone(),
+			font_sizes,
+			font_scalars,
+			spacing_options,
+			text_colors,
+			page_size_options,
+			None,
+			Some((background_path, ImageTransform::default())),
+			table_options,
+			&additional_options
+		).unwrap()
+	};
+	// The background gets averaged into its own color
+	let background = BackgroundImage::new
+	(
+		dark_path,
+		ImageTransform::default(),
+		Some(BackgroundFit::Stretch),
+		None,
+		Mm(210.0),
+		Mm(297.0),
+		&ImageCache::new()
+	).unwrap();
+	assert_eq!(background.average_color(Mm(10.0), Mm(10.0), Mm(200.0), Mm(291.0)), (40, 30, 20));
+	// The dark text colors can't be read on the dark background
+	let warnings = spellbook(dark_path, ContrastCheckOptions::default()).warnings;
+	assert_eq!(warnings.len(), 5);
+	assert!(warnings.iter().any(|warning| matches!
+	(
+		warning,
+		SpellbookWarning::LowContrast { text, color: (0, 0, 0), background_color: (40, 30, 20), adjusted_color: None }
+		if text == "body"
+	)));
+	// Adjusted colors get lightened until they have enough contrast
+	let adjust = ContrastCheckOptions { adjust_colors: true, ..Default::default() };
+	for warning in spellbook(dark_path, adjust).warnings
+	{
+		match warning
+		{
+			SpellbookWarning::LowContrast { color, adjusted_color: Some(adjusted_color), .. } =>
+			{
+				assert!(adjusted_color.0 > color.0);
+				assert!(contrast_ratio(adjusted_color, (40, 30, 20)) >= 4.5);
+			},
+			_ => panic!("Unexpected warning: {}", warning)
+		}
+	}
+	// The text colors can be read on the parchment background
+	assert!(spellbook("img/parchment.jpg", ContrastCheckOptions::default()).warnings.is_empty());
+	fs::remove_dir_all(&folder).unwrap();
+}
+
+// Makes sure every page gets filled with the background color (in bands for gradients) and that text gets checked
+// against it
+#[test]
+fn background_color()
+{
+	let spell_list = vec![spells::Spell::from_json_file("spells/players_handbook_2024/fire_bolt.json").unwrap()];
+	// Gets the number of fill operations and the fill colors on each page of a spellbook along with its warnings
+	let fills = |additional_options: &AdditionalOptions|
+	{
+		let spellbook = small_spellbook(&spell_list, additional_options);
+		let warnings = spellbook.warnings.clone();
+		(page_fills(&spellbook.save_to_bytes().unwrap()), warnings)
+	};
+	let (plain_pages, _) = fills(&AdditionalOptions::default());
+	let solid = AdditionalOptions { background_color: Some((255, 0, 0)), ..Default::default() };
+	let (solid_pages, _) = fills(&solid);
+	// Each page gets filled with the color before anything else
+	for ((fill_count, colors), (plain_fill_count, _)) in solid_pages.iter().zip(&plain_pages)
+	{
+		assert_eq!(colors[0], vec![1.0, 0.0, 0.0]);
+		assert_eq!(*fill_count, plain_fill_count + 1);
+	}
+	let gradient = AdditionalOptions
+	{
+		background_color: Some((0, 0, 0)),
+		background_gradient: Some((0, 0, 255)),
+		contrast_check: Some(ContrastCheckOptions::default()),
+		..Default::default()
+	};
+	let (gradient_pages, warnings) = fills(&gradient);
+	// Gradients fade from the top of the page to the bottom
+	let (fill_count, colors) = &gradient_pages[1];
+	assert_eq!(*fill_count, plain_pages[1].0 + 64);
+	assert!(colors[0][2] < 0.05);
+	assert!(colors[63][2] > 0.95);
+	// The default text colors are too dark for a dark background
+	assert!(!warnings.is_empty());
+}
+
+// Makes sure background images get sized and positioned to fit pages
+#[test]
+fn background_fit()
+{
+	// A 100x50 pixel image is 25.4x12.7 mm at 100 dpi
+	let transforms = |fit: BackgroundFit| fit.transforms(100, 50, 100.0, 50.8, 50.8);
+	// Returns the bounds of the image on the page (x min, y min, x max, y max) for a transform
+	let bounds = |transform: &ImageTransform|
+	{
+		let x = transform.translate_x.unwrap().0;
+		let y = transform.translate_y.unwrap().0;
+		(x, y, x + 25.4 * transform.scale_x.unwrap(), y + 12.7 * transform.scale_y.unwrap())
+	};
+	let assert_bounds = |actual: (f32, f32, f32, f32), expected: (f32, f32, f32, f32)|
+	{
+		let (a, e) = ([actual.0, actual.1, actual.2, actual.3], [expected.0, expected.1, expected.2, expected.3]);
+		assert!(a.iter().zip(e.iter()).all(|(a, e)| (a - e).abs() < 0.001), "{:?} != {:?}", actual, expected);
+	};
+	assert_bounds(bounds(&transforms(BackgroundFit::Stretch)[0]), (0.0, 0.0, 50.8, 50.8));
+	assert_bounds(bounds(&transforms(BackgroundFit::Cover)[0]), (-25.4, 0.0, 76.2, 50.8));
+	assert_bounds(bounds(&transforms(BackgroundFit::Contain)[0]), (0.0, 12.7, 50.8, 38.1));
+	assert_bounds(bounds(&transforms(BackgroundFit::ActualSize)[0]), (0.0, 38.1, 25.4, 50.8));
+	// Tiles start at the top left corner and cover the whole page
+	let tiles = transforms(BackgroundFit::Tile);
+	assert_eq!(tiles.len(), 8);
+	assert_bounds(bounds(&tiles[0]), (0.0, 38.1, 25.4, 50.8));
+	assert_bounds(bounds(&tiles[7]), (25.4, 0.0, 50.8, 12.7));
+	// Every tile goes on each page of a spellbook
+	let
+	(
+		font_paths,
+		font_sizes,
+		font_scalars,
+		spacing_options,
+		text_colors,
+		page_size_options,
+		page_number_options,
+		background_path,
+		_,
+		table_options
+	) = default_spellbook_options();
+	let spell_list = vec![spells::Spell::from_json_file("spells/players_handbook_2024/fireball.json").unwrap()];
+	let image = image::open(&background_path).unwrap();
+	let background_transform = ImageTransform { dpi: Some(300.0), ..Default::default() };
+	let tile_count = BackgroundFit::Tile.transforms
+	(
+		image.width(),
+		image.height(),
+		300.0,
+		page_size_options.width(),
+		page_size_options.height()
+	).len();
+	let additional_options = AdditionalOptions { background_fit: Some(BackgroundFit::Tile), ..Default::default() };
+	let bytes = create_spellbook_with_options
+	(
+		"Tiled Spellbook",
+		&spell_list,
+		font_paths,
+		font_sizes,
+		font_scalars,
+		spacing_options,
+		text_colors,
+		page_size_options,
+		Some(page_number_options),
+		Some((&background_path, background_transform)),
+		table_options,
+		&additional_options
+	).unwrap().save_to_bytes().unwrap();
+	let doc = lopdf::Document::load_mem(&bytes).unwrap();
+	for page_id in doc.get_pages().into_values()
+	{
+		let content = lopdf::content::Content::decode(&doc.get_page_content(page_id).unwrap()).unwrap();
+		assert_eq!(content.operations.iter().filter(|operation| operation.operator == "Do").count(), tile_count);
+	}
+}
+
+// Makes sure the title page and chapter pages get their own backgrounds
+#[test]
+fn section_backgrounds()
+{
+	let spell_list = vec![spells::Spell
+	{
+		classes: vec![String::from("Sorcerer"), String::from("Wizard")],
+		..spells::Spell::from_json_file("spells/players_handbook_2024/fire_bolt.json").unwrap()
+	}];
+	let class_chapters = ClassChapterOptions::default();
+	let chapter_count = class_chapters.chapters_for(&spell_list).len();
+	let background = (String::from("img/parchment.jpg"), ImageTransform { dpi: Some(300.0), ..Default::default() });
+	let additional_options = AdditionalOptions
+	{
+		class_chapters: Some(class_chapters),
+		section_backgrounds: Some(SectionBackgroundOptions
+		{
+			title_page: Some(background.clone()),
+			chapter_pages: Some(background)
+		}),
+		..Default::default()
+	};
+	let bytes = small_spellbook(&spell_list, &additional_options).save_to_bytes().unwrap();
+	let doc = lopdf::Document::load_mem(&bytes).unwrap();
+	// The number of images on each page
+	let image_counts: Vec<_> = doc.get_pages().into_values().map(|page_id|
+	{
+		let content = lopdf::content::Content::decode(&doc.get_page_content(page_id).unwrap()).unwrap();
+		content.operations.iter().filter(|operation| operation.operator == "Do").count()
+	})
+	.collect();
+	// Only the title page and chapter pages have a background (since there isn't a normal background)
+	assert_eq!(image_counts[0], 1);
+	assert_eq!(image_counts[1], 1);
+	assert_eq!(image_counts.iter().sum::<usize>(), chapter_count + 1);
+	assert!(image_counts.len() > chapter_count + 1);
+}
+
+// Makes sure the background image gets mirrored and rotated on every other page
+#[test]
+fn background_alternation()
+{
+	let transform = ImageTransform
+	{
+		translate_x: Some(Mm(10.0)),
+		translate_y: Some(Mm(20.0)),
+		scale_x: Some(2.0),
+		..Default::default()
+	};
+	// The image gets flipped over the middle of the page
+	let mirrored = BackgroundAlternation::MirrorHorizontally.apply(&transform, 100.0, 200.0);
+	assert_eq!((mirrored.translate_x, mirrored.translate_y), (Some(Mm(90.0)), Some(Mm(20.0))));
+	assert_eq!((mirrored.scale_x, mirrored.scale_y), (Some(-2.0), None));
+	let mirrored = BackgroundAlternation::MirrorVertically.apply(&transform, 100.0, 200.0);
+	assert_eq!((mirrored.translate_x, mirrored.translate_y), (Some(Mm(10.0)), Some(Mm(180.0))));
+	assert_eq!((mirrored.scale_x, mirrored.scale_y), (Some(2.0), Some(-1.0)));
+	let rotated = BackgroundAlternation::Rotate180.apply(&transform, 100.0, 200.0);
+	assert_eq!((rotated.translate_x, rotated.translate_y), (Some(Mm(90.0)), Some(Mm(180.0))));
+	assert_eq!((rotated.scale_x, rotated.scale_y), (Some(-2.0), Some(-1.0)));
+	// Only pages with odd indexes get changed in a spellbook
+	let
+	(
+		font_paths,
+		font_sizes,
+		font_scalars,
+		spacing_options,
+		text_colors,
+		page_size_options,
+		page_number_options,
+		background_path,
+		background_transform,
+		table_options
+	) = default_spellbook_options();
+	let spell_list = vec!
+	[
+		spells::Spell::from_json_file("spells/players_handbook_2024/augury.json").unwrap(),
+		spells::Spell::from_json_file("spells/players_handbook_2024/fireball.json").unwrap(),
+		spells::Spell::from_json_file("spells/players_handbook_2024/animate_objects.json").unwrap()
+	];
+	let additional_options = AdditionalOptions
+	{
+		background_alternation: Some(BackgroundAlternation::MirrorHorizontally),
+		..Default::default()
+	};
+	let bytes = create_spellbook_with_options
+	(
+		"Mirrored Spellbook",
+		&spell_list,
+		font_paths,
+		font_sizes,
+		font_scalars,
+		spacing_options,
+		text_colors,
+		page_size_options,
+		Some(page_number_options),
+		Some((&background_path, background_transform)),
+		table_options,
+		&additional_options
+	).unwrap().save_to_bytes().unwrap();
+	let doc = lopdf::Document::load_mem(&bytes).unwrap();
+	let pages = doc.get_pages();
+	assert!(pages.len() > 2);
+	for (page_index, page_id) in pages.into_values().enumerate()
+	{
+		// The background image is the first thing drawn on each page
+		let content = lopdf::content::Content::decode(&doc.get_page_content(page_id).unwrap()).unwrap();
+		let image_matrix = content.operations.iter().find(|operation| operation.operator == "cm").unwrap();
+		let x_scale = image_matrix.operands[0].as_float().unwrap();
+		assert_eq!(x_scale < 0.0, page_index % 2 == 1);
+	}
+}
+
+// Makes sure svg files get rasterized at the right size (or give an error without the `svg` feature)
+#[test]
+fn svg_images()
+{
+	let svg_path = "img/compass_rose.svg";
+	if cfg!(feature = "svg")
+	{
+		// Svg sizes are in css pixels, which are 96 per inch
+		let image = load_image(svg_path, 96.0).unwrap();
+		assert_eq!((image.width(), image.height()), (48, 24));
+		let image = load_image(svg_path, 300.0).unwrap();
+		assert_eq!((image.width(), image.height()), (150, 75));
+		// Svg files can be used as backgrounds
+		let
+		(
+			font_paths,
+			font_sizes,
+			font_scalars,
+			spacing_options,
+			text_colors,
+			page_size_options,
+			page_number_options,
+			_,
+			_,
+			table_options
+		) = default_spellbook_options();
+		let spell_list = vec![spells::Spell::from_json_file("spells/players_handbook_2024/fireball.json").unwrap()];
+		let additional_options = AdditionalOptions { background_fit: Some(BackgroundFit::Cover), ..Default::default() };
+		create_spellbook_with_options
+		(
+			"Svg Spellbook",
+			&spell_list,
+			font_paths,
+			font_sizes,
+			font_scalars,
+			spacing_options,
+			text_colors,
+			page_size_options,
+			Some(page_number_options),
+			Some((svg_path, ImageTransform { dpi: Some(150.0), ..Default::default() })),
+			table_options,
+			&additional_options
+		).unwrap();
+	}
+	else
+	{
+		let error = load_image(svg_path, 96.0).unwrap_err();
+		assert!(error.to_string().contains("`svg` feature"));
+	}
+	// Other images aren't affected by the dpi
+	let image = load_image("img/parchment.jpg", 96.0).unwrap();
+	assert_eq!(image.width(), load_image("img/parchment.jpg", 300.0).unwrap().width());
+}
+
+// Makes sure color tags change the color of text without changing where lines get split
+#[test]
+fn color_tags()
+{
+	let (font_paths, font_sizes, font_scalars, ..) = default_spellbook_options();
+	let measurer = layout::FontMeasurer::new(&font_paths, font_sizes.body_font_size(), font_scalars).unwrap();
+	let colored = layout::wrap_text
+	(
+		"Takes 8d6 <color=#8B0000> fire damage </color> or <color=#123456> half \\</color>",
+		1000.0,
+		1000.0,
+		FontVariant::Regular,
+		&measurer
+	);
+	let plain = "Takes 8d6 fire damage or half \\</color>";
+	let plain = layout::wrap_text(plain, 1000.0, 1000.0, FontVariant::Regular, &measurer);
+	assert_eq!(colored[0].width(), plain[0].width());
+	let color_tokens: Vec<_> = colored[0].tokens().iter()
+		.filter(|token| matches!(token, Token::ColorStart(_) | Token::ColorEnd))
+		.collect();
+	// Colors that aren't closed get closed at the end of the text
+	assert_eq!
+	(
+		color_tokens,
+		vec![&Token::ColorStart((139, 0, 0)), &Token::ColorEnd, &Token::ColorStart((18, 52, 86)), &Token::ColorEnd]
+	);
+	// Color tags that aren't valid are just text
+	let lines = layout::wrap_text("<color=#12345> <color=red>", 1000.0, 1000.0, FontVariant::Regular, &measurer);
+	assert_eq!(lines_text(&lines), "<color=#12345><color=red>");
+	assert_eq!(spells::escape_rich_text("<color=#8b0000> red </color>"), "\\<color=#8b0000> red \\</color>");
+	// The color gets used in spellbooks and then changes back to the normal text color
+	let mut spell = spells::Spell::from_json_file("spells/players_handbook_2024/fireball.json").unwrap();
+	spell.description = String::from("Takes 8d6 <color=#ff0000> fire damage </color> on a failed save.");
+	let bytes = small_spellbook(&vec![spell], &AdditionalOptions::default()).save_to_bytes().unwrap();
+	let doc = lopdf::Document::load_mem(&bytes).unwrap();
+	let page_id = doc.get_pages()[&2];
+	let content = lopdf::content::Content::decode(&doc.get_page_content(page_id).unwrap()).unwrap();
+	let fill_colors: Vec<Vec<f32>> = content.operations.iter()
+		.filter(|operation| operation.operator == "rg")
+		.map(|operation| operation.operands.iter().map(|operand| operand.as_float().unwrap()).collect())
+		.collect();
+	let red_index = fill_colors.iter().position(|color| *color == vec![1.0, 0.0, 0.0]).unwrap();
+	assert!(fill_colors[red_index + 1..].iter().any(|color| *color != vec![1.0, 0.0, 0.0]));
+}
+
+// Makes sure spells get exported to virtual tabletops with their fields and formatting
+#[test]
+fn vtt_export()
+{
+	let upcast = UpcastOptions::default();
+	let fireball = spells::Spell::from_json_file("spells/players_handbook_2024/fireball.json").unwrap();
+	let item = vtt_export::export_spell(&fireball, vtt_export::VttFormat::FoundryVtt, &upcast);
+	assert_eq!(item["name"], "Fireball");
+	assert_eq!(item["type"], "spell");
+	assert_eq!(item["system"]["level"], 3);
+	assert_eq!(item["system"]["school"], "evo");
+	assert_eq!(item["system"]["activation"]["type"], "action");
+	assert_eq!(item["system"]["range"]["value"], 150);
+	assert_eq!(item["system"]["range"]["units"], "ft");
+	assert_eq!(item["system"]["duration"]["units"], "inst");
+	assert_eq!(item["system"]["components"]["material"], true);
+	assert_eq!(item["system"]["materials"]["value"], "a ball of bat guano and sulfur");
+	let description = item["system"]["description"]["value"].as_str().unwrap();
+	assert!(description.starts_with("<p>"));
+	assert!(description.contains("<p><strong><em>Using a Higher-Level Spell Slot.</em></strong> The damage increases"));
+	// Roll20 handouts have the spell's stats in them
+	let handout = vtt_export::export_spell(&fireball, vtt_export::VttFormat::Roll20, &upcast);
+	let notes = handout["notes"].as_str().unwrap();
+	assert!(notes.contains("<strong>Range:</strong> 150 feet"));
+	assert!(notes.ends_with(description));
+	// Tables get turned into html tables
+	let chaos_bolt = spells::Spell::from_json_file("spells/xanathars_guide_to_everything/chaos_bolt.json").unwrap();
+	let html = vtt_export::spell_description_html(&chaos_bolt, &upcast);
+	assert!(html.contains("<table><thead><tr><th>"));
+	assert!(!html.contains("[table]"));
+	// Tags turn into html and text gets escaped
+	assert_eq!
+	(
+		vtt_export::rich_text_to_html
+		(
+			"A <b> bold <url=https://example.com> & linked </url> claim <r> in <color=#8b0000> red \\<i> \n- one\n- two",
+			&Vec::new()
+		),
+		"<p>A <strong>bold</strong> <a href=\"https://example.com\"><strong>&amp; linked</strong></a> \
+		<strong>claim</strong> in <span style=\"color:#8b0000\">red &lt;i&gt;</span></p><ul><li>one</li><li>two</li></ul>"
+	);
+	// Whole lists of spells can be exported
+	let spell_list = vec![fireball, chaos_bolt];
+	let export = vtt_export::export_spells(&spell_list, vtt_export::VttFormat::FoundryVtt, &upcast);
+	assert_eq!(export.as_array().unwrap().len(), 2);
+}
+
+// Makes sure spellbooks get turned into LaTeX documents
+#[test]
+fn latex_export()
+{
+	let mut fireball = spells::Spell::from_json_file("spells/players_handbook_2024/fireball.json").unwrap();
+	fireball.description = String::from("Deals 8d6 <b> fire & 50% <r> damage.\n- One_1\n- Two #2");
+	let chaos_bolt = spells::Spell::from_json_file("spells/xanathars_guide_to_everything/chaos_bolt.json").unwrap();
+	let options = LatexOptions { title: String::from("Wizard's Book"), ..Default::default() };
+	let latex = latex_export::render_spellbook_latex(&[fireball, chaos_bolt], &options);
+	assert!(latex.starts_with("\\documentclass[letterpaper,11pt]{book}"));
+	assert!(latex.contains("\\title{Wizard's Book}"));
+	assert!(latex.contains("\\tableofcontents"));
+	assert!(latex.contains("\\begin{spell}{Fireball}{Level 3 Evocation}"));
+	assert!(latex.contains("\\spellstat{Range}{150 feet}"));
+	// Special characters get escaped and tags turn into commands
+	assert!(latex.contains("\\noindent Deals 8d6 \\textbf{fire \\& 50\\%} damage."));
+	assert!(latex.contains("\\begin{itemize}\n\t\\item One\\_1\n\t\\item Two \\#2\n\\end{itemize}"));
+	assert!(latex.contains("\\textbf{\\textit{Using a Higher-Level Spell Slot.}} The damage increases"));
+	// Tables become longtables
+	assert!(latex.contains("\\begin{longtable}"));
+	assert!(!latex.contains("[table][0]"));
+	// Every environment gets closed
+	assert_eq!(latex.matches("\\begin{spell}").count(), 2);
+	assert_eq!(latex.matches("\\begin{").count(), latex.matches("\\end{").count());
+	assert!(latex.trim_end().ends_with("\\end{document}"));
+}
+
+// Makes sure spellbooks get packaged into epub files with a chapter for each spell
+#[cfg(feature = "epub")]
+#[test]
+fn epub_export()
+{
+	use std::io::Read;
+	use crate::epub_export::{self, EpubOptions};
+
+	let spell_list = vec!
+	[
+		spells::Spell::from_json_file("spells/players_handbook_2024/fireball.json").unwrap(),
+		spells::Spell::from_json_file("spells/xanathars_guide_to_everything/chaos_bolt.json").unwrap()
+	];
+	let options = EpubOptions { title: String::from("Tom & Jerry's Spells"), ..Default::default() };
+	let epub = epub_export::render_spellbook_epub(&spell_list, &options).unwrap();
+	// The mimetype file has to be the first file and not be compressed
+	assert_eq!(&epub[30..38], b"mimetype");
+	assert_eq!(&epub[38..58], b"application/epub+zip");
+	let mut archive = zip::ZipArchive::new(std::io::Cursor::new(epub)).unwrap();
+	let mut read_file = |name: &str|
+	{
+		let mut contents = String::new();
+		archive.by_name(name).unwrap().read_to_string(&mut contents).unwrap();
+		contents
+	};
+	assert!(read_file("META-INF/container.xml").contains("OEBPS/content.opf"));
+	let package = read_file("OEBPS/content.opf");
+	assert!(package.contains("<dc:title>Tom &amp; Jerry's Spells</dc:title>"));
+	assert!(regex::Regex::new(r"dcterms:modified.>\d{4}-\d{2}-\d{2}T\d{2}:\d{2}:\d{2}Z<").unwrap().is_match(&package));
+	assert_eq!(package.matches("<itemref").count(), 3);
+	// The navigation document matches the bookmarks of a pdf spellbook
+	let navigation = read_file("OEBPS/nav.xhtml");
+	let entries: Vec<_> = navigation.lines().filter(|line| line.starts_with("<li>")).collect();
+	assert_eq!(entries.len(), 3);
+	assert!(entries[0].contains(">Title Page<"));
+	assert!(entries[1].contains("spells/spell_0001.xhtml\">Fireball<"));
+	assert!(entries[2].contains("spells/spell_0002.xhtml\">Chaos Bolt<"));
+	let chapter = read_file("OEBPS/spells/spell_0002.xhtml");
+	assert!(chapter.contains("<h1>Chaos Bolt</h1>"));
+	assert!(chapter.contains("<table>"));
+	assert!(chapter.contains("href=\"../style.css\""));
+}
+
+// Makes sure fillable checkboxes get added to the interactive form of the document
+#[test]
+fn form_fields()
+{
+	let spell_list = vec!
+	[
+		spells::Spell::from_json_file("spells/players_handbook_2024/fire_bolt.json").unwrap(),
+		spells::Spell::from_json_file("spells/players_handbook_2024/alarm.json").unwrap()
+	];
+	let additional_options = AdditionalOptions
+	{
+		table_of_contents: Some(TableOfContentsOptions::default()),
+		preparation_checkboxes: Some(PreparationCheckboxOptions { in_table_of_contents: true, ..Default::default() }),
+		form_fields: Some(FormFieldOptions
+		{
+			slot_tracker: Some(SlotTrackerOptions { slots_per_level: [2, 1, 0, 0, 0, 0, 0, 0, 0], ..Default::default() }),
+			..Default::default()
+		}),
+		..Default::default()
+	};
+	let bytes = small_spellbook(&spell_list, &additional_options).save_to_bytes().unwrap();
+	let doc = lopdf::Document::load_mem(&bytes).unwrap();
+	let catalog = doc.catalog().unwrap();
+	let form = doc.get_dictionary(catalog.get(b"AcroForm").unwrap().as_reference().unwrap()).unwrap();
+	let fields = form.get(b"Fields").unwrap().as_array().unwrap();
+	// Gets the name of a field and how many widgets it has
+	let field_info = |field: &lopdf::Object|
+	{
+		let field = doc.get_dictionary(field.as_reference().unwrap()).unwrap();
+		let name = String::from_utf8(field.get(b"T").unwrap().as_str().unwrap().to_vec()).unwrap();
+		(name, field.get(b"Kids").unwrap().as_array().unwrap().len())
+	};
+	let fields: Vec<_> = fields.iter().map(field_info).collect();
+	// Slot checkboxes on the title page, then one field per spell shared by the table of contents and the spell page
+	assert_eq!
+	(
+		fields,
+		vec!
+		[
+			(String::from("slot_level_1_1"), 1),
+			(String::from("slot_level_1_2"), 1),
+			(String::from("slot_level_2_1"), 1),
+			(String::from("prepared_1"), 2),
+			(String::from("prepared_2"), 2)
+		]
+	);
+	// Widgets are added to the pages they're on alongside the table of contents links
+	let widget_counts: Vec<_> = get_annotations(&bytes).iter()
+		.map(|annotations| annotations.iter()
+			.filter(|annotation| annotation.get(b"Subtype").unwrap().as_name().unwrap() == b"Widget")
+			.count())
+		.collect();
+	assert_eq!(widget_counts, vec![3, 2, 1, 1]);
+	// Non-fillable checkboxes don't make a form
+	let additional_options = AdditionalOptions { form_fields: None, ..additional_options };
+	let bytes = small_spellbook(&spell_list, &additional_options).save_to_bytes().unwrap();
+	let doc = lopdf::Document::load_mem(&bytes).unwrap();
+	assert!(doc.catalog().unwrap().get(b"AcroForm").is_err());
+}
+
+// Makes sure spellbooks can be split into a chapter for each class
+#[test]
+fn class_chapters()
+{
+	let with_classes = |file_path: &str, classes: &[&str]| spells::Spell
+	{
+		classes: classes.iter().map(|class| String::from(*class)).collect(),
+		..spells::Spell::from_json_file(file_path).unwrap()
+	};
+	let spell_list = vec!
+	[
+		with_classes("spells/players_handbook_2024/fireball.json", &["Wizard", "Sorcerer"]),
+		with_classes("spells/players_handbook_2024/fire_bolt.json", &["sorcerer", "Wizard"]),
+		with_classes("spells/players_handbook_2024/augury.json", &["Cleric"])
+	];
+	// Makes a spellbook with class chapters and returns it along with the page number of each page after the title page
+	let make_spellbook = |layout: ClassChapterLayout|
+	{
+		let pages = Arc::new(Mutex::new(Vec::new()));
+		let hook_pages = pages.clone();
+		let mut additional_options = AdditionalOptions
+		{
+			table_of_contents: Some(TableOfContentsOptions::default()),
+			class_chapters: Some(ClassChapterOptions { layout: layout, ..Default::default() }),
+			..Default::default()
+		};
+		additional_options.page_hooks.on_page_created(move |page| hook_pages.lock().unwrap().push(page.page_number));
+		let bytes = small_spellbook(&spell_list, &additional_options).save_to_bytes().unwrap();
+		let page_numbers = pages.lock().unwrap().clone();
+		(bytes, page_numbers)
+	};
+	// Gets the title of every bookmark in a spellbook in order
+	let bookmark_names = |bytes: &Vec<u8>|
+	{
+		let doc = lopdf::Document::load_mem(bytes).unwrap();
+		let outlines = doc.get_dictionary(doc.catalog().unwrap().get(b"Outlines").unwrap().as_reference().unwrap())
+			.unwrap();
+		let mut names = Vec::new();
+		let mut next = outlines.get(b"First").ok().map(|first| first.as_reference().unwrap());
+		while let Some(id) = next
+		{
+			let bookmark = doc.get_dictionary(id).unwrap();
+			names.push(String::from_utf8_lossy(bookmark.get(b"Title").unwrap().as_str().unwrap()).into_owned());
+			next = bookmark.get(b"Next").ok().map(|next| next.as_reference().unwrap());
+		}
+		names
+	};
+	// Chapters in alphabetical order with their spells sorted by level, repeated in each chapter
+	let (bytes, page_numbers) = make_spellbook(ClassChapterLayout::RepeatSpells);
+	assert_eq!
+	(
+		bookmark_names(&bytes),
+		vec!
+		[
+			"Title Page", "Contents", "Cleric Spells", "Augury", "Sorcerer Spells", "Fire Bolt", "Fireball",
+			"Wizard Spells", "Fire Bolt", "Fireball"
+		]
+	);
+	// Page numbers start over in each chapter
+	assert_eq!(page_numbers.iter().filter(|page_number| **page_number == 1).count(), 4);
+	// The table of contents links to each chapter and each spell in each chapter
+	assert_eq!(get_annotations(&bytes)[1].len(), 8);
+	// Spells only go in the spellbook once with a list of spells after them for each class
+	let (bytes, _) = make_spellbook(ClassChapterLayout::ClassListPages);
+	assert_eq!
+	(
+		bookmark_names(&bytes),
+		vec!
+		[
+			"Title Page", "Contents", "Fireball", "Fire Bolt", "Augury", "Cleric Spells", "Sorcerer Spells",
+			"Wizard Spells"
+		]
+	);
+	// Each class list links to the spells of its class
+	let annotations = get_annotations(&bytes);
+	let list_link_counts: Vec<_> = annotations[annotations.len() - 3..].iter().map(|links| links.len()).collect();
+	assert_eq!(list_link_counts, vec![1, 2, 2]);
+	// Chapters can be chosen and renamed
+	let chapter = ClassChapter::new("wizard");
+	assert_eq!(chapter.title, "wizard Spells");
+	assert_eq!(chapter.page_number_prefix, "W-");
+	let options = ClassChapterOptions { chapters: vec![chapter.clone()], ..Default::default() };
+	assert_eq!(options.chapters_for(&spell_list), vec![chapter]);
+}
+
+// Makes sure spells can be written as a compact list of their stats
+#[test]
+fn compact_list()
+{
+	let mut spell_list = vec!
+	[
+		spells::Spell::from_json_file("spells/players_handbook_2024/fireball.json").unwrap(),
+		spells::Spell::from_json_file("spells/players_handbook_2024/fire_bolt.json").unwrap(),
+		spells::Spell::from_json_file("spells/players_handbook_2024/alarm.json").unwrap(),
+		spells::Spell::from_json_file("spells/xanathars_guide_to_everything/chaos_bolt.json").unwrap()
+	];
+	// Summaries are the first sentence of the first paragraph that isn't a table or bullet point
+	spell_list[0].description = String::from("[table][0]\n- A bullet.\nYou hurl a <b> mote <r> of fire. It explodes.");
+	assert_eq!(spell_list[0].summary(), "You hurl a <b> mote <r> of fire.");
+	spell_list[0].description = String::from("Deals 2.5 times damage");
+	assert_eq!(spell_list[0].summary(), "Deals 2.5 times damage");
+	let additional_options = AdditionalOptions
+	{
+		table_of_contents: Some(TableOfContentsOptions::default()),
+		compact_list: Some(CompactListOptions::default()),
+		..Default::default()
+	};
+	let bytes = small_spellbook(&spell_list, &additional_options).save_to_bytes().unwrap();
+	let doc = lopdf::Document::load_mem(&bytes).unwrap();
+	// Title page, table of contents, and every spell fits on one page
+	assert_eq!(doc.get_pages().len(), 3);
+	let page_ids: Vec<_> = doc.get_pages().into_values().collect();
+	// Every table of contents entry links to the list
+	let annotations = get_annotations(&bytes);
+	assert_eq!(annotations[1].len(), 4);
+	for annotation in &annotations[1]
+	{
+		let destination = annotation.get(b"Dest").unwrap().as_array().unwrap();
+		assert_eq!(destination[0].as_reference().unwrap(), page_ids[2]);
+	}
+	// Tables in descriptions don't get drawn in the list
+	let content = lopdf::content::Content::decode(&doc.get_page_content(page_ids[2]).unwrap()).unwrap();
+	assert_eq!(content.operations.iter().filter(|operation| operation.operator == "S").count(), 0);
+	// Lists still split into more pages when they don't fit
+	let long_list: Vec<_> = spell_list.iter().cycle().take(40).cloned().collect();
+	let additional_options = AdditionalOptions
+	{
+		compact_list: Some(CompactListOptions { column_count: 3, include_summary: false }),
+		..Default::default()
+	};
+	let bytes = small_spellbook(&long_list, &additional_options).save_to_bytes().unwrap();
+	let doc = lopdf::Document::load_mem(&bytes).unwrap();
+	assert!(doc.get_pages().len() > 2);
+	assert!(doc.get_pages().len() < 40);
+}
+
+// Makes sure a tapered rule gets drawn under each spell name when asked to
+#[test]
+fn name_rule()
+{
+	let spell_list = vec!
+	[
+		spells::Spell::from_json_file("spells/players_handbook_2024/fire_bolt.json").unwrap(),
+		spells::Spell::from_json_file("spells/players_handbook_2024/alarm.json").unwrap()
+	];
+	// Gets the fill operations and fill colors of each page of a spellbook
+	let fills = |name_rule: Option<NameRuleOptions>|
+	{
+		let additional_options = AdditionalOptions { name_rule: name_rule, ..Default::default() };
+		page_fills(&small_spellbook(&spell_list, &additional_options).save_to_bytes().unwrap())
+	};
+	let without_rule = fills(None);
+	let with_rule = fills(Some(NameRuleOptions { color: (255, 0, 0), ..Default::default() }));
+	// One filled shape in the rule's color on each spell page and nothing on the title page
+	assert_eq!(with_rule[0], without_rule[0]);
+	for page in 1..3
+	{
+		assert_eq!(with_rule[page].0, without_rule[page].0 + 1);
+		assert!(with_rule[page].1.contains(&vec![1.0, 0.0, 0.0]));
+		assert!(!without_rule[page].1.contains(&vec![1.0, 0.0, 0.0]));
+	}
+}
+
+// Makes sure a bar gets drawn behind each spell name and the name gets a color that shows up on it
+#[test]
+fn name_bar()
+{
+	let spell_list = vec!
+	[
+		spells::Spell::from_json_file("spells/players_handbook_2024/fire_bolt.json").unwrap(),
+		spells::Spell::from_json_file("spells/players_handbook_2024/alarm.json").unwrap()
+	];
+	// Gets the fill operations and fill colors of each page of a spellbook
+	let fills = |name_bar: Option<NameBarOptions>|
+	{
+		let additional_options = AdditionalOptions { name_bar: name_bar, ..Default::default() };
+		page_fills(&small_spellbook(&spell_list, &additional_options).save_to_bytes().unwrap())
+	};
+	let white = vec![1.0, 1.0, 1.0];
+	let without_bar = fills(None);
+	let dark_bar = fills(Some(NameBarOptions { color: (0, 0, 255), ..Default::default() }));
+	// One filled bar on each spell page with the name switched to white on top of it
+	assert_eq!(dark_bar[0], without_bar[0]);
+	for page in 1..3
+	{
+		assert_eq!(dark_bar[page].0, without_bar[page].0 + 1);
+		let bar_index = dark_bar[page].1.iter().position(|color| *color == vec![0.0, 0.0, 1.0]).unwrap();
+		let name_index = dark_bar[page].1.iter().position(|color| *color == white).unwrap();
+		assert!(bar_index < name_index);
+		assert!(!without_bar[page].1.contains(&white));
+	}
+	// Names that already show up on the bar keep their color
+	let light_bar = fills(Some(NameBarOptions { color: (255, 255, 0), ..Default::default() }));
+	assert!(!light_bar[1].1.contains(&white));
+	// Gradients are made of bands that go from one color to the other
+	let gradient_bar = NameBarOptions { color: (0, 0, 0), gradient_color: Some((0, 0, 255)), ..Default::default() };
+	let gradient_bar = fills(Some(gradient_bar));
+	assert_eq!(gradient_bar[1].0, without_bar[1].0 + 64);
+	assert!(gradient_bar[1].1.contains(&white));
+	// The text color can be picked instead
+	let red_text = fills(Some(NameBarOptions { text_color: Some((255, 0, 0)), ..Default::default() }));
+	assert!(red_text[1].1.contains(&vec![1.0, 0.0, 0.0]));
+}
+
+// Makes sure flavor quotes get split from who said them and take up space between the fields and the description
+#[test]
+fn flavor_quote()
+{
+	let mut spell = spells::Spell::from_json_file("spells/players_handbook_2024/fire_bolt.json").unwrap();
+	assert_eq!(spell.flavor_quote(), None);
+	spell.flavor = Some(String::from("Fire is a good servant.\n— Mordenkainen"));
+	assert_eq!(spell.flavor_quote(), Some(("Fire is a good servant.", Some("Mordenkainen"))));
+	spell.flavor = Some(String::from("Fire is a good servant.\n--Elminster\n"));
+	assert_eq!(spell.flavor_quote(), Some(("Fire is a good servant.", Some("Elminster"))));
+	// A dash at the start of a quote without a line before it isn't an attribution
+	spell.flavor = Some(String::from("— Not an attribution"));
+	assert_eq!(spell.flavor_quote(), Some(("— Not an attribution", None)));
+	// Gets the number of pages and text operations on the first spell page of a spellbook with a flavor quote
+	let counts = |flavor: Option<String>|
+	{
+		let mut spell = spell.clone();
+		spell.flavor = flavor;
+		let bytes = small_spellbook(&vec![spell], &AdditionalOptions::default()).save_to_bytes().unwrap();
+		let doc = lopdf::Document::load_mem(&bytes).unwrap();
+		let page_ids: Vec<_> = doc.get_pages().into_values().collect();
+		let content = lopdf::content::Content::decode(&doc.get_page_content(page_ids[1]).unwrap()).unwrap();
+		let text_count = content.operations.iter()
+			.filter(|operation| operation.operator == "Tj" || operation.operator == "TJ")
+			.count();
+		(page_ids.len(), text_count)
+	};
+	let (plain_page_count, plain_text_count) = counts(None);
+	let (page_count, text_count) = counts(Some(String::from("Fire is a good servant.\n— Mordenkainen")));
+	assert_eq!(page_count, plain_page_count);
+	assert!(text_count >= plain_text_count + 2);
+	// Long quotes push the description onto more pages
+	let (long_page_count, _) = counts(Some(vec!["Fire is a good servant."; 80].join("\n")));
+	assert!(long_page_count > plain_page_count);
+}
+
+// Makes sure comparison tables get a column for each spell and a row for each field and can go in spellbooks
+#[test]
+fn comparison_table()
+{
+	let fire_bolt = spells::Spell::from_json_file("spells/players_handbook_2024/fire_bolt.json").unwrap();
+	let mut alarm = spells::Spell::from_json_file("spells/players_handbook_2024/alarm.json").unwrap();
+	alarm.name = String::from("<b> Alarm");
+	alarm.source = Some(String::from("PHB"));
+	let fields =
+	[
+		spells::ComparisonField::Level,
+		spells::ComparisonField::Ritual,
+		spells::ComparisonField::Source
+	];
+	let table = spells::comparison_table(&[&fire_bolt, &alarm], &fields);
+	assert_eq!(table.title, "");
+	// Spell names get escaped so they show up as they are
+	assert_eq!(table.column_labels, vec!["", "Fire Bolt", "\\<b> Alarm"]);
+	assert_eq!(table.cells, vec!
+	[
+		vec!["Level", "Cantrip", "Level 1"],
+		vec!["Ritual", "No", "Yes"],
+		vec!["Source", "\u{2014}", "PHB"]
+	]);
+	// The table can go in a spellbook like any other table
+	let mut page = fire_bolt.clone();
+	page.name = String::from("Comparison");
+	page.description = String::from("[table][0]");
+	page.upcast_description = None;
+	page.tables = vec![table];
+	let bytes = small_spellbook(&vec![page], &AdditionalOptions::default()).save_to_bytes().unwrap();
+	assert_eq!(lopdf::Document::load_mem(&bytes).unwrap().get_pages().len(), 2);
+}
+
+// Makes sure pages after the first page of a spell get a footer with the spell's level and its icons
+#[test]
+fn continuation_footer()
+{
+	let mut spell = spells::Spell::from_json_file("spells/players_handbook_2024/fire_bolt.json").unwrap();
+	spell.description = vec!["Alpha."; 80].join("\n");
+	spell.duration = spells::SpellField::Controlled(spells::Duration::Minutes(1, true));
+	spell.is_ritual = true;
+	assert!(spell.requires_concentration());
+	// Gets the number of text operations and lines on each page of a spellbook
+	let counts = |continuation_footer: Option<ContinuationFooterOptions>|
+	{
+		let additional_options = AdditionalOptions { continuation_footer: continuation_footer, ..Default::default() };
+		let bytes = small_spellbook(&vec![spell.clone()], &additional_options).save_to_bytes().unwrap();
+		let doc = lopdf::Document::load_mem(&bytes).unwrap();
+		doc.get_pages().into_values()
+			.map(|page_id| lopdf::content::Content::decode(&doc.get_page_content(page_id).unwrap()).unwrap())
+			.map(|content|
+			{
+				let operation_count = |operators: &[&str]| content.operations.iter()
+					.filter(|operation| operators.contains(&operation.operator.as_str()))
+					.count();
+				(operation_count(&["Tj", "TJ"]), operation_count(&["S", "s"]))
+			})
+			.collect::<Vec<_>>()
+	};
+	let without_footer = counts(None);
+	let with_footer = counts(Some(ContinuationFooterOptions::default()));
+	assert!(with_footer.len() > 2);
+	// Nothing changes on the title page or the first page of the spell
+	assert_eq!(with_footer[..2], without_footer[..2]);
+	// The level and the letters of the two icons with a circle around each letter
+	for (with, without) in with_footer.iter().zip(&without_footer).skip(2)
+	{
+		assert_eq!(with.0, without.0 + 3);
+		assert_eq!(with.1, without.1 + 2);
+	}
+}
+
+// Makes sure the quick stats grid shades and outlines a row for each spell field
+#[test]
+fn quick_stats()
+{
+	let spell_list = vec!
+	[
+		spells::Spell::from_json_file("spells/players_handbook_2024/fire_bolt.json").unwrap(),
+		spells::Spell::from_json_file("spells/players_handbook_2024/alarm.json").unwrap()
+	];
+	// Gets the fill operations, fill colors, and outline colors of each page of a spellbook
+	let shapes = |quick_stats: Option<QuickStatsOptions>|
+	{
+		let additional_options = AdditionalOptions { quick_stats: quick_stats, ..Default::default() };
+		let bytes = small_spellbook(&spell_list, &additional_options).save_to_bytes().unwrap();
+		let doc = lopdf::Document::load_mem(&bytes).unwrap();
+		doc.get_pages().into_values()
+			.map(|page_id| lopdf::content::Content::decode(&doc.get_page_content(page_id).unwrap()).unwrap())
+			.map(|content|
+			{
+				let colors = |operator: &str| -> Vec<Vec<f32>>
+				{
+					content.operations.iter()
+						.filter(|operation| operation.operator == operator)
+						.map(|operation| operation.operands.iter().map(|operand| operand.as_float().unwrap()).collect())
+						.collect()
+				};
+				let fill_count = content.operations.iter().filter(|operation| operation.operator == "f").count();
+				(fill_count, colors("rg"), colors("RG"))
+			})
+			.collect::<Vec<_>>()
+	};
+	let without_grid = shapes(None);
+	let options = QuickStatsOptions
+	{
+		line_color: (255, 0, 0),
+		label_fill_color: Some((0, 0, 255)),
+		..Default::default()
+	};
+	let with_grid = shapes(Some(options));
+	// The spells still take up the same pages
+	assert_eq!(with_grid.len(), without_grid.len());
+	assert_eq!(with_grid[0], without_grid[0]);
+	for page in 1..3
+	{
+		// A shaded name cell and an outlined row for each of the four fields
+		assert_eq!(with_grid[page].0, without_grid[page].0 + 4);
+		assert_eq!(with_grid[page].1.iter().filter(|color| **color == vec![0.0, 0.0, 1.0]).count(), 4);
+		assert_eq!(with_grid[page].2.iter().filter(|color| **color == vec![1.0, 0.0, 0.0]).count(), 4);
+		assert!(!without_grid[page].2.contains(&vec![1.0, 0.0, 0.0]));
+	}
+}
+
+// Makes sure spells get a colored chip for each of their classes and spells without classes don't get any
+#[test]
+fn class_chips()
+{
+	let mut spell_list = vec!
+	[
+		spells::Spell::from_json_file("spells/players_handbook_2024/fire_bolt.json").unwrap(),
+		spells::Spell::from_json_file("spells/players_handbook_2024/alarm.json").unwrap()
+	];
+	spell_list[0].classes = vec![String::from("sorcerer"), String::from("Wizard"), String::from("Hexblade")];
+	// Gets the fill operations and fill colors of each page of a spellbook
+	let fills = |class_chips: Option<ClassChipOptions>|
+	{
+		let additional_options = AdditionalOptions { class_chips: class_chips, ..Default::default() };
+		page_fills(&small_spellbook(&spell_list, &additional_options).save_to_bytes().unwrap())
+	};
+	let without_chips = fills(None);
+	let options = ClassChipOptions { default_color: (0, 255, 0), ..Default::default() };
+	let with_chips = fills(Some(options));
+	// The spells still take up the same pages
+	assert_eq!(with_chips.len(), without_chips.len());
+	// A chip for each class with the color of its class (or the default color for classes without one)
+	assert_eq!(with_chips[1].0, without_chips[1].0 + 3);
+	let color = |(r, g, b): (u8, u8, u8)| vec![r as f32 / 255.0, g as f32 / 255.0, b as f32 / 255.0];
+	let chip_colors = ClassChipOptions::default().colors;
+	for chip_color in [chip_colors["Sorcerer"], chip_colors["Wizard"], (0, 255, 0)]
+	{
+		assert!(with_chips[1].1.iter().any(|fill_color| fill_color
+			.iter()
+			.zip(color(chip_color))
+			.all(|(a, b)| (a - b).abs() < 0.01)));
+	}
+	// Alarm doesn't have any classes
+	assert_eq!(with_chips[2], without_chips[2]);
+}
+
+// Makes sure the title gets outlined in the outline color when asked to and nothing else does
+#[test]
+fn title_outline()
+{
+	let spell_list = vec![spells::Spell::from_json_file("spells/players_handbook_2024/fire_bolt.json").unwrap()];
+	// Gets the text rendering modes and stroke colors of each page of a spellbook
+	let strokes = |title_outline: Option<TitleOutlineOptions>|
+	{
+		let additional_options = AdditionalOptions { title_outline: title_outline, ..Default::default() };
+		let bytes = small_spellbook(&spell_list, &additional_options).save_to_bytes().unwrap();
+		let doc = lopdf::Document::load_mem(&bytes).unwrap();
+		doc.get_pages().into_values()
+			.map(|page_id| lopdf::content::Content::decode(&doc.get_page_content(page_id).unwrap()).unwrap())
+			.map(|content|
+			{
+				let operands = |operator: &str| -> Vec<Vec<f32>>
+				{
+					content.operations.iter()
+						.filter(|operation| operation.operator == operator)
+						.map(|operation| operation.operands.iter().map(|operand| operand.as_float().unwrap()).collect())
+						.collect()
+				};
+				(operands("Tr"), operands("RG"))
+			})
+			.collect::<Vec<_>>()
+	};
+	let without_outline = strokes(None);
+	let filled = strokes(Some(TitleOutlineOptions { color: (255, 0, 0), ..Default::default() }));
+	let hollow = strokes(Some(TitleOutlineOptions { color: (255, 0, 0), filled: false, ..Default::default() }));
+	// The title page gets filled and stroked text (mode 2) or only stroked text (mode 1) in the outline color
+	assert!(!without_outline[0].1.contains(&vec![1.0, 0.0, 0.0]));
+	assert!(filled[0].0.contains(&vec![2.0]));
+	assert!(filled[0].1.contains(&vec![1.0, 0.0, 0.0]));
+	assert!(hollow[0].0.contains(&vec![1.0]));
+	assert!(hollow[0].1.contains(&vec![1.0, 0.0, 0.0]));
+	// The spell pages stay the same
+	assert_eq!(filled[1], without_outline[1]);
+	assert_eq!(hollow[1], without_outline[1]);
+}
+
+// Makes sure a narrow page with the title written sideways on it gets added to the end when asked to
+#[test]
+fn spine_page()
+{
+	let spell_list = vec![spells::Spell::from_json_file("spells/players_handbook_2024/fire_bolt.json").unwrap()];
+	// Gets the width of each page and the text matrices on the last page of a spellbook
+	let pages = |spine: Option<SpineOptions>|
+	{
+		let additional_options = AdditionalOptions { spine: spine, ..Default::default() };
+		let bytes = small_spellbook(&spell_list, &additional_options).save_to_bytes().unwrap();
+		let doc = lopdf::Document::load_mem(&bytes).unwrap();
+		let page_ids: Vec<_> = doc.get_pages().into_values().collect();
+		let widths: Vec<f32> = page_ids.iter()
+			.map(|page_id|
+			{
+				let media_box = doc.get_dictionary(*page_id).unwrap().get(b"MediaBox").unwrap().as_array().unwrap();
+				media_box[2].as_float().unwrap()
+			})
+			.collect();
+		let last_page_content = doc.get_page_content(*page_ids.last().unwrap()).unwrap();
+		let content = lopdf::content::Content::decode(&last_page_content).unwrap();
+		let matrices: Vec<Vec<f32>> = content.operations.iter()
+			.filter(|operation| operation.operator == "Tm")
+			.map(|operation| operation.operands.iter().map(|operand| operand.as_float().unwrap()).collect())
+			.collect();
+		(widths, matrices)
+	};
+	let (widths, _) = pages(None);
+	let (spine_widths, spine_matrices) = pages(Some(SpineOptions::default()));
+	let (_, bottom_to_top_matrices) =
+		pages(Some(SpineOptions { direction: SpineDirection::BottomToTop, ..Default::default() }));
+	// One more page that's as wide as the spine with the title rotated to read down the spine (or up it)
+	assert_eq!(spine_widths.len(), widths.len() + 1);
+	assert_eq!(spine_widths[..widths.len()], widths[..]);
+	assert!((spine_widths[widths.len()] - printpdf::Pt::from(Mm(20.0)).0).abs() < 0.01);
+	assert_eq!(spine_matrices.len(), 1);
+	assert_eq!(spine_matrices[0][..4], [0.0, -1.0, 1.0, 0.0]);
+	assert_eq!(bottom_to_top_matrices[0][..4], [0.0, 1.0, -1.0, 0.0]);
+}
+
+// Makes sure cover wraps are one page as wide as both covers, the spine, and the bleed with the artwork and title on it
+#[test]
+fn cover_wrap()
+{
+	let folder = std::env::temp_dir().join(format!("dnd_spellbook_maker_cover_test_{}", std::process::id()));
+	fs::create_dir_all(&folder).unwrap();
+	let art_path = folder.join("front.png");
+	image::RgbImage::from_pixel(30, 40, image::Rgb([120, 40, 30])).save(&art_path).unwrap();
+	let (font_paths, font_sizes, font_scalars, spacing_options, text_colors, page_size_options, _, _, _, table_options) =
+	default_spellbook_options();
+	let cover_options = CoverWrapOptions
+	{
+		spine: SpineOptions { width: 15.0, background_color: Some((40, 20, 10)), ..Default::default() },
+		front_image: Some(String::from(art_path.to_str().unwrap())),
+		back_text: String::from("A collection of spells for testing cover wraps."),
+		..Default::default()
+	};
+	let doc = create_cover_wrap
+	(
+		"Cover Spellbook",
+		font_paths,
+		font_sizes,
+		font_scalars,
+		spacing_options,
+		text_colors,
+		page_size_options,
+		table_options,
+		&AdditionalOptions::default(),
+		&cover_options
+	).unwrap();
+	let doc = lopdf::Document::load_mem(&doc.save_to_bytes().unwrap()).unwrap();
+	fs::remove_dir_all(&folder).unwrap();
+	let page_ids: Vec<_> = doc.get_pages().into_values().collect();
+	assert_eq!(page_ids.len(), 1);
+	let media_box = doc.get_dictionary(page_ids[0]).unwrap().get(b"MediaBox").unwrap().as_array().unwrap();
+	let width = page_size_options.width() * 2.0 + 15.0 + 3.175 * 2.0;
+	let height = page_size_options.height() + 3.175 * 2.0;
+	assert!((media_box[2].as_float().unwrap() - printpdf::Pt::from(Mm(width)).0).abs() < 0.01);
+	assert!((media_box[3].as_float().unwrap() - printpdf::Pt::from(Mm(height)).0).abs() < 0.01);
+	let content = lopdf::content::Content::decode(&doc.get_page_content(page_ids[0]).unwrap()).unwrap();
+	let operation_count = |operator: &str| content.operations.iter()
+		.filter(|operation| operation.operator == operator)
+		.count();
+	// The front artwork, the rotated title on the spine, and the title and back text in their own text blocks
+	assert_eq!(operation_count("Do"), 1);
+	assert!(content.operations.iter().any(|operation| operation.operator == "Tm" &&
+		operation.operands[..4].iter().map(|operand| operand.as_float().unwrap()).eq([0.0, -1.0, 1.0, 0.0])));
+	assert!(operation_count("Tj") + operation_count("TJ") >= 3);
+}
+
+// Makes sure QR codes get drawn on the title page and next to spell names as links to their urls
+#[cfg(feature = "qr")]
+#[test]
+fn qr_codes()
+{
+	let spell_list = vec!
+	[
+		spells::Spell::from_json_file("spells/players_handbook_2024/fire_bolt.json").unwrap(),
+		spells::Spell::from_json_file("spells/players_handbook_2024/alarm.json").unwrap()
+	];
+	let additional_options = AdditionalOptions
+	{
+		qr_codes: Some(QrCodeOptions
+		{
+			spell_url_template: Some(String::from("https://example.com/spells/{slug}?name={name}")),
+			book_url_template: Some(String::from("https://example.com/books/{title}")),
+			..Default::default()
+		}),
+		..Default::default()
+	};
+	let bytes = small_spellbook(&spell_list, &additional_options).save_to_bytes().unwrap();
+	// Gets the urls that each page links to
+	let page_urls: Vec<Vec<Vec<u8>>> = get_annotations(&bytes).iter()
+		.map(|annotations| annotations.iter()
+			.filter_map(|annotation| annotation.get(b"A").ok())
+			.map(|action| action.as_dict().unwrap().get(b"URI").unwrap().as_str().unwrap().to_vec())
+			.collect())
+		.collect();
+	assert_eq!(page_urls[0], vec![b"https://example.com/books/Small%20Spellbook".to_vec()]);
+	assert_eq!(page_urls[1], vec![b"https://example.com/spells/fire-bolt?name=Fire%20Bolt".to_vec()]);
+	assert_eq!(page_urls[2], vec![b"https://example.com/spells/alarm?name=Alarm".to_vec()]);
+}
+
+// Makes sure asking for QR codes without the `qr` feature is an error instead of leaving them out
+#[cfg(not(feature = "qr"))]
+#[test]
+fn qr_codes_need_feature()
+{
+	let spell_list = vec![spells::Spell::from_json_file("spells/players_handbook_2024/fire_bolt.json").unwrap()];
+	let qr_codes = QrCodeOptions
+	{
+		spell_url_template: Some(String::from("https://example.com/spells/{slug}")),
+		..Default::default()
+	};
+	let
+	(
+		font_paths,
+		font_sizes,
+		font_scalars,
+		spacing_options,
+		text_colors,
+		page_size_options,
+		page_number_options,
+		_,
+		_,
+		table_options
+	) = default_spellbook_options();
+	let result = create_spellbook_with_options
+	(
+		"Small Spellbook",
+		&spell_list,
+		font_paths,
+		font_sizes,
+		font_scalars,
+		spacing_options,
+		text_colors,
+		page_size_options,
+		Some(page_number_options),
+		None,
+		table_options,
+		&AdditionalOptions { qr_codes: Some(qr_codes), ..Default::default() }
+	);
+	let error = result.err().unwrap();
+	assert!(error.downcast_ref::<QrCodeError>().is_some());
+}
+
+// Makes sure title text and spell names use the display font when there is one
+#[test]
+fn display_font()
+{
+	let spell_list = vec![spells::Spell::from_json_file("spells/players_handbook_2024/fire_bolt.json").unwrap()];
+	// Gets the names of the fonts used on each page of a spellbook
+	let page_fonts = |display: Option<DisplayFontPaths>|
+	{
+		let (mut font_paths, font_sizes, font_scalars, spacing_options, text_colors, page_size_options, _, _, _,
+			table_options) = default_spellbook_options();
+		font_paths.display = display;
+		let bytes = create_spellbook_with_options
+		(
+			"Display Font Spellbook",
+			&spell_list,
+			font_paths,
+			font_sizes,
+			font_scalars,
+			spacing_options,
+			text_colors,
+			page_size_options,
+			None,
+			None,
+			table_options,
+			&AdditionalOptions::default()
+		).unwrap().save_to_bytes().unwrap();
+		let doc = lopdf::Document::load_mem(&bytes).unwrap();
+		doc.get_pages().into_values()
+			.map(|page_id| lopdf::content::Content::decode(&doc.get_page_content(page_id).unwrap()).unwrap())
+			.map(|content| content.operations.iter()
+				.filter(|operation| operation.operator == "Tf")
+				.map(|operation| operation.operands[0].as_name_str().unwrap().to_string())
+				.collect::<Vec<_>>())
+			.collect::<Vec<_>>()
+	};
+	let main_fonts = page_fonts(None);
+	// Use the bold font as the display font's regular variant so it's different from the main regular font
+	let display_fonts = page_fonts(Some(DisplayFontPaths
+	{
+		regular: String::from("fonts/TeX-Gyre-Bonum/TeX-Gyre-Bonum-Bold.otf"),
+		bold: String::from("fonts/TeX-Gyre-Bonum/TeX-Gyre-Bonum-Bold.otf"),
+		italic: String::from("fonts/TeX-Gyre-Bonum/TeX-Gyre-Bonum-Italic.otf"),
+		bold_italic: String::from("fonts/TeX-Gyre-Bonum/TeX-Gyre-Bonum-BoldItalic.otf"),
+		scalars: FontScalars::new(0.51, 0.51, 0.48, 0.515).unwrap(),
+		variable_axes: None
+	}));
+	// The title page and the spell's name use a font that the main font's text doesn't use
+	let main_spell_fonts: std::collections::HashSet<_> = main_fonts[1].iter().collect();
+	assert!(!main_spell_fonts.contains(&display_fonts[0][0]));
+	assert!(!main_spell_fonts.contains(&display_fonts[1][0]));
+	assert_eq!(display_fonts[0][0], display_fonts[1][0]);
+	// The rest of the spell uses the same fonts as before
+	assert_eq!(display_fonts[1][1..], main_fonts[1][1..]);
+}
+
+// Makes sure variable fonts get instanced at the axis values of each font variant
+#[cfg(feature = "variable-fonts")]
+#[test]
+fn variable_fonts()
+{
+	const VARIABLE_FONT: &str = "fonts/Cantarell/Cantarell-VF.otf";
+	let weight = |value: f32| vec![(String::from("wght"), value)];
+	// The instanced font is a static font with the same outlines as the variable font at that weight
+	let instanced = load_font_bytes(VARIABLE_FONT, Some(&weight(700.0))).unwrap();
+	let instanced_face = ttf_parser::Face::parse(&instanced, 0).unwrap();
+	assert!(!instanced_face.is_variable());
+	let variable_bytes = fs::read(VARIABLE_FONT).unwrap();
+	let mut variable_face = ttf_parser::Face::parse(&variable_bytes, 0).unwrap();
+	variable_face.set_variation(ttf_parser::Tag::from_bytes(b"wght"), 700.0).unwrap();
+	for c in ['H', 'o', 'g', '&']
+	{
+		let glyph_id = variable_face.glyph_index(c).unwrap();
+		assert_eq!(instanced_face.glyph_index(c), Some(glyph_id));
+		assert_eq!(instanced_face.glyph_hor_advance(glyph_id), variable_face.glyph_hor_advance(glyph_id));
+		let instanced_box = instanced_face.glyph_bounding_box(glyph_id).unwrap();
+		let variable_box = variable_face.glyph_bounding_box(glyph_id).unwrap();
+		assert!((instanced_box.x_min - variable_box.x_min).abs() <= 2);
+		assert!((instanced_box.y_min - variable_box.y_min).abs() <= 2);
+		assert!((instanced_box.x_max - variable_box.x_max).abs() <= 2);
+		assert!((instanced_box.y_max - variable_box.y_max).abs() <= 2);
+	}
+	// Axes the font doesn't have and fonts that aren't variable can't be instanced
+	assert!(load_font_bytes(VARIABLE_FONT, Some(&vec![(String::from("wdth"), 75.0)])).is_err());
+	assert!(load_font_bytes("fonts/TeX-Gyre-Bonum/TeX-Gyre-Bonum-Bold.otf", Some(&weight(700.0))).is_err());
+	// Each font variant gets its own weight
+	let axes = VariableFontAxes
+	{
+		regular: weight(400.0),
+		bold: weight(700.0),
+		italic: weight(300.0),
+		bold_italic: weight(800.0)
+	};
+	let font_paths = FontPaths::variable(VARIABLE_FONT, axes);
+	let font_scalars = FontScalars::new(0.5, 0.5, 0.5, 0.5).unwrap();
+	let measurer = layout::FontMeasurer::new(&font_paths, 12.0, font_scalars).unwrap();
+	let width = |font_variant: FontVariant| layout::TextMeasurer::text_width(&measurer, "Fireball", font_variant);
+	assert!(width(FontVariant::Italic) < width(FontVariant::Regular));
+	assert!(width(FontVariant::Regular) < width(FontVariant::Bold));
+	assert!(width(FontVariant::Bold) < width(FontVariant::BoldItalic));
+	// Spellbooks can be made with the instanced fonts
+	let (_, font_sizes, _, spacing_options, text_colors, page_size_options, page_number_options, _, _,
+		table_options) = default_spellbook_options();
+	let spell_list = vec![spells::Spell::from_json_file("spells/players_handbook_2024/fireball.json").unwrap()];
+	let bytes = create_spellbook_with_options
+	(
+		"Variable Font Spellbook",
+		&spell_list,
+		font_paths,
+		font_sizes,
+		font_scalars,
+		spacing_options,
+		text_colors,
+		page_size_options,
+		Some(page_number_options),
+		None,
+		table_options,
+		&AdditionalOptions::default()
+	).unwrap().save_to_bytes().unwrap();
+	assert_eq!(lopdf::Document::load_mem(&bytes).unwrap().get_pages().len(), 2);
+}
+
+// Makes sure font variants without their own font files get synthesized from the regular font
+#[test]
+fn synthetic_font_variants()
+{
+	let (mut font_paths, font_sizes, font_scalars, spacing_options, text_colors, page_size_options, _, _, _,
+		table_options) = default_spellbook_options();
+	font_paths.bold = String::new();
+	font_paths.italic = String::new();
+	font_paths.bold_italic = String::new();
+	// Synthetic bold text is wider than regular text by the stroke around each character
+	let measurer = layout::FontMeasurer::new(&font_paths, font_sizes.body_font_size(), font_scalars).unwrap();
+	let width = |font_variant: FontVariant| layout::TextMeasurer::text_width(&measurer, "Fire Bolt", font_variant);
+	let stroke_width: Mm = printpdf::Pt(0.03 * font_sizes.body_font_size()).into();
+	assert!((width(FontVariant::Bold) - width(FontVariant::Regular) - 9.0 * stroke_width.0).abs() < 0.0001);
+	assert_eq!(width(FontVariant::Italic), width(FontVariant::Regular));
+	assert_eq!(width(FontVariant::BoldItalic), width(FontVariant::Bold));
+	// Spellbooks only embed the regular font and fake the other variants
+	let spell_list = vec![spells::Spell::from_json_file("spells/players_handbook_2024/fire_bolt.json").unwrap()];
+	let bytes = create_spellbook_with_options
+	(
+		"Synthetic Font Spellbook",
+		&spell_list,
+		font_paths,
+		font_sizes,
+		font_scalars,
+		spacing_options,
+		text_colors,
+		page_size_options,
+		None,
+		None,
+		table_options,
+		&AdditionalOptions::default()
+	).unwrap().save_to_bytes().unwrap();
+	let doc = lopdf::Document::load_mem(&bytes).unwrap();
+	let font_files = doc.objects.values()
+		.filter_map(|object| object.as_dict().ok())
+		.filter(|dict| dict.has(b"FontFile2") || dict.has(b"FontFile3"))
+		.count();
+	assert_eq!(font_files, 1);
+	let page_id = doc.get_pages()[&2];
+	let content = lopdf::content::Content::decode(&doc.get_page_content(page_id).unwrap()).unwrap();
+	let operations_named = |operator: &str|
+		content.operations.iter().filter(|operation| operation.operator == operator).collect::<Vec<_>>();
+	// Bold text (the spell's stat labels) gets filled and stroked with its characters spaced out
+	assert!(operations_named("Tr").iter().any(|operation| operation.operands[0].as_i64().unwrap() == 2));
+	assert!(!operations_named("Tc").is_empty());
+	// Italic text (the spell's level and school) gets skewed
+	let is_skewed = |operation: &&lopdf::content::Operation|
+		(operation.operands[2].as_float().unwrap() - 0.21).abs() < 0.001;
+	assert!(operations_named("Tm").iter().any(is_skewed));
+}
+
+// Makes sure that kerning changes how wide text is measured and gets written with TJ operations
+#[test]
+fn kerning()
+{
+	let (font_paths, font_sizes, font_scalars, spacing_options, text_colors, page_size_options, _, _, _,
+		table_options) = default_spellbook_options();
+	// Letter pairs like "AV" and "To" get moved closer together with kerning
+	let size = font_sizes.body_font_size();
+	let plain = layout::FontMeasurer::new(&font_paths, size, font_scalars).unwrap();
+	let kerned = layout::FontMeasurer::with_kerning(&font_paths, size, font_scalars).unwrap();
+	let width = |measurer: &layout::FontMeasurer, text: &str|
+		layout::TextMeasurer::text_width(measurer, text, FontVariant::Regular);
+	assert!(width(&kerned, "AV") < width(&plain, "AV"));
+	assert!(width(&kerned, "To") < width(&plain, "To"));
+	// Single characters don't have any pairs to kern
+	assert_eq!(width(&kerned, "A"), width(&plain, "A"));
+	// Spellbooks only use TJ operations (with kerning adjustments between glyphs) when kerning is turned on
+	let spell_list = vec![spells::Spell::from_json_file("spells/players_handbook_2024/fire_bolt.json").unwrap()];
+	let kerning_adjustments = |kerning: bool|
+	{
+		let additional_options = AdditionalOptions { kerning: kerning, ..Default::default() };
+		let bytes = create_spellbook_with_options
+		(
+			"Kerning Spellbook",
+			&spell_list,
+			font_paths.clone(),
+			font_sizes,
+			font_scalars,
+			spacing_options,
+			text_colors,
+			page_size_options,
+			None,
+			None,
+			table_options,
+			&additional_options
+		).unwrap().save_to_bytes().unwrap();
+		let doc = lopdf::Document::load_mem(&bytes).unwrap();
+		let page_id = doc.get_pages()[&2];
+		let content = lopdf::content::Content::decode(&doc.get_page_content(page_id).unwrap()).unwrap();
+		let tj_count = content.operations.iter().filter(|operation| operation.operator == "TJ").count();
+		let adjustments: Vec<i64> = content.operations.iter()
+			.filter(|operation| operation.operator == "TJ")
+			.flat_map(|operation| operation.operands[0].as_array().unwrap().iter())
+			.filter_map(|operand| operand.as_i64().ok())
+			.collect();
+		(tj_count, adjustments)
+	};
+	let (tj_count, adjustments) = kerning_adjustments(false);
+	assert_eq!(tj_count, 0);
+	assert!(adjustments.is_empty());
+	let (tj_count, adjustments) = kerning_adjustments(true);
+	assert!(tj_count > 0);
+	assert!(adjustments.iter().any(|adjustment| *adjustment > 0));
+}
+
+// Makes sure that standard ligatures replace the glyphs they're made of when measuring and writing text
+#[test]
+fn ligatures()
+{
+	let (font_paths, font_sizes, font_scalars, spacing_options, text_colors, page_size_options, _, _, _,
+		table_options) = default_spellbook_options();
+	// "fi" and "fl" each become a single glyph with ligatures
+	let font_bytes = crate::utils::load_font_bytes(&font_paths.regular, None).unwrap();
+	let font = rusttype::Font::try_from_vec(font_bytes.clone()).unwrap();
+	let ligature_table = crate::ligatures::LigatureTable::from_font_bytes(&font_bytes);
+	let glyphs = crate::spellbook_gen_types::text_glyphs("fire flame", &font, Some(&ligature_table));
+	assert_eq!(glyphs.len(), 8);
+	assert_eq!(glyphs[0].1, 2);
+	assert_eq!(glyphs[4].1, 2);
+	assert_eq!(crate::spellbook_gen_types::text_glyphs("fire flame", &font, None).len(), 10);
+	// Text gets measured with the ligature glyphs instead of the glyphs they replace
+	let size = font_sizes.body_font_size();
+	let plain = layout::FontMeasurer::new(&font_paths, size, font_scalars).unwrap();
+	let ligated = layout::FontMeasurer::with_font_features(&font_paths, size, font_scalars, false, true, false).unwrap();
+	let width = |measurer: &layout::FontMeasurer, text: &str|
+		layout::TextMeasurer::text_width(measurer, text, FontVariant::Regular);
+	assert_eq!(width(&ligated, "Bolt"), width(&plain, "Bolt"));
+	// Ligatures in spellbooks are marked with the text they replace so it can still be copied
+	let spell_list = vec![spells::Spell::from_json_file("spells/players_handbook_2024/fire_bolt.json").unwrap()];
+	let actual_texts = |ligatures: bool|
+	{
+		let additional_options = AdditionalOptions { ligatures: ligatures, ..Default::default() };
+		let bytes = create_spellbook_with_options
+		(
+			"Ligature Spellbook",
+			&spell_list,
+			font_paths.clone(),
+			font_sizes,
+			font_scalars,
+			spacing_options,
+			text_colors,
+			page_size_options,
+			None,
+			None,
+			table_options,
+			&additional_options
+		).unwrap().save_to_bytes().unwrap();
+		let doc = lopdf::Document::load_mem(&bytes).unwrap();
+		let page_id = doc.get_pages()[&2];
+		let content = lopdf::content::Content::decode(&doc.get_page_content(page_id).unwrap()).unwrap();
+		content.operations.iter()
+			.filter(|operation| operation.operator == "BDC" && operation.operands[0].as_name().unwrap() == b"Span")
+			.map(|operation|
+			{
+				let properties = operation.operands[1].as_dict().unwrap();
+				let units: Vec<u16> = properties.get(b"ActualText").unwrap().as_str().unwrap()
+					.chunks(2)
+					.map(|bytes| u16::from_be_bytes([bytes[0], bytes[1]]))
+					.collect();
+				String::from_utf16(&units[1..]).unwrap()
+			})
+			.collect::<Vec<_>>()
+	};
+	assert!(actual_texts(false).is_empty());
+	assert!(actual_texts(true).iter().any(|text| text == "fi"));
+}
+
+// Makes sure text in scripts that need shaping gets measured and written with the glyphs it gets shaped into
+#[cfg(feature = "shaping")]
+#[test]
+fn shaping()
+{
+	let (font_paths, font_sizes, font_scalars, _, _, _, _, _, _, _) = default_spellbook_options();
+	assert!(crate::shaping::needs_shaping("\u{633}\u{644}\u{627}\u{645}"));
+	assert!(crate::shaping::needs_shaping("e\u{301}"));
+	assert!(!crate::shaping::needs_shaping("Fire Bolt"));
+	// A letter followed by a combining accent gets shaped into the single glyph of the accented letter
+	let font_bytes = crate::utils::load_font_bytes(&font_paths.regular, None).unwrap();
+	let font = rusttype::Font::try_from_vec(font_bytes.clone()).unwrap();
+	let shaper = crate::shaping::TextShaper::from_font_bytes(&font_bytes).unwrap();
+	let glyphs = shaper.shape("e\u{301}");
+	assert_eq!(glyphs.len(), 1);
+	assert_eq!(glyphs[0].id, font.glyph('\u{e9}').id().0);
+	// Shaped text gets measured by its shaped glyphs instead of each character on its own
+	let size = font_sizes.body_font_size();
+	let plain = layout::FontMeasurer::new(&font_paths, size, font_scalars).unwrap();
+	let shaped = layout::FontMeasurer::with_font_features(&font_paths, size, font_scalars, false, false, true).unwrap();
+	let width = |measurer: &layout::FontMeasurer, text: &str|
+		layout::TextMeasurer::text_width(measurer, text, FontVariant::Regular);
+	assert!((width(&shaped, "e\u{301}") - width(&plain, "\u{e9}")).abs() < 0.01);
+	assert_eq!(width(&shaped, "Fire Bolt"), width(&plain, "Fire Bolt"));
+	// Shaped text is marked with the text it came from so it can still be copied
+	let mut spell = spells::Spell::from_json_file("spells/players_handbook_2024/fire_bolt.json").unwrap();
+	spell.description = String::from("Caf\u{65}\u{301} fire.");
+	let additional_options = AdditionalOptions { shaping: true, ..Default::default() };
+	let bytes = small_spellbook(&vec![spell], &additional_options).save_to_bytes().unwrap();
+	let doc = lopdf::Document::load_mem(&bytes).unwrap();
+	let page_id = doc.get_pages()[&2];
+	let content = lopdf::content::Content::decode(&doc.get_page_content(page_id).unwrap()).unwrap();
+	assert!(content.operations.iter().any(|operation| operation.operator == "BDC"));
+}
+
+// Makes sure asking for text shaping without the `shaping` feature is an error instead of ignoring it
+#[cfg(not(feature = "shaping"))]
+#[test]
+fn shaping_needs_feature()
+{
+	let error = crate::shaping::TextShaper::from_font_bytes(&[]).unwrap_err();
+	assert!(error.to_string().contains("`shaping` feature"));
+	let (font_paths, font_sizes, font_scalars, spacing_options, text_colors, page_size_options, _, _, _,
+		table_options) = default_spellbook_options();
+	let additional_options = AdditionalOptions { shaping: true, ..Default::default() };
+	let result = create_spellbook_with_options
+	(
+		"Small Spellbook",
+		&Vec::new(),
+		font_paths,
+		font_sizes,
+		font_scalars,
+		spacing_options,
+		text_colors,
+		page_size_options,
+		None,
+		None,
+		table_options,
+		&additional_options
+	);
+	assert!(result.is_err());
+}
+
+// Makes sure that each type of text can have its own tab size and that bullet points can have a hang indent
+#[test]
+fn per_text_type_tab_amounts()
+{
+	// Every type of text uses the normal tab size until it's given its own
+	let spacing_options = SpacingOptions::new(7.5, 12.0, 8.0, 5.0, 6.4, 5.0).unwrap();
+	assert_eq!(spacing_options.body_tab_amount(), 7.5);
+	assert_eq!(spacing_options.table_body_tab_amount(), 7.5);
+	assert_eq!(spacing_options.bullet_indent(), None);
+	let custom_spacing = spacing_options.with_tab_amounts(0.0, 0.0, 20.0, 0.0, 3.0).unwrap()
+		.with_bullet_indent(15.0).unwrap();
+	assert_eq!(custom_spacing.tab_amount(), 7.5);
+	assert_eq!(custom_spacing.body_tab_amount(), 20.0);
+	assert_eq!(custom_spacing.table_body_tab_amount(), 3.0);
+	assert_eq!(custom_spacing.bullet_indent(), Some(15.0));
+	assert!(spacing_options.with_tab_amounts(0.0, 0.0, -1.0, 0.0, 0.0).is_err());
+	assert!(spacing_options.with_bullet_indent(-1.0).is_err());
+	// Paragraphs get tabbed in by the body tab size and bullet points hang in by the bullet indent
+	let (font_paths, font_sizes, font_scalars, _, text_colors, page_size_options, _, _, _, table_options) =
+		default_spellbook_options();
+	let mut spell = spells::Spell::from_json_file("spells/players_handbook_2024/fire_bolt.json").unwrap();
+	spell.description = String::from("Alpha.\nBeta.\n- Gamma.");
+	spell.upcast_description = None;
+	spell.tables = Vec::new();
+	let spell_list = vec![spell];
+	let text_x_positions = |spacing_options: SpacingOptions|
+	{
+		let bytes = create_spellbook_with_options
+		(
+			"Tab Spellbook",
+			&spell_list,
+			font_paths.clone(),
+			font_sizes,
+			font_scalars,
+			spacing_options,
+			text_colors,
+			page_size_options,
+			None,
+			None,
+			table_options,
+			&AdditionalOptions::default()
+		).unwrap().save_to_bytes().unwrap();
+		let doc = lopdf::Document::load_mem(&bytes).unwrap();
+		let page_id = doc.get_pages()[&2];
+		let content = lopdf::content::Content::decode(&doc.get_page_content(page_id).unwrap()).unwrap();
+		content.operations.iter()
+			.filter(|operation| operation.operator == "Td")
+			.map(|operation| operation.operands[0].as_float().unwrap())
+			.collect::<Vec<_>>()
+	};
+	// Positions of text in Pt (left margin is 10 Mm)
+	let has_x = |positions: &Vec<f32>, x_mm: f32|
+		positions.iter().any(|x| (x - Mm(x_mm + 10.0).into_pt().0).abs() < 0.01);
+	let default_positions = text_x_positions(spacing_options);
+	assert!(has_x(&default_positions, 7.5));
+	assert!(!has_x(&default_positions, 20.0));
+	let custom_positions = text_x_positions(custom_spacing);
+	assert!(has_x(&custom_positions, 20.0));
+	assert!(has_x(&custom_positions, 15.0));
+	assert!(!has_x(&custom_positions, 7.5));
+}
+
+// Gets the number of lines in each paragraph on each page of a spellbook after the first page of its first spell
+// (the first line of each paragraph is tabbed in)
+fn paragraph_line_counts_by_page(bytes: &[u8]) -> Vec<Vec<usize>>
+{
+	let doc = lopdf::Document::load_mem(bytes).unwrap();
+	let mut line_counts_by_page = Vec::new();
+	for page_id in doc.get_pages().values().skip(2)
+	{
+		let content = lopdf::content::Content::decode(&doc.get_page_content(*page_id).unwrap()).unwrap();
+		let line_xs: Vec<f32> = content.operations.iter()
+			.filter(|operation| operation.operator == "Td")
+			.map(|operation| operation.operands[0].as_float().unwrap())
+			.collect();
+		let x_min = line_xs.iter().copied().fold(f32::MAX, f32::min);
+		let mut line_counts = Vec::new();
+		let mut line_count = 0;
+		for x in line_xs
+		{
+			if x > x_min && line_count > 0
+			{
+				line_counts.push(line_count);
+				line_count = 0;
+			}
+			line_count += 1;
+		}
+		line_counts.push(line_count);
+		line_counts_by_page.push(line_counts);
+	}
+	line_counts_by_page
+}
+
+// Makes sure that paragraphs keep enough lines together at page breaks with paragraph break options
+#[test]
+fn paragraph_breaks()
+{
+	let (font_paths, font_sizes, font_scalars, spacing_options, text_colors, page_size_options, _, _, _,
+		table_options) = default_spellbook_options();
+	// A spell with enough paragraphs of the same length to go across a few pages
+	let mut spell = spells::Spell::from_json_file("spells/players_handbook_2024/fire_bolt.json").unwrap();
+	let paragraph = vec!["arcane"; 30].join(" ");
+	spell.description = vec![paragraph; 40].join("\n");
+	spell.upcast_description = None;
+	spell.tables = Vec::new();
+	let spell_list = vec![spell];
+	// Gets the number of lines in each paragraph after the first page of the spell
+	let paragraph_line_counts = |paragraph_breaks: Option<ParagraphBreakOptions>|
+	{
+		let additional_options = AdditionalOptions { paragraph_breaks: paragraph_breaks, ..Default::default() };
+		let bytes = create_spellbook_with_options
+		(
+			"Paragraph Spellbook",
+			&spell_list,
+			font_paths.clone(),
+			font_sizes,
+			font_scalars,
+			spacing_options,
+			text_colors,
+			page_size_options,
+			None,
+			None,
+			table_options,
+			&additional_options
+		).unwrap().save_to_bytes().unwrap();
+		let line_counts_by_page = paragraph_line_counts_by_page(&bytes);
+		assert!(line_counts_by_page.len() > 1);
+		line_counts_by_page.concat()
+	};
+	let paragraph_length = paragraph_line_counts(None).into_iter().max().unwrap();
+	assert!(paragraph_length > 1);
+	// Without paragraph break options, paragraphs get split wherever the page runs out
+	assert!(paragraph_line_counts(None).iter().any(|line_count| *line_count != paragraph_length));
+	// Paragraphs that can't leave enough lines on either side of a page break get moved to the next page
+	let options = ParagraphBreakOptions { min_lines_before_break: 10, min_lines_after_break: 10 };
+	assert!(paragraph_line_counts(Some(options)).iter().all(|line_count| *line_count == paragraph_length));
+}
+
+// Makes sure that justified lines get their words spread out to the right side of the text box unless their spaces
+// would have to be stretched too far
+#[test]
+fn justification()
+{
+	let mut spell = spells::Spell::from_json_file("spells/players_handbook_2024/fire_bolt.json").unwrap();
+	spell.description = ["a bolt of fire streaks toward a creature or object within range"; 6].join(" ");
+	spell.upcast_description = None;
+	spell.tables = Vec::new();
+	let spell_list = vec![spell];
+	// Gets the x positions of every piece of text in the spellbook
+	let text_xs = |justification: Option<JustificationOptions>|
+	{
+		let additional_options = AdditionalOptions { justification: justification, ..Default::default() };
+		let bytes = small_spellbook(&spell_list, &additional_options).save_to_bytes().unwrap();
+		let doc = lopdf::Document::load_mem(&bytes).unwrap();
+		let mut xs = Vec::new();
+		for page_id in doc.get_pages().values()
+		{
+			let content = lopdf::content::Content::decode(&doc.get_page_content(*page_id).unwrap()).unwrap();
+			xs.extend(content.operations.iter()
+				.filter(|operation| operation.operator == "Td")
+				.map(|operation| operation.operands[0].as_float().unwrap()));
+		}
+		xs
+	};
+	let ragged = text_xs(None);
+	let justified = text_xs(Some(JustificationOptions::default()));
+	// Each word of a justified line gets written on its own, and the last words of lines get moved further right
+	assert!(justified.len() > ragged.len() + 10);
+	let max_x = |xs: &Vec<f32>| xs.iter().copied().fold(f32::MIN, f32::max);
+	assert!(max_x(&justified) > max_x(&ragged));
+	// Lines that would need their spaces stretched at all or that have too few spaces are left ragged-right
+	let no_stretch = JustificationOptions { max_space_stretch: 1.0, ..Default::default() };
+	assert_eq!(text_xs(Some(no_stretch)), ragged);
+	let too_few_spaces = JustificationOptions { min_spaces: 100, ..Default::default() };
+	assert_eq!(text_xs(Some(too_few_spaces)), ragged);
+}
+
+// Makes sure spells can start right after the spell before them and split their tables across page breaks with
+// layout settings for the whole spellbook or in their spell files
+#[test]
+fn spell_layout()
+{
+	// Layout hints get read from spell files
+	let fire_bolt = spells::Spell::from_json_file("spells/players_handbook_2024/fire_bolt.json").unwrap();
+	let mut spell_json = serde_json::to_value(&fire_bolt).unwrap();
+	spell_json["layout"] = serde_json::json!({ "start_on_new_page": false });
+	let continued_fire_bolt: spells::Spell = serde_json::from_value(spell_json).unwrap();
+	assert_eq!
+	(
+		continued_fire_bolt.layout,
+		Some(spells::LayoutHints { start_on_new_page: Some(false), allow_table_split: None })
+	);
+	// Gets the number of text operations on each page of a spellbook
+	let text_counts = |spell_list: &Vec<spells::Spell>, spell_layout: SpellLayoutOptions|
+	{
+		let additional_options = AdditionalOptions { spell_layout: spell_layout, ..Default::default() };
+		let bytes = small_spellbook(spell_list, &additional_options).save_to_bytes().unwrap();
+		let doc = lopdf::Document::load_mem(&bytes).unwrap();
+		doc.get_pages().values().map(|page_id|
+		{
+			let content = lopdf::content::Content::decode(&doc.get_page_content(*page_id).unwrap()).unwrap();
+			content.operations.iter().filter(|operation| operation.operator == "Td").count()
+		})
+		.collect::<Vec<_>>()
+	};
+	// Each spell starts on its own page by default
+	let new_pages = SpellLayoutOptions::default();
+	let same_page = SpellLayoutOptions { start_on_new_page: false, ..Default::default() };
+	let spell_list = vec![fire_bolt.clone(); 3];
+	assert_eq!(text_counts(&spell_list, new_pages).len(), 4);
+	// Short spells can share a page when they don't start on new pages
+	assert_eq!(text_counts(&spell_list, same_page).len(), 2);
+	// Spells can override the settings of the spellbook
+	let spell_list = vec![fire_bolt.clone(), continued_fire_bolt, fire_bolt.clone()];
+	assert_eq!(text_counts(&spell_list, new_pages).len(), 3);
+	// A spell with a table that doesn't fit at the bottom of its first page
+	let mut spell = fire_bolt;
+	spell.description = format!("{}\n[table][0]", vec![vec!["arcane"; 30].join(" "); 12].join("\n"));
+	spell.upcast_description = None;
+	spell.tables = vec![spells::Table
+	{
+		title: String::from("Sparks"),
+		column_labels: vec![String::from("d20"), String::from("Spark")],
+		cells: (1..=20).map(|roll| vec![roll.to_string(), String::from("A spark flies")]).collect()
+	}];
+	let spell_list = vec![spell.clone()];
+	let whole_table = text_counts(&spell_list, new_pages);
+	let split_table = text_counts(&spell_list, SpellLayoutOptions { allow_table_split: true, ..Default::default() });
+	// Tables that fit on a single page get moved to the next page unless they can be split
+	assert_eq!(whole_table.len(), split_table.len());
+	assert!(split_table[1] > whole_table[1]);
+	// Spells can let their own tables be split
+	spell.layout = Some(spells::LayoutHints { allow_table_split: Some(true), ..Default::default() });
+	assert_eq!(text_counts(&vec![spell], new_pages), split_table);
+}
+
+// Makes sure footnotes go at the bottom of the page their numbers are on with their numbers in superscript
+#[test]
+fn footnotes()
+{
+	// Footnotes get replaced with their ids in superscript after the word before them
+	let (text, footnotes) = spells::extract_footnotes("Fire damage <fn>Or cold.</fn>, or <fn>unclosed", 4);
+	assert_eq!(text, "Fire damage\u{E000}4\u{E001}, or <fn>unclosed");
+	assert_eq!(footnotes, vec![String::from("Or cold.")]);
+	let (text, footnotes) = spells::extract_footnotes("Spans <fn>two\nparagraphs</fn>", 0);
+	assert_eq!(text, "Spans <fn>two\nparagraphs</fn>");
+	assert!(footnotes.is_empty());
+	// A spell with a footnote near the top of its first page and one near the bottom of its second page
+	let mut spell = spells::Spell::from_json_file("spells/players_handbook_2024/fire_bolt.json").unwrap();
+	let filler = vec!["arcane"; 30].join(" ");
+	let mut paragraphs = vec![filler.clone(); 28];
+	paragraphs[0] = format!("{} firstmark<fn>Zanzibar footnote.</fn> {}", filler, filler);
+	paragraphs[26] = format!("{} secondmark<fn>Quokka footnote that is long enough to wrap onto a second line at \
+		the bottom of the page {}.</fn> {}", filler, filler, filler);
+	spell.description = paragraphs.join("\n");
+	spell.upcast_description = None;
+	let additional_options = AdditionalOptions
+	{
+		search_index: Some(SearchIndexOptions::default()),
+		..Default::default()
+	};
+	let spellbook = small_spellbook(&vec![spell], &additional_options);
+	let search_index = spellbook.search_index.clone().unwrap();
+	// The text of each footnote is on the same page as its number
+	assert_eq!(search_index.pages("zanzibar"), search_index.pages("firstmark"));
+	assert_eq!(search_index.pages("quokka"), search_index.pages("secondmark"));
+	assert_ne!(search_index.pages("zanzibar"), search_index.pages("quokka"));
+	assert!(search_index.pages("fn").is_empty());
+	// Each footnote number is written smaller than the text around it (once in the text and once before the footnote)
+	let bytes = spellbook.save_to_bytes().unwrap();
+	let doc = lopdf::Document::load_mem(&bytes).unwrap();
+	let pages: Vec<_> = doc.get_pages().values().copied().collect();
+	let superscript_counts: Vec<usize> = pages.iter().map(|page_id|
+	{
+		let content = doc.get_and_decode_page_content(*page_id).unwrap();
+		let font_sizes: Vec<f32> = content.operations.iter().filter(|operation| operation.operator == "Tf")
+			.map(|operation| operation.operands[1].as_float().unwrap())
+			.collect();
+		// The most common size is the size of the description
+		let body_size = font_sizes.iter().copied()
+			.max_by_key(|size| font_sizes.iter().filter(|other_size| *other_size == size).count())
+			.unwrap();
+		font_sizes.iter().filter(|size| **size < body_size * 0.7).count()
+	})
+	.collect();
+	let first_page = search_index.pages("zanzibar")[0];
+	let second_page = search_index.pages("quokka")[0];
+	assert_eq!(superscript_counts[first_page], 2);
+	assert_eq!(superscript_counts[second_page], 2);
+	assert_eq!(superscript_counts.iter().sum::<usize>(), 4);
+}
+
+// Makes sure endnotes and sources go in a references section at the end that links back to the pages they came from
+#[test]
+fn endnotes()
+{
+	let mut spell_list = vec!
+	[
+		spells::Spell::from_json_file("spells/players_handbook_2024/fire_bolt.json").unwrap(),
+		spells::Spell::from_json_file("spells/players_handbook_2024/burning_hands.json").unwrap(),
+		spells::Spell::from_json_file("spells/players_handbook_2024/fireball.json").unwrap()
+	];
+	spell_list[0].description = String::from("Fire firstmark<fn>Zanzibar note.</fn> secondmark<fn>Quokka note.</fn>");
+	spell_list[0].source = Some(String::from("Xylophone Codex"));
+	spell_list[0].upcast_description = None;
+	// Spells without endnotes or sources don't get listed
+	spell_list[1].source = None;
+	spell_list[2].source = Some(String::from("Yeti Almanac"));
+	let additional_options = AdditionalOptions
+	{
+		search_index: Some(SearchIndexOptions::default()),
+		endnotes: Some(EndnoteOptions::default()),
+		..Default::default()
+	};
+	let spellbook = small_spellbook(&spell_list, &additional_options);
+	let search_index = spellbook.search_index.clone().unwrap();
+	let bytes = spellbook.save_to_bytes().unwrap();
+	let doc = lopdf::Document::load_mem(&bytes).unwrap();
+	let page_ids: Vec<_> = doc.get_pages().into_values().collect();
+	let references_page = page_ids.len() - 1;
+	let spell_page = search_index.pages("firstmark")[0];
+	// The endnotes and sources go on the last page instead of the pages their spells are on
+	for word in ["references", "zanzibar", "quokka", "xylophone", "yeti"]
+	{
+		assert_eq!(search_index.pages(word), vec![references_page]);
+	}
+	assert!(search_index.pages("burning").iter().all(|page| *page != references_page));
+	// Each endnote number links to its endnote
+	let annotations = get_annotations(&bytes);
+	let destination = |annotation: &lopdf::Dictionary|
+		annotation.get(b"Dest").unwrap().as_array().unwrap()[0].as_reference().unwrap();
+	assert_eq!(annotations[spell_page].len(), 2);
+	assert!(annotations[spell_page].iter().all(|annotation| destination(annotation) == page_ids[references_page]));
+	// The spell names and endnotes link back to the pages they came from
+	assert_eq!(annotations[references_page].len(), 4);
+	let destinations: Vec<_> = annotations[references_page].iter().map(destination).collect();
+	assert_eq!(destinations.iter().filter(|page_id| **page_id == page_ids[spell_page]).count(), 3);
+	// Without a references section, the same spells don't get another page
+	let additional_options = AdditionalOptions { endnotes: None, ..additional_options };
+	let bytes = small_spellbook(&spell_list, &additional_options).save_to_bytes().unwrap();
+	assert_eq!(lopdf::Document::load_mem(&bytes).unwrap().get_pages().len(), page_ids.len() - 1);
+}
+
+// Makes sure secrets show up in DM editions and get hidden in player editions
+#[test]
+fn secret_redaction()
+{
+	// Secrets lose their tags in DM editions and get left out or marked to be hidden in player editions
+	let text = "The <secret> orb <b> hums </secret>, softly.\n<secret>Only the DM.</secret>\nAn <secret>unclosed";
+	assert_eq!(spells::redact_secrets(text, None), "The  orb <b> hums , softly.\nOnly the DM.\nAn <secret>unclosed");
+	assert_eq!
+	(
+		spells::redact_secrets(text, Some(RedactionStyle::Omit)),
+		"The, softly.\nAn <secret>unclosed"
+	);
+	assert_eq!
+	(
+		spells::redact_secrets(text, Some(RedactionStyle::ShadedBar)).split('\n').next().unwrap(),
+		"The  \u{E002}orb\u{E003} <b> \u{E002}hums\u{E003} , softly."
+	);
+	let mut spell = spells::Spell::from_json_file("spells/players_handbook_2024/fire_bolt.json").unwrap();
+	spell.description = format!("{} <secret> Zanzibar quokka </secret> the end.", spell.description);
+	let (font_paths, font_sizes, font_scalars, spacing_options, text_colors, page_size_options, page_number_options, _,
+	_, table_options) = default_spellbook_options();
+	let additional_options = AdditionalOptions
+	{
+		search_index: Some(SearchIndexOptions::default()),
+		..Default::default()
+	};
+	let make_editions = |redaction: RedactionStyle| create_dm_and_player_spellbooks
+	(
+		"Secret Spellbook",
+		&vec![spell.clone()],
+		font_paths.clone(),
+		font_sizes,
+		font_scalars,
+		spacing_options,
+		text_colors,
+		page_size_options,
+		Some(page_number_options),
+		None,
+		table_options,
+		&additional_options,
+		redaction
+	).unwrap();
+	let (dm_edition, player_edition) = make_editions(RedactionStyle::ShadedBar);
+	let dm_index = dm_edition.search_index.clone().unwrap();
+	let player_index = player_edition.search_index.clone().unwrap();
+	assert_eq!(dm_index.pages("zanzibar"), dm_index.pages("fire"));
+	assert!(player_index.pages("zanzibar").is_empty());
+	assert!(player_index.pages("quokka").is_empty());
+	assert_eq!(player_index.pages("end"), dm_index.pages("end"));
+	// Each hidden word gets a filled bar in its place
+	let rect_count = |spellbook: Spellbook|
+	{
+		let bytes = spellbook.save_to_bytes().unwrap();
+		let doc = lopdf::Document::load_mem(&bytes).unwrap();
+		doc.get_pages().values().map(|page_id| doc.get_and_decode_page_content(*page_id).unwrap().operations.iter()
+			.filter(|operation| operation.operator == "re")
+			.count())
+			.sum::<usize>()
+	};
+	assert_eq!(rect_count(player_edition), rect_count(dm_edition) + 2);
+	// Omitted secrets don't leave bars behind
+	let (_, player_edition) = make_editions(RedactionStyle::Omit);
+	let player_index = player_edition.search_index.clone().unwrap();
+	assert!(player_index.pages("zanzibar").is_empty());
+	assert_eq!(player_index.pages("end"), dm_index.pages("end"));
+}
+
+// Makes sure that spells added to a spellbook after it was made go on the same pages that they would have if the
+// spellbook was made with them
+#[test]
+fn append_spells()
+{
+	let spell_list = vec!
+	[
+		spells::Spell::from_json_file("spells/players_handbook_2024/fire_bolt.json").unwrap(),
+		spells::Spell::from_json_file("spells/players_handbook_2024/burning_hands.json").unwrap(),
+		spells::Spell::from_json_file("spells/players_handbook_2024/fireball.json").unwrap()
+	];
+	let additional_options = AdditionalOptions::default();
+	// Gets the operations on each page of a spellbook
+	let page_operations = |spellbook: Spellbook|
+	{
+		let bytes = spellbook.save_to_bytes().unwrap();
+		let doc = lopdf::Document::load_mem(&bytes).unwrap();
+		doc.get_pages().values().map(|page_id|
+		{
+			let content = lopdf::content::Content::decode(&doc.get_page_content(*page_id).unwrap()).unwrap();
+			format!("{:?}", content.operations)
+		})
+		.collect::<Vec<_>>()
+	};
+	let all_at_once = page_operations(small_spellbook(&spell_list, &additional_options));
+	let mut spellbook = small_spellbook(&spell_list[..1].to_vec(), &additional_options);
+	spellbook.append_spells(&spell_list[1..].to_vec()).unwrap();
+	let appended = page_operations(spellbook);
+	assert_eq!(appended.len(), all_at_once.len());
+	assert_eq!(appended, all_at_once);
+}
+
+// Makes sure that page break tags start text on new pages and keep regions of paragraphs on the same page
+#[test]
+fn page_break_tags()
+{
+	let (font_paths, font_sizes, font_scalars, spacing_options, text_colors, page_size_options, _, _, _,
+		table_options) = default_spellbook_options();
+	let mut spell = spells::Spell::from_json_file("spells/players_handbook_2024/fire_bolt.json").unwrap();
+	spell.upcast_description = None;
+	spell.tables = Vec::new();
+	// Gets the number of lines in each paragraph on each page after the first page of a spell with a description
+	let line_counts_by_page = |description: String|
+	{
+		let mut spell = spell.clone();
+		spell.description = description;
+		let bytes = create_spellbook_with_options
+		(
+			"Page Break Spellbook",
+			&vec![spell],
+			font_paths.clone(),
+			font_sizes,
+			font_scalars,
+			spacing_options,
+			text_colors,
+			page_size_options,
+			None,
+			None,
+			table_options,
+			&AdditionalOptions::default()
+		).unwrap().save_to_bytes().unwrap();
+		paragraph_line_counts_by_page(&bytes)
+	};
+	// Text after a page break tag goes on a new page
+	assert!(line_counts_by_page(String::from("Alpha.\nBeta.")).is_empty());
+	assert_eq!(line_counts_by_page(format!("Alpha.\n{}\nBeta.", spells::PAGE_BREAK_TAG)), vec![vec![1]]);
+	// Escaped page break tags get written as text
+	assert!(line_counts_by_page(String::from("Alpha.\n\\[pagebreak]\nBeta.")).is_empty());
+	// Pairs of paragraphs that go across a few pages
+	let paragraph = vec!["arcane"; 30].join(" ");
+	let pair = format!("{}\n{}", paragraph, paragraph);
+	let paragraph_length = line_counts_by_page(format!("{}\n{}", spells::PAGE_BREAK_TAG, paragraph))[0][0];
+	let pages = line_counts_by_page(vec![pair.clone(); 20].join("\n"));
+	assert!(pages.len() > 1);
+	// Without no break regions, pairs get split up between pages
+	assert!(pages.iter().any(|line_counts| line_counts.len() % 2 != 0 ||
+		line_counts.iter().any(|line_count| *line_count != paragraph_length)));
+	// Each no break region gets moved to the next page if it doesn't fit on the current one
+	let region = format!("{}\n{}\n{}", spells::NO_BREAK_START_TAG, pair, spells::NO_BREAK_END_TAG);
+	let pages = line_counts_by_page(vec![region; 20].join("\n"));
+	assert!(pages.len() > 1);
+	assert!(pages.iter().all(|line_counts| line_counts.len() % 2 == 0 &&
+		line_counts.iter().all(|line_count| *line_count == paragraph_length)));
+	// Page break tags are skipped by other exporters and can be escaped
+	assert_eq!(crate::rich_text::blocks(&format!("Alpha.\n{}", spells::PAGE_BREAK_TAG), &Vec::new()).len(), 1);
+	assert_eq!(spells::escape_rich_text("[pagebreak]"), "\\[pagebreak]");
+	assert_eq!(spells::unescape_rich_text("\\[nobreak]"), "[nobreak]");
+}
+
+// Makes sure that compact density only tightens the spacing in spells that take up fewer pages with it
+#[test]
+fn compact_density()
+{
+	let (font_paths, font_sizes, font_scalars, spacing_options, text_colors, page_size_options, _, _, _,
+		table_options) = default_spellbook_options();
+	let mut spell = spells::Spell::from_json_file("spells/players_handbook_2024/fire_bolt.json").unwrap();
+	spell.upcast_description = None;
+	spell.tables = Vec::new();
+	// Gets the number of pages in a spellbook with a spell that has a certain number of lines in its description
+	let page_count = |line_count: usize, compact_density: Option<CompactDensityOptions>|
+	{
+		let mut spell = spell.clone();
+		spell.description = vec!["Alpha."; line_count].join("\n");
+		let additional_options = AdditionalOptions { compact_density: compact_density, ..Default::default() };
+		let bytes = create_spellbook_with_options
+		(
+			"Compact Spellbook",
+			&vec![spell],
+			font_paths.clone(),
+			font_sizes,
+			font_scalars,
+			spacing_options,
+			text_colors,
+			page_size_options,
+			None,
+			None,
+			table_options,
+			&additional_options
+		).unwrap().save_to_bytes().unwrap();
+		lopdf::Document::load_mem(&bytes).unwrap().get_pages().len()
+	};
+	// Spells that only spill a line or two onto another page fit on one page with compact density
+	let options = Some(CompactDensityOptions::default());
+	let mut saved_page = false;
+	for line_count in 40..60
+	{
+		let normal_page_count = page_count(line_count, None);
+		let compact_page_count = page_count(line_count, options);
+		assert!(compact_page_count <= normal_page_count);
+		if compact_page_count < normal_page_count
+		{
+			saved_page = true;
+			break;
+		}
+	}
+	assert!(saved_page);
+	// Spells that fit on one page either way keep their normal spacing
+	let positions = |compact_density: Option<CompactDensityOptions>|
+	{
+		let additional_options = AdditionalOptions { compact_density: compact_density, ..Default::default() };
+		let bytes = create_spellbook_with_options
+		(
+			"Compact Spellbook",
+			&vec![spell.clone()],
+			font_paths.clone(),
+			font_sizes,
+			font_scalars,
+			spacing_options,
+			text_colors,
+			page_size_options,
+			None,
+			None,
+			table_options,
+			&additional_options
+		).unwrap().save_to_bytes().unwrap();
+		let doc = lopdf::Document::load_mem(&bytes).unwrap();
+		let page_id = doc.get_pages()[&2];
+		let content = lopdf::content::Content::decode(&doc.get_page_content(page_id).unwrap()).unwrap();
+		content.operations.iter()
+			.filter(|operation| operation.operator == "Td")
+			.map(|operation| operation.operands[1].as_float().unwrap())
+			.collect::<Vec<_>>()
+	};
+	assert_eq!(positions(options), positions(None));
+}
+
+// Makes sure mostly empty space at the end of spells gets filled with the page fillers in order
+#[test]
+fn page_fill()
+{
+	let spell_list = vec!
+	[
+		spells::Spell::from_json_file("spells/players_handbook_2024/fire_bolt.json").unwrap(),
+		spells::Spell::from_json_file("spells/players_handbook_2024/light.json").unwrap(),
+		spells::Spell::from_json_file("spells/players_handbook_2024/acid_splash.json").unwrap()
+	];
+	// Counts the operations with a certain operator on each spell page
+	let operation_counts = |page_fill: Option<PageFillOptions>, operator: &str|
+	{
+		let additional_options = AdditionalOptions { page_fill: page_fill, ..Default::default() };
+		let bytes = small_spellbook(&spell_list, &additional_options).save_to_bytes().unwrap();
+		let doc = lopdf::Document::load_mem(&bytes).unwrap();
+		doc.get_pages().into_values().skip(1)
+			.map(|page_id| lopdf::content::Content::decode(&doc.get_page_content(page_id).unwrap()).unwrap())
+			.map(|content| content.operations.iter().filter(|operation| operation.operator == operator).count())
+			.collect::<Vec<_>>()
+	};
+	let fill_counts = operation_counts(None, "f");
+	let text_counts = operation_counts(None, "Td");
+	// Quotes and ornaments take turns filling each page
+	let page_fill = PageFillOptions
+	{
+		fillers: vec!
+		[
+			PageFiller::Ornament { width: 40.0, color: (0, 0, 0) },
+			PageFiller::Quote
+			{
+				text: String::from("Magic is the art of the possible."),
+				attribution: Some(String::from("Elminster"))
+			}
+		],
+		min_empty_space: 0.4
+	};
+	let filled_fill_counts = operation_counts(Some(page_fill.clone()), "f");
+	let filled_text_counts = operation_counts(Some(page_fill.clone()), "Td");
+	for page_index in 0..spell_list.len()
+	{
+		let ornament = page_index % 2 == 0;
+		assert_eq!(filled_fill_counts[page_index], fill_counts[page_index] + usize::from(ornament));
+		if ornament { assert_eq!(filled_text_counts[page_index], text_counts[page_index]); }
+		// One line for the quote and one for its attribution
+		else { assert_eq!(filled_text_counts[page_index], text_counts[page_index] + 2); }
+	}
+	// Pages that aren't empty enough don't get filled
+	let page_fill = PageFillOptions { min_empty_space: 1.0, ..page_fill };
+	assert_eq!(operation_counts(Some(page_fill), "f"), fill_counts);
+}
+
+// Makes sure spellbooks can be made from a list of spell names and a spell database
+#[test]
+fn spellbook_by_names()
+{
+	let (font_paths, font_sizes, font_scalars, spacing_options, text_colors, page_size_options, _, _, _,
+		table_options) = default_spellbook_options();
+	let database = SpellDatabase::from_folder("spells/players_handbook_2024").unwrap();
+	// Names get found regardless of capitalization, punctuation, and underscores
+	assert_eq!(database.get("fire BOLT").unwrap().unwrap().name, "Fire Bolt");
+	assert_eq!(database.get("bigbys_hand").unwrap().unwrap().name, "Bigby's Hand");
+	assert_eq!(database.get("Bigby\u{2019}s Hand").unwrap().unwrap().name, "Bigby's Hand");
+	assert!(database.get("Fire Bolt 2").unwrap().is_none());
+	// Creates a spellbook from a list of spell names
+	let create = |names: &[&str]| create_spellbook_by_names
+	(
+		"Character Spellbook",
+		names,
+		&database,
+		font_paths.clone(),
+		font_sizes,
+		font_scalars,
+		spacing_options,
+		text_colors,
+		page_size_options,
+		None,
+		None,
+		table_options,
+		&AdditionalOptions::default()
+	);
+	let bytes = create(&["fire bolt", "Acid_Splash"]).unwrap().save_to_bytes().unwrap();
+	assert_eq!(lopdf::Document::load_mem(&bytes).unwrap().get_pages().len(), 3);
+	// Every name that couldn't be found gets listed in the error
+	let error = create(&["Fire Bolt", "Fireball 2", "Not A Spell"]).err().unwrap();
+	let error = error.downcast_ref::<UnresolvedSpellNamesError>().unwrap();
+	assert_eq!(error.0, vec![String::from("Fireball 2"), String::from("Not A Spell")]);
+	// Spells that were added earlier take priority over spells with the same name
+	let mut database = SpellDatabase::new();
+	let mut spell = spells::Spell::from_json_file("spells/players_handbook_2024/fire_bolt.json").unwrap();
+	spell.description = String::from("First.");
+	database.add_spells(vec![spell.clone()]);
+	spell.description = String::from("Second.");
+	database.add_spells(vec![spell]);
+	assert_eq!(database.len(), 2);
+	assert_eq!(database.get("fire bolt").unwrap().unwrap().description, "First.");
+}
+
+// Makes sure spell databases only read whole spell files when they're needed and that priorities pick between
+// spells with the same name
+#[test]
+fn spell_database()
+{
+	let folder = std::env::temp_dir().join(format!("dnd_spellbook_maker_database_test_{}", std::process::id()));
+	let homebrew_folder = folder.join("homebrew");
+	fs::create_dir_all(&homebrew_folder).unwrap();
+	let fire_bolt = spells::Spell::from_json_file("spells/players_handbook_2024/fire_bolt.json").unwrap();
+	let acid_splash = spells::Spell::from_json_file("spells/players_handbook_2024/acid_splash.json").unwrap();
+	let fireball = spells::Spell::from_json_file("spells/players_handbook_2024/fireball.json").unwrap();
+	let file_path = |folder: &Path, name: &str| folder.join(name).to_str().unwrap().to_string();
+	fire_bolt.to_json_file(&file_path(&folder, "fire_bolt.json"), false).unwrap();
+	acid_splash.to_json_file(&file_path(&folder, "acid_splash.json"), false).unwrap();
+	fireball.to_json_file(&file_path(&folder, "fireball.json"), false).unwrap();
+	let mut homebrew_fire_bolt = fire_bolt.clone();
+	homebrew_fire_bolt.description = String::from("Homebrew.");
+	homebrew_fire_bolt.to_json_file(&file_path(&homebrew_folder, "fire_bolt.json"), false).unwrap();
+	let mut database = SpellDatabase::from_folder(folder.to_str().unwrap()).unwrap();
+	database.add_folder_with_priority(homebrew_folder.to_str().unwrap(), 1).unwrap();
+	// Spells with higher priorities get used even if they were added later
+	assert_eq!(database.len(), 4);
+	assert_eq!(database.get("Fire Bolt").unwrap().unwrap().description, "Homebrew.");
+	// Spell files only get read once the spell is needed
+	let mut changed_acid_splash = acid_splash.clone();
+	changed_acid_splash.description = String::from("Changed.");
+	changed_acid_splash.to_json_file(&file_path(&folder, "acid_splash.json"), false).unwrap();
+	assert_eq!(database.get_header("acid splash").unwrap().name, "Acid Splash");
+	assert_eq!(database.get("acid splash").unwrap().unwrap().description, "Changed.");
+	// Spells can be found by level and school without reading their files
+	let cantrips: Vec<_> = database.headers_with_level(spells::Level::Cantrip)
+		.map(|header| header.name.clone())
+		.collect();
+	assert_eq!(cantrips, vec![String::from("Acid Splash"), String::from("Fire Bolt")]);
+	let evocation_count = database.headers_with_school(spells::MagicSchool::Evocation).count();
+	assert_eq!(evocation_count, 3);
+	// Only the spells that match a filter get read
+	fs::write(file_path(&folder, "fireball.json"), "{}").unwrap();
+	let filtered = database.filter(|header| header.level == spells::SpellField::Controlled(spells::Level::Cantrip))
+		.unwrap();
+	let descriptions: Vec<_> = filtered.iter().map(|spell| spell.description.as_str()).collect();
+	assert_eq!(descriptions, vec!["Changed.", "Homebrew."]);
+	assert!(database.get("fireball").is_err());
+	// Folders with invalid spell files don't get added
+	assert!(SpellDatabase::from_folder(folder.to_str().unwrap()).is_err());
+	fs::remove_dir_all(&folder).unwrap();
+}
+
+// Makes sure document stats count the resources in spellbooks
+#[test]
+fn document_stats()
+{
+	let (font_paths, font_sizes, font_scalars, spacing_options, text_colors, page_size_options, _, background_path,
+		background_transform, table_options) = default_spellbook_options();
+	let spell_list = vec!
+	[
+		spells::Spell::from_json_file("spells/players_handbook_2024/fire_bolt.json").unwrap(),
+		spells::Spell::from_json_file("spells/players_handbook_2024/acid_splash.json").unwrap()
+	];
+	let bytes = create_spellbook_with_options
+	(
+		"Stats Spellbook",
+		&spell_list,
+		font_paths,
+		font_sizes,
+		font_scalars,
+		spacing_options,
+		text_colors,
+		page_size_options,
+		None,
+		Some((&background_path, background_transform)),
+		table_options,
+		&AdditionalOptions::default()
+	).unwrap().save_to_bytes().unwrap();
+	let stats = doc_stats(&lopdf::Document::load_mem(&bytes).unwrap());
+	// One background image on every page that each have the same data
+	assert_eq!(stats.page_count, 3);
+	assert_eq!(stats.image_count, 3);
+	assert_eq!(stats.duplicate_image_count, 2);
+	// Regular, bold, italic, and bold-italic
+	assert_eq!(stats.font_count, 4);
+	assert!(stats.operation_count > 0);
+	// Resources go from largest to smallest and make up most of the file
+	assert!(stats.resources.windows(2).all(|pair| pair[0].size >= pair[1].size));
+	assert_eq!(stats.resources[0].kind, ResourceKind::Image);
+	let total_size = stats.total_size(ResourceKind::Image) + stats.total_size(ResourceKind::Font) +
+		stats.total_size(ResourceKind::PageContent);
+	assert!(total_size <= bytes.len() && total_size > bytes.len() / 2);
+}
+
+// Makes sure reference pages get added to the end of spellbooks with whichever sections are turned on
+#[test]
+fn reference_appendix()
+{
+	let spell_list = vec![spells::Spell::from_json_file("spells/players_handbook_2024/fire_bolt.json").unwrap()];
+	// Gets the number of pages in a spellbook and the title of its last bookmark
+	let make_spellbook = |reference_appendix: Option<ReferenceAppendixOptions>|
+	{
+		let additional_options = AdditionalOptions { reference_appendix: reference_appendix, ..Default::default() };
+		let bytes = small_spellbook(&spell_list, &additional_options).save_to_bytes().unwrap();
+		let doc = lopdf::Document::load_mem(&bytes).unwrap();
+		let outlines = doc.get_dictionary(doc.catalog().unwrap().get(b"Outlines").unwrap().as_reference().unwrap())
+			.unwrap();
+		let last_bookmark = doc.get_dictionary(outlines.get(b"Last").unwrap().as_reference().unwrap()).unwrap();
+		let last_bookmark_name = String::from_utf8_lossy(last_bookmark.get(b"Title").unwrap().as_str().unwrap())
+			.into_owned();
+		(doc.get_pages().len(), last_bookmark_name)
+	};
+	let (page_count, last_bookmark_name) = make_spellbook(None);
+	assert_eq!(last_bookmark_name, "Fire Bolt");
+	// Spell slot tables take up more than a page
+	let (appendix_page_count, last_bookmark_name) = make_spellbook(Some(ReferenceAppendixOptions::default()));
+	assert_eq!(last_bookmark_name, "Spellcasting Reference");
+	assert!(appendix_page_count >= page_count + 2);
+	// Just the concentration rules fit on one page
+	let options = ReferenceAppendixOptions { spell_slot_tables: false, ..Default::default() };
+	assert_eq!(make_spellbook(Some(options)).0, page_count + 1);
+	// Nothing gets added if every section is turned off
+	let options = ReferenceAppendixOptions
+	{
+		spell_slot_tables: false,
+		concentration_rules: false,
+		..Default::default()
+	};
+	assert_eq!(make_spellbook(Some(options)), (page_count, String::from("Fire Bolt")));
+}
+
+// Makes sure homebrew schools get saved to spell files, sorted, indexed, and colored like the standard schools
+#[test]
+fn homebrew_schools()
+{
+	let mut spell = spells::Spell::from_json_file("spells/players_handbook_2024/fireball.json").unwrap();
+	spell.school = spells::SpellField::Controlled(spells::MagicSchool::Homebrew(String::from("Chronurgy")));
+	assert_eq!(spell.get_level_school_text(), "Level 3 Chronurgy");
+	// Homebrew schools can be saved to and read from spell files
+	let json = serde_json::to_string(&spell).unwrap();
+	assert!(json.contains("{\"Homebrew\":\"Chronurgy\"}"));
+	assert_eq!(serde_json::from_str::<spells::Spell>(&json).unwrap(), spell);
+	// Homebrew schools go after the standard schools
+	let mut schools =
+	[
+		spells::MagicSchool::Homebrew(String::from("Graviturgy")),
+		spells::MagicSchool::Necromancy,
+		spells::MagicSchool::Homebrew(String::from("Chronurgy")),
+		spells::MagicSchool::Abjuration
+	];
+	schools.sort();
+	assert_eq!(schools[0], spells::MagicSchool::Abjuration);
+	assert_eq!(schools[3], spells::MagicSchool::Homebrew(String::from("Graviturgy")));
+	// Spell databases can find spells in homebrew schools
+	let mut database = SpellDatabase::new();
+	database.add_spells(vec![spell.clone()]);
+	let chronurgy = spells::MagicSchool::Homebrew(String::from("Chronurgy"));
+	assert_eq!(database.headers_with_school(chronurgy.clone()).count(), 1);
+	assert_eq!(database.headers_with_school(spells::MagicSchool::Evocation).count(), 0);
+	// Gets the fill colors used on the spell's page
+	let fill_colors = |school_colors: Option<SchoolColorOptions>|
+	{
+		let additional_options = AdditionalOptions { school_colors: school_colors, ..Default::default() };
+		let bytes = small_spellbook(&vec![spell.clone()], &additional_options).save_to_bytes().unwrap();
+		let doc = lopdf::Document::load_mem(&bytes).unwrap();
+		let content = lopdf::content::Content::decode(&doc.get_page_content(doc.get_pages()[&2]).unwrap()).unwrap();
+		content.operations.iter()
+			.filter(|operation| operation.operator == "rg")
+			.map(|operation| operation.operands.iter().map(|operand| operand.as_float().unwrap()).collect())
+			.collect::<Vec<Vec<f32>>>()
+	};
+	// The spell name uses the color of its school
+	let school_colors = SchoolColorOptions { name_colors: std::collections::HashMap::from([(chronurgy, (0, 0, 255))]) };
+	let blue = vec![0.0, 0.0, 1.0];
+	assert!(!fill_colors(None).contains(&blue));
+	assert!(fill_colors(Some(school_colors)).contains(&blue));
+}
+
+// Makes sure distances can be written in and converted to metric units and grid squares
+#[test]
+fn distance_units()
+{
+	use spells::{Aoe, Distance, DistanceConversion, Range};
+	// New units get displayed with singular and plural names
+	assert_eq!(Distance::Meters(18).to_string(), "18 meters");
+	assert_eq!(Distance::Kilometers(1).to_string(), "1 kilometer");
+	assert_eq!(Distance::Squares(12).to_string(), "12 squares");
+	assert_eq!(Aoe::Cone(Distance::Meters(5)).to_string(), "5-meter cone");
+	// Feet and miles look the same as before when they aren't converted
+	assert_eq!(Distance::Feet(60).to_string(), "60 feet");
+	assert_eq!(Aoe::Sphere(Distance::Feet(20)).to_string(), "20-foot radius");
+	// Conversion helpers
+	assert_eq!(Distance::Squares(2).to_feet(), 10.0);
+	assert_eq!(Distance::Feet(30).to_meters(false), 9.0);
+	assert!((Distance::Feet(30).to_meters(true) - 9.144).abs() < 0.001);
+	assert_eq!(Distance::Meters(3).to_squares(), 2.0);
+	// Ranges in each conversion
+	let range = Range::Dist(Distance::Feet(60));
+	assert_eq!(range.to_string_with(Some(DistanceConversion::GridMeters)), "18 meters");
+	assert_eq!(range.to_string_with(Some(DistanceConversion::ExactMeters)), "18.3 meters");
+	assert_eq!(range.to_string_with(Some(DistanceConversion::Squares)), "12 squares");
+	let range = Range::Yourself(Some(Aoe::Cone(Distance::Feet(15))));
+	assert_eq!(range.to_string_with(Some(DistanceConversion::GridMeters)), "Self (4.5-meter cone)");
+	assert_eq!(range.to_string_with(Some(DistanceConversion::Squares)), "Self (3-square cone)");
+	let range = Range::Dist(Distance::Miles(1));
+	assert_eq!(range.to_string_with(Some(DistanceConversion::GridMeters)), "1.5 kilometers");
+	assert_eq!(range.to_string_with(Some(DistanceConversion::Squares)), "1 mile");
+	// Spellbooks display converted ranges
+	let spell = spells::Spell::from_json_file("spells/players_handbook_2024/fireball.json").unwrap();
+	assert_eq!(spell.get_range_text(Some(DistanceConversion::GridMeters)), "45 meters");
+	let page_content = |distance_conversion: Option<DistanceConversion>|
+	{
+		let additional_options = AdditionalOptions { distance_conversion: distance_conversion, ..Default::default() };
+		let bytes = small_spellbook(&vec![spell.clone()], &additional_options).save_to_bytes().unwrap();
+		let doc = lopdf::Document::load_mem(&bytes).unwrap();
+		doc.get_page_content(doc.get_pages()[&2]).unwrap()
+	};
+	assert_ne!(page_content(None), page_content(Some(DistanceConversion::GridMeters)));
+}
+
+// Makes sure spells can have either of two casting times
+#[test]
+fn either_casting_time()
+{
+	use spells::CastingTime;
+	let casting_time = CastingTime::Either(Box::new(CastingTime::Actions(1)), Box::new(CastingTime::Hours(8)));
+	assert_eq!(casting_time.to_string(), "Action or 8 hours");
+	// Either casting times can be read from spell files
+	let json = r#"{ "Either": [ { "Actions": 1 }, { "Hours": 8 } ] }"#;
+	assert_eq!(serde_json::from_str::<CastingTime>(json).unwrap(), casting_time);
+	// Spells can be filtered by any of their casting times
+	assert_eq!(casting_time.alternatives(), vec![&CastingTime::Actions(1), &CastingTime::Hours(8)]);
+	assert!(casting_time.matches_any(|casting_time| matches!(casting_time, CastingTime::Hours(_))));
+	assert!(!casting_time.matches_any(|casting_time| matches!(casting_time, CastingTime::BonusAction(_))));
+	let nested = CastingTime::Either(Box::new(CastingTime::BonusAction(None)), Box::new(casting_time.clone()));
+	assert_eq!(nested.alternatives().len(), 3);
+	// Rituals still get added to the end
+	let mut spell = spells::Spell::from_json_file("spells/players_handbook_2024/fireball.json").unwrap();
+	spell.casting_time = spells::SpellField::Controlled(casting_time);
+	spell.is_ritual = true;
+	assert_eq!(spell.get_casting_time_text(), "Action or 8 hours or Ritual");
+	// Virtual tabletops get the first casting time
+	let item = vtt_export::export_spell(&spell, vtt_export::VttFormat::FoundryVtt, &UpcastOptions::default());
+	assert_eq!(item["system"]["activation"]["type"], "action");
+}
+
+// Makes sure durations that are rolled or last until dispelled or triggered work
+#[test]
+fn rolled_and_triggered_durations()
+{
+	use spells::{DiceExpr, Duration, TimeUnit};
+	let dice = DiceExpr { count: 1, sides: 4 };
+	assert_eq!(Duration::Rolled(dice, TimeUnit::Hours, false).to_string(), "1d4 hours");
+	assert_eq!(Duration::Rolled(dice, TimeUnit::Rounds, true).to_string(), "Concentration, up to 1d4 rounds");
+	// Dice get read from and written to spell files as strings
+	let duration = serde_json::from_str::<Duration>(r#"{ "Rolled": ["2d6", "Minutes", false] }"#).unwrap();
+	assert_eq!(duration, Duration::Rolled(DiceExpr { count: 2, sides: 6 }, TimeUnit::Minutes, false));
+	assert_eq!(serde_json::to_string(&duration).unwrap(), r#"{"Rolled":["2d6","Minutes",false]}"#);
+	assert!(serde_json::from_str::<Duration>(r#"{ "Rolled": ["d6", "Minutes", false] }"#).is_err());
+	assert!(serde_json::from_str::<Duration>(r#"{ "Rolled": ["2d0", "Minutes", false] }"#).is_err());
+	// Spell files with the old name still work
+	let duration = serde_json::from_str::<Duration>(r#"{ "DispelledOrTriggered": false }"#).unwrap();
+	assert_eq!(duration, Duration::UntilDispelledOrTriggered(false));
+	assert_eq!(duration.to_string(), "Until dispelled or triggered");
+	let spell = spells::Spell::from_json_file("spells/players_handbook_2014/glyph_of_warding.json").unwrap();
+	assert_eq!(spell.duration, spells::SpellField::Controlled(Duration::UntilDispelledOrTriggered(false)));
+	// Virtual tabletops get dice formulas
+	let mut spell = spell;
+	spell.duration = spells::SpellField::Controlled(Duration::Rolled(dice, TimeUnit::Weeks, true));
+	let item = vtt_export::export_spell(&spell, vtt_export::VttFormat::FoundryVtt, &UpcastOptions::default());
+	assert_eq!(item["system"]["duration"]["value"], "1d4 * 7");
+	assert_eq!(item["system"]["duration"]["units"], "day");
+}
+
+// Makes sure a diagram of the area of effect gets drawn after the range of spells with a range of self and an area
+#[test]
+fn aoe_glyphs()
+{
+	let spell_list = vec!
+	[
+		spells::Spell::from_json_file("spells/players_handbook_2024/burning_hands.json").unwrap(),
+		spells::Spell::from_json_file("spells/players_handbook_2024/fire_bolt.json").unwrap()
+	];
+	// Gets the number of stroke and fill operations on each page of a spellbook
+	let shape_counts = |aoe_glyphs: Option<AoeGlyphOptions>|
+	{
+		let additional_options = AdditionalOptions { aoe_glyphs: aoe_glyphs, ..Default::default() };
+		let bytes = small_spellbook(&spell_list, &additional_options).save_to_bytes().unwrap();
+		let doc = lopdf::Document::load_mem(&bytes).unwrap();
+		doc.get_pages().into_values()
+			.map(|page_id| lopdf::content::Content::decode(&doc.get_page_content(page_id).unwrap()).unwrap())
+			.map(|content|
+			{
+				let count = |operators: &[&str]| content.operations.iter()
+					.filter(|operation| operators.contains(&operation.operator.as_str()))
+					.count();
+				(count(&["S", "s"]), count(&["f", "f*"]))
+			})
+			.collect::<Vec<_>>()
+	};
+	let without_glyphs = shape_counts(None);
+	let outlined = shape_counts(Some(AoeGlyphOptions::default()));
+	let filled = shape_counts(Some(AoeGlyphOptions { filled: true, ..Default::default() }));
+	// Only the spell with a cone gets a diagram
+	assert_eq!(outlined[1], (without_glyphs[1].0 + 1, without_glyphs[1].1));
+	assert_eq!(filled[1], (without_glyphs[1].0, without_glyphs[1].1 + 1));
+	for page in [0, 2]
+	{
+		assert_eq!(outlined[page], without_glyphs[page]);
+		assert_eq!(filled[page], without_glyphs[page]);
+	}
+}
+
+// Makes sure spell components can be shown as shorthand, icons, or full sentences
+#[test]
+fn component_styles()
+{
+	use spells::ComponentStyle;
+	let mut spell = spells::Spell::from_json_file("spells/players_handbook_2024/fireball.json").unwrap();
+	assert_eq!(spell.get_component_text(ComponentStyle::Shorthand), "V, S, M (a ball of bat guano and sulfur)");
+	assert_eq!(spell.get_component_text(ComponentStyle::Icons), "V, S, M (a ball of bat guano and sulfur)");
+	assert_eq!
+	(
+		spell.get_component_text(ComponentStyle::Verbose),
+		"Verbal and somatic components, plus a material component: a ball of bat guano and sulfur"
+	);
+	spell.has_s_component = false;
+	assert_eq!
+	(
+		spell.get_component_text(ComponentStyle::Verbose),
+		"Verbal component, plus a material component: a ball of bat guano and sulfur"
+	);
+	spell.has_v_component = false;
+	assert_eq!
+	(
+		spell.get_component_text(ComponentStyle::Verbose),
+		"A material component: a ball of bat guano and sulfur"
+	);
+	spell.m_components = None;
+	assert_eq!(spell.get_component_text(ComponentStyle::Verbose), "None");
+	spell.has_s_component = true;
+	assert_eq!(spell.get_component_text(ComponentStyle::Verbose), "Somatic component");
+	// Each component gets a circle around it when components are shown as icons
+	let spell_list = vec!
+	[
+		spells::Spell::from_json_file("spells/players_handbook_2024/fireball.json").unwrap(),
+		spells::Spell::from_json_file("spells/players_handbook_2024/fire_bolt.json").unwrap()
+	];
+	let stroke_counts = |component_style: ComponentStyle|
+	{
+		let additional_options = AdditionalOptions { component_style: component_style, ..Default::default() };
+		let bytes = small_spellbook(&spell_list, &additional_options).save_to_bytes().unwrap();
+		let doc = lopdf::Document::load_mem(&bytes).unwrap();
+		doc.get_pages().into_values()
+			.map(|page_id| lopdf::content::Content::decode(&doc.get_page_content(page_id).unwrap()).unwrap())
+			.map(|content| content.operations.iter().filter(|operation| operation.operator == "s").count())
+			.collect::<Vec<_>>()
+	};
+	let shorthand = stroke_counts(ComponentStyle::Shorthand);
+	let icons = stroke_counts(ComponentStyle::Icons);
+	assert_eq!(icons[0], shorthand[0]);
+	assert_eq!(icons[1], shorthand[1] + 3);
+	assert_eq!(icons[2], shorthand[2] + 2);
+}
+
+// Makes sure spell files can have text for other locales that gets used when spells are read in that locale
+#[test]
+fn spell_locales()
+{
+	let folder = std::env::temp_dir().join(format!("dnd_spellbook_maker_locale_test_{}", std::process::id()));
+	fs::create_dir_all(&folder).unwrap();
+	let file_path = folder.join("fireball.json").to_str().unwrap().to_string();
+	let fireball = spells::Spell::from_json_file("spells/players_handbook_2024/fireball.json").unwrap();
+	let mut spell_json = serde_json::to_value(&fireball).unwrap();
+	spell_json["name@de"] = serde_json::json!("Feuerball");
+	spell_json["description@de"] = serde_json::json!("Ein heller Blitz.");
+	spell_json["description@de-AT"] = serde_json::json!("Ein heller Blitz aus Wien.");
+	fs::write(&file_path, spell_json.to_string()).unwrap();
+	// The default text gets used without a locale or for locales the file doesn't have
+	assert_eq!(spells::Spell::from_json_file(&file_path).unwrap(), fireball);
+	assert_eq!(spells::Spell::from_json_file_with_locale(&file_path, "fr").unwrap(), fireball);
+	// Fields without text for the locale stay the same
+	let german = spells::Spell::from_json_file_with_locale(&file_path, "de").unwrap();
+	assert_eq!(german.name, "Feuerball");
+	assert_eq!(german.description, "Ein heller Blitz.");
+	assert_eq!(german.tables, fireball.tables);
+	// Locales with regions fall back to the language
+	let austrian = spells::Spell::from_json_file_with_locale(&file_path, "de-AT").unwrap();
+	assert_eq!(austrian.name, "Feuerball");
+	assert_eq!(austrian.description, "Ein heller Blitz aus Wien.");
+	assert_eq!(spells::Spell::from_json_file_with_locale(&file_path, "de-CH").unwrap(), german);
+	// Folders and spell databases can be read in a locale
+	let spell_list = get_all_spells_in_folder_with_locale(folder.to_str().unwrap(), "de").unwrap();
+	assert_eq!(spell_list, vec![german.clone()]);
+	let mut database = SpellDatabase::with_locale("de");
+	database.add_folder(folder.to_str().unwrap()).unwrap();
+	assert_eq!(database.get("feuerball").unwrap(), Some(&german));
+	assert!(database.get("fireball").unwrap().is_none());
+	fs::remove_dir_all(&folder).unwrap();
+}
+
+// Makes sure tables can load their cells from csv files next to spell files
+#[test]
+fn csv_tables()
+{
+	let folder = std::env::temp_dir().join(format!("dnd_spellbook_maker_csv_test_{}", std::process::id()));
+	fs::create_dir_all(folder.join("tables")).unwrap();
+	let file_path = folder.join("wild_surge.json").to_str().unwrap().to_string();
+	let csv = concat!
+	(
+		"\u{FEFF}d100,Effect\r\n",
+		"01-02,\"Roll on this table, again\"\r\n\r\n",
+		"03-04, \"You say \"\"hi\"\"\n loudly\"\n",
+		"05,Nothing"
+	);
+	fs::write(folder.join("tables/wild_surge.csv"), csv).unwrap();
+	let mut spell = spells::Spell::from_json_file("spells/players_handbook_2024/fireball.json").unwrap();
+	spell.tables = Vec::new();
+	let mut spell_json = serde_json::to_value(&spell).unwrap();
+	spell_json["tables"] = serde_json::json!([{ "title": "Wild Surge", "cells_csv": "tables/wild_surge.csv" }]);
+	fs::write(&file_path, spell_json.to_string()).unwrap();
+	// The first row becomes the column labels when the table doesn't have any
+	let expected = spells::Table
+	{
+		title: String::from("Wild Surge"),
+		column_labels: vec![String::from("d100"), String::from("Effect")],
+		cells: vec!
+		[
+			vec![String::from("01-02"), String::from("Roll on this table, again")],
+			vec![String::from("03-04"), String::from("You say \"hi\"\n loudly")],
+			vec![String::from("05"), String::from("Nothing")]
+		]
+	};
+	assert_eq!(spells::Spell::from_json_file(&file_path).unwrap().tables, vec![expected.clone()]);
+	let mut database = SpellDatabase::new();
+	database.add_folder(folder.to_str().unwrap()).unwrap();
+	assert_eq!(database.get("fireball").unwrap().unwrap().tables, vec![expected]);
+	// Tables with column labels use every row as cells
+	spell_json["tables"][0]["column_labels"] = serde_json::json!(["Roll", "Effect"]);
+	fs::write(&file_path, spell_json.to_string()).unwrap();
+	assert_eq!(spells::Spell::from_json_file(&file_path).unwrap().tables[0].cells.len(), 4);
+	// Quoted cells keep their spaces while unquoted cells get trimmed
+	fs::write(folder.join("tables/wild_surge.csv"), " 1 ,\"  Spaced out  \" \n").unwrap();
+	let cells = spells::Spell::from_json_file(&file_path).unwrap().tables[0].cells.clone();
+	assert_eq!(cells, vec![vec![String::from("1"), String::from("  Spaced out  ")]]);
+	// Text after the closing quote of a cell and rows with the wrong number of cells are errors that name the row
+	fs::write(folder.join("tables/wild_surge.csv"), "1,Fine\n2,\"a\"b,\n").unwrap();
+	let error = spells::Spell::from_json_file(&file_path).unwrap_err();
+	assert_eq!(error.downcast_ref::<spells::CsvTableError>().unwrap().message,
+		"the row on line 2 has text after the closing quote of a cell");
+	fs::write(folder.join("tables/wild_surge.csv"), "1,Fine\n\n2,Too,Many\n").unwrap();
+	let error = spells::Spell::from_json_file(&file_path).unwrap_err();
+	assert_eq!(error.downcast_ref::<spells::CsvTableError>().unwrap().message,
+		"the row on line 3 has 3 cells but there are 2 column labels");
+	fs::write(folder.join("tables/wild_surge.csv"), csv).unwrap();
+	// Missing files, unfinished quotes, and tables with both kinds of cells are errors
+	spell_json["tables"][0]["cells"] = serde_json::json!([]);
+	fs::write(&file_path, spell_json.to_string()).unwrap();
+	assert!(spells::Spell::from_json_file(&file_path).unwrap_err().downcast_ref::<spells::CsvTableError>().is_some());
+	spell_json["tables"][0].as_object_mut().unwrap().remove("cells");
+	fs::write(folder.join("tables/wild_surge.csv"), "1,\"Never ends").unwrap();
+	fs::write(&file_path, spell_json.to_string()).unwrap();
+	let error = spells::Spell::from_json_file(&file_path).unwrap_err();
+	assert_eq!(error.downcast_ref::<spells::CsvTableError>().unwrap().message, "a quoted cell never ends");
+	fs::remove_file(folder.join("tables/wild_surge.csv")).unwrap();
+	assert!(spells::Spell::from_json_file(&file_path).unwrap_err().downcast_ref::<spells::CsvTableError>().is_some());
+	fs::remove_dir_all(&folder).unwrap();
+}
+
+// Makes sure that diffing two folders of spells finds the spells that were added, removed, and changed
+#[test]
+fn diff_folders()
+{
+	let folder = std::env::temp_dir().join(format!("dnd_spellbook_maker_diff_test_{}", std::process::id()));
+	let old_folder = folder.join("old");
+	let new_folder = folder.join("new");
+	fs::create_dir_all(&old_folder).unwrap();
+	fs::create_dir_all(&new_folder).unwrap();
+	let read_spell = |name: &str|
+		spells::Spell::from_json_file(&format!("spells/players_handbook_2024/{}.json", name)).unwrap();
+	let write_spell = |folder: &std::path::Path, spell: &spells::Spell|
+	{
+		let file_name = format!("{}.json", spell.name.to_lowercase().replace(' ', "_"));
+		spell.to_json_file(folder.join(file_name).to_str().unwrap(), false).unwrap();
+	};
+	let fireball = read_spell("fireball");
+	let fire_bolt = read_spell("fire_bolt");
+	let mut changed_fire_bolt = fire_bolt.clone();
+	changed_fire_bolt.description = String::from("A fiery mote.");
+	changed_fire_bolt.source = Some(String::from("HB"));
+	let light = read_spell("light");
+	write_spell(&old_folder, &fireball);
+	write_spell(&old_folder, &fire_bolt);
+	write_spell(&old_folder, &light);
+	write_spell(&new_folder, &fireball);
+	write_spell(&new_folder, &changed_fire_bolt);
+	let burning_hands = read_spell("burning_hands");
+	write_spell(&new_folder, &burning_hands);
+	let diff = spells::diff_folders(old_folder.to_str().unwrap(), new_folder.to_str().unwrap()).unwrap();
+	assert_eq!(diff.added, vec![burning_hands]);
+	assert_eq!(diff.removed, vec![light]);
+	assert_eq!(diff.changed.len(), 1);
+	assert_eq!(diff.changed[0].name, fire_bolt.name);
+	// Fields that weren't in the old spell count as null
+	let fields: Vec<(&str, &str)> = diff.changed[0].fields.iter()
+		.map(|change| (change.field.as_str(), change.old.as_str()))
+		.collect();
+	let old_description = serde_json::to_string(&fire_bolt.description).unwrap();
+	assert_eq!(fields, vec![("description", old_description.as_str()), ("source", "null")]);
+	assert_eq!(diff.changed[0].fields[1].new, "\"HB\"");
+	assert!(!diff.is_empty());
+	assert_eq!(diff.to_string(), "+ Burning Hands\n- Light\n~ Fire Bolt (description, source)\n");
+	// Folders with the same spells have no differences
+	let same = spells::diff_folders(old_folder.to_str().unwrap(), old_folder.to_str().unwrap()).unwrap();
+	assert!(same.is_empty());
+	fs::remove_dir_all(&folder).unwrap();
+}
+
+// Makes sure previews only have some of the spells or pages but keep the same page numbers
+#[test]
+fn spellbook_previews()
+{
+	let spell_list = vec!
+	[
+		spells::Spell::from_json_file("spells/players_handbook_2024/fire_bolt.json").unwrap(),
+		spells::Spell::from_json_file("spells/players_handbook_2024/alarm.json").unwrap(),
+		spells::Spell::from_json_file("spells/players_handbook_2024/augury.json").unwrap(),
+		spells::Spell::from_json_file("spells/players_handbook_2024/acid_splash.json").unwrap()
+	];
+	// Makes a spellbook and returns the page number of each of its pages
+	let page_numbers = |preview: Option<PreviewOptions>|
+	{
+		let pages = Arc::new(Mutex::new(Vec::new()));
+		let hook_pages = pages.clone();
+		let mut additional_options = AdditionalOptions
+		{
+			reference_appendix: Some(ReferenceAppendixOptions::default()),
+			preview: preview,
+			..Default::default()
+		};
+		additional_options.page_hooks.on_page_created(move |page| hook_pages.lock().unwrap().push(page.page_number));
+		small_spellbook(&spell_list, &additional_options);
+		let page_numbers = pages.lock().unwrap().clone();
+		page_numbers
+	};
+	let full = page_numbers(None);
+	// A page for each spell and then the reference pages (the title page doesn't get a page number)
+	assert!(full.len() > spell_list.len());
+	// Skipped spells still count towards the page numbers
+	let some_spells = page_numbers(Some(PreviewOptions { spells: Some(2..4), ..Default::default() }));
+	assert_eq!(some_spells, full[2..].to_vec());
+	// Nothing gets added after the page limit (which includes the title page)
+	let some_pages = page_numbers(Some(PreviewOptions { max_pages: Some(3), ..Default::default() }));
+	assert_eq!(some_pages, full[..2].to_vec());
+	let preview = PreviewOptions { spells: Some(1..3), max_pages: Some(2) };
+	assert!(!preview.includes_spell(0) && preview.includes_spell(2));
+	assert_eq!(page_numbers(Some(preview)), vec![full[1]]);
+}
+
+// Makes sure layout caches remember spells between spellbooks and give previews the real page counts of spells
+#[test]
+fn layout_cache()
+{
+	let mut spell_list = vec!
+	[
+		spells::Spell::from_json_file("spells/players_handbook_2024/fire_bolt.json").unwrap(),
+		spells::Spell::from_json_file("spells/players_handbook_2024/glyph_of_warding.json").unwrap(),
+		spells::Spell::from_json_file("spells/players_handbook_2024/alarm.json").unwrap()
+	];
+	// Make the middle spell take up two pages
+	spell_list[1].description = [spell_list[1].description.as_str(); 3].join("\n");
+	let cache = LayoutCache::new();
+	// Makes a spellbook and returns the page number of each of its pages
+	let page_numbers = |spell_list: &Vec<spells::Spell>, preview: Option<PreviewOptions>|
+	{
+		let pages = Arc::new(Mutex::new(Vec::new()));
+		let hook_pages = pages.clone();
+		let mut additional_options = AdditionalOptions
+		{
+			compact_density: Some(CompactDensityOptions::default()),
+			preview: preview,
+			layout_cache: Some(cache.clone()),
+			..Default::default()
+		};
+		additional_options.page_hooks.on_page_created(move |page| hook_pages.lock().unwrap().push(page.page_number));
+		small_spellbook(spell_list, &additional_options);
+		let page_numbers = pages.lock().unwrap().clone();
+		page_numbers
+	};
+	assert!(cache.is_empty());
+	let full = page_numbers(&spell_list, None);
+	assert_eq!(full.len(), 4);
+	assert_eq!(cache.len(), 3);
+	// Spells left out of previews take up the same number of pages they did before
+	let preview = page_numbers(&spell_list, Some(PreviewOptions { spells: Some(2..3), ..Default::default() }));
+	assert_eq!(preview, vec![*full.last().unwrap()]);
+	assert_eq!(cache.len(), 3);
+	// Changed spells get measured again
+	spell_list[0].description = String::from("Changed.");
+	page_numbers(&spell_list, None);
+	assert_eq!(cache.len(), 4);
+	cache.clear();
+	assert!(cache.is_empty());
+	assert_eq!(cache, cache.clone());
+	assert_ne!(cache, LayoutCache::new());
+}
+
+// Makes sure the options and spells that go into spellbooks can be sent to other threads to make spellbooks there
+#[test]
+fn spellbooks_on_other_threads()
+{
+	fn assert_send_sync<T: Send + Sync>() {}
+	assert_send_sync::<spells::Spell>();
+	assert_send_sync::<AdditionalOptions>();
+	assert_send_sync::<FontPaths>();
+	assert_send_sync::<PageNumberOptions>();
+	assert_send_sync::<SpellDatabase>();
+	assert_send_sync::<LayoutCache>();
+	let spell_list = vec![spells::Spell::from_json_file("spells/players_handbook_2024/fire_bolt.json").unwrap()];
+	let page_count = Arc::new(Mutex::new(0));
+	let hook_page_count = page_count.clone();
+	let mut additional_options = AdditionalOptions { layout_cache: Some(LayoutCache::new()), ..Default::default() };
+	additional_options.page_hooks.on_page_created(move |_| *hook_page_count.lock().unwrap() += 1);
+	let thread_options = additional_options.clone();
+	let bytes = std::thread::spawn(move ||
+	{
+		small_spellbook(&spell_list, &thread_options).save_to_bytes()
+	})
+	.join()
+	.unwrap()
+	.unwrap();
+	assert!(bytes.starts_with(b"%PDF"));
+	assert_eq!(*page_count.lock().unwrap(), 1);
+	// Errors can be sent back from other threads too
+	let result = std::thread::spawn(||
+	{
+		let (mut font_paths, font_sizes, font_scalars, spacing_options, text_colors, page_size_options, _, _, _,
+			table_options) = default_spellbook_options();
+		font_paths.regular = String::from("fonts/missing.otf");
+		create_accessible_spellbook
+		(
+			"Large Print Spellbook",
+			&Vec::new(),
+			font_paths,
+			font_sizes,
+			font_scalars,
+			spacing_options,
+			text_colors,
+			page_size_options,
+			None,
+			table_options,
+			&AdditionalOptions::default(),
+			&AccessibilityPreset::default()
+		)
+		.map(|spellbook| spellbook.pages.len())
+	})
+	.join()
+	.unwrap();
+	assert!(result.is_err());
+	let result = std::thread::spawn(|| get_all_spells_in_folder("spells/missing_folder")).join().unwrap();
+	assert!(result.is_err());
+}
+
+// Makes sure spellbooks have tracing spans for each spell, table, and page and events for warnings
+#[cfg(feature = "tracing")]
+#[test]
+fn tracing_spans()
+{
+	use tracing::{Event, Metadata, Subscriber};
+	use tracing::span::{Attributes, Id, Record};
+
+	// Keeps track of the name of each span and the fields of each event
+	struct RecordingSubscriber
+	{
+		span_names: Arc<Mutex<Vec<String>>>,
+		event_count: Arc<Mutex<usize>>
+	}
+	impl Subscriber for RecordingSubscriber
+	{
+		fn enabled(&self, _: &Metadata) -> bool { true }
+		fn new_span(&self, span: &Attributes) -> Id
+		{
+			let mut span_names = self.span_names.lock().unwrap();
+			span_names.push(span.metadata().name().to_string());
+			Id::from_u64(span_names.len() as u64)
+		}
+		fn record(&self, _: &Id, _: &Record) {}
+		fn record_follows_from(&self, _: &Id, _: &Id) {}
+		fn event(&self, event: &Event)
+		{
+			if *event.metadata().level() == tracing::Level::WARN { *self.event_count.lock().unwrap() += 1; }
+		}
+		fn enter(&self, _: &Id) {}
+		fn exit(&self, _: &Id) {}
+	}
+	let span_names = Arc::new(Mutex::new(Vec::new()));
+	let event_count = Arc::new(Mutex::new(0));
+	let subscriber = RecordingSubscriber { span_names: span_names.clone(), event_count: event_count.clone() };
+	let spell_list = vec!
+	[
+		spells::Spell::from_json_file("spells/players_handbook_2024/confusion.json").unwrap(),
+		spells::Spell::from_json_file("spells/players_handbook_2024/fire_bolt.json").unwrap()
+	];
+	let additional_options = AdditionalOptions
+	{
+		limits: Some(SpellLimits { max_description_length: 400, ..Default::default() }),
+		..Default::default()
+	};
+	tracing::subscriber::with_default(subscriber, || { small_spellbook(&spell_list, &additional_options); });
+	let span_names = span_names.lock().unwrap();
+	let count = |name: &str| span_names.iter().filter(|span_name| *span_name == name).count();
+	assert_eq!(count("create_spellbook"), 1);
+	assert_eq!(count("add_spell"), 2);
+	assert_eq!(count("write_table"), 1);
+	assert_eq!(count("make_new_page"), 2);
+	// Only Confusion has a description that's too long
+	assert_eq!(*event_count.lock().unwrap(), 1);
+}
+
+// Makes sure fingerprints stay the same for the same spellbook and go on their own page after the title page
+#[test]
+fn spellbook_fingerprints()
+{
+	let mut spell_list = vec!
+	[
+		spells::Spell::from_json_file("spells/players_handbook_2024/fire_bolt.json").unwrap(),
+		spells::Spell::from_json_file("spells/players_handbook_2024/alarm.json").unwrap()
+	];
+	let mut additional_options = AdditionalOptions
+	{
+		school_colors: Some(SchoolColorOptions::default()),
+		fingerprint: Some(FingerprintOptions::default()),
+		..Default::default()
+	};
+	let spellbook = small_spellbook(&spell_list, &additional_options);
+	let fingerprint = spellbook.fingerprint.clone().unwrap();
+	assert_eq!(fingerprint.len(), 16);
+	assert!(fingerprint.chars().all(|c| c.is_ascii_hexdigit()));
+	// Title page, fingerprint page, and a page for each spell
+	assert_eq!(spellbook.pages.len(), 4);
+	// Layout caches don't change the fingerprint
+	additional_options.layout_cache = Some(LayoutCache::new());
+	assert_eq!(small_spellbook(&spell_list, &additional_options).fingerprint, Some(fingerprint.clone()));
+	// Changing a spell does
+	spell_list[1].description.push_str(" Changed.");
+	assert_ne!(small_spellbook(&spell_list, &additional_options).fingerprint, Some(fingerprint));
+	// No fingerprint or fingerprint page by default
+	let spellbook = small_spellbook(&spell_list, &AdditionalOptions::default());
+	assert_eq!(spellbook.fingerprint, None);
+	assert_eq!(spellbook.pages.len(), 3);
+}
+
+// Makes sure override files replace fields of spells in spell databases and report conflicts between each other
+#[test]
+fn spell_overrides()
+{
+	let folder = std::env::temp_dir().join(format!("dnd_spellbook_maker_override_test_{}", std::process::id()));
+	fs::create_dir_all(&folder).unwrap();
+	fs::write
+	(
+		folder.join("a_errata.json"),
+		r#"[{"name": "Fireball", "description": "Fixed.", "level": {"Controlled": "Level2"}},
+			{"name": "Alarm", "is_ritual": false}]"#
+	).unwrap();
+	fs::write(folder.join("b_errata.json"), r#"{"name": "fireball", "description": "Fixed again."}"#).unwrap();
+	let mut database = SpellDatabase::with_overrides(folder.to_str().unwrap()).unwrap();
+	// Only the description of fireball gets replaced by both files
+	assert_eq!(database.override_conflicts().len(), 1);
+	let conflict = &database.override_conflicts()[0];
+	assert_eq!(conflict.field, "description");
+	assert!(conflict.replaced_file_path.ends_with("a_errata.json"));
+	assert!(conflict.used_file_path.ends_with("b_errata.json"));
+	database.add_folder("spells/players_handbook_2024").unwrap();
+	// Overrides change the headers and the rest of the spell
+	let fireball = database.get("Fireball").unwrap().unwrap();
+	assert_eq!(fireball.description, "Fixed again.");
+	assert_eq!(fireball.level, spells::SpellField::Controlled(spells::Level::Level2));
+	assert!(database.headers_with_level(spells::Level::Level2).any(|header| header.name == "Fireball"));
+	assert!(!database.get("Alarm").unwrap().unwrap().is_ritual);
+	// Spells without overrides stay the same
+	let augury = spells::Spell::from_json_file("spells/players_handbook_2024/augury.json").unwrap();
+	assert_eq!(database.get("Augury").unwrap(), Some(&augury));
+	// Spells added directly get their overrides too
+	let mut database = SpellDatabase::with_overrides(folder.to_str().unwrap()).unwrap();
+	database.add_spells(vec![spells::Spell::from_json_file("spells/players_handbook_2024/alarm.json").unwrap()]);
+	assert!(!database.get("Alarm").unwrap().unwrap().is_ritual);
+	// Overrides need the name of the spell they change
+	fs::write(folder.join("c_errata.json"), r#"{"description": "No name."}"#).unwrap();
+	assert!(SpellDatabase::with_overrides(folder.to_str().unwrap()).is_err());
+	fs::remove_dir_all(&folder).unwrap();
+}
+
+// Makes sure random spell selections only pick matching spells and pick the same ones for the same seed
+#[test]
+fn random_spell_selections()
+{
+	let database = SpellDatabase::from_folder("spells/players_handbook_2024").unwrap();
+	let spec = spells::RandomSpec
+	{
+		levels: Some(vec![spells::Level::Level1, spells::Level::Level2]),
+		schools: Some(vec![spells::MagicSchool::Evocation]),
+		rarities: None,
+		count: 4,
+		seed: 7
+	};
+	let selection = spells::random_selection(&database, &spec).unwrap();
+	assert_eq!(selection.len(), 4);
+	for spell in &selection
+	{
+		assert!(spell.level == spells::SpellField::Controlled(spells::Level::Level1) ||
+			spell.level == spells::SpellField::Controlled(spells::Level::Level2));
+		assert_eq!(spell.school, spells::SpellField::Controlled(spells::MagicSchool::Evocation));
+	}
+	// The same seed always picks the same spells
+	assert_eq!(spells::random_selection(&database, &spec).unwrap(), selection);
+	let other_seeds: Vec<_> = (0..8).map(|seed|
+	{
+		spells::random_selection(&database, &spells::RandomSpec { seed: seed, ..spec.clone() }).unwrap()
+	}).collect();
+	assert!(other_seeds.iter().any(|other_selection| *other_selection != selection));
+	// Every matching spell gets picked if there aren't enough of them
+	let every_spell = database.filter(|header| spec.matches(header)).unwrap();
+	let all = spells::random_selection(&database, &spells::RandomSpec { count: 1000, ..spec.clone() }).unwrap();
+	assert_eq!(all, every_spell);
+	// Selections can be made into spellbooks
+	small_spellbook(&selection, &AdditionalOptions::default());
+}
+
+// Makes sure spell rarities can be read from spell files, filtered and sorted by, and written in tags
+#[test]
+fn spell_rarities()
+{
+	// Rarities can also be written as "availability" and signature spells as "Unique"
+	let mut spell_json = serde_json::to_value
+	(spells::Spell::from_json_file("spells/players_handbook_2024/fire_bolt.json").unwrap()).unwrap();
+	spell_json["availability"] = serde_json::json!("Unique");
+	let fire_bolt: spells::Spell = serde_json::from_value(spell_json).unwrap();
+	assert_eq!(fire_bolt.rarity, Some(spells::Rarity::Signature));
+	assert_eq!(serde_json::to_value(&fire_bolt).unwrap()["rarity"], "Signature");
+	assert_eq!(spells::Rarity::try_from("UNCOMMON"), Ok(spells::Rarity::Uncommon));
+	assert!(spells::Rarity::try_from("legendary").is_err());
+	let with_rarity = |file_path: &str, rarity: Option<spells::Rarity>| spells::Spell
+	{
+		rarity: rarity,
+		..spells::Spell::from_json_file(file_path).unwrap()
+	};
+	let spell_list = vec!
+	[
+		with_rarity("spells/players_handbook_2024/fireball.json", Some(spells::Rarity::Rare)),
+		fire_bolt,
+		with_rarity("spells/players_handbook_2024/alarm.json", None),
+		with_rarity("spells/players_handbook_2024/augury.json", Some(spells::Rarity::Common))
+	];
+	// Spells without a rarity get sorted first
+	let mut sorted_list = spell_list.clone();
+	sorted_list.sort_by_key(spells::Spell::rarity_sort_key);
+	let sorted_names: Vec<_> = sorted_list.iter().map(|spell| spell.name.as_str()).collect();
+	assert_eq!(sorted_names, vec!["Alarm", "Augury", "Fireball", "Fire Bolt"]);
+	// Spell databases can look spells up by rarity
+	let mut database = SpellDatabase::new();
+	database.add_spells(spell_list.clone());
+	let rare_names: Vec<_> = database.headers_with_rarity(spells::Rarity::Rare).map(|header| &header.name).collect();
+	assert_eq!(rare_names, vec!["Fireball"]);
+	let spec = spells::RandomSpec
+	{
+		rarities: Some(vec![spells::Rarity::Common, spells::Rarity::Signature]),
+		count: 10,
+		..Default::default()
+	};
+	let selection = spells::random_selection(&database, &spec).unwrap();
+	let selected_names: Vec<_> = selection.iter().map(|spell| spell.name.as_str()).collect();
+	assert_eq!(selected_names, vec!["Fire Bolt", "Augury"]);
+	// Each spell with a rarity gets a tag on its page
+	let additional_options = AdditionalOptions
+	{
+		search_index: Some(SearchIndexOptions::default()),
+		..Default::default()
+	};
+	let spellbook = small_spellbook(&spell_list, &additional_options);
+	let search_index = spellbook.search_index.clone().unwrap();
+	assert_eq!(search_index.pages("rare"), search_index.pages("fireball")[..1]);
+	assert_eq!(search_index.pages("signature").len(), 1);
+	assert!(search_index.pages("uncommon").is_empty());
+	// The tag's text is smaller than the text around it
+	let bytes = spellbook.save_to_bytes().unwrap();
+	let doc = lopdf::Document::load_mem(&bytes).unwrap();
+	let page_ids: Vec<_> = doc.get_pages().into_values().collect();
+	let tag_counts: Vec<usize> = page_ids.iter().map(|page_id|
+	{
+		let content = doc.get_and_decode_page_content(*page_id).unwrap();
+		content.operations.iter()
+			.filter(|operation| operation.operator == "Tf" && operation.operands[1].as_float().unwrap() < 9.0)
+			.count()
+	})
+	.collect();
+	assert_eq!(tag_counts.iter().filter(|count| **count > 0).count(), 3);
+}
+
+// Makes sure the scribing cost appendix adds up the time and gold of each spell
+#[test]
+fn scribing_costs()
+{
+	let spell_list = vec!
+	[
+		spells::Spell::from_json_file("spells/players_handbook_2024/fire_bolt.json").unwrap(),
+		spells::Spell::from_json_file("spells/players_handbook_2024/alarm.json").unwrap(),
+		spells::Spell::from_json_file("spells/players_handbook_2024/fireball.json").unwrap()
+	];
+	let table = ScribingCostOptions::default().table(&spell_list);
+	assert_eq!(table.cells.len(), 4);
+	// Cantrips don't cost anything
+	assert_eq!(table.cells[0][2], "\u{2014}");
+	assert_eq!(table.cells[1][2..], [String::from("2 hours"), String::from("50 gp")]);
+	assert_eq!(table.cells[3], vec!["Total", "", "8 hours", "200 gp"]);
+	let options = ScribingCostOptions { hours_per_level: 0.5, gold_per_level: 500, ..Default::default() };
+	assert_eq!(options.table(&spell_list).cells[3][2..], [String::from("2 hours"), String::from("2,000 gp")]);
+	// The appendix gets its own page
+	let additional_options = AdditionalOptions { scribing_costs: Some(options), ..Default::default() };
+	let with_costs = small_spellbook(&spell_list, &additional_options).pages.len();
+	assert_eq!(with_costs, small_spellbook(&spell_list, &AdditionalOptions::default()).pages.len() + 1);
+}
+
+// Makes sure the level plan lists the class level each spell becomes available at along with its page
+#[test]
+fn level_plan()
+{
+	// Spell levels become available at the class levels in the spell slot tables (and mystic arcanum for warlocks)
+	let ninth = spells::Level::Level9;
+	assert_eq!(CasterProgression::Full.class_level_for(&spells::Level::Cantrip), Some(1));
+	assert_eq!(CasterProgression::Full.class_level_for(&spells::Level::Level3), Some(5));
+	assert_eq!(CasterProgression::Full.class_level_for(&ninth), Some(17));
+	assert_eq!(CasterProgression::Half.class_level_for(&spells::Level::Level3), Some(9));
+	assert_eq!(CasterProgression::Half.class_level_for(&spells::Level::Level6), None);
+	assert_eq!(CasterProgression::Pact.class_level_for(&spells::Level::Level5), Some(9));
+	assert_eq!(CasterProgression::Pact.class_level_for(&spells::Level::Level7), Some(13));
+	assert_eq!(CasterProgression::Pact.class_level_for(&ninth), Some(17));
+	assert_eq!(CasterProgression::Custom(vec![0, 1, 2]).class_level_for(&ninth), None);
+	assert_eq!(CasterProgression::Custom(vec![0, 1, 2]).max_spell_level(20), 2);
+	let mut spell_list = vec!
+	[
+		spells::Spell::from_json_file("spells/players_handbook_2024/fireball.json").unwrap(),
+		spells::Spell::from_json_file("spells/players_handbook_2024/fire_bolt.json").unwrap(),
+		spells::Spell::from_json_file("spells/players_handbook_2024/alarm.json").unwrap()
+	];
+	spell_list[0].classes = vec![String::from("Wizard")];
+	spell_list[1].classes = vec![String::from("Wizard"), String::from("Sorcerer")];
+	let page_numbers = vec![String::from("2"), String::from("3"), String::from("4")];
+	let table = LevelPlanOptions::default().table(&spell_list, &page_numbers);
+	let rows: Vec<_> = table.cells.iter().map(|row| (row[0].as_str(), row[1].as_str(), row[3].as_str())).collect();
+	assert_eq!(rows, vec![("1st", "Fire Bolt", "3"), ("1st", "Alarm", "4"), ("5th", "Fireball", "2")]);
+	// Only the class's spells are listed when there's a class
+	let options = LevelPlanOptions
+	{
+		class: Some(String::from("wizard")),
+		progression: CasterProgression::Half,
+		..Default::default()
+	};
+	let table = options.table(&spell_list, &page_numbers);
+	assert_eq!(table.cells.len(), 2);
+	assert_eq!(table.cells[1][0], "9th");
+	// The appendix gets its own page with the page each spell starts on
+	let additional_options = AdditionalOptions
+	{
+		level_plan: Some(options),
+		search_index: Some(SearchIndexOptions::default()),
+		..Default::default()
+	};
+	let spellbook = small_spellbook(&spell_list, &additional_options);
+	let search_index = spellbook.search_index.clone().unwrap();
+	let plan_page = spellbook.pages.len() - 1;
+	assert_eq!(search_index.pages("progression"), vec![plan_page]);
+	assert_eq!(spellbook.pages.len(), small_spellbook(&spell_list, &AdditionalOptions::default()).pages.len() + 1);
+}
+
+// Makes sure the component list lists each material component once with its cost and the spells that need it
+#[test]
+fn component_list()
+{
+	let mut spell_list = vec!
+	[
+		spells::Spell::from_json_file("spells/players_handbook_2024/identify.json").unwrap(),
+		spells::Spell::from_json_file("spells/players_handbook_2024/find_familiar.json").unwrap(),
+		spells::Spell::from_json_file("spells/players_handbook_2024/fireball.json").unwrap(),
+		spells::Spell::from_json_file("spells/players_handbook_2024/fire_bolt.json").unwrap()
+	];
+	// Costs in gold pieces get parsed and the part about the spell consuming the component gets taken off
+	let incense = spell_list[1].material_component().unwrap();
+	assert_eq!(incense.description, "burning incense worth 10+ GP");
+	assert_eq!(incense.cost, Some(10));
+	assert!(incense.consumed);
+	spell_list[3].m_components = Some(String::from("a jacinth worth 1,100+ GP, which the spell consumes"));
+	assert_eq!(spell_list[3].material_component().unwrap().cost, Some(1100));
+	assert_eq!(spell_list[2].material_component().unwrap().cost, None);
+	// Components written the same way are only listed once
+	spell_list[3].m_components = Some(String::from("A Pearl worth 100+ GP"));
+	let table = ComponentListOptions::default().table(&spell_list);
+	let rows: Vec<_> = table.cells.iter().map(|row| (row[1].as_str(), row[2].as_str(), row[3].as_str())).collect();
+	assert_eq!(rows, vec!
+	[
+		("a ball of bat guano and sulfur", "\u{2014}", "Fireball"),
+		("a pearl worth 100+ GP", "100 gp", "Identify, Fire Bolt"),
+		("burning incense worth 10+ GP", "10 gp (consumed)", "Find Familiar"),
+		("Total", "110 gp", "")
+	]);
+	// Components without costs can be left off
+	let options = ComponentListOptions { include_free_components: false, ..Default::default() };
+	assert_eq!(options.table(&spell_list).cells.len(), 3);
+	// The appendix gets its own page
+	let additional_options = AdditionalOptions { component_list: Some(options), ..Default::default() };
+	let with_list = small_spellbook(&spell_list, &additional_options).pages.len();
+	assert_eq!(with_list, small_spellbook(&spell_list, &AdditionalOptions::default()).pages.len() + 1);
+}
+
+// Makes sure the damage tiers of cantrips get picked out of their text and put in a table under them
+#[test]
+fn cantrip_scaling()
+{
+	let spell = |path: &str| spells::Spell::from_json_file(path).unwrap();
+	let fire_bolt = spell("spells/players_handbook_2024/fire_bolt.json");
+	let tiers = vec![(1, "1d10"), (5, "2d10"), (11, "3d10"), (17, "4d10")];
+	let expected: Vec<(u8, String)> = tiers.into_iter().map(|(level, damage)| (level, String::from(damage))).collect();
+	// The 2024 spells have it in their upcast description and the 2014 spells have it in their description
+	assert_eq!(fire_bolt.cantrip_damage_tiers(), Some(expected.clone()));
+	assert_eq!(spell("spells/players_handbook_2014/fire_bolt.json").cantrip_damage_tiers(), Some(expected));
+	let toll_the_dead = spell("spells/xanathars_guide_to_everything/toll_the_dead.json").cantrip_damage_tiers().unwrap();
+	assert_eq!(toll_the_dead[0], (1, String::from("1d8 or 1d12")));
+	// Cantrips that don't increase their damage and leveled spells don't have tiers
+	assert_eq!(spell("spells/players_handbook_2024/eldritch_blast.json").cantrip_damage_tiers(), None);
+	assert_eq!(spell("spells/players_handbook_2024/light.json").cantrip_damage_tiers(), None);
+	assert_eq!(spell("spells/players_handbook_2024/fireball.json").cantrip_damage_tiers(), None);
+	// Each tier goes up to the level before the next one
+	let table = CantripScalingOptions::default().table(&fire_bolt.cantrip_damage_tiers().unwrap());
+	assert_eq!(table.cells[0], vec![String::from("1\u{2013}4"), String::from("1d10")]);
+	assert_eq!(table.cells[3], vec![String::from("17\u{2013}20"), String::from("4d10")]);
+	// The table gets written on the spell's page
+	let spell_list = vec![fire_bolt];
+	let search_pages = |cantrip_scaling: Option<CantripScalingOptions>|
+	{
+		let additional_options = AdditionalOptions
+		{
+			cantrip_scaling: cantrip_scaling,
+			search_index: Some(SearchIndexOptions::default()),
+			..Default::default()
+		};
+		small_spellbook(&spell_list, &additional_options).search_index.unwrap().pages("character")
+	};
+	assert!(search_pages(None).is_empty());
+	assert_eq!(search_pages(Some(CantripScalingOptions::default())), vec![1]);
+}
+
+// Makes sure page references get the page numbers of the tables and sections they point to and link to them
+#[test]
+fn page_references()
+{
+	let mut spell_list = vec!
+	[
+		spells::Spell::from_json_file("spells/players_handbook_2024/fireball.json").unwrap(),
+		spells::Spell::from_json_file("spells/players_handbook_2024/control_weather.json").unwrap()
+	];
+	spell_list[0].description = String::from("Gusts quagmire ([ref:wind]) and costs ([ref:Scribing_Costs]).");
+	spell_list[0].upcast_description = None;
+	let additional_options = AdditionalOptions
+	{
+		scribing_costs: Some(ScribingCostOptions::default()),
+		search_index: Some(SearchIndexOptions::default()),
+		..Default::default()
+	};
+	let spellbook = small_spellbook(&spell_list, &additional_options);
+	let search_index = spellbook.search_index.clone().unwrap();
+	let bytes = spellbook.save_to_bytes().unwrap();
+	let doc = lopdf::Document::load_mem(&bytes).unwrap();
+	let page_ids: Vec<_> = doc.get_pages().into_values().collect();
+	let reference_page = search_index.pages("quagmire")[0];
+	let wind_page = search_index.pages("wind").into_iter().max().unwrap();
+	// The tags get replaced with text and the page numbers link to the pages they're the numbers of
+	assert!(search_index.pages("see").contains(&reference_page));
+	assert!(search_index.pages("ref").is_empty());
+	let annotations = get_annotations(&bytes);
+	let destinations: Vec<_> = annotations[reference_page].iter()
+		.map(|annotation| annotation.get(b"Dest").unwrap().as_array().unwrap()[0].as_reference().unwrap())
+		.collect();
+	assert_eq!(destinations, vec![page_ids[wind_page], page_ids[page_ids.len() - 1]]);
+	// References to anchors that aren't in the spellbook are an error
+	spell_list[0].description = String::from("See the chart ([ref:missing-chart]).");
+	let (font_paths, font_sizes, font_scalars, spacing_options, text_colors, page_size_options, _, _, _,
+	table_options) = default_spellbook_options();
+	let result = create_spellbook_with_options
+	(
+		"Broken Spellbook",
+		&spell_list,
+		font_paths,
+		font_sizes,
+		font_scalars,
+		spacing_options,
+		text_colors,
+		page_size_options,
+		None,
+		None,
+		table_options,
+		&AdditionalOptions::default()
+	);
+	let error = result.err().unwrap().downcast::<PageReferenceError>().unwrap();
+	assert_eq!(error.0, vec![String::from("missing-chart")]);
+}
+
+// Makes sure spells, tables, and sections get named destinations that go to the pages they start on
+#[test]
+fn named_destinations()
+{
+	let spell_list = vec!
+	[
+		spells::Spell::from_json_file("spells/players_handbook_2024/chill_touch.json").unwrap(),
+		spells::Spell::from_json_file("spells/players_handbook_2024/control_weather.json").unwrap()
+	];
+	let additional_options = AdditionalOptions
+	{
+		scribing_costs: Some(ScribingCostOptions::default()),
+		named_destinations: true,
+		..Default::default()
+	};
+	let bytes = small_spellbook(&spell_list, &additional_options).save_to_bytes().unwrap();
+	let doc = lopdf::Document::load_mem(&bytes).unwrap();
+	let page_ids: Vec<_> = doc.get_pages().into_values().collect();
+	let catalog = doc.catalog().unwrap();
+	let names = catalog.get(b"Names").unwrap().as_dict().unwrap();
+	let dests_id = names.get(b"Dests").unwrap().as_reference().unwrap();
+	let dests = doc.get_dictionary(dests_id).unwrap().get(b"Names").unwrap().as_array().unwrap();
+	let destinations: Vec<_> = dests.chunks(2).map(|pair|
+	(
+		String::from_utf8(pair[0].as_str().unwrap().to_vec()).unwrap(),
+		page_ids.iter().position(|id| *id == pair[1].as_array().unwrap()[0].as_reference().unwrap()).unwrap()
+	))
+	.collect();
+	// The names are sorted and each one goes to the page its spell, table, or section starts on
+	let names: Vec<_> = destinations.iter().map(|(name, _)| name.as_str()).collect();
+	assert_eq!(names, vec!["chill-touch", "control-weather", "precipitation", "scribing-costs", "temperature", "wind"]);
+	assert_eq!(destinations[0].1, 1);
+	assert_eq!(destinations[3].1, page_ids.len() - 1);
+	assert!(destinations[1].1 <= destinations[2].1 && destinations[2].1 <= destinations[5].1);
+	// Without named destinations, the document doesn't get any
+	let bytes = small_spellbook(&spell_list, &AdditionalOptions::default()).save_to_bytes().unwrap();
+	let doc = lopdf::Document::load_mem(&bytes).unwrap();
+	assert!(doc.catalog().unwrap().get(b"Names").is_err());
+}
+
+// Makes sure the accessibility preset makes the text bigger and scales the spacing that depends on it
+#[test]
+fn accessibility_preset()
+{
+	let (font_paths, font_sizes, font_scalars, spacing_options, text_colors, page_size_options, page_number_options, _,
+	_, table_options) = default_spellbook_options();
+	let preset = AccessibilityPreset { text_scale: 1.5, line_spacing: 1.2, margin_scale: 2.0, font: None };
+	let large_sizes = preset.font_sizes(&font_sizes);
+	assert_eq!(large_sizes.body_font_size(), 18.0);
+	assert_eq!(large_sizes.title_font_size(), font_sizes.title_font_size());
+	let large_spacing = preset.spacing_options(&spacing_options);
+	assert!((large_spacing.body_newline_amount() - 9.0).abs() < 0.001);
+	assert_eq!(large_spacing.body_tab_amount(), 11.25);
+	assert_eq!(preset.table_options(&table_options).horizontal_cell_margin(), 15.0);
+	assert_eq!(preset.page_number_options(&page_number_options).font_size(), 18.0);
+	// Margins get wider but never take up more than a quarter of the page
+	let large_page = preset.page_size_options(&page_size_options);
+	assert_eq!(large_page.left_margin(), 20.0);
+	let wide_margins = PageSizeOptions::new(210.0, 297.0, 40.0, 40.0, 40.0, 40.0).unwrap();
+	assert_eq!(preset.page_size_options(&wide_margins).left_margin(), 52.5);
+	// The large print spellbook takes up more pages and can use its own font
+	let spell_list = vec!
+	[
+		spells::Spell::from_json_file("spells/players_handbook_2024/glyph_of_warding.json").unwrap(),
+		spells::Spell::from_json_file("spells/players_handbook_2024/fireball.json").unwrap()
+	];
+	let dyslexia_font = FontPaths
+	{
+		bold: String::new(),
+		italic: String::new(),
+		bold_italic: String::new(),
+		..font_paths.clone()
+	};
+	let preset = AccessibilityPreset { font: Some((dyslexia_font, font_scalars)), ..Default::default() };
+	let spellbook = create_accessible_spellbook
+	(
+		"Large Print Spellbook",
+		&spell_list,
+		font_paths,
+		font_sizes,
+		font_scalars,
+		spacing_options,
+		text_colors,
+		page_size_options,
+		Some(page_number_options),
+		table_options,
+		&AdditionalOptions::default(),
+		&preset
+	).unwrap();
+	assert!(spellbook.pages.len() > small_spellbook(&spell_list, &AdditionalOptions::default()).pages.len());
+}
+
+// Makes sure encrypted spellbooks need the user password to be read and only have the permissions they allow
+#[test]
+fn encrypted_spellbooks()
+{
+	let spell_list = vec![spells::Spell::from_json_file("spells/players_handbook_2024/fire_bolt.json").unwrap()];
+	let encryption = EncryptionOptions
+	{
+		user_password: String::from("reader"),
+		owner_password: String::from("dungeon master"),
+		..Default::default()
+	};
+	let additional_options = AdditionalOptions { encryption: Some(encryption.clone()), ..Default::default() };
+	let spellbook = small_spellbook(&spell_list, &additional_options);
+	assert_eq!(spellbook.encryption, Some(encryption));
+	let bytes = spellbook.save_to_bytes().unwrap();
+	let mut doc = lopdf::Document::load_mem(&bytes).unwrap();
+	assert!(doc.is_encrypted());
+	let permissions = doc.get_encrypted().unwrap().get(b"P").unwrap().as_i64().unwrap();
+	// Printing is allowed but copying isn't
+	assert_ne!(permissions & (1 << 2), 0);
+	assert_eq!(permissions & (1 << 4), 0);
+	let page_id = *doc.get_pages().values().last().unwrap();
+	// Page contents can't be read until the document is decrypted with the user password
+	let encrypted_content = doc.get_page_content(page_id).unwrap();
+	assert!(lopdf::encryption::get_encryption_key(&doc, "wrong", true).is_err());
+	doc.decrypt("reader").unwrap();
+	assert_ne!(doc.get_page_content(page_id).unwrap(), encrypted_content);
+	let operations = doc.get_and_decode_page_content(page_id).unwrap().operations;
+	assert!(operations.iter().any(|operation| operation.operator == "BT"));
+	// Spellbooks aren't encrypted by default
+	let bytes = small_spellbook(&spell_list, &AdditionalOptions::default()).save_to_bytes().unwrap();
+	assert!(!lopdf::Document::load_mem(&bytes).unwrap().is_encrypted());
+}
+
+// Makes sure merged spellbooks have the pages, bookmarks, and fonts of every spellbook
+#[test]
+fn merged_spellbooks()
+{
+	let fire_bolt = spells::Spell::from_json_file("spells/players_handbook_2024/fire_bolt.json").unwrap();
+	let fireball = spells::Spell::from_json_file("spells/players_handbook_2024/fireball.json").unwrap();
+	let spell_lists = [vec![fire_bolt.clone()], vec![fire_bolt, fireball]];
+	// Gets the pdf documents of a spellbook for each spell list
+	let docs = |additional_options: &AdditionalOptions| -> Vec<lopdf::Document>
+	{
+		spell_lists.iter().map(|spell_list|
+		{
+			let bytes = small_spellbook(spell_list, additional_options).save_to_bytes().unwrap();
+			lopdf::Document::load_mem(&bytes).unwrap()
+		})
+		.collect()
+	};
+	// Saves a document and loads it back
+	let reload = |mut doc: lopdf::Document|
+	{
+		let mut bytes = Vec::new();
+		doc.save_to(&mut bytes).unwrap();
+		lopdf::Document::load_mem(&bytes).unwrap()
+	};
+	let separate = docs(&AdditionalOptions::default());
+	let separate_stats: Vec<DocStats> = separate.iter().map(doc_stats).collect();
+	let merged = reload(merge_spellbooks(separate.clone(), &MergeOptions::default()).unwrap());
+	// The pages of each spellbook are in order
+	let merged_stats = doc_stats(&merged);
+	assert_eq!(merged_stats.page_count, separate_stats[0].page_count + separate_stats[1].page_count);
+	let page_texts = |doc: &lopdf::Document| -> Vec<String>
+	{
+		doc.get_pages().values().map(|page_id| format!("{:?}", doc.get_and_decode_page_content(*page_id).unwrap()))
+			.collect()
+	};
+	assert_eq!(page_texts(&merged), [page_texts(&separate[0]), page_texts(&separate[1])].concat());
+	// The same font files only get stored once
+	assert_eq!(merged_stats.total_size(ResourceKind::Font), separate_stats[1].total_size(ResourceKind::Font));
+	let options = MergeOptions { deduplicate_resources: false, ..Default::default() };
+	let duplicate_fonts = merge_spellbooks(separate.clone(), &options).unwrap();
+	assert!(doc_stats(&duplicate_fonts).total_size(ResourceKind::Font) > merged_stats.total_size(ResourceKind::Font));
+	// Each spellbook's bookmarks go under a bookmark that goes to its first page
+	let outlines = merged.get_dict_in_dict(merged.catalog().unwrap(), b"Outlines").unwrap();
+	let first_group = merged.get_dictionary(outlines.get(b"First").unwrap().as_reference().unwrap()).unwrap();
+	assert_eq!(first_group.get(b"Title").unwrap().as_str().unwrap(), b"Small Spellbook");
+	let second_group = merged.get_dictionary(first_group.get(b"Next").unwrap().as_reference().unwrap()).unwrap();
+	let page_ids: Vec<_> = merged.get_pages().values().copied().collect();
+	let destination_page = |bookmark: &lopdf::Dictionary|
+	{
+		let destination = bookmark.get(b"Dest").unwrap().as_array().unwrap()[0].as_reference().unwrap();
+		page_ids.iter().position(|page_id| *page_id == destination).unwrap()
+	};
+	assert_eq!(destination_page(second_group), separate_stats[0].page_count);
+	let last_bookmark = merged.get_dictionary(second_group.get(b"Last").unwrap().as_reference().unwrap()).unwrap();
+	assert_eq!(destination_page(last_bookmark), page_ids.len() - 1);
+	// Page numbers can start over at each spellbook
+	let options = MergeOptions { renumber_pages: false, group_bookmarks: false, ..Default::default() };
+	let merged = reload(merge_spellbooks(separate, &options).unwrap());
+	let labels = merged.catalog().unwrap().get(b"PageLabels").unwrap().as_dict().unwrap();
+	let starts: Vec<i64> = labels.get(b"Nums").unwrap().as_array().unwrap().iter()
+		.filter_map(|number| number.as_i64().ok()).collect();
+	assert_eq!(starts, vec![0, separate_stats[0].page_count as i64]);
+	// The named destinations of each spellbook go to the same pages, with names that the first spellbook already has
+	// getting the number of their spellbook in front
+	let merged = reload(merge_spellbooks(docs(&AdditionalOptions { named_destinations: true, ..Default::default() }),
+		&MergeOptions::default()).unwrap());
+	let page_ids: Vec<_> = merged.get_pages().values().copied().collect();
+	let names = merged.catalog().unwrap().get(b"Names").unwrap().as_dict().unwrap();
+	let dests_id = names.get(b"Dests").unwrap().as_reference().unwrap();
+	let dests = merged.get_dictionary(dests_id).unwrap().get(b"Names").unwrap().as_array().unwrap();
+	let destinations: Vec<_> = dests.chunks(2).map(|pair|
+	(
+		String::from_utf8(pair[0].as_str().unwrap().to_vec()).unwrap(),
+		page_ids.iter().position(|id| *id == pair[1].as_array().unwrap()[0].as_reference().unwrap()).unwrap()
+	))
+	.collect();
+	let first_book_pages = separate_stats[0].page_count;
+	assert_eq!
+	(
+		destinations,
+		vec!
+		[
+			(String::from("fire-bolt"), 1),
+			(String::from("fireball"), first_book_pages + 2),
+			(String::from("spellbook2-fire-bolt"), first_book_pages + 1)
+		]
+	);
+	// Password protected spellbooks can't be merged
+	let encryption = EncryptionOptions { user_password: String::from("reader"), ..Default::default() };
+	let encrypted = docs(&AdditionalOptions { encryption: Some(encryption), ..Default::default() });
+	assert!(merge_spellbooks(encrypted, &MergeOptions::default()).is_err());
+	assert!(merge_spellbooks(Vec::new(), &MergeOptions::default()).is_err());
+}
+
+// Makes sure search indexes have the pages that each word was written on
+#[test]
+fn search_indexes()
+{
+	let spell_list = vec!
+	[
+		spells::Spell::from_json_file("spells/players_handbook_2024/fire_bolt.json").unwrap(),
+		spells::Spell::from_json_file("spells/players_handbook_2024/fireball.json").unwrap()
+	];
+	let additional_options = AdditionalOptions
+	{
+		search_index: Some(SearchIndexOptions::default()),
+		..Default::default()
+	};
+	let spellbook = small_spellbook(&spell_list, &additional_options);
+	let search_index = spellbook.search_index.unwrap();
+	// The title page is 0 and each spell gets one page
+	assert_eq!(search_index.pages("Fireball"), vec![2]);
+	assert_eq!(search_index.pages("bolt"), vec![1]);
+	assert_eq!(search_index.pages("evocation"), vec![1, 2]);
+	// Ignored words and short words aren't in the index
+	assert!(search_index.pages("the").is_empty());
+	assert!(search_index.pages("of").is_empty());
+	let json: std::collections::HashMap<String, Vec<usize>> = serde_json::from_str(&search_index.to_json()).unwrap();
+	assert_eq!(json.len(), search_index.len());
+	assert_eq!(json["fireball"], vec![2]);
+	assert_eq!(small_spellbook(&spell_list, &AdditionalOptions::default()).search_index, None);
+}
+
+// Makes sure that creating valid spell files works
+#[test]
+fn create_spell_files()
+{
+	// Path to hand-made spell files that are compared to generated spells
+	let comparison_folder = String::from("spells/necronomicon/");
+
+	// Create the spells (necronomicon spell duplicates)
+	let hell_spell = spells::Spell
+	{
+		name: String::from("HELL SPELL AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA A A A A A A A A A A A AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA A A A A A A AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A"),
+		level: spells::SpellField::Custom(String::from("100TH-LEVEL")),
+		school: spells::SpellField::Custom(String::from("SUPER NECROMANCY")),
+		is_ritual: true,
+		casting_time: spells::SpellField::Controlled(spells::CastingTime::Reaction(Some(String::from("THAT YOU TAKE WHEN YOU FEEL LIKE CASTING SPELLS AND DOING MAGIC AND AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A")))),
+		range: spells::SpellField::Controlled(spells::Range::Yourself(Some(spells::Aoe::Cylinder(spells::Distance::Miles(63489), spells::Distance::Miles(49729))))),
+		has_v_component: true,
+		has_s_component: true,
+		m_components: Some(String::from("UNLIMITED POWAHHHHHHHHHHHHHHHHHHHHHHHHHHHHHHHHHHHHHHHHHHHHHHHHHHHHHHHHHHH H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H H")),
+		duration: spells::SpellField::Controlled(spells::Duration::Years(57394, true)),
+		description: String::from("<ib> CASTING SPELLS AND CONJURING ABOMINATIONS <b> AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA <r> THIS SPELL ISN'T FOR <i> weak underpowered feeble wizards -_-. <r> THIS SPELL IS FOR ONLY THE MOST POWERFUL OF ARCHMAGES AND NECROMANCERS WHO CAN WIELD THE MIGHTIEST OF <bi> ARCANE ENERGY <r> WITH THE FORTITUDE OF A <ib> MOUNTAIN. <b> A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A\nA A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A \\<r> A A A A A \\<b> A A A A A A A \\<i> A A A A A A A \\<bi> A A A A \\<ib> A A A A A \\\\<r> A A A A \\\\\\<b> A A A A \\\\\\\\<i> A A A A \\\\\\\\\\<bi> A A A A \\\\\\\\\\\\<ib> A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A\n\\[table][1]\n\\\\[table[0]\n\\\\\\[table][1]\n\\\\\\\\[table][0]\nA A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A\nA A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A\n[table][0]\nMORE MAGIC SPELLS AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A\nA A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A\nA A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A\n[table][1]\nYOU CAN'T HANDLE THIS SPELL A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A\nA A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A\nA A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A"),
+		upcast_description: Some(String::from("HELL ON EARTH")),
+		source: None,
+		classes: Vec::new(),
+		rarity: None,
+		layout: None,
+		flavor: None,
+		tables: vec!
+		[
+			spells::Table
+			{
+				title: String::from("A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A"),
+				column_labels: vec![String::from("COLUMN OF CHAOS"), String::from("COLUMN OF NECROMANCY")],
+				cells: vec!
+				[
+					vec!
+					[
+						String::from("A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A"),
+						String::from("A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A")
+					],
+					vec!
+					[
+						String::from("B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B"),
+						String::from("B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B"),
+						String::from("B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B")
+					],
+					vec!
+					[
+						String::from("POWER"),
+						String::from("WIZARDRY")
+					],
+					vec!
+					[
+						String::new(),
+						String::from("C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C"),
+						String::from("C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C")
+					]
+				]
+			},
+			spells::Table
+			{
+				title: String::from("THIS TABLE AGAIN A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A \\A \\\\A \\\\\\A \\<title> \\\\<title> \\\\\\<title> A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A"),
+				column_labels: vec![String::from("COLUMN OF CHAOS"), String::from("COLUMN OF NECROMANCY")],
+				cells: vec!
+				[
+					vec!
+					[
+						String::from("A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A"),
+						String::from("A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A A")
+					],
+					vec!
+					[
+						String::from("B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B"),
+						String::from("B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B"),
+						String::from("B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B B")
+					],
+					vec!
+					[
+						String::from("POWER"),
+						String::from("WIZARDRY")
+					],
+					vec!
+					[
+						String::new(),
+						String::from("C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C"),
+						String::from("C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C C")
+					]
+				]
+			}
+		]
+	};
+	let power_word_scrunch = spells::Spell
+	{
+		name: String::from("Power Word Scrunch"),
+		level: spells::SpellField::Controlled(spells::Level::Level9),
+		school: spells::SpellField::Controlled(spells::MagicSchool::Transmutation),
+		is_ritual: false,
+		casting_time: spells::SpellField::Controlled(spells::CastingTime::Actions(1)),
+		range: spells::SpellField::Controlled(spells::Range::Dist(spells::Distance::Feet(60))),
+		has_v_component: true,
+		has_s_component: false,
+		m_components: None,
+		duration: spells::SpellField::Controlled(spells::Duration::Instant),
+		description: String::from("Choose 1 target creature or object within range. That target gets scrunched.
+- Scrunching has these effects
+[table][0]
+- Scrunch balls (balls produced from scrunching) can be thrown and do 1d6 bludgeoning damage on hit.
+Scrunch ball funny lol."),
+		upcast_description: None,
+		source: None,
+		classes: Vec::new(),
+		rarity: None,
+		layout: None,
+		flavor: None,
+		tables: vec!
+		[
+			spells::Table
+			{
+				title: String::from("Scrunching Effects"),
+				column_labels: vec![String::from("Target"), String::from("Effect")],
+				cells: vec!
+				[
+					vec!
+					[
+						String::from("Creature"),
+						String::from("Flesh Ball")
+					],
+					vec!
+					[
+						String::from("Object"),
+						String::from("Ball of that object's material")
+					],
+					vec!
+					[
+						String::from("Creature not made of flesh"),
+						String::from("Ball of that creature's material")
+					]
+				]
+			}
+		]
+	};
+	let the_ten_hells = spells::Spell
+	{
+		name: String::from("The Ten Hells"),
+		level: spells::SpellField::Controlled(spells::Level::Level9),
+		school: spells::SpellField::Controlled(spells::MagicSchool::Necromancy),
+		is_ritual: true,
+		casting_time: spells::SpellField::Controlled(spells::CastingTime::Actions(1)),
+		range: spells::SpellField::Controlled(spells::Range::Yourself(Some(spells::Aoe::Sphere(spells::Distance::Feet(90))))),
+		has_v_component: true,
+		has_s_component: false,
+		m_components: Some(String::from("the nail or claw of a creature from an evil plane")),
+		duration: spells::SpellField::Controlled(spells::Duration::Instant),
+		description: String::from("Choose any number of creatures made of tangible matter within range. Those creatures must all make a constitution saving throw against your spell save DC. All creatures that fail this saving throw get turned inside out, immediately die, and have their souls eternally damned to all nine hells simultaneously.
+Creatures that succeed the saving throw take 20d4 scrunching damage."),
+		upcast_description: None,
+		source: None,
+		classes: Vec::new(),
+		rarity: None,
+		layout: None,
+		flavor: None,
+		tables: Vec::new()
+	};
+
+	// Create vec of test spells and their file names (without extension or path)
+	let spell_list = vec![(hell_spell, "hell_spell"), (power_word_scrunch, "power_word_scrunch"), (the_ten_hells, "the_ten_hells")];
+	// Test to make sure spell files can be created properly
+	json_file_test(&spell_list, false, "spells/tests/", &comparison_folder);
+}
+
+// Creates json files from a list of spells into the output folder and compares them to the same hand-crafted spells in the comparison folder
+fn json_file_test(spell_list: &Vec<(spells::Spell, &str)>, compress: bool, output_folder: &str, comparison_folder: &str)
+{
+	const FILE_EXTENSION: &str = ".json";
+
+	// If the output folder doesn't exist yet
+	if !Path::new(&output_folder).exists()
+	{
+		// Create it
+		fs::create_dir(&output_folder).unwrap();
+	}
+
+	// Create vec of file paths to each spell that is going to be generated
+	let mut spell_paths = Vec::with_capacity(spell_list.len());
+	for spell in spell_list
+	{
+		// Get file path to the spell that's about to be generated and add it to the vec
+		let spell_path = output_folder.to_owned() + spell.1 + FILE_EXTENSION;
+		spell_paths.push(spell_path.clone());
+		// Generate the json file for this spell
+		spell.0.to_json_file(&spell_path, compress).unwrap();
+	}
+
+	// Create a list of just the spells and the file names from the spell_list parameter
+	let real_spell_list: Vec<spells::Spell> = spell_list.into_iter().map(|(s, _)| s.clone()).collect();
+	// Read all of the generated spell files into spell objects and put them into a list
+	let test_spell_list = get_all_spells_in_folder(&output_folder).unwrap();
+	// Compare if the spell objects are the same
+	assert_eq!(real_spell_list, test_spell_list);
+
+	// Loop through spell file paths
+	for (spell, spell_path) in spell_list.iter().zip(spell_paths.iter())
+	{
+		// Read all of the bytes from the original spell that the generated one was based on
+		let real_spell_bytes = fs::read(&(comparison_folder.to_owned() + spell.1 + FILE_EXTENSION)).unwrap();
+		// Read all of the bytes from the generated spell file
+		let test_spell_bytes = fs::read(&spell_path).unwrap();
+		// Compare the bytes from both files to make sure they are the same
+		assert_eq!(real_spell_bytes, test_spell_bytes);
+	}
+}
+
+// Makes sure font files get checked for missing font variants and characters, and that characters a font doesn't
+// have get replaced with plainer text
+#[test]
+fn glyph_fallbacks()
+{
+	let (font_paths, ..) = default_spellbook_options();
+	assert!(font_report(&font_paths).unwrap().is_complete());
+	// Font variants without their own font file get reported along with how they get made
+	let single_font = FontPaths
+	{
+		regular: String::from("fonts/Cantarell/Cantarell-VF.otf"),
+		bold: String::new(),
+		italic: String::new(),
+		bold_italic: String::new(),
+		..font_paths
+	};
+	let report = font_report(&single_font).unwrap();
+	assert!(!report.is_complete());
+	assert_eq!(report.synthesized_variants.len(), 3);
+	assert_eq!(report.synthesized_variants[0].0, FontVariant::Bold);
+	assert_eq!(report.synthesized_variants[0].1, FontVariant::Regular);
+	assert!(report.synthesized_variants[2].2.bold && report.synthesized_variants[2].2.italic);
+	// Characters the font doesn't have get replaced and other text gets left alone
+	let fallbacks = crate::glyph_fallbacks::GlyphFallbacks(vec![('\u{2022}', "-"), ('\u{2014}', "--")]);
+	assert_eq!(fallbacks.apply("\u{2022} Burns\u{2014}things"), "- Burns--things");
+	assert!(matches!(fallbacks.apply("Fire Bolt"), std::borrow::Cow::Borrowed(_)));
+	// Fonts that have every character don't replace anything
+	let font_bytes = load_font_bytes(&font_paths.regular, None).unwrap();
+	let font = rusttype::Font::try_from_vec(font_bytes).unwrap();
+	let fallbacks = crate::glyph_fallbacks::GlyphFallbacks::for_font(&font);
+	assert_eq!(fallbacks.apply("\u{2022} Burns"), "\u{2022} Burns");
+}
+
+// Makes sure each spell can be saved as its own pdf file without a title page
+#[test]
+fn spell_pdf_export()
+{
+	let folder = std::env::temp_dir().join(format!("dnd_spellbook_maker_spell_pdf_test_{}", std::process::id()));
+	let folder_path = folder.to_str().unwrap();
+	let (font_paths, font_sizes, font_scalars, spacing_options, text_colors, page_size_options, page_number_options,
+		_, _, table_options) = default_spellbook_options();
+	let fire_bolt = spells::Spell::from_json_file("spells/players_handbook_2024/fire_bolt.json").unwrap();
+	let spell_list = vec![fire_bolt.clone(), fire_bolt.clone()];
+	// Leaving out the title page makes the spellbook start with its first spell
+	let additional_options = AdditionalOptions { skip_title_page: true, ..Default::default() };
+	let page_count = small_spellbook(&spell_list, &AdditionalOptions::default()).pages.len();
+	assert_eq!(small_spellbook(&spell_list, &additional_options).pages.len(), page_count - 1);
+	// Each spell gets its own file named after it (with a number at the end for spells with the same name)
+	let file_paths = export_spell_pdfs
+	(
+		&spell_list,
+		font_paths,
+		font_sizes,
+		font_scalars,
+		spacing_options,
+		text_colors,
+		page_size_options,
+		Some(page_number_options),
+		None,
+		table_options,
+		&AdditionalOptions::default(),
+		folder_path
+	).unwrap();
+	assert_eq!(file_paths, vec![format!("{}/fire_bolt.pdf", folder_path), format!("{}/fire_bolt_2.pdf", folder_path)]);
+	for file_path in &file_paths
+	{
+		let doc = lopdf::Document::load(file_path).unwrap();
+		assert_eq!(doc.get_pages().len(), 1);
+		let page_id = doc.get_pages()[&1];
+		let content = lopdf::content::Content::decode(&doc.get_page_content(page_id).unwrap()).unwrap();
+		assert!(content.operations.iter().any(|operation| operation.operator == "Tj" || operation.operator == "TJ"));
+	}
+	fs::remove_dir_all(&folder).unwrap();
+}
+
+// Makes sure spell slugs match how spell files are named and that file names are valid everywhere
+#[test]
+fn slugs_and_file_names()
+{
+	// Spell files are named after the slugs of their spells
+	for slug in ["antipathy_sympathy", "bigbys_hand", "leomunds_tiny_hut", "fire_bolt"]
+	{
+		let spell = spells::Spell::from_json_file(&format!("spells/players_handbook_2024/{}.json", slug)).unwrap();
+		assert_eq!(spell.slug(), slug);
+	}
+	let mut spell = spells::Spell::from_json_file("spells/players_handbook_2024/fire_bolt.json").unwrap();
+	spell.name = String::from("Tasha’s  Hideous Laughter!");
+	assert_eq!(spell.slug(), "tashas_hideous_laughter");
+	// Characters file names can't have get replaced and names Windows saves for devices get changed
+	assert_eq!(safe_filename("Antipathy / Sympathy"), "Antipathy _ Sympathy");
+	assert_eq!(safe_filename("What? <Now>: \"a\\b\"|*"), "What_ _Now__ _a_b___");
+	assert_eq!(safe_filename("Ends with dots.. "), "Ends with dots");
+	assert_eq!(safe_filename(""), "_");
+	assert_eq!(safe_filename("con"), "con_");
+	assert_eq!(safe_filename("NUL.txt"), "NUL.txt_");
+	assert_eq!(safe_filename("COM1"), "COM1_");
+	assert_eq!(safe_filename("COM10"), "COM10");
+	assert_eq!(safe_filename("Console"), "Console");
+	assert!(safe_filename(&"é".repeat(300)).len() <= 200);
+}
+
+// Makes sure spells saved to a folder get named after their slugs and read back the same
+#[test]
+fn save_spells_to_folder_round_trip()
+{
+	let folder = std::env::temp_dir().join(format!("dnd_spellbook_maker_spell_folder_test_{}", std::process::id()));
+	let folder_path = folder.to_str().unwrap();
+	let fire_bolt = spells::Spell::from_json_file("spells/players_handbook_2024/fire_bolt.json").unwrap();
+	let bigbys_hand = spells::Spell::from_json_file("spells/players_handbook_2024/bigbys_hand.json").unwrap();
+	let spell_list = vec![fire_bolt.clone(), bigbys_hand.clone(), fire_bolt.clone()];
+	let file_paths = save_spells_to_folder(&spell_list, folder_path, false).unwrap();
+	assert_eq!
+	(
+		file_paths,
+		vec!
+		[
+			format!("{}/fire_bolt.json", folder_path),
+			format!("{}/bigbys_hand.json", folder_path),
+			format!("{}/fire_bolt_2.json", folder_path)
+		]
+	);
+	let mut saved_spells = get_all_spells_in_folder(folder_path).unwrap();
+	saved_spells.sort_by(|a, b| a.name.cmp(&b.name));
+	assert_eq!(saved_spells, vec![bigbys_hand, fire_bolt.clone(), fire_bolt]);
+	fs::remove_dir_all(&folder).unwrap();
+}
+
+// Makes sure spells whose names have the same slug get saved to different files instead of overwriting each other
+#[test]
+fn save_spells_to_folder_same_slug()
+{
+	let folder = std::env::temp_dir().join(format!("dnd_spellbook_maker_same_slug_test_{}", std::process::id()));
+	let folder_path = folder.to_str().unwrap();
+	let mut spell_list = Vec::new();
+	for name in ["Fire Bolt", "Fire-Bolt!", "Fire Bolt 2"]
+	{
+		let mut spell = spells::Spell::from_json_file("spells/players_handbook_2024/fire_bolt.json").unwrap();
+		spell.name = String::from(name);
+		spell_list.push(spell);
+	}
+	let file_paths = save_spells_to_folder(&spell_list, folder_path, true).unwrap();
+	assert_eq!
+	(
+		file_paths,
+		vec!
+		[
+			format!("{}/fire_bolt.json", folder_path),
+			format!("{}/fire_bolt_2.json", folder_path),
+			format!("{}/fire_bolt_2_2.json", folder_path)
+		]
+	);
+	let mut saved_spells = get_all_spells_in_folder(folder_path).unwrap();
+	saved_spells.sort_by(|a, b| a.name.cmp(&b.name));
+	spell_list.sort_by(|a, b| a.name.cmp(&b.name));
+	assert_eq!(saved_spells, spell_list);
+	fs::remove_dir_all(&folder).unwrap();
+}
+
+// Makes sure spell checkers get spell text without tags and their words get mapped back to the spell's text
+#[test]
+fn spell_check_hook()
+{
+	let mut spell = spells::Spell::from_json_file("spells/players_handbook_2024/fire_bolt.json").unwrap();
+	spell.description = String::from
+	(
+		"<b> Fyre <r> bolt<fn>Or fyre.</fn> \\<i> é fyre\n[table][0]\n<secret>fyre</secret>"
+	);
+	spell.upcast_description = Some(String::from("<color=#8b0000> fyre </color>"));
+	let texts = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+	let checked_texts = texts.clone();
+	let spell_check = SpellCheckHook::new(move |text: &str|
+	{
+		checked_texts.lock().unwrap().push(String::from(text));
+		text.match_indices("yre").map(|(index, _)| SpellingSuggestion
+		{
+			start: index - 1,
+			end: index + 3,
+			suggestions: vec![String::from("fire")]
+		})
+		.collect()
+	});
+	let warnings = spell_check.check(&spell);
+	assert_eq!
+	(
+		*texts.lock().unwrap(),
+		vec![String::from(" Fyre  boltOr fyre. <i> é fyre\n\nfyre"), String::from(" fyre ")]
+	);
+	assert_eq!(warnings.len(), 5);
+	for warning in &warnings
+	{
+		let (field, word, start, end) = match warning
+		{
+			SpellbookWarning::PossibleMisspelling { field, word, start, end, .. } => (field, word, *start, *end),
+			_ => panic!("Expected a misspelling warning")
+		};
+		let text = if field == "description" { &spell.description } else { spell.upcast_description.as_ref().unwrap() };
+		assert_eq!(&text[start..end], word);
+	}
+	assert!(warnings[0].to_string().contains("Did you mean \"fire\"?"));
+	// Words the spell checker gives that aren't in the text are left out
+	let suggestion = SpellingSuggestion { start: 3, end: 1000, ..Default::default() };
+	let spell_check = SpellCheckHook::new(move |_: &str| vec![suggestion.clone()]);
+	assert!(spell_check.check(&spell).is_empty());
+	// Warnings get returned with the spellbook
+	let spell_check = SpellCheckHook::new(|text: &str| text.match_indices("Fire").map(|(index, word)| SpellingSuggestion
+	{
+		start: index,
+		end: index + word.len(),
+		suggestions: Vec::new()
+	})
+	.collect());
+	let additional_options = AdditionalOptions { spell_check: Some(spell_check), ..Default::default() };
+	let spell_list = vec![spells::Spell::from_json_file("spells/players_handbook_2024/fire_bolt.json").unwrap()];
+	let spellbook = small_spellbook(&spell_list, &additional_options);
+	assert!(!spellbook.warnings.is_empty());
+	assert!(spellbook.warnings.iter().all(|warning| warning.spell_name() == "Fire Bolt"));
+}
+
+// Makes sure paragraphs with the marker of a paragraph style get written in that style
+#[test]
+fn paragraph_styles()
+{
+	assert_eq!(spells::paragraph_class("::readaloud::"), Some("readaloud"));
+	assert_eq!(spells::paragraph_class("::::"), None);
+	assert_eq!(spells::paragraph_class("::a:b::"), None);
+	assert_eq!(spells::paragraph_class("readaloud"), None);
+	let mut spell = spells::Spell::from_json_file("spells/players_handbook_2024/fire_bolt.json").unwrap();
+	spell.description = String::from
+	(
+		"A normal paragraph.\n::readaloud:: A cold wind howls <color=#00ff00> through </color> the chamber.\n\
+		::unknown:: This marker gets written."
+	);
+	// Gets the fill colors and the number of rectangles on the spell's page
+	let page_content = |paragraph_styles: std::collections::BTreeMap<String, ParagraphStyle>|
+	{
+		let additional_options = AdditionalOptions { paragraph_styles: paragraph_styles, ..Default::default() };
+		let bytes = small_spellbook(&vec![spell.clone()], &additional_options).save_to_bytes().unwrap();
+		let doc = lopdf::Document::load_mem(&bytes).unwrap();
+		let content = lopdf::content::Content::decode(&doc.get_page_content(doc.get_pages()[&2]).unwrap()).unwrap();
+		let fill_colors: Vec<Vec<f32>> = content.operations.iter()
+			.filter(|operation| operation.operator == "rg")
+			.map(|operation| operation.operands.iter().map(|operand| operand.as_float().unwrap()).collect())
+			.collect();
+		let rect_count = content.operations.iter().filter(|operation| operation.operator == "re").count();
+		(fill_colors, rect_count)
+	};
+	let style = ParagraphStyle
+	{
+		font_variant: Some(FontVariant::Italic),
+		color: Some((255, 0, 0)),
+		indent: 3.0,
+		background_color: Some((0, 0, 255))
+	};
+	let (red, green, blue) = (vec![1.0, 0.0, 0.0], vec![0.0, 1.0, 0.0], vec![0.0, 0.0, 1.0]);
+	let (plain_colors, plain_rect_count) = page_content(Default::default());
+	assert!(!plain_colors.contains(&red) && !plain_colors.contains(&blue));
+	let (colors, rect_count) = page_content(std::collections::BTreeMap::from([(String::from("readaloud"), style)]));
+	// The box behind the paragraph gets filled in behind each of its lines
+	assert!(colors.contains(&blue));
+	assert!(rect_count > plain_rect_count);
+	// Text goes back to the color of the paragraph style after color tags
+	let green_index = colors.iter().position(|color| *color == green).unwrap();
+	assert!(colors[..green_index].contains(&red) && colors[green_index..].contains(&red));
+}
+
+// Makes sure layout caches reuse the layouts of unchanged spells and lay spells out again when options change
+#[test]
+fn layout_cache_options()
+{
+	let spell_list = vec!
+	[
+		spells::Spell::from_json_file("spells/players_handbook_2024/fire_bolt.json").unwrap(),
+		spells::Spell::from_json_file("spells/players_handbook_2024/alarm.json").unwrap()
+	];
+	let cache = LayoutCache::new();
+	let mut additional_options = AdditionalOptions
+	{
+		compact_density: Some(CompactDensityOptions::default()),
+		layout_cache: Some(cache.clone()),
+		..Default::default()
+	};
+	small_spellbook(&spell_list, &additional_options);
+	assert_eq!(cache.len(), 2);
+	// Making the same spellbook again reuses the layouts instead of adding new ones
+	let hits = cache.hits();
+	let first_bytes = small_spellbook(&spell_list, &additional_options).save_to_bytes().unwrap();
+	assert_eq!(cache.len(), 2);
+	assert!(cache.hits() > hits);
+	// Options that only give warnings don't change the layout
+	additional_options.limits = Some(SpellLimits::default());
+	small_spellbook(&spell_list, &additional_options);
+	assert_eq!(cache.len(), 2);
+	// Options that can move paragraphs to new pages do
+	additional_options.paragraph_breaks = Some(ParagraphBreakOptions::default());
+	small_spellbook(&spell_list, &additional_options);
+	assert_eq!(cache.len(), 4);
+	additional_options.paragraph_breaks = None;
+	additional_options.balance_columns = true;
+	small_spellbook(&spell_list, &additional_options);
+	assert_eq!(cache.len(), 6);
+	// Spellbooks come out the same with a cache as without one
+	additional_options.balance_columns = false;
+	additional_options.limits = None;
+	additional_options.layout_cache = None;
+	let uncached_bytes = small_spellbook(&spell_list, &additional_options).save_to_bytes().unwrap();
+	assert_eq!(first_bytes.len(), uncached_bytes.len());
+}
+
+// Makes sure spells added to a spellbook get unique bookmarks and warnings and can't go after back matter
+#[test]
+fn append_spells_checks()
+{
+	let fire_bolt = spells::Spell::from_json_file("spells/players_handbook_2024/fire_bolt.json").unwrap();
+	let additional_options = AdditionalOptions
+	{
+		limits: Some(SpellLimits { max_name_length: 3, ..Default::default() }),
+		..Default::default()
+	};
+	let mut spellbook = small_spellbook(&vec![fire_bolt.clone()], &additional_options);
+	assert_eq!(spellbook.warnings.len(), 1);
+	spellbook.append_spells(&vec![fire_bolt.clone()]).unwrap();
+	// The new spell gets checked against the limits too
+	assert_eq!(spellbook.warnings.len(), 2);
+	// Bookmarks of new spells aren't the same as the ones already in the spellbook
+	let bytes = spellbook.save_to_bytes().unwrap();
+	let doc = lopdf::Document::load_mem(&bytes).unwrap();
+	let outlines = doc.get_dictionary(doc.catalog().unwrap().get(b"Outlines").unwrap().as_reference().unwrap())
+		.unwrap();
+	let mut bookmark_names = Vec::new();
+	let mut bookmark = outlines.get(b"First").ok().map(|first| first.as_reference().unwrap());
+	while let Some(id) = bookmark
+	{
+		let dictionary = doc.get_dictionary(id).unwrap();
+		bookmark_names.push(String::from_utf8_lossy(dictionary.get(b"Title").unwrap().as_str().unwrap()).into_owned());
+		bookmark = dictionary.get(b"Next").ok().map(|next| next.as_reference().unwrap());
+	}
+	assert_eq!(bookmark_names, vec!["Title Page", "Fire Bolt", "Fire Bolt (2)"]);
+	// Spells can't be added after pages that come after the spells
+	let additional_options = AdditionalOptions
+	{
+		reference_appendix: Some(ReferenceAppendixOptions::default()),
+		..Default::default()
+	};
+	let mut spellbook = small_spellbook(&vec![fire_bolt.clone()], &additional_options);
+	let page_count = spellbook.pages.len();
+	let error = spellbook.append_spells(&vec![fire_bolt.clone()]).unwrap_err();
+	assert!(error.downcast_ref::<AppendSpellsError>().is_some());
+	assert_eq!(spellbook.pages.len(), page_count);
+	// Including a references section that spells added before got for their endnotes
+	let mut plain_fire_bolt = fire_bolt.clone();
+	plain_fire_bolt.source = None;
+	let mut noted_fire_bolt = plain_fire_bolt.clone();
+	noted_fire_bolt.description = String::from("Fire<fn>A note.</fn>");
+	let additional_options = AdditionalOptions { endnotes: Some(EndnoteOptions::default()), ..Default::default() };
+	let mut spellbook = small_spellbook(&vec![plain_fire_bolt.clone()], &additional_options);
+	let page_count = spellbook.pages.len();
+	spellbook.append_spells(&vec![noted_fire_bolt]).unwrap();
+	assert_eq!(spellbook.pages.len(), page_count + 2);
+	let page_count = spellbook.pages.len();
+	let error = spellbook.append_spells(&vec![plain_fire_bolt.clone()]).unwrap_err();
+	assert!(error.downcast_ref::<AppendSpellsError>().is_some());
+	assert_eq!(spellbook.pages.len(), page_count);
+	// Spellbooks without the writer that made them can't have spells added either
+	let mut spellbook = small_spellbook(&vec![plain_fire_bolt.clone()], &AdditionalOptions::default());
+	spellbook.state = None;
+	let error = spellbook.append_spells(&vec![plain_fire_bolt]).unwrap_err();
+	assert!(error.downcast_ref::<AppendSpellsError>().is_some());
 }
@@ -7,12 +7,36 @@
 use std::fs;
 use std::error::Error;
 
+// The longest file name `safe_filename()` gives (in bytes)
+const MAX_FILE_NAME_BYTES: usize = 200;
+
 pub use printpdf::{PdfDocumentReference, PdfLayerReference, PdfPageIndex};
+pub use image::DynamicImage;
 
 use crate::spellbook_writer::*;
 
 pub use crate::spells;
 pub use crate::spellbook_options::*;
+pub use crate::spellbook_output::{Spellbook, SpellbookWarning, AppendSpellsError};
+pub use crate::spellbook_hooks::*;
+pub use crate::spell_database::
+{
+	SpellDatabase,
+	SpellHeader,
+	OverrideConflict,
+	UnresolvedSpellNamesError,
+	InvalidOverrideFileError,
+	normalize_spell_name
+};
+pub use crate::doc_stats::{doc_stats, DocStats, ResourceStats, ResourceKind};
+pub use crate::layout_cache::{LayoutCache, SpellLayout};
+pub use crate::image_cache::ImageCache;
+pub use crate::search_index::SearchIndex;
+pub use crate::qr_codes::QrCodeError;
+pub use crate::hyphenation::{Hyphenator, HyphenationError};
+pub use crate::glyph_fallbacks::{font_report, FontReport, MissingGlyph, GLYPH_FALLBACKS};
+pub use crate::pdf_merging::{merge_spellbooks, MergeError};
+pub use crate::spellbook_gen_types::{PageReferenceError, SyntheticStyle};
 
 /// # Parameters
 ///
@@ -25,7 +49,7 @@ pub use crate::spellbook_options::*;
 /// - `page_size_options` Page width, height, and margin values.
 /// - `page_number_options` Settings for how page numbers look (`None` for no page numbers).
 /// - `background` An image filepath to use as backgrounds for each page and transform data to make it fit on
-/// the page the way you want.
+///   the page the way you want.
 /// - `table_options` Sizing and color options for tables in spell descriptions.
 ///
 /// # Output
@@ -46,7 +70,7 @@ pub fn create_spellbook
 	background: Option<(&str, ImageTransform)>,
 	table_options: TableOptions
 )
--> Result<(PdfDocumentReference, Vec<PdfLayerReference>, Vec<PdfPageIndex>), Box<dyn Error>>
+-> Result<(PdfDocumentReference, Vec<PdfLayerReference>, Vec<PdfPageIndex>), Box<dyn Error + Send + Sync>>
 {
 	SpellbookWriter::create_spellbook
 	(
@@ -60,10 +84,438 @@ pub fn create_spellbook
 		page_size_options,
 		page_number_options,
 		background,
-		table_options
+		table_options,
+		&AdditionalOptions::default()
+	)
+	.map(|spellbook| (spellbook.doc, spellbook.layers, spellbook.pages))
+}
+
+/// Same as `create_spellbook()` but with extra optional features (like a table of contents).
+///
+/// # Parameters
+///
+/// - `title` The title of the spellbook.
+/// - `font_paths` File paths to all of the font variants (regular, bold, italic, bold-italic).
+/// - `font_sizes` Font sizes for each type of text in the spellbook (except page numbers).
+/// - `font_scalars` Scalar values to make sure text width can be calculated correctly for each font variant.
+/// - `spacing_options` Tab size and newline sizes for each type of text (except page numbers).
+/// - `text_colors` The RGB color values for each type of text (except page numbers).
+/// - `page_size_options` Page width, height, and margin values.
+/// - `page_number_options` Settings for how page numbers look (`None` for no page numbers).
+/// - `background` An image filepath to use as backgrounds for each page and transform data to make it fit on
+///   the page the way you want.
+/// - `table_options` Sizing and color options for tables in spell descriptions.
+/// - `additional_options` Settings for optional features. `AdditionalOptions::default()` turns them all off.
+///
+/// # Output
+///
+/// - `Ok` Returns a `Spellbook` holding the PDF document. Use `Spellbook::save()` to save it so that features
+///   `printpdf` doesn't support (like links between pages) get added to the file.
+/// - `Err` Returns any errors that occured.
+#[allow(clippy::too_many_arguments)]
+pub fn create_spellbook_with_options
+(
+	title: &str,
+	spells: &Vec<spells::Spell>,
+	font_paths: FontPaths,
+	font_sizes: FontSizes,
+	font_scalars: FontScalars,
+	spacing_options: SpacingOptions,
+	text_colors: TextColorOptions,
+	page_size_options: PageSizeOptions,
+	page_number_options: Option<PageNumberOptions>,
+	background: Option<(&str, ImageTransform)>,
+	table_options: TableOptions,
+	additional_options: &AdditionalOptions
+)
+-> Result<Spellbook, Box<dyn Error + Send + Sync>>
+{
+	SpellbookWriter::create_spellbook
+	(
+		title,
+		spells,
+		font_paths,
+		font_sizes,
+		font_scalars,
+		spacing_options,
+		text_colors,
+		page_size_options,
+		page_number_options,
+		background,
+		table_options,
+		additional_options
+	)
+}
+
+/// Same as `create_spellbook_with_options()` but gets the spells by name from a spell database (like the list of
+/// spells on a character sheet).
+///
+/// # Parameters
+///
+/// - `title` The title of the spellbook.
+/// - `names` The names of the spells to put in the spellbook (in order).
+/// - `database` The spell database to get the spells from.
+/// - `font_paths` File paths to all of the font variants (regular, bold, italic, bold-italic).
+/// - `font_sizes` Font sizes for each type of text in the spellbook (except page numbers).
+/// - `font_scalars` Scalar values to make sure text width can be calculated correctly for each font variant.
+/// - `spacing_options` Tab size and newline sizes for each type of text (except page numbers).
+/// - `text_colors` The RGB color values for each type of text (except page numbers).
+/// - `page_size_options` Page width, height, and margin values.
+/// - `page_number_options` Settings for how page numbers look (`None` for no page numbers).
+/// - `background` An image filepath to use as backgrounds for each page and transform data to make it fit on
+///   the page the way you want.
+/// - `table_options` Sizing and color options for tables in spell descriptions.
+/// - `additional_options` Settings for optional features. `AdditionalOptions::default()` turns them all off.
+///
+/// # Output
+///
+/// - `Ok` Returns a `Spellbook` holding the PDF document.
+/// - `Err` Returns an `UnresolvedSpellNamesError` listing every name that isn't in the database, or any other errors
+///   that occured.
+#[allow(clippy::too_many_arguments)]
+pub fn create_spellbook_by_names
+(
+	title: &str,
+	names: &[&str],
+	database: &SpellDatabase,
+	font_paths: FontPaths,
+	font_sizes: FontSizes,
+	font_scalars: FontScalars,
+	spacing_options: SpacingOptions,
+	text_colors: TextColorOptions,
+	page_size_options: PageSizeOptions,
+	page_number_options: Option<PageNumberOptions>,
+	background: Option<(&str, ImageTransform)>,
+	table_options: TableOptions,
+	additional_options: &AdditionalOptions
+)
+-> Result<Spellbook, Box<dyn Error + Send + Sync>>
+{
+	let spell_list = database.get_all(names)?;
+	create_spellbook_with_options
+	(
+		title,
+		&spell_list,
+		font_paths,
+		font_sizes,
+		font_scalars,
+		spacing_options,
+		text_colors,
+		page_size_options,
+		page_number_options,
+		background,
+		table_options,
+		additional_options
+	)
+}
+
+/// Same as `create_spellbook_with_options()` but makes a DM edition and a player edition of the spellbook at once. The
+/// text between `<secret>` and `</secret>` tags in spell descriptions shows up like any other text in the DM edition
+/// and gets hidden in the player edition.
+///
+/// # Parameters
+///
+/// - `title` The title of the spellbook.
+/// - `font_paths` File paths to all of the font variants (regular, bold, italic, bold-italic).
+/// - `font_sizes` Font sizes for each type of text in the spellbook (except page numbers).
+/// - `font_scalars` Scalar values to make sure text width can be calculated correctly for each font variant.
+/// - `spacing_options` Tab size and newline sizes for each type of text (except page numbers).
+/// - `text_colors` The RGB color values for each type of text (except page numbers).
+/// - `page_size_options` Page width, height, and margin values.
+/// - `page_number_options` Settings for how page numbers look (`None` for no page numbers).
+/// - `background` An image filepath to use as backgrounds for each page and transform data to make it fit on
+///   the page the way you want.
+/// - `table_options` Sizing and color options for tables in spell descriptions.
+/// - `additional_options` Settings for optional features. The `redaction` setting gets ignored.
+/// - `redaction` How secrets get hidden in the player edition.
+///
+/// # Output
+///
+/// - `Ok` Returns the DM edition and then the player edition.
+/// - `Err` Returns any errors that occured.
+#[allow(clippy::too_many_arguments)]
+pub fn create_dm_and_player_spellbooks
+(
+	title: &str,
+	spells: &Vec<spells::Spell>,
+	font_paths: FontPaths,
+	font_sizes: FontSizes,
+	font_scalars: FontScalars,
+	spacing_options: SpacingOptions,
+	text_colors: TextColorOptions,
+	page_size_options: PageSizeOptions,
+	page_number_options: Option<PageNumberOptions>,
+	background: Option<(&str, ImageTransform)>,
+	table_options: TableOptions,
+	additional_options: &AdditionalOptions,
+	redaction: RedactionStyle
+)
+-> Result<(Spellbook, Spellbook), Box<dyn Error + Send + Sync>>
+{
+	// Share the background images between the editions so they only get opened once
+	let image_cache = additional_options.image_cache.clone().unwrap_or_default();
+	let mut edition_options =
+	AdditionalOptions { redaction: None, image_cache: Some(image_cache), ..additional_options.clone() };
+	let dm_edition = create_spellbook_with_options
+	(
+		title,
+		spells,
+		font_paths.clone(),
+		font_sizes,
+		font_scalars,
+		spacing_options,
+		text_colors,
+		page_size_options,
+		page_number_options,
+		background,
+		table_options,
+		&edition_options
+	)?;
+	edition_options.redaction = Some(redaction);
+	let player_edition = create_spellbook_with_options
+	(
+		title,
+		spells,
+		font_paths,
+		font_sizes,
+		font_scalars,
+		spacing_options,
+		text_colors,
+		page_size_options,
+		page_number_options,
+		background,
+		table_options,
+		&edition_options
+	)?;
+	Ok((dm_edition, player_edition))
+}
+
+/// Same as `create_spellbook_with_options()` but makes a large print version of the spellbook that's easier to read,
+/// without any background images. The text gets bigger (with any spacing that depends on its size scaled to match),
+/// the lines and margins get further apart, and all of the text can use a different font (like a dyslexia-friendly
+/// font).
+///
+/// # Parameters
+///
+/// - `title` The title of the spellbook.
+/// - `font_paths` File paths to all of the font variants (regular, bold, italic, bold-italic). Ignored if the preset
+///   has its own font.
+/// - `font_sizes` Font sizes for each type of text in the spellbook (except page numbers).
+/// - `font_scalars` Scalar values to make sure text width can be calculated correctly for each font variant.
+/// - `spacing_options` Tab size and newline sizes for each type of text (except page numbers).
+/// - `text_colors` The RGB color values for each type of text (except page numbers).
+/// - `page_size_options` Page width, height, and margin values.
+/// - `page_number_options` Settings for how page numbers look (`None` for no page numbers).
+/// - `table_options` Sizing and color options for tables in spell descriptions.
+/// - `additional_options` Settings for optional features. `AdditionalOptions::default()` turns them all off. Section
+///   backgrounds get left out.
+/// - `preset` How much bigger the text and margins get and what font the text uses.
+///
+/// # Output
+///
+/// - `Ok` Returns a `Spellbook` holding the PDF document.
+/// - `Err` Returns any errors that occured.
+#[allow(clippy::too_many_arguments)]
+pub fn create_accessible_spellbook
+(
+	title: &str,
+	spells: &Vec<spells::Spell>,
+	font_paths: FontPaths,
+	font_sizes: FontSizes,
+	font_scalars: FontScalars,
+	spacing_options: SpacingOptions,
+	text_colors: TextColorOptions,
+	page_size_options: PageSizeOptions,
+	page_number_options: Option<PageNumberOptions>,
+	table_options: TableOptions,
+	additional_options: &AdditionalOptions,
+	preset: &AccessibilityPreset
+)
+-> Result<Spellbook, Box<dyn Error + Send + Sync>>
+{
+	let (font_paths, font_scalars) = match &preset.font
+	{
+		Some((font_paths, font_scalars)) => (font_paths.clone(), *font_scalars),
+		None => (font_paths, font_scalars)
+	};
+	let additional_options = AdditionalOptions { section_backgrounds: None, ..additional_options.clone() };
+	create_spellbook_with_options
+	(
+		title,
+		spells,
+		font_paths,
+		preset.font_sizes(&font_sizes),
+		font_scalars,
+		preset.spacing_options(&spacing_options),
+		text_colors,
+		preset.page_size_options(&page_size_options),
+		page_number_options.map(|options| preset.page_number_options(&options)),
+		None,
+		preset.table_options(&table_options),
+		&additional_options
 	)
 }
 
+/// Same as `create_spellbook_with_options()` but makes a separate pdf file for each spell (like handouts for single
+/// spells) instead of putting them all in one spellbook. Each file has no title page and starts with its spell, and
+/// gets named after the spell's slug (see `Spell::slug()`, ex: "fire_bolt.pdf"). Spells with the same name get a number
+/// at the end of their file names.
+///
+/// # Parameters
+///
+/// - `spells` The spells to make files for.
+/// - `font_paths` File paths to all of the font variants (regular, bold, italic, bold-italic).
+/// - `font_sizes` Font sizes for each type of text in the spellbook (except page numbers).
+/// - `font_scalars` Scalar values to make sure text width can be calculated correctly for each font variant.
+/// - `spacing_options` Tab size and newline sizes for each type of text (except page numbers).
+/// - `text_colors` The RGB color values for each type of text (except page numbers).
+/// - `page_size_options` Page width, height, and margin values.
+/// - `page_number_options` Settings for how page numbers look (`None` for no page numbers).
+/// - `background` An image filepath to use as backgrounds for each page and transform data to make it fit on
+///   the page the way you want.
+/// - `table_options` Sizing and color options for tables in spell descriptions.
+/// - `additional_options` Settings for optional features. The table of contents, class chapters, and spine page get
+///   left out.
+/// - `out_dir` The folder to save the files in (gets created if it doesn't exist).
+///
+/// # Output
+///
+/// - `Ok` The file path of each spell's file (in the same order as the spells).
+/// - `Err` Returns any errors that occured.
+#[allow(clippy::too_many_arguments)]
+pub fn export_spell_pdfs
+(
+	spells: &[spells::Spell],
+	font_paths: FontPaths,
+	font_sizes: FontSizes,
+	font_scalars: FontScalars,
+	spacing_options: SpacingOptions,
+	text_colors: TextColorOptions,
+	page_size_options: PageSizeOptions,
+	page_number_options: Option<PageNumberOptions>,
+	background: Option<(&str, ImageTransform)>,
+	table_options: TableOptions,
+	additional_options: &AdditionalOptions,
+	out_dir: &str
+)
+-> Result<Vec<String>, Box<dyn Error + Send + Sync>>
+{
+	fs::create_dir_all(out_dir)?;
+	// Share the background images between the files so they only get opened once and leave out everything that's
+	// about a whole spellbook
+	let image_cache = additional_options.image_cache.clone().unwrap_or_default();
+	let spell_options = AdditionalOptions
+	{
+		skip_title_page: true,
+		table_of_contents: None,
+		class_chapters: None,
+		spine: None,
+		image_cache: Some(image_cache),
+		..additional_options.clone()
+	};
+	let mut file_paths: Vec<String> = Vec::with_capacity(spells.len());
+	for spell in spells
+	{
+		let spellbook = create_spellbook_with_options
+		(
+			&spell.name,
+			&vec![spell.clone()],
+			font_paths.clone(),
+			font_sizes,
+			font_scalars,
+			spacing_options,
+			text_colors,
+			page_size_options,
+			page_number_options,
+			background,
+			table_options,
+			&spell_options
+		)?;
+		// Name the file after the spell (with a number at the end if another spell already has that name)
+		let file_path = unused_file_path(out_dir, &safe_filename(&spell.slug()), "pdf", &file_paths);
+		spellbook.save(&file_path)?;
+		file_paths.push(file_path);
+	}
+	Ok(file_paths)
+}
+
+/// Makes a cover wrap for a spellbook: a single wide page with the back cover, spine, and front cover side by side (in
+/// that order from left to right), which is the kind of cover file that print-on-demand services ask for. Use the same
+/// options that the spellbook was made with so the cover matches it.
+///
+/// # Parameters
+///
+/// - `title` The title of the spellbook.
+/// - `font_paths` File paths to all of the font variants (regular, bold, italic, bold-italic).
+/// - `font_sizes` Font sizes for each type of text in the spellbook (except page numbers).
+/// - `font_scalars` Scalar values to make sure text width can be calculated correctly for each font variant.
+/// - `spacing_options` Tab size and newline sizes for each type of text (except page numbers).
+/// - `text_colors` The RGB color values for each type of text (except page numbers).
+/// - `page_size_options` Page width, height, and margin values of the spellbook. The covers are as tall as the pages
+///   (plus the bleed) and keep the same margins.
+/// - `table_options` Sizing and color options for tables in the back cover text.
+/// - `additional_options` Settings for optional features. Only kerning, ligatures, shaping, justification, and the
+///   image cache get used.
+/// - `cover_options` The widths, artwork, and back cover text of the cover wrap.
+///
+/// # Output
+///
+/// - `Ok` Returns the PDF document with the cover wrap on its only page.
+/// - `Err` Returns any errors that occured.
+#[allow(clippy::too_many_arguments)]
+pub fn create_cover_wrap
+(
+	title: &str,
+	font_paths: FontPaths,
+	font_sizes: FontSizes,
+	font_scalars: FontScalars,
+	spacing_options: SpacingOptions,
+	text_colors: TextColorOptions,
+	page_size_options: PageSizeOptions,
+	table_options: TableOptions,
+	additional_options: &AdditionalOptions,
+	cover_options: &CoverWrapOptions
+)
+-> Result<PdfDocumentReference, Box<dyn Error + Send + Sync>>
+{
+	SpellbookWriter::create_cover_wrap
+	(
+		title,
+		font_paths,
+		font_sizes,
+		font_scalars,
+		spacing_options,
+		text_colors,
+		page_size_options,
+		table_options,
+		additional_options,
+		cover_options
+	)
+}
+
+/// Turns some text (like a spell's slug or title) into a file name that's valid on Windows, macOS, and Linux.
+/// Characters that file names can't have (like "/" and ":") and control characters get replaced with underscores,
+/// spaces and periods at the end get removed, names that Windows saves for devices (like "CON" and "NUL") get an
+/// underscore at the end, and names that are too long get cut off. Add the file extension after.
+///
+/// Ex: `safe_filename("Antipathy / Sympathy")` gives "Antipathy _ Sympathy" and `safe_filename("")` gives "_".
+pub fn safe_filename(name: &str) -> String
+{
+	let mut file_name: String = name.chars()
+		.map(|c| if c.is_control() || "<>:\"/\\|?*".contains(c) { '_' } else { c })
+		.collect();
+	// Leave room for a file extension in the 255 bytes that most file systems allow
+	while file_name.len() > MAX_FILE_NAME_BYTES { file_name.pop(); }
+	let mut file_name = String::from(file_name.trim_end_matches([' ', '.']));
+	if file_name.is_empty() { file_name.push('_'); }
+	let stem = file_name.split('.').next().unwrap_or_default().to_uppercase();
+	let is_device_name = ["CON", "PRN", "AUX", "NUL"].contains(&stem.as_str()) ||
+		((stem.starts_with("COM") || stem.starts_with("LPT")) && stem.len() == 4 &&
+		stem.ends_with(|c: char| c.is_ascii_digit() && c != '0'));
+	if is_device_name { file_name.push('_'); }
+	file_name
+}
+
 /// Saves spellbooks to a file as a pdf document.
 ///
 /// # Parameters
@@ -75,13 +527,131 @@ pub fn create_spellbook
 ///
 /// - `Ok` Returns nothing.
 /// - `Err` Returns any errors that occurred.
-pub fn save_spellbook(doc: PdfDocumentReference, file_name: &str) -> Result<(), Box<dyn std::error::Error>>
+pub fn save_spellbook(doc: PdfDocumentReference, file_name: &str)
+-> Result<(), Box<dyn std::error::Error + Send + Sync>>
 {
 	let file = fs::File::create(file_name)?;
 	doc.save(&mut std::io::BufWriter::new(file))?;
 	Ok(())
 }
 
+/// Error for when an svg file couldn't be turned into an image.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SvgLoadError(String);
+// Makes the struct displayable
+impl std::fmt::Display for SvgLoadError
+{
+	fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result
+	{
+		write!(f, "{}", self.0)
+	}
+}
+// Makes the struct officially an error
+impl Error for SvgLoadError {}
+
+/// Opens an image file. Svg files (files that end in `.svg`) are rasterized so that they're their actual size when
+/// placed on a page at the given dpi, which requires the `svg` feature.
+///
+/// # Parameters
+///
+/// - `image_path` The file path to the image.
+/// - `dpi` How many pixels of the image go in an inch of the page (only used for svg files).
+///
+/// # Output
+///
+/// - `Ok` The image.
+/// - `Err` Returns any errors that occurred.
+pub fn load_image(image_path: &str, dpi: f32) -> Result<DynamicImage, Box<dyn Error + Send + Sync>>
+{
+	if image_path.to_lowercase().ends_with(".svg") { load_svg(image_path, dpi) }
+	else { Ok(image::open(image_path)?) }
+}
+
+/// Rasterizes an svg file. Svg sizes are in css pixels, which are 96 per inch.
+#[cfg(feature = "svg")]
+fn load_svg(image_path: &str, dpi: f32) -> Result<DynamicImage, Box<dyn Error + Send + Sync>>
+{
+	use resvg::{tiny_skia, usvg};
+
+	let tree = usvg::Tree::from_data(&fs::read(image_path)?, &usvg::Options::default())?;
+	let scale = dpi / 96.0;
+	let size = tree.size().to_int_size().scale_by(scale)
+		.ok_or_else(|| SvgLoadError(format!("{} can't be rasterized at {} dpi.", image_path, dpi)))?;
+	let mut pixmap = tiny_skia::Pixmap::new(size.width(), size.height())
+		.ok_or_else(|| SvgLoadError(format!("{} is too large to rasterize at {} dpi.", image_path, dpi)))?;
+	resvg::render(&tree, tiny_skia::Transform::from_scale(scale, scale), &mut pixmap.as_mut());
+	// tiny-skia stores colors premultiplied by their alpha, but images don't
+	let pixels = pixmap.pixels().iter()
+		.map(|pixel| pixel.demultiply())
+		.flat_map(|color| [color.red(), color.green(), color.blue(), color.alpha()])
+		.collect();
+	let image = image::RgbaImage::from_raw(size.width(), size.height(), pixels)
+		.ok_or_else(|| SvgLoadError(format!("Failed to rasterize {}.", image_path)))?;
+	Ok(DynamicImage::ImageRgba8(image))
+}
+
+/// Svg files can't be opened without the `svg` feature.
+#[cfg(not(feature = "svg"))]
+fn load_svg(image_path: &str, _dpi: f32) -> Result<DynamicImage, Box<dyn Error + Send + Sync>>
+{
+	Err(Box::new(SvgLoadError(format!("The `svg` feature is needed to use {}.", image_path))))
+}
+
+/// Error for when a variable font couldn't be instanced at the axis values it was given.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct VariableFontError(pub(crate) String);
+// Makes the struct displayable
+impl std::fmt::Display for VariableFontError
+{
+	fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result
+	{
+		write!(f, "{}", self.0)
+	}
+}
+// Makes the struct officially an error
+impl Error for VariableFontError {}
+
+/// Reads a font file. If axis values are given, the font must be a variable font and it gets instanced at those axis
+/// values into a static TrueType font, which requires the `variable-fonts` feature.
+///
+/// # Parameters
+///
+/// - `font_path` The file path to the font.
+/// - `axes` The axis values to instance the font at (`None` for static fonts).
+///
+/// # Output
+///
+/// - `Ok` The bytes of the font.
+/// - `Err` Returns any errors that occurred.
+pub fn load_font_bytes(font_path: &str, axes: Option<&Vec<(String, f32)>>)
+-> Result<Vec<u8>, Box<dyn Error + Send + Sync>>
+{
+	let bytes = fs::read(font_path)?;
+	match axes
+	{
+		Some(axes) => instance_font(&bytes, axes, font_path),
+		None => Ok(bytes)
+	}
+}
+
+/// Instances a variable font at some axis values.
+#[cfg(feature = "variable-fonts")]
+fn instance_font(bytes: &[u8], axes: &Vec<(String, f32)>, font_path: &str)
+-> Result<Vec<u8>, Box<dyn Error + Send + Sync>>
+{
+	crate::font_instancing::instance_variable_font(bytes, axes)
+		.map_err(|error| VariableFontError(format!("{} ({})", error.0, font_path)).into())
+}
+
+/// Variable fonts can't be instanced without the `variable-fonts` feature.
+#[cfg(not(feature = "variable-fonts"))]
+fn instance_font(_bytes: &[u8], _axes: &Vec<(String, f32)>, font_path: &str)
+-> Result<Vec<u8>, Box<dyn Error + Send + Sync>>
+{
+	Err(Box::new(VariableFontError(format!("The `variable-fonts` feature is needed to use axis values with {}.",
+		font_path))))
+}
+
 /// Error for when a file name could not be retrieved when processing spell files in `get_all_spells_in_folder()`.
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub struct SpellFileNameReadError;
@@ -108,7 +678,33 @@ impl Error for SpellFileNameReadError {}
 ///
 /// - `Ok` Returns a vec of spell objects that can be inputted into `generate_spellbook()`.
 /// - `Err` Returns any errors that occurred.
-pub fn get_all_spells_in_folder(folder_path: &str) -> Result<Vec<spells::Spell>, Box<dyn std::error::Error>>
+pub fn get_all_spells_in_folder(folder_path: &str)
+-> Result<Vec<spells::Spell>, Box<dyn std::error::Error + Send + Sync>>
+{
+	read_spells_in_folder(folder_path, None)
+}
+
+/// Returns a vec of spells from every json spell file in a folder, using the text for a certain locale (language)
+/// wherever the spell files have it (see `Spell::from_json_file_with_locale()`).
+///
+/// # Parameters
+///
+/// - `folder_path` The file path to the folder to extract every spell from.
+/// - `locale` The locale to use the text for (ex: "de" or "pt-BR").
+///
+/// # Output
+///
+/// - `Ok` Returns a vec of spell objects in the locale.
+/// - `Err` Returns any errors that occurred.
+pub fn get_all_spells_in_folder_with_locale(folder_path: &str, locale: &str)
+-> Result<Vec<spells::Spell>, Box<dyn std::error::Error + Send + Sync>>
+{
+	read_spells_in_folder(folder_path, Some(locale))
+}
+
+// Reads every json spell file in a folder in a locale (or with the default text if there isn't one)
+fn read_spells_in_folder(folder_path: &str, locale: Option<&str>)
+-> Result<Vec<spells::Spell>, Box<dyn Error + Send + Sync>>
 {
 	// Gets a list of every file in the folder
 	let file_paths = fs::read_dir(folder_path)?;
@@ -131,10 +727,59 @@ pub fn get_all_spells_in_folder(folder_path: &str) -> Result<Vec<spells::Spell>,
 		// If the file is a json file
 		if file_name.ends_with(".json")
 		{
-			// Read the file, turn it into a spell, and push it to the spell_list vec
-			spell_list.push(spells::Spell::from_json_file(file_name)?);
+			// Read the file, turn it into a spell (in the locale if there is one), and push it to the spell_list vec
+			let spell = match locale
+			{
+				Some(locale) => spells::Spell::from_json_file_with_locale(file_name, locale)?,
+				None => spells::Spell::from_json_file(file_name)?
+			};
+			spell_list.push(spell);
 		}
 	}
 	// Return the list of spells
 	Ok(spell_list)
 }
+
+/// Saves spells to json spell files in a folder so they can be read back with `get_all_spells_in_folder()`. Each file
+/// gets named after its spell's slug (see `Spell::slug()`, ex: "fire_bolt.json") the same way the spell files in this
+/// repository are. Spells whose names have the same slug (ex: "Fire Bolt" and "Fire-Bolt!") get a number at the end
+/// of their file names instead of overwriting each other.
+///
+/// # Parameters
+///
+/// - `spells` The spells to save.
+/// - `folder_path` The folder to save the files in (gets created if it doesn't exist).
+/// - `compress` True to put all the data of each spell onto one line, false to make the files more human readable.
+///
+/// # Output
+///
+/// - `Ok` The file path of each spell's file (in the same order as the spells).
+/// - `Err` Returns any errors that occurred.
+pub fn save_spells_to_folder(spells: &[spells::Spell], folder_path: &str, compress: bool)
+-> Result<Vec<String>, Box<dyn Error + Send + Sync>>
+{
+	fs::create_dir_all(folder_path)?;
+	let mut file_paths = Vec::with_capacity(spells.len());
+	for spell in spells
+	{
+		let file_path = unused_file_path(folder_path, &safe_filename(&spell.slug()), "json", &file_paths);
+		spell.to_json_file(&file_path, compress)?;
+		file_paths.push(file_path);
+	}
+	Ok(file_paths)
+}
+
+// Gives the path to a file with a name and extension in a folder that isn't one of the file paths that are already
+// taken (with a number at the end of the name if it is)
+fn unused_file_path(folder_path: &str, file_name: &str, extension: &str, taken_paths: &[String]) -> String
+{
+	let folder_path = folder_path.trim_end_matches('/');
+	let mut file_path = format!("{}/{}.{}", folder_path, file_name, extension);
+	let mut number = 2;
+	while taken_paths.contains(&file_path)
+	{
+		file_path = format!("{}/{}_{}.{}", folder_path, file_name, number, extension);
+		number += 1;
+	}
+	file_path
+}
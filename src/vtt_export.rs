@@ -0,0 +1,356 @@
+//////////////////////////////////////////////////////////////////////////////////////////////////////////////
+//
+//	Exporting spells to virtual tabletops (Foundry VTT and Roll20)
+//
+//////////////////////////////////////////////////////////////////////////////////////////////////////////////
+
+use std::fs;
+use std::error::Error;
+
+use serde_json::{json, Value};
+
+use crate::spellbook_options::{FontVariant, UpcastOptions};
+use crate::rich_text::{blocks, description_with_upcast, styled_runs, Block};
+use crate::spells::{self, Aoe, CastingTime, DiceExpr, Distance, Duration, MagicSchool, Range, Spell, SpellField,
+	TimeUnit};
+
+/// Virtual tabletops that spells can be exported to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum VttFormat
+{
+	/// Spell items for the dnd5e system in Foundry VTT. The whole export is a json array of item documents.
+	FoundryVtt,
+	/// Handouts for Roll20. The whole export is a json array of handouts with their text in `notes`.
+	Roll20
+}
+
+/// Turns a spell into the json that a virtual tabletop uses for it.
+///
+/// # Parameters
+///
+/// - `spell` The spell to export.
+/// - `format` The virtual tabletop to export the spell for.
+/// - `upcast` The text that goes before the upcast description of the spell.
+///
+/// # Output
+///
+/// The spell in the virtual tabletop's format.
+pub fn export_spell(spell: &Spell, format: VttFormat, upcast: &UpcastOptions) -> Value
+{
+	match format
+	{
+		VttFormat::FoundryVtt => foundry_spell(spell, upcast),
+		VttFormat::Roll20 => roll20_handout(spell, upcast)
+	}
+}
+
+/// Turns a list of spells into a json array in a virtual tabletop's format (see `export_spell()`).
+pub fn export_spells(spells: &Vec<Spell>, format: VttFormat, upcast: &UpcastOptions) -> Value
+{
+	Value::Array(spells.iter().map(|spell| export_spell(spell, format, upcast)).collect())
+}
+
+/// Saves a list of spells to a json file in a virtual tabletop's format (see `export_spell()`).
+///
+/// # Parameters
+///
+/// - `spells` The spells to export.
+/// - `format` The virtual tabletop to export the spells for.
+/// - `upcast` The text that goes before the upcast description of each spell.
+/// - `file_path` The file to save the spells to.
+///
+/// # Output
+///
+/// - `Ok` Returns nothing.
+/// - `Err` Returns any errors that occurred.
+pub fn save_vtt_export(spells: &Vec<Spell>, format: VttFormat, upcast: &UpcastOptions, file_path: &str)
+-> Result<(), Box<dyn Error + Send + Sync>>
+{
+	fs::write(file_path, serde_json::to_string_pretty(&export_spells(spells, format, upcast))?)?;
+	Ok(())
+}
+
+/// Turns the description of a spell (with its upcast description at the end) into html.
+///
+/// Paragraphs become `<p>` elements, bullet points become `<ul>` lists, table tags become `<table>` elements, font
+/// tags become `<strong>` and `<em>` elements, url tags become links, and color tags become colored `<span>`
+/// elements. Escaped tags are treated as text. Custom tags from tag handlers are left as text since there's no
+/// spellbook page for them to be handled on.
+pub fn spell_description_html(spell: &Spell, upcast: &UpcastOptions) -> String
+{
+	rich_text_to_html(&description_with_upcast(spell, upcast), &spell.tables)
+}
+
+/// Turns text that can have paragraphs, bullet points, tables, and tags in it (like spell descriptions) into html.
+///
+/// # Parameters
+///
+/// - `text` The text to turn into html.
+/// - `tables` The tables that table tags in the text refer to.
+///
+/// # Output
+///
+/// The html.
+pub fn rich_text_to_html(text: &str, tables: &Vec<spells::Table>) -> String
+{
+	let mut html = String::with_capacity(text.len() * 2);
+	for block in blocks(text, tables)
+	{
+		match block
+		{
+			Block::Paragraph(paragraph) => html.push_str(&format!("<p>{}</p>", inline_html(paragraph))),
+			Block::Bullets(bullets) =>
+			{
+				html.push_str("<ul>");
+				for bullet in bullets { html.push_str(&format!("<li>{}</li>", inline_html(bullet))); }
+				html.push_str("</ul>");
+			},
+			Block::Table(table) => html.push_str(&table_html(table))
+		}
+	}
+	html
+}
+
+/// Turns a table into html.
+fn table_html(table: &spells::Table) -> String
+{
+	let mut html = String::new();
+	if !table.title.is_empty() { html.push_str(&format!("<p><strong>{}</strong></p>", inline_html(&table.title))); }
+	html.push_str("<table>");
+	if !table.column_labels.is_empty()
+	{
+		html.push_str("<thead><tr>");
+		for label in &table.column_labels { html.push_str(&format!("<th>{}</th>", inline_html(label))); }
+		html.push_str("</tr></thead>");
+	}
+	html.push_str("<tbody>");
+	for row in &table.cells
+	{
+		html.push_str("<tr>");
+		for cell in row { html.push_str(&format!("<td>{}</td>", inline_html(cell))); }
+		html.push_str("</tr>");
+	}
+	html.push_str("</tbody></table>");
+	html
+}
+
+/// Turns a paragraph of text with font tags, url tags, and color tags into html.
+/// Each run of text with the same style gets its own tags so the html is always well formed.
+fn inline_html(text: &str) -> String
+{
+	let runs: Vec<_> = styled_runs(text).iter().map(|(style, text)|
+	{
+		let mut html = String::new();
+		if let Some(url) = &style.link { html.push_str(&format!("<a href=\"{}\">", escape_html(url))); }
+		if let Some((r, g, b)) = style.color
+		{
+			html.push_str(&format!("<span style=\"color:#{:02x}{:02x}{:02x}\">", r, g, b));
+		}
+		let (open, close) = match style.font_variant
+		{
+			FontVariant::Regular => ("", ""),
+			FontVariant::Bold => ("<strong>", "</strong>"),
+			FontVariant::Italic => ("<em>", "</em>"),
+			FontVariant::BoldItalic => ("<strong><em>", "</em></strong>")
+		};
+		html.push_str(&format!("{}{}{}", open, escape_html(text), close));
+		if style.color.is_some() { html.push_str("</span>"); }
+		if style.link.is_some() { html.push_str("</a>"); }
+		html
+	}).collect();
+	runs.join(" ")
+}
+
+/// Replaces characters that have special meanings in html with html entities.
+pub(crate) fn escape_html(text: &str) -> String
+{
+	let mut escaped = String::with_capacity(text.len());
+	for c in text.chars()
+	{
+		match c
+		{
+			'&' => escaped.push_str("&amp;"),
+			'<' => escaped.push_str("&lt;"),
+			'>' => escaped.push_str("&gt;"),
+			'"' => escaped.push_str("&quot;"),
+			_ => escaped.push(c)
+		}
+	}
+	escaped
+}
+
+/// Turns a rolled duration into a Foundry dice formula and units along with whether it needs concentration.
+/// Foundry doesn't have seconds or weeks, so they get turned into rounds and days in the formula.
+fn foundry_rolled_duration(dice: &DiceExpr, unit: &TimeUnit, concentration: bool)
+	-> (Option<String>, &'static str, bool)
+{
+	let (formula, units) = match unit
+	{
+		TimeUnit::Seconds => (format!("ceil({} / 6)", dice), "round"),
+		TimeUnit::Rounds => (dice.to_string(), "round"),
+		TimeUnit::Minutes => (dice.to_string(), "minute"),
+		TimeUnit::Hours => (dice.to_string(), "hour"),
+		TimeUnit::Days => (dice.to_string(), "day"),
+		TimeUnit::Weeks => (format!("{} * 7", dice), "day"),
+		TimeUnit::Months => (dice.to_string(), "month"),
+		TimeUnit::Years => (dice.to_string(), "year")
+	};
+	(Some(formula), units, concentration)
+}
+
+/// Turns a spell into a spell item for the dnd5e system in Foundry VTT.
+/// Custom values in spell fields become the "special" version of each field.
+fn foundry_spell(spell: &Spell, upcast: &UpcastOptions) -> Value
+{
+	let level = match &spell.level
+	{
+		SpellField::Controlled(level) => u8::from(level),
+		SpellField::Custom(_) => 0
+	};
+	let school = match &spell.school
+	{
+		SpellField::Controlled(school) => match school
+		{
+			MagicSchool::Abjuration => "abj",
+			MagicSchool::Conjuration => "con",
+			MagicSchool::Divination => "div",
+			MagicSchool::Enchantment => "enc",
+			MagicSchool::Evocation => "evo",
+			MagicSchool::Illusion => "ill",
+			MagicSchool::Necromancy => "nec",
+			MagicSchool::Transmutation => "trs",
+			// Foundry only knows about the standard schools
+			MagicSchool::Homebrew(_) => ""
+		},
+		SpellField::Custom(_) => ""
+	};
+	let (activation_type, activation_cost, activation_condition) = match &spell.casting_time
+	{
+		// Foundry only has one casting time per spell, so spells with more than one use the first one
+		SpellField::Controlled(casting_time) => match casting_time.alternatives()[0]
+		{
+			// Foundry doesn't have seconds, so they're rounded up to minutes
+			CastingTime::Seconds(t) => ("minute", Some(t.div_ceil(60)), String::new()),
+			CastingTime::Actions(t) => ("action", Some(*t), String::new()),
+			CastingTime::BonusAction(circumstance) => ("bonus", Some(1), circumstance.clone().unwrap_or_default()),
+			CastingTime::Reaction(circumstance) => ("reaction", Some(1), circumstance.clone().unwrap_or_default()),
+			CastingTime::Minutes(t) => ("minute", Some(*t), String::new()),
+			CastingTime::Hours(t) => ("hour", Some(*t), String::new()),
+			CastingTime::Days(t) => ("day", Some(*t), String::new()),
+			// Foundry doesn't have weeks, so they're turned into days
+			CastingTime::Weeks(t) => ("day", Some(t.saturating_mul(7)), String::new()),
+			CastingTime::Months(t) => ("month", Some(*t), String::new()),
+			CastingTime::Years(t) => ("year", Some(*t), String::new()),
+			// (`alternatives()` never gives an `Either`)
+			CastingTime::Special | CastingTime::Either(..) => ("special", None, String::new())
+		},
+		SpellField::Custom(text) => ("special", None, text.clone())
+	};
+	let (duration_value, duration_units, concentration) = match &spell.duration
+	{
+		SpellField::Controlled(duration) => match duration
+		{
+			Duration::Instant => (None, "inst", false),
+			// Foundry doesn't have seconds, so they're turned into rounds (6 seconds each)
+			Duration::Seconds(t, c) => (Some(t.div_ceil(6).to_string()), "round", *c),
+			Duration::Rounds(t, c) => (Some(t.to_string()), "round", *c),
+			Duration::Minutes(t, c) => (Some(t.to_string()), "minute", *c),
+			Duration::Hours(t, c) => (Some(t.to_string()), "hour", *c),
+			Duration::Days(t, c) => (Some(t.to_string()), "day", *c),
+			// Foundry doesn't have weeks, so they're turned into days
+			Duration::Weeks(t, c) => (Some(t.saturating_mul(7).to_string()), "day", *c),
+			Duration::Months(t, c) => (Some(t.to_string()), "month", *c),
+			Duration::Years(t, c) => (Some(t.to_string()), "year", *c),
+			Duration::Rolled(dice, unit, c) => foundry_rolled_duration(dice, unit, *c),
+			Duration::UntilDispelledOrTriggered(c) => (None, "dstr", *c),
+			Duration::UntilDispelled(c) => (None, "disp", *c),
+			Duration::Permanent => (None, "perm", false),
+			Duration::Special(c) => (None, "spec", *c)
+		},
+		SpellField::Custom(_) => (None, "spec", false)
+	};
+	// Splits a distance into its value and Foundry's units for it
+	let distance = |distance: &Distance| match distance
+	{
+		Distance::Feet(d) => (*d, "ft"),
+		Distance::Miles(d) => (*d, "mi"),
+		Distance::Meters(d) => (*d, "m"),
+		Distance::Kilometers(d) => (*d, "km"),
+		// Foundry doesn't have grid squares, so they're turned into feet
+		Distance::Squares(d) => (d.saturating_mul(5), "ft")
+	};
+	let (range_value, range_units, area) = match &spell.range
+	{
+		SpellField::Controlled(range) => match range
+		{
+			Range::Yourself(aoe) => (None, "self", aoe.clone()),
+			Range::Touch => (None, "touch", None),
+			Range::Dist(d) => (Some(distance(d).0), distance(d).1, None),
+			Range::Sight | Range::Special => (None, "spec", None),
+			Range::Unlimited => (None, "any", None)
+		},
+		SpellField::Custom(_) => (None, "spec", None)
+	};
+	let target = match area
+	{
+		Some(aoe) =>
+		{
+			let (shape, size) = match &aoe
+			{
+				Aoe::Line(d) => ("line", d),
+				Aoe::Cone(d) => ("cone", d),
+				Aoe::Cube(d) => ("cube", d),
+				Aoe::Sphere(d) | Aoe::Emanation(d) | Aoe::Hemisphere(d) => ("sphere", d),
+				Aoe::Cylinder(d, _) => ("cylinder", d)
+			};
+			json!({ "value": distance(size).0, "units": distance(size).1, "type": shape })
+		},
+		None => json!({ "value": null, "units": "", "type": "" })
+	};
+	json!
+	({
+		"name": spell.name,
+		"type": "spell",
+		"system":
+		{
+			"description": { "value": spell_description_html(spell, upcast), "chat": "" },
+			"source": { "book": spell.source.clone().unwrap_or_default() },
+			"activation": { "type": activation_type, "cost": activation_cost, "condition": activation_condition },
+			"duration": { "value": duration_value, "units": duration_units },
+			"target": target,
+			"range": { "value": range_value, "units": range_units },
+			"level": level,
+			"school": school,
+			"components":
+			{
+				"vocal": spell.has_v_component,
+				"somatic": spell.has_s_component,
+				"material": spell.m_components.is_some(),
+				"ritual": spell.is_ritual,
+				"concentration": concentration
+			},
+			"materials": { "value": spell.m_components.clone().unwrap_or_default() }
+		}
+	})
+}
+
+/// Turns a spell into a Roll20 handout with the spell's stats at the top of its notes.
+fn roll20_handout(spell: &Spell, upcast: &UpcastOptions) -> Value
+{
+	let stats = format!
+	(
+		"<p><em>{}</em></p><p><strong>Casting Time:</strong> {}<br><strong>Range:</strong> {}<br>\
+		<strong>Components:</strong> {}<br><strong>Duration:</strong> {}</p>",
+		inline_html(&spell.get_level_school_text()),
+		inline_html(&spell.get_casting_time_text()),
+		inline_html(&spell.range.to_string()),
+		inline_html(&spell.get_component_string()),
+		inline_html(&spell.duration.to_string())
+	);
+	json!
+	({
+		"name": spell.name,
+		"notes": format!("{}{}", stats, spell_description_html(spell, upcast)),
+		"gmnotes": ""
+	})
+}